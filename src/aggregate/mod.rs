@@ -0,0 +1,298 @@
+//! Merging saved result files into one report
+//!
+//! `aggregate` reads `test --format json` results, from files or every
+//! `*.json` file in a directory, such as the outputs of parallel CI shards or
+//! of runs against several instances, and sums them into one
+//! [`AggregateReport`]. Every shard and every tool result keeps the file it
+//! came from.
+
+use crate::{
+    AllToolsTestResult, CategoryRollup, ErrorCategory, FailOn, GleanMcpError, Result, RunOutcome,
+    SuccessCriteria, ToolTestResult, count_error_categories, load_result_file, schema_version,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+/// One result file's part of an aggregate
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShardSummary {
+    /// File the result was read from
+    pub shard: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub run_id: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub labels: BTreeMap<String, String>,
+    pub outcome: RunOutcome,
+    pub successful_tools: usize,
+    pub failed_tools: usize,
+    pub skipped_tools: usize,
+    pub degraded_tools: usize,
+    pub total_duration_ms: u64,
+    pub start_time: String,
+}
+
+/// A tool result and the shard it came from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShardToolResult {
+    pub shard: String,
+    #[serde(flatten)]
+    pub result: ToolTestResult,
+}
+
+/// Results of many runs, summed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AggregateReport {
+    #[serde(default = "schema_version")]
+    pub schema_version: String,
+    /// Every shard's gated tools passed
+    pub success: bool,
+    pub outcome: RunOutcome,
+    pub successful_tools: usize,
+    pub failed_tools: usize,
+    pub skipped_tools: usize,
+    pub degraded_tools: usize,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub categories: BTreeMap<String, CategoryRollup>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub error_categories: BTreeMap<ErrorCategory, usize>,
+    pub shards: Vec<ShardSummary>,
+    /// Every shard's tool results, ordered by shard, then tool
+    pub tool_results: Vec<ShardToolResult>,
+    /// Files in the given directories that aren't test results, with the reason
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub ignored: BTreeMap<String, String>,
+}
+
+impl AggregateReport {
+    /// Sum `results`, each named by the file it came from
+    #[must_use]
+    pub fn from_results(results: &[(String, AllToolsTestResult)]) -> Self {
+        let mut categories: BTreeMap<String, CategoryRollup> = BTreeMap::new();
+        let mut shards = Vec::new();
+        let mut tool_results = Vec::new();
+        for (shard, result) in results {
+            for (category, rollup) in &result.categories {
+                let total = categories.entry(category.clone()).or_default();
+                total.total += rollup.total;
+                total.successful += rollup.successful;
+                total.failed += rollup.failed;
+                total.degraded += rollup.degraded;
+            }
+            shards.push(ShardSummary {
+                shard: shard.clone(),
+                run_id: result.run_id.clone(),
+                labels: result.labels.clone(),
+                outcome: RunOutcome::for_result(
+                    result,
+                    &FailOn::All,
+                    &SuccessCriteria::default(),
+                    None,
+                ),
+                successful_tools: result.successful_tools,
+                failed_tools: result.failed_tools,
+                skipped_tools: result.skipped_tools,
+                degraded_tools: result.degraded_tools,
+                total_duration_ms: result.execution_summary.total_duration_ms,
+                start_time: result.execution_summary.start_time.clone(),
+            });
+            tool_results.extend(result.tool_results.values().map(|tool| ShardToolResult {
+                shard: shard.clone(),
+                result: tool.clone(),
+            }));
+        }
+
+        let outcome = combined_outcome(shards.iter().map(|shard| shard.outcome));
+        Self {
+            schema_version: schema_version(),
+            success: outcome == RunOutcome::Success || outcome == RunOutcome::Degraded,
+            outcome,
+            successful_tools: shards.iter().map(|shard| shard.successful_tools).sum(),
+            failed_tools: shards.iter().map(|shard| shard.failed_tools).sum(),
+            skipped_tools: shards.iter().map(|shard| shard.skipped_tools).sum(),
+            degraded_tools: shards.iter().map(|shard| shard.degraded_tools).sum(),
+            categories,
+            error_categories: count_error_categories(tool_results.iter().map(|tool| &tool.result)),
+            shards,
+            tool_results,
+            ignored: BTreeMap::new(),
+        }
+    }
+
+    /// Tools that ran, passing or failing
+    #[must_use]
+    pub const fn tested_tools(&self) -> usize {
+        self.successful_tools + self.failed_tools
+    }
+
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn format_text(&self) -> String {
+        let mut output = String::new();
+        let _ = writeln!(
+            output,
+            "📦 Aggregated Results: {} shard(s)",
+            self.shards.len()
+        );
+        output.push_str("=".repeat(50).as_str());
+        output.push('\n');
+        let _ = writeln!(
+            output,
+            "📊 Overall Status: {} ({})",
+            if self.success {
+                "✅ SUCCESS"
+            } else {
+                "❌ FAILED"
+            },
+            self.outcome.as_str()
+        );
+        let _ = writeln!(
+            output,
+            "🔧 Tools Tested: {}/{} successful",
+            self.successful_tools,
+            self.tested_tools()
+        );
+        if self.degraded_tools > 0 {
+            let _ = writeln!(
+                output,
+                "🐢 Degraded: {} tool(s) over latency budget",
+                self.degraded_tools
+            );
+        }
+        if self.skipped_tools > 0 {
+            let _ = writeln!(output, "⏭️  Skipped: {}", self.skipped_tools);
+        }
+        if !self.error_categories.is_empty() {
+            let counts: Vec<String> = self
+                .error_categories
+                .iter()
+                .map(|(category, count)| format!("{count} {}", category.as_str()))
+                .collect();
+            let _ = writeln!(output, "💥 Failures: {}", counts.join(", "));
+        }
+        if !self.categories.is_empty() {
+            let categories: Vec<String> = self
+                .categories
+                .iter()
+                .map(|(category, rollup)| {
+                    format!(
+                        "{category} {}/{} ({:.0}%)",
+                        rollup.successful,
+                        rollup.total,
+                        rollup.success_rate()
+                    )
+                })
+                .collect();
+            let _ = writeln!(output, "📂 Categories: {}", categories.join(", "));
+        }
+
+        output.push_str("\n🗂️  Shards:\n");
+        for shard in &self.shards {
+            let status = match shard.outcome {
+                RunOutcome::Success => "✅",
+                RunOutcome::Degraded => "🐢",
+                _ => "❌",
+            };
+            let run_id = shard
+                .run_id
+                .as_deref()
+                .map_or_else(String::new, |run_id| format!(" [{run_id}]"));
+            let _ = writeln!(
+                output,
+                "  {status} {}{run_id}: {}/{} passed, {:.2}s",
+                shard.shard,
+                shard.successful_tools,
+                shard.successful_tools + shard.failed_tools,
+                shard.total_duration_ms as f64 / 1000.0
+            );
+        }
+
+        let failures: Vec<_> = self
+            .tool_results
+            .iter()
+            .filter(|tool| tool.result.is_failure())
+            .collect();
+        if !failures.is_empty() {
+            output.push_str("\n🚨 Failed Tools:\n");
+            for tool in failures {
+                let _ = writeln!(
+                    output,
+                    "  ❌ {} ({}): {}",
+                    tool.result.tool_name,
+                    tool.shard,
+                    tool.result
+                        .error_message
+                        .as_deref()
+                        .unwrap_or("unknown error")
+                );
+            }
+        }
+
+        if !self.ignored.is_empty() {
+            output.push_str("\n⚠️  Ignored files:\n");
+            for (path, reason) in &self.ignored {
+                let _ = writeln!(output, "  {path}: {reason}");
+            }
+        }
+        output
+    }
+}
+
+/// The shards' outcome if they agree, `Degraded` if each passed or was degraded, otherwise `PartialFailure`
+fn combined_outcome(outcomes: impl IntoIterator<Item = RunOutcome>) -> RunOutcome {
+    let mut outcomes = outcomes.into_iter();
+    let Some(first) = outcomes.next() else {
+        return RunOutcome::Error;
+    };
+    outcomes.fold(first, |combined, outcome| match (combined, outcome) {
+        (a, b) if a == b => a,
+        (
+            RunOutcome::Success | RunOutcome::Degraded,
+            RunOutcome::Success | RunOutcome::Degraded,
+        ) => RunOutcome::Degraded,
+        _ => RunOutcome::PartialFailure,
+    })
+}
+
+/// Merge the results in `paths`: files are read as given, directories for every `*.json` file in them
+///
+/// A named file that isn't a test result is an error; one found in a
+/// directory is listed in [`AggregateReport::ignored`].
+pub fn aggregate_results(paths: &[PathBuf]) -> Result<AggregateReport> {
+    let mut results = Vec::new();
+    let mut ignored = BTreeMap::new();
+    for path in paths {
+        if path.is_dir() {
+            for file in json_files(path)? {
+                match load_result_file(&file) {
+                    Ok(result) => results.push((file.display().to_string(), result)),
+                    Err(e) => {
+                        ignored.insert(file.display().to_string(), e.to_string());
+                    }
+                }
+            }
+        } else {
+            results.push((path.display().to_string(), load_result_file(path)?));
+        }
+    }
+    if results.is_empty() {
+        return Err(GleanMcpError::Config(
+            "No test results found to aggregate".to_string(),
+        ));
+    }
+
+    let mut report = AggregateReport::from_results(&results);
+    report.ignored = ignored;
+    Ok(report)
+}
+
+/// `*.json` files directly in `dir`, sorted by name
+fn json_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files: Vec<PathBuf> = std::fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    files.sort();
+    Ok(files)
+}
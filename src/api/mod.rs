@@ -0,0 +1,473 @@
+//! Stable embedding API
+//!
+//! This module is the supported way to drive the framework from other Rust
+//! services. Everything here follows semver. Structs are `#[non_exhaustive]`
+//! and only gain fields or methods in minor releases. Breaking changes wait
+//! for a major release.
+//!
+//! Everything outside `api` is **unstable**. That includes
+//! `GleanMCPInspector`, `AllToolsTestResult`, the transport, and the
+//! validator internals. Those types track the CLI and may change in any
+//! release.
+//!
+//! ```no_run
+//! use glean_mcp_test::api::{ReportFormat, Suite};
+//!
+//! # async fn example() -> glean_mcp_test::api::Result<()> {
+//! let report = Suite::for_instance("scio-prod")
+//!     .with_tools("core")
+//!     .with_label("caller", "deploy-bot")
+//!     .run()
+//!     .await?;
+//! if !report.passed {
+//!     eprintln!("{}", report.render(ReportFormat::Summary));
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+    AllToolsTestResult, Cassette, CorpusMode, GleanMCPInspector, GleanMcpError, HistoryStore,
+    HostController, ProxyConfig, QueryRule, Reporter, RunRecord, SilentReporter, SkipReason,
+    TestAllOptions, TestQueryGenerator, TlsConfig, base_tool_name,
+};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Why a [`Suite`] run or [`run_host`] call couldn't produce a report
+///
+/// Tool failures are not errors; they show up in the report. New variants
+/// may be added in minor releases, and anything without a variant of its own
+/// arrives as [`Error::Other`].
+#[derive(thiserror::Error, Debug)]
+#[non_exhaustive]
+pub enum Error {
+    #[error("Configuration error: {0}")]
+    Config(String),
+
+    #[error("Authentication error: {0}")]
+    Auth(String),
+
+    #[error("Network error: {0}")]
+    Network(String),
+
+    #[error("HTTP {status}: {message}")]
+    #[non_exhaustive]
+    Http {
+        status: u16,
+        message: String,
+        /// Delay requested by the server's `Retry-After` header
+        retry_after: Option<Duration>,
+    },
+
+    #[error("Host application error: {0}")]
+    Host(String),
+
+    #[error("IO error: {0}")]
+    Io(#[source] std::io::Error),
+
+    #[error("{0}")]
+    Other(String),
+}
+
+impl Error {
+    /// Map the unstable internal error onto the stable variants, keeping only its message otherwise
+    fn from_internal(error: GleanMcpError) -> Self {
+        match error {
+            GleanMcpError::Config(message) => Self::Config(message),
+            GleanMcpError::Auth(message) => Self::Auth(message),
+            GleanMcpError::Network(message) => Self::Network(message),
+            GleanMcpError::Http {
+                status,
+                message,
+                retry_after,
+            } => Self::Http {
+                status,
+                message,
+                retry_after,
+            },
+            GleanMcpError::Host(message) => Self::Host(message),
+            GleanMcpError::Io(error) => Self::Io(error),
+            other => Self::Other(other.to_string()),
+        }
+    }
+}
+
+/// Result of the embedding API
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// A tool test run against one Glean instance, built up with `with_*` methods
+///
+/// Runs are silent unless a reporter is given. Implement the crate-root
+/// [`Reporter`] to receive progress events. It and its event payloads are
+/// unstable, so match on only the fields you need.
+#[derive(Clone)]
+pub struct Suite {
+    instance: String,
+    endpoints: Option<(String, String)>,
+    replay: Option<PathBuf>,
+    proxy: ProxyConfig,
+    tls: TlsConfig,
+    headers: BTreeMap<String, String>,
+    options: TestAllOptions,
+    reporter: Arc<dyn Reporter>,
+}
+
+impl Suite {
+    /// Test `instance`'s `https://<instance>-be.glean.com` endpoints, authenticating with `GLEAN_AUTH_TOKEN`
+    #[must_use]
+    pub fn for_instance(instance: &str) -> Self {
+        Self {
+            instance: instance.to_string(),
+            endpoints: None,
+            replay: None,
+            proxy: ProxyConfig::default(),
+            tls: TlsConfig::default(),
+            headers: BTreeMap::new(),
+            options: TestAllOptions::default(),
+            reporter: Arc::new(SilentReporter),
+        }
+    }
+
+    /// Target explicit default and `ChatGPT` endpoint URLs, e.g. a proxy or mock server
+    #[must_use]
+    pub fn with_endpoints(mut self, default_url: &str, chatgpt_url: &str) -> Self {
+        self.endpoints = Some((default_url.to_string(), chatgpt_url.to_string()));
+        self
+    }
+
+    /// Replay MCP responses from a cassette instead of calling the server
+    #[must_use]
+    pub fn with_replay(mut self, cassette: impl Into<PathBuf>) -> Self {
+        self.replay = Some(cassette.into());
+        self
+    }
+
+    /// Send requests through the proxy at `url` (default: the proxy environment variables)
+    #[must_use]
+    pub fn with_proxy(mut self, url: &str) -> Self {
+        self.proxy = self.proxy.with_url(url);
+        self
+    }
+
+    /// Connect directly, ignoring the proxy environment variables
+    #[must_use]
+    pub fn without_proxy(mut self) -> Self {
+        self.proxy = self.proxy.direct();
+        self
+    }
+
+    /// Trust the PEM CA bundle at `path` instead of the system store
+    #[must_use]
+    pub fn with_ca_cert(mut self, path: impl Into<PathBuf>) -> Self {
+        self.tls = self.tls.with_ca_cert(path);
+        self
+    }
+
+    /// Present the PEM client certificate at `cert` with the private key at `key`
+    #[must_use]
+    pub fn with_client_cert(mut self, cert: impl Into<PathBuf>, key: impl Into<PathBuf>) -> Self {
+        self.tls = self.tls.with_client_cert(cert).with_client_key(key);
+        self
+    }
+
+    /// Send `name: value` with every MCP request, e.g. `X-Glean-ActAs`
+    #[must_use]
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.insert(name.to_string(), value.to_string());
+        self
+    }
+
+    /// Tools to test: `core` (default), `enterprise`, `all`, or a comma-separated list of names and globs
+    #[must_use]
+    pub fn with_tools(mut self, tools: &str) -> Self {
+        self.options.tools_filter = tools.to_string();
+        self
+    }
+
+    /// Skip tools whose names match `pattern`, e.g. `gmail_*`
+    #[must_use]
+    pub fn with_exclude(mut self, pattern: &str) -> Self {
+        self.options.exclude.push(pattern.to_string());
+        self
+    }
+
+    /// Only test tools tagged `tag` among those `with_tools` selects; a tool needs any one of the tags
+    ///
+    /// Combine with `with_tools("all")` to pick from every tool.
+    #[must_use]
+    pub fn with_tag(mut self, tag: &str) -> Self {
+        self.options.tags.push(tag.to_string());
+        self
+    }
+
+    /// Run up to `max_concurrent` tools at once
+    #[must_use]
+    pub fn with_parallelism(mut self, max_concurrent: usize) -> Self {
+        self.options.parallel = max_concurrent > 1;
+        self.options.max_concurrent = max_concurrent.max(1);
+        self
+    }
+
+    /// Per-tool timeout, rounded up to whole seconds
+    #[must_use]
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.options.timeout = timeout.as_secs() + u64::from(timeout.subsec_nanos() > 0);
+        self
+    }
+
+    /// Attempts per tool and the initial backoff between them
+    #[must_use]
+    pub const fn with_retries(mut self, attempts: u32, backoff: Duration) -> Self {
+        self.options.retry_attempts = attempts;
+        self.options.retry_backoff_seconds = backoff.as_secs();
+        self
+    }
+
+    /// Call `tool` with structured `arguments` instead of its generated query
+    #[must_use]
+    pub fn with_arguments(mut self, tool: &str, arguments: serde_json::Value) -> Self {
+        self.options
+            .tool_arguments
+            .insert(tool.to_string(), arguments);
+        self
+    }
+
+    /// Call tools without a built-in test query that match `pattern` with `query`
+    #[must_use]
+    pub fn with_query_rule(mut self, pattern: &str, query: &str) -> Self {
+        self.options.query_rules.push(QueryRule {
+            pattern: pattern.to_string(),
+            query: query.to_string(),
+        });
+        self
+    }
+
+    /// Call `tool` with `queries` instead of its generated query; see [`Self::with_corpus_mode`]
+    #[must_use]
+    pub fn with_queries(mut self, tool: &str, queries: &[&str]) -> Self {
+        self.options.corpus.queries.insert(
+            tool.to_string(),
+            queries.iter().map(ToString::to_string).collect(),
+        );
+        self
+    }
+
+    /// Whether runs call tools with one corpus query (rotating) or all of them
+    #[must_use]
+    pub const fn with_corpus_mode(mut self, mode: CorpusMode) -> Self {
+        self.options.corpus.mode = mode;
+        self
+    }
+
+    /// Attach a `key=value` label to the run
+    #[must_use]
+    pub fn with_label(mut self, key: &str, value: &str) -> Self {
+        self.options
+            .labels
+            .insert(key.to_string(), value.to_string());
+        self
+    }
+
+    /// Send progress events to `reporter` (an unstable trait, see [`Suite`])
+    #[must_use]
+    pub fn with_reporter(mut self, reporter: Arc<dyn Reporter>) -> Self {
+        self.reporter = reporter;
+        self
+    }
+
+    /// Run the suite against both endpoints
+    #[allow(clippy::future_not_send)]
+    pub async fn run(&self) -> Result<SuiteReport> {
+        let mut inspector =
+            GleanMCPInspector::with_reporter(Some(&self.instance), self.reporter.clone())
+                .with_proxy(self.proxy.clone())
+                .with_tls(self.tls.clone())
+                .with_headers(self.headers.clone());
+        if let Some((default_url, chatgpt_url)) = &self.endpoints {
+            inspector = inspector.with_endpoints(default_url, chatgpt_url);
+        }
+        if let Some(path) = &self.replay {
+            inspector =
+                inspector.with_cassette(Cassette::for_replay(path).map_err(Error::from_internal)?);
+        }
+
+        let result = inspector
+            .test_all_tools(&self.options)
+            .await
+            .map_err(Error::from_internal)?;
+        Ok(SuiteReport::new(&self.instance, result))
+    }
+}
+
+/// Outcome of one tool on one endpoint
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ToolReport {
+    /// Tool name, suffixed `#n` for the nth corpus query of an exhaustive run
+    pub name: String,
+    /// Endpoint the tool was tested on (`default` or `chatgpt`), when known
+    pub endpoint: Option<String>,
+    /// `core`, `enterprise`, `agents`, or `unknown`
+    pub category: String,
+    pub passed: bool,
+    /// Passed, but slower than its latency budget
+    pub degraded: bool,
+    /// Why the tool wasn't run, in which case it neither passed nor failed
+    pub skipped: Option<SkipReason>,
+    pub latency: Duration,
+    pub error: Option<String>,
+    /// HTTP status of the failed request, when the server answered with an error status
+    pub http_status: Option<u16>,
+    /// `X-Correlation-Id` of the tool's last request, for finding it in server logs
+    pub correlation_id: Option<String>,
+    /// Request and trace id headers the server answered the tool's last request with
+    pub request_ids: BTreeMap<String, String>,
+}
+
+/// Outcome of a [`Suite`] run
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct SuiteReport {
+    pub instance: String,
+    /// Every tool passed
+    pub passed: bool,
+    /// Tool outcomes sorted by name, then endpoint
+    pub tools: Vec<ToolReport>,
+    pub duration: Duration,
+    pub labels: BTreeMap<String, String>,
+    /// Share of responses conforming to JSON-RPC 2.0 and MCP, as a percentage
+    pub conformance_score: Option<f64>,
+    /// Unique id of the run, the prefix of every request's correlation id
+    pub run_id: Option<String>,
+    raw: AllToolsTestResult,
+}
+
+impl SuiteReport {
+    fn new(instance: &str, raw: AllToolsTestResult) -> Self {
+        let mut tools: Vec<ToolReport> = raw
+            .tool_results
+            .values()
+            .map(|result| {
+                // Named like `search#2 (chatgpt)`: tool, corpus query, endpoint
+                let (name, endpoint) = result
+                    .tool_name
+                    .split_once(" (")
+                    .map_or((result.tool_name.as_str(), None), |(name, suffix)| {
+                        (name, suffix.strip_suffix(')'))
+                    });
+                ToolReport {
+                    name: name.to_string(),
+                    endpoint: endpoint.map(str::to_string),
+                    category: TestQueryGenerator::get_tool_category(base_tool_name(name))
+                        .to_string(),
+                    passed: result.success,
+                    degraded: result.degraded,
+                    skipped: result.skipped,
+                    latency: Duration::from_millis(result.response_time_ms),
+                    error: result.error_message.clone(),
+                    http_status: result
+                        .error_detail
+                        .as_ref()
+                        .and_then(|detail| detail.http_status)
+                        .filter(|status| *status >= 400),
+                    correlation_id: result.correlation_id.clone(),
+                    request_ids: result.request_ids.clone(),
+                }
+            })
+            .collect();
+        tools.sort_by(|a, b| a.name.cmp(&b.name).then(a.endpoint.cmp(&b.endpoint)));
+
+        Self {
+            instance: instance.to_string(),
+            passed: raw.success,
+            tools,
+            duration: Duration::from_millis(raw.execution_summary.total_duration_ms),
+            labels: raw.labels.clone(),
+            conformance_score: raw.conformance.as_ref().map(|report| report.score),
+            run_id: raw.run_id.clone(),
+            raw,
+        }
+    }
+
+    /// Tools that ran and failed
+    pub fn failures(&self) -> impl Iterator<Item = &ToolReport> {
+        self.tools
+            .iter()
+            .filter(|tool| !tool.passed && tool.skipped.is_none())
+    }
+
+    /// Tools that weren't run
+    pub fn skipped(&self) -> impl Iterator<Item = &ToolReport> {
+        self.tools.iter().filter(|tool| tool.skipped.is_some())
+    }
+
+    /// Render the report the way the CLI prints it
+    #[must_use]
+    pub fn render(&self, format: ReportFormat) -> String {
+        let format = match format {
+            ReportFormat::Text => "text",
+            ReportFormat::Json => "json",
+            ReportFormat::Summary => "summary",
+        };
+        self.raw.format_output(format, false, false)
+    }
+
+    /// Record the run in the history store at `history_dir` under `suite`, returning its run id
+    ///
+    /// Recorded runs show up in `history list`, `report compare`, and `report serve`.
+    pub fn record(&self, history_dir: &Path, suite: &str) -> Result<String> {
+        let record = RunRecord::for_tool_run(&self.instance, suite, &self.raw);
+        HistoryStore::new(history_dir)
+            .record(&record)
+            .map_err(Error::from_internal)?;
+        Ok(record.id)
+    }
+}
+
+/// Output format for [`SuiteReport::render`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ReportFormat {
+    Text,
+    Json,
+    Summary,
+}
+
+/// Outcome of testing every Glean tool through a host application
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct HostReport {
+    pub host: String,
+    pub passed: bool,
+    pub details: String,
+    pub error: Option<String>,
+    pub duration: Option<Duration>,
+}
+
+/// Test every Glean tool through `host`
+///
+/// Custom hosts plug in by implementing the crate-root [`HostController`],
+/// which is unstable.
+#[allow(clippy::future_not_send)]
+pub async fn run_host<H: HostController>(host: &H) -> Result<HostReport> {
+    if !host.check_availability().map_err(Error::from_internal)? {
+        return Err(Error::Host(format!(
+            "{} is not available",
+            host.host_name()
+        )));
+    }
+    let result = host
+        .test_all_glean_tools()
+        .await
+        .map_err(Error::from_internal)?;
+    Ok(HostReport {
+        host: result.host,
+        passed: result.success,
+        details: result.details,
+        error: result.error,
+        duration: result.duration,
+    })
+}
@@ -0,0 +1,315 @@
+//! Comparison baselines and run-to-run comparison
+//!
+//! A baseline is a recorded run that later runs are compared against. Runs
+//! that fully pass on a protected branch are promoted automatically (see
+//! [`BaselineConfig`](crate::BaselineConfig)), so `compare` can default to the
+//! latest promoted baseline for the same instance and suite. Saved
+//! `test --format json` files can be compared the same way without history,
+//! via [`load_result_file`].
+
+use crate::{
+    AllToolsTestResult, BaselineConfig, GleanMcpError, Result, RunRecord, SkipReason,
+    ToolTestResult,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+/// Environment variables CI systems use to expose the branch being built
+const BRANCH_ENV_VARS: &[&str] = &[
+    "GITHUB_REF_NAME",
+    "CI_COMMIT_BRANCH",
+    "BUILDKITE_BRANCH",
+    "BRANCH_NAME",
+    "GIT_BRANCH",
+];
+
+/// Detect the current branch from CI environment variables, falling back to git
+#[must_use]
+pub fn current_branch() -> Option<String> {
+    BRANCH_ENV_VARS
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|value| !value.trim().is_empty())
+        .or_else(|| {
+            let output = std::process::Command::new("git")
+                .args(["rev-parse", "--abbrev-ref", "HEAD"])
+                .output()
+                .ok()?;
+            let branch = String::from_utf8_lossy(&output.stdout).trim().to_string();
+            // Detached HEAD has no branch
+            (output.status.success() && !branch.is_empty() && branch != "HEAD").then_some(branch)
+        })
+}
+
+/// Whether a run qualifies for automatic promotion under `config`
+///
+/// Runs that skipped tools as cached passes (`--skip-recently-passed`) don't
+/// qualify: they would leave those tools out of the baseline.
+#[must_use]
+pub fn should_promote(config: &BaselineConfig, record: &RunRecord, branch: Option<&str>) -> bool {
+    let fully_passed = record.success
+        && record.tool_result.as_ref().is_some_and(|result| {
+            result.tested_tools() > 0
+                && result.failed_tools == 0
+                && result.skipped_for(SkipReason::CachedPass) == 0
+        });
+
+    config.auto_promote
+        && fully_passed
+        && branch.is_some_and(|branch| config.protected_branches.iter().any(|b| b == branch))
+}
+
+/// A promotion of a run to baseline
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BaselineEntry {
+    pub run_id: String,
+    pub instance: String,
+    pub suite: String,
+    pub branch: Option<String>,
+    pub promoted_at: String,
+}
+
+/// Promotion log stored alongside the run history
+#[derive(Debug, Clone)]
+pub struct BaselineStore {
+    path: PathBuf,
+}
+
+impl BaselineStore {
+    /// Store for the history directory `history_dir`
+    #[must_use]
+    pub fn new(history_dir: impl AsRef<Path>) -> Self {
+        Self {
+            path: history_dir.as_ref().join("baselines").join("promoted.json"),
+        }
+    }
+
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// All promotions, oldest first
+    pub fn load(&self) -> Result<Vec<BaselineEntry>> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+        let contents = std::fs::read_to_string(&self.path)?;
+        Ok(serde_json::from_str(&contents)?)
+    }
+
+    /// Promote `record` to be the baseline for its instance and suite
+    pub fn promote(&self, record: &RunRecord, branch: Option<&str>) -> Result<BaselineEntry> {
+        let mut entries = self.load()?;
+        let entry = BaselineEntry {
+            run_id: record.id.clone(),
+            instance: record.instance.clone(),
+            suite: record.suite.clone(),
+            branch: branch.map(str::to_string),
+            promoted_at: chrono::Utc::now().to_rfc3339(),
+        };
+        entries.push(entry.clone());
+
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&self.path, serde_json::to_string_pretty(&entries)?)?;
+        Ok(entry)
+    }
+
+    /// Latest promoted baseline for an instance and suite
+    pub fn latest(&self, instance: &str, suite: &str) -> Result<Option<BaselineEntry>> {
+        Ok(self
+            .load()?
+            .into_iter()
+            .rev()
+            .find(|entry| entry.instance == instance && entry.suite == suite))
+    }
+}
+
+/// Load a saved `test --format json` result, or a history record with tool results
+pub fn load_result_file(path: &Path) -> Result<AllToolsTestResult> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| GleanMcpError::Config(format!("Could not read {}: {e}", path.display())))?;
+    serde_json::from_str::<AllToolsTestResult>(&text)
+        .or_else(|e| {
+            serde_json::from_str::<RunRecord>(&text)
+                .ok()
+                .and_then(|record| record.tool_result)
+                .ok_or(e)
+        })
+        .map_err(|e| {
+            GleanMcpError::Config(format!(
+                "{} is not a saved test result (`test --format json --output FILE`): {e}",
+                path.display()
+            ))
+        })
+}
+
+/// How a tool's outcome changed between baseline and current run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolChange {
+    Regressed,
+    Fixed,
+    StillPassing,
+    StillFailing,
+    Added,
+    Removed,
+}
+
+/// Outcome of one tool in both runs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolComparison {
+    pub tool_name: String,
+    pub change: ToolChange,
+    pub baseline_response_time_ms: Option<u64>,
+    pub current_response_time_ms: Option<u64>,
+    pub current_error: Option<String>,
+}
+
+/// Tool-by-tool comparison of a run against a baseline
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunComparison {
+    pub baseline_id: String,
+    pub current_id: String,
+    pub tools: Vec<ToolComparison>,
+}
+
+impl RunComparison {
+    /// Compare the tool results of `current` against `baseline`
+    pub fn between(baseline: &RunRecord, current: &RunRecord) -> Result<Self> {
+        let (Some(baseline_result), Some(current_result)) =
+            (&baseline.tool_result, &current.tool_result)
+        else {
+            return Err(GleanMcpError::Validation(
+                "Only runs with tool results can be compared".to_string(),
+            ));
+        };
+        Ok(Self::between_results(
+            &baseline.id,
+            baseline_result,
+            &current.id,
+            current_result,
+        ))
+    }
+
+    /// Compare `current_result` against `baseline_result`, naming them by `baseline_id` and `current_id`
+    #[must_use]
+    pub fn between_results(
+        baseline_id: &str,
+        baseline_result: &AllToolsTestResult,
+        current_id: &str,
+        current_result: &AllToolsTestResult,
+    ) -> Self {
+        // Skipped tools didn't run, so they compare as absent
+        let names: BTreeSet<&String> = baseline_result
+            .tool_results
+            .values()
+            .chain(current_result.tool_results.values())
+            .filter(|r| !r.is_skipped())
+            .map(|r| &r.tool_name)
+            .collect();
+
+        let tools = names
+            .into_iter()
+            .map(|name| {
+                let before = Self::tested(baseline_result, name);
+                let after = Self::tested(current_result, name);
+                ToolComparison {
+                    tool_name: name.clone(),
+                    change: Self::classify(before, after),
+                    baseline_response_time_ms: before.map(|r| r.response_time_ms),
+                    current_response_time_ms: after.map(|r| r.response_time_ms),
+                    current_error: after.and_then(|r| r.error_message.clone()),
+                }
+            })
+            .collect();
+
+        Self {
+            baseline_id: baseline_id.to_string(),
+            current_id: current_id.to_string(),
+            tools,
+        }
+    }
+
+    /// Result of `name` in `result`, unless it was skipped
+    fn tested<'a>(result: &'a AllToolsTestResult, name: &str) -> Option<&'a ToolTestResult> {
+        result.tool_results.get(name).filter(|r| !r.is_skipped())
+    }
+
+    fn classify(before: Option<&ToolTestResult>, after: Option<&ToolTestResult>) -> ToolChange {
+        match (before.map(|r| r.success), after.map(|r| r.success)) {
+            (Some(true), Some(false)) => ToolChange::Regressed,
+            (Some(false), Some(true)) => ToolChange::Fixed,
+            (Some(true), Some(true)) => ToolChange::StillPassing,
+            (Some(false), Some(false)) => ToolChange::StillFailing,
+            (None, _) => ToolChange::Added,
+            (_, None) => ToolChange::Removed,
+        }
+    }
+
+    /// Tools that passed in the baseline but fail now
+    pub fn regressions(&self) -> impl Iterator<Item = &ToolComparison> {
+        self.tools
+            .iter()
+            .filter(|tool| tool.change == ToolChange::Regressed)
+    }
+
+    #[must_use]
+    pub fn has_regressions(&self) -> bool {
+        self.regressions().next().is_some()
+    }
+
+    #[must_use]
+    pub fn format_text(&self) -> String {
+        let mut output = String::new();
+
+        output.push_str("🔀 Run Comparison\n");
+        output.push_str("=".repeat(50).as_str());
+        output.push('\n');
+        let _ = writeln!(output, "📌 Baseline: {}", self.baseline_id);
+        let _ = writeln!(output, "🆕 Current:  {}", self.current_id);
+        output.push('\n');
+
+        for tool in &self.tools {
+            let marker = match tool.change {
+                ToolChange::Regressed => "🔻 regressed",
+                ToolChange::Fixed => "🔺 fixed",
+                ToolChange::StillPassing => "✅ passing",
+                ToolChange::StillFailing => "❌ still failing",
+                ToolChange::Added => "➕ added",
+                ToolChange::Removed => "➖ removed",
+            };
+            let latency = match (
+                tool.baseline_response_time_ms,
+                tool.current_response_time_ms,
+            ) {
+                (Some(before), Some(after)) => format!(
+                    " ({before}ms → {after}ms, {:+}ms)",
+                    i128::from(after) - i128::from(before)
+                ),
+                _ => String::new(),
+            };
+            let _ = writeln!(output, "  {marker:<18} {}{latency}", tool.tool_name);
+
+            if tool.change == ToolChange::Regressed
+                && let Some(error) = &tool.current_error
+            {
+                let _ = writeln!(output, "     Error: {error}");
+            }
+        }
+
+        let regressions = self.regressions().count();
+        output.push('\n');
+        if regressions == 0 {
+            output.push_str("✅ No regressions against baseline\n");
+        } else {
+            let _ = writeln!(output, "🚨 {regressions} regression(s) against baseline");
+        }
+
+        output
+    }
+}
@@ -0,0 +1,485 @@
+//! Load testing against a single MCP tool
+//!
+//! [`run_bench`] keeps `concurrency` requests in flight against one tool for
+//! a fixed duration, optionally paced to a global request rate, and reports
+//! latency percentiles, throughput, and error rates. A result saved with
+//! `--save-baseline` becomes the reference later runs are compared against
+//! with `--baseline`: [`BenchComparison`] fails the run when p95 latency or
+//! the error rate grew past the `bench` config's thresholds.
+
+use crate::{GleanMCPInspector, GleanMcpError, Result, TestQueryGenerator};
+use serde::{Deserialize, Serialize};
+use smol::Timer;
+use std::cell::{Cell, RefCell};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// Load profile for a benchmark run
+#[derive(Debug, Clone)]
+pub struct BenchOptions {
+    pub tool: String,
+    /// Query sent with every call; defaults to the tool's standard test query
+    pub query: Option<String>,
+    /// Requests kept in flight at once
+    pub concurrency: usize,
+    pub duration: Duration,
+    /// Cap on requests started per second across all workers
+    pub max_rps: Option<f64>,
+    /// Per-request timeout
+    pub timeout: Duration,
+}
+
+/// Latency distribution of successful requests, in milliseconds
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LatencyStats {
+    pub min: u64,
+    pub mean: u64,
+    pub p50: u64,
+    pub p95: u64,
+    pub p99: u64,
+    pub max: u64,
+}
+
+impl LatencyStats {
+    /// Compute stats from unsorted samples (all zero when empty)
+    #[must_use]
+    pub fn from_samples(samples: &[u64]) -> Self {
+        if samples.is_empty() {
+            return Self::default();
+        }
+        let mut sorted = samples.to_vec();
+        sorted.sort_unstable();
+
+        Self {
+            min: sorted[0],
+            mean: sorted.iter().sum::<u64>() / sorted.len() as u64,
+            p50: percentile(&sorted, 50.0),
+            p95: percentile(&sorted, 95.0),
+            p99: percentile(&sorted, 99.0),
+            max: sorted[sorted.len() - 1],
+        }
+    }
+}
+
+/// Successful requests whose latency fell in `from_ms..to_ms`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LatencyBucket {
+    pub from_ms: u64,
+    pub to_ms: u64,
+    pub count: usize,
+}
+
+/// Bars a sparkline is drawn with, shortest first
+const SPARK_BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// Up to `buckets` equal-width buckets spanning the samples' range (empty without samples)
+#[must_use]
+pub fn latency_histogram(samples: &[u64], buckets: usize) -> Vec<LatencyBucket> {
+    let (Some(min), Some(max)) = (samples.iter().min(), samples.iter().max()) else {
+        return Vec::new();
+    };
+    let buckets = buckets.max(1) as u64;
+    let width = ((max - min) / buckets + 1).max(1);
+    let count = (max - min) / width + 1;
+    let mut histogram: Vec<LatencyBucket> = (0..count)
+        .map(|index| LatencyBucket {
+            from_ms: min + index * width,
+            to_ms: min + (index + 1) * width,
+            count: 0,
+        })
+        .collect();
+    for sample in samples {
+        let index = usize::try_from((sample - min) / width).unwrap_or(usize::MAX);
+        if let Some(bucket) = histogram.get_mut(index) {
+            bucket.count += 1;
+        }
+    }
+    histogram
+}
+
+/// `histogram` as one bar per bucket, scaled to the fullest bucket; empty buckets show as `▁`
+#[must_use]
+pub fn sparkline(histogram: &[LatencyBucket]) -> String {
+    let fullest = histogram
+        .iter()
+        .map(|bucket| bucket.count)
+        .max()
+        .unwrap_or_default()
+        .max(1);
+    histogram
+        .iter()
+        .map(|bucket| SPARK_BARS[bucket.count * (SPARK_BARS.len() - 1) / fullest])
+        .collect()
+}
+
+/// Nearest-rank percentile of sorted, non-empty samples
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss,
+    clippy::cast_precision_loss
+)]
+fn percentile(sorted: &[u64], p: f64) -> u64 {
+    let rank = (p / 100.0 * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+/// Outcome of a benchmark run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BenchResult {
+    pub tool: String,
+    pub endpoint: String,
+    pub concurrency: usize,
+    pub max_rps: Option<f64>,
+    pub duration_ms: u64,
+    pub total_requests: usize,
+    pub successful_requests: usize,
+    pub failed_requests: usize,
+    /// Completed requests per second
+    pub throughput_rps: f64,
+    /// Fraction of requests that failed (0.0-1.0)
+    pub error_rate: f64,
+    pub latency: LatencyStats,
+    /// Failure counts by error message
+    pub errors: BTreeMap<String, usize>,
+    /// Comparison with the `--baseline` run
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub baseline: Option<BenchComparison>,
+}
+
+impl BenchResult {
+    #[must_use]
+    pub fn format_text(&self) -> String {
+        let mut output = String::new();
+
+        output.push_str("🏎️  Benchmark Results\n");
+        output.push_str("=".repeat(50).as_str());
+        output.push('\n');
+        let _ = writeln!(output, "🔧 Tool: {}", self.tool);
+        let _ = writeln!(output, "📍 Endpoint: {}", self.endpoint);
+        let _ = writeln!(
+            output,
+            "⚡ Concurrency: {}{}",
+            self.concurrency,
+            self.max_rps
+                .map_or_else(String::new, |rps| format!(" (max {rps} req/s)"))
+        );
+        #[allow(clippy::cast_precision_loss)]
+        let seconds = self.duration_ms as f64 / 1000.0;
+        let _ = writeln!(output, "⏱️  Duration: {seconds:.1}s");
+        output.push('\n');
+
+        let _ = writeln!(
+            output,
+            "📨 Requests: {} total, {} ok, {} failed ({:.1}% errors)",
+            self.total_requests,
+            self.successful_requests,
+            self.failed_requests,
+            self.error_rate * 100.0
+        );
+        let _ = writeln!(output, "🚀 Throughput: {:.2} req/s", self.throughput_rps);
+        let _ = writeln!(
+            output,
+            "📈 Latency: p50 {}ms | p95 {}ms | p99 {}ms (min {}ms, mean {}ms, max {}ms)",
+            self.latency.p50,
+            self.latency.p95,
+            self.latency.p99,
+            self.latency.min,
+            self.latency.mean,
+            self.latency.max
+        );
+
+        if !self.errors.is_empty() {
+            output.push('\n');
+            output.push_str("❌ Errors:\n");
+            for (error, count) in &self.errors {
+                let _ = writeln!(output, "  {count:>5} × {error}");
+            }
+        }
+
+        if let Some(baseline) = &self.baseline {
+            output.push('\n');
+            output.push_str(&baseline.format_text());
+        }
+
+        output
+    }
+}
+
+/// Regression thresholds for `bench --baseline` (`bench` section of the config file)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BenchConfig {
+    /// Largest allowed p95 latency increase, in percent of the baseline's
+    pub max_p95_increase_percent: f64,
+    /// p95 increases of fewer milliseconds never count, whatever their percentage
+    pub min_p95_increase_ms: u64,
+    /// Largest allowed error rate increase, in percentage points
+    pub max_error_rate_increase: f64,
+}
+
+impl Default for BenchConfig {
+    fn default() -> Self {
+        Self {
+            max_p95_increase_percent: 20.0,
+            min_p95_increase_ms: 50,
+            max_error_rate_increase: 1.0,
+        }
+    }
+}
+
+/// How one metric moved against the baseline
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BenchMetricChange {
+    /// e.g. `p95 latency`
+    pub metric: String,
+    pub baseline: f64,
+    pub current: f64,
+    /// Whether the metric gates the run
+    pub gated: bool,
+    pub regressed: bool,
+}
+
+/// A benchmark result compared with a saved baseline run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BenchComparison {
+    /// File the baseline was read from
+    pub file: String,
+    pub changes: Vec<BenchMetricChange>,
+    /// Load differences that make the comparison less meaningful
+    pub warnings: Vec<String>,
+    /// p95 latency or the error rate grew past its threshold
+    pub regressed: bool,
+}
+
+impl BenchComparison {
+    /// Compare `current` with `baseline` under `config`'s thresholds
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn new(
+        file: &str,
+        baseline: &BenchResult,
+        current: &BenchResult,
+        config: &BenchConfig,
+    ) -> Self {
+        let latency = |metric: &str, baseline: u64, current: u64| BenchMetricChange {
+            metric: format!("{metric} latency"),
+            baseline: baseline as f64,
+            current: current as f64,
+            gated: false,
+            regressed: false,
+        };
+        let p95_increase = current.latency.p95.saturating_sub(baseline.latency.p95);
+        let p95_limit = baseline.latency.p95 as f64 * config.max_p95_increase_percent / 100.0;
+        let error_increase = (current.error_rate - baseline.error_rate) * 100.0;
+        let changes = vec![
+            latency("p50", baseline.latency.p50, current.latency.p50),
+            BenchMetricChange {
+                gated: true,
+                regressed: p95_increase >= config.min_p95_increase_ms
+                    && p95_increase as f64 > p95_limit,
+                ..latency("p95", baseline.latency.p95, current.latency.p95)
+            },
+            latency("p99", baseline.latency.p99, current.latency.p99),
+            BenchMetricChange {
+                metric: "error rate".to_string(),
+                baseline: baseline.error_rate * 100.0,
+                current: current.error_rate * 100.0,
+                gated: true,
+                regressed: error_increase > config.max_error_rate_increase,
+            },
+            BenchMetricChange {
+                metric: "throughput".to_string(),
+                baseline: baseline.throughput_rps,
+                current: current.throughput_rps,
+                gated: false,
+                regressed: false,
+            },
+        ];
+
+        let mut warnings = Vec::new();
+        if baseline.tool != current.tool {
+            warnings.push(format!(
+                "baseline benchmarked {}, this run {}",
+                baseline.tool, current.tool
+            ));
+        }
+        if baseline.concurrency != current.concurrency || baseline.max_rps != current.max_rps {
+            warnings.push(format!(
+                "baseline ran {} concurrent request(s){}, this run {}{}",
+                baseline.concurrency,
+                baseline
+                    .max_rps
+                    .map_or_else(String::new, |rps| format!(" at {rps} req/s")),
+                current.concurrency,
+                current
+                    .max_rps
+                    .map_or_else(String::new, |rps| format!(" at {rps} req/s")),
+            ));
+        }
+        if baseline.successful_requests == 0 {
+            warnings
+                .push("baseline has no successful requests to compare latency with".to_string());
+        }
+
+        Self {
+            file: file.to_string(),
+            regressed: changes.iter().any(|change| change.regressed),
+            changes,
+            warnings,
+        }
+    }
+
+    #[must_use]
+    pub fn format_text(&self) -> String {
+        let mut output = String::new();
+        let _ = writeln!(output, "📏 Against baseline {}:", self.file);
+        for change in &self.changes {
+            let (unit, precision) = match change.metric.as_str() {
+                "error rate" => ("%", 1),
+                "throughput" => (" req/s", 2),
+                _ => ("ms", 0),
+            };
+            let delta = if change.baseline > 0.0 {
+                format!(
+                    " ({:+.0}%)",
+                    (change.current / change.baseline - 1.0) * 100.0
+                )
+            } else {
+                String::new()
+            };
+            let marker = match (change.gated, change.regressed) {
+                (_, true) => "❌",
+                (true, false) => "✅",
+                (false, false) => "  ",
+            };
+            let _ = writeln!(
+                output,
+                "  {marker} {}: {:.precision$}{unit} → {:.precision$}{unit}{delta}",
+                change.metric, change.baseline, change.current
+            );
+        }
+        for warning in &self.warnings {
+            let _ = writeln!(output, "  ⚠️  {warning}");
+        }
+        if self.regressed {
+            let _ = writeln!(output, "📉 Performance regressed against the baseline");
+        } else {
+            let _ = writeln!(output, "✅ No regression against the baseline");
+        }
+        output
+    }
+}
+
+/// Read a benchmark result saved with `--save-baseline` (or `--format json`)
+pub fn load_bench_baseline(path: &Path) -> Result<BenchResult> {
+    let text = std::fs::read_to_string(path).map_err(|e| {
+        GleanMcpError::Config(format!(
+            "Could not read baseline {}: {e} (record one with --save-baseline)",
+            path.display()
+        ))
+    })?;
+    serde_json::from_str(&text).map_err(|e| {
+        GleanMcpError::Config(format!(
+            "{} is not a saved benchmark result (`bench --baseline FILE --save-baseline`): {e}",
+            path.display()
+        ))
+    })
+}
+
+/// Write `result` to `path` as the baseline later runs are compared against
+pub fn save_bench_baseline(result: &BenchResult, path: &Path) -> Result<()> {
+    if let Some(parent) = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+    {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, serde_json::to_string_pretty(result)?)?;
+    Ok(())
+}
+
+/// Drive load against `options.tool` on the inspector's default endpoint
+#[allow(clippy::future_not_send)]
+#[allow(clippy::cast_possible_truncation)]
+pub async fn run_bench(inspector: &GleanMCPInspector, options: &BenchOptions) -> BenchResult {
+    let query = options
+        .query
+        .clone()
+        .unwrap_or_else(|| TestQueryGenerator::generate_test_query(&options.tool));
+    let interval = options
+        .max_rps
+        .filter(|rps| *rps > 0.0)
+        .map(|rps| Duration::from_secs_f64(1.0 / rps));
+
+    let started = Instant::now();
+    let deadline = started + options.duration;
+    // Next start slot shared by all workers when pacing to --rps
+    let next_slot = Cell::new(started);
+    let latencies = RefCell::new(Vec::new());
+    let errors = RefCell::new(BTreeMap::<String, usize>::new());
+
+    let worker = || async {
+        loop {
+            if let Some(interval) = interval {
+                let slot = next_slot.get().max(Instant::now());
+                if slot >= deadline {
+                    break;
+                }
+                next_slot.set(slot + interval);
+                Timer::at(slot).await;
+            } else if Instant::now() >= deadline {
+                break;
+            }
+
+            let request_start = Instant::now();
+            match inspector
+                .call_tool(&options.tool, &query, options.timeout)
+                .await
+            {
+                Ok(_) => latencies
+                    .borrow_mut()
+                    .push(request_start.elapsed().as_millis() as u64),
+                Err(e) => {
+                    let message = GleanMCPInspector::truncate_error_message(&e.to_string());
+                    *errors.borrow_mut().entry(message).or_default() += 1;
+                }
+            }
+        }
+    };
+    futures::future::join_all((0..options.concurrency.max(1)).map(|_| worker())).await;
+
+    let elapsed = started.elapsed();
+    let latencies = latencies.into_inner();
+    let errors = errors.into_inner();
+    let successful_requests = latencies.len();
+    let failed_requests: usize = errors.values().sum();
+    let total_requests = successful_requests + failed_requests;
+
+    #[allow(clippy::cast_precision_loss)]
+    let (throughput_rps, error_rate) = (
+        total_requests as f64 / elapsed.as_secs_f64().max(f64::EPSILON),
+        if total_requests == 0 {
+            0.0
+        } else {
+            failed_requests as f64 / total_requests as f64
+        },
+    );
+
+    BenchResult {
+        tool: options.tool.clone(),
+        endpoint: inspector.server_url().to_string(),
+        concurrency: options.concurrency.max(1),
+        max_rps: options.max_rps,
+        duration_ms: elapsed.as_millis() as u64,
+        total_requests,
+        successful_requests,
+        failed_requests,
+        throughput_rps,
+        error_rate,
+        latency: LatencyStats::from_samples(&latencies),
+        errors,
+        baseline: None,
+    }
+}
@@ -0,0 +1,383 @@
+//! Coverage of what recorded runs exercised
+//!
+//! [`CoverageReport::build`] crosses the tools, endpoints, hosts, and
+//! protocol methods the framework knows about (a [`CoverageSurface`]) with
+//! what a set of recorded runs actually exercised. Tools are covered on an
+//! endpoint by `test` and monitor runs, and on a host by the `hosts` suite;
+//! protocol methods come from the requests tallied in each run's conformance
+//! report. Every cell no run reached is a blind spot.
+
+use crate::{GleanConfig, RunRecord, SUPPORTED_HOSTS, base_tool_name, schema_version};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+
+/// JSON-RPC methods the framework sends
+pub const PROTOCOL_METHODS: [&str; 6] = [
+    "initialize",
+    "notifications/initialized",
+    "tools/list",
+    "tools/call",
+    "ping",
+    "notifications/cancelled",
+];
+
+/// Everything a run could exercise
+#[derive(Debug, Clone, Default)]
+pub struct CoverageSurface {
+    pub tools: BTreeSet<String>,
+    pub endpoints: BTreeSet<String>,
+    pub hosts: BTreeSet<String>,
+    pub methods: Vec<String>,
+}
+
+impl CoverageSurface {
+    /// The profile's tools, the configured tools, endpoints, and hosts, and the supported hosts
+    ///
+    /// Host names are normalized to the `claude-code` form `hosts` suites use.
+    #[must_use]
+    pub fn from_config(config: &GleanConfig) -> Self {
+        let configured_tools = config
+            .tools_to_test
+            .core_tools
+            .iter()
+            .chain(&config.tools_to_test.enterprise_tools)
+            .map(|tool| tool.strip_prefix("glean_").unwrap_or(tool).to_string());
+        Self {
+            tools: config
+                .profile
+                .tool_names()
+                .into_iter()
+                .chain(configured_tools)
+                .collect(),
+            endpoints: ["default", "chatgpt"]
+                .into_iter()
+                .map(String::from)
+                .chain(
+                    config
+                        .glean_instance
+                        .endpoints
+                        .iter()
+                        .map(|endpoint| endpoint.name.clone()),
+                )
+                .collect(),
+            hosts: SUPPORTED_HOSTS
+                .iter()
+                .map(ToString::to_string)
+                .chain(
+                    config
+                        .host_applications
+                        .keys()
+                        .map(|host| host.replace('_', "-")),
+                )
+                .collect(),
+            methods: PROTOCOL_METHODS.iter().map(ToString::to_string).collect(),
+        }
+    }
+}
+
+/// Whether a matrix column is an endpoint or a host
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CoverageTarget {
+    Endpoint,
+    Host,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CoverageColumn {
+    pub name: String,
+    pub target: CoverageTarget,
+}
+
+/// How often a tool ran on one endpoint or host
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CoverageCell {
+    pub tested: usize,
+    pub passed: usize,
+}
+
+impl CoverageCell {
+    fn format(self) -> String {
+        if self.tested == 0 {
+            "·".to_string()
+        } else {
+            format!("{}/{}", self.passed, self.tested)
+        }
+    }
+}
+
+/// One tool's cells, in column order
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CoverageRow {
+    pub tool: String,
+    pub cells: Vec<CoverageCell>,
+}
+
+/// What a set of runs exercised, against everything they could have
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CoverageReport {
+    #[serde(default = "schema_version")]
+    pub schema_version: String,
+    /// Ids of the runs covered, oldest first
+    pub runs: Vec<String>,
+    pub columns: Vec<CoverageColumn>,
+    pub rows: Vec<CoverageRow>,
+    /// Requests sent per protocol method; known methods that were never sent count 0
+    pub methods: BTreeMap<String, usize>,
+    /// Tool cells exercised at least once
+    pub covered_cells: usize,
+    pub total_cells: usize,
+    /// What no run reached, e.g. `outlook_search never tested on cursor`
+    pub blind_spots: Vec<String>,
+}
+
+/// Endpoint a result name was tested on: `search (chatgpt)` → `chatgpt`, `search` → `default`
+fn endpoint_of(tool_name: &str) -> &str {
+    tool_name
+        .rsplit_once(" (")
+        .and_then(|(_, suffix)| suffix.strip_suffix(')'))
+        .unwrap_or("default")
+}
+
+impl CoverageReport {
+    /// Cross `runs` with `surface`; tools, endpoints, and hosts seen in runs are added to it
+    #[must_use]
+    pub fn build(runs: &[RunRecord], surface: &CoverageSurface) -> Self {
+        let mut surface = surface.clone();
+        let mut endpoint_cells: BTreeMap<(String, String), CoverageCell> = BTreeMap::new();
+        let mut host_cells: BTreeMap<(String, String), CoverageCell> = BTreeMap::new();
+        let mut methods: BTreeMap<String, usize> = surface
+            .methods
+            .iter()
+            .map(|method| (method.clone(), 0))
+            .collect();
+
+        for run in runs {
+            if let Some(result) = &run.tool_result {
+                for (name, tool_result) in &result.tool_results {
+                    if tool_result.is_skipped() {
+                        continue;
+                    }
+                    let tool = base_tool_name(name).to_string();
+                    let endpoint = endpoint_of(name).to_string();
+                    surface.tools.insert(tool.clone());
+                    surface.endpoints.insert(endpoint.clone());
+                    let cell = endpoint_cells.entry((tool, endpoint)).or_default();
+                    cell.tested += 1;
+                    cell.passed += usize::from(tool_result.success);
+                }
+                for (method, count) in result
+                    .conformance
+                    .iter()
+                    .flat_map(|conformance| &conformance.methods)
+                {
+                    *methods.entry(method.clone()).or_default() += count;
+                }
+            }
+            for host_result in &run.host_results {
+                surface.hosts.insert(host_result.host.clone());
+                for (tool, passed) in &host_result.tools {
+                    surface.tools.insert(tool.clone());
+                    let cell = host_cells
+                        .entry((tool.clone(), host_result.host.clone()))
+                        .or_default();
+                    cell.tested += 1;
+                    cell.passed += usize::from(*passed);
+                }
+            }
+        }
+
+        let columns: Vec<CoverageColumn> = surface
+            .endpoints
+            .iter()
+            .map(|name| CoverageColumn {
+                name: name.clone(),
+                target: CoverageTarget::Endpoint,
+            })
+            .chain(surface.hosts.iter().map(|name| CoverageColumn {
+                name: name.clone(),
+                target: CoverageTarget::Host,
+            }))
+            .collect();
+        let rows: Vec<CoverageRow> = surface
+            .tools
+            .iter()
+            .map(|tool| CoverageRow {
+                tool: tool.clone(),
+                cells: columns
+                    .iter()
+                    .map(|column| {
+                        let cells = match column.target {
+                            CoverageTarget::Endpoint => &endpoint_cells,
+                            CoverageTarget::Host => &host_cells,
+                        };
+                        cells
+                            .get(&(tool.clone(), column.name.clone()))
+                            .copied()
+                            .unwrap_or_default()
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        let mut report = Self {
+            schema_version: schema_version(),
+            runs: runs.iter().map(|run| run.id.clone()).collect(),
+            covered_cells: rows
+                .iter()
+                .flat_map(|row| &row.cells)
+                .filter(|cell| cell.tested > 0)
+                .count(),
+            total_cells: rows.len() * columns.len(),
+            columns,
+            rows,
+            methods,
+            blind_spots: Vec::new(),
+        };
+        report.blind_spots = report.find_blind_spots();
+        report
+    }
+
+    /// Columns nothing ran on, then each tool's unreached columns, then unsent methods
+    fn find_blind_spots(&self) -> Vec<String> {
+        let unused: Vec<bool> = (0..self.columns.len())
+            .map(|index| self.rows.iter().all(|row| row.cells[index].tested == 0))
+            .collect();
+        let mut blind_spots: Vec<String> = self
+            .columns
+            .iter()
+            .zip(&unused)
+            .filter(|(_, unused)| **unused)
+            .map(|(column, _)| match column.target {
+                CoverageTarget::Endpoint => format!("endpoint {} never tested", column.name),
+                CoverageTarget::Host => format!("host {} never tested", column.name),
+            })
+            .collect();
+        for row in &self.rows {
+            let missed: Vec<&str> = self
+                .columns
+                .iter()
+                .zip(&row.cells)
+                .zip(&unused)
+                .filter(|((_, cell), unused)| cell.tested == 0 && !**unused)
+                .map(|((column, _), _)| column.name.as_str())
+                .collect();
+            if missed.len() == self.columns.len() {
+                blind_spots.push(format!("{} never tested", row.tool));
+            } else if !missed.is_empty() {
+                blind_spots.push(format!(
+                    "{} never tested on {}",
+                    row.tool,
+                    missed.join(", ")
+                ));
+            }
+        }
+        blind_spots.extend(
+            self.methods
+                .iter()
+                .filter(|(_, count)| **count == 0)
+                .map(|(method, _)| format!("{method} never sent")),
+        );
+        blind_spots
+    }
+
+    /// Share of tool cells exercised, as a percentage
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn percent(&self) -> f64 {
+        if self.total_cells == 0 {
+            return 100.0;
+        }
+        self.covered_cells as f64 * 100.0 / self.total_cells as f64
+    }
+
+    #[must_use]
+    pub fn format_text(&self) -> String {
+        let mut output = String::new();
+        let _ = writeln!(output, "🧭 Coverage of {} run(s)", self.runs.len());
+        output.push_str("=".repeat(50).as_str());
+        output.push('\n');
+        let _ = writeln!(
+            output,
+            "📊 {}/{} tool cells exercised ({:.0}%), cells show passed/tested runs",
+            self.covered_cells,
+            self.total_cells,
+            self.percent()
+        );
+
+        let tool_width = self
+            .rows
+            .iter()
+            .map(|row| row.tool.chars().count())
+            .max()
+            .unwrap_or_default()
+            .max("Tool".len());
+        let kind = |column: &CoverageColumn| match column.target {
+            CoverageTarget::Endpoint => "endpoint",
+            CoverageTarget::Host => "host",
+        };
+        let widths: Vec<usize> = self
+            .columns
+            .iter()
+            .enumerate()
+            .map(|(index, column)| {
+                self.rows
+                    .iter()
+                    .map(|row| row.cells[index].format().chars().count())
+                    .chain([column.name.chars().count(), kind(column).len()])
+                    .max()
+                    .unwrap_or_default()
+            })
+            .collect();
+        let kinds: Vec<String> = self
+            .columns
+            .iter()
+            .zip(&widths)
+            .map(|(column, width)| format!("{:<width$}", kind(column)))
+            .collect();
+        let _ = writeln!(
+            output,
+            "\n{:tool_width$}  {}",
+            "",
+            kinds.join("  ").trim_end()
+        );
+        let header: Vec<String> = self
+            .columns
+            .iter()
+            .zip(&widths)
+            .map(|(column, width)| format!("{:<width$}", column.name))
+            .collect();
+        let _ = writeln!(output, "{:tool_width$}  {}", "Tool", header.join("  "));
+        for row in &self.rows {
+            let cells: Vec<String> = row
+                .cells
+                .iter()
+                .zip(&widths)
+                .map(|(cell, width)| format!("{:<width$}", cell.format()))
+                .collect();
+            let _ = writeln!(
+                output,
+                "{:tool_width$}  {}",
+                row.tool,
+                cells.join("  ").trim_end()
+            );
+        }
+
+        let methods: Vec<String> = self
+            .methods
+            .iter()
+            .map(|(method, count)| format!("{method} {count}"))
+            .collect();
+        let _ = writeln!(output, "\n📨 Protocol methods: {}", methods.join(", "));
+        if self.blind_spots.is_empty() {
+            let _ = writeln!(output, "✅ No blind spots");
+        } else {
+            let _ = writeln!(output, "\n🕳️  Blind spots:");
+            for blind_spot in &self.blind_spots {
+                let _ = writeln!(output, "  - {blind_spot}");
+            }
+        }
+        output
+    }
+}
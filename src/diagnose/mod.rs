@@ -0,0 +1,597 @@
+//! Deep diagnosis of a single tool
+//!
+//! [`run_diagnosis`] runs everything worth knowing about one tool in one go,
+//! stopping at the first stage that leaves nothing more to learn: whether
+//! the server is reachable and accepts the token, whether `tools/list`
+//! advertises the tool and with what schema, how it answers queries of
+//! increasing complexity, its latency over several calls, and how it
+//! handles invalid input. Problems come with a fix where one is known.
+
+use crate::doctor::check_server;
+use crate::mcp_inspector::async_timeout;
+use crate::{
+    CheckStatus, DoctorArea, GleanMCPInspector, NegativeCase, QueryRule, RequestTrace, RunOutcome,
+    TestAllOptions, ToolInfo, ToolRepeatStats, response_text, sparkline, string_parameter,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::time::{Duration, Instant};
+
+/// Listed tools named in the fix when the diagnosed one is missing
+const MAX_SUGGESTED: usize = 5;
+
+/// What a step looked at, in the order the diagnosis runs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiagnosisStage {
+    Connectivity,
+    Auth,
+    Discovery,
+    Schema,
+    Queries,
+    Latency,
+    NegativeInput,
+}
+
+impl DiagnosisStage {
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Connectivity => "Connectivity",
+            Self::Auth => "Authentication",
+            Self::Discovery => "Discovery",
+            Self::Schema => "Schema",
+            Self::Queries => "Queries",
+            Self::Latency => "Latency",
+            Self::NegativeInput => "Invalid input",
+        }
+    }
+}
+
+/// How demanding a diagnostic query is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryLevel {
+    /// A single word from the standard query
+    Simple,
+    /// The tool's usual test query
+    Standard,
+    /// The standard query with several constraints added
+    Complex,
+}
+
+impl QueryLevel {
+    pub const ALL: [Self; 3] = [Self::Simple, Self::Standard, Self::Complex];
+
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Simple => "simple",
+            Self::Standard => "standard",
+            Self::Complex => "complex",
+        }
+    }
+
+    /// This level's version of `standard`, or `None` when `standard` can't be varied (e.g. a URL)
+    fn query(self, standard: &str) -> Option<String> {
+        if self == Self::Standard {
+            return Some(standard.to_string());
+        }
+        if standard.contains("://") || !standard.contains(char::is_whitespace) {
+            return None;
+        }
+        Some(match self {
+            Self::Simple => standard
+                .split_whitespace()
+                .map(|word| word.trim_matches(|c: char| !c.is_alphanumeric()))
+                .max_by_key(|word| word.chars().count())
+                .unwrap_or(standard)
+                .to_string(),
+            _ => format!(
+                "{}, comparing this quarter with the last one, grouped by team, with the owner and a link for each",
+                standard.trim_end_matches(['?', '.'])
+            ),
+        })
+    }
+}
+
+/// One finding and, when something is wrong, how to fix it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiagnosisStep {
+    pub stage: DiagnosisStage,
+    /// What was checked, e.g. the server URL or `complex query`
+    pub name: String,
+    pub status: CheckStatus,
+    pub detail: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fix: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<u64>,
+}
+
+impl DiagnosisStep {
+    fn new(
+        stage: DiagnosisStage,
+        name: impl Into<String>,
+        status: CheckStatus,
+        detail: impl Into<String>,
+    ) -> Self {
+        Self {
+            stage,
+            name: name.into(),
+            status,
+            detail: detail.into(),
+            fix: None,
+            duration_ms: None,
+        }
+    }
+
+    fn with_fix(mut self, fix: impl Into<String>) -> Self {
+        self.fix = Some(fix.into());
+        self
+    }
+
+    const fn with_duration(mut self, duration_ms: u64) -> Self {
+        self.duration_ms = Some(duration_ms);
+        self
+    }
+}
+
+/// Settings for `diagnose`
+#[derive(Debug, Clone)]
+pub struct DiagnoseOptions {
+    /// Calls made for the latency samples; 0 skips them
+    pub samples: usize,
+    /// Timeout per request
+    pub timeout: Duration,
+    /// Skip the invalid-input cases
+    pub skip_negative: bool,
+    /// Configured `tools/call` arguments per tool, used for every query
+    pub tool_arguments: BTreeMap<String, Value>,
+    pub query_rules: Vec<QueryRule>,
+}
+
+impl Default for DiagnoseOptions {
+    fn default() -> Self {
+        Self {
+            samples: 5,
+            timeout: Duration::from_secs(30),
+            skip_negative: false,
+            tool_arguments: BTreeMap::new(),
+            query_rules: Vec::new(),
+        }
+    }
+}
+
+/// Everything `diagnose` found out about one tool
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolDiagnosis {
+    /// No step failed
+    pub success: bool,
+    pub tool: String,
+    pub endpoint: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// The tool's `inputSchema` as listed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schema: Option<Value>,
+    pub steps: Vec<DiagnosisStep>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latency: Option<ToolRepeatStats>,
+    pub duration_ms: u64,
+}
+
+impl ToolDiagnosis {
+    fn failed(&self, stage: DiagnosisStage) -> bool {
+        self.steps
+            .iter()
+            .any(|step| step.stage == stage && step.status == CheckStatus::Fail)
+    }
+
+    /// Steps with a fix, failures before warnings, then in [`DiagnosisStage`] order
+    #[must_use]
+    pub fn fixes(&self) -> Vec<&DiagnosisStep> {
+        let mut fixes: Vec<&DiagnosisStep> =
+            self.steps.iter().filter(|s| s.fix.is_some()).collect();
+        fixes.sort_by_key(|s| (s.status, s.stage));
+        fixes
+    }
+
+    /// How the diagnosis ended, from CI's point of view; warnings don't fail
+    #[must_use]
+    pub fn outcome(&self) -> RunOutcome {
+        if self.success {
+            RunOutcome::Success
+        } else if self.failed(DiagnosisStage::Connectivity) {
+            RunOutcome::ConnectivityFailure
+        } else if self.failed(DiagnosisStage::Auth) {
+            RunOutcome::AuthFailure
+        } else if self.failed(DiagnosisStage::Discovery) {
+            RunOutcome::ValidationFailure
+        } else {
+            RunOutcome::PartialFailure
+        }
+    }
+
+    #[must_use]
+    pub fn format_text(&self) -> String {
+        let mut output = String::new();
+        let _ = writeln!(output, "🔬 Diagnosis of {} ({})", self.tool, self.endpoint);
+        let mut stages: Vec<DiagnosisStage> = self.steps.iter().map(|s| s.stage).collect();
+        stages.dedup();
+        for stage in stages {
+            let _ = writeln!(output, "  {}", stage.as_str());
+            for step in self.steps.iter().filter(|s| s.stage == stage) {
+                let status = match step.status {
+                    CheckStatus::Fail => "❌",
+                    CheckStatus::Warn => "⚠️",
+                    CheckStatus::Pass => "✅",
+                    CheckStatus::Skip => "➖",
+                };
+                let duration = step
+                    .duration_ms
+                    .map_or_else(String::new, |ms| format!(" ({ms}ms)"));
+                let _ = writeln!(
+                    output,
+                    "    {status} {}: {}{duration}",
+                    step.name, step.detail
+                );
+            }
+            if stage == DiagnosisStage::Schema
+                && let Some(schema) = &self.schema
+            {
+                let pretty = serde_json::to_string_pretty(schema).unwrap_or_default();
+                for line in pretty.lines() {
+                    let _ = writeln!(output, "       {line}");
+                }
+            }
+        }
+
+        let fixes = self.fixes();
+        if !fixes.is_empty() {
+            let _ = writeln!(output, "\n🔧 Fix-it list (most important first):");
+            for (index, step) in fixes.iter().enumerate() {
+                let level = if step.status == CheckStatus::Fail {
+                    "error"
+                } else {
+                    "warning"
+                };
+                let _ = writeln!(
+                    output,
+                    "  {}. [{level}] {}: {}",
+                    index + 1,
+                    step.name,
+                    step.fix.as_deref().unwrap_or_default()
+                );
+            }
+        }
+        output
+    }
+}
+
+/// e.g. `3 parameter(s), required: query`
+fn describe_schema(schema: &Value) -> String {
+    let properties = schema
+        .get("properties")
+        .and_then(Value::as_object)
+        .map_or(0, serde_json::Map::len);
+    let required: Vec<&str> = schema
+        .get("required")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str)
+        .collect();
+    format!(
+        "{properties} parameter(s), required: {}",
+        if required.is_empty() {
+            "none".to_string()
+        } else {
+            required.join(", ")
+        }
+    )
+}
+
+/// Diagnose `tool` on `inspector`'s default endpoint
+///
+/// Stops after connectivity or authentication fails, or when the tool isn't
+/// listed, since every later stage would fail the same way.
+#[allow(clippy::too_many_lines)]
+pub async fn run_diagnosis(
+    inspector: &GleanMCPInspector,
+    tool: &str,
+    options: &DiagnoseOptions,
+) -> ToolDiagnosis {
+    let started = Instant::now();
+    let url = inspector.server_url().to_string();
+    let mut diagnosis = ToolDiagnosis {
+        success: false,
+        tool: tool.to_string(),
+        endpoint: url.clone(),
+        description: None,
+        schema: None,
+        steps: Vec::new(),
+        latency: None,
+        duration_ms: 0,
+    };
+    let finish = |mut diagnosis: ToolDiagnosis| {
+        diagnosis.success = !diagnosis
+            .steps
+            .iter()
+            .any(|step| step.status == CheckStatus::Fail);
+        diagnosis.duration_ms = u64::try_from(started.elapsed().as_millis()).unwrap_or(u64::MAX);
+        diagnosis
+    };
+
+    for check in check_server(inspector, &url, options.timeout).await {
+        let stage = if check.area == DoctorArea::Network {
+            DiagnosisStage::Connectivity
+        } else {
+            DiagnosisStage::Auth
+        };
+        let mut step = DiagnosisStep::new(stage, check.name, check.status, check.detail);
+        step.fix = check.fix;
+        diagnosis.steps.push(step);
+    }
+    if diagnosis
+        .steps
+        .iter()
+        .any(|step| step.status == CheckStatus::Fail)
+    {
+        return finish(diagnosis);
+    }
+
+    let discovery = |status, detail: String| {
+        DiagnosisStep::new(DiagnosisStage::Discovery, "tools/list", status, detail)
+    };
+    let listed = match async_timeout(options.timeout, inspector.list_available_tools(false)).await {
+        Ok(result) => result
+            .inspector_data
+            .as_ref()
+            .map(ToolInfo::from_tools_list)
+            .unwrap_or_default(),
+        Err(e) => {
+            diagnosis
+                .steps
+                .push(discovery(CheckStatus::Fail, format!("failed: {e}")));
+            return finish(diagnosis);
+        }
+    };
+    let Some(info) = listed.iter().find(|listed| listed.name == tool) else {
+        let similar: Vec<&str> = listed
+            .iter()
+            .map(|listed| listed.name.as_str())
+            .filter(|name| name.contains(tool) || tool.contains(name))
+            .collect();
+        let suggested: Vec<&str> = if similar.is_empty() {
+            listed
+                .iter()
+                .map(|listed| listed.name.as_str())
+                .take(MAX_SUGGESTED)
+                .collect()
+        } else {
+            similar
+        };
+        let fix = if suggested.is_empty() {
+            "The server lists no tools; check the endpoint URL".to_string()
+        } else {
+            format!(
+                "Check the tool name; listed tools include {}",
+                suggested.join(", ")
+            )
+        };
+        diagnosis.steps.push(
+            discovery(
+                CheckStatus::Fail,
+                format!("{tool} is not among the {} listed tool(s)", listed.len()),
+            )
+            .with_fix(fix),
+        );
+        return finish(diagnosis);
+    };
+    diagnosis.steps.push(discovery(
+        CheckStatus::Pass,
+        format!("{tool} is listed among {} tool(s)", listed.len()),
+    ));
+    diagnosis.description.clone_from(&info.description);
+
+    let schema_step = |status, detail: String| {
+        DiagnosisStep::new(DiagnosisStage::Schema, "inputSchema", status, detail)
+    };
+    diagnosis.steps.push(info.schema.as_ref().map_or_else(
+        || {
+            schema_step(
+                CheckStatus::Warn,
+                "not listed; arguments are guessed from the tool name".to_string(),
+            )
+            .with_fix(format!("Publish an inputSchema for {tool} in tools/list"))
+        },
+        |schema| schema_step(CheckStatus::Pass, describe_schema(schema)),
+    ));
+    diagnosis.schema.clone_from(&info.schema);
+
+    let test_options = TestAllOptions {
+        tool_arguments: options.tool_arguments.clone(),
+        query_rules: options.query_rules.clone(),
+        ..TestAllOptions::default()
+    };
+    let (standard_arguments, standard) = test_options.arguments_for(info);
+    let configured = options.tool_arguments.contains_key(tool);
+    for level in QueryLevel::ALL {
+        let name = format!("{} query", level.as_str());
+        let query = if configured && level != QueryLevel::Standard {
+            None
+        } else {
+            level.query(&standard)
+        };
+        let Some(query) = query else {
+            diagnosis.steps.push(DiagnosisStep::new(
+                DiagnosisStage::Queries,
+                name,
+                CheckStatus::Skip,
+                if configured {
+                    "arguments are configured"
+                } else {
+                    "the standard query can't be varied"
+                },
+            ));
+            continue;
+        };
+        let (arguments, _) = test_options.arguments_for_query(info, Some(&query));
+        let call_start = Instant::now();
+        let response = async_timeout(
+            options.timeout,
+            GleanMCPInspector::test_tool_direct(
+                inspector.transport(),
+                &url,
+                tool,
+                &arguments,
+                &mut RequestTrace::default(),
+            ),
+        )
+        .await;
+        let elapsed = u64::try_from(call_start.elapsed().as_millis()).unwrap_or(u64::MAX);
+        let step = |status, detail: String| {
+            DiagnosisStep::new(DiagnosisStage::Queries, name.clone(), status, detail)
+                .with_duration(elapsed)
+        };
+        let reproduce = format!(
+            "Reproduce with `server test-tool --tool {tool} --args '{arguments}'` and check the server logs"
+        );
+        diagnosis.steps.push(match response {
+            Ok(result) if result.get("isError").and_then(Value::as_bool) == Some(true) => step(
+                CheckStatus::Fail,
+                format!(
+                    "{query:?} returned a tool error: {}",
+                    GleanMCPInspector::truncate_error_message(&response_text(&result))
+                ),
+            )
+            .with_fix(reproduce),
+            Ok(result) => {
+                let chars = response_text(&result).chars().count();
+                if chars == 0 {
+                    step(CheckStatus::Warn, format!("{query:?} returned no text"))
+                        .with_fix(format!("Check that {tool}'s connector has indexed content"))
+                } else {
+                    step(
+                        CheckStatus::Pass,
+                        format!("{query:?} returned {chars} characters of text"),
+                    )
+                }
+            }
+            Err(e) => step(
+                CheckStatus::Fail,
+                format!(
+                    "{query:?} failed: {}",
+                    GleanMCPInspector::truncate_error_message(&e.to_string())
+                ),
+            )
+            .with_fix(reproduce),
+        });
+    }
+
+    if options.samples > 0 {
+        let report = inspector
+            .repeat_test_tool(
+                tool,
+                &standard_arguments,
+                options.timeout,
+                options.samples,
+                false,
+            )
+            .await;
+        if let Some(stats) = report.tools.into_iter().next() {
+            let status = if stats.passed == stats.runs {
+                CheckStatus::Pass
+            } else if stats.passed > 0 {
+                CheckStatus::Warn
+            } else {
+                CheckStatus::Fail
+            };
+            let mut detail = format!("{}/{} calls passed", stats.passed, stats.runs);
+            if stats.passed > 0 {
+                let _ = write!(
+                    detail,
+                    ", p50 {}ms, p95 {}ms (min {}ms, max {}ms)",
+                    stats.latency.p50, stats.latency.p95, stats.latency.min, stats.latency.max
+                );
+            }
+            if stats.passed > 1 {
+                let _ = write!(detail, "  {}", sparkline(&stats.histogram));
+            }
+            if let Some((error, count)) = stats.errors.iter().max_by_key(|(_, count)| **count) {
+                let _ = write!(detail, "; most common error (×{count}): {error}");
+            }
+            let mut step = DiagnosisStep::new(DiagnosisStage::Latency, "samples", status, detail);
+            match status {
+                CheckStatus::Warn => {
+                    step = step.with_fix(format!(
+                        "{tool} is flaky; run `server test-tool --tool {tool} --repeat 20` to see how often it fails"
+                    ));
+                }
+                CheckStatus::Fail => {
+                    step = step.with_fix(format!(
+                        "Every call failed; reproduce with `server test-tool --tool {tool}` and check the server logs"
+                    ));
+                }
+                _ => {}
+            }
+            diagnosis.steps.push(step);
+            diagnosis.latency = Some(stats);
+        }
+    }
+
+    if !options.skip_negative {
+        let parameter = info
+            .schema
+            .as_ref()
+            .and_then(string_parameter)
+            .unwrap_or_else(|| "query".to_string());
+        for case in NegativeCase::ALL
+            .into_iter()
+            .filter(|case| *case != NegativeCase::UnknownTool)
+        {
+            let result = inspector.run_case(case, tool, &parameter).await;
+            let http = result.http_status.map_or_else(
+                || "no response".to_string(),
+                |status| format!("HTTP {status}"),
+            );
+            let code = result.error_code.map_or_else(
+                || "no error code".to_string(),
+                |code| format!("code {code}"),
+            );
+            // Wrong error codes are sloppy; a 5xx or no answer at all is broken
+            let broken = result.http_status.is_none_or(|status| status >= 500);
+            let (status, detail) = if result.passed {
+                (CheckStatus::Pass, format!("{http}, {code}"))
+            } else {
+                (
+                    if broken {
+                        CheckStatus::Fail
+                    } else {
+                        CheckStatus::Warn
+                    },
+                    format!("{http}, {code}: {}", result.problems.join("; ")),
+                )
+            };
+            let mut step =
+                DiagnosisStep::new(DiagnosisStage::NegativeInput, case.as_str(), status, detail)
+                    .with_duration(result.response_time_ms);
+            if !result.passed {
+                step = step.with_fix(format!(
+                    "Have {tool} reject invalid arguments with a JSON-RPC error (code {})",
+                    case.expected_codes()
+                        .iter()
+                        .map(ToString::to_string)
+                        .collect::<Vec<_>>()
+                        .join(" or ")
+                ));
+            }
+            diagnosis.steps.push(step);
+        }
+    }
+
+    finish(diagnosis)
+}
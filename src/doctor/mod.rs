@@ -0,0 +1,446 @@
+//! Host environment diagnostics
+//!
+//! [`run_doctor`] goes further than `prerequisites`: it checks the host CLIs
+//! and their versions, reads each configured host's MCP config to confirm a
+//! Glean entry points at the expected server, sends `tools/list` to every
+//! configured server URL to check it is reachable and accepts the token, and
+//! turns what it found into a fix-it list, most important first.
+
+use crate::host_controllers::claude_code::ClaudeCodeController;
+use crate::{
+    AuthStrategy, GleanConfig, GleanMCPInspector, HostConfigFile, HostController, RunOutcome,
+    async_timeout, auth_token_variables, find_executable, primary_token_variable,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::fmt::Write;
+use std::time::Duration;
+
+/// Programs checked, what needs them, and whether every run does
+const BINARIES: [(&str, &str, bool); 5] = [
+    ("curl", "all MCP requests", true),
+    ("npx", "the MCP Inspector and the mcp-remote bridge", false),
+    ("claude", "Claude Code host tests", false),
+    ("cursor", "Cursor host tests", false),
+    ("code", "VS Code host tests", false),
+];
+
+/// What a check looked at, in the order problems should be fixed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DoctorArea {
+    Binary,
+    Network,
+    Auth,
+    Host,
+}
+
+impl DoctorArea {
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Binary => "Binaries",
+            Self::Network => "Network",
+            Self::Auth => "Authentication",
+            Self::Host => "Host configs",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStatus {
+    /// Something the framework needs is broken
+    Fail,
+    /// Works, but probably not as intended
+    Warn,
+    Pass,
+    /// Not applicable here, e.g. a host that isn't installed
+    Skip,
+}
+
+/// One diagnostic and, when something is wrong, how to fix it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DoctorCheck {
+    pub area: DoctorArea,
+    /// What was checked, e.g. `curl` or `cursor`
+    pub name: String,
+    pub status: CheckStatus,
+    pub detail: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fix: Option<String>,
+}
+
+impl DoctorCheck {
+    fn new(area: DoctorArea, name: &str, status: CheckStatus, detail: impl Into<String>) -> Self {
+        Self {
+            area,
+            name: name.to_string(),
+            status,
+            detail: detail.into(),
+            fix: None,
+        }
+    }
+
+    fn with_fix(mut self, fix: impl Into<String>) -> Self {
+        self.fix = Some(fix.into());
+        self
+    }
+}
+
+/// Everything `doctor` checked
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DoctorReport {
+    pub success: bool,
+    pub checks: Vec<DoctorCheck>,
+}
+
+impl DoctorReport {
+    fn new(checks: Vec<DoctorCheck>) -> Self {
+        Self {
+            success: !checks.iter().any(|c| c.status == CheckStatus::Fail),
+            checks,
+        }
+    }
+
+    /// Checks with a fix, failures before warnings, then in [`DoctorArea`] order
+    #[must_use]
+    pub fn fixes(&self) -> Vec<&DoctorCheck> {
+        let mut fixes: Vec<&DoctorCheck> = self.checks.iter().filter(|c| c.fix.is_some()).collect();
+        fixes.sort_by_key(|c| (c.status, c.area));
+        fixes
+    }
+
+    /// How the diagnostics ended, from CI's point of view; warnings don't fail
+    #[must_use]
+    pub fn outcome(&self) -> RunOutcome {
+        let failed = |area| {
+            self.checks
+                .iter()
+                .any(|c| c.area == area && c.status == CheckStatus::Fail)
+        };
+        if self.success {
+            RunOutcome::Success
+        } else if failed(DoctorArea::Network) {
+            RunOutcome::ConnectivityFailure
+        } else if failed(DoctorArea::Auth) {
+            RunOutcome::AuthFailure
+        } else {
+            RunOutcome::Error
+        }
+    }
+
+    #[must_use]
+    pub fn format_text(&self) -> String {
+        let mut output = String::new();
+        let _ = writeln!(output, "🩺 Environment diagnostics");
+        let areas: BTreeSet<DoctorArea> = self.checks.iter().map(|c| c.area).collect();
+        for area in areas {
+            let _ = writeln!(output, "  {}", area.as_str());
+            for check in self.checks.iter().filter(|c| c.area == area) {
+                let status = match check.status {
+                    CheckStatus::Fail => "❌",
+                    CheckStatus::Warn => "⚠️",
+                    CheckStatus::Pass => "✅",
+                    CheckStatus::Skip => "➖",
+                };
+                let _ = writeln!(output, "    {status} {}: {}", check.name, check.detail);
+            }
+        }
+
+        let fixes = self.fixes();
+        if !fixes.is_empty() {
+            let _ = writeln!(output, "\n🔧 Fix-it list (most important first):");
+            for (index, check) in fixes.iter().enumerate() {
+                let level = if check.status == CheckStatus::Fail {
+                    "error"
+                } else {
+                    "warning"
+                };
+                let _ = writeln!(
+                    output,
+                    "  {}. [{level}] {}: {}",
+                    index + 1,
+                    check.name,
+                    check.fix.as_deref().unwrap_or_default()
+                );
+            }
+        }
+        output
+    }
+}
+
+/// Check the binaries, host configs, and servers `config` and `inspector` point at
+///
+/// Every server URL is probed once, however many hosts share it.
+pub async fn run_doctor(
+    config: &GleanConfig,
+    inspector: &GleanMCPInspector,
+    timeout: Duration,
+) -> DoctorReport {
+    let mut checks: Vec<DoctorCheck> = BINARIES
+        .iter()
+        .map(|(program, needed_for, required)| check_binary(program, needed_for, *required))
+        .collect();
+
+    let mut hosts: Vec<(&String, &crate::HostConfig)> = config.host_applications.iter().collect();
+    hosts.sort_by_key(|(name, _)| *name);
+    let mut urls = vec![inspector.server_url().to_string()];
+    for (key, host) in &hosts {
+        let name = key.replace('_', "-");
+        checks.push(check_host(&name, host, config).await);
+        if !urls.contains(&host.server_url) {
+            urls.push(host.server_url.clone());
+        }
+    }
+
+    for url in &urls {
+        checks.extend(check_server(inspector, url, timeout).await);
+    }
+    DoctorReport::new(checks)
+}
+
+fn check_binary(program: &str, needed_for: &str, required: bool) -> DoctorCheck {
+    let Some(path) = find_executable(program) else {
+        let (status, detail) = if required {
+            (
+                CheckStatus::Fail,
+                format!("not found; needed for {needed_for}"),
+            )
+        } else {
+            (
+                CheckStatus::Skip,
+                format!("not found; only needed for {needed_for}"),
+            )
+        };
+        let check = DoctorCheck::new(DoctorArea::Binary, program, status, detail);
+        return if required {
+            check.with_fix(format!("Install {program} and make sure it is on PATH"))
+        } else {
+            check
+        };
+    };
+
+    match crate::command(&path).arg("--version").output() {
+        Ok(output) if output.status.success() => {
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            let version = stdout
+                .lines()
+                .map(str::trim)
+                .find(|line| !line.is_empty())
+                // `curl 8.5.0 (x86_64-pc-linux-gnu) libcurl/8.5.0 ...` is shown as `curl 8.5.0`
+                .map_or("version unknown", |line| {
+                    line.split(" (").next().unwrap_or(line)
+                });
+            DoctorCheck::new(
+                DoctorArea::Binary,
+                program,
+                CheckStatus::Pass,
+                format!("{version} ({})", path.display()),
+            )
+        }
+        _ => DoctorCheck::new(
+            DoctorArea::Binary,
+            program,
+            CheckStatus::Warn,
+            format!("{} --version failed", path.display()),
+        )
+        .with_fix(format!(
+            "Reinstall {program}; `{program} --version` should succeed"
+        )),
+    }
+}
+
+/// Whether a config entry connects to `server_url`, directly or through `mcp-remote`
+fn entry_targets(entry: &Value, server_url: &str) -> bool {
+    let url = entry
+        .get("url")
+        .or_else(|| entry.get("serverUrl"))
+        .and_then(Value::as_str);
+    let in_args = entry
+        .get("args")
+        .and_then(Value::as_array)
+        .is_some_and(|args| args.iter().any(|arg| arg.as_str() == Some(server_url)));
+    url == Some(server_url) || in_args
+}
+
+/// Whether a config entry looks like some Glean server, whatever its URL
+fn entry_is_glean(name: &str, entry: &Value) -> bool {
+    name.to_lowercase().contains("glean") || entry.to_string().contains("glean.com")
+}
+
+async fn check_host(name: &str, host: &crate::HostConfig, config: &GleanConfig) -> DoctorCheck {
+    let check = |status, detail: String| DoctorCheck::new(DoctorArea::Host, name, status, detail);
+    let configure = format!("glean-mcp-test host configure -H {name}");
+
+    if host.mcp_config_path.is_none() {
+        if name != "claude-code" {
+            return check(
+                CheckStatus::Skip,
+                "configured through its own CLI; run `host verify` to check it".to_string(),
+            );
+        }
+        if find_executable("claude").is_none() {
+            return check(CheckStatus::Skip, "claude not installed".to_string());
+        }
+        return match ClaudeCodeController::new().list_mcp_servers().await {
+            Ok(result) if result.details.contains(&host.server_url) => check(
+                CheckStatus::Pass,
+                format!("`claude mcp list` includes {}", host.server_url),
+            ),
+            Ok(result) if result.details.to_lowercase().contains("glean") => check(
+                CheckStatus::Warn,
+                format!("has a Glean server, but not {}", host.server_url),
+            )
+            .with_fix(format!(
+                "Point the Glean server at {}: claude mcp add --transport http glean {}",
+                host.server_url, host.server_url
+            )),
+            Ok(_) => check(CheckStatus::Warn, "no Glean MCP server".to_string()).with_fix(format!(
+                "claude mcp add --transport http glean {}",
+                host.server_url
+            )),
+            Err(e) => check(CheckStatus::Warn, format!("`claude mcp list` failed: {e}"))
+                .with_fix("Run `claude mcp list` and fix the error it reports"),
+        };
+    }
+
+    let file = match HostConfigFile::for_host(name, config) {
+        Ok(file) => file,
+        Err(e) => return check(CheckStatus::Fail, e.to_string()),
+    };
+    match file.servers() {
+        Ok(None) => check(
+            CheckStatus::Skip,
+            format!("no config file at {}", file.path.display()),
+        ),
+        Err(e) => check(CheckStatus::Fail, e.to_string())
+            .with_fix(format!("Fix or remove {}", file.path.display())),
+        Ok(Some(servers)) => {
+            if let Some((server, entry)) = servers
+                .iter()
+                .find(|(_, entry)| entry_targets(entry, &host.server_url))
+            {
+                return match AuthStrategy::of_entry(entry) {
+                    Some(strategy) if strategy != file.auth => check(
+                        CheckStatus::Warn,
+                        format!(
+                            "'{server}' uses {} auth, but auth_method is {}",
+                            strategy.as_str(),
+                            file.auth.as_str()
+                        ),
+                    )
+                    .with_fix(format!("{configure} --server-name {server}")),
+                    _ => check(
+                        CheckStatus::Pass,
+                        format!(
+                            "'{server}' points at {} ({} auth)",
+                            host.server_url,
+                            file.auth.as_str()
+                        ),
+                    ),
+                };
+            }
+            match servers
+                .iter()
+                .find(|(server, entry)| entry_is_glean(server, entry))
+            {
+                Some((server, _)) => check(
+                    CheckStatus::Warn,
+                    format!(
+                        "'{server}' in {} doesn't point at {}",
+                        file.path.display(),
+                        host.server_url
+                    ),
+                )
+                .with_fix(format!("{configure} --server-name {server}")),
+                None => check(
+                    CheckStatus::Warn,
+                    format!("no Glean entry in {}", file.path.display()),
+                )
+                .with_fix(configure),
+            }
+        }
+    }
+}
+
+/// Reachability and token checks for one server URL
+pub(crate) async fn check_server(
+    inspector: &GleanMCPInspector,
+    url: &str,
+    timeout: Duration,
+) -> Vec<DoctorCheck> {
+    let request = serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "tools/list",
+        "params": {}
+    });
+    let response = async_timeout(timeout, inspector.transport().post_json_rpc(url, &request)).await;
+    let network =
+        |status, detail: String| DoctorCheck::new(DoctorArea::Network, url, status, detail);
+    let auth = |status, detail: String| DoctorCheck::new(DoctorArea::Auth, url, status, detail);
+
+    let network_fix =
+        "Check the host name, VPN, and proxy settings (`--proxy`, `proxy` in the config)";
+    let response = match response {
+        Ok(response) if response.success => response,
+        Ok(response) => {
+            let detail = format!("unreachable: {}", response.stderr.trim());
+            return vec![network(CheckStatus::Fail, detail).with_fix(network_fix)];
+        }
+        Err(e) => return vec![network(CheckStatus::Fail, e.to_string()).with_fix(network_fix)],
+    };
+
+    let status = response.status.unwrap_or_default();
+    let mut checks = vec![network(
+        CheckStatus::Pass,
+        format!("reachable (HTTP {status})"),
+    )];
+    let variables = auth_token_variables(inspector.instance());
+    let token_fix = format!(
+        "Set a valid token: export {}=<token> (see `glean-mcp-test auth`)",
+        primary_token_variable()
+    );
+    checks.push(match status {
+        401 | 403 if !inspector.transport().has_auth_token() => auth(
+            CheckStatus::Fail,
+            format!("HTTP {status} and none of {} is set", variables.join(", ")),
+        )
+        .with_fix(token_fix),
+        401 | 403 => {
+            auth(CheckStatus::Fail, format!("token rejected (HTTP {status})")).with_fix(token_fix)
+        }
+        200..=299 => {
+            let tools = serde_json::from_str::<Value>(&response.body)
+                .ok()
+                .and_then(|body| {
+                    body.pointer("/result/tools")
+                        .and_then(Value::as_array)
+                        .map(Vec::len)
+                });
+            tools.map_or_else(
+                || {
+                    auth(
+                        CheckStatus::Warn,
+                        "token accepted, but the response has no tool list".to_string(),
+                    )
+                    .with_fix(format!("Check that {url} is an MCP endpoint"))
+                },
+                |count| {
+                    auth(
+                        CheckStatus::Pass,
+                        format!("token accepted; {count} tool(s) listed"),
+                    )
+                },
+            )
+        }
+        _ => auth(
+            CheckStatus::Warn,
+            format!("token not verified: server answered HTTP {status}"),
+        )
+        .with_fix(format!("Retry later or check the server status of {url}")),
+    });
+    checks
+}
@@ -0,0 +1,275 @@
+//! Run history persistence
+//!
+//! Each completed run is written as one JSON document in the history
+//! directory, named so that lexical order matches chronological order.
+//! Runs can also be kept in a `SQLite` database (see [`SqliteHistoryStore`]),
+//! which scales better for per-tool trend queries (see [`trends`]).
+
+pub mod sqlite;
+pub mod stats;
+pub mod trends;
+
+pub use sqlite::*;
+pub use stats::*;
+pub use trends::*;
+
+use crate::{AllToolsTestResult, GleanMcpError, HostOperationResult, Result};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Parse a `key=value` run label
+pub fn parse_label(raw: &str) -> Result<(String, String)> {
+    match raw.split_once('=') {
+        Some((key, value)) if !key.trim().is_empty() => {
+            Ok((key.trim().to_string(), value.trim().to_string()))
+        }
+        _ => Err(GleanMcpError::Config(format!(
+            "Invalid label '{raw}' (expected key=value)"
+        ))),
+    }
+}
+
+/// A single recorded test run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunRecord {
+    pub id: String,
+    pub started_at: String,
+    pub finished_at: String,
+    pub instance: String,
+    /// Suite that was executed (`core`, `all`, `hosts`, a tool list, ...)
+    pub suite: String,
+    /// Monitor schedule that triggered the run, if any
+    #[serde(default)]
+    pub schedule: Option<String>,
+    pub success: bool,
+    #[serde(default)]
+    pub tool_result: Option<AllToolsTestResult>,
+    #[serde(default)]
+    pub host_results: Vec<HostOperationResult>,
+    /// Free-form labels (`release=4.2`, `trigger=deploy`, ...)
+    #[serde(default)]
+    pub labels: BTreeMap<String, String>,
+}
+
+impl RunRecord {
+    /// Generate a sortable, unique run id (`20250101T120000123Z-1a2b3c`)
+    #[must_use]
+    pub fn generate_id() -> String {
+        let suffix: u32 = rand::thread_rng().gen_range(0..0x0100_0000);
+        format!(
+            "{}-{suffix:06x}",
+            chrono::Utc::now().format("%Y%m%dT%H%M%S%3fZ")
+        )
+    }
+
+    /// Build a record for a completed `test` run, keeping the run id its requests were tagged with
+    #[must_use]
+    pub fn for_tool_run(instance: &str, suite: &str, result: &AllToolsTestResult) -> Self {
+        Self {
+            id: result.run_id.clone().unwrap_or_else(Self::generate_id),
+            started_at: result.execution_summary.start_time.clone(),
+            finished_at: result.execution_summary.end_time.clone(),
+            instance: instance.to_string(),
+            suite: suite.to_string(),
+            schedule: None,
+            success: result.success,
+            tool_result: Some(result.clone()),
+            host_results: Vec::new(),
+            labels: result.labels.clone(),
+        }
+    }
+
+    /// Each tool's result in this run; empty for host runs
+    #[must_use]
+    pub fn tool_outcomes(&self) -> Vec<ToolOutcome> {
+        let mut outcomes: Vec<ToolOutcome> = self
+            .tool_result
+            .iter()
+            .flat_map(|result| result.tool_results.values())
+            .filter(|result| !result.is_skipped())
+            .map(|result| ToolOutcome {
+                run_id: self.id.clone(),
+                started_at: self.started_at.clone(),
+                tool: result.tool_name.clone(),
+                success: result.success,
+                degraded: result.degraded,
+                response_time_ms: result.response_time_ms,
+                error: result.error_message.clone(),
+            })
+            .collect();
+        outcomes.sort_by(|a, b| a.tool.cmp(&b.tool));
+        outcomes
+    }
+}
+
+/// Criteria for selecting runs from history
+#[derive(Debug, Clone, Default)]
+pub struct RunFilter {
+    /// Every label must be present with the given value
+    pub labels: Vec<(String, String)>,
+    pub schedule: Option<String>,
+    pub instance: Option<String>,
+}
+
+impl RunFilter {
+    #[must_use]
+    pub fn matches(&self, record: &RunRecord) -> bool {
+        self.labels
+            .iter()
+            .all(|(key, value)| record.labels.get(key) == Some(value))
+            && self
+                .schedule
+                .as_ref()
+                .is_none_or(|schedule| record.schedule.as_ref() == Some(schedule))
+            && self
+                .instance
+                .as_ref()
+                .is_none_or(|instance| &record.instance == instance)
+    }
+}
+
+/// Directory-backed store of [`RunRecord`]s
+#[derive(Debug, Clone)]
+pub struct HistoryStore {
+    dir: PathBuf,
+}
+
+impl HistoryStore {
+    #[must_use]
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    #[must_use]
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Persist a run record, returning the path written
+    pub fn record(&self, record: &RunRecord) -> Result<PathBuf> {
+        std::fs::create_dir_all(&self.dir)?;
+        let path = self.dir.join(format!("{}.json", record.id));
+        let json = serde_json::to_string_pretty(record)?;
+        std::fs::write(&path, json)?;
+        Ok(path)
+    }
+
+    /// Load all run records, oldest first; unreadable files are skipped
+    pub fn load_all(&self) -> Result<Vec<RunRecord>> {
+        if !self.dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut paths: Vec<PathBuf> = std::fs::read_dir(&self.dir)?
+            .filter_map(std::result::Result::ok)
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .collect();
+        paths.sort();
+
+        Ok(paths
+            .iter()
+            .filter_map(|path| std::fs::read_to_string(path).ok())
+            .filter_map(|contents| serde_json::from_str(&contents).ok())
+            .collect())
+    }
+
+    /// Load run records matching `filter`, oldest first
+    pub fn query(&self, filter: &RunFilter) -> Result<Vec<RunRecord>> {
+        Ok(self
+            .load_all()?
+            .into_iter()
+            .filter(|record| filter.matches(record))
+            .collect())
+    }
+
+    /// Load a single run record by id
+    pub fn load(&self, id: &str) -> Result<RunRecord> {
+        if id.is_empty() || id.contains(['/', '\\']) || id.contains("..") {
+            return Err(GleanMcpError::Config(format!("Invalid run id '{id}'")));
+        }
+        let path = self.dir.join(format!("{id}.json"));
+        let contents = std::fs::read_to_string(&path)
+            .map_err(|_| GleanMcpError::Config(format!("Run '{id}' not found in history")))?;
+        Ok(serde_json::from_str(&contents)?)
+    }
+}
+
+impl HistoryStore {
+    /// Per-tool outcomes of the newest `last_runs` runs matching `filter`, oldest run first
+    pub fn tool_outcomes(&self, filter: &RunFilter, last_runs: usize) -> Result<Vec<ToolOutcome>> {
+        let runs = self.query(filter)?;
+        let skip = runs.len().saturating_sub(last_runs);
+        Ok(runs
+            .iter()
+            .skip(skip)
+            .flat_map(RunRecord::tool_outcomes)
+            .collect())
+    }
+}
+
+/// Run history in either a directory of JSON files or a `SQLite` database
+#[derive(Debug, Clone)]
+pub enum AnyHistoryStore {
+    Files(HistoryStore),
+    Sqlite(SqliteHistoryStore),
+}
+
+impl AnyHistoryStore {
+    /// The `SQLite` database at `db` when given, else the JSON files in `dir`
+    #[must_use]
+    pub fn open(dir: &str, db: Option<&Path>) -> Self {
+        db.map_or_else(
+            || Self::Files(HistoryStore::new(dir)),
+            |db| Self::Sqlite(SqliteHistoryStore::new(db)),
+        )
+    }
+
+    pub fn query(&self, filter: &RunFilter) -> Result<Vec<RunRecord>> {
+        match self {
+            Self::Files(store) => store.query(filter),
+            Self::Sqlite(store) => store.query(filter),
+        }
+    }
+
+    pub fn load(&self, id: &str) -> Result<RunRecord> {
+        match self {
+            Self::Files(store) => store.load(id),
+            Self::Sqlite(store) => store.load(id),
+        }
+    }
+
+    pub fn tool_outcomes(&self, filter: &RunFilter, last_runs: usize) -> Result<Vec<ToolOutcome>> {
+        match self {
+            Self::Files(store) => store.tool_outcomes(filter, last_runs),
+            Self::Sqlite(store) => store.tool_outcomes(filter, last_runs),
+        }
+    }
+}
+
+/// Results whose latest outcome is a pass in a run started at or after `since`, with that run's id
+///
+/// `outcomes` are ordered oldest run first, as [`AnyHistoryStore::tool_outcomes`]
+/// returns them. Skipped results aren't outcomes, so a cached pass never
+/// extends the window.
+#[must_use]
+pub fn recently_passed(
+    outcomes: &[ToolOutcome],
+    since: chrono::DateTime<chrono::Utc>,
+) -> BTreeMap<String, String> {
+    let mut latest: BTreeMap<&str, &ToolOutcome> = BTreeMap::new();
+    for outcome in outcomes {
+        latest.insert(&outcome.tool, outcome);
+    }
+    latest
+        .into_iter()
+        .filter(|(_, outcome)| {
+            outcome.success
+                && chrono::DateTime::parse_from_rfc3339(&outcome.started_at)
+                    .is_ok_and(|started| started >= since)
+        })
+        .map(|(tool, outcome)| (tool.to_string(), outcome.run_id.clone()))
+        .collect()
+}
@@ -0,0 +1,298 @@
+//! Run history in a `SQLite` database
+//!
+//! [`SqliteHistoryStore`] keeps every [`RunRecord`] in a single database
+//! file: a `runs` row holding the full record as JSON, plus one
+//! `tool_results` row per tool, so per-tool trends can be queried without
+//! loading whole runs.
+
+use super::{RunFilter, RunRecord, ToolOutcome};
+use crate::{GleanMcpError, Result};
+use chrono::Utc;
+use rusqlite::{Connection, OptionalExtension, params, params_from_iter};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS runs (
+    id TEXT PRIMARY KEY,
+    started_at TEXT NOT NULL,
+    finished_at TEXT NOT NULL,
+    instance TEXT NOT NULL,
+    suite TEXT NOT NULL,
+    schedule TEXT,
+    success INTEGER NOT NULL,
+    labels TEXT NOT NULL,
+    record TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS runs_by_instance ON runs (instance, id);
+CREATE TABLE IF NOT EXISTS tool_results (
+    run_id TEXT NOT NULL REFERENCES runs (id) ON DELETE CASCADE,
+    tool_name TEXT NOT NULL,
+    success INTEGER NOT NULL,
+    degraded INTEGER NOT NULL,
+    response_time_ms INTEGER NOT NULL,
+    error_message TEXT,
+    PRIMARY KEY (run_id, tool_name)
+);
+";
+
+/// `SQLite` database of [`RunRecord`]s
+#[derive(Debug, Clone)]
+pub struct SqliteHistoryStore {
+    path: PathBuf,
+}
+
+impl SqliteHistoryStore {
+    #[must_use]
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Open the database, creating it and its tables if needed
+    fn connect(&self) -> Result<Connection> {
+        if let Some(parent) = self.path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+        let connection = Connection::open(&self.path)?;
+        connection.execute_batch(SCHEMA)?;
+        Ok(connection)
+    }
+
+    /// Persist a run record and its per-tool results, replacing any run with the same id
+    pub fn record(&self, record: &RunRecord) -> Result<()> {
+        let mut connection = self.connect()?;
+        let transaction = connection.transaction()?;
+        transaction.execute(
+            "INSERT OR REPLACE INTO runs
+                 (id, started_at, finished_at, instance, suite, schedule, success, labels, record)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                record.id,
+                record.started_at,
+                record.finished_at,
+                record.instance,
+                record.suite,
+                record.schedule,
+                record.success,
+                serde_json::to_string(&record.labels)?,
+                serde_json::to_string(record)?,
+            ],
+        )?;
+        transaction.execute(
+            "DELETE FROM tool_results WHERE run_id = ?1",
+            params![record.id],
+        )?;
+        for outcome in record.tool_outcomes() {
+            transaction.execute(
+                "INSERT INTO tool_results
+                     (run_id, tool_name, success, degraded, response_time_ms, error_message)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![
+                    outcome.run_id,
+                    outcome.tool,
+                    outcome.success,
+                    outcome.degraded,
+                    i64::try_from(outcome.response_time_ms).unwrap_or(i64::MAX),
+                    outcome.error,
+                ],
+            )?;
+        }
+        transaction.commit()?;
+        Ok(())
+    }
+
+    /// Load run records matching `filter`, oldest first
+    pub fn query(&self, filter: &RunFilter) -> Result<Vec<RunRecord>> {
+        let connection = self.connect()?;
+        let (conditions, values) = filter_sql(filter);
+        let mut statement = connection.prepare(&format!(
+            "SELECT record FROM runs WHERE {conditions} ORDER BY id"
+        ))?;
+        let records = statement
+            .query_map(params_from_iter(values), |row| row.get::<_, String>(0))?
+            .filter_map(std::result::Result::ok)
+            .filter_map(|json| serde_json::from_str(&json).ok())
+            .collect();
+        Ok(records)
+    }
+
+    /// Load a single run record by id
+    pub fn load(&self, id: &str) -> Result<RunRecord> {
+        let connection = self.connect()?;
+        let json: Option<String> = connection
+            .query_row(
+                "SELECT record FROM runs WHERE id = ?1",
+                params![id],
+                |row| row.get(0),
+            )
+            .optional()?;
+        let json =
+            json.ok_or_else(|| GleanMcpError::Config(format!("Run '{id}' not found in history")))?;
+        Ok(serde_json::from_str(&json)?)
+    }
+
+    /// Remove runs beyond the newest `keep_runs` or older than `keep_days`, except `protected` ones
+    ///
+    /// Returns the removed run ids, newest first; with `dry_run`, nothing is
+    /// deleted. A database that doesn't exist yet is left alone.
+    pub fn prune(
+        &self,
+        keep_runs: Option<usize>,
+        keep_days: Option<u64>,
+        protected: &HashSet<String>,
+        dry_run: bool,
+    ) -> Result<Vec<String>> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+        // Run ids start with their UTC start time, so they sort and compare by age
+        let cutoff = keep_days.map(|days| {
+            (Utc::now() - chrono::Duration::days(i64::try_from(days).unwrap_or(i64::MAX / 86_400)))
+                .format("%Y%m%dT%H%M%S%3fZ")
+                .to_string()
+        });
+
+        let mut connection = self.connect()?;
+        let ids: Vec<String> = connection
+            .prepare("SELECT id FROM runs ORDER BY id DESC")?
+            .query_map([], |row| row.get(0))?
+            .collect::<rusqlite::Result<_>>()?;
+        let removed: Vec<String> = ids
+            .into_iter()
+            .enumerate()
+            .filter(|(index, id)| {
+                let over_count = keep_runs.is_some_and(|keep| *index >= keep);
+                let too_old = cutoff.as_ref().is_some_and(|cutoff| id < cutoff);
+                (over_count || too_old) && !protected.contains(id)
+            })
+            .map(|(_, id)| id)
+            .collect();
+
+        if !dry_run && !removed.is_empty() {
+            let transaction = connection.transaction()?;
+            for id in &removed {
+                transaction.execute("DELETE FROM tool_results WHERE run_id = ?1", params![id])?;
+                transaction.execute("DELETE FROM runs WHERE id = ?1", params![id])?;
+            }
+            transaction.commit()?;
+        }
+        Ok(removed)
+    }
+
+    /// Per-tool outcomes of the newest `last_runs` runs matching `filter`, oldest run first
+    pub fn tool_outcomes(&self, filter: &RunFilter, last_runs: usize) -> Result<Vec<ToolOutcome>> {
+        let connection = self.connect()?;
+        let (conditions, values) = filter_sql(filter);
+        let last_runs = i64::try_from(last_runs).unwrap_or(i64::MAX);
+        let mut statement = connection.prepare(&format!(
+            "SELECT r.id, r.started_at, t.tool_name, t.success, t.degraded,
+                    t.response_time_ms, t.error_message
+             FROM (SELECT id, started_at FROM runs WHERE {conditions}
+                   ORDER BY id DESC LIMIT {last_runs}) r
+             JOIN tool_results t ON t.run_id = r.id
+             ORDER BY r.id, t.tool_name"
+        ))?;
+        let outcomes = statement
+            .query_map(params_from_iter(values), |row| {
+                Ok(ToolOutcome {
+                    run_id: row.get(0)?,
+                    started_at: row.get(1)?,
+                    tool: row.get(2)?,
+                    success: row.get(3)?,
+                    degraded: row.get(4)?,
+                    response_time_ms: u64::try_from(row.get::<_, i64>(5)?).unwrap_or(0),
+                    error: row.get(6)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(outcomes)
+    }
+}
+
+/// SQL condition selecting the runs `filter` matches, with its parameters
+fn filter_sql(filter: &RunFilter) -> (String, Vec<String>) {
+    let mut conditions = vec!["1 = 1".to_string()];
+    let mut values = Vec::new();
+    if let Some(instance) = &filter.instance {
+        values.push(instance.clone());
+        conditions.push(format!("instance = ?{}", values.len()));
+    }
+    if let Some(schedule) = &filter.schedule {
+        values.push(schedule.clone());
+        conditions.push(format!("schedule = ?{}", values.len()));
+    }
+    for (key, value) in &filter.labels {
+        values.push(key.clone());
+        values.push(value.clone());
+        conditions.push(format!(
+            "json_extract(labels, '$.' || json_quote(?{})) = ?{}",
+            values.len() - 1,
+            values.len()
+        ));
+    }
+    (conditions.join(" AND "), values)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(id: &str) -> RunRecord {
+        RunRecord {
+            id: id.to_string(),
+            started_at: String::new(),
+            finished_at: String::new(),
+            instance: "test".to_string(),
+            suite: "core".to_string(),
+            schedule: None,
+            success: true,
+            tool_result: None,
+            host_results: Vec::new(),
+            labels: std::collections::BTreeMap::new(),
+        }
+    }
+
+    fn ids(store: &SqliteHistoryStore) -> Vec<String> {
+        store
+            .query(&RunFilter::default())
+            .unwrap()
+            .into_iter()
+            .map(|record| record.id)
+            .collect()
+    }
+
+    #[test]
+    fn prune_removes_old_and_surplus_runs_except_protected_ones() {
+        let path =
+            std::env::temp_dir().join(format!("glean-mcp-test-{}-prune.db", std::process::id()));
+        let store = SqliteHistoryStore::new(&path);
+        let recent = RunRecord::generate_id();
+        for id in [
+            "20200101T000000000Z-000001",
+            "20200102T000000000Z-000002",
+            "20200103T000000000Z-000003",
+            recent.as_str(),
+        ] {
+            store.record(&run(id)).unwrap();
+        }
+        let protected: HashSet<String> = ["20200101T000000000Z-000001".to_string()].into();
+
+        let planned = store.prune(Some(2), None, &protected, true).unwrap();
+        assert_eq!(planned, ["20200102T000000000Z-000002"]);
+        assert_eq!(ids(&store).len(), 4, "dry run deletes nothing");
+
+        let removed = store.prune(None, Some(30), &protected, false).unwrap();
+        assert_eq!(
+            removed,
+            ["20200103T000000000Z-000003", "20200102T000000000Z-000002"]
+        );
+        assert_eq!(ids(&store), ["20200101T000000000Z-000001", recent.as_str()]);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
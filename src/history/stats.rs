@@ -0,0 +1,104 @@
+//! Per-tool availability and flakiness across recorded runs
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+/// One tool's result in one recorded run
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ToolOutcome {
+    pub run_id: String,
+    pub started_at: String,
+    pub tool: String,
+    pub success: bool,
+    pub degraded: bool,
+    pub response_time_ms: u64,
+    pub error: Option<String>,
+}
+
+/// How one tool behaved across a window of runs
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ToolStats {
+    pub tool: String,
+    /// Runs the tool was tested in
+    pub runs: usize,
+    pub passed: usize,
+    pub failed: usize,
+    pub degraded: usize,
+    /// Share of runs the tool passed, as a percentage
+    pub availability: f64,
+    /// Share of consecutive runs where the tool flipped between pass and fail, as a percentage
+    pub flakiness: f64,
+    pub mean_latency_ms: u64,
+    /// Most recent run the tool failed in
+    pub last_failure: Option<String>,
+}
+
+impl ToolStats {
+    /// Stats per tool, sorted by name, from outcomes ordered oldest run first
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn from_outcomes(outcomes: &[ToolOutcome]) -> Vec<Self> {
+        let mut by_tool: BTreeMap<&str, Vec<&ToolOutcome>> = BTreeMap::new();
+        for outcome in outcomes {
+            by_tool.entry(&outcome.tool).or_default().push(outcome);
+        }
+
+        by_tool
+            .into_iter()
+            .map(|(tool, outcomes)| {
+                let runs = outcomes.len();
+                let passed = outcomes.iter().filter(|o| o.success).count();
+                let flips = outcomes
+                    .windows(2)
+                    .filter(|pair| pair[0].success != pair[1].success)
+                    .count();
+                Self {
+                    tool: tool.to_string(),
+                    runs,
+                    passed,
+                    failed: runs - passed,
+                    degraded: outcomes.iter().filter(|o| o.degraded).count(),
+                    availability: passed as f64 * 100.0 / runs as f64,
+                    flakiness: if runs > 1 {
+                        flips as f64 * 100.0 / (runs - 1) as f64
+                    } else {
+                        0.0
+                    },
+                    mean_latency_ms: outcomes.iter().map(|o| o.response_time_ms).sum::<u64>()
+                        / runs as u64,
+                    last_failure: outcomes
+                        .iter()
+                        .rev()
+                        .find(|o| !o.success)
+                        .map(|o| o.run_id.clone()),
+                }
+            })
+            .collect()
+    }
+
+    /// Render `stats` as an aligned table
+    #[must_use]
+    pub fn format_table(stats: &[Self]) -> String {
+        let width = stats.iter().map(|s| s.tool.len()).max().unwrap_or(4).max(4);
+        let mut output = String::new();
+        let _ = writeln!(
+            output,
+            "{:<width$}  {:>4}  {:>12}  {:>9}  {:>8}  LAST FAILURE",
+            "TOOL", "RUNS", "AVAILABILITY", "FLAKINESS", "MEAN MS"
+        );
+        for s in stats {
+            let _ = writeln!(
+                output,
+                "{:<width$}  {:>4}  {:>11.1}%  {:>8.1}%  {:>8}  {}",
+                s.tool,
+                s.runs,
+                s.availability,
+                s.flakiness,
+                s.mean_latency_ms,
+                s.last_failure.as_deref().unwrap_or("-")
+            );
+        }
+        output
+    }
+}
@@ -0,0 +1,391 @@
+//! Per-tool pass-rate and latency trends over recorded history
+//!
+//! [`FlakinessTrend`] and [`LatencyTrend`] summarize each tool over a window
+//! of days with a daily series, and compare the most recent days against the
+//! rest of the window so tools whose behaviour changed stand out.
+
+use super::{ToolOutcome, ToolStats};
+use crate::LatencyStats;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+/// Window and significance thresholds for trend analysis
+#[derive(Debug, Clone)]
+pub struct TrendOptions {
+    /// Days of history to analyze
+    pub days: u32,
+    /// Most recent days, compared against the rest of the window
+    pub recent_days: u32,
+    /// Pass-rate change, in percentage points, that counts as significant
+    pub pass_rate_threshold: f64,
+    /// p95 latency change, as a percentage of the earlier p95, that counts as significant
+    pub latency_threshold: f64,
+}
+
+impl Default for TrendOptions {
+    fn default() -> Self {
+        Self {
+            days: 30,
+            recent_days: 7,
+            pass_rate_threshold: 10.0,
+            latency_threshold: 50.0,
+        }
+    }
+}
+
+/// One tool's results on one day
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DailyPoint {
+    /// UTC date, `YYYY-MM-DD`
+    pub date: String,
+    pub runs: usize,
+    /// Share of runs passed, as a percentage
+    pub pass_rate: f64,
+    /// Latency of passing runs; zero when none passed
+    pub mean_latency_ms: u64,
+    pub p95_latency_ms: u64,
+}
+
+/// Pass-rate trend of one tool
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FlakinessTrend {
+    pub tool: String,
+    pub runs: usize,
+    pub passed: usize,
+    /// Share of runs passed across the whole window, as a percentage
+    pub pass_rate: f64,
+    /// Share of consecutive runs that flipped between pass and fail, as a percentage
+    pub flakiness: f64,
+    /// Pass rate over the recent days; `None` without recent runs
+    pub recent_pass_rate: Option<f64>,
+    /// Pass rate before the recent days; `None` without earlier runs
+    pub earlier_pass_rate: Option<f64>,
+    /// Recent minus earlier pass rate, in percentage points
+    pub change: Option<f64>,
+    /// Whether `change` reached the significance threshold
+    pub changed: bool,
+    pub daily: Vec<DailyPoint>,
+}
+
+/// Latency trend of one tool, over passing runs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LatencyTrend {
+    pub tool: String,
+    /// Passing runs the latency is computed from
+    pub samples: usize,
+    pub latency: LatencyStats,
+    /// p95 over the recent days; `None` without recent passing runs
+    pub recent_p95_ms: Option<u64>,
+    /// p95 before the recent days; `None` without earlier passing runs
+    pub earlier_p95_ms: Option<u64>,
+    /// Recent p95 relative to earlier p95, as a percentage change
+    pub change: Option<f64>,
+    /// Whether `change` reached the significance threshold
+    pub changed: bool,
+    pub daily: Vec<DailyPoint>,
+}
+
+/// Outcomes within the analysis window, split at the start of the recent days
+struct Window<'a> {
+    outcomes: Vec<ToolOutcome>,
+    by_tool: BTreeMap<&'a str, Vec<(DateTime<Utc>, &'a ToolOutcome)>>,
+    recent_since: DateTime<Utc>,
+}
+
+impl<'a> Window<'a> {
+    fn new(outcomes: &'a [ToolOutcome], options: &TrendOptions, now: DateTime<Utc>) -> Self {
+        let since = now - Duration::days(i64::from(options.days));
+        let mut by_tool: BTreeMap<&str, Vec<_>> = BTreeMap::new();
+        for outcome in outcomes {
+            let Ok(at) = DateTime::parse_from_rfc3339(&outcome.started_at) else {
+                continue;
+            };
+            let at = at.with_timezone(&Utc);
+            if at >= since {
+                by_tool
+                    .entry(&outcome.tool)
+                    .or_default()
+                    .push((at, outcome));
+            }
+        }
+        Self {
+            outcomes: by_tool
+                .values()
+                .flatten()
+                .map(|(_, outcome)| (*outcome).clone())
+                .collect(),
+            by_tool,
+            recent_since: now - Duration::days(i64::from(options.recent_days)),
+        }
+    }
+
+    /// `tool`'s outcomes before and within the recent days
+    fn split(&self, tool: &str) -> (Vec<&'a ToolOutcome>, Vec<&'a ToolOutcome>) {
+        let (recent, earlier): (Vec<_>, Vec<_>) = self
+            .by_tool
+            .get(tool)
+            .into_iter()
+            .flatten()
+            .partition(|(at, _)| *at >= self.recent_since);
+        (
+            earlier.into_iter().map(|(_, outcome)| outcome).collect(),
+            recent.into_iter().map(|(_, outcome)| outcome).collect(),
+        )
+    }
+
+    fn daily(&self, tool: &str) -> Vec<DailyPoint> {
+        let mut days: BTreeMap<String, Vec<&ToolOutcome>> = BTreeMap::new();
+        for (at, outcome) in self.by_tool.get(tool).into_iter().flatten() {
+            days.entry(at.format("%Y-%m-%d").to_string())
+                .or_default()
+                .push(outcome);
+        }
+        days.into_iter()
+            .map(|(date, outcomes)| {
+                let latency = LatencyStats::from_samples(&passing_latencies(&outcomes));
+                DailyPoint {
+                    date,
+                    runs: outcomes.len(),
+                    pass_rate: pass_rate(&outcomes).unwrap_or(0.0),
+                    mean_latency_ms: latency.mean,
+                    p95_latency_ms: latency.p95,
+                }
+            })
+            .collect()
+    }
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn pass_rate(outcomes: &[&ToolOutcome]) -> Option<f64> {
+    if outcomes.is_empty() {
+        return None;
+    }
+    let passed = outcomes.iter().filter(|o| o.success).count();
+    Some(passed as f64 * 100.0 / outcomes.len() as f64)
+}
+
+fn passing_latencies(outcomes: &[&ToolOutcome]) -> Vec<u64> {
+    outcomes
+        .iter()
+        .filter(|o| o.success)
+        .map(|o| o.response_time_ms)
+        .collect()
+}
+
+/// Pass-rate trend per tool, sorted by name, over runs started in the window ending at `now`
+#[must_use]
+pub fn analyze_flakiness(
+    outcomes: &[ToolOutcome],
+    options: &TrendOptions,
+    now: DateTime<Utc>,
+) -> Vec<FlakinessTrend> {
+    let window = Window::new(outcomes, options, now);
+    ToolStats::from_outcomes(&window.outcomes)
+        .into_iter()
+        .map(|stats| {
+            let (earlier, recent) = window.split(&stats.tool);
+            let recent_pass_rate = pass_rate(&recent);
+            let earlier_pass_rate = pass_rate(&earlier);
+            let change = recent_pass_rate
+                .zip(earlier_pass_rate)
+                .map(|(recent, earlier)| recent - earlier);
+            FlakinessTrend {
+                daily: window.daily(&stats.tool),
+                tool: stats.tool,
+                runs: stats.runs,
+                passed: stats.passed,
+                pass_rate: stats.availability,
+                flakiness: stats.flakiness,
+                recent_pass_rate,
+                earlier_pass_rate,
+                change,
+                changed: change.is_some_and(|change| change.abs() >= options.pass_rate_threshold),
+            }
+        })
+        .collect()
+}
+
+/// Latency trend per tool, sorted by name, over runs started in the window ending at `now`
+#[must_use]
+#[allow(clippy::cast_precision_loss)]
+pub fn analyze_latency(
+    outcomes: &[ToolOutcome],
+    options: &TrendOptions,
+    now: DateTime<Utc>,
+) -> Vec<LatencyTrend> {
+    let window = Window::new(outcomes, options, now);
+    window
+        .by_tool
+        .keys()
+        .map(|tool| {
+            let (earlier, recent) = window.split(tool);
+            let all: Vec<_> = earlier.iter().chain(&recent).copied().collect();
+            let samples = passing_latencies(&all);
+            let p95 = |outcomes: &[&ToolOutcome]| {
+                let samples = passing_latencies(outcomes);
+                (!samples.is_empty()).then(|| LatencyStats::from_samples(&samples).p95)
+            };
+            let recent_p95_ms = p95(&recent);
+            let earlier_p95_ms = p95(&earlier);
+            let change = recent_p95_ms
+                .zip(earlier_p95_ms.filter(|earlier| *earlier > 0))
+                .map(|(recent, earlier)| (recent as f64 - earlier as f64) * 100.0 / earlier as f64);
+            LatencyTrend {
+                tool: (*tool).to_string(),
+                samples: samples.len(),
+                latency: LatencyStats::from_samples(&samples),
+                recent_p95_ms,
+                earlier_p95_ms,
+                change,
+                changed: change.is_some_and(|change| change.abs() >= options.latency_threshold),
+                daily: window.daily(tool),
+            }
+        })
+        .collect()
+}
+
+fn format_rate(rate: Option<f64>) -> String {
+    rate.map_or_else(|| "-".to_string(), |rate| format!("{rate:.1}%"))
+}
+
+fn format_ms(ms: Option<u64>) -> String {
+    ms.map_or_else(|| "-".to_string(), |ms| ms.to_string())
+}
+
+fn format_change(change: Option<f64>, unit: &str, changed: bool) -> String {
+    change.map_or_else(
+        || "-".to_string(),
+        |change| format!("{change:+.1}{unit}{}", if changed { " ⚠️" } else { "" }),
+    )
+}
+
+/// Quote a CSV field when it contains a delimiter, quote, or newline
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn csv_option<T: ToString>(value: Option<T>) -> String {
+    value.map(|value| value.to_string()).unwrap_or_default()
+}
+
+impl FlakinessTrend {
+    /// Render `trends` as an aligned table, marking significant changes
+    #[must_use]
+    pub fn format_table(trends: &[Self]) -> String {
+        let width = trends
+            .iter()
+            .map(|t| t.tool.len())
+            .max()
+            .unwrap_or(4)
+            .max(4);
+        let mut output = String::new();
+        let _ = writeln!(
+            output,
+            "{:<width$}  {:>4}  {:>9}  {:>9}  {:>7}  {:>7}  CHANGE",
+            "TOOL", "RUNS", "PASS RATE", "FLAKINESS", "EARLIER", "RECENT"
+        );
+        for t in trends {
+            let _ = writeln!(
+                output,
+                "{:<width$}  {:>4}  {:>8.1}%  {:>8.1}%  {:>7}  {:>7}  {}",
+                t.tool,
+                t.runs,
+                t.pass_rate,
+                t.flakiness,
+                format_rate(t.earlier_pass_rate),
+                format_rate(t.recent_pass_rate),
+                format_change(t.change, "pp", t.changed)
+            );
+        }
+        output
+    }
+
+    /// Render `trends` as CSV, one row per tool
+    #[must_use]
+    pub fn format_csv(trends: &[Self]) -> String {
+        let mut output = String::from(
+            "tool,runs,passed,pass_rate,flakiness,earlier_pass_rate,recent_pass_rate,change,changed\n",
+        );
+        for t in trends {
+            let _ = writeln!(
+                output,
+                "{},{},{},{:.1},{:.1},{},{},{},{}",
+                csv_field(&t.tool),
+                t.runs,
+                t.passed,
+                t.pass_rate,
+                t.flakiness,
+                csv_option(t.earlier_pass_rate.map(|rate| format!("{rate:.1}"))),
+                csv_option(t.recent_pass_rate.map(|rate| format!("{rate:.1}"))),
+                csv_option(t.change.map(|change| format!("{change:.1}"))),
+                t.changed
+            );
+        }
+        output
+    }
+}
+
+impl LatencyTrend {
+    /// Render `trends` as an aligned table, marking significant changes
+    #[must_use]
+    pub fn format_table(trends: &[Self]) -> String {
+        let width = trends
+            .iter()
+            .map(|t| t.tool.len())
+            .max()
+            .unwrap_or(4)
+            .max(4);
+        let mut output = String::new();
+        let _ = writeln!(
+            output,
+            "{:<width$}  {:>7}  {:>7}  {:>7}  {:>7}  {:>11}  {:>10}  CHANGE",
+            "TOOL", "SAMPLES", "MEAN MS", "P50 MS", "P95 MS", "EARLIER P95", "RECENT P95"
+        );
+        for t in trends {
+            let _ = writeln!(
+                output,
+                "{:<width$}  {:>7}  {:>7}  {:>7}  {:>7}  {:>11}  {:>10}  {}",
+                t.tool,
+                t.samples,
+                t.latency.mean,
+                t.latency.p50,
+                t.latency.p95,
+                format_ms(t.earlier_p95_ms),
+                format_ms(t.recent_p95_ms),
+                format_change(t.change, "%", t.changed)
+            );
+        }
+        output
+    }
+
+    /// Render `trends` as CSV, one row per tool
+    #[must_use]
+    pub fn format_csv(trends: &[Self]) -> String {
+        let mut output = String::from(
+            "tool,samples,mean_ms,p50_ms,p95_ms,p99_ms,earlier_p95_ms,recent_p95_ms,change,changed\n",
+        );
+        for t in trends {
+            let _ = writeln!(
+                output,
+                "{},{},{},{},{},{},{},{},{},{}",
+                csv_field(&t.tool),
+                t.samples,
+                t.latency.mean,
+                t.latency.p50,
+                t.latency.p95,
+                t.latency.p99,
+                csv_option(t.earlier_p95_ms),
+                csv_option(t.recent_p95_ms),
+                csv_option(t.change.map(|change| format!("{change:.1}"))),
+                t.changed
+            );
+        }
+        output
+    }
+}
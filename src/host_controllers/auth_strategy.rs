@@ -0,0 +1,184 @@
+//! How a host authenticates to the Glean MCP server
+//!
+//! With `bridge` auth the host launches the `mcp-remote` stdio bridge
+//! (`npx -y mcp-remote <url>`), which runs the OAuth flow and caches tokens
+//! under `~/.mcp-auth`. With `native` auth the host connects to the server URL
+//! and runs OAuth itself. `host_applications.<host>.auth_method` says which one
+//! a host is meant to use; `host verify` detects the one actually configured,
+//! checks its prerequisites, and reports it.
+
+use crate::{GleanConfig, GleanMcpError, Result, find_executable, home_dir};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Package that bridges stdio-only hosts to the remote server
+pub const BRIDGE_PACKAGE: &str = "mcp-remote";
+
+/// An authentication strategy for a host's Glean server entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuthStrategy {
+    /// The `mcp-remote` stdio bridge, with tokens cached locally
+    Bridge,
+    /// The host's own OAuth support, against the server URL
+    Native,
+}
+
+impl AuthStrategy {
+    pub const ALL: [Self; 2] = [Self::Bridge, Self::Native];
+
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Bridge => "bridge",
+            Self::Native => "native",
+        }
+    }
+
+    /// Strategy `host_applications` configures for `host` (e.g. `claude-code`), if any
+    #[must_use]
+    pub fn for_host(host: &str, config: &GleanConfig) -> Option<Self> {
+        config
+            .host_applications
+            .get(&host.replace('-', "_"))
+            .and_then(|host| parse_auth_strategy(&host.auth_method).ok())
+    }
+
+    /// Server entry connecting to `server_url` with this strategy, in the `mcpServers` format
+    #[must_use]
+    pub fn server_entry(self, server_url: &str) -> Value {
+        match self {
+            Self::Bridge => serde_json::json!({
+                "command": "npx",
+                "args": ["-y", BRIDGE_PACKAGE, server_url]
+            }),
+            Self::Native => serde_json::json!({
+                "type": "http",
+                "url": server_url
+            }),
+        }
+    }
+
+    /// Strategy a config file's server entry uses
+    #[must_use]
+    pub fn of_entry(entry: &Value) -> Option<Self> {
+        Self::of_description(&entry.to_string())
+    }
+
+    /// Strategy of `server_name` in a host CLI's `mcp list` output
+    #[must_use]
+    pub fn detect(listing: &str, server_name: &str) -> Option<Self> {
+        let server_name = server_name.to_lowercase();
+        listing
+            .lines()
+            .filter(|line| line.to_lowercase().contains(&server_name))
+            .find_map(Self::of_description)
+    }
+
+    /// Strategy a server description (command line, URL, or JSON entry) points to
+    fn of_description(description: &str) -> Option<Self> {
+        if description.contains(BRIDGE_PACKAGE) {
+            Some(Self::Bridge)
+        } else if description.contains("http://") || description.contains("https://") {
+            Some(Self::Native)
+        } else {
+            None
+        }
+    }
+
+    /// Check this strategy's local prerequisites
+    #[must_use]
+    pub fn verify(self) -> AuthCheck {
+        match self {
+            Self::Bridge => {
+                if find_executable("npx").is_none() {
+                    return AuthCheck::failed(
+                        self,
+                        "npx is not on PATH, so the mcp-remote bridge can't start",
+                    );
+                }
+                let cached = home_dir()
+                    .map(|home| home.join(".mcp-auth"))
+                    .and_then(|dir| std::fs::read_dir(dir).ok())
+                    .is_some_and(|mut entries| entries.next().is_some());
+                AuthCheck::passed(
+                    self,
+                    if cached {
+                        "mcp-remote bridge via npx, with cached tokens in ~/.mcp-auth"
+                    } else {
+                        "mcp-remote bridge via npx; no cached tokens yet, so the first call opens a browser sign-in"
+                    },
+                )
+            }
+            Self::Native => AuthCheck::passed(
+                self,
+                "the host runs OAuth against the server URL and stores the token itself",
+            ),
+        }
+    }
+}
+
+/// Parse an auth method name, as in `host_applications.<host>.auth_method`
+pub fn parse_auth_strategy(raw: &str) -> Result<AuthStrategy> {
+    let name = raw.trim();
+    AuthStrategy::ALL
+        .into_iter()
+        .find(|strategy| strategy.as_str() == name)
+        .ok_or_else(|| {
+            GleanMcpError::Config(format!(
+                "Unknown auth method '{name}' (expected bridge, native)"
+            ))
+        })
+}
+
+/// Outcome of checking a host's authentication setup
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuthCheck {
+    /// Strategy the host is set up with
+    pub strategy: AuthStrategy,
+    pub success: bool,
+    pub details: String,
+}
+
+impl AuthCheck {
+    fn passed(strategy: AuthStrategy, details: &str) -> Self {
+        Self {
+            strategy,
+            success: true,
+            details: details.to_string(),
+        }
+    }
+
+    fn failed(strategy: AuthStrategy, details: &str) -> Self {
+        Self {
+            strategy,
+            success: false,
+            details: details.to_string(),
+        }
+    }
+
+    /// Check the `detected` strategy, or the `configured` one when nothing was detected
+    ///
+    /// A host whose detected strategy differs from the configured one fails.
+    /// `None` when neither is known.
+    #[must_use]
+    pub fn run(configured: Option<AuthStrategy>, detected: Option<AuthStrategy>) -> Option<Self> {
+        match (configured, detected) {
+            (Some(configured), Some(detected)) if configured != detected => Some(Self::failed(
+                detected,
+                &format!(
+                    "configured for {} auth, but the host's entry uses {}",
+                    configured.as_str(),
+                    detected.as_str()
+                ),
+            )),
+            _ => detected.or(configured).map(AuthStrategy::verify),
+        }
+    }
+
+    /// One line for host results, e.g. `Auth: native (...)`
+    #[must_use]
+    pub fn summary(&self) -> String {
+        format!("Auth: {} ({})", self.strategy.as_str(), self.details)
+    }
+}
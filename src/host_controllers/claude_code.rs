@@ -4,11 +4,12 @@
 //! Assumes: MCP server is already configured and authenticated via `claude mcp add`
 //! Testing: Uses `claude mcp` commands to test Glean tool functionality
 
-use super::{HostController, HostOperationResult};
-use crate::{GleanMcpError, Result};
-use async_process::Command;
+use super::{AuthCheck, AuthStrategy, HostController, HostOperationResult};
+use crate::{GleanMcpError, Result, async_command, command, find_executable, home_dir};
 use smol::io::{AsyncBufReadExt, BufReader};
 use smol::stream::StreamExt;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
 use std::process::Stdio;
 use std::time::Instant;
 
@@ -16,6 +17,8 @@ use std::time::Instant;
 pub struct ClaudeCodeController {
     /// Path to the claude binary (defaults to "claude" assuming it's in PATH)
     claude_path: String,
+    /// Strategy the Glean server is expected to authenticate with
+    auth_strategy: Option<AuthStrategy>,
 }
 
 impl ClaudeCodeController {
@@ -24,40 +27,53 @@ impl ClaudeCodeController {
     pub fn new() -> Self {
         // Try to find the actual claude binary path, fallback to "claude"
         let claude_path = Self::find_claude_binary().unwrap_or_else(|| "claude".to_string());
-        Self { claude_path }
+        Self {
+            claude_path,
+            auth_strategy: None,
+        }
     }
 
-    /// Find the Claude Code binary in common installation locations
+    /// Find the Claude Code binary in common installation locations, then on `PATH`
     fn find_claude_binary() -> Option<String> {
-        // Common installation paths for Claude Code
-        let common_paths = [
-            format!(
-                "{}/.claude/local/claude",
-                std::env::var("HOME").unwrap_or_default()
-            ),
-            "/usr/local/bin/claude".to_string(),
-            "/opt/homebrew/bin/claude".to_string(),
-            "claude".to_string(), // Fallback to PATH
-        ];
-
-        for path in &common_paths {
-            if std::path::Path::new(path).exists() {
-                return Some(path.clone());
-            }
+        let mut common_paths: Vec<PathBuf> = Vec::new();
+        if cfg!(windows) {
+            let env_dir = |var: &str| std::env::var_os(var).map(PathBuf::from);
+            common_paths.extend(home_dir().map(|home| home.join(r".claude\local\claude.exe")));
+            common_paths
+                .extend(env_dir("LOCALAPPDATA").map(|dir| dir.join(r"Programs\claude\claude.exe")));
+            common_paths.extend(env_dir("APPDATA").map(|dir| dir.join(r"npm\claude.cmd")));
+        } else {
+            common_paths.extend(home_dir().map(|home| home.join(".claude/local/claude")));
+            common_paths.push(PathBuf::from("/usr/local/bin/claude"));
+            common_paths.push(PathBuf::from("/opt/homebrew/bin/claude"));
         }
 
-        None
+        common_paths
+            .into_iter()
+            .find(|path| path.is_file())
+            .or_else(|| find_executable("claude"))
+            .map(|path| path.to_string_lossy().into_owned())
     }
 
     /// Create a new Claude Code controller with custom binary path
     #[must_use]
     pub const fn with_path(claude_path: String) -> Self {
-        Self { claude_path }
+        Self {
+            claude_path,
+            auth_strategy: None,
+        }
+    }
+
+    /// Expect the Glean server to authenticate with `strategy`
+    #[must_use]
+    pub const fn with_auth_strategy(mut self, strategy: Option<AuthStrategy>) -> Self {
+        self.auth_strategy = strategy;
+        self
     }
 
     /// List all configured MCP servers in Claude Code
     async fn list_mcp_servers_internal(&self) -> Result<String> {
-        let mut child = Command::new(&self.claude_path)
+        let mut child = async_command(&self.claude_path)
             .args(["mcp", "list"])
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
@@ -157,9 +173,7 @@ impl Default for ClaudeCodeController {
 }
 
 impl HostController for ClaudeCodeController {
-    async fn verify_mcp_server(
-        &self,
-    ) -> Result<HostOperationResult> {
+    async fn verify_mcp_server(&self) -> Result<HostOperationResult> {
         let start_time = Instant::now();
 
         match self.list_mcp_servers_internal().await {
@@ -168,6 +182,10 @@ impl HostController for ClaudeCodeController {
                 "verify_mcp_server",
                 &format!("MCP servers verified: {output}"),
             )
+            .with_auth(AuthCheck::run(
+                self.auth_strategy,
+                AuthStrategy::detect(&output, "glean_default"),
+            ))
             .with_duration(start_time.elapsed())),
             Err(e) => Ok(HostOperationResult::new_error(
                 "claude-code",
@@ -210,36 +228,38 @@ impl HostController for ClaudeCodeController {
         }
     }
 
-    async fn test_all_glean_tools(
-        &self,
-    ) -> Result<HostOperationResult> {
-        let start_time = Instant::now();
-
-        // Define core Glean tools to test
-        let glean_tools = vec![
+    fn glean_test_cases(&self) -> &'static [(&'static str, &'static str)] {
+        // Core Glean tools, under the names Claude Code exposes them as
+        &[
             ("glean_search", "remote work policy"),
             ("chat", "What are the benefits of using Glean?"),
             ("read_document", "https://docs.glean.com"),
-        ];
+        ]
+    }
 
+    async fn test_all_glean_tools(&self) -> Result<HostOperationResult> {
+        let start_time = Instant::now();
+
+        let glean_tools = self.glean_test_cases();
         let mut results = Vec::new();
+        let mut tools = BTreeMap::new();
         let mut success_count = 0;
 
-        for (tool_name, sample_query) in &glean_tools {
-            match self.test_glean_tool(tool_name, sample_query).await {
-                Ok(result) => {
-                    if result.success {
-                        success_count += 1;
-                    }
-                    results.push(format!(
-                        "{tool_name}: {}",
-                        if result.success { "✅" } else { "❌" }
-                    ));
-                }
-                Err(_) => {
-                    results.push(format!("{tool_name}: ❌ Error"));
-                }
+        for (tool_name, sample_query) in glean_tools {
+            let passed = self
+                .test_glean_tool(tool_name, sample_query)
+                .await
+                .map(|result| result.success);
+            if passed.as_ref().is_ok_and(|passed| *passed) {
+                success_count += 1;
             }
+            let status = match passed {
+                Ok(true) => "✅",
+                Ok(false) => "❌",
+                Err(_) => "❌ Error",
+            };
+            results.push(format!("{tool_name}: {status}"));
+            tools.insert((*tool_name).to_string(), passed.unwrap_or(false));
         }
 
         let total_tools = glean_tools.len();
@@ -250,16 +270,14 @@ impl HostController for ClaudeCodeController {
 
         Ok(
             HostOperationResult::new_success("claude-code", "test_all_glean_tools", &details)
+                .with_tools(tools)
                 .with_duration(start_time.elapsed()),
         )
     }
 
     fn check_availability(&self) -> Result<bool> {
         // Check if claude command is available in PATH
-        match std::process::Command::new(&self.claude_path)
-            .arg("--version")
-            .output()
-        {
+        match command(&self.claude_path).arg("--version").output() {
             Ok(output) => Ok(output.status.success()),
             Err(_) => Ok(false),
         }
@@ -269,9 +287,7 @@ impl HostController for ClaudeCodeController {
         "claude-code"
     }
 
-    async fn list_mcp_servers(
-        &self,
-    ) -> Result<HostOperationResult> {
+    async fn list_mcp_servers(&self) -> Result<HostOperationResult> {
         let start_time = Instant::now();
 
         match self.list_mcp_servers_internal().await {
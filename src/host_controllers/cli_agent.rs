@@ -0,0 +1,307 @@
+//! Generic controller for command-line agents (gemini-cli, codex, ...)
+//!
+//! Assumes: the Glean MCP server is already configured in the agent
+//! Testing: lists the agent's MCP servers and runs one-shot prompts that ask
+//! the agent to call a Glean tool. Presets cover `gemini` and `codex`; other
+//! agents only need a [`CliAgentConfig`].
+
+use super::{AuthCheck, AuthStrategy, HostController, HostOperationResult};
+use crate::{GleanMcpError, Result, async_command, command};
+use std::collections::BTreeMap;
+use std::process::Stdio;
+use std::time::{Duration, Instant};
+
+/// Placeholder in [`CliAgentConfig::invocation`] replaced with the prompt text
+#[allow(clippy::literal_string_with_formatting_args)]
+pub const PROMPT_PLACEHOLDER: &str = "{prompt}";
+
+/// How to drive a command-line agent
+#[derive(Debug, Clone)]
+pub struct CliAgentConfig {
+    /// Host name used in results and `--host`
+    pub name: &'static str,
+    /// Binary to run, resolved on `PATH`
+    pub binary: String,
+    /// Arguments that list configured MCP servers
+    pub list_servers_args: Vec<String>,
+    /// Arguments for a one-shot prompt; [`PROMPT_PLACEHOLDER`] is replaced with the prompt text
+    pub invocation: Vec<String>,
+    /// MCP server name the Glean server is configured under
+    pub server_name: String,
+    /// Longest a single prompt may run
+    pub timeout: Duration,
+}
+
+impl CliAgentConfig {
+    /// gemini-cli: `gemini mcp list`, `gemini --prompt <prompt>`
+    #[must_use]
+    pub fn gemini() -> Self {
+        Self {
+            name: "gemini",
+            binary: "gemini".to_string(),
+            list_servers_args: args(&["mcp", "list"]),
+            invocation: args(&["--prompt", PROMPT_PLACEHOLDER]),
+            server_name: "glean".to_string(),
+            timeout: Duration::from_mins(2),
+        }
+    }
+
+    /// The `OpenAI` codex CLI: `codex mcp list`, `codex exec <prompt>`
+    #[must_use]
+    pub fn codex() -> Self {
+        Self {
+            name: "codex",
+            binary: "codex".to_string(),
+            list_servers_args: args(&["mcp", "list"]),
+            invocation: args(&["exec", PROMPT_PLACEHOLDER]),
+            server_name: "glean".to_string(),
+            timeout: Duration::from_mins(2),
+        }
+    }
+
+    /// Built-in preset for `name`, if there is one
+    #[must_use]
+    pub fn preset(name: &str) -> Option<Self> {
+        match name {
+            "gemini" | "gemini-cli" => Some(Self::gemini()),
+            "codex" => Some(Self::codex()),
+            _ => None,
+        }
+    }
+}
+
+fn args(values: &[&str]) -> Vec<String> {
+    values.iter().map(ToString::to_string).collect()
+}
+
+/// Controller for any agent described by a [`CliAgentConfig`]
+pub struct CliAgentController {
+    config: CliAgentConfig,
+    /// Strategy the Glean server is expected to authenticate with
+    auth_strategy: Option<AuthStrategy>,
+}
+
+/// Captured result of one agent invocation
+struct AgentOutput {
+    success: bool,
+    stdout: String,
+    stderr: String,
+}
+
+impl CliAgentController {
+    #[must_use]
+    pub const fn new(config: CliAgentConfig) -> Self {
+        Self {
+            config,
+            auth_strategy: None,
+        }
+    }
+
+    /// Expect the Glean server to authenticate with `strategy`
+    #[must_use]
+    pub const fn with_auth_strategy(mut self, strategy: Option<AuthStrategy>) -> Self {
+        self.auth_strategy = strategy;
+        self
+    }
+
+    #[must_use]
+    pub const fn config(&self) -> &CliAgentConfig {
+        &self.config
+    }
+
+    /// Run the agent with `args`, killing it after the configured timeout
+    async fn run(&self, args: &[String]) -> Result<AgentOutput> {
+        let child = async_command(&self.config.binary)
+            .args(args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| {
+                GleanMcpError::Host(format!("Failed to spawn {}: {e}", self.config.binary))
+            })?;
+
+        let timeout = self.config.timeout;
+        let output = smol::future::or(async { Some(child.output().await) }, async {
+            smol::Timer::after(timeout).await;
+            None
+        })
+        .await
+        .ok_or_else(|| {
+            GleanMcpError::Host(format!(
+                "{} did not finish within {}s",
+                self.config.name,
+                timeout.as_secs()
+            ))
+        })?
+        .map_err(|e| GleanMcpError::Host(format!("Failed to run {}: {e}", self.config.binary)))?;
+
+        Ok(AgentOutput {
+            success: output.status.success(),
+            stdout: String::from_utf8_lossy(&output.stdout).trim().to_string(),
+            stderr: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        })
+    }
+
+    /// Arguments for a one-shot `prompt`
+    fn invocation(&self, prompt: &str) -> Vec<String> {
+        self.config
+            .invocation
+            .iter()
+            .map(|arg| arg.replace(PROMPT_PLACEHOLDER, prompt))
+            .collect()
+    }
+
+    fn prompt(&self, tool_name: &str, query: &str) -> String {
+        format!(
+            "Use the {tool_name} tool from the {} MCP server with the input \"{query}\" and reply with a short summary of what it returned.",
+            self.config.server_name
+        )
+    }
+}
+
+impl HostController for CliAgentController {
+    async fn verify_mcp_server(&self) -> Result<HostOperationResult> {
+        let start_time = Instant::now();
+        let output = self.run(&self.config.list_servers_args).await?;
+        let configured = output
+            .stdout
+            .to_lowercase()
+            .contains(&self.config.server_name.to_lowercase());
+
+        let result = if output.success && configured {
+            HostOperationResult::new_success(
+                self.config.name,
+                "verify_mcp_server",
+                &format!("MCP servers verified: {}", output.stdout),
+            )
+            .with_auth(AuthCheck::run(
+                self.auth_strategy,
+                AuthStrategy::detect(&output.stdout, &self.config.server_name),
+            ))
+        } else if output.success {
+            HostOperationResult::new_error(
+                self.config.name,
+                "verify_mcp_server",
+                &format!(
+                    "No '{}' MCP server configured in {}",
+                    self.config.server_name, self.config.name
+                ),
+            )
+        } else {
+            HostOperationResult::new_error(self.config.name, "verify_mcp_server", &output.stderr)
+        };
+        Ok(result.with_duration(start_time.elapsed()))
+    }
+
+    fn test_glean_tool(
+        &self,
+        tool_name: &str,
+        query: &str,
+    ) -> impl std::future::Future<Output = Result<HostOperationResult>> + Send {
+        let args = self.invocation(&self.prompt(tool_name, query));
+        let tool_name = tool_name.to_string();
+        async move {
+            let start_time = Instant::now();
+            let result = match self.run(&args).await {
+                Ok(output) if output.success && !output.stdout.is_empty() => {
+                    HostOperationResult::new_success(
+                        self.config.name,
+                        "test_glean_tool",
+                        &format!(
+                            "Tool '{tool_name}' executed successfully: {}",
+                            output.stdout
+                        ),
+                    )
+                }
+                Ok(output) => HostOperationResult::new_error(
+                    self.config.name,
+                    "test_glean_tool",
+                    &format!(
+                        "Tool '{tool_name}' failed: {}",
+                        if output.stderr.is_empty() {
+                            "empty response"
+                        } else {
+                            &output.stderr
+                        }
+                    ),
+                ),
+                Err(e) => HostOperationResult::new_error(
+                    self.config.name,
+                    "test_glean_tool",
+                    &format!("Tool '{tool_name}' failed: {e}"),
+                ),
+            };
+            Ok(result.with_duration(start_time.elapsed()))
+        }
+    }
+
+    fn glean_test_cases(&self) -> &'static [(&'static str, &'static str)] {
+        &[
+            ("search", "remote work policy"),
+            ("chat", "What are the benefits of using Glean?"),
+            ("read_document", "https://docs.glean.com"),
+        ]
+    }
+
+    async fn test_all_glean_tools(&self) -> Result<HostOperationResult> {
+        let start_time = Instant::now();
+        let glean_tools = self.glean_test_cases();
+
+        let mut results = Vec::new();
+        let mut tools = BTreeMap::new();
+        let mut success_count = 0;
+        for (tool_name, sample_query) in glean_tools {
+            let result = self.test_glean_tool(tool_name, sample_query).await?;
+            if result.success {
+                success_count += 1;
+            }
+            results.push(format!(
+                "{tool_name}: {}",
+                if result.success { "✅" } else { "❌" }
+            ));
+            tools.insert((*tool_name).to_string(), result.success);
+        }
+
+        let details = format!(
+            "Tested {} Glean tools, {success_count} successful:\n{}",
+            glean_tools.len(),
+            results.join("\n")
+        );
+        let mut result =
+            HostOperationResult::new_success(self.config.name, "test_all_glean_tools", &details)
+                .with_tools(tools);
+        if success_count < glean_tools.len() {
+            result.success = false;
+            result.error = Some(format!(
+                "{} of {} tools failed",
+                glean_tools.len() - success_count,
+                glean_tools.len()
+            ));
+        }
+        Ok(result.with_duration(start_time.elapsed()))
+    }
+
+    fn check_availability(&self) -> Result<bool> {
+        Ok(command(&self.config.binary)
+            .arg("--version")
+            .output()
+            .is_ok_and(|output| output.status.success()))
+    }
+
+    fn host_name(&self) -> &'static str {
+        self.config.name
+    }
+
+    async fn list_mcp_servers(&self) -> Result<HostOperationResult> {
+        let start_time = Instant::now();
+        let output = self.run(&self.config.list_servers_args).await?;
+        let result = if output.success {
+            HostOperationResult::new_success(self.config.name, "list_mcp_servers", &output.stdout)
+        } else {
+            HostOperationResult::new_error(self.config.name, "list_mcp_servers", &output.stderr)
+        };
+        Ok(result.with_duration(start_time.elapsed()))
+    }
+}
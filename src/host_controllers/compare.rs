@@ -0,0 +1,326 @@
+//! Comparing results through a host against direct calls
+//!
+//! [`compare_host_to_direct`] sends each tool and query straight to the MCP
+//! endpoint and through a host application, then compares the two: whether
+//! both succeeded, how much latency the host adds, and whether the host's
+//! answer still carries the direct response's content. The titles and URLs
+//! of the direct response's results are its key terms; a host answer missing
+//! most of them, reading as cut off, or carrying escaped or garbled text is
+//! flagged as host-layer truncation or mangling.
+
+use super::{HostController, HostOperationResult};
+use crate::{GleanMCPInspector, RunOutcome, response_text, search_results};
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+use std::time::{Duration, Instant};
+
+/// Share of the direct response's key terms the host answer must mention
+const MIN_TERMS_FOUND: f64 = 0.5;
+
+/// Key terms taken from a direct response
+const MAX_KEY_TERMS: usize = 10;
+
+/// Markers of output a host cut short; an answer ending in an ellipsis counts too
+const TRUNCATION_MARKERS: [&str; 3] = ["[truncated]", "(truncated)", "output truncated"];
+
+/// Escapes and entities that show text was double-encoded on the way through the host
+const MANGLING_MARKERS: [&str; 7] = [
+    "\u{fffd}", "\\n", "\\\"", "\\u00", "&quot;", "&amp;", "&#39;",
+];
+
+/// How a host's result differs from the direct call's
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HostDivergence {
+    /// The direct call passed but the host failed
+    HostFailed,
+    /// The host answered although the direct call failed
+    DirectFailed,
+    /// The host answer reads as cut off
+    Truncated,
+    /// The host answer carries escaped or garbled text the direct response doesn't
+    Mangled,
+    /// The host answer mentions few of the direct response's key terms
+    ContentMissing,
+}
+
+impl HostDivergence {
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::HostFailed => "host failed where the direct call passed",
+            Self::DirectFailed => "host answered although the direct call failed",
+            Self::Truncated => "host answer looks truncated",
+            Self::Mangled => "host answer looks mangled",
+            Self::ContentMissing => "host answer is missing the direct response's content",
+        }
+    }
+}
+
+/// One tool and query, sent directly and through the host
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HostComparison {
+    /// Tool name on the MCP server
+    pub tool: String,
+    /// Tool name the host was asked to use
+    pub host_tool: String,
+    pub query: String,
+    pub direct_success: bool,
+    pub direct_ms: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub direct_error: Option<String>,
+    /// Size of the direct response's text
+    pub direct_chars: usize,
+    pub host_success: bool,
+    pub host_ms: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub host_error: Option<String>,
+    /// Size of the host's answer
+    pub host_chars: usize,
+    /// Latency the host adds over the direct call
+    pub overhead_ms: i64,
+    /// Titles and URLs of the direct response's results
+    pub key_terms: Vec<String>,
+    /// Key terms the host answer doesn't mention
+    pub missing_terms: Vec<String>,
+    pub divergences: Vec<HostDivergence>,
+}
+
+impl HostComparison {
+    #[must_use]
+    pub const fn matches(&self) -> bool {
+        self.divergences.is_empty()
+    }
+}
+
+/// Every comparison made through one host
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HostComparisonReport {
+    pub host: String,
+    pub endpoint: String,
+    /// No comparison diverged
+    pub success: bool,
+    pub comparisons: Vec<HostComparison>,
+}
+
+impl HostComparisonReport {
+    /// How the comparison ended: a failure on one side only is a partial failure, other divergences fail validation
+    #[must_use]
+    pub fn outcome(&self) -> RunOutcome {
+        let mut divergences = self
+            .comparisons
+            .iter()
+            .flat_map(|comparison| &comparison.divergences);
+        if self.success {
+            RunOutcome::Success
+        } else if divergences.any(|divergence| {
+            matches!(
+                divergence,
+                HostDivergence::HostFailed | HostDivergence::DirectFailed
+            )
+        }) {
+            RunOutcome::PartialFailure
+        } else {
+            RunOutcome::ValidationFailure
+        }
+    }
+
+    #[must_use]
+    pub fn format_text(&self) -> String {
+        let mut output = String::new();
+        let _ = writeln!(
+            output,
+            "🔀 Host vs direct: {} against {}",
+            self.host, self.endpoint
+        );
+        for comparison in &self.comparisons {
+            let _ = writeln!(
+                output,
+                "\n{} {} \"{}\"",
+                if comparison.matches() { "✅" } else { "❌" },
+                comparison.tool,
+                comparison.query
+            );
+            let status = |success: bool, error: Option<&String>| {
+                if success {
+                    "passed".to_string()
+                } else {
+                    format!("failed: {}", error.map_or("unknown error", String::as_str))
+                }
+            };
+            let _ = writeln!(
+                output,
+                "  Direct: {} in {}ms, {} chars",
+                status(comparison.direct_success, comparison.direct_error.as_ref()),
+                comparison.direct_ms,
+                comparison.direct_chars
+            );
+            let _ = writeln!(
+                output,
+                "  Host:   {} in {}ms ({:+}ms), {} chars",
+                status(comparison.host_success, comparison.host_error.as_ref()),
+                comparison.host_ms,
+                comparison.overhead_ms,
+                comparison.host_chars
+            );
+            if !comparison.key_terms.is_empty() {
+                let _ = writeln!(
+                    output,
+                    "  Content: {}/{} key terms carried through",
+                    comparison.key_terms.len() - comparison.missing_terms.len(),
+                    comparison.key_terms.len()
+                );
+            }
+            for divergence in &comparison.divergences {
+                let _ = writeln!(output, "  ⚠️  {}", divergence.as_str());
+            }
+            if comparison
+                .divergences
+                .contains(&HostDivergence::ContentMissing)
+            {
+                let _ = writeln!(
+                    output,
+                    "     missing: {}",
+                    comparison.missing_terms.join(", ")
+                );
+            }
+        }
+        let diverged = self
+            .comparisons
+            .iter()
+            .filter(|comparison| !comparison.matches())
+            .count();
+        let _ = writeln!(
+            output,
+            "\n📊 {}/{} comparison(s) matched",
+            self.comparisons.len() - diverged,
+            self.comparisons.len()
+        );
+        output
+    }
+}
+
+/// The host's answer: the text after `Tool '…' executed successfully:`
+fn host_answer(result: &HostOperationResult) -> &str {
+    result
+        .details
+        .split_once(": ")
+        .map_or(result.details.as_str(), |(_, answer)| answer)
+        .trim()
+}
+
+/// Divergences between a direct response's `direct_text` and the host's `answer`
+fn content_divergences(
+    direct_text: &str,
+    answer: &str,
+    missing: &[String],
+    key_terms: &[String],
+) -> Vec<HostDivergence> {
+    let mut divergences = Vec::new();
+    let lowered = answer.to_lowercase();
+    if TRUNCATION_MARKERS
+        .iter()
+        .any(|marker| lowered.contains(marker))
+        || answer.ends_with("...")
+        || answer.ends_with('…')
+    {
+        divergences.push(HostDivergence::Truncated);
+    }
+    if MANGLING_MARKERS
+        .iter()
+        .any(|marker| answer.contains(marker) && !direct_text.contains(marker))
+    {
+        divergences.push(HostDivergence::Mangled);
+    }
+    #[allow(clippy::cast_precision_loss)]
+    let found = (key_terms.len() - missing.len()) as f64 / key_terms.len().max(1) as f64;
+    if !key_terms.is_empty() && found < MIN_TERMS_FOUND {
+        divergences.push(HostDivergence::ContentMissing);
+    }
+    divergences
+}
+
+/// Send each `(host tool, query)` of `cases` directly through `inspector` and through `host`
+///
+/// The direct call uses the tool's server name, without the `glean_`
+/// prefix some hosts add. Each direct call may take up to `timeout`.
+#[allow(clippy::future_not_send)]
+#[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
+pub async fn compare_host_to_direct(
+    inspector: &GleanMCPInspector,
+    host: &impl HostController,
+    cases: &[(String, String)],
+    timeout: Duration,
+) -> HostComparisonReport {
+    let mut comparisons = Vec::with_capacity(cases.len());
+    for (host_tool, query) in cases {
+        let tool = host_tool.strip_prefix("glean_").unwrap_or(host_tool);
+
+        let started = Instant::now();
+        let direct = inspector.call_tool(tool, query, timeout).await;
+        let direct_ms = started.elapsed().as_millis() as u64;
+        let direct_text = direct.as_ref().map(response_text).unwrap_or_default();
+        let key_terms: Vec<String> = direct
+            .as_ref()
+            .map(search_results)
+            .unwrap_or_default()
+            .into_iter()
+            .flat_map(|item| item.title.into_iter().chain(item.url))
+            .filter(|term| term.chars().count() >= 4)
+            .take(MAX_KEY_TERMS)
+            .collect();
+
+        let started = Instant::now();
+        let hosted = host
+            .test_glean_tool(host_tool, query)
+            .await
+            .unwrap_or_else(|e| {
+                HostOperationResult::new_error(host.host_name(), "test_glean_tool", &e.to_string())
+            });
+        let host_ms = hosted
+            .duration
+            .unwrap_or_else(|| started.elapsed())
+            .as_millis() as u64;
+        let answer = if hosted.success {
+            host_answer(&hosted)
+        } else {
+            ""
+        };
+        let lowered = answer.to_lowercase();
+        let missing_terms: Vec<String> = key_terms
+            .iter()
+            .filter(|term| !lowered.contains(&term.to_lowercase()))
+            .cloned()
+            .collect();
+
+        let divergences = match (direct.is_ok(), hosted.success) {
+            (true, false) => vec![HostDivergence::HostFailed],
+            (false, true) => vec![HostDivergence::DirectFailed],
+            (true, true) => content_divergences(&direct_text, answer, &missing_terms, &key_terms),
+            (false, false) => Vec::new(),
+        };
+        comparisons.push(HostComparison {
+            tool: tool.to_string(),
+            host_tool: host_tool.clone(),
+            query: query.clone(),
+            direct_success: direct.is_ok(),
+            direct_ms,
+            direct_error: direct.as_ref().err().map(ToString::to_string),
+            direct_chars: direct_text.chars().count(),
+            host_success: hosted.success,
+            host_ms,
+            host_error: hosted.error.clone(),
+            host_chars: answer.chars().count(),
+            overhead_ms: host_ms as i64 - direct_ms as i64,
+            key_terms,
+            missing_terms,
+            divergences,
+        });
+    }
+    HostComparisonReport {
+        host: host.host_name().to_string(),
+        endpoint: inspector.server_url().to_string(),
+        success: comparisons.iter().all(HostComparison::matches),
+        comparisons,
+    }
+}
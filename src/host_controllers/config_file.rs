@@ -0,0 +1,321 @@
+//! Configuring Glean in hosts that read MCP servers from a JSON config file
+//!
+//! [`HostConfigFile::configure`] backs up the host's config file, injects the
+//! Glean server entry, and re-reads the file to verify it. If verification
+//! fails, it restores the backup. [`HostConfigFile::rollback`] restores the
+//! backup explicitly. Every write goes to a temporary file that is then
+//! renamed over the original, so an interrupted run never leaves a truncated
+//! config behind.
+//!
+//! The backup sits next to the config file as `<file>.glean-mcp-test.bak` and
+//! always holds the state from before the first `configure`, byte for byte,
+//! even when that was an empty file. If the file did not exist, an empty
+//! `<file>.glean-mcp-test.absent` marker is written instead, and rolling back
+//! removes the file.
+
+use super::{AuthStrategy, HostOperationResult, parse_auth_strategy};
+use crate::{GleanConfig, GleanMcpError, HostConfig, Result, expand_home};
+use serde_json::{Map, Value};
+use std::path::{Path, PathBuf};
+
+/// Server name used for the injected Glean entry
+pub const DEFAULT_SERVER_NAME: &str = "glean_default";
+
+const BACKUP_SUFFIX: &str = ".glean-mcp-test.bak";
+const ABSENT_SUFFIX: &str = ".glean-mcp-test.absent";
+const TEMP_SUFFIX: &str = ".glean-mcp-test.tmp";
+
+/// A host's MCP config file and how Glean entries are written into it
+#[derive(Debug, Clone)]
+pub struct HostConfigFile {
+    pub host: String,
+    pub path: PathBuf,
+    /// Server URL configured for this host in `host_applications`
+    pub server_url: String,
+    /// Object path to the server map, e.g. `["mcpServers"]`
+    servers_key: &'static [&'static str],
+    /// How the written entry authenticates
+    pub auth: AuthStrategy,
+}
+
+impl HostConfigFile {
+    /// Resolve `host` (e.g. `cursor`, `claude-desktop`) against `config.host_applications`
+    pub fn for_host(host: &str, config: &GleanConfig) -> Result<Self> {
+        let key = host.replace('-', "_");
+        let host_config: &HostConfig = config.host_applications.get(&key).ok_or_else(|| {
+            let mut known: Vec<&str> = config
+                .host_applications
+                .keys()
+                .map(String::as_str)
+                .collect();
+            known.sort_unstable();
+            GleanMcpError::Config(format!(
+                "Unknown host application '{host}'. Known hosts: {}",
+                known.join(", ")
+            ))
+        })?;
+        let path = host_config.mcp_config_path.as_deref().ok_or_else(|| {
+            GleanMcpError::Config(format!(
+                "Host '{host}' has no MCP config file; configure it with its own CLI (e.g. `claude mcp add`)"
+            ))
+        })?;
+
+        Ok(Self {
+            host: host.to_string(),
+            path: expand_home(path),
+            server_url: host_config.server_url.clone(),
+            servers_key: if key == "vscode" {
+                &["mcp", "servers"]
+            } else {
+                &["mcpServers"]
+            },
+            auth: parse_auth_strategy(&host_config.auth_method)?,
+        })
+    }
+
+    #[must_use]
+    pub fn backup_path(&self) -> PathBuf {
+        with_suffix(&self.path, BACKUP_SUFFIX)
+    }
+
+    /// Marker recording that the config file did not exist before `configure`
+    fn absent_marker_path(&self) -> PathBuf {
+        with_suffix(&self.path, ABSENT_SUFFIX)
+    }
+
+    /// Raw contents of the config file, or `None` if it doesn't exist
+    fn read_original(&self) -> Result<Option<Vec<u8>>> {
+        match std::fs::read(&self.path) {
+            Ok(contents) => Ok(Some(contents)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Entry for `server_url` in this host's format
+    #[must_use]
+    pub fn server_entry(&self, server_url: &str) -> Value {
+        self.auth.server_entry(server_url)
+    }
+
+    /// Back up the config file, add or replace the `server_name` entry, and verify it
+    ///
+    /// With `dry_run`, nothing is written and the result describes the change.
+    pub fn configure(
+        &self,
+        server_name: &str,
+        server_url: &str,
+        dry_run: bool,
+    ) -> Result<HostOperationResult> {
+        let mut document = self.read()?.unwrap_or_else(|| Value::Object(Map::new()));
+        let entry = self.server_entry(server_url);
+        self.servers_mut(&mut document)?
+            .insert(server_name.to_string(), entry.clone());
+
+        if dry_run {
+            return Ok(HostOperationResult::new_success(
+                &self.host,
+                "configure",
+                &format!(
+                    "Would add '{server_name}' to {}:\n{}",
+                    self.path.display(),
+                    serde_json::to_string_pretty(&entry)?
+                ),
+            ));
+        }
+
+        let backup = self.backup_path();
+        let absent_marker = self.absent_marker_path();
+        let backed_up = if backup.exists() || absent_marker.exists() {
+            false
+        } else {
+            match self.read_original()? {
+                Some(original) => write_atomic(&backup, &original)?,
+                None => write_atomic(&absent_marker, b"")?,
+            }
+            true
+        };
+        write_atomic(
+            &self.path,
+            serde_json::to_string_pretty(&document)?.as_bytes(),
+        )?;
+
+        if !self.has_server(server_name, &entry)? {
+            self.rollback()?;
+            return Err(GleanMcpError::Host(format!(
+                "Verification failed: '{server_name}' is missing from {} after writing; restored the backup",
+                self.path.display()
+            )));
+        }
+
+        Ok(HostOperationResult::new_success(
+            &self.host,
+            "configure",
+            &format!(
+                "Added '{server_name}' ({server_url}) to {}; {}",
+                self.path.display(),
+                match (backed_up, backup.exists()) {
+                    (_, false) => "rollback removes the file".to_string(),
+                    (true, true) => format!("backup at {}", backup.display()),
+                    (false, true) => format!("kept the earlier backup at {}", backup.display()),
+                }
+            ),
+        ))
+    }
+
+    /// Restore the config file from its backup and delete the backup
+    pub fn rollback(&self) -> Result<HostOperationResult> {
+        let backup = self.backup_path();
+        let absent_marker = self.absent_marker_path();
+        let details = if backup.exists() {
+            let original = std::fs::read(&backup)?;
+            write_atomic(&self.path, &original)?;
+            std::fs::remove_file(&backup)?;
+            format!("Restored {} from backup", self.path.display())
+        } else if absent_marker.exists() {
+            if self.path.exists() {
+                std::fs::remove_file(&self.path)?;
+            }
+            std::fs::remove_file(&absent_marker)?;
+            format!(
+                "Removed {} (it did not exist before configure)",
+                self.path.display()
+            )
+        } else {
+            return Err(GleanMcpError::Config(format!(
+                "No backup found at {}; nothing to roll back",
+                backup.display()
+            )));
+        };
+
+        Ok(HostOperationResult::new_success(
+            &self.host, "rollback", &details,
+        ))
+    }
+
+    /// Whether the config file currently holds `entry` under `server_name`
+    pub fn has_server(&self, server_name: &str, entry: &Value) -> Result<bool> {
+        let Some(document) = self.read()? else {
+            return Ok(false);
+        };
+        let servers = self
+            .servers_key
+            .iter()
+            .try_fold(&document, |value, key| value.get(key));
+        Ok(servers.and_then(|servers| servers.get(server_name)) == Some(entry))
+    }
+
+    /// MCP server entries in the config file by name, or `None` if the file doesn't exist
+    pub fn servers(&self) -> Result<Option<Map<String, Value>>> {
+        Ok(self.read()?.map(|document| {
+            self.servers_key
+                .iter()
+                .try_fold(&document, |value, key| value.get(key))
+                .and_then(Value::as_object)
+                .cloned()
+                .unwrap_or_default()
+        }))
+    }
+
+    /// Parsed config file, or `None` if it doesn't exist or is empty
+    fn read(&self) -> Result<Option<Value>> {
+        if !self.path.exists() {
+            return Ok(None);
+        }
+        let contents = std::fs::read_to_string(&self.path)?;
+        if contents.trim().is_empty() {
+            return Ok(None);
+        }
+        serde_json::from_str(&contents).map(Some).map_err(|e| {
+            GleanMcpError::Config(format!(
+                "Could not parse {} as JSON ({e}); comments and trailing commas aren't supported, edit it manually",
+                self.path.display()
+            ))
+        })
+    }
+
+    /// The server map inside `document`, created if missing
+    fn servers_mut<'a>(&self, document: &'a mut Value) -> Result<&'a mut Map<String, Value>> {
+        let mut current = document;
+        for key in self.servers_key {
+            let object = current.as_object_mut().ok_or_else(|| {
+                GleanMcpError::Config(format!(
+                    "Unexpected structure in {}: '{key}' is not inside an object",
+                    self.path.display()
+                ))
+            })?;
+            current = object
+                .entry((*key).to_string())
+                .or_insert_with(|| Value::Object(Map::new()));
+        }
+        current.as_object_mut().ok_or_else(|| {
+            GleanMcpError::Config(format!(
+                "Unexpected structure in {}: '{}' is not an object",
+                self.path.display(),
+                self.servers_key.join(".")
+            ))
+        })
+    }
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+/// Write `contents` to a temporary sibling of `path`, then rename it into place
+fn write_atomic(path: &Path, contents: &[u8]) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let temp = with_suffix(path, TEMP_SUFFIX);
+    std::fs::write(&temp, contents)?;
+    std::fs::rename(&temp, path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config_file(name: &str) -> HostConfigFile {
+        let path =
+            std::env::temp_dir().join(format!("glean-mcp-test-{}-{name}.json", std::process::id()));
+        for leftover in [
+            path.clone(),
+            with_suffix(&path, BACKUP_SUFFIX),
+            with_suffix(&path, ABSENT_SUFFIX),
+        ] {
+            let _ = std::fs::remove_file(leftover);
+        }
+        HostConfigFile {
+            host: "cursor".to_string(),
+            path,
+            server_url: "https://example-be.glean.com/mcp/default".to_string(),
+            servers_key: &["mcpServers"],
+            auth: AuthStrategy::Native,
+        }
+    }
+
+    #[test]
+    fn rollback_restores_an_empty_file_and_removes_a_new_one() {
+        let empty = config_file("empty");
+        std::fs::write(&empty.path, b"").unwrap();
+        empty
+            .configure(DEFAULT_SERVER_NAME, &empty.server_url, false)
+            .unwrap();
+        empty.rollback().unwrap();
+        assert_eq!(std::fs::read(&empty.path).unwrap(), b"");
+        std::fs::remove_file(&empty.path).unwrap();
+
+        let missing = config_file("missing");
+        missing
+            .configure(DEFAULT_SERVER_NAME, &missing.server_url, false)
+            .unwrap();
+        assert!(missing.path.exists());
+        missing.rollback().unwrap();
+        assert!(!missing.path.exists());
+        assert!(!missing.absent_marker_path().exists());
+    }
+}
@@ -4,10 +4,23 @@
 //! across different host applications. It assumes MCP servers are already
 //! configured and authenticated in each host application.
 
+pub mod auth_strategy;
 pub mod claude_code;
+pub mod cli_agent;
+pub mod compare;
+pub mod config_file;
+pub mod multi_host;
 
-use crate::Result;
+pub use auth_strategy::*;
+pub use cli_agent::*;
+pub use compare::*;
+pub use config_file::*;
+pub use multi_host::*;
+
+use crate::{GleanMcpError, Result};
+use claude_code::ClaudeCodeController;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::time::Duration;
 
 /// Result of a host application testing operation
@@ -19,6 +32,12 @@ pub struct HostOperationResult {
     pub details: String,
     pub error: Option<String>,
     pub duration: Option<Duration>,
+    /// Authentication strategy found active, checked by `verify_mcp_server`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auth: Option<AuthCheck>,
+    /// Whether each tool passed, for `test_all_glean_tools`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub tools: BTreeMap<String, bool>,
 }
 
 impl HostOperationResult {
@@ -31,6 +50,8 @@ impl HostOperationResult {
             details: details.to_string(),
             error: None,
             duration: None,
+            auth: None,
+            tools: BTreeMap::new(),
         }
     }
 
@@ -43,14 +64,40 @@ impl HostOperationResult {
             details: String::new(),
             error: Some(error.to_string()),
             duration: None,
+            auth: None,
+            tools: BTreeMap::new(),
         }
     }
 
+    /// Record whether each tool passed
+    #[must_use]
+    pub fn with_tools(mut self, tools: BTreeMap<String, bool>) -> Self {
+        self.tools = tools;
+        self
+    }
+
     #[must_use]
     pub const fn with_duration(mut self, duration: Duration) -> Self {
         self.duration = Some(duration);
         self
     }
+
+    /// Attach an auth check, failing the result if the check failed
+    #[must_use]
+    pub fn with_auth(mut self, auth: Option<AuthCheck>) -> Self {
+        if let Some(auth) = &auth {
+            if !self.details.is_empty() {
+                self.details.push('\n');
+            }
+            self.details.push_str(&auth.summary());
+            if self.success && !auth.success {
+                self.success = false;
+                self.error = Some(auth.summary());
+            }
+        }
+        self.auth = auth;
+        self
+    }
 }
 
 /// Trait for all host application testing controllers
@@ -73,6 +120,9 @@ pub trait HostController {
         &self,
     ) -> impl std::future::Future<Output = Result<HostOperationResult>> + Send;
 
+    /// Tool names and sample queries exercised by `test_all_glean_tools`
+    fn glean_test_cases(&self) -> &'static [(&'static str, &'static str)];
+
     /// Check if the host application is installed and available
     fn check_availability(&self) -> Result<bool>;
 
@@ -84,3 +134,91 @@ pub trait HostController {
         &self,
     ) -> impl std::future::Future<Output = Result<HostOperationResult>> + Send;
 }
+
+/// Host application names accepted by [`create_host_controller`]
+pub const SUPPORTED_HOSTS: &[&str] = &["claude-code", "gemini", "codex"];
+
+/// Any built-in controller, so hosts can be chosen by name at runtime
+pub enum AnyHostController {
+    ClaudeCode(ClaudeCodeController),
+    CliAgent(CliAgentController),
+}
+
+impl HostController for AnyHostController {
+    async fn verify_mcp_server(&self) -> Result<HostOperationResult> {
+        match self {
+            Self::ClaudeCode(controller) => controller.verify_mcp_server().await,
+            Self::CliAgent(controller) => controller.verify_mcp_server().await,
+        }
+    }
+
+    async fn test_glean_tool(&self, tool_name: &str, query: &str) -> Result<HostOperationResult> {
+        match self {
+            Self::ClaudeCode(controller) => controller.test_glean_tool(tool_name, query).await,
+            Self::CliAgent(controller) => controller.test_glean_tool(tool_name, query).await,
+        }
+    }
+
+    async fn test_all_glean_tools(&self) -> Result<HostOperationResult> {
+        match self {
+            Self::ClaudeCode(controller) => controller.test_all_glean_tools().await,
+            Self::CliAgent(controller) => controller.test_all_glean_tools().await,
+        }
+    }
+
+    fn glean_test_cases(&self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Self::ClaudeCode(controller) => controller.glean_test_cases(),
+            Self::CliAgent(controller) => controller.glean_test_cases(),
+        }
+    }
+
+    fn check_availability(&self) -> Result<bool> {
+        match self {
+            Self::ClaudeCode(controller) => controller.check_availability(),
+            Self::CliAgent(controller) => controller.check_availability(),
+        }
+    }
+
+    fn host_name(&self) -> &'static str {
+        match self {
+            Self::ClaudeCode(controller) => controller.host_name(),
+            Self::CliAgent(controller) => controller.host_name(),
+        }
+    }
+
+    async fn list_mcp_servers(&self) -> Result<HostOperationResult> {
+        match self {
+            Self::ClaudeCode(controller) => controller.list_mcp_servers().await,
+            Self::CliAgent(controller) => controller.list_mcp_servers().await,
+        }
+    }
+}
+
+impl AnyHostController {
+    /// Expect the host to use `strategy`, as configured in `host_applications`
+    #[must_use]
+    pub fn with_auth_strategy(self, strategy: Option<AuthStrategy>) -> Self {
+        match self {
+            Self::ClaudeCode(controller) => {
+                Self::ClaudeCode(controller.with_auth_strategy(strategy))
+            }
+            Self::CliAgent(controller) => Self::CliAgent(controller.with_auth_strategy(strategy)),
+        }
+    }
+}
+
+/// Create a controller for a host application name
+pub fn create_host_controller(host: &str) -> Result<AnyHostController> {
+    if host == "claude-code" {
+        return Ok(AnyHostController::ClaudeCode(ClaudeCodeController::new()));
+    }
+    CliAgentConfig::preset(host)
+        .map(|config| AnyHostController::CliAgent(CliAgentController::new(config)))
+        .ok_or_else(|| {
+            GleanMcpError::Host(format!(
+                "Unsupported host application: '{host}'. Supported hosts: {}",
+                SUPPORTED_HOSTS.join(", ")
+            ))
+        })
+}
@@ -0,0 +1,198 @@
+//! Testing several host applications in one sweep
+//!
+//! Hosts run one after another, or concurrently with `parallel`. Controllers
+//! capture their subprocess output, so concurrent hosts never interleave on
+//! the terminal. Each host's result is reported as one block when it
+//! finishes. A host that can't be created or fails outright is recorded as a
+//! failed result instead of aborting the sweep. [`plan_hosts`] describes a
+//! sweep for `--dry-run` without running it.
+
+use super::{HostController, HostOperationResult, create_host_controller};
+use crate::Reporter;
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+use std::time::{Duration, Instant};
+
+/// Aggregated results of testing all Glean tools on several hosts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MultiHostTestResult {
+    pub success: bool,
+    pub total_hosts: usize,
+    pub successful_hosts: usize,
+    pub failed_hosts: usize,
+    pub parallel: bool,
+    pub total_duration_ms: u64,
+    /// One result per host, in the order the hosts were given
+    pub results: Vec<HostOperationResult>,
+}
+
+impl MultiHostTestResult {
+    #[must_use]
+    pub fn format_text(&self) -> String {
+        let mut output = String::new();
+        let _ = writeln!(
+            output,
+            "🖥️  Hosts: {}/{} successful ({}, {:.2}s)",
+            self.successful_hosts,
+            self.total_hosts,
+            if self.parallel {
+                "parallel"
+            } else {
+                "sequential"
+            },
+            Duration::from_millis(self.total_duration_ms).as_secs_f64()
+        );
+        for result in &self.results {
+            let status = if result.success { "✅" } else { "❌" };
+            let duration = result
+                .duration
+                .map_or_else(String::new, |d| format!(" ({:.2}s)", d.as_secs_f64()));
+            let _ = writeln!(output, "  {status} {}{duration}", result.host);
+            if let Some(error) = &result.error {
+                let _ = writeln!(output, "    Error: {error}");
+            }
+        }
+        output
+    }
+}
+
+/// Test all Glean tools on each of `hosts`, concurrently when `parallel`
+pub async fn test_hosts(
+    hosts: &[String],
+    parallel: bool,
+    reporter: &dyn Reporter,
+) -> MultiHostTestResult {
+    let start = Instant::now();
+
+    let results = if parallel {
+        futures::future::join_all(hosts.iter().map(|host| test_host(host, reporter))).await
+    } else {
+        let mut results = Vec::with_capacity(hosts.len());
+        for host in hosts {
+            results.push(test_host(host, reporter).await);
+        }
+        results
+    };
+
+    let successful_hosts = results.iter().filter(|r| r.success).count();
+    MultiHostTestResult {
+        success: !results.is_empty() && successful_hosts == results.len(),
+        total_hosts: results.len(),
+        successful_hosts,
+        failed_hosts: results.len() - successful_hosts,
+        parallel,
+        total_duration_ms: u64::try_from(start.elapsed().as_millis()).unwrap_or(u64::MAX),
+        results,
+    }
+}
+
+async fn test_host(host: &str, reporter: &dyn Reporter) -> HostOperationResult {
+    reporter.host_operation_started(host, "test_all");
+    let result = match create_host_controller(host) {
+        Ok(controller) => controller.test_all_glean_tools().await.unwrap_or_else(|e| {
+            HostOperationResult::new_error(host, "test_all_glean_tools", &e.to_string())
+        }),
+        Err(e) => HostOperationResult::new_error(host, "test_all_glean_tools", &e.to_string()),
+    };
+    reporter.host_operation_finished(&result);
+    result
+}
+
+/// Rough wall time of one agent turn through a host, for dry-run estimates
+const HOST_CALL_ESTIMATE: Duration = Duration::from_secs(30);
+
+/// What `test_all_glean_tools` would send through one host
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HostPlan {
+    pub host: String,
+    /// Whether the host's CLI is installed; `None` when the host is unknown
+    pub available: Option<bool>,
+    /// Tool names and the queries sent to them
+    pub calls: Vec<(String, String)>,
+    pub error: Option<String>,
+}
+
+/// The hosts, tools, and queries `host test-all-hosts` would exercise
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MultiHostTestPlan {
+    pub parallel: bool,
+    pub hosts: Vec<HostPlan>,
+    /// Rough wall time, assuming every call takes a typical agent turn
+    pub estimated_ms: u64,
+}
+
+impl MultiHostTestPlan {
+    #[must_use]
+    pub fn format_text(&self) -> String {
+        let mut output = String::new();
+        let calls: usize = self.hosts.iter().map(|host| host.calls.len()).sum();
+        let _ = writeln!(
+            output,
+            "📝 Dry run: {calls} call(s) on {} host(s), {}, about {:.0}s",
+            self.hosts.len(),
+            if self.parallel {
+                "parallel"
+            } else {
+                "sequential"
+            },
+            Duration::from_millis(self.estimated_ms).as_secs_f64()
+        );
+        for host in &self.hosts {
+            let status = match host.available {
+                Some(true) => "installed",
+                Some(false) => "not installed",
+                None => "unknown host",
+            };
+            let _ = writeln!(output, "  🖥️  {} ({status})", host.host);
+            for (tool, query) in &host.calls {
+                let _ = writeln!(output, "    - {tool}: {query:?}");
+            }
+            if let Some(error) = &host.error {
+                let _ = writeln!(output, "    Error: {error}");
+            }
+        }
+        output
+    }
+}
+
+/// Plan testing all Glean tools on each of `hosts` without running any agent
+///
+/// Only each host's `--version` is run, to report whether it's installed.
+#[must_use]
+pub fn plan_hosts(hosts: &[String], parallel: bool) -> MultiHostTestPlan {
+    let plans: Vec<HostPlan> = hosts
+        .iter()
+        .map(|host| match create_host_controller(host) {
+            Ok(controller) => HostPlan {
+                host: host.clone(),
+                available: Some(controller.check_availability().unwrap_or(false)),
+                calls: controller
+                    .glean_test_cases()
+                    .iter()
+                    .map(|(tool, query)| ((*tool).to_string(), (*query).to_string()))
+                    .collect(),
+                error: None,
+            },
+            Err(e) => HostPlan {
+                host: host.clone(),
+                available: None,
+                calls: Vec::new(),
+                error: Some(e.to_string()),
+            },
+        })
+        .collect();
+
+    let per_host = plans
+        .iter()
+        .map(|plan| HOST_CALL_ESTIMATE * u32::try_from(plan.calls.len()).unwrap_or(u32::MAX));
+    let estimated = if parallel {
+        per_host.max().unwrap_or_default()
+    } else {
+        per_host.sum()
+    };
+    MultiHostTestPlan {
+        parallel,
+        hosts: plans,
+        estimated_ms: u64::try_from(estimated.as_millis()).unwrap_or(u64::MAX),
+    }
+}
@@ -0,0 +1,514 @@
+//! Filing issues for persistent monitor failures
+//!
+//! With a provider set in [`IssueFilingConfig`], the monitor looks back
+//! through a schedule's history after every run. A tool that has failed
+//! `after_failures` consecutive runs with the same [`ErrorCategory`] gets a
+//! GitHub issue or Jira ticket with its error, the failing runs, and the
+//! environment. Issues are deduplicated by tool and category through their
+//! title: when an open one already exists, the run is added to it as a
+//! comment instead. Titles and bodies are redacted before they are sent.
+
+use crate::{
+    ErrorCategory, GleanMcpError, ProxyConfig, Result, RunRecord, ToolTestResult, base_tool_name,
+    redact,
+};
+use async_process::Command;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use smol::io::AsyncWriteExt;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::process::Stdio;
+
+/// Where issues are filed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IssueProvider {
+    Github,
+    Jira,
+}
+
+/// When and where persistent failures are filed as issues
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IssueFilingConfig {
+    /// Where issues are filed; unset disables issue filing
+    pub provider: Option<IssueProvider>,
+    /// Consecutive failing runs of a schedule before an issue is filed
+    pub after_failures: usize,
+    /// Link to a run, with `{run_id}` replaced by its id
+    pub run_url: Option<String>,
+    pub github: GithubIssueConfig,
+    pub jira: JiraIssueConfig,
+}
+
+impl Default for IssueFilingConfig {
+    fn default() -> Self {
+        Self {
+            provider: None,
+            after_failures: 3,
+            run_url: None,
+            github: GithubIssueConfig::default(),
+            jira: JiraIssueConfig::default(),
+        }
+    }
+}
+
+/// GitHub repository issues are filed in
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GithubIssueConfig {
+    /// Repository as `owner/name`
+    pub repo: String,
+    /// Labels added to opened issues
+    pub labels: Vec<String>,
+    /// Environment variable holding the API token
+    pub token_env: String,
+    pub api_url: String,
+}
+
+impl Default for GithubIssueConfig {
+    fn default() -> Self {
+        Self {
+            repo: String::new(),
+            labels: Vec::new(),
+            token_env: "GITHUB_TOKEN".to_string(),
+            api_url: "https://api.github.com".to_string(),
+        }
+    }
+}
+
+/// Jira project tickets are filed in
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct JiraIssueConfig {
+    /// Site URL, e.g. `https://example.atlassian.net`
+    pub base_url: String,
+    /// Project key
+    pub project: String,
+    pub issue_type: String,
+    /// Labels added to opened tickets
+    pub labels: Vec<String>,
+    /// Environment variable holding the account email
+    pub email_env: String,
+    /// Environment variable holding the API token
+    pub token_env: String,
+}
+
+impl Default for JiraIssueConfig {
+    fn default() -> Self {
+        Self {
+            base_url: String::new(),
+            project: String::new(),
+            issue_type: "Bug".to_string(),
+            labels: Vec::new(),
+            email_env: "JIRA_EMAIL".to_string(),
+            token_env: "JIRA_API_TOKEN".to_string(),
+        }
+    }
+}
+
+/// One run in a tool's failure streak
+#[derive(Debug, Clone)]
+pub struct FailedRun {
+    pub run_id: String,
+    pub started_at: String,
+    pub error: Option<String>,
+    pub request_reference: Option<String>,
+}
+
+/// A tool failing the same way in every one of a schedule's latest runs
+#[derive(Debug, Clone)]
+pub struct PersistentFailure {
+    pub tool: String,
+    pub category: ErrorCategory,
+    /// The streak's runs, newest first
+    pub runs: Vec<FailedRun>,
+}
+
+impl PersistentFailure {
+    /// Issue title, which is also the deduplication key
+    #[must_use]
+    pub fn title(&self) -> String {
+        format!(
+            "[glean-mcp-test] {} failing: {}",
+            self.tool,
+            self.category.as_str()
+        )
+    }
+}
+
+/// The first failed result of each tool in `record`, by base tool name
+fn failures_in(record: &RunRecord) -> BTreeMap<&str, &ToolTestResult> {
+    let mut failures = BTreeMap::new();
+    for (name, result) in record.tool_result.iter().flat_map(|r| &r.tool_results) {
+        if result.is_failure() && result.error_category.is_some() {
+            failures.entry(base_tool_name(name)).or_insert(result);
+        }
+    }
+    failures
+}
+
+/// Tools failing with the same category in at least the last `after` of `runs`
+///
+/// `runs` are one schedule's records, oldest first.
+#[must_use]
+pub fn persistent_failures(runs: &[RunRecord], after: usize) -> Vec<PersistentFailure> {
+    let after = after.max(1);
+    let history: Vec<_> = runs.iter().rev().map(failures_in).collect();
+    let Some(latest) = history.first() else {
+        return Vec::new();
+    };
+    let mut persistent = Vec::new();
+    for (tool, result) in latest {
+        let Some(category) = result.error_category else {
+            continue;
+        };
+        let streak: Vec<FailedRun> = runs
+            .iter()
+            .rev()
+            .zip(&history)
+            .map_while(|(record, failures)| {
+                failures
+                    .get(tool)
+                    .filter(|failed| failed.error_category == Some(category))
+                    .map(|failed| FailedRun {
+                        run_id: record.id.clone(),
+                        started_at: record.started_at.clone(),
+                        error: failed.error_message.clone(),
+                        request_reference: failed.request_reference(),
+                    })
+            })
+            .collect();
+        if streak.len() >= after {
+            persistent.push(PersistentFailure {
+                tool: (*tool).to_string(),
+                category,
+                runs: streak,
+            });
+        }
+    }
+    persistent
+}
+
+/// What filing a persistent failure did
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FiledIssue {
+    /// A new issue was opened, with its link or key
+    Opened(String),
+    /// The run was added to the open issue with this link or key
+    Updated(String),
+}
+
+/// Files persistent failures with the configured provider
+pub struct IssueFiler {
+    config: IssueFilingConfig,
+    proxy: ProxyConfig,
+}
+
+impl IssueFiler {
+    /// A filer for `config`, or `None` when no provider is set
+    #[must_use]
+    pub fn new(config: &IssueFilingConfig, proxy: ProxyConfig) -> Option<Self> {
+        config.provider.map(|_| Self {
+            config: config.clone(),
+            proxy,
+        })
+    }
+
+    #[must_use]
+    pub const fn after_failures(&self) -> usize {
+        self.config.after_failures
+    }
+
+    /// Open an issue for `failure`, or comment on the open one with the same title
+    ///
+    /// `record` is the latest run, whose instance, suite, and labels are
+    /// reported as the environment.
+    pub async fn file(
+        &self,
+        failure: &PersistentFailure,
+        record: &RunRecord,
+    ) -> Result<FiledIssue> {
+        // Errors can quote headers and tokens, which mustn't reach a third-party tracker
+        let title = redact(&failure.title()).into_owned();
+        let body = redact(&self.describe(failure, record)).into_owned();
+        match self.config.provider {
+            Some(IssueProvider::Github) => self.file_github(&title, &body).await,
+            Some(IssueProvider::Jira) => self.file_jira(&title, &body).await,
+            None => Err(GleanMcpError::Config(
+                "No issue provider configured".to_string(),
+            )),
+        }
+    }
+
+    /// Issue body or comment: the error, the failing runs, and the environment
+    fn describe(&self, failure: &PersistentFailure, record: &RunRecord) -> String {
+        let mut body = String::new();
+        let _ = writeln!(
+            body,
+            "{} has failed {} consecutive monitor runs ({}).",
+            failure.tool,
+            failure.runs.len(),
+            failure.category.as_str()
+        );
+        if let Some(latest) = failure.runs.first() {
+            let _ = writeln!(
+                body,
+                "\nError: {}",
+                latest.error.as_deref().unwrap_or("(no error message)")
+            );
+            if let Some(reference) = &latest.request_reference {
+                let _ = writeln!(body, "Request: {reference}");
+            }
+        }
+        let _ = writeln!(body, "\nFailing runs, newest first:");
+        for run in &failure.runs {
+            let _ = writeln!(
+                body,
+                "- {} at {}: {}",
+                self.run_link(&run.run_id),
+                run.started_at,
+                run.error.as_deref().unwrap_or("(no error message)")
+            );
+        }
+        let _ = writeln!(body, "\nEnvironment:");
+        let _ = writeln!(body, "- Instance: {}", record.instance);
+        let _ = writeln!(body, "- Suite: {}", record.suite);
+        if let Some(schedule) = &record.schedule {
+            let _ = writeln!(body, "- Schedule: {schedule}");
+        }
+        if !record.labels.is_empty() {
+            let labels: Vec<String> = record
+                .labels
+                .iter()
+                .map(|(key, value)| format!("{key}={value}"))
+                .collect();
+            let _ = writeln!(body, "- Labels: {}", labels.join(", "));
+        }
+        let _ = writeln!(
+            body,
+            "- glean-mcp-test {} on {}/{}",
+            env!("CARGO_PKG_VERSION"),
+            std::env::consts::OS,
+            std::env::consts::ARCH
+        );
+        body
+    }
+
+    /// `run_url` with the run id, or the command that shows the run
+    fn run_link(&self, id: &str) -> String {
+        self.config.run_url.as_ref().map_or_else(
+            || format!("{id} (glean-mcp-test history show {id})"),
+            |template| template.replace("{run_id}", id),
+        )
+    }
+
+    async fn file_github(&self, title: &str, body: &str) -> Result<FiledIssue> {
+        let github = &self.config.github;
+        let token = secret(&github.token_env)?;
+        let auth = vec![
+            "-H".to_string(),
+            format!("Authorization: Bearer {token}"),
+            "-H".to_string(),
+            "Accept: application/vnd.github+json".to_string(),
+        ];
+        let api = github.api_url.trim_end_matches('/');
+        let search = self
+            .request(
+                &format!("{api}/search/issues"),
+                &auth,
+                &[(
+                    "q",
+                    format!(
+                        "repo:{} is:issue is:open in:title \"{}\"",
+                        github.repo,
+                        title.replace('"', "")
+                    ),
+                )],
+                None,
+            )
+            .await?;
+        let existing = search
+            .get("items")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .find(|issue| issue.get("title").and_then(Value::as_str) == Some(title));
+        if let Some(issue) = existing {
+            let number = issue
+                .get("number")
+                .and_then(Value::as_u64)
+                .unwrap_or_default();
+            self.request(
+                &format!("{api}/repos/{}/issues/{number}/comments", github.repo),
+                &auth,
+                &[],
+                Some(&json!({ "body": body })),
+            )
+            .await?;
+            return Ok(FiledIssue::Updated(html_url(issue)));
+        }
+        let issue = self
+            .request(
+                &format!("{api}/repos/{}/issues", github.repo),
+                &auth,
+                &[],
+                Some(&json!({ "title": title, "body": body, "labels": github.labels })),
+            )
+            .await?;
+        Ok(FiledIssue::Opened(html_url(&issue)))
+    }
+
+    async fn file_jira(&self, title: &str, body: &str) -> Result<FiledIssue> {
+        let jira = &self.config.jira;
+        let auth = vec![
+            "-u".to_string(),
+            format!("{}:{}", secret(&jira.email_env)?, secret(&jira.token_env)?),
+        ];
+        let api = format!("{}/rest/api/2", jira.base_url.trim_end_matches('/'));
+        let escaped = title.replace('\\', "\\\\").replace('"', "\\\"");
+        let search = self
+            .request(
+                &format!("{api}/search"),
+                &auth,
+                &[
+                    (
+                        "jql",
+                        format!(
+                            "project = \"{}\" AND summary ~ \"{escaped}\" AND statusCategory != Done",
+                            jira.project
+                        ),
+                    ),
+                    ("fields", "summary".to_string()),
+                ],
+                None,
+            )
+            .await?;
+        let existing = search
+            .get("issues")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter(|issue| issue.pointer("/fields/summary").and_then(Value::as_str) == Some(title))
+            .find_map(|issue| issue.get("key").and_then(Value::as_str));
+        if let Some(key) = existing {
+            self.request(
+                &format!("{api}/issue/{key}/comment"),
+                &auth,
+                &[],
+                Some(&json!({ "body": body })),
+            )
+            .await?;
+            return Ok(FiledIssue::Updated(key.to_string()));
+        }
+        let issue = self
+            .request(
+                &format!("{api}/issue"),
+                &auth,
+                &[],
+                Some(&json!({
+                    "fields": {
+                        "project": { "key": jira.project },
+                        "summary": title,
+                        "description": body,
+                        "issuetype": { "name": jira.issue_type },
+                        "labels": jira.labels,
+                    }
+                })),
+            )
+            .await?;
+        Ok(FiledIssue::Opened(
+            issue
+                .get("key")
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_string(),
+        ))
+    }
+
+    /// Send a JSON request through curl: a GET with `query`, or a POST of `body`
+    async fn request(
+        &self,
+        url: &str,
+        auth: &[String],
+        query: &[(&str, String)],
+        body: Option<&Value>,
+    ) -> Result<Value> {
+        let mut args: Vec<String> = [
+            "-sS",
+            "-w",
+            "\n%{http_code}",
+            "-H",
+            "Content-Type: application/json",
+            "-H",
+            "User-Agent: glean-mcp-test",
+        ]
+        .map(String::from)
+        .to_vec();
+        args.extend_from_slice(auth);
+        args.extend(self.proxy.curl_args(url));
+        if body.is_some() {
+            args.extend(["--data-binary".to_string(), "@-".to_string()]);
+        } else {
+            args.push("--get".to_string());
+            for (name, value) in query {
+                args.extend(["--data-urlencode".to_string(), format!("{name}={value}")]);
+            }
+        }
+        args.push(url.to_string());
+
+        let mut child = Command::new("curl")
+            .args(&args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| GleanMcpError::Process(format!("Failed to spawn curl: {e}")))?;
+        if let Some(mut stdin) = child.stdin.take() {
+            if let Some(body) = body {
+                stdin.write_all(body.to_string().as_bytes()).await?;
+            }
+            stdin.close().await?;
+        }
+        let output = child.output().await?;
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let (response, status) = stdout.rsplit_once('\n').unwrap_or(("", &stdout));
+        let status: u16 = status.trim().parse().unwrap_or_default();
+        if status == 0 {
+            return Err(GleanMcpError::Network(format!(
+                "Request to {url} failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        if status >= 400 {
+            return Err(GleanMcpError::Http {
+                status,
+                message: format!("{url}: {}", response.trim()),
+                retry_after: None,
+            });
+        }
+        // A search answer that doesn't parse must not look like "no open issue"
+        if response.trim().is_empty() {
+            return Ok(Value::Null);
+        }
+        serde_json::from_str(response)
+            .map_err(|e| GleanMcpError::Network(format!("Unreadable response from {url}: {e}")))
+    }
+}
+
+/// The value of environment variable `name`
+fn secret(name: &str) -> Result<String> {
+    std::env::var(name)
+        .ok()
+        .filter(|value| !value.trim().is_empty())
+        .ok_or_else(|| GleanMcpError::Config(format!("Set ${name} to file issues")))
+}
+
+fn html_url(issue: &Value) -> String {
+    issue
+        .get("html_url")
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .to_string()
+}
@@ -2,17 +2,59 @@
 //!
 //! A comprehensive testing framework for validating Glean's MCP (Model Context Protocol)
 //! server functionality across all supported host applications.
+//!
+//! Services embedding the framework should use the semver-stable [`api`]
+//! module. The other modules are re-exported for the CLI and may change in
+//! any release.
 
+pub mod aggregate;
+pub mod api;
+pub mod baseline;
+pub mod bench;
+pub mod coverage;
+pub mod diagnose;
+pub mod doctor;
+pub mod fuzz;
+pub mod history;
 pub mod host_controllers;
+pub mod issues;
 pub mod mcp_inspector;
+pub mod mock_server;
+pub mod monitor;
+pub mod outcome;
+pub mod report;
+pub mod reporters;
+pub mod retention;
+pub mod schema;
+pub mod soak;
 pub mod utils;
 
+pub use aggregate::*;
+pub use baseline::*;
+pub use bench::*;
+pub use coverage::*;
+pub use diagnose::*;
+pub use doctor::*;
+pub use fuzz::*;
+pub use history::*;
 pub use host_controllers::*;
+pub use issues::*;
 pub use mcp_inspector::*;
+pub use mock_server::*;
+pub use monitor::*;
+pub use outcome::*;
+pub use report::*;
+pub use reporters::*;
+pub use retention::*;
+pub use schema::*;
+pub use soak::*;
 pub use utils::*;
 
 // Re-export the new test functionality
-pub use mcp_inspector::{AllToolsTestResult, TestAllOptions, ToolTestResult, run_test_all};
+pub use mcp_inspector::{
+    AllToolsTestResult, TestAllOptions, ToolTestResult, run_test_all, run_test_all_async,
+    run_test_all_with_reporter, run_test_all_with_reporter_async,
+};
 
 /// Main error type for the framework
 #[derive(thiserror::Error, Debug)]
@@ -32,6 +74,14 @@ pub enum GleanMcpError {
     #[error("Network error: {0}")]
     Network(String),
 
+    #[error("HTTP {status}: {message}")]
+    Http {
+        status: u16,
+        message: String,
+        /// Delay requested by the server's `Retry-After` header
+        retry_after: Option<std::time::Duration>,
+    },
+
     #[error("Validation error: {0}")]
     Validation(String),
 
@@ -43,6 +93,9 @@ pub enum GleanMcpError {
 
     #[error("Process error: {0}")]
     Process(String),
+
+    #[error("Database error: {0}")]
+    Database(#[from] rusqlite::Error),
 }
 
 pub type Result<T> = std::result::Result<T, GleanMcpError>;
@@ -1,10 +1,34 @@
-use clap::{Parser, Subcommand};
+use clap::{Args, Parser, Subcommand};
 use console::{Emoji, Term, style};
 use glean_mcp_test::{
-    GleanConfig, GleanMcpError, HostController, HostOperationResult, Result,
-    claude_code::ClaudeCodeController, run_list_tools, run_test_all, run_validation,
+    AgentCheckConfig, AnyHistoryStore, AuthStrategy, BaselineStore, BenchComparison, BenchOptions,
+    CancelReason, CancellationToken, Cassette, Chaos, ChaosConfig, ChaosFault, CorpusMode,
+    CoverageReport, CoverageSurface, DEFAULT_CONFIG_FILE, DEFAULT_SERVER_NAME, DiagnoseOptions,
+    Engine, FailOn, FlakinessTrend, FuzzOptions, GithubActions, GleanConfig, GleanMCPInspector,
+    GleanMcpError, HistoryStore, HostConfigFile, HostController, HostOperationResult,
+    INTERRUPTED_EXIT_CODE, LatencyTrend, MaxConcurrent, McpInspectorConfig, MockServer,
+    MockServerConfig, Monitor, ProgressMode, Redactor, RepeatReport, ReportServer, Reporter,
+    Result, ResultSort, RetentionReport, RunComparison, RunFilter, RunOutcome, RunRecord,
+    SUPPORTED_HOSTS, SchemaDocument, ServerIdentity, Shard, SkipReason, SnapshotCheck, SoakOptions,
+    SqliteHistoryStore, SuccessCriteria, TestQueryGenerator, ToolStats, ToolTestResult,
+    TranscriptCapture, TrendOptions, VersionRequirement, aggregate_results, analyze_flakiness,
+    analyze_latency, apply_retention, auth_token_variables, compare_host_to_direct,
+    configure_auth_tokens, configure_output, configure_progress, configure_redaction,
+    configure_tool_profile, create_host_controller, current_branch, endpoint_url, find_auth_token,
+    install_package, installed_package_version, json_schema, load_bench_baseline, load_result_file,
+    parse_chaos_fault, parse_chaos_rate, parse_corpus_mode, parse_duration_ms, parse_engine,
+    parse_fail_on, parse_fuzz_categories, parse_header, parse_label, parse_languages,
+    parse_max_concurrent, parse_progress_mode, parse_requirements, parse_result_sort,
+    parse_schema_document, parse_shard, parse_tool_arguments, parse_url_template,
+    parse_version_requirement, plan_hosts, primary_token_variable, progress_bar, quiet_output,
+    recently_passed, redact, redacted_json, reporter_for_format, run_bench, run_diagnosis,
+    run_doctor, run_fuzz, run_soak, save_bench_baseline, should_promote, test_hosts,
+    token_from_env, write_line, write_str,
 };
-use indicatif::{ProgressBar, ProgressStyle};
+use indicatif::{HumanBytes, ProgressStyle};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use std::time::Duration;
 
 // Define consistent emojis with fallbacks
@@ -18,6 +42,12 @@ static LOCK: Emoji<'_, '_> = Emoji("🔐 ", "[AUTH] ");
 static PARTY: Emoji<'_, '_> = Emoji("🎉 ", "[SUCCESS] ");
 static WARNING: Emoji<'_, '_> = Emoji("⚠️ ", "[WARN] ");
 
+/// Recent runs whose latencies feed `--dry-run` duration estimates
+const DRY_RUN_HISTORY_RUNS: usize = 20;
+
+/// Iteration cap for `--until-failure` without `--repeat`
+const UNTIL_FAILURE_MAX_ITERATIONS: u32 = 100;
+
 #[derive(Parser)]
 #[command(name = "glean-mcp-test")]
 #[command(
@@ -25,32 +55,100 @@ static WARNING: Emoji<'_, '_> = Emoji("⚠️ ", "[WARN] ");
 )]
 #[command(version)]
 struct Cli {
+    /// Path to a YAML config file (default: ./glean-mcp-test.yaml when present)
+    #[arg(long, global = true)]
+    config: Option<PathBuf>,
+
+    /// Send MCP requests through this proxy (overrides `proxy.url` and the proxy environment variables)
+    #[arg(long, value_name = "URL", global = true, conflicts_with = "no_proxy")]
+    proxy: Option<String>,
+
+    /// Connect to MCP servers directly, ignoring any configured or environment proxy
+    #[arg(long, global = true)]
+    no_proxy: bool,
+
+    /// PEM CA bundle to trust for MCP requests (overrides `tls.ca_cert`)
+    #[arg(long, value_name = "FILE", global = true)]
+    cacert: Option<PathBuf>,
+
+    /// PEM client certificate for mTLS (overrides `tls.client_cert`)
+    #[arg(long, value_name = "FILE", global = true)]
+    cert: Option<PathBuf>,
+
+    /// PEM private key for `--cert` (overrides `tls.client_key`)
+    #[arg(long, value_name = "FILE", global = true, requires = "cert")]
+    key: Option<PathBuf>,
+
+    /// MCP server URL, or a template with {instance} and {endpoint}, e.g. `https://glean.acme.internal/mcp/agents` (overrides `glean_instance.url_template`)
+    #[arg(long, value_name = "URL", global = true, value_parser = parse_url_template)]
+    server_url: Option<String>,
+
+    /// Extra header for every MCP request, e.g. `X-Glean-ActAs: user@example.com` (repeatable; overrides `headers`)
+    #[arg(long = "header", value_name = "NAME: VALUE", global = true, value_parser = parse_header)]
+    headers: Vec<(String, String)>,
+
+    /// Act as this user by sending `act_as.header` (default `X-Glean-ActAs`) on every MCP request; the token must be allowed to impersonate
+    #[arg(long, value_name = "EMAIL", global = true)]
+    act_as: Option<String>,
+
+    /// Only print results and errors: no progress output, emoji, or colors (implies `--no-color`)
+    #[arg(long, global = true)]
+    quiet: bool,
+
+    /// Disable colors, emoji, and progress bars (also enabled by a non-empty `NO_COLOR`)
+    #[arg(long, global = true)]
+    no_color: bool,
+
+    /// How test progress is shown: bars, dots, plain, or none (default: bars on a terminal, plain otherwise)
+    #[arg(long, global = true, value_name = "STYLE", value_parser = parse_progress_mode)]
+    progress: Option<ProgressMode>,
+
     #[command(subcommand)]
     command: Commands,
 }
 
 #[derive(Subcommand)]
 enum Commands {
-    /// Validate Glean MCP server using MCP Inspector
-    Inspect {
-        /// Glean instance name (default: scio-prod)
-        #[arg(short, long, default_value = "scio-prod")]
-        instance: String,
+    /// Test the Glean MCP server directly (inspect, list-tools, test-tool, test-all, chatgpt)
+    Server {
+        #[command(subcommand)]
+        command: ServerCommands,
+    },
 
-        /// Output format (json, text)
-        #[arg(short, long, default_value = "text")]
-        format: String,
+    /// Test Glean tools through host applications (verify, test, test-all, check, list)
+    Host {
+        #[command(subcommand)]
+        command: HostCommands,
     },
 
-    /// Show current configuration
+    /// Show, validate, or initialize configuration
     Config {
-        /// Show full configuration details
+        /// Show full configuration details (same as `config show --verbose`)
         #[arg(short, long)]
         verbose: bool,
+
+        #[command(subcommand)]
+        command: Option<ConfigCommands>,
+    },
+
+    /// Compare, diff, and serve recorded run reports
+    Report {
+        #[command(subcommand)]
+        command: ReportCommands,
     },
 
     /// Check system prerequisites
-    Prerequisites,
+    Prerequisites {
+        /// Install the MCP Inspector (at `mcp_inspector.version`) with npm when it's missing or the wrong version
+        #[arg(long)]
+        install_missing: bool,
+    },
+
+    /// Diagnose the host environment: CLIs, host MCP configs, server reachability, and the token
+    Doctor(DoctorArgs),
+
+    /// Diagnose one tool in depth: connectivity, auth, discovery, schema, queries of increasing complexity, latency, and invalid input
+    Diagnose(DiagnoseArgs),
 
     /// Test authentication with current environment variables
     Auth {
@@ -59,885 +157,4467 @@ enum Commands {
         instance: String,
     },
 
-    /// List available tools from the MCP server
-    ListTools {
-        /// Glean instance name (default: scio-prod)
-        #[arg(short, long, default_value = "scio-prod")]
-        instance: String,
+    /// Inspect recorded run history
+    History {
+        /// Read runs from this `SQLite` history database instead of the history directory (default: `monitor.history_db`)
+        #[arg(long, value_name = "PATH", global = true)]
+        history_db: Option<PathBuf>,
 
-        /// Output format (text, json)
-        #[arg(short, long, default_value = "text")]
-        format: String,
+        #[command(subcommand)]
+        command: HistoryCommands,
     },
 
-    /// Verify MCP servers are configured and list available tools in a host
-    VerifyHost {
-        /// Host application (claude-code, cursor, vscode, claude-desktop)
-        #[arg(short = 'H', long)]
-        host: String,
+    /// Analyze per-tool trends across recorded run history
+    Analyze {
+        /// Read runs from this `SQLite` history database instead of the history directory (default: `monitor.history_db`)
+        #[arg(long, value_name = "PATH", global = true)]
+        history_db: Option<PathBuf>,
+
+        #[command(subcommand)]
+        command: AnalyzeCommands,
+    },
+
+    /// Manage comparison baselines
+    Baseline {
+        #[command(subcommand)]
+        command: BaselineCommands,
+    },
+
+    /// Print the JSON Schemas of the JSON output
+    Schema {
+        #[command(subcommand)]
+        command: SchemaCommands,
+    },
+
+    /// Merge saved result files, e.g. from parallel CI shards, into one report
+    Aggregate {
+        /// `test --format json` result files, or directories of them
+        #[arg(value_name = "PATH", required = true)]
+        paths: Vec<PathBuf>,
 
         /// Output format (text, json)
         #[arg(short, long, default_value = "text")]
         format: String,
     },
 
-    /// Test a specific Glean tool through a host application
-    TestHostTool {
-        /// Host application (claude-code, cursor, vscode, claude-desktop)
-        #[arg(short = 'H', long)]
-        host: String,
+    /// Cross-reference the tools, endpoints, hosts, and protocol methods known to the framework with what recorded runs exercised
+    Coverage {
+        /// Cover only this run (repeatable; default: every run in the window)
+        #[arg(long = "run", value_name = "ID")]
+        runs: Vec<String>,
 
-        /// Tool name (`glean_search`, chat, `read_document`, etc.)
-        #[arg(short, long)]
-        tool: String,
+        /// Days of history to cover
+        #[arg(long, default_value = "30")]
+        days: u32,
+
+        /// Only include runs carrying this label (repeatable, all must match)
+        #[arg(long = "label", value_name = "KEY=VALUE", value_parser = parse_label)]
+        labels: Vec<(String, String)>,
 
-        /// Query to send to the tool
+        /// Only include runs triggered by this monitor schedule
+        #[arg(long)]
+        schedule: Option<String>,
+
+        /// Only include runs against this Glean instance
         #[arg(short, long)]
-        query: String,
+        instance: Option<String>,
+
+        /// Read runs from this `SQLite` history database instead of the history directory (default: `monitor.history_db`)
+        #[arg(long, value_name = "PATH")]
+        history_db: Option<PathBuf>,
 
         /// Output format (text, json)
         #[arg(short, long, default_value = "text")]
         format: String,
     },
 
-    /// Test all available Glean tools through a host application
-    TestAllHostTools {
-        /// Host application (claude-code, cursor, vscode, claude-desktop)
-        #[arg(short = 'H', long)]
-        host: String,
+    /// Prune old run history, artifacts, caches, and fixtures per the retention policy
+    Clean {
+        /// Keep only the newest N entries (overrides `retention.keep_runs`)
+        #[arg(long)]
+        keep_runs: Option<usize>,
 
-        /// Output format (text, json)
-        #[arg(short, long, default_value = "text")]
-        format: String,
+        /// Remove entries older than N days (overrides `retention.keep_days`)
+        #[arg(long)]
+        keep_days: Option<u64>,
+
+        /// List what would be removed without deleting anything
+        #[arg(long)]
+        dry_run: bool,
     },
 
-    /// Check if a host application is available
-    CheckHost {
-        /// Host application (claude-code, cursor, vscode, claude-desktop)
-        #[arg(short = 'H', long)]
-        host: String,
+    /// Run configured suites continuously on their cron schedules
+    Monitor {
+        /// Run every schedule once and exit instead of waiting for cron triggers
+        #[arg(long)]
+        once: bool,
 
         /// Output format (text, json)
         #[arg(short, long, default_value = "text")]
         format: String,
     },
 
-    /// List all configured MCP servers in a host application
-    ListHostServers {
-        /// Host application (claude-code, cursor, vscode, claude-desktop)
-        #[arg(short = 'H', long)]
-        host: String,
+    /// Load-test a single tool and report latency percentiles and throughput
+    Bench(BenchArgs),
 
-        /// Output format (text, json)
-        #[arg(short, long, default_value = "text")]
-        format: String,
+    /// Call tools with hostile arguments and check the server never breaks
+    Fuzz(FuzzArgs),
+
+    /// Test long-lived MCP sessions (soak, resume)
+    Session {
+        #[command(subcommand)]
+        command: SessionCommands,
+    },
+
+    /// Serve a mock Glean MCP server for framework self-tests
+    MockServer {
+        /// Address to listen on
+        #[arg(short, long, default_value = "127.0.0.1:8765")]
+        bind: String,
+
+        /// Mock server configuration (YAML): tools, canned responses, latencies, errors
+        #[arg(long = "mock-config", value_name = "FILE")]
+        mock_config: Option<PathBuf>,
     },
 
+    // Back-compatible aliases for the pre-namespace command names
+    /// Alias for `server inspect`
+    #[command(hide = true)]
+    Inspect(InspectArgs),
+
+    /// Alias for `server list-tools`
+    #[command(hide = true)]
+    ListTools(ListToolsArgs),
+
+    /// Alias for `server test-tool`
+    #[command(hide = true)]
+    TestTool(TestToolArgs),
+
+    /// Alias for `server test-all`
+    #[command(hide = true, alias = "test-all")]
+    Test(TestArgs),
+
+    /// Alias for `host verify`
+    #[command(hide = true)]
+    VerifyHost(HostArgs),
+
+    /// Alias for `host test`
+    #[command(hide = true)]
+    TestHostTool(HostToolArgs),
+
+    /// Alias for `host test-all`
+    #[command(hide = true)]
+    TestAllHostTools(HostArgs),
+
+    /// Alias for `host test-all-hosts`
+    #[command(hide = true)]
+    TestAllHosts(MultiHostArgs),
+
+    /// Alias for `host check`
+    #[command(hide = true)]
+    CheckHost(HostArgs),
+
+    /// Alias for `host list`
+    #[command(hide = true)]
+    ListHostServers(HostArgs),
+
+    /// Alias for `host configure`
+    #[command(hide = true)]
+    ConfigureHost(ConfigureHostArgs),
+
+    /// Alias for `host rollback`
+    #[command(hide = true)]
+    RollbackHost(HostArgs),
+
+    /// Alias for `report compare`
+    #[command(hide = true)]
+    Compare(CompareArgs),
+}
+
+// Parsed once per process, so variant size doesn't matter
+#[allow(clippy::large_enum_variant)]
+#[derive(Subcommand)]
+enum ServerCommands {
+    /// Validate Glean MCP server using MCP Inspector
+    Inspect(InspectArgs),
+
+    /// List available tools from the MCP server
+    ListTools(ListToolsArgs),
+
+    /// Test a single tool with a direct MCP call
+    TestTool(TestToolArgs),
+
     /// Test MCP tools and report status
-    Test {
-        /// Glean instance name (default: glean-dev)
-        #[arg(short, long, default_value = "glean-dev")]
-        instance: String,
+    #[command(visible_alias = "test")]
+    TestAll(TestArgs),
 
-        /// Test all tools including ChatGPT-specific tools
-        #[arg(long)]
-        all: bool,
+    /// Check the `ChatGPT` connector endpoint's tool set and compare it with the default endpoint
+    Chatgpt(ChatGptArgs),
+}
 
-        /// Comma-separated list of specific tools to test (mutually exclusive with --all)
-        #[arg(short, long)]
-        tools: Option<String>,
+#[derive(Subcommand)]
+enum HostCommands {
+    /// Verify MCP servers are configured and list available tools in a host
+    Verify(HostArgs),
 
-        /// Enable parallel testing
-        #[arg(short, long)]
-        parallel: bool,
+    /// Test a specific Glean tool through a host application
+    Test(HostToolArgs),
+
+    /// Test all available Glean tools through a host application
+    TestAll(HostArgs),
+
+    /// Test all Glean tools through several host applications, optionally in parallel
+    TestAllHosts(MultiHostArgs),
+
+    /// Run the same tools and queries directly and through a host, flagging host-layer failures, truncation, and mangling
+    Compare(HostCompareArgs),
+
+    /// Check if a host application is available
+    Check(HostArgs),
+
+    /// List all configured MCP servers in a host application
+    List(HostArgs),
 
-        /// Maximum concurrent tests when parallel is enabled
-        #[arg(long, default_value = "3")]
-        max_concurrent: usize,
+    /// Back up a host's MCP config file and add the Glean server to it
+    Configure(ConfigureHostArgs),
 
-        /// Timeout per tool test in seconds
-        #[arg(long, default_value = "60")]
-        timeout: u64,
+    /// Restore a host's MCP config file from the backup taken by `configure`
+    Rollback(HostArgs),
+}
 
-        /// Verbose output (show detailed results)
+#[derive(Subcommand)]
+enum ConfigCommands {
+    /// Show current configuration
+    Show {
+        /// Show full configuration details
         #[arg(short, long)]
         verbose: bool,
+    },
 
-        /// Debug output (show full tool response data)
-        #[arg(short, long)]
-        debug: bool,
+    /// Check the configuration file for errors, with file locations and fix hints
+    Validate {
+        /// Skip checks that need the network (server hostname resolution)
+        #[arg(long)]
+        offline: bool,
 
-        /// Number of retry attempts for failed tests (default: 4)
-        #[arg(long, default_value = "4")]
-        retry_attempts: u32,
+        /// Output format (text, json)
+        #[arg(short, long, default_value = "text")]
+        format: String,
+    },
 
-        /// Initial backoff time in seconds for retries with jitter (default: 5)
-        #[arg(long, default_value = "5")]
-        retry_backoff: u64,
+    /// Write a starter configuration file
+    Init {
+        /// Destination path
+        #[arg(default_value = DEFAULT_CONFIG_FILE)]
+        path: PathBuf,
 
-        /// Output results as JSON (default: text)
+        /// Overwrite an existing file
         #[arg(long)]
-        json: bool,
+        force: bool,
+    },
+}
 
-        /// Output file path (optional)
-        #[arg(short, long)]
-        output: Option<String>,
+#[derive(Subcommand)]
+enum ReportCommands {
+    /// Compare a recorded run against a baseline, or two result files (exits 7 on regressions)
+    Compare(CompareArgs),
+
+    /// Show tool-by-tool differences between two recorded runs
+    Diff {
+        /// Earlier run id
+        from: String,
+
+        /// Later run id
+        to: String,
+
+        /// Output format (text, json)
+        #[arg(short, long, default_value = "text")]
+        format: String,
+    },
+
+    /// Serve recorded runs as a browsable HTML/JSON report
+    Serve {
+        /// Address to listen on
+        #[arg(short, long, default_value = "127.0.0.1:8766")]
+        bind: String,
     },
 }
 
-fn main() {
-    let cli = Cli::parse();
+#[derive(Args)]
+struct InspectArgs {
+    /// Glean instance name (default: scio-prod)
+    #[arg(short, long, default_value = "scio-prod")]
+    instance: String,
 
-    // For async operations, use smol::block_on
-    if let Err(e) = smol::block_on(async { handle_command(cli.command).await }) {
-        let term = Term::stderr();
-        let _ = term.write_line(&format!(
-            "{}{}",
-            CROSS_MARK,
-            style(format!("Command failed: {e}")).red().bold()
-        ));
-        std::process::exit(1);
-    }
+    /// Output format (json, text)
+    #[arg(short, long, default_value = "text")]
+    format: String,
 }
 
-#[allow(clippy::cognitive_complexity)]
-async fn handle_command(command: Commands) -> Result<()> {
-    match command {
-        Commands::Inspect { instance, format } => {
-            let term = Term::stdout();
-            let _ = term.write_line(&format!(
-                "{}{}",
-                ROCKET,
-                style("Starting Glean MCP Inspector validation...")
-                    .cyan()
-                    .bold()
-            ));
-            let _ = term.write_line(&format!(
-                "{}{} {}",
-                CLIPBOARD,
-                style("Instance:").bold(),
-                style(&instance).cyan()
-            ));
+#[derive(Args)]
+struct ListToolsArgs {
+    /// Glean instance name (default: scio-prod)
+    #[arg(short, long, default_value = "scio-prod")]
+    instance: String,
 
-            match run_validation(Some(&instance)) {
-                Ok(result) => {
-                    if format == "json" {
-                        match serde_json::to_string_pretty(&result) {
-                            Ok(json_output) => println!("{json_output}"),
-                            Err(e) => {
-                                let _ = term.write_line(&format!(
-                                    "{}{}",
-                                    CROSS_MARK,
-                                    style(format!("Failed to serialize JSON: {e}")).red()
-                                ));
-                                std::process::exit(1);
-                            }
-                        }
-                    } else {
-                        print_enhanced_text_result(&result);
-                    }
+    /// Output format (text, json)
+    #[arg(short, long, default_value = "text")]
+    format: String,
+}
 
-                    let _ = term.write_line("");
-                    if result.success {
-                        let _ = term.write_line(&format!(
-                            "{}{}",
-                            PARTY,
-                            style("Validation completed successfully!").green().bold()
-                        ));
-                        let _ = term.write_line(&format!(
-                            "{}{}",
-                            ROCKET,
-                            style("Ready to proceed to host application testing").blue()
-                        ));
-                        std::process::exit(0);
-                    } else {
-                        let _ = term.write_line(&format!(
-                            "{}{}",
-                            CROSS_MARK,
-                            style("Validation failed!").red().bold()
-                        ));
-                        if let Some(error) = &result.error {
-                            let _ = term.write_line(&format!("Error: {}", style(error).red()));
-                        }
-                        std::process::exit(1);
-                    }
-                }
-                Err(e) => {
-                    let _ = term.write_line(&format!(
-                        "{}{}",
-                        CROSS_MARK,
-                        style(format!("Failed to run MCP Inspector: {e}")).red()
-                    ));
-                    std::process::exit(1);
-                }
-            }
-        }
+#[derive(Args)]
+struct ChatGptArgs {
+    /// Glean instance name (default: the configured `glean_instance` endpoints)
+    #[arg(short, long)]
+    instance: Option<String>,
 
-        Commands::Config { verbose } => {
-            let config = GleanConfig::default();
+    /// Output format (text, json)
+    #[arg(short, long, default_value = "text")]
+    format: String,
+}
 
-            let term = Term::stdout();
+#[derive(Args)]
+struct TestToolArgs {
+    /// Glean instance name (default: glean-dev)
+    #[arg(short, long, default_value = "glean-dev")]
+    instance: String,
 
-            if verbose {
-                match serde_yaml::to_string(&config) {
-                    Ok(config_yaml) => {
-                        let _ = term.write_line(&format!(
-                            "📋 {}\n{}",
-                            style("Current Configuration:").bold().underlined(),
-                            config_yaml
-                        ));
-                        let _ = term.write_line("");
-                        let _ = term.write_line(&format!(
-                            "{}{}",
-                            CHECKMARK,
-                            style("Configuration displayed successfully!")
-                                .green()
-                                .bold()
-                        ));
-                        std::process::exit(0);
-                    }
-                    Err(e) => {
-                        let term = Term::stderr();
-                        let _ = term.write_line(&format!(
-                            "{}{}",
-                            CROSS_MARK,
-                            style(format!("Failed to serialize config: {e}")).red()
-                        ));
-                        std::process::exit(1);
-                    }
-                }
-            } else {
-                let _ = term.write_line(&format!(
-                    "📋 {}: {}",
-                    style("Glean Instance").bold(),
-                    style(&config.glean_instance.name).cyan()
-                ));
-                let _ = term.write_line(&format!(
-                    "🔗 {}: {}",
-                    style("Server URL").bold(),
-                    style(&config.glean_instance.server_url).dim()
-                ));
-                let _ = term.write_line(&format!(
-                    "🔧 {}: {}",
-                    style("Inspector Package").bold(),
-                    style(&config.mcp_inspector.package).cyan()
-                ));
-                let _ = term.write_line(&format!(
-                    "🔑 {}: {}",
-                    style("Auth Method").bold(),
-                    style(&config.authentication.method).cyan()
-                ));
-                let _ = term.write_line(&format!(
-                    "📊 {}: {}",
-                    style("Core Tools").bold(),
-                    style(config.tools_to_test.core_tools.len().to_string()).cyan()
-                ));
-                let _ = term.write_line(&format!(
-                    "🏢 {}: {}",
-                    style("Enterprise Tools").bold(),
-                    style(config.tools_to_test.enterprise_tools.len().to_string()).cyan()
-                ));
-                let _ = term.write_line(&format!(
-                    "💻 {}: {}",
-                    style("Host Applications").bold(),
-                    style(config.host_applications.len().to_string()).cyan()
-                ));
-                let _ = term.write_line("");
-                let _ = term.write_line(&format!(
-                    "{}{}",
-                    CHECKMARK,
-                    style("Configuration displayed successfully!")
-                        .green()
-                        .bold()
-                ));
-                std::process::exit(0);
-            }
-        }
+    /// Tool name (search, chat, `read_document`, etc.)
+    #[arg(short, long)]
+    tool: String,
 
-        Commands::Prerequisites => match check_prerequisites_with_progress().await {
-            Ok(()) => {
-                let term = Term::stdout();
-                let _ = term.write_line("");
-                let _ = term.write_line(&format!(
-                    "{}{}",
-                    PARTY,
-                    style("Prerequisites check completed successfully!")
-                        .green()
-                        .bold()
-                ));
-                std::process::exit(0);
-            }
-            Err(e) => {
-                let term = Term::stderr();
-                let _ = term.write_line("");
-                let _ = term.write_line(&format!(
-                    "{}{}",
-                    CROSS_MARK,
-                    style(format!("Prerequisites check failed: {e}")).red()
-                ));
-                std::process::exit(1);
-            }
-        },
+    /// Query to send to the tool (default: the tool's standard test query)
+    #[arg(short, long)]
+    query: Option<String>,
 
-        Commands::Auth { instance } => {
-            let term = Term::stdout();
-            let _ = term.write_line(&format!(
-                "{}{} {}",
-                LOCK,
-                style("Testing authentication for Glean instance:")
-                    .cyan()
-                    .bold(),
-                style(&instance).yellow()
-            ));
+    /// Structured tool arguments as a JSON object, e.g. '{"query": "...", "filters": {...}}'
+    #[arg(long, value_name = "JSON", value_parser = parse_tool_arguments, conflicts_with = "query")]
+    args: Option<serde_json::Value>,
 
-            // Create progress bar for authentication steps
-            let auth_pb = ProgressBar::new(3);
-            auth_pb.set_style(ProgressStyle::with_template(
-                "{spinner:.green} [{elapsed_precise}] {bar:30.cyan/blue} {pos:>1}/{len:1} {msg}"
-            ).unwrap_or_else(|_| ProgressStyle::default_bar()));
-            auth_pb.enable_steady_tick(Duration::from_millis(100));
+    /// Output format (text, json)
+    #[arg(short, long, default_value = "text")]
+    format: String,
 
-            auth_pb.set_message("Checking environment variables...");
+    /// Call the tool N times and report its pass rate and latency spread
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+    repeat: Option<u32>,
 
-            // Check GLEAN_AUTH_TOKEN environment variable
-            let _ = term.write_line("");
-            let _ = term.write_line(&format!(
-                "{}{}",
-                MAGNIFYING_GLASS,
-                style("Checking GLEAN_AUTH_TOKEN environment variable:").bold()
-            ));
+    /// Stop repeating at the first failure (up to --repeat, default 100 calls)
+    #[arg(long)]
+    until_failure: bool,
 
-            #[allow(clippy::option_if_let_else)]
-            let found_token = if let Ok(value) = std::env::var("GLEAN_AUTH_TOKEN") {
-                let masked = if value.len() > 8 {
-                    format!("{}...{}", &value[..4], &value[value.len() - 4..])
-                } else {
-                    "***".to_string()
-                };
-                let _ = term.write_line(&format!(
-                    "  {}{} {}",
-                    CHECKMARK,
-                    style("GLEAN_AUTH_TOKEN:").green(),
-                    style(masked).dim()
-                ));
-                true
-            } else {
-                let _ = term.write_line(&format!(
-                    "  {}{}",
-                    CROSS_MARK,
-                    style("GLEAN_AUTH_TOKEN: not set").red()
-                ));
-                false
-            };
-            auth_pb.inc(1);
+    /// Client making the call: direct (HTTP), inspector (MCP Inspector CLI), or both, compared
+    #[arg(long, value_name = "ENGINE", value_parser = parse_engine, conflicts_with_all = ["repeat", "until_failure"])]
+    engine: Option<Engine>,
+}
 
-            if !found_token {
-                auth_pb.finish_with_message(
-                    style("❌ No authentication token found").red().to_string(),
-                );
-                let _ = term.write_line("");
-                let _ = term.write_line(&format!(
-                    "💡 {}",
-                    style("No authentication token found.").yellow()
-                ));
-                let _ = term.write_line(&format!(
-                    "   {}: {}",
-                    style("Set the Glean auth token").bold(),
-                    style("export GLEAN_AUTH_TOKEN=your_token_here").cyan()
-                ));
-                let _ = term.write_line("");
-                let _ = term.write_line(&format!("🔗 {}", style("For mise users:").bold()));
-                let _ = term.write_line(&format!(
-                    "   {}",
-                    style("mise set GLEAN_AUTH_TOKEN=your_token_here").cyan()
-                ));
-                std::process::exit(1);
-            }
+#[derive(Args)]
+#[allow(clippy::struct_excessive_bools)]
+struct TestArgs {
+    /// Glean instance name (default: glean-dev)
+    #[arg(short, long, default_value = "glean-dev")]
+    instance: String,
 
-            auth_pb.set_message("Testing server connection...");
-            auth_pb.inc(1);
+    /// Test all tools including ChatGPT-specific tools
+    #[arg(long)]
+    all: bool,
 
-            let _ = term.write_line("");
-            let _ = term.write_line(&format!(
+    /// Comma-separated list of specific tools to test, names or globs like `gmail_*` (mutually exclusive with --all)
+    #[arg(short, long)]
+    tools: Option<String>,
+
+    /// Skip tools whose names match these globs, e.g. `gmail_*,outlook_*` (added to `tools_to_test.exclude`)
+    #[arg(long, value_name = "GLOBS", value_delimiter = ',')]
+    exclude: Vec<String>,
+
+    /// Only test tools tagged with one of these tags in `tools_to_test.tags`, e.g. email,people (default suite: all)
+    #[arg(long = "tag", value_name = "TAGS", value_delimiter = ',')]
+    tags: Vec<String>,
+
+    /// Skip tools tagged with any of these tags
+    #[arg(long = "exclude-tag", value_name = "TAGS", value_delimiter = ',')]
+    exclude_tags: Vec<String>,
+
+    /// Enable parallel testing
+    #[arg(short, long)]
+    parallel: bool,
+
+    /// Maximum concurrent tests when parallel is enabled, or `auto` to start low and adapt to the server's 429s and 5xx (implies --parallel)
+    #[arg(long, default_value = "3", value_parser = parse_max_concurrent)]
+    max_concurrent: MaxConcurrent,
+
+    /// Timeout per tool test in seconds
+    #[arg(long, default_value = "60")]
+    timeout: u64,
+
+    /// Verbose output (show detailed results)
+    #[arg(short, long)]
+    verbose: bool,
+
+    /// Debug output (show full tool response data)
+    #[arg(short, long)]
+    debug: bool,
+
+    /// Number of attempts for tests failing with retryable errors (default: 4)
+    #[arg(long, default_value = "4")]
+    retry_attempts: u32,
+
+    /// Initial backoff time in seconds for retries with jitter (default: 5)
+    #[arg(long, default_value = "5")]
+    retry_backoff: u64,
+
+    /// Output results as JSON (same as `--format json`)
+    #[arg(long)]
+    json: bool,
+
+    /// Output format (text, summary, json, markdown)
+    #[arg(short, long, default_value = "text")]
+    format: String,
+
+    /// Order of the tools in the text report: name, duration (slowest first), or status (failures first)
+    #[arg(long, value_name = "ORDER", value_parser = parse_result_sort, default_value = "name")]
+    sort: ResultSort,
+
+    /// Output file path (optional)
+    #[arg(short, long)]
+    output: Option<String>,
+
+    /// Record every MCP request/response pair to a cassette file
+    #[arg(long, value_name = "CASSETTE", conflicts_with = "replay")]
+    record: Option<PathBuf>,
+
+    /// Replay MCP responses from a cassette file instead of the network
+    #[arg(long, value_name = "CASSETTE", conflicts_with = "mock")]
+    replay: Option<PathBuf>,
+
+    /// Write each tool's request/response transcript to `<DIR>/<run-id>/<tool>.json` (default: logs)
+    #[arg(long, value_name = "DIR", num_args = 0..=1, default_missing_value = "logs")]
+    capture_dir: Option<PathBuf>,
+
+    /// Attach a label to the run (repeatable, e.g. --label release=4.2)
+    #[arg(long = "label", value_name = "KEY=VALUE", value_parser = parse_label)]
+    labels: Vec<(String, String)>,
+
+    /// Don't record this run in the history directory
+    #[arg(long)]
+    no_history: bool,
+
+    /// Also record this run in a `SQLite` history database (default: `monitor.history_db`)
+    #[arg(long, value_name = "PATH", conflicts_with = "no_history")]
+    history_db: Option<PathBuf>,
+
+    /// Branch the run belongs to (default: detected from CI environment or git)
+    #[arg(long)]
+    branch: Option<String>,
+
+    #[command(flatten)]
+    mock: MockArgs,
+
+    /// Inject transport faults into this fraction (0.0-1.0) of tool calls
+    #[arg(long, value_name = "RATE", value_parser = parse_chaos_rate)]
+    chaos: Option<f64>,
+
+    /// Faults to inject with --chaos (timeout, truncated, server-error, malformed-json; default: all)
+    #[arg(long, value_name = "FAULTS", value_delimiter = ',', value_parser = parse_chaos_fault, requires = "chaos")]
+    chaos_faults: Vec<ChaosFault>,
+
+    /// Seed for a reproducible --chaos fault sequence
+    #[arg(long, value_name = "SEED", requires = "chaos")]
+    chaos_seed: Option<u64>,
+
+    /// Check that chat/search responses are in LANG (ISO 639-1), or in the query's language when omitted
+    #[arg(long, value_name = "LANG", num_args = 0..=1, default_missing_value = "auto")]
+    check_language: Option<String>,
+
+    /// Fail search results containing duplicate URLs/titles or near-identical snippets
+    #[arg(long)]
+    check_duplicates: bool,
+
+    /// Scan responses for SSNs, card numbers, keys, and the `pii_scan.patterns`, failing tools with findings unless `pii_scan.fail_on_findings` is off
+    #[arg(long)]
+    scan_pii: bool,
+
+    /// Don't fail search responses that are empty, lack title/url/snippet, or link outside `result_check.allowed_domains`
+    #[arg(long)]
+    skip_result_check: bool,
+
+    /// Compare each tool's normalized response shape against its stored snapshot, recording missing ones
+    #[arg(long)]
+    snapshot: bool,
+
+    /// Re-record every tool's snapshot from this run's responses
+    #[arg(long)]
+    update_snapshots: bool,
+
+    /// Flag responses larger than BYTES, which MCP hosts may truncate (default: `response_size.max_bytes`)
+    #[arg(long, value_name = "BYTES")]
+    max_response_bytes: Option<u64>,
+
+    /// Maximum tool calls per second across the run, backing off further on HTTP 429 (default: unlimited)
+    #[arg(long, value_name = "RPS")]
+    max_rps: Option<f64>,
+
+    /// YAML or CSV file of queries per tool, called in place of the generated test query (default: `corpus.file`)
+    #[arg(long, value_name = "FILE")]
+    corpus: Option<String>,
+
+    /// How runs use the corpus: round-robin (one query per run, rotating) or exhaustive (every query) (default: `corpus.mode`)
+    #[arg(long, value_name = "MODE", value_parser = parse_corpus_mode)]
+    corpus_mode: Option<CorpusMode>,
+
+    /// Tools whose failures fail the run: all, core, enterprise, or a comma-separated list
+    #[arg(long, value_name = "SCOPE", value_parser = parse_fail_on, default_value = "all")]
+    fail_on: FailOn,
+
+    /// Minimum success rate per category, e.g. core=100%,enterprise=80%; failures in a category meeting its threshold don't fail the run
+    #[arg(long, value_name = "THRESHOLDS", value_parser = parse_requirements)]
+    require: Option<SuccessCriteria>,
+
+    /// Send invalid requests (missing/mistyped arguments, unknown tool, oversized payload) and check the server rejects them with proper JSON-RPC errors
+    #[arg(long, conflicts_with_all = ["all", "tools"])]
+    negative: bool,
+
+    /// Follow `nextCursor` through every tools/list page and check search tools' result pages advance without repeating cursors
+    #[arg(long, conflicts_with_all = ["all", "tools", "negative"])]
+    pagination: bool,
+
+    /// Open one MCP session and send this many overlapping tools/call requests, checking every response carries its request's id
+    #[arg(long, value_name = "REQUESTS", conflicts_with_all = ["all", "tools", "negative", "pagination"])]
+    stress: Option<usize>,
+
+    /// Run the `permissions` config's queries under each privileged/restricted token pair and fail on results leaked to the restricted identity
+    #[arg(long, conflicts_with_all = ["all", "tools", "negative", "pagination", "stress"])]
+    permissions: bool,
+
+    /// Run the `act_as` config's tools and queries as each user (comma-separated emails, default: `act_as.users`) and compare the results each got back
+    #[arg(long, value_name = "USERS", num_args = 0..=1, default_missing_value = "", conflicts_with_all = ["all", "tools", "negative", "pagination", "stress", "permissions", "auth_challenge", "truncation", "i18n", "notifications", "dry_run", "repeat", "until_failure"])]
+    act_as_users: Option<String>,
+
+    /// Send missing, malformed, and expired tokens and check each is refused with HTTP 401 and a `WWW-Authenticate` challenge carrying `resource_metadata`
+    #[arg(long, conflicts_with_all = ["all", "tools", "negative", "pagination", "stress", "permissions"])]
+    auth_challenge: bool,
+
+    /// Read a deliberately large document through `read_document` and check the server truncates it to the response size limit (default URL: `response_size.large_document_url`)
+    #[arg(long, value_name = "URL", num_args = 0..=1, default_missing_value = "", conflicts_with_all = ["all", "tools", "negative", "pagination", "stress", "permissions", "auth_challenge"])]
+    truncation: Option<String>,
+
+    /// Send the standard queries in each language (comma-separated ISO 639-1 codes, default: `i18n.languages`) and report per-language pass rates
+    #[arg(long, value_name = "LANGUAGES", num_args = 0..=1, default_missing_value = "", conflicts_with_all = ["all", "tools", "negative", "pagination", "stress", "permissions", "auth_challenge", "truncation"])]
+    i18n: Option<String>,
+
+    /// Open one MCP session and read its event stream for up to SECONDS (default 30), checking every notification is well-formed JSON-RPC and `tools/list_changed` is only sent when advertised
+    #[arg(long, value_name = "SECONDS", num_args = 0..=1, default_missing_value = "30", value_parser = clap::value_parser!(u64).range(1..), conflicts_with_all = ["all", "tools", "negative", "pagination", "stress", "permissions", "auth_challenge", "truncation", "i18n"])]
+    notifications: Option<u64>,
+
+    /// Print the endpoints, tools, and queries the run would exercise, with an estimated duration, without contacting the server
+    #[arg(long, conflicts_with_all = ["negative", "pagination", "stress", "permissions", "auth_challenge", "truncation", "i18n", "notifications", "mock", "record", "replay"])]
+    dry_run: bool,
+
+    /// Run the suite N times and report each tool's pass rate and latency spread, to find flaky tools
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..), conflicts_with_all = ["negative", "pagination", "stress", "permissions", "auth_challenge", "truncation", "i18n", "notifications", "dry_run"])]
+    repeat: Option<u32>,
+
+    /// Stop repeating after the first iteration with a failing tool (up to --repeat, default 100 iterations)
+    #[arg(long, conflicts_with_all = ["negative", "pagination", "stress", "permissions", "auth_challenge", "truncation", "i18n", "notifications", "dry_run"])]
+    until_failure: bool,
+
+    /// Stop starting new tools and cancel calls in flight after the first failing tool
+    #[arg(long)]
+    fail_fast: bool,
+
+    /// Skip tools whose last recorded run on this instance passed within WINDOW (e.g. 30m, 1h), reporting them as cached passes
+    #[arg(long, value_name = "WINDOW", value_parser = parse_duration_ms, conflicts_with_all = ["dry_run", "repeat", "until_failure"])]
+    skip_recently_passed: Option<u64>,
+
+    /// When `tools/list` yields no tools, test the built-in default tool set instead of failing
+    #[arg(long)]
+    assume_default_tools: bool,
+
+    /// Endpoints to test: `all` (every endpoint in the config) or a comma-separated list of names [default: default,chatgpt]
+    #[arg(long, value_name = "NAMES", conflicts_with_all = ["negative", "pagination", "stress", "permissions", "auth_challenge", "truncation", "i18n", "notifications"])]
+    endpoint: Option<String>,
+
+    /// Agent id to run when testing `run_agent` (repeatable; overrides `agents.agents` in the config)
+    #[arg(long = "agent", value_name = "ID")]
+    agents: Vec<String>,
+
+    /// Fail the run unless the server's `initialize` version satisfies this, e.g. >=2.4 (also >, <=, <, =)
+    #[arg(long, value_name = "REQUIREMENT", value_parser = parse_version_requirement, conflicts_with_all = ["negative", "pagination", "stress", "permissions", "auth_challenge", "truncation", "i18n", "notifications", "dry_run", "repeat", "until_failure"])]
+    expect_server_version: Option<VersionRequirement>,
+
+    /// Run only part INDEX of the tools split COUNT ways, e.g. 2/5, to spread a run across CI runners (merge with `aggregate`)
+    #[arg(long, value_name = "INDEX/COUNT", value_parser = parse_shard, conflicts_with_all = ["negative", "pagination", "stress", "permissions", "auth_challenge", "truncation", "i18n", "notifications"])]
+    shard: Option<Shard>,
+
+    /// Emit GitHub Actions annotations for failed tools and append a Markdown summary to `$GITHUB_STEP_SUMMARY`
+    #[arg(long)]
+    gha: bool,
+}
+
+/// `--mock`/`--mock-config`, shared by every command that can run against the mock server
+#[derive(Args)]
+struct MockArgs {
+    /// Run against a built-in mock MCP server instead of the Glean instance
+    #[arg(id = "mock", long = "mock")]
+    enabled: bool,
+
+    /// Mock server configuration (YAML) used with --mock
+    #[arg(
+        id = "mock_config",
+        long = "mock-config",
+        value_name = "FILE",
+        requires = "mock"
+    )]
+    config: Option<PathBuf>,
+}
+
+#[derive(Args)]
+struct BenchArgs {
+    /// Glean instance name (default: glean-dev)
+    #[arg(short, long, default_value = "glean-dev")]
+    instance: String,
+
+    /// Tool to call
+    #[arg(short, long, default_value = "search")]
+    tool: String,
+
+    /// Query to send (default: the tool's standard test query)
+    #[arg(short, long)]
+    query: Option<String>,
+
+    /// Requests kept in flight at once
+    #[arg(short, long, default_value = "4")]
+    concurrency: usize,
+
+    /// How long to generate load, in seconds
+    #[arg(short, long, default_value = "30")]
+    duration: u64,
+
+    /// Maximum requests started per second across all workers (default: unlimited)
+    #[arg(long)]
+    rps: Option<f64>,
+
+    /// Timeout per request in seconds
+    #[arg(long, default_value = "30")]
+    timeout: u64,
+
+    /// Compare with the benchmark saved in FILE, failing when p95 latency or the error rate regressed past the `bench` thresholds
+    #[arg(long, value_name = "FILE")]
+    baseline: Option<PathBuf>,
+
+    /// Save this run to the --baseline file instead of comparing with it
+    #[arg(long, requires = "baseline")]
+    save_baseline: bool,
+
+    /// Output format (text, json)
+    #[arg(short, long, default_value = "text")]
+    format: String,
+
+    #[command(flatten)]
+    mock: MockArgs,
+}
+
+#[derive(Args)]
+struct DoctorArgs {
+    /// Glean instance whose server is probed besides the host server URLs (default: glean-dev)
+    #[arg(short, long, default_value = "glean-dev")]
+    instance: String,
+
+    /// Timeout per server probe in seconds
+    #[arg(long, default_value = "10")]
+    timeout: u64,
+
+    /// Output format (text, json)
+    #[arg(short, long, default_value = "text")]
+    format: String,
+}
+
+#[derive(Args)]
+struct DiagnoseArgs {
+    /// Glean instance name (default: glean-dev)
+    #[arg(short, long, default_value = "glean-dev")]
+    instance: String,
+
+    /// Tool to diagnose
+    #[arg(short, long)]
+    tool: String,
+
+    /// Calls made to sample the tool's latency (0 skips them)
+    #[arg(long, default_value = "5")]
+    samples: usize,
+
+    /// Timeout per request in seconds
+    #[arg(long, default_value = "30")]
+    timeout: u64,
+
+    /// Don't send the invalid-input cases
+    #[arg(long)]
+    skip_negative: bool,
+
+    /// Output format (text, json)
+    #[arg(short, long, default_value = "text")]
+    format: String,
+
+    #[command(flatten)]
+    mock: MockArgs,
+}
+
+#[derive(Args)]
+struct FuzzArgs {
+    /// Glean instance name (default: glean-dev)
+    #[arg(short, long, default_value = "glean-dev")]
+    instance: String,
+
+    /// Comma-separated tools to fuzz
+    #[arg(
+        short,
+        long,
+        default_value = "search,chat,read_document",
+        value_delimiter = ','
+    )]
+    tools: Vec<String>,
+
+    /// Comma-separated payload categories (emoji, rtl, control, huge, json, injection; default: all)
+    #[arg(short, long)]
+    categories: Option<String>,
+
+    /// Timeout per request in seconds
+    #[arg(long, default_value = "30")]
+    timeout: u64,
+
+    /// Output format (text, json)
+    #[arg(short, long, default_value = "text")]
+    format: String,
+
+    #[command(flatten)]
+    mock: MockArgs,
+}
+
+#[derive(Subcommand)]
+enum SessionCommands {
+    /// Hold one session open, pinging it and calling a tool periodically, and check it isn't dropped and latencies don't degrade
+    Soak(SoakArgs),
+
+    /// Drop a streamed tool call mid-stream, then reconnect with `Last-Event-ID` and re-initialize, reporting whether the server supports resumption
+    Resume(ResumeArgs),
+
+    /// Start a slow tool call, send `notifications/cancelled` for it, and check the server stops work and the session stays usable
+    Cancel(CancelArgs),
+}
+
+#[derive(Args)]
+struct CancelArgs {
+    /// Glean instance name (default: glean-dev)
+    #[arg(short, long, default_value = "glean-dev")]
+    instance: String,
+
+    /// Tool to call and cancel
+    #[arg(short, long, default_value = "chat")]
+    tool: String,
+
+    /// Query to send (default: the tool's standard test query)
+    #[arg(short, long)]
+    query: Option<String>,
+
+    /// How long after starting the call to cancel it
+    #[arg(long, default_value = "1s", value_parser = parse_duration_ms)]
+    cancel_after: u64,
+
+    /// How long to watch the call after cancelling it
+    #[arg(long, default_value = "20s", value_parser = parse_duration_ms)]
+    wait: u64,
+
+    /// Output format (text, json)
+    #[arg(short, long, default_value = "text")]
+    format: String,
+
+    #[command(flatten)]
+    mock: MockArgs,
+}
+
+#[derive(Args)]
+struct ResumeArgs {
+    /// Glean instance name (default: glean-dev)
+    #[arg(short, long, default_value = "glean-dev")]
+    instance: String,
+
+    /// How long to read each stream before giving up on it
+    #[arg(long, default_value = "30s", value_parser = parse_duration_ms)]
+    wait: u64,
+
+    /// Output format (text, json)
+    #[arg(short, long, default_value = "text")]
+    format: String,
+
+    #[command(flatten)]
+    mock: MockArgs,
+}
+
+#[derive(Args)]
+struct SoakArgs {
+    /// Glean instance name (default: glean-dev)
+    #[arg(short, long, default_value = "glean-dev")]
+    instance: String,
+
+    /// How long to keep the session open, e.g. 30m or 4h
+    #[arg(short, long, default_value = "10m", value_parser = parse_duration_ms)]
+    duration: u64,
+
+    /// Time between `ping` requests
+    #[arg(long, default_value = "30s", value_parser = parse_duration_ms)]
+    ping_interval: u64,
+
+    /// Time between tool calls
+    #[arg(long, default_value = "5m", value_parser = parse_duration_ms)]
+    call_interval: u64,
+
+    /// Tool to call
+    #[arg(short, long, default_value = "search")]
+    tool: String,
+
+    /// Query to send (default: the tool's standard test query)
+    #[arg(short, long)]
+    query: Option<String>,
+
+    /// Mark the run degraded when late median latency exceeds early median latency by this factor
+    #[arg(long, default_value = "2.0")]
+    max_latency_growth: f64,
+
+    /// Output format (text, json)
+    #[arg(short, long, default_value = "text")]
+    format: String,
+
+    #[command(flatten)]
+    mock: MockArgs,
+}
+
+#[derive(Args)]
+struct HostArgs {
+    /// Host application (claude-code, gemini, codex)
+    #[arg(short = 'H', long)]
+    host: String,
+
+    /// Output format (text, json)
+    #[arg(short, long, default_value = "text")]
+    format: String,
+}
+
+#[derive(Args)]
+struct ConfigureHostArgs {
+    /// Host application with an MCP config file (cursor, vscode, claude-desktop)
+    #[arg(short = 'H', long)]
+    host: String,
+
+    /// Glean instance to point the host at (default: the host's configured server URL)
+    #[arg(short, long)]
+    instance: Option<String>,
+
+    /// Name of the MCP server entry
+    #[arg(long, default_value = DEFAULT_SERVER_NAME)]
+    server_name: String,
+
+    /// Show the entry that would be written without changing anything
+    #[arg(long)]
+    dry_run: bool,
+
+    /// Output format (text, json)
+    #[arg(short, long, default_value = "text")]
+    format: String,
+}
+
+#[derive(Args)]
+struct MultiHostArgs {
+    /// Host applications to test (repeatable; default: every supported host)
+    #[arg(short = 'H', long = "host")]
+    hosts: Vec<String>,
+
+    /// Test hosts concurrently
+    #[arg(long)]
+    parallel: bool,
+
+    /// Output format (text, json)
+    #[arg(short, long, default_value = "text")]
+    format: String,
+
+    /// Print the hosts, tools, and queries the sweep would exercise, with an estimated duration, without running any agent
+    #[arg(long)]
+    dry_run: bool,
+}
+
+#[derive(Args)]
+struct HostCompareArgs {
+    /// Host application (claude-code, gemini, codex)
+    #[arg(short = 'H', long)]
+    host: String,
+
+    /// Compare only this tool, under the name the host uses (default: the host's test cases)
+    #[arg(short, long)]
+    tool: Option<String>,
+
+    /// Query to send (default: the host's sample query, or a generated one for --tool)
+    #[arg(short, long)]
+    query: Option<String>,
+
+    /// Glean instance the direct calls go to
+    #[arg(short, long, default_value = "glean-dev")]
+    instance: String,
+
+    /// Seconds to wait for each direct call
+    #[arg(long, default_value = "60")]
+    timeout: u64,
+
+    /// Output format (text, json)
+    #[arg(short, long, default_value = "text")]
+    format: String,
+}
+
+#[derive(Args)]
+struct HostToolArgs {
+    /// Host application (claude-code, gemini, codex)
+    #[arg(short = 'H', long)]
+    host: String,
+
+    /// Tool name (`glean_search`, chat, `read_document`, etc.)
+    #[arg(short, long)]
+    tool: String,
+
+    /// Query to send to the tool
+    #[arg(short, long)]
+    query: String,
+
+    /// Output format (text, json)
+    #[arg(short, long, default_value = "text")]
+    format: String,
+}
+
+#[derive(Args)]
+struct CompareArgs {
+    /// Run id to compare (default: most recent run in history), or the earlier of two result files
+    run: Option<String>,
+
+    /// Later `test --format json` result file, compared against the file given first
+    #[arg(
+        value_name = "RESULT_FILE",
+        requires = "run",
+        conflicts_with = "baseline"
+    )]
+    against: Option<PathBuf>,
+
+    /// Baseline run id (default: latest promoted baseline for the run's instance and suite)
+    #[arg(short, long)]
+    baseline: Option<String>,
+
+    /// Output format (text, json)
+    #[arg(short, long, default_value = "text")]
+    format: String,
+}
+
+#[derive(Subcommand)]
+enum HistoryCommands {
+    /// List recorded runs, newest first
+    List {
+        /// Only show runs carrying this label (repeatable, all must match)
+        #[arg(long = "label", value_name = "KEY=VALUE", value_parser = parse_label)]
+        labels: Vec<(String, String)>,
+
+        /// Only show runs triggered by this monitor schedule
+        #[arg(long)]
+        schedule: Option<String>,
+
+        /// Only show runs against this Glean instance
+        #[arg(short, long)]
+        instance: Option<String>,
+
+        /// Maximum number of runs to show
+        #[arg(short = 'n', long, default_value = "20")]
+        limit: usize,
+
+        /// Output format (text, json)
+        #[arg(short, long, default_value = "text")]
+        format: String,
+    },
+
+    /// Show a recorded run
+    Show {
+        /// Run id to show
+        run: String,
+
+        /// Output format (text, json)
+        #[arg(short, long, default_value = "text")]
+        format: String,
+    },
+
+    /// Per-tool availability and flakiness over the most recent runs
+    Stats {
+        /// Number of most recent runs to analyze
+        #[arg(short = 'n', long, default_value = "20")]
+        last: usize,
+
+        /// Only include runs carrying this label (repeatable, all must match)
+        #[arg(long = "label", value_name = "KEY=VALUE", value_parser = parse_label)]
+        labels: Vec<(String, String)>,
+
+        /// Only include runs triggered by this monitor schedule
+        #[arg(long)]
+        schedule: Option<String>,
+
+        /// Only include runs against this Glean instance
+        #[arg(short, long)]
+        instance: Option<String>,
+
+        /// Output format (text, json)
+        #[arg(short, long, default_value = "text")]
+        format: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum AnalyzeCommands {
+    /// Per-tool pass rate and flakiness, flagging tools whose pass rate changed recently
+    Flakiness(AnalyzeArgs),
+
+    /// Per-tool latency percentiles, flagging tools whose p95 changed recently
+    Latency(AnalyzeArgs),
+}
+
+#[derive(Args)]
+struct AnalyzeArgs {
+    /// Days of history to analyze
+    #[arg(long, default_value = "30")]
+    days: u32,
+
+    /// Compare the last N days against the rest of the window
+    #[arg(long, default_value = "7")]
+    recent_days: u32,
+
+    /// Change that counts as significant: percentage points of pass rate (default: 10) or percent of p95 latency (default: 50)
+    #[arg(long)]
+    threshold: Option<f64>,
+
+    /// Only include runs carrying this label (repeatable, all must match)
+    #[arg(long = "label", value_name = "KEY=VALUE", value_parser = parse_label)]
+    labels: Vec<(String, String)>,
+
+    /// Only include runs triggered by this monitor schedule
+    #[arg(long)]
+    schedule: Option<String>,
+
+    /// Only include runs against this Glean instance
+    #[arg(short, long)]
+    instance: Option<String>,
+
+    /// Output format (text, json, csv)
+    #[arg(short, long, default_value = "text")]
+    format: String,
+}
+
+#[derive(Subcommand)]
+enum BaselineCommands {
+    /// Promote a recorded run to the baseline for its instance and suite
+    Promote {
+        /// Run id to promote
+        run: String,
+    },
+
+    /// List baseline promotions, newest first
+    List,
+}
+
+#[derive(Subcommand)]
+enum SchemaCommands {
+    /// Print the JSON Schema of a JSON output document
+    Print {
+        /// Document: test-result (`test --format json`) or inspector-result (`inspect --format json`)
+        #[arg(value_parser = parse_schema_document, default_value = "test-result")]
+        document: SchemaDocument,
+    },
+}
+
+fn main() {
+    let cli = Cli::parse();
+    configure_output(cli.quiet, cli.no_color);
+    if let Some(mode) = cli.progress {
+        configure_progress(mode);
+    }
+
+    // For async operations, use smol::block_on
+    let result = GleanConfig::load_or_default(cli.config.as_deref())
+        .map(|mut config| {
+            if let Some(proxy) = &cli.proxy {
+                config.proxy = config.proxy.with_url(proxy);
+            } else if cli.no_proxy {
+                config.proxy = config.proxy.direct();
+            }
+            if let Some(cacert) = &cli.cacert {
+                config.tls.ca_cert = Some(cacert.clone());
+            }
+            if let Some(cert) = &cli.cert {
+                config.tls.client_cert = Some(cert.clone());
+                config.tls.client_key.clone_from(&cli.key);
+            }
+            if let Some(server_url) = &cli.server_url {
+                let instance = &config.glean_instance.name;
+                config.glean_instance.server_url = endpoint_url(server_url, instance, "default");
+                config.glean_instance.chatgpt_url = endpoint_url(server_url, instance, "chatgpt");
+                config.glean_instance.url_template.clone_from(server_url);
+            }
+            for (name, value) in &cli.headers {
+                config
+                    .headers
+                    .retain(|existing, _| !existing.eq_ignore_ascii_case(name));
+                config.headers.insert(name.clone(), value.clone());
+            }
+            if let Some(user) = &cli.act_as {
+                let header = config.act_as.header.clone();
+                config
+                    .headers
+                    .retain(|existing, _| !existing.eq_ignore_ascii_case(&header));
+                config.headers.insert(header, user.clone());
+            }
+            config
+        })
+        .and_then(|config| {
+            let redactor = Redactor::for_config(&config).or_else(|e| {
+                // `config` commands still run, so `config validate` can point at the bad pattern
+                if !matches!(cli.command, Commands::Config { .. }) {
+                    return Err(e);
+                }
+                let mut config = config.clone();
+                config.redaction.patterns.clear();
+                Redactor::for_config(&config)
+            })?;
+            configure_redaction(redactor);
+            configure_auth_tokens(config.authentication.clone());
+            configure_tool_profile(config.profile.clone());
+            Ok(config)
+        })
+        .and_then(|config| smol::block_on(Box::pin(handle_command(cli.command, config))));
+    if let Err(e) = result {
+        let term = Term::stderr();
+        write_line(
+            &term,
+            &format!(
                 "{}{}",
-                ROCKET,
-                style("Running authentication test...").cyan()
-            ));
+                CROSS_MARK,
+                style(format!("Command failed: {e}")).red().bold()
+            ),
+        );
+        std::process::exit(RunOutcome::for_error(&e).exit_code());
+    }
+}
+
+#[allow(clippy::future_not_send)]
+async fn handle_command(command: Commands, config: GleanConfig) -> Result<()> {
+    match command {
+        Commands::Server { command } => handle_server(command, &config).await,
+        Commands::Host { command } => handle_host(command, &config).await,
+        Commands::Config { verbose, command } => handle_config(command, verbose, &config),
+        Commands::Report { command } => handle_report(command, &config).await,
+
+        Commands::Prerequisites { install_missing } => {
+            match check_prerequisites_with_progress(
+                &config.mcp_inspector,
+                &config.glean_instance.name,
+                install_missing,
+            )
+            .await
+            {
+                Ok(()) => {
+                    let term = Term::stdout();
+                    write_line(&term, "");
+                    write_line(
+                        &term,
+                        &format!(
+                            "{}{}",
+                            PARTY,
+                            style("Prerequisites check completed successfully!")
+                                .green()
+                                .bold()
+                        ),
+                    );
+                    std::process::exit(0);
+                }
+                Err(e) => {
+                    let term = Term::stderr();
+                    write_line(&term, "");
+                    write_line(
+                        &term,
+                        &format!(
+                            "{}{}",
+                            CROSS_MARK,
+                            style(format!("Prerequisites check failed: {e}")).red()
+                        ),
+                    );
+                    std::process::exit(1);
+                }
+            }
+        }
+
+        Commands::Doctor(args) => doctor(args, &config).await,
+
+        Commands::Diagnose(args) => diagnose(args, &config).await,
+
+        Commands::Auth { instance } => {
+            let term = Term::stdout();
+            write_line(
+                &term,
+                &format!(
+                    "{}{} {}",
+                    LOCK,
+                    style("Testing authentication for Glean instance:")
+                        .cyan()
+                        .bold(),
+                    style(&instance).yellow()
+                ),
+            );
+
+            // Create progress bar for authentication steps
+            let auth_pb = progress_bar(3);
+            auth_pb.set_style(ProgressStyle::with_template(
+                "{spinner:.green} [{elapsed_precise}] {bar:30.cyan/blue} {pos:>1}/{len:1} {msg}"
+            ).unwrap_or_else(|_| ProgressStyle::default_bar()));
+            auth_pb.enable_steady_tick(Duration::from_millis(100));
+
+            auth_pb.set_message("Checking environment variables...");
+
+            // Check every variable the instance's token may come from, in lookup order
+            let variables = auth_token_variables(&instance);
+            write_line(&term, "");
+            write_line(
+                &term,
+                &format!(
+                    "{}{}",
+                    MAGNIFYING_GLASS,
+                    style("Checking auth token environment variables, in lookup order:").bold()
+                ),
+            );
+
+            let found_token = find_auth_token(&instance);
+            for variable in &variables {
+                let line = match token_from_env(variable) {
+                    Some(token) if found_token.as_ref() == Some(&token) => format!(
+                        "  {}{} {} {}",
+                        CHECKMARK,
+                        style(format!("{variable}:")).green(),
+                        style(token.masked()).dim(),
+                        style("(used)").green()
+                    ),
+                    Some(token) => format!(
+                        "  {}{} {} {}",
+                        WARNING,
+                        style(format!("{variable}:")).yellow(),
+                        style(token.masked()).dim(),
+                        style(format!(
+                            "(ignored: {} comes first)",
+                            found_token
+                                .as_ref()
+                                .map_or("", |used| used.variable.as_str())
+                        ))
+                        .dim()
+                    ),
+                    None => format!(
+                        "  {}{}",
+                        CROSS_MARK,
+                        style(format!("{variable}: not set")).red()
+                    ),
+                };
+                write_line(&term, &line);
+            }
+            auth_pb.inc(1);
+
+            if found_token.is_none() {
+                let variable = primary_token_variable();
+                auth_pb.finish_with_message(
+                    style("❌ No authentication token found").red().to_string(),
+                );
+                write_line(&term, "");
+                write_line(
+                    &term,
+                    &format!("💡 {}", style("No authentication token found.").yellow()),
+                );
+                write_line(
+                    &term,
+                    &format!(
+                        "   {}: {}",
+                        style("Set the Glean auth token").bold(),
+                        style(format!("export {variable}=your_token_here")).cyan()
+                    ),
+                );
+                write_line(&term, "");
+                write_line(&term, &format!("🔗 {}", style("For mise users:").bold()));
+                write_line(
+                    &term,
+                    &format!(
+                        "   {}",
+                        style(format!("mise set {variable}=your_token_here")).cyan()
+                    ),
+                );
+                std::process::exit(1);
+            }
+
+            auth_pb.set_message("Testing server connection...");
+            auth_pb.inc(1);
+
+            write_line(&term, "");
+            write_line(
+                &term,
+                &format!(
+                    "{}{}",
+                    ROCKET,
+                    style("Running authentication test...").cyan()
+                ),
+            );
+
+            match inspector_for(&config, &instance, reporter_for_format("text"))
+                .validate_server_with_inspector()
+                .await
+            {
+                Ok(result) => {
+                    auth_pb.inc(1);
+
+                    if result.success {
+                        auth_pb.finish_with_message(format!(
+                            "{}{}",
+                            CHECKMARK,
+                            style("Authentication successful").green()
+                        ));
+                        report_clock_skew(&instance, &config).await;
+                        write_line(&term, "");
+                        write_line(
+                            &term,
+                            &format!(
+                                "{}{}",
+                                PARTY,
+                                style("Authentication test successful!").green().bold()
+                            ),
+                        );
+                        std::process::exit(0);
+                    } else {
+                        auth_pb.finish_with_message(
+                            style("❌ Authentication failed").red().to_string(),
+                        );
+                        write_line(&term, "");
+                        write_line(
+                            &term,
+                            &format!(
+                                "{}{}",
+                                CROSS_MARK,
+                                style("Authentication test failed!").red().bold()
+                            ),
+                        );
+                        if let Some(error) = &result.error {
+                            write_line(&term, &format!("Error: {}", style(error).red()));
+                        }
+                        report_clock_skew(&instance, &config).await;
+                        std::process::exit(1);
+                    }
+                }
+                Err(e) => {
+                    auth_pb
+                        .finish_with_message(style("❌ Test execution failed").red().to_string());
+                    write_line(
+                        &term,
+                        &format!(
+                            "{}{}",
+                            CROSS_MARK,
+                            style(format!("Failed to run authentication test: {e}")).red()
+                        ),
+                    );
+                    std::process::exit(1);
+                }
+            }
+        }
+
+        Commands::MockServer { bind, mock_config } => {
+            let mock_config = mock_config
+                .as_deref()
+                .map_or_else(|| Ok(MockServerConfig::default()), MockServerConfig::load)?;
+            let tool_count = mock_config.tools.len();
+            let server = MockServer::start(&bind, mock_config).await?;
+
+            let term = Term::stdout();
+            write_line(
+                &term,
+                &format!(
+                    "{}{}",
+                    ROCKET,
+                    style(format!("Mock MCP server serving {tool_count} tool(s)"))
+                        .cyan()
+                        .bold()
+                ),
+            );
+            write_line(
+                &term,
+                &format!(
+                    "{}{} {}",
+                    CLIPBOARD,
+                    style("Default endpoint:").bold(),
+                    style(server.endpoint_url("default")).cyan()
+                ),
+            );
+            write_line(
+                &term,
+                &format!(
+                    "{}{} {}",
+                    CLIPBOARD,
+                    style("ChatGPT endpoint:").bold(),
+                    style(server.endpoint_url("chatgpt")).cyan()
+                ),
+            );
+
+            smol::future::pending::<()>().await;
+            Ok(())
+        }
+
+        Commands::Monitor { once, format } => {
+            let monitor = Monitor::new(&config, reporter_for_format(&format))?;
+
+            let term = Term::stdout();
+            if format != "json" {
+                write_line(
+                    &term,
+                    &format!(
+                        "{}{}",
+                        ROCKET,
+                        style(format!(
+                            "Starting monitor with {} schedule(s)",
+                            monitor.suites().len()
+                        ))
+                        .cyan()
+                        .bold()
+                    ),
+                );
+                write_line(
+                    &term,
+                    &format!(
+                        "{}{} {}",
+                        CLIPBOARD,
+                        style("History:").bold(),
+                        style(&config.monitor.history_dir).dim()
+                    ),
+                );
+            }
+
+            monitor.run(once).await?;
+            Ok(())
+        }
+
+        Commands::History {
+            history_db,
+            command,
+        } => {
+            let history_db =
+                history_db.or_else(|| config.monitor.history_db.clone().map(PathBuf::from));
+            let store = AnyHistoryStore::open(&config.monitor.history_dir, history_db.as_deref());
+            match command {
+                HistoryCommands::List {
+                    labels,
+                    schedule,
+                    instance,
+                    limit,
+                    format,
+                } => {
+                    let filter = RunFilter {
+                        labels,
+                        schedule,
+                        instance,
+                    };
+                    let mut runs = store.query(&filter)?;
+                    runs.reverse();
+                    runs.truncate(limit);
+
+                    if format == "json" {
+                        println!("{}", redacted_json(&runs)?);
+                    } else {
+                        print_history_list(&runs);
+                    }
+                    Ok(())
+                }
+                HistoryCommands::Show { run, format } => {
+                    let record = store.load(&run)?;
+                    if format == "json" {
+                        println!("{}", redacted_json(&record)?);
+                    } else {
+                        write_line(&Term::stdout(), &format!("📜 {}", history_line(&record)));
+                        write_line(
+                            &Term::stdout(),
+                            &format!("   {} → {}", record.started_at, record.finished_at),
+                        );
+                        if let Some(result) = &record.tool_result {
+                            write_line(&Term::stdout(), "");
+                            write_line(
+                                &Term::stdout(),
+                                &result.format_output("text", false, false),
+                            );
+                        }
+                        for host in &record.host_results {
+                            write_line(
+                                &Term::stdout(),
+                                &format!(
+                                    "   {}{} {}: {}",
+                                    if host.success { CHECKMARK } else { CROSS_MARK },
+                                    host.host,
+                                    host.operation,
+                                    host.error.as_deref().unwrap_or(&host.details)
+                                ),
+                            );
+                        }
+                    }
+                    Ok(())
+                }
+                HistoryCommands::Stats {
+                    last,
+                    labels,
+                    schedule,
+                    instance,
+                    format,
+                } => {
+                    let filter = RunFilter {
+                        labels,
+                        schedule,
+                        instance,
+                    };
+                    let stats = ToolStats::from_outcomes(&store.tool_outcomes(&filter, last)?);
+                    if format == "json" {
+                        println!("{}", redacted_json(&stats)?);
+                    } else if stats.is_empty() {
+                        write_line(
+                            &Term::stdout(),
+                            &format!(
+                                "{}{}",
+                                CLIPBOARD,
+                                style("No matching tool runs in history").dim()
+                            ),
+                        );
+                    } else {
+                        write_line(
+                            &Term::stdout(),
+                            &format!(
+                                "📊 {}",
+                                style(format!("Tool Stats (last {last} runs)"))
+                                    .bold()
+                                    .underlined()
+                            ),
+                        );
+                        write_str(&Term::stdout(), &ToolStats::format_table(&stats));
+                    }
+                    Ok(())
+                }
+            }
+        }
+
+        Commands::Analyze {
+            history_db,
+            command,
+        } => {
+            let history_db =
+                history_db.or_else(|| config.monitor.history_db.clone().map(PathBuf::from));
+            let store = AnyHistoryStore::open(&config.monitor.history_dir, history_db.as_deref());
+            let (latency, args) = match command {
+                AnalyzeCommands::Flakiness(args) => (false, args),
+                AnalyzeCommands::Latency(args) => (true, args),
+            };
+            if args.recent_days >= args.days {
+                return Err(GleanMcpError::Config(format!(
+                    "--recent-days ({}) must be less than --days ({})",
+                    args.recent_days, args.days
+                )));
+            }
+            let mut options = TrendOptions {
+                days: args.days,
+                recent_days: args.recent_days,
+                ..TrendOptions::default()
+            };
+            if let Some(threshold) = args.threshold {
+                if latency {
+                    options.latency_threshold = threshold;
+                } else {
+                    options.pass_rate_threshold = threshold;
+                }
+            }
+            let filter = RunFilter {
+                labels: args.labels,
+                schedule: args.schedule,
+                instance: args.instance,
+            };
+            let outcomes = store.tool_outcomes(&filter, usize::MAX)?;
+            let now = chrono::Utc::now();
+
+            let (json, csv, table, changed, tools) = if latency {
+                let trends = analyze_latency(&outcomes, &options, now);
+                (
+                    redacted_json(&trends)?,
+                    LatencyTrend::format_csv(&trends),
+                    LatencyTrend::format_table(&trends),
+                    trends
+                        .iter()
+                        .filter(|t| t.changed)
+                        .map(|t| t.tool.clone())
+                        .collect::<Vec<_>>(),
+                    trends.len(),
+                )
+            } else {
+                let trends = analyze_flakiness(&outcomes, &options, now);
+                (
+                    redacted_json(&trends)?,
+                    FlakinessTrend::format_csv(&trends),
+                    FlakinessTrend::format_table(&trends),
+                    trends
+                        .iter()
+                        .filter(|t| t.changed)
+                        .map(|t| t.tool.clone())
+                        .collect::<Vec<_>>(),
+                    trends.len(),
+                )
+            };
+
+            match args.format.as_str() {
+                "json" => println!("{json}"),
+                "csv" => print!("{csv}"),
+                _ if tools == 0 => {
+                    write_line(
+                        &Term::stdout(),
+                        &format!(
+                            "{}{}",
+                            CLIPBOARD,
+                            style(format!(
+                                "No matching tool runs in the last {} days",
+                                args.days
+                            ))
+                            .dim()
+                        ),
+                    );
+                }
+                _ => {
+                    let title = if latency {
+                        "Latency Trends"
+                    } else {
+                        "Flakiness Trends"
+                    };
+                    write_line(
+                        &Term::stdout(),
+                        &format!(
+                            "📈 {} {}",
+                            style(title).bold().underlined(),
+                            style(format!(
+                                "(last {} days, recent = last {})",
+                                args.days, args.recent_days
+                            ))
+                            .dim()
+                        ),
+                    );
+                    write_str(&Term::stdout(), &table);
+                    if !changed.is_empty() {
+                        write_line(&Term::stdout(), "");
+                        write_line(
+                            &Term::stdout(),
+                            &format!(
+                                "⚠️  Changed significantly in the last {} days: {}",
+                                args.recent_days,
+                                changed.join(", ")
+                            ),
+                        );
+                    }
+                }
+            }
+            Ok(())
+        }
+
+        Commands::Baseline { command } => {
+            let baselines = BaselineStore::new(&config.monitor.history_dir);
+            let term = Term::stdout();
+            match command {
+                BaselineCommands::Promote { run } => {
+                    let record = HistoryStore::new(&config.monitor.history_dir).load(&run)?;
+                    let entry = baselines.promote(&record, current_branch().as_deref())?;
+                    write_line(
+                        &term,
+                        &format!(
+                            "{}Run {} is now the baseline for {} / {}",
+                            CHECKMARK,
+                            style(&entry.run_id).cyan(),
+                            style(&entry.instance).bold(),
+                            entry.suite
+                        ),
+                    );
+                }
+                BaselineCommands::List => {
+                    let entries = baselines.load()?;
+                    if entries.is_empty() {
+                        write_line(
+                            &term,
+                            &format!("{}{}", CLIPBOARD, style("No baselines promoted yet").dim()),
+                        );
+                    }
+                    for entry in entries.iter().rev() {
+                        write_line(
+                            &term,
+                            &format!(
+                                "  {} {} {} {} {}",
+                                style(&entry.promoted_at).dim(),
+                                style(&entry.run_id).cyan(),
+                                style(&entry.instance).bold(),
+                                entry.suite,
+                                entry
+                                    .branch
+                                    .as_deref()
+                                    .map_or_else(String::new, |b| format!("({b})"))
+                            ),
+                        );
+                    }
+                }
+            }
+            Ok(())
+        }
+        Commands::Schema {
+            command: SchemaCommands::Print { document },
+        } => {
+            // Not redacted: the schema holds no run data, and its examples must stay intact
+            println!("{}", serde_json::to_string_pretty(&json_schema(document))?);
+            Ok(())
+        }
+        Commands::Aggregate { paths, format } => {
+            let report = aggregate_results(&paths)?;
+            if format == "json" {
+                println!("{}", redacted_json(&report)?);
+            } else {
+                write_line(&Term::stdout(), &report.format_text());
+            }
+            std::process::exit(report.outcome.exit_code());
+        }
+        Commands::Coverage {
+            runs,
+            days,
+            labels,
+            schedule,
+            instance,
+            history_db,
+            format,
+        } => {
+            let history_db =
+                history_db.or_else(|| config.monitor.history_db.clone().map(PathBuf::from));
+            let store = AnyHistoryStore::open(&config.monitor.history_dir, history_db.as_deref());
+            let records = if runs.is_empty() {
+                let since = chrono::Utc::now() - chrono::Duration::days(i64::from(days));
+                store
+                    .query(&RunFilter {
+                        labels,
+                        schedule,
+                        instance,
+                    })?
+                    .into_iter()
+                    .filter(|record| {
+                        chrono::DateTime::parse_from_rfc3339(&record.started_at)
+                            .is_ok_and(|started| started >= since)
+                    })
+                    .collect()
+            } else {
+                runs.iter()
+                    .map(|id| store.load(id))
+                    .collect::<Result<Vec<_>>>()?
+            };
+            if records.is_empty() {
+                return Err(GleanMcpError::Config(
+                    "No recorded runs to cover (run `test` or `monitor`, or widen --days)"
+                        .to_string(),
+                ));
+            }
+            let report = CoverageReport::build(&records, &CoverageSurface::from_config(&config));
+            if format == "json" {
+                println!("{}", redacted_json(&report)?);
+            } else {
+                write_line(&Term::stdout(), &report.format_text());
+            }
+            Ok(())
+        }
+        Commands::Clean {
+            keep_runs,
+            keep_days,
+            dry_run,
+        } => {
+            let mut retention = config.retention.clone();
+            if keep_runs.is_some() {
+                retention.keep_runs = keep_runs;
+            }
+            if keep_days.is_some() {
+                retention.keep_days = keep_days;
+            }
+            if !retention.is_enabled() {
+                return Err(GleanMcpError::Config(
+                    "No retention policy: pass --keep-runs/--keep-days or set retention in the config"
+                        .to_string(),
+                ));
+            }
+            if retention.keep_runs == Some(0) {
+                return Err(GleanMcpError::Config(
+                    "--keep-runs must be at least 1".to_string(),
+                ));
+            }
+
+            let history_db = config
+                .monitor
+                .history_db
+                .as_ref()
+                .map(SqliteHistoryStore::new);
+            let report = apply_retention(
+                &retention,
+                Path::new(&config.monitor.history_dir),
+                history_db.as_ref(),
+                dry_run,
+            )?;
+            print_retention_report(&report);
+            Ok(())
+        }
+        Commands::Bench(args) => bench(args, &config).await,
+        Commands::Fuzz(args) => fuzz(args, &config).await,
+        Commands::Session { command } => match command {
+            SessionCommands::Soak(args) => soak(args, &config).await,
+            SessionCommands::Resume(args) => resume(args, &config).await,
+            SessionCommands::Cancel(args) => cancel(args, &config).await,
+        },
+
+        // Legacy top-level names
+        Commands::Inspect(args) => handle_server(ServerCommands::Inspect(args), &config).await,
+        Commands::ListTools(args) => handle_server(ServerCommands::ListTools(args), &config).await,
+        Commands::TestTool(args) => handle_server(ServerCommands::TestTool(args), &config).await,
+        Commands::Test(args) => handle_server(ServerCommands::TestAll(args), &config).await,
+        Commands::VerifyHost(args) => handle_host(HostCommands::Verify(args), &config).await,
+        Commands::TestHostTool(args) => handle_host(HostCommands::Test(args), &config).await,
+        Commands::TestAllHostTools(args) => handle_host(HostCommands::TestAll(args), &config).await,
+        Commands::TestAllHosts(args) => {
+            handle_host(HostCommands::TestAllHosts(args), &config).await
+        }
+        Commands::CheckHost(args) => handle_host(HostCommands::Check(args), &config).await,
+        Commands::ListHostServers(args) => handle_host(HostCommands::List(args), &config).await,
+        Commands::ConfigureHost(args) => handle_host(HostCommands::Configure(args), &config).await,
+        Commands::RollbackHost(args) => handle_host(HostCommands::Rollback(args), &config).await,
+        Commands::Compare(args) => handle_report(ReportCommands::Compare(args), &config).await,
+    }
+}
+
+/// Dispatch `server` subcommands
+#[allow(clippy::future_not_send)]
+async fn handle_server(command: ServerCommands, config: &GleanConfig) -> Result<()> {
+    match command {
+        ServerCommands::Inspect(InspectArgs { instance, format }) => {
+            let term = Term::stdout();
+            write_line(
+                &term,
+                &format!(
+                    "{}{}",
+                    ROCKET,
+                    style("Starting Glean MCP Inspector validation...")
+                        .cyan()
+                        .bold()
+                ),
+            );
+            write_line(
+                &term,
+                &format!(
+                    "{}{} {}",
+                    CLIPBOARD,
+                    style("Instance:").bold(),
+                    style(&instance).cyan()
+                ),
+            );
+
+            let inspector = inspector_for(config, &instance, reporter_for_format("text"));
+            match inspector
+                .validate_server_and_tools(&config.inspected_endpoints())
+                .await
+            {
+                Ok(result) => {
+                    if format == "json" {
+                        match redacted_json(&result) {
+                            Ok(json_output) => println!("{json_output}"),
+                            Err(e) => {
+                                write_line(
+                                    &term,
+                                    &format!(
+                                        "{}{}",
+                                        CROSS_MARK,
+                                        style(format!("Failed to serialize JSON: {e}")).red()
+                                    ),
+                                );
+                                std::process::exit(1);
+                            }
+                        }
+                    } else {
+                        print_enhanced_text_result(&result);
+                    }
+
+                    write_line(&term, "");
+                    if result.success {
+                        write_line(
+                            &term,
+                            &format!(
+                                "{}{}",
+                                PARTY,
+                                style("Validation completed successfully!").green().bold()
+                            ),
+                        );
+                        write_line(
+                            &term,
+                            &format!(
+                                "{}{}",
+                                ROCKET,
+                                style("Ready to proceed to host application testing").blue()
+                            ),
+                        );
+                        std::process::exit(0);
+                    } else {
+                        write_line(
+                            &term,
+                            &format!("{}{}", CROSS_MARK, style("Validation failed!").red().bold()),
+                        );
+                        if let Some(error) = &result.error {
+                            write_line(&term, &format!("Error: {}", style(error).red()));
+                        }
+                        std::process::exit(1);
+                    }
+                }
+                Err(e) => {
+                    write_line(
+                        &term,
+                        &format!(
+                            "{}{}",
+                            CROSS_MARK,
+                            style(format!("Failed to run MCP Inspector: {e}")).red()
+                        ),
+                    );
+                    std::process::exit(1);
+                }
+            }
+        }
+
+        ServerCommands::ListTools(ListToolsArgs { instance, format }) => {
+            let term = Term::stdout();
+            write_line(
+                &term,
+                &format!(
+                    "📋 {}",
+                    style("Listing available tools from MCP server")
+                        .cyan()
+                        .bold()
+                ),
+            );
+            write_line(&term, &format!("📋 Instance: {}", style(&instance).cyan()));
+
+            match inspector_for(config, &instance, reporter_for_format("text"))
+                .list_available_tools(false)
+                .await
+            {
+                Ok(result) => {
+                    if result.success {
+                        if format == "json" {
+                            println!(
+                                "{}",
+                                redacted_json(&result).unwrap_or_else(|_| "{}".to_string())
+                            );
+                        } else {
+                            write_line(&term, "");
+                            write_line(
+                                &term,
+                                &format!(
+                                    "{}{}",
+                                    PARTY,
+                                    style("Tools listed successfully!").green().bold()
+                                ),
+                            );
+                        }
+                        std::process::exit(0);
+                    } else {
+                        write_line(
+                            &term,
+                            &format!(
+                                "{}{}",
+                                CROSS_MARK,
+                                style("Failed to list tools!").red().bold()
+                            ),
+                        );
+                        if let Some(error) = &result.error {
+                            write_line(&term, &format!("Error: {}", style(error).red()));
+                        }
+                        std::process::exit(1);
+                    }
+                }
+                Err(e) => {
+                    let term = Term::stderr();
+                    write_line(
+                        &term,
+                        &format!(
+                            "{}{}",
+                            CROSS_MARK,
+                            style(format!("Failed to list tools: {e}")).red()
+                        ),
+                    );
+                    std::process::exit(1);
+                }
+            }
+        }
+
+        ServerCommands::TestTool(args) => test_single_tool(args, config).await,
+
+        ServerCommands::Chatgpt(ChatGptArgs { instance, format }) => {
+            let term = Term::stdout();
+            let reporter = reporter_for_format(&format);
+            let inspector = match &instance {
+                Some(instance) => GleanMCPInspector::with_reporter(Some(instance), reporter)
+                    .with_url_template(&config.glean_instance.url_template),
+                None => {
+                    GleanMCPInspector::with_reporter(Some(&config.glean_instance.name), reporter)
+                        .with_endpoints(
+                            &config.glean_instance.server_url,
+                            &config.glean_instance.chatgpt_url,
+                        )
+                }
+            }
+            .with_proxy(config.proxy.clone())
+            .with_tls(config.tls.clone())
+            .with_headers(config.headers.clone());
+            if format != "json" {
+                write_line(
+                    &term,
+                    &format!(
+                        "{}{}",
+                        MAGNIFYING_GLASS,
+                        style("Validating ChatGPT connector endpoint...")
+                            .cyan()
+                            .bold()
+                    ),
+                );
+            }
+
+            let result = inspector
+                .validate_chatgpt_endpoint(&config.glean_instance.chatgpt_tools)
+                .await?;
+            if format == "json" {
+                println!("{}", redacted_json(&result)?);
+            } else {
+                write_str(&term, &result.format_text());
+                write_line(&term, "");
+                if result.success {
+                    write_line(
+                        &term,
+                        &format!(
+                            "{}{}",
+                            PARTY,
+                            style("ChatGPT endpoint exposes the expected tools")
+                                .green()
+                                .bold()
+                        ),
+                    );
+                } else {
+                    write_line(
+                        &term,
+                        &format!(
+                            "{}{}",
+                            CROSS_MARK,
+                            style("ChatGPT endpoint validation failed!").red().bold()
+                        ),
+                    );
+                }
+            }
+            std::process::exit(i32::from(!result.success));
+        }
+
+        ServerCommands::TestAll(TestArgs {
+            instance,
+            all,
+            tools,
+            exclude,
+            tags,
+            exclude_tags,
+            parallel,
+            max_concurrent,
+            timeout,
+            verbose,
+            debug,
+            retry_attempts,
+            retry_backoff,
+            json,
+            format,
+            sort,
+            output,
+            record,
+            replay,
+            capture_dir,
+            labels,
+            no_history,
+            history_db,
+            branch,
+            mock,
+            chaos,
+            chaos_faults,
+            chaos_seed,
+            check_language,
+            check_duplicates,
+            scan_pii,
+            skip_result_check,
+            snapshot,
+            update_snapshots,
+            max_response_bytes,
+            max_rps,
+            corpus,
+            corpus_mode,
+            fail_on,
+            require,
+            negative,
+            pagination,
+            stress,
+            permissions,
+            act_as_users,
+            auth_challenge,
+            truncation,
+            i18n,
+            notifications,
+            dry_run,
+            repeat,
+            until_failure,
+            fail_fast,
+            skip_recently_passed,
+            assume_default_tools,
+            endpoint,
+            agents,
+            expect_server_version,
+            shard,
+            gha,
+        }) => {
+            // --json is shorthand for --format json
+            let actual_format = if json { "json".to_string() } else { format };
+            // --max-concurrent auto only makes sense in parallel
+            let parallel = parallel || max_concurrent == MaxConcurrent::Auto;
+            let max_concurrent_display = match max_concurrent {
+                MaxConcurrent::Fixed(limit) => limit.to_string(),
+                MaxConcurrent::Auto => "auto".to_string(),
+            };
+
+            let term = Term::stdout();
+
+            // Only show progress for non-JSON, non-quiet output
+            if actual_format != "json" && !quiet_output() {
+                // Clean header
+                write_line(
+                    &term,
+                    &format!(
+                        "\n{} {}",
+                        GEAR,
+                        style("Glean MCP Tool Testing").cyan().bold()
+                    ),
+                );
+
+                // Configuration summary - clean and compact
+                let mut tools_display = if all {
+                    "all (including ChatGPT)".to_string()
+                } else if let Some(ref tools_list) = tools {
+                    tools_list.clone()
+                } else if !tags.is_empty() {
+                    "all".to_string()
+                } else {
+                    "core".to_string()
+                };
+                if !tags.is_empty() {
+                    tools_display = format!("{tools_display} tagged {}", tags.join("/"));
+                }
+                let excluded: Vec<String> = exclude
+                    .iter()
+                    .cloned()
+                    .chain(exclude_tags.iter().map(|tag| format!("tag {tag}")))
+                    .collect();
+                if !excluded.is_empty() {
+                    tools_display = format!("{tools_display} except {}", excluded.join(", "));
+                }
+                if let Some(shard) = shard {
+                    tools_display = format!("{tools_display}, shard {shard}");
+                }
+
+                write_line(
+                    &term,
+                    &format!(
+                        "📋 {} | 🔧 {} | ⚡ {} {}",
+                        style(&instance).cyan(),
+                        style(&tools_display).cyan(),
+                        if parallel { "Parallel" } else { "Sequential" },
+                        if parallel {
+                            format!("({})", style(max_concurrent_display).dim())
+                        } else {
+                            String::new()
+                        }
+                    ),
+                );
+
+                write_line(&term, "");
+            }
+
+            // Determine tools to test - validate mutually exclusive flags
+            if all && tools.is_some() {
+                write_line(
+                    &term,
+                    &format!(
+                        "{}{}",
+                        CROSS_MARK,
+                        style("Error: --all and --tools are mutually exclusive")
+                            .red()
+                            .bold()
+                    ),
+                );
+                std::process::exit(1);
+            }
+
+            let tools_filter = if all {
+                "all".to_string()
+            } else if let Some(tools_list) = tools {
+                tools_list
+            } else if !tags.is_empty() {
+                "all".to_string()
+            } else {
+                "core".to_string()
+            };
+
+            let mut labels: BTreeMap<String, String> = labels.into_iter().collect();
+            if let Some(rate) = chaos {
+                labels
+                    .entry("chaos".to_string())
+                    .or_insert_with(|| rate.to_string());
+            }
+            if let Some(shard) = shard {
+                labels
+                    .entry("shard".to_string())
+                    .or_insert_with(|| shard.to_string());
+            }
+
+            let mut language_check = config.language_check.clone();
+            if let Some(language) = check_language {
+                language_check.enabled = true;
+                if language != "auto" {
+                    language_check.expected = Some(language);
+                }
+            }
+
+            let mut duplicate_check = config.duplicate_check.clone();
+            duplicate_check.enabled |= check_duplicates;
+
+            let mut result_check = config.result_check.clone();
+            result_check.enabled &= !skip_result_check;
+
+            let mut pii_scan = config.pii_scan.clone();
+            pii_scan.enabled |= scan_pii;
+
+            let snapshot = (snapshot || update_snapshots)
+                .then(|| SnapshotCheck::new(&config.snapshot, &instance, update_snapshots));
+
+            let mut response_size = config.response_size.clone();
+            if let Some(max_bytes) = max_response_bytes {
+                response_size.max_bytes = max_bytes;
+                response_size.tool_max_bytes.clear();
+            }
+
+            let mut corpus_config = config.corpus.clone();
+            if let Some(file) = corpus {
+                corpus_config.file = Some(file);
+            }
+            if let Some(mode) = corpus_mode {
+                corpus_config.mode = mode;
+            }
+            let mut corpus = corpus_config.load()?;
+            if corpus.mode == CorpusMode::RoundRobin && !corpus.is_empty() {
+                // Each run moves one query further through the lists
+                let history_db = history_db
+                    .clone()
+                    .or_else(|| config.monitor.history_db.clone().map(PathBuf::from));
+                let filter = RunFilter {
+                    instance: Some(instance.clone()),
+                    ..RunFilter::default()
+                };
+                corpus.offset =
+                    AnyHistoryStore::open(&config.monitor.history_dir, history_db.as_deref())
+                        .query(&filter)
+                        .map_or(0, |runs| runs.len());
+            }
+
+            let recently_passed = match skip_recently_passed {
+                Some(window_ms) => {
+                    let history_db = history_db
+                        .clone()
+                        .or_else(|| config.monitor.history_db.clone().map(PathBuf::from));
+                    let filter = RunFilter {
+                        instance: Some(instance.clone()),
+                        ..RunFilter::default()
+                    };
+                    let outcomes =
+                        AnyHistoryStore::open(&config.monitor.history_dir, history_db.as_deref())
+                            .tool_outcomes(&filter, usize::MAX)?;
+                    let since = chrono::Utc::now()
+                        - chrono::Duration::milliseconds(
+                            i64::try_from(window_ms).unwrap_or(i64::MAX),
+                        );
+                    recently_passed(&outcomes, since)
+                }
+                None => BTreeMap::new(),
+            };
+
+            let test_options = glean_mcp_test::TestAllOptions {
+                tools_filter: tools_filter.clone(),
+                parallel,
+                max_concurrent: max_concurrent.limit(),
+                adaptive_concurrency: max_concurrent == MaxConcurrent::Auto,
+                timeout,
+                verbose,
+                debug,
+                retry_attempts,
+                retry_backoff_seconds: retry_backoff,
+                labels,
+                tool_max_concurrent: config.tools_to_test.max_concurrent.clone(),
+                latency_budgets_ms: config.slo.budgets_ms(),
+                language_check,
+                duplicate_check,
+                result_check,
+                snapshot,
+                response_size,
+                pii_scan,
+                expectations: config.profile.expectations(),
+                max_rps,
+                tool_arguments: config.tool_arguments(),
+                exclude: [config.tools_to_test.exclude.clone(), exclude].concat(),
+                tags,
+                exclude_tags,
+                tool_tags: config.tool_tags(),
+                depends_on: config.tools_to_test.depends_on.clone(),
+                corpus,
+                fail_fast,
+                shard,
+                recently_passed,
+                assume_default_tools,
+                endpoints: config
+                    .glean_instance
+                    .select_endpoints(endpoint.as_deref())?,
+                agents: AgentCheckConfig {
+                    agents: if agents.is_empty() {
+                        config.agents.agents.clone()
+                    } else {
+                        agents
+                    },
+                    ..config.agents.clone()
+                },
+                query_rules: config.tools_to_test.query_rules.clone(),
+                cancel: CancellationToken::new(),
+            };
 
-            match run_validation(Some(&instance)) {
-                Ok(result) => {
-                    auth_pb.inc(1);
+            if dry_run {
+                let history_db =
+                    history_db.or_else(|| config.monitor.history_db.clone().map(PathBuf::from));
+                let filter = RunFilter {
+                    instance: Some(instance.clone()),
+                    ..RunFilter::default()
+                };
+                let history =
+                    AnyHistoryStore::open(&config.monitor.history_dir, history_db.as_deref())
+                        .tool_outcomes(&filter, DRY_RUN_HISTORY_RUNS)
+                        .unwrap_or_default();
+                let plan = GleanMCPInspector::with_reporter(
+                    Some(&instance),
+                    reporter_for_format("silent"),
+                )
+                .with_url_template(&config.glean_instance.url_template)
+                .plan_test_all(&test_options, &config.glean_instance.chatgpt_tools)
+                .with_history(&history);
+                if actual_format == "json" {
+                    println!("{}", redacted_json(&plan)?);
+                } else {
+                    write_str(&term, &plan.format_text());
+                }
+                std::process::exit(0);
+            }
 
-                    if result.success {
-                        auth_pb.finish_with_message(format!(
-                            "{}{}",
-                            CHECKMARK,
-                            style("Authentication successful").green()
-                        ));
-                        let _ = term.write_line("");
-                        let _ = term.write_line(&format!(
-                            "{}{}",
-                            PARTY,
-                            style("Authentication test successful!").green().bold()
-                        ));
-                        std::process::exit(0);
-                    } else {
-                        auth_pb.finish_with_message(
-                            style("❌ Authentication failed").red().to_string(),
-                        );
-                        let _ = term.write_line("");
-                        let _ = term.write_line(&format!(
+            // Always test both endpoints when using --all or test according to tools filter
+            if actual_format != "json" && !quiet_output() {
+                let heading = if all {
+                    "Testing both default and ChatGPT MCP endpoints"
+                } else {
+                    "Testing MCP tools"
+                };
+                write_line(&term, &format!("🌐 {}", style(heading).cyan().bold()));
+            }
+            let reporter = reporter_for_format(&actual_format);
+            let mut inspector = inspector_for(config, &instance, reporter)
+                .with_request_timeout(Duration::from_secs(timeout));
+
+            // Keep the mock server alive for the duration of the run
+            let mock_server =
+                start_mock_if_requested(&mut inspector, mock.enabled, mock.config.as_deref())
+                    .await?;
+            if let Some(server) = &mock_server {
+                inspector.reporter().info(&format!(
+                    "Testing against mock MCP server at {}",
+                    server.base_url()
+                ));
+            }
+
+            // Results that didn't come from the instance stay out of its history and baselines
+            let simulated = mock.enabled || replay.is_some();
+            if let Some(path) = record {
+                inspector
+                    .reporter()
+                    .info(&format!("Recording MCP interactions to {}", path.display()));
+                inspector = inspector.with_cassette(Cassette::for_recording(path));
+            } else if let Some(path) = replay {
+                inspector.reporter().info(&format!(
+                    "Replaying MCP interactions from {}",
+                    path.display()
+                ));
+                inspector = inspector.with_cassette(Cassette::for_replay(path)?);
+            }
+            if let Some(dir) = capture_dir {
+                let capture = TranscriptCapture::new(&dir, inspector.run_id().unwrap_or("run"));
+                inspector.reporter().info(&format!(
+                    "Capturing per-tool transcripts to {}",
+                    capture.dir().display()
+                ));
+                inspector = inspector.with_capture(capture);
+            }
+            let chaos = chaos.map(|rate| {
+                Chaos::new(ChaosConfig {
+                    rate,
+                    faults: chaos_faults,
+                    seed: chaos_seed,
+                })
+            });
+            if let Some(chaos) = &chaos {
+                inspector.reporter().info(&format!(
+                    "Chaos mode: faulting {:.0}% of tool calls",
+                    chaos.config().rate * 100.0
+                ));
+                inspector = inspector.with_chaos(chaos.clone());
+            }
+
+            if negative {
+                let result = inspector.test_error_handling().await?;
+                if actual_format == "json" {
+                    println!("{}", redacted_json(&result)?);
+                } else {
+                    write_str(&term, &result.format_text());
+                    write_line(&term, "");
+                    write_line(
+                        &term,
+                        &if result.success {
+                            format!(
+                                "{}{}",
+                                PARTY,
+                                style("Server rejected every invalid request correctly")
+                                    .green()
+                                    .bold()
+                            )
+                        } else {
+                            format!(
+                                "{}{}",
+                                CROSS_MARK,
+                                style(format!(
+                                    "{} of {} invalid requests were not handled correctly",
+                                    result.failed_cases, result.total_cases
+                                ))
+                                .red()
+                                .bold()
+                            )
+                        },
+                    );
+                }
+                std::process::exit(result.outcome().exit_code());
+            }
+
+            if pagination {
+                let result = inspector.test_pagination().await;
+                if actual_format == "json" {
+                    println!("{}", redacted_json(&result)?);
+                } else {
+                    write_str(&term, &result.format_text());
+                    write_line(&term, "");
+                    let failed = result.checks.iter().filter(|check| !check.passed).count();
+                    write_line(
+                        &term,
+                        &if result.success {
+                            format!(
+                                "{}{}",
+                                PARTY,
+                                style("Pagination works for tools/list and every search tool")
+                                    .green()
+                                    .bold()
+                            )
+                        } else {
+                            format!(
+                                "{}{}",
+                                CROSS_MARK,
+                                style(format!(
+                                    "{failed} of {} pagination checks failed",
+                                    result.checks.len()
+                                ))
+                                .red()
+                                .bold()
+                            )
+                        },
+                    );
+                }
+                std::process::exit(result.outcome().exit_code());
+            }
+
+            if let Some(requests) = stress {
+                let result = inspector.stress_session(requests).await;
+                if actual_format == "json" {
+                    println!("{}", redacted_json(&result)?);
+                } else {
+                    write_str(&term, &result.format_text());
+                    write_line(&term, "");
+                    write_line(
+                        &term,
+                        &if result.success {
+                            format!(
+                                "{}{}",
+                                PARTY,
+                                style("Every concurrent call was answered with its own id")
+                                    .green()
+                                    .bold()
+                            )
+                        } else if result.error.is_some() {
+                            format!(
+                                "{}{}",
+                                CROSS_MARK,
+                                style("Could not open an MCP session").red().bold()
+                            )
+                        } else {
+                            format!(
+                                "{}{}",
+                                CROSS_MARK,
+                                style(format!(
+                                    "{} mismatched and {} dropped of {} concurrent calls",
+                                    result.mismatched.len(),
+                                    result.dropped,
+                                    result.total_requests
+                                ))
+                                .red()
+                                .bold()
+                            )
+                        },
+                    );
+                }
+                std::process::exit(result.outcome().exit_code());
+            }
+
+            if let Some(seconds) = notifications {
+                let result = inspector
+                    .watch_notifications(Duration::from_secs(seconds))
+                    .await;
+                if actual_format == "json" {
+                    println!("{}", redacted_json(&result)?);
+                } else {
+                    write_str(&term, &result.format_text());
+                    write_line(&term, "");
+                    write_line(
+                        &term,
+                        &if result.success && !result.stream_offered {
+                            format!(
+                                "{}{}",
+                                PARTY,
+                                style("The server offers no event stream, so there is nothing to check")
+                                    .green()
+                                    .bold()
+                            )
+                        } else if result.success {
+                            format!(
+                                "{}{}",
+                                PARTY,
+                                style("Every notification was well-formed").green().bold()
+                            )
+                        } else if result.error.is_some() {
+                            format!(
+                                "{}{}",
+                                CROSS_MARK,
+                                style("Could not watch the event stream").red().bold()
+                            )
+                        } else {
+                            format!(
+                                "{}{}",
+                                CROSS_MARK,
+                                style(format!(
+                                    "{} malformed notification(s)",
+                                    result.malformed.len()
+                                ))
+                                .red()
+                                .bold()
+                            )
+                        },
+                    );
+                }
+                std::process::exit(result.outcome().exit_code());
+            }
+
+            if auth_challenge {
+                let result = inspector.test_auth_challenge().await;
+                if actual_format == "json" {
+                    println!("{}", redacted_json(&result)?);
+                } else {
+                    write_str(&term, &result.format_text());
+                    write_line(&term, "");
+                    let failed = result.results.iter().filter(|r| !r.passed).count();
+                    write_line(
+                        &term,
+                        &if result.success {
+                            format!(
+                                "{}{}",
+                                PARTY,
+                                style("Every invalid credential got a proper auth challenge")
+                                    .green()
+                                    .bold()
+                            )
+                        } else {
+                            format!(
                             "{}{}",
                             CROSS_MARK,
-                            style("Authentication test failed!").red().bold()
-                        ));
-                        if let Some(error) = &result.error {
-                            let _ = term.write_line(&format!("Error: {}", style(error).red()));
-                        }
-                        std::process::exit(1);
-                    }
+                            style(format!(
+                                "{failed} of {} invalid credentials were not challenged correctly",
+                                result.results.len()
+                            ))
+                            .red()
+                            .bold()
+                        )
+                        },
+                    );
                 }
-                Err(e) => {
-                    auth_pb
-                        .finish_with_message(style("❌ Test execution failed").red().to_string());
-                    let _ = term.write_line(&format!(
-                        "{}{}",
-                        CROSS_MARK,
-                        style(format!("Failed to run authentication test: {e}")).red()
-                    ));
-                    std::process::exit(1);
+                std::process::exit(result.outcome().exit_code());
+            }
+
+            if permissions {
+                let result = inspector.test_permissions(&config.permissions).await?;
+                if actual_format == "json" {
+                    println!("{}", redacted_json(&result)?);
+                } else {
+                    write_str(&term, &result.format_text());
+                    write_line(&term, "");
+                    let failed = result.results.iter().filter(|r| !r.passed).count();
+                    write_line(
+                        &term,
+                        &if result.success {
+                            format!(
+                                "{}{}",
+                                PARTY,
+                                style("No results leaked to restricted identities")
+                                    .green()
+                                    .bold()
+                            )
+                        } else if result.leaked() > 0 {
+                            format!(
+                                "{}{}",
+                                CROSS_MARK,
+                                style(format!(
+                                    "{} result(s) leaked to restricted identities",
+                                    result.leaked()
+                                ))
+                                .red()
+                                .bold()
+                            )
+                        } else {
+                            format!(
+                                "{}{}",
+                                CROSS_MARK,
+                                style(format!(
+                                    "{failed} of {} permission checks failed",
+                                    result.results.len()
+                                ))
+                                .red()
+                                .bold()
+                            )
+                        },
+                    );
                 }
+                std::process::exit(result.outcome().exit_code());
             }
-        }
 
-        Commands::ListTools { instance, format } => {
-            let term = Term::stdout();
-            let _ = term.write_line(&format!(
-                "📋 {}",
-                style("Listing available tools from MCP server")
-                    .cyan()
-                    .bold()
-            ));
-            let _ = term.write_line(&format!("📋 Instance: {}", style(&instance).cyan()));
+            if let Some(users) = act_as_users {
+                let users: Vec<String> = if users.is_empty() {
+                    config.act_as.users.clone()
+                } else {
+                    users
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|user| !user.is_empty())
+                        .map(String::from)
+                        .collect()
+                };
+                let result = inspector.test_act_as(&config.act_as, &users).await?;
+                if actual_format == "json" {
+                    println!("{}", redacted_json(&result)?);
+                } else {
+                    write_str(&term, &result.format_text());
+                    write_line(&term, "");
+                    let failed = result.comparisons.iter().filter(|c| !c.passed()).count();
+                    write_line(
+                        &term,
+                        &if result.success {
+                            format!(
+                                "{}{}",
+                                PARTY,
+                                style(format!(
+                                    "Every call passed as all {} users",
+                                    result.users.len()
+                                ))
+                                .green()
+                                .bold()
+                            )
+                        } else {
+                            format!(
+                                "{}{}",
+                                CROSS_MARK,
+                                style(format!(
+                                    "{failed} of {} tool calls failed for at least one user",
+                                    result.comparisons.len()
+                                ))
+                                .red()
+                                .bold()
+                            )
+                        },
+                    );
+                }
+                std::process::exit(result.outcome().exit_code());
+            }
 
-            match run_list_tools(Some(&instance), &format) {
-                Ok(result) => {
-                    if result.success {
-                        if format == "json" {
-                            println!(
-                                "{}",
-                                serde_json::to_string_pretty(&result)
-                                    .unwrap_or_else(|_| "{}".to_string())
-                            );
+            if let Some(languages) = i18n {
+                let mut i18n_config = config.i18n.clone();
+                if !languages.is_empty() {
+                    i18n_config.languages = parse_languages(&languages);
+                }
+                let result = inspector
+                    .test_i18n(&i18n_config, Duration::from_secs(timeout))
+                    .await?;
+                if actual_format == "json" {
+                    println!("{}", redacted_json(&result)?);
+                } else {
+                    write_str(&term, &result.format_text());
+                    write_line(&term, "");
+                    let failing: Vec<&str> = result
+                        .languages
+                        .iter()
+                        .filter(|rate| rate.passed < rate.total)
+                        .map(|rate| rate.language.as_str())
+                        .collect();
+                    write_line(
+                        &term,
+                        &if result.success {
+                            format!(
+                                "{}{}",
+                                PARTY,
+                                style("Every language was handled correctly").green().bold()
+                            )
                         } else {
-                            let _ = term.write_line("");
-                            let _ = term.write_line(&format!(
+                            format!(
+                                "{}{}",
+                                CROSS_MARK,
+                                style(format!(
+                                    "Failures in {} of {} languages: {}",
+                                    failing.len(),
+                                    result.languages.len(),
+                                    failing.join(", ")
+                                ))
+                                .red()
+                                .bold()
+                            )
+                        },
+                    );
+                }
+                std::process::exit(result.outcome().exit_code());
+            }
+
+            if let Some(url) = truncation {
+                let url = Some(url)
+                    .filter(|url| !url.is_empty())
+                    .or_else(|| test_options.response_size.large_document_url.clone())
+                    .ok_or_else(|| {
+                        GleanMcpError::Config(
+                            "--truncation needs a document URL (or response_size.large_document_url in the config file)"
+                                .to_string(),
+                        )
+                    })?;
+                let result = inspector
+                    .test_truncation(
+                        &url,
+                        &test_options.response_size,
+                        Duration::from_secs(timeout),
+                    )
+                    .await;
+                if actual_format == "json" {
+                    println!("{}", redacted_json(&result)?);
+                } else {
+                    write_str(&term, &result.format_text());
+                    write_line(&term, "");
+                    write_line(
+                        &term,
+                        &if result.success {
+                            format!(
                                 "{}{}",
                                 PARTY,
-                                style("Tools listed successfully!").green().bold()
-                            ));
-                        }
-                        std::process::exit(0);
+                                style("The large document fits within the response size limit")
+                                    .green()
+                                    .bold()
+                            )
+                        } else if result.response_bytes.is_none() {
+                            format!(
+                                "{}{}",
+                                CROSS_MARK,
+                                style("Could not read the document").red().bold()
+                            )
+                        } else {
+                            format!(
+                                "{}{}",
+                                CROSS_MARK,
+                                style("The server did not truncate the large document")
+                                    .red()
+                                    .bold()
+                            )
+                        },
+                    );
+                }
+                std::process::exit(result.outcome().exit_code());
+            }
+
+            // Ctrl-C stops the run but still reports what finished; a second one exits at once
+            if let Err(e) = test_options.cancel.cancel_on_interrupt() {
+                inspector.reporter().warning(&e.to_string());
+            }
+
+            if repeat.is_some() || until_failure {
+                let iterations = repeat.unwrap_or(UNTIL_FAILURE_MAX_ITERATIONS) as usize;
+                let report = inspector
+                    .repeat_test_all(&test_options, iterations, until_failure)
+                    .await?;
+                print_repeat_report(&report, &actual_format)?;
+                std::process::exit(report.outcome().exit_code());
+            }
+
+            let mut result = inspector.test_all_tools(&test_options).await?;
+
+            // Skewed clocks make valid tokens look expired; say so next to the auth errors
+            if result.has_auth_failures()
+                && let Ok(skew) = inspector.clock_skew().await
+                && skew.exceeds(config.authentication.max_clock_skew_secs)
+            {
+                result.annotate_auth_failures(&skew.describe());
+            }
+
+            let version_mismatch = expect_server_version
+                .as_ref()
+                .and_then(|requirement| requirement.check(result.server.as_ref()).err());
+            if let Some(reason) = &version_mismatch {
+                result.success = false;
+                result.error = Some(reason.clone());
+            }
+            if let Some(server) = &result.server {
+                warn_lost_capabilities(config, &instance, server, inspector.reporter());
+            }
+            // Shards only see their share of the advertised tools
+            let unknown_fail_on = if shard.is_none() && result.tool_discovery.is_some() {
+                fail_on.unknown_tools(&result)
+            } else {
+                Vec::new()
+            };
+            if !unknown_fail_on.is_empty() {
+                inspector.reporter().warning(&format!(
+                    "--fail-on names tools the server didn't advertise: {} (scopes are all, core, enterprise, or tool names)",
+                    unknown_fail_on.join(", ")
+                ));
+            }
+            // A scope gating nothing would pass every run, so a typo like `cor` is an error
+            let fail_on_gates_nothing =
+                matches!(&fail_on, FailOn::Tools(tools) if tools.len() == unknown_fail_on.len());
+
+            if let Some(chaos) = &chaos {
+                let injected = chaos.injected();
+                let summary: Vec<String> = injected
+                    .iter()
+                    .map(|(fault, count)| format!("{}: {count}", fault.as_str()))
+                    .collect();
+                inspector.reporter().info(&format!(
+                    "Chaos mode injected {} fault(s){}",
+                    injected.values().sum::<usize>(),
+                    if summary.is_empty() {
+                        String::new()
                     } else {
-                        let _ = term.write_line(&format!(
-                            "{}{}",
-                            CROSS_MARK,
-                            style("Failed to list tools!").red().bold()
-                        ));
-                        if let Some(error) = &result.error {
-                            let _ = term.write_line(&format!("Error: {}", style(error).red()));
+                        format!(" ({})", summary.join(", "))
+                    }
+                ));
+            }
+
+            let interrupted = result.cancelled == Some(CancelReason::Interrupted);
+            let record = RunRecord::for_tool_run(&instance, &tools_filter, &result);
+            if interrupted {
+                inspector
+                    .reporter()
+                    .info("Interrupted run not recorded to history");
+            } else if simulated {
+                inspector
+                    .reporter()
+                    .info("Mock and replayed runs are not recorded to history");
+            } else if !no_history {
+                let store = HistoryStore::new(&config.monitor.history_dir);
+                match store.record(&record) {
+                    Ok(_) => {
+                        inspector
+                            .reporter()
+                            .info(&format!("Run {} recorded to history", record.id));
+                        // Runs with injected faults never become baselines, nor do
+                        // shards, which cover only part of the suite
+                        if chaos.is_none() && shard.is_none() {
+                            promote_if_eligible(
+                                config,
+                                &record,
+                                branch.or_else(current_branch).as_deref(),
+                                inspector.reporter(),
+                            );
                         }
+                    }
+                    Err(e) => inspector
+                        .reporter()
+                        .warning(&format!("Could not record run to history: {e}")),
+                }
+
+                let history_db =
+                    history_db.or_else(|| config.monitor.history_db.clone().map(PathBuf::from));
+                if let Some(db) = history_db
+                    && let Err(e) = SqliteHistoryStore::new(&db).record(&record)
+                {
+                    inspector
+                        .reporter()
+                        .warning(&format!("Could not record run to {}: {e}", db.display()));
+                }
+            }
+
+            let output_content = result.format_output_sorted(&actual_format, verbose, debug, sort);
+
+            if let Some(output_file) = output {
+                match std::fs::write(&output_file, redact(&output_content).as_ref()) {
+                    Ok(()) => {
+                        write_line(
+                            &term,
+                            &format!("📄 Results written to: {}", style(&output_file).cyan()),
+                        );
+                    }
+                    Err(e) => {
+                        write_line(
+                            &term,
+                            &format!(
+                                "{}{}",
+                                CROSS_MARK,
+                                style(format!("Failed to write output file: {e}")).red()
+                            ),
+                        );
                         std::process::exit(1);
                     }
                 }
-                Err(e) => {
-                    let term = Term::stderr();
-                    let _ = term.write_line(&format!(
+            } else if actual_format == "json" {
+                // For JSON output, print directly without styling
+                println!("{}", redact(&output_content));
+            } else {
+                // For text output, use console
+                write_line(&term, &output_content);
+            }
+
+            let comparison = baseline_comparison(config, &record);
+            let criteria = require.unwrap_or_default();
+            // More shards than tools leaves the last shards empty; shard 1 is only empty when nothing was selected
+            let outcome = if shard.is_some_and(|shard| shard.index > 1)
+                && result.tested_tools() == 0
+                && !interrupted
+            {
+                RunOutcome::Success
+            } else {
+                RunOutcome::for_result(&result, &fail_on, &criteria, comparison.as_ref())
+            };
+            let outcome = match outcome {
+                _ if fail_on_gates_nothing => RunOutcome::Error,
+                // Passing tools don't count against the wrong server build
+                RunOutcome::Success | RunOutcome::Degraded if version_mismatch.is_some() => {
+                    RunOutcome::ValidationFailure
+                }
+                outcome => outcome,
+            };
+            if actual_format != "json" {
+                let tolerated = result.failed_tools
+                    - result
+                        .tool_results
+                        .values()
+                        .filter(|tool| {
+                            tool.is_failure() && criteria.gates(&result, &fail_on, &tool.tool_name)
+                        })
+                        .count();
+                if let Some(reason) = &version_mismatch {
+                    write_line(&term, &format!("{}{}", CROSS_MARK, style(reason).red()));
+                }
+                if fail_on_gates_nothing {
+                    write_line(
+                        &term,
+                        &format!(
+                            "{}{}",
+                            CROSS_MARK,
+                            style("--fail-on matches none of the server's tools, so nothing would gate the run").red()
+                        ),
+                    );
+                }
+                for (requirement, rate) in criteria.unmet(&result) {
+                    write_line(
+                        &term,
+                        &format!(
+                            "{}{}",
+                            CROSS_MARK,
+                            style(format!(
+                                "{} tools passed {rate:.0}%, below the required {}%",
+                                requirement.category, requirement.min_success_rate
+                            ))
+                            .red()
+                        ),
+                    );
+                }
+                if result.oversized_tools > 0 {
+                    write_line(
+                        &term,
+                        &format!(
+                            "{}{}",
+                            WARNING,
+                            style(format!(
+                                "{} tool(s) returned responses over the size limit, which MCP hosts may truncate",
+                                result.oversized_tools
+                            ))
+                            .yellow()
+                        ),
+                    );
+                }
+                let message = match outcome {
+                    _ if interrupted => format!(
+                        "{}{}",
+                        WARNING,
+                        style(format!(
+                            "Interrupted: {} tool(s) finished, {} not run",
+                            result.tested_tools(),
+                            result.skipped_for(SkipReason::Interrupted)
+                        ))
+                        .yellow()
+                        .bold()
+                    ),
+                    RunOutcome::Success | RunOutcome::Degraded if tolerated > 0 => format!(
+                        "{}{}",
+                        WARNING,
+                        style(format!(
+                            "{tolerated} tool(s) failed outside the --fail-on scope or within --require thresholds; not failing the run"
+                        ))
+                        .yellow()
+                        .bold()
+                    ),
+                    RunOutcome::Degraded => format!(
+                        "{}{}",
+                        WARNING,
+                        style(format!(
+                            "All tests passed, but {} tool(s) exceeded their latency budget",
+                            result.degraded_tools
+                        ))
+                        .yellow()
+                        .bold()
+                    ),
+                    RunOutcome::Success => format!(
+                        "{}{}",
+                        PARTY,
+                        style("All tests completed successfully!").green().bold()
+                    ),
+                    _ => format!(
                         "{}{}",
                         CROSS_MARK,
-                        style(format!("Failed to list tools: {e}")).red()
+                        style(format!("Some tools failed testing! ({})", outcome.as_str()))
+                            .red()
+                            .bold()
+                    ),
+                };
+                write_line(&term, &format!("\n{message}"));
+            }
+
+            if gha {
+                let actions = GithubActions::new(&result, outcome, &instance, &tools_filter);
+                let gates =
+                    |tool: &ToolTestResult| criteria.gates(&result, &fail_on, &tool.tool_name);
+                if let Err(e) = actions.emit(gates, actual_format == "json") {
+                    inspector.reporter().warning(&format!(
+                        "Could not write the GitHub Actions step summary: {e}"
                     ));
-                    std::process::exit(1);
                 }
             }
+
+            std::process::exit(match outcome {
+                _ if interrupted => INTERRUPTED_EXIT_CODE,
+                RunOutcome::Degraded => config.slo.degraded_exit_code,
+                outcome => outcome.exit_code(),
+            });
         }
+    }
+}
 
-        Commands::VerifyHost { host, format } => {
+/// Dispatch `host` subcommands
+async fn handle_host(command: HostCommands, config: &GleanConfig) -> Result<()> {
+    match command {
+        HostCommands::Verify(HostArgs { host, format }) => {
             let term = Term::stdout();
-            let _ = term.write_line(&format!(
-                "🔍 Verifying MCP servers in host: {}",
-                style(&host).cyan().bold()
-            ));
+            write_line(
+                &term,
+                &format!(
+                    "🔍 Verifying MCP servers in host: {}",
+                    style(&host).cyan().bold()
+                ),
+            );
 
-            match run_host_operation(&host, "verify", "", None, None, &format).await {
+            match run_host_operation(config, &host, "verify", "", None, None, &format).await {
                 Ok(result) => {
                     if result.success {
-                        let _ = term.write_line(&format!(
-                            "{}{}",
-                            CHECKMARK,
-                            style("Host verification completed successfully!")
-                                .green()
-                                .bold()
-                        ));
+                        write_line(
+                            &term,
+                            &format!(
+                                "{}{}",
+                                CHECKMARK,
+                                style("Host verification completed successfully!")
+                                    .green()
+                                    .bold()
+                            ),
+                        );
                         std::process::exit(0);
                     } else {
-                        let _ = term.write_line(&format!(
-                            "{}{}",
-                            CROSS_MARK,
-                            style("Host verification failed!").red().bold()
-                        ));
+                        write_line(
+                            &term,
+                            &format!(
+                                "{}{}",
+                                CROSS_MARK,
+                                style("Host verification failed!").red().bold()
+                            ),
+                        );
                         if let Some(error) = &result.error {
-                            let _ = term.write_line(&format!("Error: {}", style(error).red()));
+                            write_line(&term, &format!("Error: {}", style(error).red()));
                         }
                         std::process::exit(1);
                     }
                 }
                 Err(e) => {
-                    let _ = term.write_line(&format!(
-                        "{}{}",
-                        CROSS_MARK,
-                        style(format!("Failed to verify host: {e}")).red()
-                    ));
+                    write_line(
+                        &term,
+                        &format!(
+                            "{}{}",
+                            CROSS_MARK,
+                            style(format!("Failed to verify host: {e}")).red()
+                        ),
+                    );
                     std::process::exit(1);
                 }
             }
         }
 
-        Commands::TestHostTool {
+        HostCommands::Test(HostToolArgs {
             host,
             tool,
             query,
             format,
-        } => {
+        }) => {
             let term = Term::stdout();
-            let _ = term.write_line(&format!(
-                "🧪 Testing Glean tool '{}' on host '{}' with query: \"{}\"",
-                style(&tool).cyan(),
-                style(&host).cyan(),
-                style(&query).dim()
-            ));
+            write_line(
+                &term,
+                &format!(
+                    "🧪 Testing Glean tool '{}' on host '{}' with query: \"{}\"",
+                    style(&tool).cyan(),
+                    style(&host).cyan(),
+                    style(&query).dim()
+                ),
+            );
 
-            match run_host_operation(&host, "test_tool", "", Some(&tool), Some(&query), &format)
-                .await
-            {
-                Ok(result) => {
-                    if result.success {
-                        let _ = term.write_line(&format!(
-                            "{}{}",
-                            CHECKMARK,
-                            style("Glean tool test completed successfully!")
-                                .green()
-                                .bold()
-                        ));
+            match run_host_operation(
+                config,
+                &host,
+                "test_tool",
+                "",
+                Some(&tool),
+                Some(&query),
+                &format,
+            )
+            .await
+            {
+                Ok(result) => {
+                    if result.success {
+                        write_line(
+                            &term,
+                            &format!(
+                                "{}{}",
+                                CHECKMARK,
+                                style("Glean tool test completed successfully!")
+                                    .green()
+                                    .bold()
+                            ),
+                        );
                         std::process::exit(0);
                     } else {
-                        let _ = term.write_line(&format!(
-                            "{}{}",
-                            CROSS_MARK,
-                            style("Glean tool test failed!").red().bold()
-                        ));
+                        write_line(
+                            &term,
+                            &format!(
+                                "{}{}",
+                                CROSS_MARK,
+                                style("Glean tool test failed!").red().bold()
+                            ),
+                        );
                         if let Some(error) = &result.error {
-                            let _ = term.write_line(&format!("Error: {}", style(error).red()));
+                            write_line(&term, &format!("Error: {}", style(error).red()));
                         }
                         std::process::exit(1);
                     }
                 }
                 Err(e) => {
-                    let _ = term.write_line(&format!(
-                        "{}{}",
-                        CROSS_MARK,
-                        style(format!("Failed to test Glean tool on host: {e}")).red()
-                    ));
+                    write_line(
+                        &term,
+                        &format!(
+                            "{}{}",
+                            CROSS_MARK,
+                            style(format!("Failed to test Glean tool on host: {e}")).red()
+                        ),
+                    );
                     std::process::exit(1);
                 }
             }
         }
 
-        Commands::TestAllHostTools { host, format } => {
+        HostCommands::TestAll(HostArgs { host, format }) => {
             let term = Term::stdout();
-            let _ = term.write_line(&format!(
-                "🧪 Testing all Glean tools on host: {}",
-                style(&host).cyan().bold()
-            ));
+            write_line(
+                &term,
+                &format!(
+                    "🧪 Testing all Glean tools on host: {}",
+                    style(&host).cyan().bold()
+                ),
+            );
 
-            match run_host_operation(&host, "test_all", "", None, None, &format).await {
+            match run_host_operation(config, &host, "test_all", "", None, None, &format).await {
                 Ok(result) => {
                     if result.success {
-                        let _ = term.write_line(&format!(
-                            "{}{}",
-                            CHECKMARK,
-                            style("All Glean tools test completed successfully!")
-                                .green()
-                                .bold()
-                        ));
+                        write_line(
+                            &term,
+                            &format!(
+                                "{}{}",
+                                CHECKMARK,
+                                style("All Glean tools test completed successfully!")
+                                    .green()
+                                    .bold()
+                            ),
+                        );
                         std::process::exit(0);
                     } else {
-                        let _ = term.write_line(&format!(
-                            "{}{}",
-                            CROSS_MARK,
-                            style("Some Glean tools failed!").red().bold()
-                        ));
+                        write_line(
+                            &term,
+                            &format!(
+                                "{}{}",
+                                CROSS_MARK,
+                                style("Some Glean tools failed!").red().bold()
+                            ),
+                        );
                         if let Some(error) = &result.error {
-                            let _ = term.write_line(&format!("Error: {}", style(error).red()));
+                            write_line(&term, &format!("Error: {}", style(error).red()));
                         }
                         std::process::exit(1);
                     }
                 }
                 Err(e) => {
-                    let _ = term.write_line(&format!(
-                        "{}{}",
-                        CROSS_MARK,
-                        style(format!("Failed to test all Glean tools: {e}")).red()
-                    ));
+                    write_line(
+                        &term,
+                        &format!(
+                            "{}{}",
+                            CROSS_MARK,
+                            style(format!("Failed to test all Glean tools: {e}")).red()
+                        ),
+                    );
                     std::process::exit(1);
                 }
             }
         }
 
-        Commands::CheckHost { host, format } => {
+        HostCommands::Compare(HostCompareArgs {
+            host,
+            tool,
+            query,
+            instance,
+            timeout,
+            format,
+        }) => {
+            let controller = create_host_controller(&host)?;
+            let cases: Vec<(String, String)> = match tool {
+                Some(tool) => {
+                    let query = query.unwrap_or_else(|| {
+                        TestQueryGenerator::generate_test_query(
+                            tool.strip_prefix("glean_").unwrap_or(&tool),
+                        )
+                    });
+                    vec![(tool, query)]
+                }
+                None => controller
+                    .glean_test_cases()
+                    .iter()
+                    .map(|(tool, sample)| {
+                        (
+                            (*tool).to_string(),
+                            query.clone().unwrap_or_else(|| (*sample).to_string()),
+                        )
+                    })
+                    .collect(),
+            };
+            let inspector = inspector_for(config, &instance, reporter_for_format("text"));
+            let report = compare_host_to_direct(
+                &inspector,
+                &controller,
+                &cases,
+                Duration::from_secs(timeout),
+            )
+            .await;
+            if format == "json" {
+                println!("{}", redacted_json(&report)?);
+            } else {
+                write_line(&Term::stdout(), &report.format_text());
+            }
+            std::process::exit(report.outcome().exit_code());
+        }
+        HostCommands::TestAllHosts(MultiHostArgs {
+            hosts,
+            parallel,
+            format,
+            dry_run,
+        }) => {
+            let hosts = if hosts.is_empty() {
+                SUPPORTED_HOSTS.iter().map(ToString::to_string).collect()
+            } else {
+                hosts
+            };
             let term = Term::stdout();
-            let _ = term.write_line(&format!(
-                "🔍 Checking if host application '{}' is available",
-                style(&host).cyan().bold()
-            ));
+            if dry_run {
+                let plan = plan_hosts(&hosts, parallel);
+                if format == "json" {
+                    println!("{}", redacted_json(&plan)?);
+                } else {
+                    write_str(&term, &plan.format_text());
+                }
+                std::process::exit(0);
+            }
+            if format != "json" {
+                write_line(
+                    &term,
+                    &format!(
+                        "🧪 Testing all Glean tools on {} host(s){}: {}",
+                        hosts.len(),
+                        if parallel { " in parallel" } else { "" },
+                        style(hosts.join(", ")).cyan().bold()
+                    ),
+                );
+            }
+
+            let reporter = reporter_for_format(&format);
+            let result = test_hosts(&hosts, parallel, reporter.as_ref()).await;
+
+            if format == "json" {
+                println!("{}", redacted_json(&result)?);
+            } else {
+                write_line(&term, &format!("\n{}", result.format_text()));
+                write_line(
+                    &term,
+                    &if result.success {
+                        format!("{}{}", CHECKMARK, style("All hosts passed!").green().bold())
+                    } else {
+                        format!(
+                            "{}{}",
+                            CROSS_MARK,
+                            style(format!("{} host(s) failed!", result.failed_hosts))
+                                .red()
+                                .bold()
+                        )
+                    },
+                );
+            }
+            std::process::exit(i32::from(!result.success));
+        }
+
+        HostCommands::Configure(ConfigureHostArgs {
+            host,
+            instance,
+            server_name,
+            dry_run,
+            format,
+        }) => {
+            let file = HostConfigFile::for_host(&host, config)?;
+            let server_url = instance.map_or_else(
+                || file.server_url.clone(),
+                |instance| endpoint_url(&config.glean_instance.url_template, &instance, "default"),
+            );
+            let result = file.configure(&server_name, &server_url, dry_run)?;
+            print_host_config_result(&result, &format)
+        }
+
+        HostCommands::Rollback(HostArgs { host, format }) => {
+            let result = HostConfigFile::for_host(&host, config)?.rollback()?;
+            print_host_config_result(&result, &format)
+        }
+
+        HostCommands::Check(HostArgs { host, format }) => {
+            let term = Term::stdout();
+            write_line(
+                &term,
+                &format!(
+                    "🔍 Checking if host application '{}' is available",
+                    style(&host).cyan().bold()
+                ),
+            );
 
             match check_host_availability(&host, &format) {
                 Ok(available) => {
                     if available {
-                        let _ = term.write_line(&format!(
-                            "{}{} '{}' is available and ready for testing",
-                            CHECKMARK,
-                            style("Host").green(),
-                            style(host).cyan()
-                        ));
+                        write_line(
+                            &term,
+                            &format!(
+                                "{}{} '{}' is available and ready for testing",
+                                CHECKMARK,
+                                style("Host").green(),
+                                style(host).cyan()
+                            ),
+                        );
                         std::process::exit(0);
                     } else {
-                        let _ = term.write_line(&format!(
-                            "{}{} '{}' is not available",
-                            CROSS_MARK,
-                            style("Host").red(),
-                            style(host).cyan()
-                        ));
+                        write_line(
+                            &term,
+                            &format!(
+                                "{}{} '{}' is not available",
+                                CROSS_MARK,
+                                style("Host").red(),
+                                style(host).cyan()
+                            ),
+                        );
                         std::process::exit(1);
                     }
                 }
                 Err(e) => {
-                    let _ = term.write_line(&format!(
-                        "{}{}",
-                        CROSS_MARK,
-                        style(format!("Failed to check host availability: {e}")).red()
-                    ));
+                    write_line(
+                        &term,
+                        &format!(
+                            "{}{}",
+                            CROSS_MARK,
+                            style(format!("Failed to check host availability: {e}")).red()
+                        ),
+                    );
                     std::process::exit(1);
                 }
             }
         }
 
-        Commands::ListHostServers { host, format } => {
+        HostCommands::List(HostArgs { host, format }) => {
             let term = Term::stdout();
-            let _ = term.write_line(&format!(
-                "📋 Listing MCP servers in host: {}",
-                style(&host).cyan().bold()
-            ));
+            write_line(
+                &term,
+                &format!(
+                    "📋 Listing MCP servers in host: {}",
+                    style(&host).cyan().bold()
+                ),
+            );
 
-            match run_host_operation(&host, "list", "", None, None, &format).await {
+            match run_host_operation(config, &host, "list", "", None, None, &format).await {
                 Ok(result) => {
                     if result.success {
-                        let _ = term.write_line(&format!(
-                            "{}{}",
-                            CHECKMARK,
-                            style("MCP servers listed successfully!").green().bold()
-                        ));
+                        write_line(
+                            &term,
+                            &format!(
+                                "{}{}",
+                                CHECKMARK,
+                                style("MCP servers listed successfully!").green().bold()
+                            ),
+                        );
                         std::process::exit(0);
                     } else {
-                        let _ = term.write_line(&format!(
-                            "{}{}",
-                            CROSS_MARK,
-                            style("Failed to list MCP servers!").red().bold()
-                        ));
+                        write_line(
+                            &term,
+                            &format!(
+                                "{}{}",
+                                CROSS_MARK,
+                                style("Failed to list MCP servers!").red().bold()
+                            ),
+                        );
                         if let Some(error) = &result.error {
-                            let _ = term.write_line(&format!("Error: {}", style(error).red()));
+                            write_line(&term, &format!("Error: {}", style(error).red()));
                         }
                         std::process::exit(1);
                     }
                 }
                 Err(e) => {
-                    let _ = term.write_line(&format!(
-                        "{}{}",
-                        CROSS_MARK,
-                        style(format!("Failed to list MCP servers: {e}")).red()
-                    ));
+                    write_line(
+                        &term,
+                        &format!(
+                            "{}{}",
+                            CROSS_MARK,
+                            style(format!("Failed to list MCP servers: {e}")).red()
+                        ),
+                    );
                     std::process::exit(1);
                 }
             }
         }
+    }
+}
+
+/// Dispatch `config` subcommands; a bare `config` shows the configuration
+fn handle_config(
+    command: Option<ConfigCommands>,
+    verbose: bool,
+    config: &GleanConfig,
+) -> Result<()> {
+    match command.unwrap_or(ConfigCommands::Show { verbose }) {
+        ConfigCommands::Show { verbose } => {
+            let term = Term::stdout();
+
+            if verbose {
+                match serde_yaml::to_string(&config) {
+                    Ok(config_yaml) => {
+                        write_line(
+                            &term,
+                            &format!(
+                                "📋 {}\n{}",
+                                style("Current Configuration:").bold().underlined(),
+                                config_yaml
+                            ),
+                        );
+                        write_line(&term, "");
+                        write_line(
+                            &term,
+                            &format!(
+                                "{}{}",
+                                CHECKMARK,
+                                style("Configuration displayed successfully!")
+                                    .green()
+                                    .bold()
+                            ),
+                        );
+                        std::process::exit(0);
+                    }
+                    Err(e) => {
+                        let term = Term::stderr();
+                        write_line(
+                            &term,
+                            &format!(
+                                "{}{}",
+                                CROSS_MARK,
+                                style(format!("Failed to serialize config: {e}")).red()
+                            ),
+                        );
+                        std::process::exit(1);
+                    }
+                }
+            } else {
+                write_line(
+                    &term,
+                    &format!(
+                        "📋 {}: {}",
+                        style("Glean Instance").bold(),
+                        style(&config.glean_instance.name).cyan()
+                    ),
+                );
+                write_line(
+                    &term,
+                    &format!(
+                        "🔗 {}: {}",
+                        style("Server URL").bold(),
+                        style(&config.glean_instance.server_url).dim()
+                    ),
+                );
+                write_line(
+                    &term,
+                    &format!(
+                        "🔧 {}: {}",
+                        style("Inspector Package").bold(),
+                        style(&config.mcp_inspector.package).cyan()
+                    ),
+                );
+                write_line(
+                    &term,
+                    &format!(
+                        "🔑 {}: {}",
+                        style("Auth Method").bold(),
+                        style(&config.authentication.method).cyan()
+                    ),
+                );
+                write_line(
+                    &term,
+                    &format!(
+                        "📊 {}: {}",
+                        style("Core Tools").bold(),
+                        style(config.tools_to_test.core_tools.len().to_string()).cyan()
+                    ),
+                );
+                write_line(
+                    &term,
+                    &format!(
+                        "🏢 {}: {}",
+                        style("Enterprise Tools").bold(),
+                        style(config.tools_to_test.enterprise_tools.len().to_string()).cyan()
+                    ),
+                );
+                write_line(
+                    &term,
+                    &format!(
+                        "💻 {}: {}",
+                        style("Host Applications").bold(),
+                        style(config.host_applications.len().to_string()).cyan()
+                    ),
+                );
+                write_line(&term, "");
+                write_line(
+                    &term,
+                    &format!(
+                        "{}{}",
+                        CHECKMARK,
+                        style("Configuration displayed successfully!")
+                            .green()
+                            .bold()
+                    ),
+                );
+                std::process::exit(0);
+            }
+        }
+
+        ConfigCommands::Validate { offline, format } => {
+            let report = config.check(offline);
+            if format == "json" {
+                println!("{}", redacted_json(&report)?);
+                std::process::exit(i32::from(report.errors() > 0));
+            }
+            let term = Term::stdout();
+            if !report.diagnostics.is_empty() {
+                write_line(&term, report.format_text().trim_end());
+                write_line(&term, "");
+            }
+            let summary = format!(
+                "{} error(s), {} warning(s)",
+                report.errors(),
+                report.warnings()
+            );
+            if report.errors() == 0 {
+                write_line(
+                    &term,
+                    &format!(
+                        "{}{}",
+                        CHECKMARK,
+                        style(format!("Configuration is valid ({summary})"))
+                            .green()
+                            .bold()
+                    ),
+                );
+                std::process::exit(0);
+            }
+            write_line(
+                &term,
+                &format!(
+                    "{}",
+                    style(format!("Configuration has problems: {summary}"))
+                        .red()
+                        .bold()
+                ),
+            );
+            std::process::exit(1);
+        }
+
+        ConfigCommands::Init { path, force } => {
+            if path.exists() && !force {
+                return Err(GleanMcpError::Config(format!(
+                    "{} already exists (use --force to overwrite)",
+                    path.display()
+                )));
+            }
+            let yaml = serde_yaml::to_string(&GleanConfig::default())
+                .map_err(|e| GleanMcpError::Config(format!("Failed to serialize config: {e}")))?;
+            std::fs::write(&path, yaml)?;
+            write_line(
+                &Term::stdout(),
+                &format!(
+                    "{}Wrote default configuration to {}",
+                    CHECKMARK,
+                    style(path.display()).cyan()
+                ),
+            );
+            Ok(())
+        }
+    }
+}
+/// Dispatch `report` subcommands
+#[allow(clippy::future_not_send)]
+async fn handle_report(command: ReportCommands, config: &GleanConfig) -> Result<()> {
+    match command {
+        ReportCommands::Compare(CompareArgs {
+            run,
+            against: Some(after),
+            format,
+            ..
+        }) => {
+            // `requires = "run"` guarantees the first file
+            let before = PathBuf::from(run.unwrap_or_default());
+            let comparison = RunComparison::between_results(
+                &before.display().to_string(),
+                &load_result_file(&before)?,
+                &after.display().to_string(),
+                &load_result_file(&after)?,
+            );
+            if format == "json" {
+                println!("{}", redacted_json(&comparison)?);
+            } else {
+                write_line(&Term::stdout(), &comparison.format_text());
+            }
+            std::process::exit(if comparison.has_regressions() {
+                RunOutcome::Regression.exit_code()
+            } else {
+                0
+            });
+        }
+
+        ReportCommands::Compare(CompareArgs {
+            run,
+            baseline,
+            format,
+            ..
+        }) => {
+            let store = HistoryStore::new(&config.monitor.history_dir);
+            let current = match run {
+                Some(id) => store.load(&id)?,
+                None => store.load_all()?.pop().ok_or_else(|| {
+                    GleanMcpError::Config("No runs recorded in history".to_string())
+                })?,
+            };
+
+            let baseline_id = match baseline {
+                Some(id) => id,
+                None => BaselineStore::new(&config.monitor.history_dir)
+                    .latest(&current.instance, &current.suite)?
+                    .map(|entry| entry.run_id)
+                    .ok_or_else(|| {
+                        GleanMcpError::Config(format!(
+                            "No promoted baseline for instance '{}' and suite '{}' (use --baseline or `baseline promote`)",
+                            current.instance, current.suite
+                        ))
+                    })?,
+            };
+            let baseline_run = store.load(&baseline_id)?;
+
+            let comparison = RunComparison::between(&baseline_run, &current)?;
+            if format == "json" {
+                println!("{}", redacted_json(&comparison)?);
+            } else {
+                write_line(&Term::stdout(), &comparison.format_text());
+            }
+
+            std::process::exit(if comparison.has_regressions() {
+                RunOutcome::Regression.exit_code()
+            } else {
+                0
+            });
+        }
+
+        ReportCommands::Diff { from, to, format } => {
+            let store = HistoryStore::new(&config.monitor.history_dir);
+            let comparison = RunComparison::between(&store.load(&from)?, &store.load(&to)?)?;
+            if format == "json" {
+                println!("{}", redacted_json(&comparison)?);
+            } else {
+                write_line(&Term::stdout(), &comparison.format_text());
+            }
+            Ok(())
+        }
+
+        ReportCommands::Serve { bind } => {
+            let server = ReportServer::start(&bind, &config.monitor.history_dir).await?;
+            write_line(
+                &Term::stdout(),
+                &format!(
+                    "{}Report server listening on {} (Ctrl+C to stop)",
+                    ROCKET,
+                    style(server.base_url()).cyan().bold()
+                ),
+            );
+            smol::future::pending::<()>().await;
+            Ok(())
+        }
+    }
+}
+
+/// An inspector for `instance` with the configured proxy, TLS, headers, and URL template
+fn inspector_for(
+    config: &GleanConfig,
+    instance: &str,
+    reporter: Arc<dyn Reporter>,
+) -> GleanMCPInspector {
+    GleanMCPInspector::with_reporter(Some(instance), reporter)
+        .with_proxy(config.proxy.clone())
+        .with_tls(config.tls.clone())
+        .with_headers(config.headers.clone())
+        .with_url_template(&config.glean_instance.url_template)
+}
+
+/// With `mock`, start a mock server and point `inspector` at it; the server
+/// stops when the returned handle is dropped
+async fn start_mock_if_requested(
+    inspector: &mut GleanMCPInspector,
+    mock: bool,
+    mock_config: Option<&Path>,
+) -> Result<Option<MockServer>> {
+    if !mock {
+        return Ok(None);
+    }
+    let mock_config =
+        mock_config.map_or_else(|| Ok(MockServerConfig::default()), MockServerConfig::load)?;
+    let server = MockServer::start("127.0.0.1:0", mock_config).await?;
+    inspector.set_endpoints(
+        &server.endpoint_url("default"),
+        &server.endpoint_url("chatgpt"),
+    );
+    Ok(Some(server))
+}
+
+/// Load-test one tool and print the benchmark summary
+#[allow(clippy::future_not_send)]
+async fn bench(args: BenchArgs, config: &GleanConfig) -> Result<()> {
+    // Read the baseline up front, so a missing one fails before the load does
+    let baseline = match &args.baseline {
+        Some(path) if !args.save_baseline => Some((path, load_bench_baseline(path)?)),
+        _ => None,
+    };
+    let reporter = reporter_for_format(&args.format);
+    let mut inspector = inspector_for(config, &args.instance, reporter)
+        .with_request_timeout(Duration::from_secs(args.timeout));
+
+    // Keep the mock server alive for the duration of the run
+    let _mock_server = start_mock_if_requested(
+        &mut inspector,
+        args.mock.enabled,
+        args.mock.config.as_deref(),
+    )
+    .await?;
+
+    let options = BenchOptions {
+        tool: args.tool,
+        query: args.query,
+        concurrency: args.concurrency,
+        duration: Duration::from_secs(args.duration),
+        max_rps: args.rps,
+        timeout: Duration::from_secs(args.timeout),
+    };
+    inspector.reporter().info(&format!(
+        "Benchmarking '{}' at {} for {}s with {} concurrent request(s)",
+        options.tool,
+        inspector.server_url(),
+        args.duration,
+        options.concurrency
+    ));
+
+    let mut result = run_bench(&inspector, &options).await;
+    if let Some((path, baseline)) = &baseline {
+        result.baseline = Some(BenchComparison::new(
+            &path.display().to_string(),
+            baseline,
+            &result,
+            &config.bench,
+        ));
+    }
+    if args.format == "json" {
+        println!("{}", redacted_json(&result)?);
+    } else {
+        write_line(&Term::stdout(), &result.format_text());
+    }
+
+    // Only a run where nothing succeeded counts as a failure, then a regression
+    if result.successful_requests == 0 {
+        std::process::exit(RunOutcome::Error.exit_code());
+    }
+    if let Some(path) = args.baseline.as_deref().filter(|_| args.save_baseline) {
+        save_bench_baseline(&result, path)?;
+        inspector.reporter().info(&format!(
+            "Saved the benchmark baseline to {}",
+            path.display()
+        ));
+    }
+    if result
+        .baseline
+        .as_ref()
+        .is_some_and(|baseline| baseline.regressed)
+    {
+        std::process::exit(RunOutcome::Regression.exit_code());
+    }
+    Ok(())
+}
+
+async fn soak(args: SoakArgs, config: &GleanConfig) -> Result<()> {
+    if args.ping_interval == 0 || args.call_interval == 0 {
+        return Err(GleanMcpError::Config(
+            "--ping-interval and --call-interval must be greater than zero".to_string(),
+        ));
+    }
+    let reporter = reporter_for_format(&args.format);
+    let mut inspector = inspector_for(config, &args.instance, reporter);
+
+    // Keep the mock server alive for the duration of the run
+    let _mock_server = start_mock_if_requested(
+        &mut inspector,
+        args.mock.enabled,
+        args.mock.config.as_deref(),
+    )
+    .await?;
+
+    let options = SoakOptions {
+        duration: Duration::from_millis(args.duration),
+        ping_interval: Duration::from_millis(args.ping_interval),
+        call_interval: Duration::from_millis(args.call_interval),
+        tool: args.tool,
+        query: args.query,
+        max_latency_growth: args.max_latency_growth,
+        cancel: CancellationToken::new(),
+    };
+    if let Err(e) = options.cancel.cancel_on_interrupt() {
+        inspector.reporter().warning(&e.to_string());
+    }
+    inspector.reporter().info(&format!(
+        "Holding a session on {} for {:?}, pinging every {:?} and calling '{}' every {:?}",
+        inspector.server_url(),
+        options.duration,
+        options.ping_interval,
+        options.tool,
+        options.call_interval
+    ));
+
+    let result = run_soak(&inspector, &options).await;
+    if args.format == "json" {
+        println!("{}", redacted_json(&result)?);
+    } else {
+        write_line(&Term::stdout(), &result.format_text());
+    }
+    std::process::exit(result.outcome().exit_code());
+}
+
+async fn resume(args: ResumeArgs, config: &GleanConfig) -> Result<()> {
+    let reporter = reporter_for_format(&args.format);
+    let mut inspector = inspector_for(config, &args.instance, reporter);
+
+    // Keep the mock server alive for the duration of the run
+    let _mock_server = start_mock_if_requested(
+        &mut inspector,
+        args.mock.enabled,
+        args.mock.config.as_deref(),
+    )
+    .await?;
+
+    let result = inspector
+        .test_resumption(Duration::from_millis(args.wait))
+        .await;
+    if args.format == "json" {
+        println!("{}", redacted_json(&result)?);
+    } else {
+        write_line(&Term::stdout(), &result.format_text());
+    }
+    std::process::exit(result.outcome().exit_code());
+}
+
+async fn cancel(args: CancelArgs, config: &GleanConfig) -> Result<()> {
+    let reporter = reporter_for_format(&args.format);
+    let mut inspector = inspector_for(config, &args.instance, reporter);
+
+    // Keep the mock server alive for the duration of the run
+    let _mock_server = start_mock_if_requested(
+        &mut inspector,
+        args.mock.enabled,
+        args.mock.config.as_deref(),
+    )
+    .await?;
+
+    let result = inspector
+        .test_cancellation(
+            &args.tool,
+            args.query.as_deref(),
+            Duration::from_millis(args.cancel_after),
+            Duration::from_millis(args.wait),
+        )
+        .await;
+    if args.format == "json" {
+        println!("{}", redacted_json(&result)?);
+    } else {
+        write_line(&Term::stdout(), &result.format_text());
+    }
+    std::process::exit(result.outcome().exit_code());
+}
+
+async fn doctor(args: DoctorArgs, config: &GleanConfig) -> Result<()> {
+    let inspector = inspector_for(config, &args.instance, reporter_for_format("silent"))
+        .with_request_timeout(Duration::from_secs(args.timeout));
+
+    let report = run_doctor(config, &inspector, Duration::from_secs(args.timeout)).await;
+    if args.format == "json" {
+        println!("{}", redacted_json(&report)?);
+    } else {
+        let term = Term::stdout();
+        write_line(&term, &report.format_text());
+        let verdict = if report.success {
+            format!(
+                "{}{}",
+                PARTY,
+                style("No blocking problems found").green().bold()
+            )
+        } else {
+            format!(
+                "{}{}",
+                CROSS_MARK,
+                style("Fix the errors above, starting with the first")
+                    .red()
+                    .bold()
+            )
+        };
+        write_line(&term, &verdict);
+    }
+    std::process::exit(report.outcome().exit_code());
+}
+
+async fn diagnose(args: DiagnoseArgs, config: &GleanConfig) -> Result<()> {
+    let reporter = reporter_for_format(&args.format);
+    let mut inspector = inspector_for(config, &args.instance, reporter)
+        .with_request_timeout(Duration::from_secs(args.timeout));
+
+    // Keep the mock server alive for the duration of the run
+    let _mock_server = start_mock_if_requested(
+        &mut inspector,
+        args.mock.enabled,
+        args.mock.config.as_deref(),
+    )
+    .await?;
+
+    let options = DiagnoseOptions {
+        samples: args.samples,
+        timeout: Duration::from_secs(args.timeout),
+        skip_negative: args.skip_negative,
+        tool_arguments: config.tool_arguments(),
+        query_rules: config.tools_to_test.query_rules.clone(),
+    };
+    inspector.reporter().info(&format!(
+        "Diagnosing {} at {}",
+        args.tool,
+        inspector.server_url()
+    ));
+
+    let diagnosis = run_diagnosis(&inspector, &args.tool, &options).await;
+    if args.format == "json" {
+        println!("{}", redacted_json(&diagnosis)?);
+    } else {
+        let term = Term::stdout();
+        write_line(&term, &diagnosis.format_text());
+        let verdict = if diagnosis.success && diagnosis.fixes().is_empty() {
+            format!(
+                "{}{}",
+                PARTY,
+                style(format!("{} looks healthy", diagnosis.tool))
+                    .green()
+                    .bold()
+            )
+        } else if diagnosis.success {
+            format!(
+                "{}{}",
+                WARNING,
+                style(format!("{} works, with the warnings above", diagnosis.tool))
+                    .yellow()
+                    .bold()
+            )
+        } else {
+            format!(
+                "{}{}",
+                CROSS_MARK,
+                style("Fix the errors above, starting with the first")
+                    .red()
+                    .bold()
+            )
+        };
+        write_line(&term, &verdict);
+    }
+    std::process::exit(diagnosis.outcome().exit_code());
+}
+
+async fn fuzz(args: FuzzArgs, config: &GleanConfig) -> Result<()> {
+    let categories = args
+        .categories
+        .as_deref()
+        .map(parse_fuzz_categories)
+        .transpose()?
+        .filter(|categories| !categories.is_empty());
+    let reporter = reporter_for_format(&args.format);
+    let mut inspector = inspector_for(config, &args.instance, reporter)
+        .with_request_timeout(Duration::from_secs(args.timeout));
+
+    // Keep the mock server alive for the duration of the run
+    let _mock_server = start_mock_if_requested(
+        &mut inspector,
+        args.mock.enabled,
+        args.mock.config.as_deref(),
+    )
+    .await?;
+
+    let defaults = FuzzOptions::default();
+    let options = FuzzOptions {
+        tools: args.tools,
+        categories: categories.unwrap_or(defaults.categories),
+        timeout: Duration::from_secs(args.timeout),
+    };
+    inspector.reporter().info(&format!(
+        "Fuzzing {} at {}",
+        options.tools.join(", "),
+        inspector.server_url()
+    ));
+
+    let result = run_fuzz(&inspector, &options).await;
+    if args.format == "json" {
+        println!("{}", redacted_json(&result)?);
+    } else {
+        let term = Term::stdout();
+        write_line(&term, &result.format_text());
+        let verdict = if result.success {
+            format!(
+                "{}{}",
+                PARTY,
+                style(format!(
+                    "Server handled all {} fuzz cases",
+                    result.total_cases
+                ))
+                .green()
+                .bold()
+            )
+        } else {
+            format!(
+                "{}{}",
+                CROSS_MARK,
+                style(format!(
+                    "{} of {} fuzz cases broke the server",
+                    result.failed_cases, result.total_cases
+                ))
+                .red()
+                .bold()
+            )
+        };
+        write_line(&term, &verdict);
+    }
+    std::process::exit(result.outcome().exit_code());
+}
+
+/// Run one tool against the instance and print the inspector result
+#[allow(clippy::future_not_send)]
+async fn test_single_tool(args: TestToolArgs, config: &GleanConfig) -> Result<()> {
+    let TestToolArgs {
+        instance,
+        tool,
+        query,
+        args,
+        format,
+        repeat,
+        until_failure,
+        engine,
+    } = args;
+    let arguments = args.unwrap_or_else(|| {
+        let query = query.unwrap_or_else(|| TestQueryGenerator::generate_test_query(&tool));
+        TestQueryGenerator::generate_arguments(&tool, &query)
+    });
+    let reporter = reporter_for_format(&format);
+
+    if repeat.is_some() || until_failure {
+        let iterations = repeat.unwrap_or(UNTIL_FAILURE_MAX_ITERATIONS) as usize;
+        let report = inspector_for(config, &instance, reporter)
+            .repeat_test_tool(
+                &tool,
+                &arguments,
+                Duration::from_secs(glean_mcp_test::TestAllOptions::default().timeout),
+                iterations,
+                until_failure,
+            )
+            .await;
+        print_repeat_report(&report, &format)?;
+        std::process::exit(report.outcome().exit_code());
+    }
+
+    if let Some(engine @ (Engine::Inspector | Engine::Both)) = engine {
+        let comparison = inspector_for(config, &instance, reporter)
+            .cross_check_tool(
+                engine,
+                &config.mcp_inspector.package_spec(),
+                &tool,
+                &arguments,
+                Duration::from_secs(glean_mcp_test::TestAllOptions::default().timeout),
+            )
+            .await;
+        if format == "json" {
+            println!("{}", redacted_json(&comparison)?);
+        } else {
+            write_str(&Term::stdout(), &comparison.format_text());
+        }
+        std::process::exit(comparison.outcome().exit_code());
+    }
 
-        Commands::Test {
-            instance,
-            all,
-            tools,
-            parallel,
-            max_concurrent,
-            timeout,
-            verbose,
-            debug,
-            retry_attempts,
-            retry_backoff,
-            json,
-            output,
-        } => {
-            // Determine the actual format to use (--json flag enables JSON, otherwise text)
-            let actual_format = if json {
-                "json".to_string()
-            } else {
-                "text".to_string()
-            };
+    let result = inspector_for(config, &instance, reporter)
+        .test_tool_with_arguments(&tool, &arguments)
+        .await?;
+    if format == "json" {
+        println!("{}", redacted_json(&result)?);
+    } else {
+        print_enhanced_text_result(&result);
+    }
 
-            let term = Term::stdout();
+    std::process::exit(i32::from(!result.success));
+}
 
-            // Only show progress for non-JSON output
-            if actual_format != "json" {
-                // Clean header
-                let _ = term.write_line(&format!(
-                    "\n{} {}",
-                    GEAR,
-                    style("Glean MCP Tool Testing").cyan().bold()
-                ));
+/// Print a `--repeat` report, ending with a line naming flaky and failing tools
+fn print_repeat_report(report: &RepeatReport, format: &str) -> Result<()> {
+    if format == "json" {
+        println!("{}", redacted_json(report)?);
+        return Ok(());
+    }
+    let term = Term::stdout();
+    write_str(&term, &report.format_text());
+    write_line(&term, "");
+    let flaky = report.flaky().count();
+    let failing = report.tools.iter().filter(|tool| tool.passed == 0).count();
+    write_line(
+        &term,
+        &if report.success() {
+            format!(
+                "{}{}",
+                PARTY,
+                style(format!(
+                    "Every tool passed all {} iteration(s)",
+                    report.iterations
+                ))
+                .green()
+                .bold()
+            )
+        } else {
+            format!(
+                "{}{}",
+                CROSS_MARK,
+                style(format!(
+                    "{flaky} flaky and {failing} always-failing tool(s) over {} iteration(s)",
+                    report.iterations
+                ))
+                .red()
+                .bold()
+            )
+        },
+    );
+    Ok(())
+}
 
-                // Configuration summary - clean and compact
-                let tools_display = if all {
-                    "all (including ChatGPT)".to_string()
-                } else if let Some(ref tools_list) = tools {
-                    tools_list.clone()
-                } else {
-                    "core".to_string()
-                };
+/// Apply the baseline auto-promotion policy to a freshly recorded run
+/// Print the local clock's skew against `instance`, warning above `authentication.max_clock_skew_secs`
+#[allow(clippy::future_not_send)]
+async fn report_clock_skew(instance: &str, config: &GleanConfig) {
+    let term = Term::stdout();
+    let max_skew_secs = config.authentication.max_clock_skew_secs;
+    match inspector_for(config, instance, reporter_for_format("text"))
+        .clock_skew()
+        .await
+    {
+        Ok(skew) if skew.exceeds(max_skew_secs) => {
+            write_line(&term, &format!(
+                "{}{}",
+                WARNING,
+                style(format!(
+                    "Clock skew: {} (more than {max_skew_secs}s); tokens may be rejected as expired or not yet valid. Sync the system clock (e.g. with NTP).",
+                    skew.describe()
+                ))
+                .yellow()
+            ));
+        }
+        Ok(skew) => {
+            write_line(
+                &term,
+                &format!(
+                    "🕐 {} {}",
+                    style("Clock skew:").bold(),
+                    style(skew.describe()).dim()
+                ),
+            );
+        }
+        Err(e) => {
+            write_line(
+                &term,
+                &format!(
+                    "🕐 {} {}",
+                    style("Clock skew:").bold(),
+                    style(format!("could not be measured ({e})")).dim()
+                ),
+            );
+        }
+    }
+}
 
-                let _ = term.write_line(&format!(
-                    "📋 {} | 🔧 {} | ⚡ {} {}",
-                    style(&instance).cyan(),
-                    style(&tools_display).cyan(),
-                    if parallel { "Parallel" } else { "Sequential" },
-                    if parallel {
-                        format!("({})", style(max_concurrent.to_string()).dim())
-                    } else {
-                        String::new()
-                    }
-                ));
+/// Compare `record` against the promoted baseline for its instance and suite, if any
+fn baseline_comparison(config: &GleanConfig, record: &RunRecord) -> Option<RunComparison> {
+    let entry = BaselineStore::new(&config.monitor.history_dir)
+        .latest(&record.instance, &record.suite)
+        .ok()
+        .flatten()?;
+    let baseline = HistoryStore::new(&config.monitor.history_dir)
+        .load(&entry.run_id)
+        .ok()?;
+    RunComparison::between(&baseline, record).ok()
+}
 
-                let _ = term.write_line("");
-            }
+/// Warn about capabilities the instance's last identified run advertised but `server` doesn't
+fn warn_lost_capabilities(
+    config: &GleanConfig,
+    instance: &str,
+    server: &ServerIdentity,
+    reporter: &dyn Reporter,
+) {
+    let filter = RunFilter {
+        instance: Some(instance.to_string()),
+        ..RunFilter::default()
+    };
+    let Ok(runs) = HistoryStore::new(&config.monitor.history_dir).query(&filter) else {
+        return;
+    };
+    let Some((run_id, previous)) = runs.iter().rev().find_map(|record| {
+        record
+            .tool_result
+            .as_ref()
+            .and_then(|result| result.server.as_ref())
+            .filter(|server| !server.capabilities.is_empty())
+            .map(|server| (&record.id, server))
+    }) else {
+        return;
+    };
+    let lost = server.lost_capabilities(previous);
+    if !lost.is_empty() {
+        reporter.warning(&format!(
+            "Server no longer advertises {} (advertised in run {run_id})",
+            lost.join(", ")
+        ));
+    }
+}
 
-            // Determine tools to test - validate mutually exclusive flags
-            if all && tools.is_some() {
-                let _ = term.write_line(&format!(
-                    "{}{}",
-                    CROSS_MARK,
-                    style("Error: --all and --tools are mutually exclusive")
-                        .red()
-                        .bold()
-                ));
-                std::process::exit(1);
-            }
+fn promote_if_eligible(
+    config: &GleanConfig,
+    record: &RunRecord,
+    branch: Option<&str>,
+    reporter: &dyn Reporter,
+) {
+    if !should_promote(&config.baseline, record, branch) {
+        return;
+    }
 
-            let tools_filter = if all {
-                "all".to_string()
-            } else if let Some(tools_list) = tools {
-                tools_list
-            } else {
-                "core".to_string()
-            };
+    match BaselineStore::new(&config.monitor.history_dir).promote(record, branch) {
+        Ok(entry) => reporter.info(&format!(
+            "Run {} promoted to baseline for {} / {} (branch {})",
+            entry.run_id,
+            entry.instance,
+            entry.suite,
+            branch.unwrap_or_default()
+        )),
+        Err(e) => reporter.warning(&format!("Could not promote run to baseline: {e}")),
+    }
+}
 
-            let test_options = glean_mcp_test::TestAllOptions {
-                tools_filter,
-                parallel,
-                max_concurrent,
-                timeout,
-                verbose,
-                debug,
-                retry_attempts,
-                retry_backoff_seconds: retry_backoff,
-            };
+fn print_history_list(runs: &[RunRecord]) {
+    let term = Term::stdout();
 
-            // Always test both endpoints when using --all or test according to tools filter
-            let result = if all {
-                // Test both endpoints (default and ChatGPT)
-                if actual_format != "json" {
-                    let _ = term.write_line(&format!(
-                        "🌐 {}",
-                        style("Testing both default and ChatGPT MCP endpoints")
-                            .cyan()
-                            .bold()
-                    ));
-                }
-                run_test_all(Some(&instance), &test_options)?
-            } else {
-                // Test tools according to filter (core tools by default)
-                if actual_format != "json" {
-                    let _ = term
-                        .write_line(&format!("🔧 {}", style("Testing MCP tools").cyan().bold()));
-                }
-                run_test_all(Some(&instance), &test_options)?
-            };
+    if runs.is_empty() {
+        write_line(
+            &term,
+            &format!(
+                "{}{}",
+                CLIPBOARD,
+                style("No matching runs in history").dim()
+            ),
+        );
+        return;
+    }
 
-            let output_content = result.format_output(&actual_format, verbose, debug);
+    write_line(
+        &term,
+        &format!("📜 {}", style("Run History").bold().underlined()),
+    );
+    for run in runs {
+        write_line(&term, &format!("  {}", history_line(run)));
+    }
+}
 
-            if let Some(output_file) = output {
-                match std::fs::write(&output_file, &output_content) {
-                    Ok(()) => {
-                        let _ = term.write_line(&format!(
-                            "📄 Results written to: {}",
-                            style(&output_file).cyan()
-                        ));
-                    }
-                    Err(e) => {
-                        let _ = term.write_line(&format!(
-                            "{}{}",
-                            CROSS_MARK,
-                            style(format!("Failed to write output file: {e}")).red()
-                        ));
-                        std::process::exit(1);
-                    }
-                }
-            } else if actual_format == "json" {
-                // For JSON output, print directly without styling
-                println!("{output_content}");
-            } else {
-                // For text output, use console
-                let _ = term.write_line(&output_content);
-            }
+/// One-line summary of a recorded run
+fn history_line(run: &RunRecord) -> String {
+    let status = if run.success {
+        format!("{}", style("✅ passed").green())
+    } else {
+        format!("{}", style("❌ failed").red())
+    };
+    let tools = run.tool_result.as_ref().map_or_else(String::new, |result| {
+        format!(
+            " {}/{} tools",
+            result.successful_tools,
+            result.tested_tools()
+        )
+    });
+    let labels: Vec<String> = run.labels.iter().map(|(k, v)| format!("{k}={v}")).collect();
 
-            if result.success {
-                if actual_format != "json" {
-                    let _ = term.write_line(&format!(
-                        "\n{}{}",
-                        PARTY,
-                        style("All tests completed successfully!").green().bold()
-                    ));
-                }
-                std::process::exit(0);
-            } else {
-                if actual_format != "json" {
-                    let _ = term.write_line(&format!(
-                        "\n{}{}",
-                        CROSS_MARK,
-                        style("Some tools failed testing!").red().bold()
-                    ));
-                }
-                std::process::exit(1);
-            }
+    format!(
+        "{} {} {} {}{}{}",
+        style(&run.id).cyan(),
+        status,
+        style(&run.instance).bold(),
+        run.suite,
+        tools,
+        if labels.is_empty() {
+            String::new()
+        } else {
+            format!(" {}", style(format!("[{}]", labels.join(", "))).dim())
         }
+    )
+}
+
+fn print_retention_report(report: &RetentionReport) {
+    let term = Term::stdout();
+    let verb = if report.dry_run {
+        "Would remove"
+    } else {
+        "Removed"
+    };
+
+    for path in &report.removed {
+        write_line(
+            &term,
+            &format!("  {} {}", style(verb).dim(), path.display()),
+        );
+    }
+    for id in &report.removed_db_runs {
+        write_line(
+            &term,
+            &format!("  {} run {id} from the history database", style(verb).dim()),
+        );
     }
+
+    let protected = if report.protected > 0 {
+        format!(" ({} kept as baselines)", report.protected)
+    } else {
+        String::new()
+    };
+    write_line(
+        &term,
+        &format!(
+            "{}{} {} entries, freeing {}; kept {}{}",
+            if report.dry_run { CLIPBOARD } else { CHECKMARK },
+            verb,
+            style(report.removed_count()).bold(),
+            style(HumanBytes(report.freed_bytes)).bold(),
+            report.kept,
+            protected
+        ),
+    );
 }
 
 fn print_enhanced_text_result(result: &glean_mcp_test::InspectorResult) {
     let term = Term::stdout();
 
-    let _ = term.write_line("");
-    let _ = term.write_line(&format!(
-        "📊 {}",
-        style("MCP Inspector Results").bold().underlined()
-    ));
-    let _ = term.write_line(&style("─".repeat(50)).dim().to_string());
+    write_line(&term, "");
+    write_line(
+        &term,
+        &format!("📊 {}", style("MCP Inspector Results").bold().underlined()),
+    );
+    write_line(&term, &style("─".repeat(50)).dim().to_string());
 
     // Status with enhanced styling
     let status_text = if result.success {
@@ -945,16 +4625,15 @@ fn print_enhanced_text_result(result: &glean_mcp_test::InspectorResult) {
     } else {
         format!("{}{}", CROSS_MARK, style("FAILED").red().bold())
     };
-    let _ = term.write_line(&format!("Status: {status_text}"));
+    write_line(&term, &format!("Status: {status_text}"));
 
     if let Some(tool_results) = &result.tool_results {
-        let _ = term.write_line("");
-        let _ = term.write_line(&format!(
-            "{}{}",
-            GEAR,
-            style("Tool Validation Results:").bold()
-        ));
-        let _ = term.write_line(&style("─".repeat(30)).dim().to_string());
+        write_line(&term, "");
+        write_line(
+            &term,
+            &format!("{}{}", GEAR, style("Tool Validation Results:").bold()),
+        );
+        write_line(&term, &style("─".repeat(30)).dim().to_string());
 
         for (tool, success) in tool_results {
             let (emoji, tool_style) = if *success {
@@ -962,31 +4641,37 @@ fn print_enhanced_text_result(result: &glean_mcp_test::InspectorResult) {
             } else {
                 (CROSS_MARK, style(tool).red())
             };
-            let _ = term.write_line(&format!("  {emoji}{tool_style}"));
+            write_line(&term, &format!("  {emoji}{tool_style}"));
         }
     }
 
     if let Some(error) = &result.error {
-        let _ = term.write_line("");
-        let _ = term.write_line(&format!(
-            "{}{}",
-            WARNING,
-            style("Error Details:").red().bold()
-        ));
-        let _ = term.write_line(&format!("  {}", style(error).dim()));
+        write_line(&term, "");
+        write_line(
+            &term,
+            &format!("{}{}", WARNING, style("Error Details:").red().bold()),
+        );
+        write_line(&term, &format!("  {}", style(error).dim()));
     }
 }
 
-async fn check_prerequisites_with_progress() -> Result<()> {
+async fn check_prerequisites_with_progress(
+    inspector: &McpInspectorConfig,
+    instance: &str,
+    install_missing: bool,
+) -> Result<()> {
     let term = Term::stdout();
-    let _ = term.write_line(&format!(
-        "{}{}",
-        MAGNIFYING_GLASS,
-        style("Checking system prerequisites...").cyan().bold()
-    ));
+    write_line(
+        &term,
+        &format!(
+            "{}{}",
+            MAGNIFYING_GLASS,
+            style("Checking system prerequisites...").cyan().bold()
+        ),
+    );
 
     // Create progress bar for prerequisites checking
-    let pb = ProgressBar::new(4);
+    let pb = progress_bar(4);
     pb.set_style(
         ProgressStyle::with_template(
             "{spinner:.green} [{elapsed_precise}] {bar:30.cyan/blue} {pos:>1}/{len:1} {msg}",
@@ -997,28 +4682,34 @@ async fn check_prerequisites_with_progress() -> Result<()> {
 
     // Check if npx is available
     pb.set_message("Checking Node.js/npm...");
-    if let Ok(output) = std::process::Command::new("npx").arg("--version").output() {
+    if let Ok(output) = glean_mcp_test::command("npx").arg("--version").output() {
         if output.status.success() {
             let version = String::from_utf8_lossy(&output.stdout);
-            let _ = term.write_line(&format!(
-                "{}{} {}",
-                CHECKMARK,
-                style("npx available:").green(),
-                style(version.trim()).dim()
-            ));
+            write_line(
+                &term,
+                &format!(
+                    "{}{} {}",
+                    CHECKMARK,
+                    style("npx available:").green(),
+                    style(version.trim()).dim()
+                ),
+            );
         } else {
             pb.finish_with_message(style("❌ npx command failed").red().to_string());
-            let _ = term.write_line(&format!(
-                "{}{}",
-                CROSS_MARK,
-                style("npx command failed").red()
-            ));
+            write_line(
+                &term,
+                &format!("{}{}", CROSS_MARK, style("npx command failed").red()),
+            );
             return Err(GleanMcpError::Config("npx not available".to_string()));
         }
     } else {
         pb.finish_with_message(style("❌ npx not found").red().to_string());
-        let _ = term.write_line(&format!("{}{}", CROSS_MARK, style("npx not found").red()));
-        let _ = term.write_line(
+        write_line(
+            &term,
+            &format!("{}{}", CROSS_MARK, style("npx not found").red()),
+        );
+        write_line(
+            &term,
             &style("Please install Node.js and npm to use MCP Inspector")
                 .yellow()
                 .to_string(),
@@ -1030,38 +4721,84 @@ async fn check_prerequisites_with_progress() -> Result<()> {
     // Add small delay for visual effect
     smol::Timer::after(Duration::from_millis(200)).await;
 
-    // Check if MCP Inspector package is available
+    // Check the installed MCP Inspector against the pinned version
     pb.set_message("Checking MCP Inspector...");
-    match std::process::Command::new("npx")
-        .args(["@modelcontextprotocol/inspector", "--help"])
-        .output()
+    let spec = inspector.package_spec();
+    let mut installed = installed_package_version(&inspector.package);
+    if install_missing
+        && !installed
+            .as_deref()
+            .is_some_and(|v| inspector.accepts_version(v))
     {
-        Ok(output) => {
-            if output.status.success() {
-                let _ = term.write_line(&format!(
-                    "{}{}",
+        pb.set_message(format!("Installing {spec}..."));
+        write_line(
+            &term,
+            &format!("📦 {} {}", style("Installing").bold(), style(&spec).cyan()),
+        );
+        install_package(&spec)?;
+        installed = installed_package_version(&inspector.package);
+    }
+    let pinned = inspector
+        .version
+        .as_deref()
+        .map_or_else(String::new, |version| format!(" (pinned: {version})"));
+    match installed {
+        Some(version) if inspector.accepts_version(&version) => {
+            write_line(
+                &term,
+                &format!(
+                    "{}{}{}",
                     CHECKMARK,
-                    style("MCP Inspector available").green()
-                ));
-            } else {
-                let _ = term.write_line(&format!(
+                    style(format!("MCP Inspector {version} installed")).green(),
+                    style(pinned).dim()
+                ),
+            );
+        }
+        Some(version) => {
+            pb.finish_with_message(style("❌ MCP Inspector version mismatch").red().to_string());
+            write_line(
+                &term,
+                &format!(
                     "{}{}",
-                    WARNING,
-                    style("MCP Inspector may need to be installed").yellow()
-                ));
-                let _ = term.write_line(&format!(
+                    CROSS_MARK,
+                    style(format!(
+                        "MCP Inspector {version} installed, but {spec} is pinned"
+                    ))
+                    .red()
+                ),
+            );
+            write_line(
+                &term,
+                &format!(
                     "  {}: {}",
                     style("Run").bold(),
-                    style("npx @modelcontextprotocol/inspector --help").cyan()
-                ));
-            }
+                    style("glean-mcp-test prerequisites --install-missing").cyan()
+                ),
+            );
+            return Err(GleanMcpError::Config(format!(
+                "MCP Inspector {version} doesn't match mcp_inspector.version"
+            )));
         }
-        Err(_) => {
-            let _ = term.write_line(&format!(
-                "{}{}",
-                WARNING,
-                style("Could not check MCP Inspector").yellow()
-            ));
+        None => {
+            write_line(
+                &term,
+                &format!(
+                    "{}{}",
+                    WARNING,
+                    style(format!(
+                        "MCP Inspector not installed; npx downloads {spec} on first use"
+                    ))
+                    .yellow()
+                ),
+            );
+            write_line(
+                &term,
+                &format!(
+                    "  {}: {}",
+                    style("Run").bold(),
+                    style("glean-mcp-test prerequisites --install-missing").cyan()
+                ),
+            );
         }
     }
     pb.inc(1);
@@ -1073,17 +4810,23 @@ async fn check_prerequisites_with_progress() -> Result<()> {
     pb.set_message("Checking curl...");
     if let Ok(output) = std::process::Command::new("curl").arg("--version").output() {
         if output.status.success() {
-            let _ = term.write_line(&format!("{}{}", CHECKMARK, style("curl available").green()));
+            write_line(
+                &term,
+                &format!("{}{}", CHECKMARK, style("curl available").green()),
+            );
         } else {
-            let _ = term.write_line(&format!(
-                "{}{}",
-                WARNING,
-                style("curl command failed").yellow()
-            ));
+            write_line(
+                &term,
+                &format!("{}{}", WARNING, style("curl command failed").yellow()),
+            );
         }
     } else {
-        let _ = term.write_line(&format!("{}{}", WARNING, style("curl not found").yellow()));
-        let _ = term.write_line(
+        write_line(
+            &term,
+            &format!("{}{}", WARNING, style("curl not found").yellow()),
+        );
+        write_line(
+            &term,
             &style("curl is required for MCP server testing")
                 .yellow()
                 .to_string(),
@@ -1096,18 +4839,28 @@ async fn check_prerequisites_with_progress() -> Result<()> {
 
     // Check environment variables
     pb.set_message("Checking environment...");
-    if std::env::var("GLEAN_AUTH_TOKEN").is_ok() {
-        let _ = term.write_line(&format!(
-            "{}{}",
-            CHECKMARK,
-            style("GLEAN_AUTH_TOKEN configured").green()
-        ));
+    if let Some(token) = find_auth_token(instance) {
+        write_line(
+            &term,
+            &format!(
+                "{}{}",
+                CHECKMARK,
+                style(format!("{} configured", token.variable)).green()
+            ),
+        );
     } else {
-        let _ = term.write_line(&format!(
-            "{}{}",
-            WARNING,
-            style("GLEAN_AUTH_TOKEN not set (optional)").yellow()
-        ));
+        write_line(
+            &term,
+            &format!(
+                "{}{}",
+                WARNING,
+                style(format!(
+                    "{} not set (optional)",
+                    auth_token_variables(instance).join(", ")
+                ))
+                .yellow()
+            ),
+        );
     }
     pb.inc(1);
 
@@ -1117,28 +4870,22 @@ async fn check_prerequisites_with_progress() -> Result<()> {
         style("Prerequisites check complete").green()
     ));
 
-    let _ = term.write_line("");
-    let _ = term.write_line(&format!(
-        "💡 {}: {}",
-        style("Next step").bold(),
-        style("glean-mcp-test inspect").cyan()
-    ));
+    write_line(&term, "");
+    write_line(
+        &term,
+        &format!(
+            "💡 {}: {}",
+            style("Next step").bold(),
+            style("glean-mcp-test inspect").cyan()
+        ),
+    );
 
     Ok(())
 }
 
-/// Create a Claude Code controller (only supported host for now)
-fn create_claude_code_controller(host: &str) -> Result<ClaudeCodeController> {
-    match host {
-        "claude-code" => Ok(ClaudeCodeController::new()),
-        _ => Err(GleanMcpError::Host(format!(
-            "Unsupported host application: '{host}'. Supported hosts: claude-code"
-        ))),
-    }
-}
-
-/// Run a host operation (configure, verify, `test_tool`, rollback)
+/// Run a host operation (verify, `test_tool`, `test_all`, list)
 async fn run_host_operation(
+    config: &GleanConfig,
     host: &str,
     operation: &str,
     instance: &str,
@@ -1146,10 +4893,14 @@ async fn run_host_operation(
     query: Option<&str>,
     format: &str,
 ) -> Result<HostOperationResult> {
-    let controller = create_claude_code_controller(host)?;
+    let controller =
+        create_host_controller(host)?.with_auth_strategy(AuthStrategy::for_host(host, config));
+    let reporter = reporter_for_format(format);
 
     // Note: Server URL generation no longer needed for testing approach
-    let _server_url = format!("https://{instance}-be.glean.com/mcp/default");
+    let _server_url = endpoint_url(&config.glean_instance.url_template, instance, "default");
+
+    reporter.host_operation_started(host, operation);
 
     let result = match operation {
         "verify" => controller.verify_mcp_server().await?,
@@ -1171,20 +4922,33 @@ async fn run_host_operation(
         }
     };
 
-    // Print result based on format
+    reporter.host_operation_finished(&result);
+
+    // Text output is rendered by the reporter; JSON goes straight to stdout
     if format == "json" {
-        let json_output = serde_json::to_string_pretty(&result).map_err(GleanMcpError::Json)?;
+        let json_output = redacted_json(&result).map_err(GleanMcpError::Json)?;
         println!("{json_output}");
-    } else {
-        print_host_result(&result);
     }
 
     Ok(result)
 }
 
+/// Print the result of `host configure` or `host rollback`
+fn print_host_config_result(result: &HostOperationResult, format: &str) -> Result<()> {
+    if format == "json" {
+        println!("{}", redacted_json(result)?);
+    } else {
+        write_line(
+            &Term::stdout(),
+            &format!("{}{}", CHECKMARK, style(&result.details).green()),
+        );
+    }
+    Ok(())
+}
+
 /// Check if a host application is available
 fn check_host_availability(host: &str, format: &str) -> Result<bool> {
-    let controller = create_claude_code_controller(host)?;
+    let controller = create_host_controller(host)?;
     let available = controller.check_availability()?;
 
     if format == "json" {
@@ -1195,40 +4959,19 @@ fn check_host_availability(host: &str, format: &str) -> Result<bool> {
         });
         println!(
             "{}",
-            serde_json::to_string_pretty(&result).unwrap_or_else(|_| "{}".to_string())
+            redacted_json(&result).unwrap_or_else(|_| "{}".to_string())
         );
     } else if available {
-        println!("✅ Host '{host}' is available");
+        write_line(
+            &Term::stdout(),
+            &format!("{CHECKMARK}Host '{host}' is available"),
+        );
     } else {
-        println!("❌ Host '{host}' is not available");
+        write_line(
+            &Term::stdout(),
+            &format!("{CROSS_MARK}Host '{host}' is not available"),
+        );
     }
 
     Ok(available)
 }
-
-/// Print host operation result in text format
-fn print_host_result(result: &HostOperationResult) {
-    println!("\n📊 Host Operation Results:");
-    println!("Host: {}", result.host);
-    println!("Operation: {}", result.operation);
-    println!(
-        "Status: {}",
-        if result.success {
-            "✅ Success"
-        } else {
-            "❌ Failed"
-        }
-    );
-
-    if !result.details.is_empty() {
-        println!("Details: {}", result.details);
-    }
-
-    if let Some(error) = &result.error {
-        println!("⚠️  Error: {error}");
-    }
-
-    if let Some(duration) = result.duration {
-        println!("⏱️  Duration: {duration:?}");
-    }
-}
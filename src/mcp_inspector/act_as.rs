@@ -0,0 +1,276 @@
+//! Running the same calls as several users
+//!
+//! Glean tokens allowed to impersonate act as the user named in the
+//! `X-Glean-ActAs` header. `--act-as` sends it on every request, and
+//! [`GleanMCPInspector::test_act_as`] runs the `act_as` config's tools and
+//! queries once per user, then lines up what each user got back. Documents
+//! only some users see show permission trimming at work; a call that fails
+//! for any user fails the check. Report rows name users by alias (`user-1`,
+//! `user-2`, ... in the order given) so they stay distinct once emails are
+//! redacted.
+
+use super::{GleanMCPInspector, PermissionDocument, documents};
+use crate::{GleanMcpError, Result, RunOutcome, TestQueryGenerator};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+
+/// Header Glean reads the impersonated user from
+pub const DEFAULT_ACT_AS_HEADER: &str = "X-Glean-ActAs";
+
+/// Documents per user-specific list in the text report before the rest are summarized
+const MAX_LISTED: usize = 5;
+
+/// Settings for `--act-as` and `test --act-as-users` (`act_as` section of the config file)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ActAsConfig {
+    /// Header carrying the user to act as
+    pub header: String,
+    /// Users `test --act-as-users` runs as when none are given on the command line
+    pub users: Vec<String>,
+    /// Permission-sensitive tools to compare
+    pub tools: Vec<String>,
+    /// Queries every tool is called with (default: each tool's generated test query)
+    pub queries: Vec<String>,
+}
+
+impl Default for ActAsConfig {
+    fn default() -> Self {
+        Self {
+            header: DEFAULT_ACT_AS_HEADER.to_string(),
+            users: Vec::new(),
+            tools: vec!["search".to_string(), "chat".to_string()],
+            queries: Vec::new(),
+        }
+    }
+}
+
+/// Alias for the user at `index` in the list given to [`GleanMCPInspector::test_act_as`]
+#[must_use]
+pub fn user_alias(index: usize) -> String {
+    format!("user-{}", index + 1)
+}
+
+/// One user's call of a tool and query
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActAsCall {
+    /// The user's alias (see [`user_alias`])
+    pub user: String,
+    pub success: bool,
+    pub status: Option<u16>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub results: usize,
+}
+
+/// A document only some users got back
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActAsDifference {
+    pub document: PermissionDocument,
+    /// Aliases of the users who saw it
+    pub users: Vec<String>,
+}
+
+/// How one tool and query came back for each user
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActAsComparison {
+    pub tool: String,
+    pub query: String,
+    pub calls: Vec<ActAsCall>,
+    /// Documents every user saw
+    pub shared: usize,
+    pub differences: Vec<ActAsDifference>,
+}
+
+impl ActAsComparison {
+    #[must_use]
+    pub fn passed(&self) -> bool {
+        self.calls.iter().all(|call| call.success)
+    }
+}
+
+/// Results of running the `act_as` tools as each user against one endpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActAsTestResult {
+    /// Every call succeeded for every user
+    pub success: bool,
+    pub endpoint: String,
+    /// Users in alias order: `users[0]` is `user-1`
+    pub users: Vec<String>,
+    pub comparisons: Vec<ActAsComparison>,
+}
+
+impl ActAsTestResult {
+    /// How the run ended, from CI's point of view
+    #[must_use]
+    pub fn outcome(&self) -> RunOutcome {
+        let statuses: Vec<Option<u16>> = self
+            .comparisons
+            .iter()
+            .flat_map(|comparison| &comparison.calls)
+            .map(|call| call.status)
+            .collect();
+        if self.success {
+            RunOutcome::Success
+        } else if statuses.iter().all(Option::is_none) {
+            RunOutcome::ConnectivityFailure
+        } else if statuses.iter().any(|s| matches!(s, Some(401 | 403))) {
+            RunOutcome::AuthFailure
+        } else {
+            RunOutcome::PartialFailure
+        }
+    }
+
+    #[must_use]
+    pub fn format_text(&self) -> String {
+        let mut output = String::new();
+        let passed = self.comparisons.iter().filter(|c| c.passed()).count();
+        let users: Vec<String> = self
+            .users
+            .iter()
+            .enumerate()
+            .map(|(index, user)| format!("{} = {user}", user_alias(index)))
+            .collect();
+        let _ = writeln!(
+            output,
+            "🎭 Acting as {}: {passed}/{} tool calls passed for every user ({})",
+            users.join(", "),
+            self.comparisons.len(),
+            self.endpoint
+        );
+        for comparison in &self.comparisons {
+            let status = if comparison.passed() { "✅" } else { "❌" };
+            let counts: Vec<String> = comparison
+                .calls
+                .iter()
+                .map(|call| {
+                    if call.success {
+                        format!("{} {}", call.user, call.results)
+                    } else {
+                        format!("{} failed", call.user)
+                    }
+                })
+                .collect();
+            let _ = writeln!(
+                output,
+                "  {status} {} {:?}: {} result(s) shared; {}",
+                comparison.tool,
+                comparison.query,
+                comparison.shared,
+                counts.join(", ")
+            );
+            for call in comparison.calls.iter().filter(|call| !call.success) {
+                let _ = writeln!(
+                    output,
+                    "    - {}: {}",
+                    call.user,
+                    call.error.as_deref().unwrap_or("unknown error")
+                );
+            }
+            for difference in comparison.differences.iter().take(MAX_LISTED) {
+                let _ = writeln!(
+                    output,
+                    "    👤 Only {}: {}",
+                    difference.users.join(", "),
+                    difference.document.describe()
+                );
+            }
+            if comparison.differences.len() > MAX_LISTED {
+                let _ = writeln!(
+                    output,
+                    "    👤 ...and {} more user-specific results",
+                    comparison.differences.len() - MAX_LISTED
+                );
+            }
+        }
+        output
+    }
+}
+
+impl GleanMCPInspector {
+    /// Call each of `config`'s tools and queries as each of `users` and compare the results
+    ///
+    /// Users are impersonated through `config.header`, replacing any
+    /// `--act-as` or configured value. Fails before sending anything when
+    /// fewer than two users are given.
+    pub async fn test_act_as(
+        &self,
+        config: &ActAsConfig,
+        users: &[String],
+    ) -> Result<ActAsTestResult> {
+        if users.len() < 2 {
+            return Err(GleanMcpError::Config(
+                "Comparing users needs at least two (act_as.users or --act-as-users)".to_string(),
+            ));
+        }
+        let transports: Vec<_> = users
+            .iter()
+            .enumerate()
+            .map(|(index, user)| {
+                (
+                    user_alias(index),
+                    self.transport().clone().with_header(&config.header, user),
+                )
+            })
+            .collect();
+
+        let mut comparisons = Vec::new();
+        for tool in &config.tools {
+            let queries = if config.queries.is_empty() {
+                vec![TestQueryGenerator::generate_test_query(tool)]
+            } else {
+                config.queries.clone()
+            };
+            for query in queries {
+                let mut calls = Vec::with_capacity(users.len());
+                let mut seen: BTreeMap<PermissionDocument, Vec<String>> = BTreeMap::new();
+                for (user, transport) in &transports {
+                    let (status, result) = self.search_as(transport, tool, &query).await;
+                    let found: BTreeSet<PermissionDocument> =
+                        result.as_ref().map(documents).unwrap_or_default();
+                    for document in &found {
+                        seen.entry(document.clone()).or_default().push(user.clone());
+                    }
+                    calls.push(ActAsCall {
+                        user: user.clone(),
+                        success: result.is_ok(),
+                        status,
+                        error: result.err(),
+                        results: found.len(),
+                    });
+                }
+                let shared = seen
+                    .values()
+                    .filter(|seen_by| seen_by.len() == users.len())
+                    .count();
+                let differences = seen
+                    .into_iter()
+                    .filter(|(_, seen_by)| seen_by.len() < users.len())
+                    .map(|(document, users)| ActAsDifference { document, users })
+                    .collect();
+                let comparison = ActAsComparison {
+                    tool: tool.clone(),
+                    query,
+                    calls,
+                    shared,
+                    differences,
+                };
+                self.reporter().info(&format!(
+                    "{} {:?}: {}",
+                    comparison.tool,
+                    comparison.query,
+                    if comparison.passed() { "ok" } else { "failed" }
+                ));
+                comparisons.push(comparison);
+            }
+        }
+
+        Ok(ActAsTestResult {
+            success: comparisons.iter().all(ActAsComparison::passed),
+            endpoint: self.server_url().to_string(),
+            users: users.to_vec(),
+            comparisons,
+        })
+    }
+}
@@ -0,0 +1,389 @@
+//! Glean agents exposed as MCP tools
+//!
+//! Deployments with agents advertise `list_agents` and `run_agent`. When a
+//! run selects `run_agent`, [`GleanMCPInspector::discover_agents`] finds the
+//! agents to run with `list_agents` (or takes them from `agents.agents`) and
+//! [`GleanMCPInspector::run_agents`] runs each with a test input. Runs that
+//! answer as still in progress are polled with `agents.status_tool` until
+//! they finish or `agents.max_wait_secs` runs out. Each agent gets its own
+//! result, named `run_agent#<agent id>`, alongside the regular tools.
+
+use super::{
+    CancelReason, ErrorClass, GleanMCPInspector, McpTransport, RequestTrace, SkipReason,
+    TestAllOptions, ToolInfo, ToolTestResult, response_text,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value, json};
+use smol::Timer;
+use std::time::{Duration, Instant};
+
+/// Tool listing the deployment's agents
+pub const LIST_AGENTS: &str = "list_agents";
+/// Tool starting an agent run
+pub const RUN_AGENT: &str = "run_agent";
+
+/// Run statuses meaning the agent is still working
+const RUNNING_STATUSES: [&str; 5] = ["pending", "queued", "running", "in_progress", "started"];
+/// Run statuses meaning the agent gave up
+const FAILED_STATUSES: [&str; 5] = ["failed", "error", "cancelled", "canceled", "timed_out"];
+
+/// `run_agent` parameters naming the agent, in order of preference
+const AGENT_ID_PARAMETERS: [&str; 3] = ["agent_id", "agentId", "id"];
+/// `run_agent` parameters taking the input, in order of preference
+const INPUT_PARAMETERS: [&str; 4] = ["input", "message", "query", "prompt"];
+
+/// Settings for testing agents through `run_agent`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AgentCheckConfig {
+    /// Agent ids to run; empty runs the first `max_agents` that `list_agents` returns
+    pub agents: Vec<String>,
+    pub max_agents: usize,
+    /// Input every agent is run with
+    pub input: String,
+    /// Tool polled with a run's `run_id` while the agent is still working
+    pub status_tool: String,
+    pub poll_interval_secs: u64,
+    /// Longest an agent run may take, polling included
+    pub max_wait_secs: u64,
+}
+
+impl Default for AgentCheckConfig {
+    fn default() -> Self {
+        Self {
+            agents: Vec::new(),
+            max_agents: 3,
+            input: "Summarize what you can help with in one sentence.".to_string(),
+            status_tool: "get_agent_run".to_string(),
+            poll_interval_secs: 2,
+            max_wait_secs: 300,
+        }
+    }
+}
+
+/// An agent `run_agent` can start
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AgentInfo {
+    pub id: String,
+    pub name: Option<String>,
+}
+
+impl AgentInfo {
+    /// Name of the agent's result, e.g. `run_agent#sales-assistant`
+    #[must_use]
+    pub fn result_name(&self) -> String {
+        format!("{RUN_AGENT}#{}", self.id)
+    }
+
+    fn label(&self) -> &str {
+        self.name.as_deref().unwrap_or(&self.id)
+    }
+}
+
+/// Where an agent run stands after a `run_agent` or status call
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum RunState {
+    Finished,
+    /// Still working, with the run id to poll when the response had one
+    Running(Option<String>),
+    /// Why the run failed
+    Failed(String),
+}
+
+/// The JSON a tool answered with: `structuredContent`, JSON text content, or the result itself
+fn payload(response: &Value) -> Value {
+    response
+        .get("structuredContent")
+        .cloned()
+        .or_else(|| serde_json::from_str(&response_text(response)).ok())
+        .unwrap_or_else(|| response.clone())
+}
+
+/// The first of `keys` holding a string or number in `object`
+fn field(object: &Value, keys: &[&str]) -> Option<String> {
+    keys.iter()
+        .filter_map(|key| object.get(key))
+        .find_map(|value| match value {
+            Value::String(text) => Some(text.clone()),
+            Value::Number(number) => Some(number.to_string()),
+            _ => None,
+        })
+}
+
+/// Agents in a `list_agents` response, either a list or an object with an `agents` list
+#[must_use]
+pub fn agents_in(response: &Value) -> Vec<AgentInfo> {
+    let payload = payload(response);
+    let list = payload
+        .as_array()
+        .or_else(|| payload.get("agents").and_then(Value::as_array));
+    list.into_iter()
+        .flatten()
+        .filter_map(|agent| match agent {
+            Value::String(id) => Some(AgentInfo {
+                id: id.clone(),
+                name: None,
+            }),
+            _ => Some(AgentInfo {
+                id: field(agent, &AGENT_ID_PARAMETERS)?,
+                name: field(agent, &["name", "display_name", "displayName"]),
+            }),
+        })
+        .collect()
+}
+
+fn run_state(response: &Value) -> RunState {
+    if response.get("isError") == Some(&Value::Bool(true)) {
+        return RunState::Failed(format!(
+            "Agent run returned an error: {}",
+            response_text(response)
+        ));
+    }
+    let payload = payload(response);
+    let status = field(&payload, &["status", "state"])
+        .unwrap_or_default()
+        .to_lowercase();
+    if RUNNING_STATUSES.contains(&status.as_str()) {
+        RunState::Running(field(&payload, &["run_id", "runId", "execution_id", "id"]))
+    } else if FAILED_STATUSES.contains(&status.as_str()) {
+        RunState::Failed(format!("Agent run ended with status '{status}'"))
+    } else {
+        RunState::Finished
+    }
+}
+
+/// `run_agent` arguments, using the parameter names the tool's schema declares
+fn run_arguments(tool: &ToolInfo, agent_id: &str, input: &str) -> Value {
+    let properties = tool
+        .schema
+        .as_ref()
+        .and_then(|schema| schema.get("properties"))
+        .and_then(Value::as_object);
+    let pick = |candidates: &[&'static str]| {
+        properties
+            .and_then(|properties| {
+                candidates
+                    .iter()
+                    .find(|candidate| properties.contains_key(**candidate))
+            })
+            .copied()
+            .unwrap_or(candidates[0])
+    };
+    let mut arguments = Map::new();
+    arguments.insert(pick(&AGENT_ID_PARAMETERS).to_string(), json!(agent_id));
+    arguments.insert(pick(&INPUT_PARAMETERS).to_string(), json!(input));
+    Value::Object(arguments)
+}
+
+impl GleanMCPInspector {
+    /// Agents to run: `agents.agents`, or the first `agents.max_agents` that `list_agents` returns
+    ///
+    /// `list_agents` is called with arguments for its advertised schema, if
+    /// any. `Err` is the `run_agent` result reporting why there are none.
+    #[allow(clippy::future_not_send)]
+    #[allow(clippy::cast_possible_truncation)]
+    pub(crate) async fn discover_agents(
+        &self,
+        transport: &McpTransport,
+        endpoint_url: &str,
+        list_agents: Option<&ToolInfo>,
+        options: &TestAllOptions,
+    ) -> std::result::Result<Vec<AgentInfo>, ToolTestResult> {
+        let config = &options.agents;
+        if !config.agents.is_empty() {
+            return Ok(config
+                .agents
+                .iter()
+                .map(|id| AgentInfo {
+                    id: id.clone(),
+                    name: None,
+                })
+                .collect());
+        }
+        let arguments = list_agents.map_or_else(|| json!({}), |tool| options.arguments_for(tool).0);
+        let started = Instant::now();
+        let Some(response) = options
+            .cancel
+            .run_until_cancelled(Self::test_tool_with_retry(
+                transport,
+                endpoint_url,
+                LIST_AGENTS,
+                &arguments,
+                Duration::from_secs(options.timeout),
+                &mut RequestTrace::default(),
+                options.retry_policy(),
+                self.reporter(),
+            ))
+            .await
+        else {
+            return Err(ToolTestResult::new_skipped(
+                RUN_AGENT.to_string(),
+                SkipReason::Interrupted,
+                None,
+            ));
+        };
+        let failure = |message: String, class: ErrorClass| {
+            ToolTestResult::new_error(
+                RUN_AGENT.to_string(),
+                started.elapsed().as_millis() as u64,
+                String::new(),
+                message,
+            )
+            .with_error_class(class)
+        };
+        match response {
+            Ok(response) => {
+                let mut agents = agents_in(&response);
+                if agents.is_empty() {
+                    return Err(failure(
+                        format!("{LIST_AGENTS} returned no agents to run"),
+                        ErrorClass::Validation,
+                    ));
+                }
+                agents.truncate(config.max_agents.max(1));
+                Ok(agents)
+            }
+            Err(e) => Err(failure(
+                format!("Could not discover agents with {LIST_AGENTS}: {e}"),
+                ErrorClass::classify(&e),
+            )),
+        }
+    }
+
+    /// Run each of `agents` with `tool` (`run_agent`), one result per agent
+    ///
+    /// Agents not finished when the run is cancelled are skipped as
+    /// interrupted; a failure under `fail_fast` cancels the rest.
+    #[allow(clippy::future_not_send)]
+    pub(crate) async fn run_agents(
+        &self,
+        transport: &McpTransport,
+        endpoint_url: &str,
+        tool: &ToolInfo,
+        agents: &[AgentInfo],
+        options: &TestAllOptions,
+    ) -> Vec<ToolTestResult> {
+        let mut results = Vec::new();
+        for (index, agent) in agents.iter().enumerate() {
+            self.reporter().tool_started(&agent.result_name());
+            let run = self.run_agent(transport, endpoint_url, tool, agent, options);
+            let Some(result) = options.cancel.run_until_cancelled(run).await else {
+                results.extend(agents[index..].iter().map(|agent| {
+                    ToolTestResult::new_skipped(agent.result_name(), SkipReason::Interrupted, None)
+                }));
+                break;
+            };
+            self.reporter().tool_finished(&result);
+            if options.fail_fast && !result.success {
+                options.cancel.cancel(CancelReason::FailFast);
+            }
+            results.push(result);
+        }
+        results
+    }
+
+    /// Start `agent` and poll its run until it finishes
+    #[allow(clippy::future_not_send)]
+    #[allow(clippy::cast_possible_truncation)]
+    async fn run_agent(
+        &self,
+        transport: &McpTransport,
+        endpoint_url: &str,
+        tool: &ToolInfo,
+        agent: &AgentInfo,
+        options: &TestAllOptions,
+    ) -> ToolTestResult {
+        let config = &options.agents;
+        let timeout = Duration::from_secs(options.timeout);
+        let poll_interval = Duration::from_secs(config.poll_interval_secs.max(1));
+        let max_wait = Duration::from_secs(config.max_wait_secs);
+        let started = Instant::now();
+        let failure = |message: String, class: ErrorClass| {
+            ToolTestResult::new_error(
+                agent.result_name(),
+                started.elapsed().as_millis() as u64,
+                config.input.clone(),
+                message,
+            )
+            .with_error_class(class)
+        };
+
+        let mut trace = RequestTrace::default();
+        let mut response = Self::test_tool_with_retry(
+            transport,
+            endpoint_url,
+            &tool.name,
+            &run_arguments(tool, &agent.id, &config.input),
+            timeout,
+            &mut trace,
+            options.retry_policy(),
+            self.reporter(),
+        )
+        .await;
+        let mut polls = 0;
+        let response = loop {
+            let current = match response {
+                Ok(current) => current,
+                Err(e) => {
+                    return failure(e.to_string(), ErrorClass::classify(&e))
+                        .with_error_detail(trace.error_detail.clone());
+                }
+            };
+            let run_id = match run_state(&current) {
+                RunState::Finished => break current,
+                RunState::Failed(reason) => return failure(reason, ErrorClass::Server),
+                RunState::Running(None) => {
+                    return failure(
+                        "Agent run is still in progress, but the response has no run id to poll"
+                            .to_string(),
+                        ErrorClass::Validation,
+                    );
+                }
+                RunState::Running(Some(run_id)) => run_id,
+            };
+            if started.elapsed() + poll_interval > max_wait {
+                return failure(
+                    format!(
+                        "Agent run {run_id} timed out: still running after {}s",
+                        max_wait.as_secs()
+                    ),
+                    ErrorClass::Network,
+                );
+            }
+            Timer::after(poll_interval).await;
+            polls += 1;
+            response = Self::test_tool_with_retry(
+                transport,
+                endpoint_url,
+                &config.status_tool,
+                &json!({ "run_id": run_id }),
+                timeout,
+                &mut trace,
+                options.retry_policy(),
+                self.reporter(),
+            )
+            .await;
+        };
+
+        let note = if polls == 0 {
+            format!("Agent {} finished", agent.label())
+        } else {
+            format!(
+                "Agent {} finished after {polls} poll(s) of {}",
+                agent.label(),
+                config.status_tool
+            )
+        };
+        ToolTestResult::new_success(
+            agent.result_name(),
+            started.elapsed().as_millis() as u64,
+            config.input.clone(),
+            response,
+        )
+        .with_check_outcome(Ok(Some(note)))
+        .with_response_size(&options.response_size)
+        .with_timing(trace.timing)
+        .with_correlation_id(trace.correlation_id)
+        .with_request_ids(trace.request_ids)
+    }
+}
@@ -0,0 +1,134 @@
+//! Tool arguments generated from `inputSchema`
+//!
+//! `tools/list` describes each tool's parameters as JSON Schema.
+//! [`arguments_from_schema`] builds the smallest argument object the schema
+//! accepts: every required property, with a sample value of the right type.
+//! String parameters reuse the tool's test query, so known tools keep
+//! meaningful inputs and new tools get tested without code changes.
+
+use super::TestQueryGenerator;
+use serde_json::{Map, Value, json};
+
+/// Parameters the built-in tools take their test query through
+const QUERY_PARAMETERS: [&str; 3] = ["query", "message", "url"];
+
+/// Schemas nested deeper than this get a `null` sample instead of recursing further
+const MAX_DEPTH: usize = 8;
+
+/// Minimal valid arguments for `tool_name`, or `None` if `schema` declares no properties
+///
+/// Optional properties are left out, except a `query`/`message`/`url`
+/// parameter, which carries the tool's test query.
+#[must_use]
+pub fn arguments_from_schema(tool_name: &str, schema: &Value) -> Option<Value> {
+    arguments_from_schema_with_query(schema, &TestQueryGenerator::generate_test_query(tool_name))
+}
+
+/// Like [`arguments_from_schema`], with string parameters carrying `query`
+#[must_use]
+pub fn arguments_from_schema_with_query(schema: &Value, query: &str) -> Option<Value> {
+    let properties = schema.get("properties")?.as_object()?;
+
+    let mut arguments = Map::new();
+    for (name, property) in properties {
+        if is_required(schema, name) || QUERY_PARAMETERS.contains(&name.as_str()) {
+            arguments.insert(name.clone(), sample_value(name, property, query, 0));
+        }
+    }
+    Some(Value::Object(arguments))
+}
+
+fn is_required(schema: &Value, name: &str) -> bool {
+    schema
+        .get("required")
+        .and_then(Value::as_array)
+        .is_some_and(|required| required.iter().any(|field| field.as_str() == Some(name)))
+}
+
+/// A value `schema` accepts, preferring its own `const`, `default`, `enum`, or `examples`
+fn sample_value(name: &str, schema: &Value, query: &str, depth: usize) -> Value {
+    if depth > MAX_DEPTH {
+        return Value::Null;
+    }
+    if let Some(value) = schema.get("const").or_else(|| schema.get("default")) {
+        return value.clone();
+    }
+    if let Some(value) = ["enum", "examples"]
+        .iter()
+        .find_map(|key| schema.get(key)?.as_array()?.first())
+    {
+        return value.clone();
+    }
+    if let Some(variant) = ["anyOf", "oneOf", "allOf"]
+        .iter()
+        .find_map(|key| schema.get(key)?.as_array()?.first())
+    {
+        return sample_value(name, variant, query, depth + 1);
+    }
+
+    match schema_type(schema) {
+        Some("string") => sample_string(name, schema, query),
+        Some("integer") => schema
+            .get("minimum")
+            .and_then(Value::as_i64)
+            .map_or_else(|| json!(1), |minimum| json!(minimum.max(1))),
+        Some("number") => schema
+            .get("minimum")
+            .and_then(Value::as_f64)
+            .map_or_else(|| json!(1), |minimum| json!(minimum.max(1.0))),
+        Some("boolean") => json!(false),
+        Some("array") => {
+            let min_items = schema.get("minItems").and_then(Value::as_u64).unwrap_or(0);
+            let items = schema.get("items").map_or(Value::Null, |items| {
+                sample_value(name, items, query, depth + 1)
+            });
+            if min_items == 0 || items.is_null() {
+                json!([])
+            } else {
+                Value::Array(vec![items; usize::try_from(min_items).unwrap_or(1)])
+            }
+        }
+        Some("object") => {
+            let properties = schema.get("properties").and_then(Value::as_object);
+            let object = properties
+                .into_iter()
+                .flatten()
+                .filter(|(field, _)| is_required(schema, field))
+                .map(|(field, property)| {
+                    (
+                        field.clone(),
+                        sample_value(field, property, query, depth + 1),
+                    )
+                })
+                .collect();
+            Value::Object(object)
+        }
+        _ => Value::Null,
+    }
+}
+
+/// The schema's type, taking the first non-null entry of a type list
+fn schema_type(schema: &Value) -> Option<&str> {
+    match schema.get("type")? {
+        Value::String(kind) => Some(kind),
+        Value::Array(kinds) => kinds
+            .iter()
+            .filter_map(Value::as_str)
+            .find(|kind| *kind != "null"),
+        _ => None,
+    }
+}
+
+fn sample_string(name: &str, schema: &Value, query: &str) -> Value {
+    let format = schema.get("format").and_then(Value::as_str);
+    let wants_url = matches!(format, Some("uri" | "url")) || name.contains("url");
+    if wants_url && !query.starts_with("http") {
+        return json!("https://www.glean.com");
+    }
+    match format {
+        Some("date-time") => json!(chrono::Utc::now().to_rfc3339()),
+        Some("date") => json!(chrono::Utc::now().format("%Y-%m-%d").to_string()),
+        Some("email") => json!("noreply@glean.com"),
+        _ => json!(query),
+    }
+}
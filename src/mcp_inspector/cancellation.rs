@@ -0,0 +1,289 @@
+//! Cancelling an in-flight tool call
+//!
+//! [`GleanMCPInspector::test_cancellation`] starts a slow tool call (`chat` by
+//! default), sends `notifications/cancelled` for it in the same session, and
+//! watches what the server does with the call. Stopping work, by closing the
+//! call or answering it with an error, passes; finishing the call anyway or
+//! leaving it running fails. The session must still answer `ping` afterwards.
+
+use super::{GleanMCPInspector, StreamRequest, parse_event_stream};
+use crate::{RunOutcome, TestQueryGenerator};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use smol::Timer;
+use std::fmt::Write;
+use std::time::{Duration, Instant};
+
+/// What the server did with the cancelled call
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CancelVerdict {
+    /// The call was closed without a response
+    Stopped,
+    /// The call was answered with a JSON-RPC error
+    CancelledError,
+    /// The call returned its full result after the cancellation
+    CompletedAnyway,
+    /// The call was still open when the wait ran out
+    StillRunning,
+    /// The call finished before the cancellation was sent, so nothing was tested
+    FinishedFirst,
+}
+
+impl CancelVerdict {
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Stopped => "stopped (closed without a response)",
+            Self::CancelledError => "stopped (answered with an error)",
+            Self::CompletedAnyway => "completed anyway",
+            Self::StillRunning => "still running when the wait ran out",
+            Self::FinishedFirst => "finished before the cancellation was sent",
+        }
+    }
+
+    const fn stopped(self) -> bool {
+        matches!(self, Self::Stopped | Self::CancelledError)
+    }
+}
+
+/// Results of cancelling an in-flight call
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CancelTestResult {
+    pub success: bool,
+    pub endpoint: String,
+    /// `Mcp-Session-Id` of the session; `None` for stateless servers
+    pub session_id: Option<String>,
+    pub tool: String,
+    /// When the cancellation was sent, from the start of the call
+    pub cancel_after_ms: u64,
+    /// HTTP status of the `notifications/cancelled` request
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cancel_status: Option<u16>,
+    pub verdict: Option<CancelVerdict>,
+    /// When the call ended, from the start of the call
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub call_duration_ms: Option<u64>,
+    /// The session answered `ping` after the cancellation
+    pub session_usable: bool,
+    pub duration_ms: u64,
+    pub error: Option<String>,
+}
+
+impl CancelTestResult {
+    /// How the test ended, from CI's point of view
+    #[must_use]
+    pub fn outcome(&self) -> RunOutcome {
+        if self.success {
+            RunOutcome::Success
+        } else if self.error.is_some() {
+            RunOutcome::ConnectivityFailure
+        } else if self.verdict == Some(CancelVerdict::FinishedFirst) {
+            RunOutcome::Error
+        } else {
+            RunOutcome::ValidationFailure
+        }
+    }
+
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn format_text(&self) -> String {
+        let mut output = String::new();
+        let _ = writeln!(
+            output,
+            "🛑 Cancellation: cancelled a {} call on {} after {:.1}s",
+            self.tool,
+            self.endpoint,
+            self.cancel_after_ms as f64 / 1000.0
+        );
+        if let Some(error) = &self.error {
+            let _ = writeln!(output, "Error: {error}");
+            return output;
+        }
+        let _ = writeln!(
+            output,
+            "  Session: {}",
+            self.session_id
+                .as_deref()
+                .unwrap_or("none (stateless server)")
+        );
+        let _ = writeln!(
+            output,
+            "  Cancellation: {}",
+            self.cancel_status.map_or_else(
+                || "no response".to_string(),
+                |status| format!("HTTP {status}")
+            )
+        );
+        let _ = writeln!(
+            output,
+            "  Call: {}{}",
+            self.verdict.map_or("unknown", CancelVerdict::as_str),
+            self.call_duration_ms.map_or_else(String::new, |ms| format!(
+                ", ended at {:.1}s",
+                ms as f64 / 1000.0
+            ))
+        );
+        let _ = writeln!(
+            output,
+            "  Session usable afterwards: {}",
+            if self.session_usable { "yes" } else { "no" }
+        );
+        output
+    }
+}
+
+/// The JSON-RPC response to request `id` in a call's body, streamed or not
+fn response_to(body: &str, streamed: bool, id: u64) -> Option<Value> {
+    let is_response = |message: &Value| {
+        message.get("id") == Some(&json!(id))
+            && (message.get("result").is_some() || message.get("error").is_some())
+    };
+    if streamed {
+        parse_event_stream(body)
+            .iter()
+            .filter_map(|event| serde_json::from_str(&event.data).ok())
+            .find(is_response)
+    } else {
+        serde_json::from_str(body).ok().filter(is_response)
+    }
+}
+
+impl GleanMCPInspector {
+    /// Start `tool` on the default endpoint and cancel it after `cancel_after`
+    ///
+    /// The call is watched for up to `wait` after the cancellation. The test
+    /// is inconclusive when the call finishes before the cancellation is sent.
+    #[allow(clippy::cast_possible_truncation)]
+    pub async fn test_cancellation(
+        &self,
+        tool: &str,
+        query: Option<&str>,
+        cancel_after: Duration,
+        wait: Duration,
+    ) -> CancelTestResult {
+        const CALL_ID: u64 = 1;
+        let started = Instant::now();
+        let mut result = CancelTestResult {
+            success: false,
+            endpoint: self.server_url().to_string(),
+            session_id: None,
+            tool: tool.to_string(),
+            cancel_after_ms: cancel_after.as_millis() as u64,
+            cancel_status: None,
+            verdict: None,
+            call_duration_ms: None,
+            session_usable: false,
+            duration_ms: 0,
+            error: None,
+        };
+        let (session_id, transport, _) = match self.open_session().await {
+            Ok(session) => session,
+            Err(error) => {
+                result.error = Some(error);
+                return result;
+            }
+        };
+        result.session_id = session_id;
+
+        let query = query.map_or_else(
+            || TestQueryGenerator::generate_test_query(tool),
+            ToString::to_string,
+        );
+        let call = StreamRequest {
+            body: Some(json!({
+                "jsonrpc": "2.0",
+                "id": CALL_ID,
+                "method": "tools/call",
+                "params": {
+                    "name": tool,
+                    "arguments": TestQueryGenerator::generate_arguments(tool, &query)
+                }
+            })),
+            wait: cancel_after + wait,
+            ..StreamRequest::default()
+        };
+        let cancel = json!({
+            "jsonrpc": "2.0",
+            "method": "notifications/cancelled",
+            "params": { "requestId": CALL_ID, "reason": "Cancelled by glean-mcp-test" }
+        });
+
+        let call_started = Instant::now();
+        let ((response, call_elapsed), (cancel_response, cancel_elapsed)) = smol::future::zip(
+            async {
+                let response = transport.read_stream(self.server_url(), &call).await;
+                (response, call_started.elapsed())
+            },
+            async {
+                Timer::after(cancel_after).await;
+                self.reporter().info(&format!(
+                    "Cancelling the {tool} call after {:.1}s",
+                    cancel_after.as_secs_f64()
+                ));
+                let response = transport.post_json_rpc(self.server_url(), &cancel).await;
+                (response, call_started.elapsed())
+            },
+        )
+        .await;
+        result.call_duration_ms = Some(call_elapsed.as_millis() as u64);
+        result.cancel_status = cancel_response
+            .ok()
+            .filter(|response| response.success)
+            .and_then(|response| response.status);
+
+        let response = match response {
+            Ok(response) => response,
+            Err(e) => {
+                result.error = Some(e.to_string());
+                return result;
+            }
+        };
+        if let Some(status) = response.status.filter(|status| *status >= 400) {
+            result.error = Some(format!("The call failed with HTTP {status}"));
+            return result;
+        }
+        let streamed = response
+            .header("content-type")
+            .is_some_and(|content_type| content_type.starts_with("text/event-stream"));
+        let answer = response_to(&response.body, streamed, CALL_ID);
+        let verdict = if call_elapsed < cancel_elapsed {
+            if answer.is_none() {
+                result.error = Some(format!(
+                    "The call failed before it could be cancelled: {}",
+                    response.stderr
+                ));
+                return result;
+            }
+            CancelVerdict::FinishedFirst
+        } else {
+            match answer {
+                Some(answer) if answer.get("error").is_some() => CancelVerdict::CancelledError,
+                Some(_) => CancelVerdict::CompletedAnyway,
+                // curl reports its --max-time as exit code 28
+                None if response.stderr.contains("(28)") => CancelVerdict::StillRunning,
+                None => CancelVerdict::Stopped,
+            }
+        };
+        result.verdict = Some(verdict);
+        if verdict == CancelVerdict::FinishedFirst {
+            self.reporter().warning(&format!(
+                "The {tool} call finished in {:.1}s, before the cancellation; use a slower query or a shorter --cancel-after",
+                call_elapsed.as_secs_f64()
+            ));
+        }
+
+        result.session_usable = transport
+            .post_json_rpc(
+                self.server_url(),
+                &json!({ "jsonrpc": "2.0", "id": CALL_ID + 1, "method": "ping" }),
+            )
+            .await
+            .ok()
+            .and_then(|response| serde_json::from_str::<Value>(&response.body).ok())
+            .is_some_and(|body| body.get("result").is_some());
+        result.duration_ms = started.elapsed().as_millis() as u64;
+        result.success = verdict.stopped() && result.session_usable;
+        result
+    }
+}
@@ -0,0 +1,129 @@
+//! Per-tool request/response transcripts
+//!
+//! With `--capture-dir`, every exchange a run makes is kept in
+//! `<dir>/<run-id>/<tool>.json`, one file per tool with both endpoints' calls
+//! and every retry. Requests other than `tools/call` go to a file named after
+//! the method, e.g. `_tools-list.json`. A tool's file is rewritten under a
+//! lock after each of its exchanges, so parallel workers never interleave
+//! writes and a run cut short still leaves what it captured. Transcripts are
+//! redacted like all other output (see [`crate::redact`]).
+
+use super::{ConnectionTiming, RawResponse};
+use crate::{Result, redacted_json};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+
+/// One request and what came back, as written to a transcript
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CapturedExchange {
+    pub timestamp: String,
+    pub endpoint: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub correlation_id: Option<String>,
+    pub request: Value,
+    /// Time the request was allowed (curl `--max-time`), in milliseconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_ms: Option<u64>,
+    /// Whether the request completed (curl exit status)
+    pub success: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<u16>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub headers: BTreeMap<String, String>,
+    /// Response body, parsed when it is JSON
+    pub response: Value,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub stderr: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timing: Option<ConnectionTiming>,
+}
+
+/// Writes transcripts for one run; clones share the same files
+#[derive(Debug, Clone)]
+pub struct TranscriptCapture {
+    dir: PathBuf,
+    transcripts: Arc<Mutex<BTreeMap<String, Vec<CapturedExchange>>>>,
+}
+
+impl TranscriptCapture {
+    /// Capture into `<dir>/<run_id>/`
+    #[must_use]
+    pub fn new(dir: &Path, run_id: &str) -> Self {
+        Self {
+            dir: dir.join(run_id),
+            transcripts: Arc::default(),
+        }
+    }
+
+    /// Directory the transcripts are written to
+    #[must_use]
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Add an exchange, sent with `timeout`, to its tool's transcript and rewrite the file
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn record(
+        &self,
+        endpoint: &str,
+        request: &Value,
+        response: &RawResponse,
+        timeout: Duration,
+    ) -> Result<()> {
+        let exchange = CapturedExchange {
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            endpoint: endpoint.to_string(),
+            correlation_id: response.correlation_id.clone(),
+            request: request.clone(),
+            timeout_ms: Some(timeout.as_millis() as u64),
+            success: response.success,
+            status: response.status,
+            headers: response.headers.clone(),
+            response: serde_json::from_str(&response.body)
+                .unwrap_or_else(|_| Value::String(response.body.clone())),
+            stderr: response.stderr.trim().to_string(),
+            timing: response.timing,
+        };
+
+        let name = transcript_name(request);
+        let mut transcripts = self
+            .transcripts
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let exchanges = transcripts.entry(name.clone()).or_default();
+        exchanges.push(exchange);
+        // Written while holding the lock, so two workers never write one file at once
+        std::fs::create_dir_all(&self.dir)?;
+        std::fs::write(
+            self.dir.join(format!("{name}.json")),
+            redacted_json(exchanges)?,
+        )?;
+        drop(transcripts);
+        Ok(())
+    }
+}
+
+/// File name (without `.json`) for `request`: the tool for `tools/call`, `_<method>` otherwise
+fn transcript_name(request: &Value) -> String {
+    let method = request
+        .get("method")
+        .and_then(Value::as_str)
+        .unwrap_or("unknown");
+    let name = match request.pointer("/params/name").and_then(Value::as_str) {
+        Some(tool) if method == "tools/call" => tool.to_string(),
+        _ => format!("_{}", method.replace('/', "-")),
+    };
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
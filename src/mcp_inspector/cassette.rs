@@ -0,0 +1,279 @@
+//! Record-and-replay of MCP interactions
+//!
+//! A cassette is a JSON file holding every JSON-RPC request/response pair of
+//! a run. In record mode the transport appends each live interaction; in
+//! replay mode it answers from the cassette without touching the network.
+//! Requests are matched on endpoint, method, and params (the JSON-RPC `id` is
+//! ignored); repeated identical requests are served in recorded order.
+
+use super::transport::RawResponse;
+use crate::{GleanMcpError, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+const CASSETTE_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CassetteMode {
+    Record,
+    Replay,
+}
+
+/// One recorded request/response pair
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Interaction {
+    pub endpoint: String,
+    pub request: Value,
+    pub response: RawResponse,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CassetteFile {
+    version: u32,
+    recorded_at: String,
+    interactions: Vec<Interaction>,
+}
+
+#[derive(Debug, Default)]
+struct CassetteState {
+    interactions: Vec<Interaction>,
+    /// Replay: which interactions have already been served
+    consumed: Vec<bool>,
+}
+
+/// A cassette file in record or replay mode
+#[derive(Debug)]
+pub struct Cassette {
+    path: PathBuf,
+    mode: CassetteMode,
+    state: Mutex<CassetteState>,
+}
+
+impl Cassette {
+    /// Start recording a new cassette at `path` (overwritten on first interaction)
+    #[must_use]
+    pub fn for_recording(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            mode: CassetteMode::Record,
+            state: Mutex::new(CassetteState::default()),
+        }
+    }
+
+    /// Load an existing cassette for replay
+    pub fn for_replay(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let contents = std::fs::read_to_string(&path).map_err(|e| {
+            GleanMcpError::Config(format!("Failed to read cassette {}: {e}", path.display()))
+        })?;
+        let file: CassetteFile = serde_json::from_str(&contents)?;
+        if file.version != CASSETTE_VERSION {
+            return Err(GleanMcpError::Config(format!(
+                "Unsupported cassette version {} in {}",
+                file.version,
+                path.display()
+            )));
+        }
+
+        let consumed = vec![false; file.interactions.len()];
+        Ok(Self {
+            path,
+            mode: CassetteMode::Replay,
+            state: Mutex::new(CassetteState {
+                interactions: file.interactions,
+                consumed,
+            }),
+        })
+    }
+
+    #[must_use]
+    pub const fn mode(&self) -> CassetteMode {
+        self.mode
+    }
+
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn lock_state(&self) -> std::sync::MutexGuard<'_, CassetteState> {
+        self.state
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    /// Append an interaction and flush the cassette to disk
+    ///
+    /// The lock is held while writing, so parallel workers can't overwrite a
+    /// newer cassette with an older snapshot.
+    pub fn record_interaction(
+        &self,
+        endpoint: &str,
+        request: &Value,
+        response: &RawResponse,
+    ) -> Result<()> {
+        let mut state = self.lock_state();
+        state.interactions.push(Interaction {
+            endpoint: endpoint.to_string(),
+            request: request.clone(),
+            response: response.clone(),
+        });
+        self.write(&state.interactions)
+    }
+
+    fn write(&self, interactions: &[Interaction]) -> Result<()> {
+        let file = CassetteFile {
+            version: CASSETTE_VERSION,
+            recorded_at: chrono::Utc::now().to_rfc3339(),
+            interactions: interactions.to_vec(),
+        };
+        if let Some(parent) = self.path.parent()
+            && !parent.as_os_str().is_empty()
+        {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&self.path, serde_json::to_string_pretty(&file)?)?;
+        Ok(())
+    }
+
+    /// Serve the next unconsumed interaction matching the request
+    pub fn replay_interaction(&self, endpoint: &str, request: &Value) -> Result<RawResponse> {
+        let mut state = self.lock_state();
+        let position =
+            state
+                .interactions
+                .iter()
+                .zip(&state.consumed)
+                .position(|(interaction, used)| {
+                    !used
+                        && interaction.endpoint == endpoint
+                        && Self::request_key(&interaction.request) == Self::request_key(request)
+                });
+
+        let Some(index) = position else {
+            return Err(GleanMcpError::Network(format!(
+                "No cassette interaction for {} {} in {}",
+                request.get("method").and_then(Value::as_str).unwrap_or("?"),
+                endpoint,
+                self.path.display()
+            )));
+        };
+
+        state.consumed[index] = true;
+        Ok(state.interactions[index].response.clone())
+    }
+
+    /// Matching key for a JSON-RPC request: method and params, ignoring `id`
+    fn request_key(request: &Value) -> (Option<&Value>, Option<&Value>) {
+        (request.get("method"), request.get("params"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{McpTransport, MockServer, MockServerConfig};
+    use serde_json::json;
+
+    fn cassette_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("glean-mcp-test-{}-{name}.json", std::process::id()))
+    }
+
+    fn call(id: u64, query: &str) -> Value {
+        json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": "tools/call",
+            "params": { "name": "chat", "arguments": { "message": query } }
+        })
+    }
+
+    #[test]
+    fn replays_a_recorded_run_without_the_server() {
+        let path = cassette_path("round-trip");
+        let requests = [call(1, "first"), call(2, "second"), call(3, "first")];
+
+        let (endpoint, recorded) = smol::block_on(async {
+            let server = MockServer::start("127.0.0.1:0", MockServerConfig::default())
+                .await
+                .unwrap();
+            let endpoint = server.endpoint_url("default");
+            let transport = McpTransport::new(None).with_cassette(Cassette::for_recording(&path));
+            let mut responses = Vec::new();
+            for request in &requests {
+                responses.push(transport.post_json_rpc(&endpoint, request).await.unwrap());
+            }
+            (endpoint, responses)
+        });
+        // The server is gone, so only the cassette can answer
+
+        let transport = McpTransport::new(None).with_cassette(Cassette::for_replay(&path).unwrap());
+        // Ids differ from the recording and the identical calls come back in recorded order
+        for (offset, (request, recorded)) in requests.iter().zip(&recorded).enumerate() {
+            let mut request = request.clone();
+            request["id"] = json!(100 + offset);
+            let replayed = smol::block_on(transport.post_json_rpc(&endpoint, &request)).unwrap();
+            assert_eq!(replayed.status, Some(200));
+            assert_eq!(replayed.body, recorded.body);
+        }
+
+        let unmatched = smol::block_on(transport.post_json_rpc(&endpoint, &call(4, "first")));
+        assert!(unmatched.is_err(), "each interaction is served once");
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn parallel_recording_keeps_every_interaction() {
+        let path = cassette_path("parallel");
+        let cassette = Cassette::for_recording(&path);
+        let response = RawResponse {
+            success: true,
+            body: "{}".to_string(),
+            stderr: String::new(),
+            status: Some(200),
+            headers: std::collections::BTreeMap::new(),
+            timing: None,
+            correlation_id: None,
+        };
+        std::thread::scope(|scope| {
+            for worker in 0..8 {
+                let (cassette, response) = (&cassette, &response);
+                scope.spawn(move || {
+                    for call_number in 0..10 {
+                        let request = call(call_number, &format!("worker {worker}"));
+                        cassette
+                            .record_interaction("http://mcp", &request, response)
+                            .unwrap();
+                    }
+                });
+            }
+        });
+
+        let replay = Cassette::for_replay(&path).unwrap();
+        assert_eq!(replay.lock_state().interactions.len(), 80);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn rejects_unknown_versions() {
+        let path = cassette_path("version");
+        std::fs::write(
+            &path,
+            json!({ "version": 99, "recorded_at": "", "interactions": [] }).to_string(),
+        )
+        .unwrap();
+
+        let error = Cassette::for_replay(&path).unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .contains("Unsupported cassette version 99")
+        );
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,194 @@
+//! Fault injection for the MCP transport
+//!
+//! [`Chaos`] corrupts a configurable fraction of `tools/call` responses with
+//! simulated timeouts, truncated bodies, 5xx errors, or malformed JSON, so
+//! retry/backoff and error classification can be exercised without a
+//! misbehaving server. Discovery (`tools/list`) is never faulted because it
+//! is not retried and a failure there would abort the whole run.
+
+use super::transport::RawResponse;
+use crate::{GleanMcpError, Result};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+/// Kind of fault injected into a response
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ChaosFault {
+    /// The request never completes (curl exit 28)
+    Timeout,
+    /// The body is cut off halfway through
+    Truncated,
+    /// The server answers with HTTP 503
+    ServerError,
+    /// The body is syntactically invalid JSON
+    MalformedJson,
+}
+
+impl ChaosFault {
+    pub const ALL: [Self; 4] = [
+        Self::Timeout,
+        Self::Truncated,
+        Self::ServerError,
+        Self::MalformedJson,
+    ];
+
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Timeout => "timeout",
+            Self::Truncated => "truncated",
+            Self::ServerError => "server-error",
+            Self::MalformedJson => "malformed-json",
+        }
+    }
+
+    /// Corrupt `response` with this fault
+    #[must_use]
+    pub fn apply(self, response: RawResponse) -> RawResponse {
+        match self {
+            Self::Timeout => RawResponse {
+                success: false,
+                body: String::new(),
+                stderr: "curl: (28) Operation timed out (injected by chaos mode)".to_string(),
+                status: None,
+                headers: BTreeMap::new(),
+                timing: None,
+                correlation_id: response.correlation_id,
+            },
+            Self::Truncated => {
+                let mut cut = response.body.len() / 2;
+                while !response.body.is_char_boundary(cut) {
+                    cut -= 1;
+                }
+                RawResponse {
+                    body: response.body[..cut].to_string(),
+                    ..response
+                }
+            }
+            Self::ServerError => RawResponse {
+                success: true,
+                body: r#"{"error":{"code":503,"message":"Service Unavailable (injected by chaos mode)"}}"#
+                    .to_string(),
+                stderr: String::new(),
+                status: Some(503),
+                headers: BTreeMap::new(),
+                timing: response.timing,
+                correlation_id: response.correlation_id,
+            },
+            Self::MalformedJson => RawResponse {
+                body: if response.body.contains(':') {
+                    response.body.replacen(':', "=", 1)
+                } else {
+                    "{=}".to_string()
+                },
+                ..response
+            },
+        }
+    }
+}
+
+/// Parse a fault name, e.g. `server-error`
+pub fn parse_chaos_fault(raw: &str) -> Result<ChaosFault> {
+    let name = raw.trim();
+    ChaosFault::ALL
+        .into_iter()
+        .find(|fault| fault.as_str() == name)
+        .ok_or_else(|| {
+            GleanMcpError::Config(format!(
+                "Unknown chaos fault '{name}' (expected timeout, truncated, server-error, malformed-json)"
+            ))
+        })
+}
+
+/// Parse a fault rate between 0.0 and 1.0
+pub fn parse_chaos_rate(raw: &str) -> Result<f64> {
+    raw.parse::<f64>()
+        .ok()
+        .filter(|rate| (0.0..=1.0).contains(rate))
+        .ok_or_else(|| {
+            GleanMcpError::Config(format!(
+                "Invalid chaos rate '{raw}' (expected a number between 0.0 and 1.0)"
+            ))
+        })
+}
+
+/// Fault injection settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChaosConfig {
+    /// Fraction of `tools/call` requests to fault (0.0-1.0)
+    pub rate: f64,
+    /// Faults to choose from; all kinds when empty
+    pub faults: Vec<ChaosFault>,
+    /// Seed for reproducible fault sequences
+    pub seed: Option<u64>,
+}
+
+/// Shared fault injector; clones share the random source and counters
+#[derive(Debug, Clone)]
+pub struct Chaos {
+    config: ChaosConfig,
+    rng: Arc<Mutex<StdRng>>,
+    injected: Arc<Mutex<BTreeMap<ChaosFault, usize>>>,
+}
+
+impl Chaos {
+    #[must_use]
+    pub fn new(config: ChaosConfig) -> Self {
+        let rng = config
+            .seed
+            .map_or_else(StdRng::from_entropy, StdRng::seed_from_u64);
+        Self {
+            config,
+            rng: Arc::new(Mutex::new(rng)),
+            injected: Arc::new(Mutex::new(BTreeMap::new())),
+        }
+    }
+
+    #[must_use]
+    pub const fn config(&self) -> &ChaosConfig {
+        &self.config
+    }
+
+    /// Possibly replace `response` to `request` with a faulty one
+    #[must_use]
+    pub fn maybe_inject(&self, request: &Value, response: RawResponse) -> RawResponse {
+        if request.get("method").and_then(Value::as_str) != Some("tools/call") {
+            return response;
+        }
+
+        let fault = {
+            let Ok(mut rng) = self.rng.lock() else {
+                return response;
+            };
+            if !rng.gen_bool(self.config.rate.clamp(0.0, 1.0)) {
+                return response;
+            }
+            let faults = if self.config.faults.is_empty() {
+                &ChaosFault::ALL[..]
+            } else {
+                &self.config.faults[..]
+            };
+            *faults.choose(&mut *rng).unwrap_or(&ChaosFault::Timeout)
+        };
+
+        if let Ok(mut injected) = self.injected.lock() {
+            *injected.entry(fault).or_default() += 1;
+        }
+        fault.apply(response)
+    }
+
+    /// Number of faults injected so far, by kind
+    #[must_use]
+    pub fn injected(&self) -> BTreeMap<ChaosFault, usize> {
+        self.injected
+            .lock()
+            .map(|injected| injected.clone())
+            .unwrap_or_default()
+    }
+}
@@ -0,0 +1,229 @@
+//! Validation of the `ChatGPT` connector endpoint
+//!
+//! Glean serves a second MCP endpoint, `/mcp/chatgpt`, for `ChatGPT`
+//! connectors. It exposes a restricted tool set (`search` and `fetch` by
+//! default) rather than everything on `/mcp/default`.
+//! [`GleanMCPInspector::validate_chatgpt_endpoint`] lists the tools on both
+//! endpoints, checks the `ChatGPT` set against the expected one, and reports
+//! how the two endpoints differ.
+
+use super::{GleanMCPInspector, InspectorResult, ToolInfo};
+use crate::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+
+/// Tools the `ChatGPT` endpoint is expected to expose when none are configured
+pub const DEFAULT_CHATGPT_TOOLS: [&str; 2] = ["search", "fetch"];
+
+/// Result of validating the `ChatGPT` endpoint against the default endpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChatGptValidation {
+    /// The `ChatGPT` endpoint exposes exactly the expected tools
+    pub success: bool,
+    pub default_url: String,
+    pub chatgpt_url: String,
+    pub expected_tools: Vec<String>,
+    pub chatgpt_tools: Vec<String>,
+    /// Tools on the default endpoint; empty if it could not be listed
+    pub default_tools: Vec<String>,
+    /// Expected tools the `ChatGPT` endpoint does not expose
+    pub missing_tools: Vec<String>,
+    /// Tools on the `ChatGPT` endpoint outside the expected set
+    pub unexpected_tools: Vec<String>,
+    /// Tools only on the default endpoint
+    pub default_only: Vec<String>,
+    /// Tools only on the `ChatGPT` endpoint
+    pub chatgpt_only: Vec<String>,
+    /// Tools on both endpoints whose input schemas differ
+    pub schema_differences: Vec<String>,
+    pub error: Option<String>,
+}
+
+impl ChatGptValidation {
+    /// Compare the tool lists of both endpoints against `expected_tools`
+    #[must_use]
+    pub fn compare(
+        default_url: &str,
+        chatgpt_url: &str,
+        expected_tools: &[String],
+        default_tools: &[ToolInfo],
+        chatgpt_tools: &[ToolInfo],
+    ) -> Self {
+        let schemas = |tools: &[ToolInfo]| -> BTreeMap<String, Option<serde_json::Value>> {
+            tools
+                .iter()
+                .map(|tool| (tool.name.clone(), tool.schema.clone()))
+                .collect()
+        };
+        let default = schemas(default_tools);
+        let chatgpt = schemas(chatgpt_tools);
+        let expected: BTreeSet<String> = expected_tools.iter().cloned().collect();
+
+        let missing_tools: Vec<String> = expected
+            .iter()
+            .filter(|tool| !chatgpt.contains_key(*tool))
+            .cloned()
+            .collect();
+        let unexpected_tools: Vec<String> = chatgpt
+            .keys()
+            .filter(|tool| !expected.contains(*tool))
+            .cloned()
+            .collect();
+        let default_only = default
+            .keys()
+            .filter(|tool| !chatgpt.contains_key(*tool))
+            .cloned()
+            .collect();
+        let chatgpt_only = chatgpt
+            .keys()
+            .filter(|tool| !default.is_empty() && !default.contains_key(*tool))
+            .cloned()
+            .collect();
+        let schema_differences = chatgpt
+            .iter()
+            .filter(|(tool, schema)| {
+                default
+                    .get(*tool)
+                    .is_some_and(|default_schema| default_schema != *schema)
+            })
+            .map(|(tool, _)| tool.clone())
+            .collect();
+
+        let error = if chatgpt.is_empty() {
+            Some("The ChatGPT endpoint listed no tools".to_string())
+        } else if !missing_tools.is_empty() || !unexpected_tools.is_empty() {
+            Some(format!(
+                "The ChatGPT endpoint does not expose the expected tool set ({} missing, {} unexpected)",
+                missing_tools.len(),
+                unexpected_tools.len()
+            ))
+        } else {
+            None
+        };
+
+        Self {
+            success: error.is_none(),
+            default_url: default_url.to_string(),
+            chatgpt_url: chatgpt_url.to_string(),
+            expected_tools: expected.into_iter().collect(),
+            chatgpt_tools: chatgpt.into_keys().collect(),
+            default_tools: default.into_keys().collect(),
+            missing_tools,
+            unexpected_tools,
+            default_only,
+            chatgpt_only,
+            schema_differences,
+            error,
+        }
+    }
+
+    /// A failed validation for an endpoint that could not be listed
+    #[must_use]
+    pub fn new_error(default_url: &str, chatgpt_url: &str, error: &str) -> Self {
+        Self {
+            success: false,
+            default_url: default_url.to_string(),
+            chatgpt_url: chatgpt_url.to_string(),
+            expected_tools: Vec::new(),
+            chatgpt_tools: Vec::new(),
+            default_tools: Vec::new(),
+            missing_tools: Vec::new(),
+            unexpected_tools: Vec::new(),
+            default_only: Vec::new(),
+            chatgpt_only: Vec::new(),
+            schema_differences: Vec::new(),
+            error: Some(error.to_string()),
+        }
+    }
+
+    #[must_use]
+    pub fn format_text(&self) -> String {
+        let mut output = String::new();
+        let list = |tools: &[String]| {
+            if tools.is_empty() {
+                "none".to_string()
+            } else {
+                tools.join(", ")
+            }
+        };
+
+        let _ = writeln!(output, "🤖 ChatGPT endpoint: {}", self.chatgpt_url);
+        let _ = writeln!(output, "  Expected: {}", list(&self.expected_tools));
+        let _ = writeln!(output, "  Exposed:  {}", list(&self.chatgpt_tools));
+        for tool in &self.missing_tools {
+            let _ = writeln!(output, "  ❌ Missing: {tool}");
+        }
+        for tool in &self.unexpected_tools {
+            let _ = writeln!(output, "  ❌ Unexpected: {tool}");
+        }
+
+        let _ = writeln!(
+            output,
+            "🔀 Compared with default endpoint: {}",
+            self.default_url
+        );
+        if self.default_tools.is_empty() {
+            let _ = writeln!(output, "  Default endpoint tools unavailable");
+        } else {
+            let _ = writeln!(output, "  Default only: {}", list(&self.default_only));
+            let _ = writeln!(output, "  ChatGPT only: {}", list(&self.chatgpt_only));
+            let _ = writeln!(
+                output,
+                "  Different input schema: {}",
+                list(&self.schema_differences)
+            );
+        }
+
+        if let Some(error) = &self.error {
+            let _ = writeln!(output, "Error: {error}");
+        }
+        output
+    }
+}
+
+impl GleanMCPInspector {
+    /// List tools on both endpoints and check the `ChatGPT` endpoint exposes exactly `expected_tools`
+    ///
+    /// A default endpoint that can't be listed only drops the comparison; the
+    /// `ChatGPT` endpoint must be reachable.
+    pub async fn validate_chatgpt_endpoint(
+        &self,
+        expected_tools: &[String],
+    ) -> Result<ChatGptValidation> {
+        let chatgpt = self
+            .list_available_tools_from_endpoint(self.chatgpt_url(), false)
+            .await?;
+        if !chatgpt.success {
+            return Ok(ChatGptValidation::new_error(
+                self.server_url(),
+                self.chatgpt_url(),
+                chatgpt
+                    .error
+                    .as_deref()
+                    .unwrap_or("Failed to list tools from the ChatGPT endpoint"),
+            ));
+        }
+
+        let tools = |result: &InspectorResult| {
+            result
+                .inspector_data
+                .as_ref()
+                .map(ToolInfo::from_tools_list)
+                .unwrap_or_default()
+        };
+        let default_tools = self
+            .list_available_tools_from_endpoint(self.server_url(), false)
+            .await
+            .map(|default| tools(&default))
+            .unwrap_or_default();
+
+        Ok(ChatGptValidation::compare(
+            self.server_url(),
+            self.chatgpt_url(),
+            expected_tools,
+            &default_tools,
+            &tools(&chatgpt),
+        ))
+    }
+}
@@ -0,0 +1,99 @@
+//! Clock skew between this machine and the MCP server
+//!
+//! Tokens carry issue and expiry times, so a local clock that is minutes off
+//! makes valid credentials look expired or not yet valid. [`measure_clock_skew`]
+//! compares the local time against the `Date` header of a server response,
+//! taken at the midpoint of the round trip.
+
+use super::transport::McpTransport;
+use crate::{GleanMcpError, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::time::Instant;
+
+/// Measured offset of the local clock from the server's
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClockSkew {
+    /// Local time minus server time; positive when the local clock is ahead
+    pub skew_ms: i64,
+    pub round_trip_ms: u64,
+}
+
+impl ClockSkew {
+    /// Compare `server_date` against the local time halfway between `sent_at` and `received_at`
+    #[must_use]
+    pub fn between(
+        server_date: DateTime<Utc>,
+        sent_at: DateTime<Utc>,
+        received_at: DateTime<Utc>,
+    ) -> Self {
+        let round_trip = received_at - sent_at;
+        let local = sent_at + round_trip / 2;
+        Self {
+            skew_ms: (local - server_date).num_milliseconds(),
+            round_trip_ms: u64::try_from(round_trip.num_milliseconds()).unwrap_or_default(),
+        }
+    }
+
+    /// Whether the skew exceeds `threshold_secs`, allowing for the `Date` header's one-second resolution
+    #[must_use]
+    pub const fn exceeds(self, threshold_secs: u64) -> bool {
+        self.skew_ms.unsigned_abs() > threshold_secs * 1000 + 1000
+    }
+
+    /// Human-readable description, e.g. `local clock is 4m 12s ahead of the server`
+    #[must_use]
+    pub fn describe(self) -> String {
+        let seconds = self.skew_ms.unsigned_abs() / 1000;
+        if seconds <= 1 {
+            return "local clock is in sync with the server".to_string();
+        }
+        let amount = if seconds >= 60 {
+            format!("{}m {}s", seconds / 60, seconds % 60)
+        } else {
+            format!("{seconds}s")
+        };
+        let direction = if self.skew_ms > 0 {
+            "ahead of"
+        } else {
+            "behind"
+        };
+        format!("local clock is {amount} {direction} the server")
+    }
+}
+
+/// Measure skew against `endpoint` using a JSON-RPC `ping`
+///
+/// Any response carrying a `Date` header will do, including an auth error.
+pub async fn measure_clock_skew(transport: &McpTransport, endpoint: &str) -> Result<ClockSkew> {
+    let request = serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "ping"
+    });
+
+    let sent_at = Utc::now();
+    let started = Instant::now();
+    let response = transport.post_json_rpc(endpoint, &request).await?;
+    let received_at = sent_at
+        + chrono::Duration::from_std(started.elapsed())
+            .unwrap_or_else(|_| chrono::Duration::zero());
+
+    if !response.success {
+        return Err(GleanMcpError::Network(format!(
+            "Could not reach {endpoint} to check clock skew: {}",
+            response.stderr
+        )));
+    }
+    let date = response.header("date").ok_or_else(|| {
+        GleanMcpError::Validation("Server response has no Date header".to_string())
+    })?;
+    let server_date = DateTime::parse_from_rfc2822(date)
+        .map_err(|e| GleanMcpError::Validation(format!("Invalid Date header '{date}': {e}")))?;
+
+    Ok(ClockSkew::between(
+        server_date.with_timezone(&Utc),
+        sent_at,
+        received_at,
+    ))
+}
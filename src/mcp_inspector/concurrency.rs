@@ -0,0 +1,204 @@
+//! Adaptive concurrency for parallel runs
+//!
+//! With `--max-concurrent auto`, [`AdaptiveConcurrency`] decides how many
+//! tools run at once instead of a fixed pool. It starts at [`AUTO_START`]
+//! and allows one more call after every few consecutive clean responses, up
+//! to [`AUTO_CEILING`]; each 429 or 5xx halves the limit. The transport
+//! reports every response's status to it, as it does to the
+//! [`RateLimiter`](super::RateLimiter), and the limits reached end up in the
+//! run's execution summary.
+
+use crate::{GleanMcpError, Result};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use smol::lock::{Semaphore, SemaphoreGuardArc};
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// Concurrent calls an adaptive run starts with
+pub const AUTO_START: usize = 2;
+
+/// Most concurrent calls an adaptive run ramps up to
+pub const AUTO_CEILING: usize = 16;
+
+/// Consecutive clean responses before the limit grows by one
+const RAMP_AFTER: usize = 3;
+
+/// `--max-concurrent`: a fixed number of concurrent tools, or `auto`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MaxConcurrent {
+    Fixed(usize),
+    Auto,
+}
+
+impl MaxConcurrent {
+    /// The fixed limit, or the ceiling an adaptive run may reach
+    #[must_use]
+    pub const fn limit(self) -> usize {
+        match self {
+            Self::Fixed(limit) => limit,
+            Self::Auto => AUTO_CEILING,
+        }
+    }
+}
+
+/// Parse `--max-concurrent`: a positive number or `auto`
+pub fn parse_max_concurrent(raw: &str) -> Result<MaxConcurrent> {
+    let raw = raw.trim();
+    if raw.eq_ignore_ascii_case("auto") {
+        return Ok(MaxConcurrent::Auto);
+    }
+    match raw.parse::<usize>() {
+        Ok(limit) if limit > 0 => Ok(MaxConcurrent::Fixed(limit)),
+        _ => Err(GleanMcpError::Config(format!(
+            "Invalid --max-concurrent '{raw}' (expected a positive number or auto)"
+        ))),
+    }
+}
+
+/// What adaptive concurrency did over a run, reported in the execution summary
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct ConcurrencyStats {
+    /// Limit the run started with
+    pub initial: usize,
+    /// Highest limit allowed
+    pub ceiling: usize,
+    /// Highest limit reached
+    pub peak: usize,
+    /// Lowest limit backed off to
+    pub lowest: usize,
+    /// Limit in effect at the end of the run
+    pub final_limit: usize,
+    /// Responses with HTTP 429 or 5xx
+    pub throttled_responses: usize,
+}
+
+#[derive(Debug)]
+struct LimitState {
+    limit: usize,
+    /// Permits taken out of the semaphore to keep the limit below the ceiling
+    parked: usize,
+    /// Permits to hold back as soon as calls in flight release them
+    owed: usize,
+    clean_streak: usize,
+    stats: ConcurrencyStats,
+}
+
+/// Shared concurrency limit; clones adjust the same limit
+#[derive(Debug, Clone)]
+pub struct AdaptiveConcurrency {
+    semaphore: Arc<Semaphore>,
+    state: Arc<Mutex<LimitState>>,
+}
+
+/// A slot for one call; released on drop, unless the limit shrank meanwhile
+#[derive(Debug)]
+pub struct ConcurrencyPermit {
+    guard: Option<SemaphoreGuardArc>,
+    state: Arc<Mutex<LimitState>>,
+}
+
+impl Drop for ConcurrencyPermit {
+    fn drop(&mut self) {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        if state.owed > 0
+            && let Some(guard) = self.guard.take()
+        {
+            state.owed -= 1;
+            state.parked += 1;
+            drop(state);
+            // Forgetting the guard keeps its permit out of the semaphore
+            std::mem::forget(guard);
+        }
+    }
+}
+
+/// Take a permit out of `semaphore` until the limit grows again
+fn park(semaphore: &Arc<Semaphore>) -> bool {
+    semaphore.try_acquire_arc().map(std::mem::forget).is_some()
+}
+
+impl Default for AdaptiveConcurrency {
+    fn default() -> Self {
+        Self::new(AUTO_START, AUTO_CEILING)
+    }
+}
+
+impl AdaptiveConcurrency {
+    /// A limit starting at `initial` that may grow to `ceiling`
+    #[must_use]
+    pub fn new(initial: usize, ceiling: usize) -> Self {
+        let ceiling = ceiling.max(1);
+        let initial = initial.clamp(1, ceiling);
+        let semaphore = Arc::new(Semaphore::new(ceiling));
+        let parked = (initial..ceiling).filter(|_| park(&semaphore)).count();
+        Self {
+            semaphore,
+            state: Arc::new(Mutex::new(LimitState {
+                limit: initial,
+                parked,
+                owed: 0,
+                clean_streak: 0,
+                stats: ConcurrencyStats {
+                    initial,
+                    ceiling,
+                    peak: initial,
+                    lowest: initial,
+                    final_limit: initial,
+                    throttled_responses: 0,
+                },
+            })),
+        }
+    }
+
+    /// Wait for a free slot under the current limit
+    pub async fn acquire(&self) -> ConcurrencyPermit {
+        ConcurrencyPermit {
+            guard: Some(self.semaphore.acquire_arc().await),
+            state: self.state.clone(),
+        }
+    }
+
+    /// Adjust the limit to a response's HTTP status
+    pub fn record_status(&self, status: Option<u16>) {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        if status.is_some_and(|status| status == 429 || status >= 500) {
+            let limit = (state.limit / 2).max(1);
+            for _ in limit..state.limit {
+                if park(&self.semaphore) {
+                    state.parked += 1;
+                } else {
+                    state.owed += 1;
+                }
+            }
+            state.limit = limit;
+            state.clean_streak = 0;
+            state.stats.throttled_responses += 1;
+            state.stats.lowest = state.stats.lowest.min(limit);
+        } else if status.is_none_or(|status| status < 400) {
+            state.clean_streak += 1;
+            if state.clean_streak >= RAMP_AFTER && state.limit < state.stats.ceiling {
+                if state.owed > 0 {
+                    state.owed -= 1;
+                } else if state.parked > 0 {
+                    // Returning a parked permit frees one more slot
+                    state.parked -= 1;
+                    self.semaphore.add_permits(1);
+                }
+                state.limit += 1;
+                state.clean_streak = 0;
+                state.stats.peak = state.stats.peak.max(state.limit);
+            }
+        }
+        state.stats.final_limit = state.limit;
+    }
+
+    /// Limits reached so far
+    #[must_use]
+    pub fn stats(&self) -> ConcurrencyStats {
+        self.state
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .stats
+            .clone()
+    }
+}
@@ -0,0 +1,181 @@
+//! JSON-RPC 2.0 and MCP conformance of server responses
+//!
+//! [`ConformanceChecker`] inspects every response that passes through the
+//! transport during a run. [`check_response`] applies the JSON-RPC rules: a
+//! `"jsonrpc": "2.0"` member, an `id` matching the request, and exactly one
+//! of `result` or `error`. It also applies MCP's structure rules for
+//! `tools/list` and `tools/call` results. HTTP error responses and
+//! notifications are skipped, since they carry no JSON-RPC reply. The run's
+//! share of conforming responses is reported as its conformance score.
+
+use super::RawResponse;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+/// Conformance of the responses seen during a run
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ConformanceReport {
+    /// Responses checked
+    pub checked: usize,
+    /// Responses without any violation
+    pub conforming: usize,
+    /// Share of conforming responses, as a percentage
+    pub score: f64,
+    /// Number of responses with each violation
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub violations: BTreeMap<String, usize>,
+    /// Requests and notifications sent, by JSON-RPC method
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub methods: BTreeMap<String, usize>,
+}
+
+/// Collects conformance results; clones share the same tally
+#[derive(Debug, Clone, Default)]
+pub struct ConformanceChecker {
+    report: Arc<Mutex<ConformanceReport>>,
+}
+
+impl ConformanceChecker {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Tally `request`'s method, then check `response` as its reply and tally the outcome
+    pub fn record(&self, request: &Value, response: &RawResponse) {
+        let Ok(mut report) = self.report.lock() else {
+            return;
+        };
+        if let Some(method) = request.get("method").and_then(Value::as_str) {
+            *report.methods.entry(method.to_string()).or_default() += 1;
+        }
+        let expects_reply = request.get("id").is_some();
+        let is_http_error = response.status.is_some_and(|status| status >= 400);
+        if !response.success || !expects_reply || is_http_error {
+            return;
+        }
+
+        let violations = check_response(request, &response.body);
+        report.checked += 1;
+        if violations.is_empty() {
+            report.conforming += 1;
+        }
+        for violation in violations {
+            *report.violations.entry(violation).or_default() += 1;
+        }
+        #[allow(clippy::cast_precision_loss)]
+        let score = report.conforming as f64 * 100.0 / report.checked as f64;
+        report.score = score;
+    }
+
+    /// Tally so far; `None` when no response has been checked
+    #[must_use]
+    pub fn report(&self) -> Option<ConformanceReport> {
+        self.report
+            .lock()
+            .ok()
+            .map(|report| report.clone())
+            .filter(|report| report.checked > 0)
+    }
+}
+
+/// Violations of JSON-RPC 2.0 and MCP structure in `body`, the reply to `request`
+#[must_use]
+pub fn check_response(request: &Value, body: &str) -> Vec<String> {
+    let Ok(response) = serde_json::from_str::<Value>(body) else {
+        return vec!["Response is not valid JSON".to_string()];
+    };
+    let Some(response) = response.as_object() else {
+        return vec!["Response is not a JSON-RPC object".to_string()];
+    };
+
+    let mut violations = Vec::new();
+    if response.get("jsonrpc").and_then(Value::as_str) != Some("2.0") {
+        violations.push("`jsonrpc` is not \"2.0\"".to_string());
+    }
+    if response.get("id") != request.get("id") {
+        violations.push("`id` does not match the request".to_string());
+    }
+
+    match (response.get("result"), response.get("error")) {
+        (Some(_), Some(_)) => violations.push("Both `result` and `error` are present".to_string()),
+        (None, None) => violations.push("Neither `result` nor `error` is present".to_string()),
+        (None, Some(error)) => {
+            if error.get("code").and_then(Value::as_i64).is_none() {
+                violations.push("`error.code` is not an integer".to_string());
+            }
+            if error.get("message").and_then(Value::as_str).is_none() {
+                violations.push("`error.message` is not a string".to_string());
+            }
+        }
+        (Some(result), None) => match request.get("method").and_then(Value::as_str) {
+            Some("tools/list") => check_tools_list(result, &mut violations),
+            Some("tools/call") => check_tool_call(result, &mut violations),
+            _ => {}
+        },
+    }
+    violations
+}
+
+fn check_tools_list(result: &Value, violations: &mut Vec<String>) {
+    let Some(tools) = result.get("tools").and_then(Value::as_array) else {
+        violations.push("`tools/list` result has no `tools` array".to_string());
+        return;
+    };
+    if tools
+        .iter()
+        .any(|tool| tool.get("name").and_then(Value::as_str).is_none())
+    {
+        violations.push("A listed tool has no string `name`".to_string());
+    }
+    if tools
+        .iter()
+        .any(|tool| !tool.get("inputSchema").is_some_and(Value::is_object))
+    {
+        violations.push("A listed tool has no `inputSchema` object".to_string());
+    }
+}
+
+fn check_tool_call(result: &Value, violations: &mut Vec<String>) {
+    let Some(content) = result.get("content").and_then(Value::as_array) else {
+        violations.push("`tools/call` result has no `content` array".to_string());
+        return;
+    };
+    if result
+        .get("isError")
+        .is_some_and(|is_error| !is_error.is_boolean())
+    {
+        violations.push("`isError` is not a boolean".to_string());
+    }
+
+    for item in content {
+        let Some(kind) = item.get("type").and_then(Value::as_str) else {
+            violations.push("A content item has no string `type`".to_string());
+            continue;
+        };
+        let required: &[&str] = match kind {
+            "text" => &["text"],
+            "image" | "audio" => &["data", "mimeType"],
+            "resource_link" => &["uri", "name"],
+            "resource" => {
+                if !item.get("resource").is_some_and(Value::is_object) {
+                    violations
+                        .push("A `resource` content item has no `resource` object".to_string());
+                }
+                &[]
+            }
+            other => {
+                violations.push(format!("Unknown content type `{other}`"));
+                &[]
+            }
+        };
+        for field in required {
+            if item.get(*field).and_then(Value::as_str).is_none() {
+                violations.push(format!("A `{kind}` content item has no string `{field}`"));
+            }
+        }
+    }
+}
@@ -0,0 +1,193 @@
+//! Query corpora for data-driven tool tests
+//!
+//! A corpus lists representative queries per tool, inline in the config or
+//! in a YAML (`tool: [queries]`) or CSV (`tool,query` rows) file. In
+//! round-robin mode each run calls a tool once, with the next query in its
+//! list; in exhaustive mode it calls the tool once per query, naming the
+//! results `search#1`, `search#2`, and so on.
+
+use crate::{GleanMcpError, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// How a run picks queries from a tool's list
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CorpusMode {
+    /// One query per run, rotating through the list from run to run
+    #[default]
+    RoundRobin,
+    /// Every query, every run
+    Exhaustive,
+}
+
+impl CorpusMode {
+    pub const ALL: [Self; 2] = [Self::RoundRobin, Self::Exhaustive];
+
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::RoundRobin => "round-robin",
+            Self::Exhaustive => "exhaustive",
+        }
+    }
+}
+
+/// Parse a corpus mode name, as given to `--corpus-mode`
+pub fn parse_corpus_mode(raw: &str) -> Result<CorpusMode> {
+    let name = raw.trim();
+    CorpusMode::ALL
+        .into_iter()
+        .find(|mode| mode.as_str() == name)
+        .ok_or_else(|| {
+            GleanMcpError::Config(format!(
+                "Unknown corpus mode '{name}' (expected round-robin, exhaustive)"
+            ))
+        })
+}
+
+/// Settings for the query corpus
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CorpusConfig {
+    /// YAML or CSV file of queries per tool, added to `queries`
+    pub file: Option<String>,
+    pub mode: CorpusMode,
+    /// Queries per tool name
+    pub queries: BTreeMap<String, Vec<String>>,
+}
+
+impl CorpusConfig {
+    /// Inline queries merged with those in `file`
+    pub fn load(&self) -> Result<QueryCorpus> {
+        let mut queries = self.queries.clone();
+        if let Some(file) = &self.file {
+            for (tool, file_queries) in load_corpus_file(Path::new(file))? {
+                queries.entry(tool).or_default().extend(file_queries);
+            }
+        }
+        queries.retain(|_, queries| !queries.is_empty());
+        Ok(QueryCorpus {
+            queries,
+            mode: self.mode,
+            offset: 0,
+        })
+    }
+}
+
+/// Queries per tool, read from a `.csv` file or otherwise YAML
+pub fn load_corpus_file(path: &Path) -> Result<BTreeMap<String, Vec<String>>> {
+    let content = std::fs::read_to_string(path).map_err(|e| {
+        GleanMcpError::Config(format!(
+            "Failed to read query corpus {}: {e}",
+            path.display()
+        ))
+    })?;
+    let is_csv = path
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("csv"));
+    let parsed = if is_csv {
+        parse_csv_corpus(&content)
+    } else {
+        serde_yaml::from_str(&content).map_err(|e| e.to_string())
+    };
+    parsed
+        .map_err(|e| GleanMcpError::Config(format!("Invalid query corpus {}: {e}", path.display())))
+}
+
+/// `tool,query` rows, with an optional `tool,query` header; quote fields holding commas
+fn parse_csv_corpus(content: &str) -> std::result::Result<BTreeMap<String, Vec<String>>, String> {
+    let mut queries: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for (index, line) in content.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let fields = csv_fields(line).map_err(|e| format!("line {}: {e}", index + 1))?;
+        let [tool, query] = fields.as_slice() else {
+            return Err(format!(
+                "line {}: expected 2 fields (tool,query), found {}",
+                index + 1,
+                fields.len()
+            ));
+        };
+        if index == 0 && tool.eq_ignore_ascii_case("tool") && query.eq_ignore_ascii_case("query") {
+            continue;
+        }
+        if tool.trim().is_empty() || query.trim().is_empty() {
+            return Err(format!("line {}: empty tool or query", index + 1));
+        }
+        queries
+            .entry(tool.trim().to_string())
+            .or_default()
+            .push(query.clone());
+    }
+    Ok(queries)
+}
+
+/// Fields of one CSV line; `""` inside a quoted field is a literal quote
+fn csv_fields(line: &str) -> std::result::Result<Vec<String>, String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' if quoted => quoted = false,
+            '"' if field.trim().is_empty() => {
+                field.clear();
+                quoted = true;
+            }
+            ',' if !quoted => fields.push(std::mem::take(&mut field)),
+            c => field.push(c),
+        }
+    }
+    if quoted {
+        return Err("unclosed quote".to_string());
+    }
+    fields.push(field);
+    Ok(fields)
+}
+
+/// Loaded queries per tool, and how a run picks from them
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct QueryCorpus {
+    pub queries: BTreeMap<String, Vec<String>>,
+    pub mode: CorpusMode,
+    /// Position in each tool's list for round-robin runs, usually the number of earlier runs
+    pub offset: usize,
+}
+
+impl QueryCorpus {
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.queries.is_empty()
+    }
+
+    /// Result names and queries `tool_name` is called with in a run
+    ///
+    /// A tool without corpus queries is called once, under its own name and
+    /// with its generated query (`None`).
+    #[must_use]
+    pub fn cases(&self, tool_name: &str) -> Vec<(String, Option<String>)> {
+        let queries = self.queries.get(tool_name).map_or(&[][..], Vec::as_slice);
+        match (self.mode, queries) {
+            (_, []) => vec![(tool_name.to_string(), None)],
+            (_, [query]) => vec![(tool_name.to_string(), Some(query.clone()))],
+            (CorpusMode::RoundRobin, queries) => vec![(
+                tool_name.to_string(),
+                Some(queries[self.offset % queries.len()].clone()),
+            )],
+            (CorpusMode::Exhaustive, queries) => queries
+                .iter()
+                .enumerate()
+                .map(|(index, query)| (format!("{tool_name}#{}", index + 1), Some(query.clone())))
+                .collect(),
+        }
+    }
+}
@@ -0,0 +1,265 @@
+//! Duplicate result detection for search responses
+//!
+//! Collects the documents in a search response, whether they arrive as
+//! structured JSON or as JSON embedded in text blocks, and flags repeated
+//! URLs, repeated titles, and snippets whose word shingles overlap by at
+//! least the configured similarity.
+
+use super::language::response_text;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::Write;
+
+/// Words per shingle when comparing snippets
+const SHINGLE_SIZE: usize = 3;
+
+/// Findings listed in a failure message before the rest are summarized
+const MAX_REPORTED: usize = 3;
+
+/// Settings for the duplicate result check
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DuplicateCheckConfig {
+    pub enabled: bool,
+    /// Tools whose responses are checked
+    pub tools: Vec<String>,
+    /// Snippet similarity (0.0-1.0, Jaccard over word shingles) treated as a near-duplicate
+    pub similarity_threshold: f64,
+    /// Also flag results sharing a title
+    pub check_titles: bool,
+}
+
+impl Default for DuplicateCheckConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            tools: vec!["search".to_string()],
+            similarity_threshold: 0.9,
+            check_titles: true,
+        }
+    }
+}
+
+/// One document in a search response
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SearchResultItem {
+    pub title: Option<String>,
+    pub url: Option<String>,
+    pub snippet: Option<String>,
+}
+
+impl DuplicateCheckConfig {
+    /// Check the results in `response` from `tool_name` for duplicates.
+    ///
+    /// Returns `Ok(Some(note))` when checked, `Ok(None)` when the tool isn't
+    /// covered or no results could be found, and `Err` listing the duplicates.
+    pub fn check(
+        &self,
+        tool_name: &str,
+        response: &Value,
+    ) -> std::result::Result<Option<String>, String> {
+        if !self.enabled || !self.tools.iter().any(|tool| tool == tool_name) {
+            return Ok(None);
+        }
+
+        let items = search_results(response);
+        if items.is_empty() {
+            return Ok(None);
+        }
+
+        let findings = self.find_duplicates(&items);
+        if findings.is_empty() {
+            return Ok(Some(format!(
+                "Checked {} results: no duplicates",
+                items.len()
+            )));
+        }
+
+        let mut message = format!(
+            "Duplicate results: {} finding(s) in {} results",
+            findings.len(),
+            items.len()
+        );
+        for finding in findings.iter().take(MAX_REPORTED) {
+            let _ = write!(message, "\n- {finding}");
+        }
+        if findings.len() > MAX_REPORTED {
+            let _ = write!(
+                message,
+                "\n- ... and {} more",
+                findings.len() - MAX_REPORTED
+            );
+        }
+        Err(message)
+    }
+
+    /// Describe every duplicate URL, title, and near-identical snippet pair
+    #[must_use]
+    pub fn find_duplicates(&self, items: &[SearchResultItem]) -> Vec<String> {
+        let mut findings = Vec::new();
+
+        let urls = items
+            .iter()
+            .map(|item| item.url.as_deref().map(normalize_url));
+        find_repeats("URL", urls, &mut findings);
+        if self.check_titles {
+            let titles = items
+                .iter()
+                .map(|item| item.title.as_deref().map(normalize_text));
+            find_repeats("title", titles, &mut findings);
+        }
+
+        let shingles: Vec<Option<BTreeSet<String>>> = items
+            .iter()
+            .map(|item| item.snippet.as_deref().map(shingles))
+            .collect();
+        for (i, left) in shingles.iter().enumerate() {
+            let Some(left) = left.as_ref().filter(|set| !set.is_empty()) else {
+                continue;
+            };
+            for (j, right) in shingles.iter().enumerate().skip(i + 1) {
+                let Some(right) = right.as_ref().filter(|set| !set.is_empty()) else {
+                    continue;
+                };
+                let similarity = jaccard(left, right);
+                if similarity >= self.similarity_threshold {
+                    findings.push(format!(
+                        "results #{} and #{} have near-identical snippets ({:.0}% similar)",
+                        i + 1,
+                        j + 1,
+                        similarity * 100.0
+                    ));
+                }
+            }
+        }
+
+        findings
+    }
+}
+
+/// Record a finding for every value equal to an earlier one
+fn find_repeats(
+    kind: &str,
+    values: impl Iterator<Item = Option<String>>,
+    findings: &mut Vec<String>,
+) {
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    for (index, value) in values.enumerate() {
+        let Some(value) = value.filter(|value| !value.is_empty()) else {
+            continue;
+        };
+        if let Some(first) = seen.get(&value) {
+            findings.push(format!(
+                "results #{} and #{} share {kind} '{value}'",
+                first + 1,
+                index + 1
+            ));
+        } else {
+            seen.insert(value, index);
+        }
+    }
+}
+
+/// Documents found anywhere in a search response
+#[must_use]
+pub fn search_results(response: &Value) -> Vec<SearchResultItem> {
+    let mut items = Vec::new();
+    collect_results(response, &mut items);
+
+    // Glean's MCP server returns results as JSON inside text content blocks
+    if items.is_empty()
+        && let Ok(embedded) = serde_json::from_str::<Value>(&response_text(response))
+    {
+        collect_results(&embedded, &mut items);
+    }
+
+    items
+}
+
+fn collect_results(value: &Value, items: &mut Vec<SearchResultItem>) {
+    match value {
+        Value::Array(values) => {
+            for value in values {
+                collect_results(value, items);
+            }
+        }
+        Value::Object(object) => {
+            let url = object.get("url").and_then(Value::as_str);
+            let title = object.get("title").and_then(Value::as_str);
+            if url.is_some() || (title.is_some() && object.contains_key("snippets")) {
+                items.push(SearchResultItem {
+                    title: title.map(str::to_string),
+                    url: url.map(str::to_string),
+                    snippet: snippet_text(value),
+                });
+                return;
+            }
+            for value in object.values() {
+                collect_results(value, items);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn snippet_text(document: &Value) -> Option<String> {
+    if let Some(snippet) = document.get("snippet").and_then(Value::as_str) {
+        return Some(snippet.to_string());
+    }
+    let snippets = document.get("snippets").and_then(Value::as_array)?;
+    let text: Vec<&str> = snippets
+        .iter()
+        .filter_map(|snippet| {
+            snippet
+                .as_str()
+                .or_else(|| snippet.get("text").and_then(Value::as_str))
+                .or_else(|| snippet.get("snippet").and_then(Value::as_str))
+        })
+        .collect();
+    (!text.is_empty()).then(|| text.join(" "))
+}
+
+/// Lowercased URL without scheme, fragment, or trailing slash
+fn normalize_url(url: &str) -> String {
+    let url = url.trim();
+    let url = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let url = url.split('#').next().unwrap_or(url);
+    url.trim_end_matches('/').to_lowercase()
+}
+
+fn normalize_text(text: &str) -> String {
+    text.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+fn shingles(text: &str) -> BTreeSet<String> {
+    let lowered = text.to_lowercase();
+    let words: Vec<&str> = lowered
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect();
+    if words.is_empty() {
+        return BTreeSet::new();
+    }
+    if words.len() < SHINGLE_SIZE {
+        return BTreeSet::from([words.join(" ")]);
+    }
+    words
+        .windows(SHINGLE_SIZE)
+        .map(|window| window.join(" "))
+        .collect()
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn jaccard(left: &BTreeSet<String>, right: &BTreeSet<String>) -> f64 {
+    let shared = left.intersection(right).count();
+    let total = left.union(right).count();
+    if total == 0 {
+        0.0
+    } else {
+        shared as f64 / total as f64
+    }
+}
@@ -0,0 +1,438 @@
+//! Cross-checking tool calls through the MCP Inspector CLI
+//!
+//! `test-tool --engine inspector` sends the call through
+//! `npx @modelcontextprotocol/inspector --cli` instead of the framework's own
+//! curl transport; `--engine both` makes the call both ways and reports where
+//! the two disagree: one failing while the other succeeds, a different
+//! `isError` flag, different content item types, or structured content on
+//! only one side. Text that differs between the two calls is only noted,
+//! since they are separate requests and results can change between them.
+//!
+//! The Inspector gets the auth token and custom headers as `--header`
+//! arguments, the resolved proxy as `HTTPS_PROXY`/`HTTP_PROXY`/`NO_PROXY`,
+//! and `tls.ca_cert` as `NODE_EXTRA_CA_CERTS` (trusted on top of Node's own
+//! CAs). Node has no way to present a client certificate, so runs with
+//! `tls.client_cert` fail the Inspector call instead of connecting without
+//! it. It makes its own requests, so cassettes and chaos faults don't apply.
+
+use super::McpTransport;
+use super::{GleanMCPInspector, RequestTrace, async_timeout, response_text};
+use crate::{GleanMcpError, Result, RunOutcome, async_command};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt::Write;
+use std::time::{Duration, Instant};
+
+/// Proxy variables Node and npx read, cleared for direct connections
+const PROXY_VARS: [&str; 6] = [
+    "HTTPS_PROXY",
+    "https_proxy",
+    "HTTP_PROXY",
+    "http_proxy",
+    "ALL_PROXY",
+    "all_proxy",
+];
+
+/// Which client a tool call goes through
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Engine {
+    /// The framework's own HTTP transport
+    #[default]
+    Direct,
+    /// `npx @modelcontextprotocol/inspector --cli`
+    Inspector,
+    /// Both, compared
+    Both,
+}
+
+impl Engine {
+    pub const ALL: [Self; 3] = [Self::Direct, Self::Inspector, Self::Both];
+
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Direct => "direct",
+            Self::Inspector => "inspector",
+            Self::Both => "both",
+        }
+    }
+}
+
+/// Parse an engine name, as given to `--engine`
+pub fn parse_engine(raw: &str) -> Result<Engine> {
+    let name = raw.trim();
+    Engine::ALL
+        .into_iter()
+        .find(|engine| engine.as_str() == name)
+        .ok_or_else(|| {
+            GleanMcpError::Config(format!(
+                "Unknown engine '{name}' (expected direct, inspector, both)"
+            ))
+        })
+}
+
+/// One tool call through one engine
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EngineRun {
+    pub engine: Engine,
+    pub success: bool,
+    /// The `tools/call` result, when the call succeeded
+    pub result: Option<Value>,
+    pub error: Option<String>,
+    /// HTTP status of a failed direct call, when the server answered with one
+    pub http_status: Option<u16>,
+    pub response_time_ms: u64,
+}
+
+impl EngineRun {
+    fn is_error(&self) -> bool {
+        self.result
+            .as_ref()
+            .and_then(|result| result.get("isError"))
+            .and_then(Value::as_bool)
+            == Some(true)
+    }
+
+    /// Types of the result's content items, e.g. `["text", "resource"]`
+    fn content_types(&self) -> Vec<&str> {
+        self.result
+            .as_ref()
+            .and_then(|result| result.get("content"))
+            .and_then(Value::as_array)
+            .map(|content| {
+                content
+                    .iter()
+                    .map(|item| item.get("type").and_then(Value::as_str).unwrap_or("?"))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    fn has_structured_content(&self) -> bool {
+        self.result
+            .as_ref()
+            .is_some_and(|result| result.get("structuredContent").is_some())
+    }
+}
+
+/// A tool call made through one or both engines
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EngineComparison {
+    pub success: bool,
+    pub tool: String,
+    pub endpoint: String,
+    pub runs: Vec<EngineRun>,
+    /// Ways the engines' answers disagree; empty with a single engine
+    pub discrepancies: Vec<String>,
+    /// Differences that don't fail the comparison
+    pub notes: Vec<String>,
+}
+
+impl EngineComparison {
+    /// How the calls ended, from CI's point of view
+    #[must_use]
+    pub fn outcome(&self) -> RunOutcome {
+        if self.success {
+            RunOutcome::Success
+        } else if !self.discrepancies.is_empty() {
+            RunOutcome::ValidationFailure
+        } else if self
+            .runs
+            .iter()
+            .any(|run| matches!(run.http_status, Some(401 | 403)))
+        {
+            RunOutcome::AuthFailure
+        } else {
+            RunOutcome::Error
+        }
+    }
+
+    #[must_use]
+    pub fn format_text(&self) -> String {
+        let mut output = String::new();
+        let _ = writeln!(output, "🔀 {} ({})", self.tool, self.endpoint);
+        for run in &self.runs {
+            let status = if run.success { "✅" } else { "❌" };
+            let detail = run.error.as_deref().map_or_else(
+                || format!("{} content item(s)", run.content_types().len()),
+                ToString::to_string,
+            );
+            let _ = writeln!(
+                output,
+                "  {status} {}: {detail} ({}ms)",
+                run.engine.as_str(),
+                run.response_time_ms
+            );
+        }
+        for discrepancy in &self.discrepancies {
+            let _ = writeln!(output, "  ✗ {discrepancy}");
+        }
+        for note in &self.notes {
+            let _ = writeln!(output, "  ⚠️ {note}");
+        }
+        output
+    }
+}
+
+/// Where the direct and Inspector runs disagree, and differences only worth noting
+fn compare_runs(direct: &EngineRun, inspector: &EngineRun) -> (Vec<String>, Vec<String>) {
+    let mut discrepancies = Vec::new();
+    let mut notes = Vec::new();
+    match (direct.success, inspector.success) {
+        (true, false) | (false, true) => {
+            let (passed, failed) = if direct.success {
+                (direct, inspector)
+            } else {
+                (inspector, direct)
+            };
+            discrepancies.push(format!(
+                "{} succeeded but {} failed: {}",
+                passed.engine.as_str(),
+                failed.engine.as_str(),
+                failed.error.as_deref().unwrap_or("unknown error")
+            ));
+        }
+        (false, false) => {}
+        (true, true) => {
+            if direct.is_error() != inspector.is_error() {
+                discrepancies.push(format!(
+                    "isError differs: direct {}, inspector {}",
+                    direct.is_error(),
+                    inspector.is_error()
+                ));
+            }
+            let (direct_types, inspector_types) =
+                (direct.content_types(), inspector.content_types());
+            if direct_types != inspector_types {
+                discrepancies.push(format!(
+                    "Content types differ: direct [{}], inspector [{}]",
+                    direct_types.join(", "),
+                    inspector_types.join(", ")
+                ));
+            }
+            if direct.has_structured_content() != inspector.has_structured_content() {
+                discrepancies.push(format!(
+                    "structuredContent only in the {} result",
+                    if direct.has_structured_content() {
+                        "direct"
+                    } else {
+                        "inspector"
+                    }
+                ));
+            }
+            let texts = [direct, inspector]
+                .map(|run| run.result.as_ref().map(response_text).unwrap_or_default());
+            if texts[0] != texts[1] {
+                notes.push(format!(
+                    "Response text differs ({} vs {} chars); expected if results change between calls",
+                    texts[0].chars().count(),
+                    texts[1].chars().count()
+                ));
+            }
+        }
+    }
+    (discrepancies, notes)
+}
+
+/// `npx` running `args`, sent through `transport`'s proxy and trusting its CA bundle
+fn inspector_command(
+    transport: &McpTransport,
+    endpoint: &str,
+    args: &[String],
+) -> Result<async_process::Command> {
+    let tls = transport.tls();
+    if tls.client_cert.is_some() {
+        return Err(GleanMcpError::Config(
+            "The Inspector CLI can't present tls.client_cert; use --engine direct for mTLS endpoints"
+                .to_string(),
+        ));
+    }
+    let mut command = async_command("npx");
+    command.args(args).kill_on_drop(true);
+    let proxy = transport.proxy();
+    if let Some(url) = proxy.proxy_for(endpoint) {
+        for var in PROXY_VARS {
+            command.env(var, &url);
+        }
+        // Node's fetch only reads the proxy variables when asked to
+        command.env("NODE_USE_ENV_PROXY", "1");
+        let bypass = proxy.bypass_hosts().join(",");
+        for var in ["NO_PROXY", "no_proxy"] {
+            command.env(var, &bypass);
+        }
+    } else {
+        for var in PROXY_VARS {
+            command.env_remove(var);
+        }
+    }
+    if let Some(ca_cert) = &tls.ca_cert {
+        command.env("NODE_EXTRA_CA_CERTS", ca_cert);
+    }
+    Ok(command)
+}
+
+/// `--tool-arg` value for one argument: strings as-is, anything else as JSON
+fn tool_arg(name: &str, value: &Value) -> String {
+    match value {
+        Value::String(text) => format!("{name}={text}"),
+        other => format!("{name}={other}"),
+    }
+}
+
+impl GleanMCPInspector {
+    /// Call `tool_name` with `arguments` through `engine`, comparing the answers for [`Engine::Both`]
+    ///
+    /// `package` is the Inspector's package spec (`McpInspectorConfig::package_spec`).
+    pub async fn cross_check_tool(
+        &self,
+        engine: Engine,
+        package: &str,
+        tool_name: &str,
+        arguments: &Value,
+        timeout: Duration,
+    ) -> EngineComparison {
+        let mut runs = Vec::new();
+        if matches!(engine, Engine::Direct | Engine::Both) {
+            runs.push(self.run_direct(tool_name, arguments, timeout).await);
+        }
+        if matches!(engine, Engine::Inspector | Engine::Both) {
+            runs.push(
+                self.run_inspector_cli(package, tool_name, arguments, timeout)
+                    .await,
+            );
+        }
+
+        let (discrepancies, notes) = match runs.as_slice() {
+            [direct, inspector] => compare_runs(direct, inspector),
+            _ => (Vec::new(), Vec::new()),
+        };
+        EngineComparison {
+            success: discrepancies.is_empty() && runs.iter().all(|run| run.success),
+            tool: tool_name.to_string(),
+            endpoint: self.server_url().to_string(),
+            runs,
+            discrepancies,
+            notes,
+        }
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    async fn run_direct(&self, tool_name: &str, arguments: &Value, timeout: Duration) -> EngineRun {
+        let start = Instant::now();
+        let response = async_timeout(
+            timeout,
+            Self::test_tool_direct(
+                self.transport(),
+                self.server_url(),
+                tool_name,
+                arguments,
+                &mut RequestTrace::default(),
+            ),
+        )
+        .await;
+        let response_time_ms = start.elapsed().as_millis() as u64;
+        self.reporter().info(&format!(
+            "direct: {tool_name} answered in {response_time_ms}ms"
+        ));
+        engine_run(Engine::Direct, response, response_time_ms)
+    }
+
+    /// Call the tool with `npx <package> --cli ... --method tools/call`
+    #[allow(clippy::cast_possible_truncation)]
+    async fn run_inspector_cli(
+        &self,
+        package: &str,
+        tool_name: &str,
+        arguments: &Value,
+        timeout: Duration,
+    ) -> EngineRun {
+        let mut args = vec![
+            "-y".to_string(),
+            package.to_string(),
+            "--cli".to_string(),
+            self.server_url().to_string(),
+            "--transport".to_string(),
+            "http".to_string(),
+            "--method".to_string(),
+            "tools/call".to_string(),
+            "--tool-name".to_string(),
+            tool_name.to_string(),
+        ];
+        for (name, value) in arguments.as_object().into_iter().flatten() {
+            args.extend(["--tool-arg".to_string(), tool_arg(name, value)]);
+        }
+        let transport = self.transport();
+        let headers = transport
+            .auth_token()
+            .map(|token| format!("Authorization: Bearer {token}"))
+            .into_iter()
+            .chain(
+                transport
+                    .headers()
+                    .iter()
+                    .map(|(name, value)| format!("{name}: {value}")),
+            );
+        for header in headers {
+            args.extend(["--header".to_string(), header]);
+        }
+
+        let start = Instant::now();
+        let response = async_timeout(timeout, async {
+            let output = inspector_command(transport, self.server_url(), &args)?
+                .output()
+                .await
+                .map_err(|e| {
+                    GleanMcpError::Inspector(format!("Failed to run npx {package}: {e}"))
+                })?;
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            if !output.status.success() {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                let message = stderr
+                    .lines()
+                    .chain(stdout.lines())
+                    .map(str::trim)
+                    .find(|line| !line.is_empty())
+                    .unwrap_or("no output")
+                    .to_string();
+                return Err(GleanMcpError::Inspector(format!(
+                    "Inspector CLI exited with {}: {message}",
+                    output.status
+                )));
+            }
+            serde_json::from_str::<Value>(&stdout).map_err(|e| {
+                GleanMcpError::Inspector(format!("Inspector CLI printed invalid JSON: {e}"))
+            })
+        })
+        .await;
+        let response_time_ms = start.elapsed().as_millis() as u64;
+        self.reporter().info(&format!(
+            "inspector: {tool_name} answered in {response_time_ms}ms"
+        ));
+        engine_run(Engine::Inspector, response, response_time_ms)
+    }
+}
+
+fn engine_run(engine: Engine, response: Result<Value>, response_time_ms: u64) -> EngineRun {
+    match response {
+        Ok(result) => EngineRun {
+            engine,
+            success: true,
+            result: Some(result),
+            error: None,
+            http_status: None,
+            response_time_ms,
+        },
+        Err(e) => EngineRun {
+            engine,
+            success: false,
+            result: None,
+            http_status: match &e {
+                GleanMcpError::Http { status, .. } => Some(*status),
+                _ => None,
+            },
+            error: Some(e.to_string()),
+            response_time_ms,
+        },
+    }
+}
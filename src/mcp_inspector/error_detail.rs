@@ -0,0 +1,127 @@
+//! Structured details of a failed tool call
+//!
+//! An error message flattens the server's answer into one line. For triage,
+//! [`ErrorDetail`] keeps its parts: the HTTP status, the response headers
+//! worth quoting to the server team (`Retry-After`, request and trace ids),
+//! the JSON-RPC error's code, message, and data, and the start of the body.
+
+use super::RawResponse;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// Longest body excerpt kept, in characters
+const MAX_BODY_EXCERPT: usize = 500;
+
+/// Response headers kept by name, besides the request and trace ids
+const TRIAGE_HEADERS: [&str; 2] = ["retry-after", "www-authenticate"];
+
+/// Trace headers kept by name, besides any `*request-id` or `*trace-id` header
+const ID_HEADERS: [&str; 3] = ["traceparent", "x-cloud-trace-context", "cf-ray"];
+
+/// What the server answered a failed request with
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct ErrorDetail {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub http_status: Option<u16>,
+    /// Triage-relevant response headers, keyed by lowercased name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub headers: BTreeMap<String, String>,
+    /// `error.code` of the JSON-RPC response
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jsonrpc_code: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jsonrpc_message: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jsonrpc_data: Option<Value>,
+    /// Start of the response body, cut to 500 characters
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body_excerpt: Option<String>,
+}
+
+impl ErrorDetail {
+    /// Details of `response`, or `None` when it is a JSON-RPC result
+    ///
+    /// HTTP errors, JSON-RPC errors, and bodies that aren't JSON all count as
+    /// failed answers. A request that got no response has no details.
+    #[must_use]
+    pub fn from_response(response: &RawResponse) -> Option<Self> {
+        if !response.success {
+            return None;
+        }
+        let parsed: Option<Value> = serde_json::from_str(&response.body).ok();
+        let error = parsed.as_ref().and_then(|value| value.get("error"));
+        let http_error = response.status.is_some_and(|status| status >= 400);
+        if !http_error && error.is_none() && parsed.is_some() {
+            return None;
+        }
+
+        let headers = response
+            .headers
+            .iter()
+            .filter(|(name, _)| is_triage_header(name))
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect();
+        let body = response.body.trim();
+        Some(Self {
+            http_status: response.status,
+            headers,
+            jsonrpc_code: error
+                .and_then(|error| error.get("code"))
+                .and_then(Value::as_i64),
+            jsonrpc_message: error
+                .and_then(|error| error.get("message"))
+                .and_then(Value::as_str)
+                .map(ToString::to_string),
+            jsonrpc_data: error.and_then(|error| error.get("data")).cloned(),
+            body_excerpt: (!body.is_empty()).then(|| excerpt(body)),
+        })
+    }
+
+    /// One-line summary, e.g. `HTTP 503 · JSON-RPC -32603 Internal error · retry-after: 30`
+    #[must_use]
+    pub fn describe(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(status) = self.http_status {
+            parts.push(format!("HTTP {status}"));
+        }
+        match (self.jsonrpc_code, &self.jsonrpc_message) {
+            (Some(code), Some(message)) => parts.push(format!("JSON-RPC {code} {message}")),
+            (Some(code), None) => parts.push(format!("JSON-RPC {code}")),
+            (None, Some(message)) => parts.push(format!("JSON-RPC error {message}")),
+            (None, None) => {}
+        }
+        parts.extend(
+            self.headers
+                .iter()
+                .map(|(name, value)| format!("{name}: {value}")),
+        );
+        parts.join(" · ")
+    }
+}
+
+/// The request and trace id headers among `headers`, for finding the request in backend logs
+#[must_use]
+pub fn request_ids(headers: &BTreeMap<String, String>) -> BTreeMap<String, String> {
+    headers
+        .iter()
+        .filter(|(name, _)| is_id_header(name))
+        .map(|(name, value)| (name.clone(), value.clone()))
+        .collect()
+}
+
+fn is_id_header(name: &str) -> bool {
+    ID_HEADERS.contains(&name) || name.ends_with("request-id") || name.ends_with("trace-id")
+}
+
+fn is_triage_header(name: &str) -> bool {
+    TRIAGE_HEADERS.contains(&name) || is_id_header(name)
+}
+
+fn excerpt(body: &str) -> String {
+    match body.char_indices().nth(MAX_BODY_EXCERPT) {
+        Some((end, _)) => format!("{}…", &body[..end]),
+        None => body.to_string(),
+    }
+}
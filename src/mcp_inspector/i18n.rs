@@ -0,0 +1,409 @@
+//! Localized query checks
+//!
+//! [`GleanMCPInspector::test_i18n`] sends the standard tool queries in
+//! several languages and checks the server copes with non-ASCII input: the
+//! call must succeed, the response must not show signs of mis-encoded text
+//! (replacement characters, UTF-8 read as Latin-1), and tools that answer in
+//! prose, like `chat`, must answer in the query's language. Results are
+//! rolled up into a pass rate per language.
+
+use super::{
+    DetectedLanguage, GleanMCPInspector, RequestTrace, async_timeout, detect_language,
+    response_text,
+};
+use crate::{GleanMcpError, Result, RunOutcome, TestQueryGenerator};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::time::{Duration, Instant};
+
+/// Built-in translations of the `search` and `chat` test queries
+const TRANSLATIONS: &[(&str, &str, &str)] = &[
+    (
+        "en",
+        "remote work policy",
+        "What are the main benefits of using Glean?",
+    ),
+    (
+        "de",
+        "Richtlinie für Homeoffice",
+        "Was sind die wichtigsten Vorteile, wenn man Glean nutzt?",
+    ),
+    (
+        "fr",
+        "politique de télétravail",
+        "Quels sont les principaux avantages de Glean pour les équipes ?",
+    ),
+    (
+        "es",
+        "política de teletrabajo",
+        "¿Cuáles son los principales beneficios de usar Glean en el trabajo?",
+    ),
+    (
+        "it",
+        "politica sul lavoro da remoto",
+        "Quali sono i principali vantaggi di usare Glean per il lavoro?",
+    ),
+    (
+        "pt",
+        "política de trabalho remoto",
+        "Quais são os principais benefícios de usar o Glean no trabalho?",
+    ),
+    (
+        "nl",
+        "beleid voor thuiswerken",
+        "Wat zijn de belangrijkste voordelen van het gebruik van Glean?",
+    ),
+    (
+        "ja",
+        "リモートワークの規定",
+        "Gleanを使う主なメリットは何ですか？",
+    ),
+    ("zh", "远程办公政策", "使用 Glean 的主要好处是什么？"),
+    (
+        "ko",
+        "재택근무 정책",
+        "Glean을 사용하면 어떤 장점이 있나요?",
+    ),
+    (
+        "ru",
+        "политика удалённой работы",
+        "Каковы основные преимущества использования Glean?",
+    ),
+    (
+        "ar",
+        "سياسة العمل عن بعد",
+        "ما هي الفوائد الرئيسية لاستخدام Glean؟",
+    ),
+    (
+        "he",
+        "מדיניות עבודה מרחוק",
+        "מהם היתרונות העיקריים של השימוש ב-Glean?",
+    ),
+    (
+        "hi",
+        "रिमोट वर्क नीति",
+        "Glean का उपयोग करने के मुख्य लाभ क्या हैं?",
+    ),
+];
+
+/// Settings for `test --i18n` (`i18n` section of the config file)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct I18nConfig {
+    /// ISO 639-1 codes of the languages to query in
+    pub languages: Vec<String>,
+    /// Tools called in every language
+    pub tools: Vec<String>,
+    /// Tools whose responses must be in the query's language
+    pub match_language: Vec<String>,
+    /// Queries per language and tool, added to or replacing the built-in translations
+    pub queries: BTreeMap<String, BTreeMap<String, String>>,
+    /// Language detections below this confidence are not acted on
+    pub min_confidence: f64,
+}
+
+impl Default for I18nConfig {
+    fn default() -> Self {
+        Self {
+            languages: ["en", "de", "fr", "es", "ja", "zh", "ko", "ar"]
+                .map(String::from)
+                .to_vec(),
+            tools: vec!["search".to_string(), "chat".to_string()],
+            match_language: vec!["chat".to_string()],
+            queries: BTreeMap::new(),
+            min_confidence: 0.5,
+        }
+    }
+}
+
+impl I18nConfig {
+    /// The query `tool` is called with in `language`
+    ///
+    /// Configured queries win; otherwise `chat` gets the translated chat
+    /// question and other tools the translated search query.
+    #[must_use]
+    pub fn query_for(&self, language: &str, tool: &str) -> Option<String> {
+        if let Some(query) = self.queries.get(language).and_then(|tools| tools.get(tool)) {
+            return Some(query.clone());
+        }
+        TRANSLATIONS
+            .iter()
+            .find(|(code, _, _)| *code == language)
+            .map(|(_, search, chat)| if tool == "chat" { chat } else { search }.to_string())
+    }
+}
+
+/// Parse the `--i18n` language list, e.g. `de,ja,ko`
+#[must_use]
+pub fn parse_languages(raw: &str) -> Vec<String> {
+    raw.split(',')
+        .map(|code| code.trim().to_lowercase())
+        .filter(|code| !code.is_empty())
+        .collect()
+}
+
+/// One tool called in one language
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct I18nCaseResult {
+    pub language: String,
+    pub tool: String,
+    pub query: String,
+    pub passed: bool,
+    /// Why the call failed, when it did
+    pub error: Option<String>,
+    /// HTTP status of a failed call, when the server answered with one
+    pub status: Option<u16>,
+    pub response_time_ms: u64,
+    /// Language the response was detected to be in
+    pub detected: Option<DetectedLanguage>,
+    /// Empty when passed
+    pub problems: Vec<String>,
+}
+
+/// Share of a language's calls that passed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LanguagePassRate {
+    pub language: String,
+    pub passed: usize,
+    pub total: usize,
+}
+
+impl LanguagePassRate {
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn rate(&self) -> f64 {
+        if self.total == 0 {
+            0.0
+        } else {
+            self.passed as f64 / self.total as f64 * 100.0
+        }
+    }
+}
+
+/// Results of the localized query checks against one endpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct I18nTestResult {
+    pub success: bool,
+    pub endpoint: String,
+    /// Pass rate per language, in the configured order
+    pub languages: Vec<LanguagePassRate>,
+    pub results: Vec<I18nCaseResult>,
+}
+
+impl I18nTestResult {
+    /// How the checks ended, from CI's point of view
+    #[must_use]
+    pub fn outcome(&self) -> RunOutcome {
+        if self.success {
+            RunOutcome::Success
+        } else if self
+            .results
+            .iter()
+            .any(|r| matches!(r.status, Some(401 | 403)))
+        {
+            RunOutcome::AuthFailure
+        } else if self
+            .results
+            .iter()
+            .all(|r| r.error.is_some() && r.status.is_none())
+        {
+            RunOutcome::ConnectivityFailure
+        } else {
+            RunOutcome::ValidationFailure
+        }
+    }
+
+    #[must_use]
+    pub fn format_text(&self) -> String {
+        let mut output = String::new();
+        let _ = writeln!(
+            output,
+            "🌐 Localized queries: {} language(s) ({})",
+            self.languages.len(),
+            self.endpoint
+        );
+        for rate in &self.languages {
+            let status = if rate.passed == rate.total {
+                "✅"
+            } else {
+                "❌"
+            };
+            let _ = writeln!(
+                output,
+                "  {status} {}: {}/{} passed ({:.0}%)",
+                rate.language,
+                rate.passed,
+                rate.total,
+                rate.rate()
+            );
+            for result in self.results.iter().filter(|r| r.language == rate.language) {
+                let status = if result.passed { "✓" } else { "✗" };
+                let detected = result.detected.as_ref().map_or_else(String::new, |d| {
+                    format!(", answered in {} ({:.0}%)", d.code, d.confidence * 100.0)
+                });
+                let _ = writeln!(
+                    output,
+                    "    {status} {} {:?}{detected}",
+                    result.tool, result.query
+                );
+                for problem in &result.problems {
+                    let _ = writeln!(output, "      - {problem}");
+                }
+            }
+        }
+        output
+    }
+}
+
+/// Common signs of mis-encoded text: replacement characters, C1 control
+/// characters, and UTF-8 read as Latin-1 or Windows-1252 (`Ã©`, `â€™`)
+fn encoding_damage(text: &str) -> Option<&'static str> {
+    if text.contains('\u{FFFD}') {
+        return Some("U+FFFD replacement characters");
+    }
+    if text.chars().any(|c| ('\u{80}'..='\u{9F}').contains(&c)) {
+        return Some("C1 control characters");
+    }
+    let mojibake = text
+        .chars()
+        .zip(text.chars().skip(1))
+        .any(|pair| match pair {
+            ('Ã' | 'Â', next) => ('\u{A0}'..='\u{BF}').contains(&next),
+            ('â', '€') => true,
+            _ => false,
+        });
+    mojibake.then_some("UTF-8 decoded as Latin-1 (mojibake)")
+}
+
+impl GleanMCPInspector {
+    /// Call each of `config.tools` once per language and check how the server handled it
+    ///
+    /// Fails before sending anything when a language has no query for a
+    /// tool, neither built in nor configured.
+    #[allow(clippy::future_not_send)]
+    pub async fn test_i18n(
+        &self,
+        config: &I18nConfig,
+        timeout: Duration,
+    ) -> Result<I18nTestResult> {
+        if config.languages.is_empty() || config.tools.is_empty() {
+            return Err(GleanMcpError::Config(
+                "No languages or tools to test (i18n.languages, i18n.tools)".to_string(),
+            ));
+        }
+        let mut cases = Vec::new();
+        for language in &config.languages {
+            for tool in &config.tools {
+                let query = config.query_for(language, tool).ok_or_else(|| {
+                    GleanMcpError::Config(format!(
+                        "No built-in '{language}' query for {tool}; add i18n.queries.{language}.{tool}"
+                    ))
+                })?;
+                cases.push((language, tool, query));
+            }
+        }
+
+        let mut results = Vec::new();
+        for (language, tool, query) in cases {
+            results.push(self.query_in(config, language, tool, query, timeout).await);
+        }
+
+        let languages = config
+            .languages
+            .iter()
+            .map(|language| {
+                let calls: Vec<&I18nCaseResult> =
+                    results.iter().filter(|r| &r.language == language).collect();
+                LanguagePassRate {
+                    language: language.clone(),
+                    passed: calls.iter().filter(|r| r.passed).count(),
+                    total: calls.len(),
+                }
+            })
+            .collect();
+        Ok(I18nTestResult {
+            success: results.iter().all(|r| r.passed),
+            endpoint: self.server_url().to_string(),
+            languages,
+            results,
+        })
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    async fn query_in(
+        &self,
+        config: &I18nConfig,
+        language: &str,
+        tool: &str,
+        query: String,
+        timeout: Duration,
+    ) -> I18nCaseResult {
+        let start = Instant::now();
+        let response = async_timeout(
+            timeout,
+            Self::test_tool_direct(
+                self.transport(),
+                self.server_url(),
+                tool,
+                &TestQueryGenerator::generate_arguments(tool, &query),
+                &mut RequestTrace::default(),
+            ),
+        )
+        .await;
+        let response_time_ms = start.elapsed().as_millis() as u64;
+
+        let mut problems = Vec::new();
+        let mut error = None;
+        let mut status = None;
+        let mut detected = None;
+        match response {
+            Ok(response) if response.get("isError").and_then(Value::as_bool) == Some(true) => {
+                problems.push(format!("{tool} returned an error result"));
+            }
+            Ok(response) => {
+                let text = response_text(&response);
+                if let Some(damage) = encoding_damage(&text) {
+                    problems.push(format!("Response contains {damage}"));
+                }
+                detected = detect_language(&text)
+                    .filter(|detected| detected.confidence >= config.min_confidence);
+                // Responses whose language can't be told reliably aren't held against the server
+                if let Some(detected) = &detected
+                    && detected.code != language
+                    && config.match_language.iter().any(|name| name == tool)
+                {
+                    problems.push(format!(
+                        "Asked in '{language}', answered in '{}' ({:.0}% confidence)",
+                        detected.code,
+                        detected.confidence * 100.0
+                    ));
+                }
+            }
+            Err(e) => {
+                if let GleanMcpError::Http { status: code, .. } = &e {
+                    status = Some(*code);
+                }
+                problems.push(format!("{tool} failed: {e}"));
+                error = Some(e.to_string());
+            }
+        }
+
+        self.reporter().info(&format!(
+            "{language} {tool} {query:?}: {}",
+            if problems.is_empty() { "ok" } else { "failed" }
+        ));
+        I18nCaseResult {
+            language: language.to_string(),
+            tool: tool.to_string(),
+            query,
+            passed: problems.is_empty(),
+            error,
+            status,
+            response_time_ms,
+            detected,
+            problems,
+        }
+    }
+}
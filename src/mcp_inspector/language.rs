@@ -0,0 +1,253 @@
+//! Response language detection
+//!
+//! A deliberately small detector: non-Latin scripts are identified by their
+//! Unicode ranges, and Latin-script languages by how many of their most
+//! common words appear in the text. Good enough to catch an answer in the
+//! wrong language; not a general-purpose language identifier.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Latin-script languages and their most frequent function words
+const STOPWORDS: &[(&str, &[&str])] = &[
+    (
+        "en",
+        &[
+            "the", "and", "is", "are", "of", "to", "in", "that", "for", "with", "what", "how",
+            "this", "you", "be",
+        ],
+    ),
+    (
+        "de",
+        &[
+            "der", "die", "das", "und", "ist", "nicht", "mit", "ein", "eine", "für", "auf", "wie",
+            "ich", "sie", "zu",
+        ],
+    ),
+    (
+        "fr",
+        &[
+            "le", "la", "les", "et", "est", "des", "une", "pour", "dans", "que", "qui", "pas",
+            "sur", "avec", "du",
+        ],
+    ),
+    (
+        "es",
+        &[
+            "el", "los", "las", "y", "es", "una", "para", "que", "con", "por", "del", "como", "en",
+            "se", "está",
+        ],
+    ),
+    (
+        "it",
+        &[
+            "il", "gli", "e", "è", "di", "che", "per", "una", "con", "non", "della", "sono",
+            "come", "del", "anche",
+        ],
+    ),
+    (
+        "pt",
+        &[
+            "o", "os", "e", "é", "de", "que", "para", "uma", "com", "não", "do", "da", "em",
+            "como", "são",
+        ],
+    ),
+    (
+        "nl",
+        &[
+            "de", "het", "een", "en", "is", "van", "dat", "niet", "voor", "met", "op", "zijn",
+            "wat", "hoe", "ook",
+        ],
+    ),
+];
+
+/// Minimum Latin stopword hits before a guess is considered reliable
+const MIN_STOPWORD_HITS: usize = 3;
+
+/// A detected language with a rough confidence (0.0-1.0)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DetectedLanguage {
+    /// ISO 639-1 code, e.g. `en`, `ja`
+    pub code: String,
+    pub confidence: f64,
+}
+
+/// Settings for the response language check
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LanguageCheckConfig {
+    pub enabled: bool,
+    /// Tools whose responses are checked
+    pub tools: Vec<String>,
+    /// ISO 639-1 code every response must be in; when unset, the query's language is expected
+    pub expected: Option<String>,
+    /// Detections below this confidence are not acted on
+    pub min_confidence: f64,
+}
+
+impl Default for LanguageCheckConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            tools: vec!["chat".to_string(), "search".to_string()],
+            expected: None,
+            min_confidence: 0.5,
+        }
+    }
+}
+
+impl LanguageCheckConfig {
+    /// Check that `response` to `query` from `tool_name` is in the expected language.
+    ///
+    /// Returns `Ok(Some(note))` when checked, `Ok(None)` when the tool isn't
+    /// covered or the languages couldn't be determined reliably, and `Err`
+    /// with a description on mismatch.
+    pub fn check(
+        &self,
+        tool_name: &str,
+        query: &str,
+        response: &Value,
+    ) -> std::result::Result<Option<String>, String> {
+        if !self.enabled || !self.tools.iter().any(|tool| tool == tool_name) {
+            return Ok(None);
+        }
+
+        let expected = match &self.expected {
+            Some(code) => code.to_lowercase(),
+            None => match detect_language(query) {
+                Some(detected) if detected.confidence >= self.min_confidence => detected.code,
+                _ => return Ok(None),
+            },
+        };
+
+        let text = response_text(response);
+        match detect_language(&text) {
+            Some(detected) if detected.confidence >= self.min_confidence => {
+                if detected.code == expected {
+                    Ok(Some(format!(
+                        "Response language: {} ({:.0}% confidence)",
+                        detected.code,
+                        detected.confidence * 100.0
+                    )))
+                } else {
+                    Err(format!(
+                        "Language mismatch: expected '{expected}', response is '{}' ({:.0}% confidence)",
+                        detected.code,
+                        detected.confidence * 100.0
+                    ))
+                }
+            }
+            _ => Ok(None),
+        }
+    }
+}
+
+/// Concatenated text content of an MCP tool result
+#[must_use]
+pub fn response_text(response: &Value) -> String {
+    response
+        .get("content")
+        .and_then(Value::as_array)
+        .map_or_else(
+            || response.to_string(),
+            |blocks| {
+                blocks
+                    .iter()
+                    .filter_map(|block| block.get("text").and_then(Value::as_str))
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            },
+        )
+}
+
+/// Detect the dominant language of `text`
+#[must_use]
+#[allow(clippy::cast_precision_loss)]
+pub fn detect_language(text: &str) -> Option<DetectedLanguage> {
+    let mut script_counts: Vec<(&str, usize)> = Vec::new();
+    let mut letters = 0_usize;
+    for ch in text.chars().filter(|c| c.is_alphabetic()) {
+        letters += 1;
+        if let Some(code) = script_language(ch) {
+            match script_counts.iter_mut().find(|(c, _)| *c == code) {
+                Some((_, count)) => *count += 1,
+                None => script_counts.push((code, 1)),
+            }
+        }
+    }
+    if letters == 0 {
+        return None;
+    }
+
+    // Kana anywhere means Japanese, even when kanji dominate
+    let kana = script_counts
+        .iter()
+        .find(|(code, _)| *code == "ja")
+        .map_or(0, |(_, count)| *count);
+    let han = script_counts
+        .iter()
+        .find(|(code, _)| *code == "zh")
+        .map_or(0, |(_, count)| *count);
+    if kana > 0 {
+        script_counts.retain(|(code, _)| *code != "zh");
+        if let Some((_, count)) = script_counts.iter_mut().find(|(code, _)| *code == "ja") {
+            *count += han;
+        }
+    }
+
+    if let Some((code, count)) = script_counts.iter().max_by_key(|(_, count)| *count)
+        && *count * 2 >= letters
+    {
+        return Some(DetectedLanguage {
+            code: (*code).to_string(),
+            confidence: (*count as f64 / letters as f64).min(1.0),
+        });
+    }
+
+    detect_latin(text)
+}
+
+/// Language implied by a character's script, for scripts used by one main language
+const fn script_language(ch: char) -> Option<&'static str> {
+    match ch as u32 {
+        0x3040..=0x30FF => Some("ja"),
+        0x4E00..=0x9FFF | 0x3400..=0x4DBF => Some("zh"),
+        0xAC00..=0xD7AF | 0x1100..=0x11FF => Some("ko"),
+        0x0400..=0x04FF => Some("ru"),
+        0x0600..=0x06FF => Some("ar"),
+        0x0590..=0x05FF => Some("he"),
+        0x0370..=0x03FF => Some("el"),
+        0x0E00..=0x0E7F => Some("th"),
+        0x0900..=0x097F => Some("hi"),
+        _ => None,
+    }
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn detect_latin(text: &str) -> Option<DetectedLanguage> {
+    let lowered = text.to_lowercase();
+    let words: Vec<&str> = lowered
+        .split(|c: char| !c.is_alphabetic())
+        .filter(|word| !word.is_empty())
+        .collect();
+
+    let mut scores: Vec<(&str, usize)> = STOPWORDS
+        .iter()
+        .map(|(code, stopwords)| {
+            let hits = words.iter().filter(|word| stopwords.contains(word)).count();
+            (*code, hits)
+        })
+        .collect();
+    scores.sort_by_key(|(_, hits)| std::cmp::Reverse(*hits));
+
+    let (best_code, best) = scores[0];
+    let runner_up = scores.get(1).map_or(0, |(_, hits)| *hits);
+    if best < MIN_STOPWORD_HITS {
+        return None;
+    }
+
+    Some(DetectedLanguage {
+        code: best_code.to_string(),
+        confidence: (best - runner_up) as f64 / best as f64,
+    })
+}
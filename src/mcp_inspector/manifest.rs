@@ -0,0 +1,132 @@
+//! Expected-tool manifests
+//!
+//! An endpoint's `expected_tools` lists tools it must advertise. With
+//! `exact_tools` the list is the endpoint's whole manifest, and any other tool
+//! it advertises is unexpected. [`ToolManifestCheck::compare`] diffs a live
+//! `tools/list` against the manifest for both `test` and `inspect`.
+
+use super::{GleanMCPInspector, InspectorResult, ToolInfo};
+use crate::{EndpointConfig, Result, schema_version};
+use std::collections::BTreeMap;
+
+/// How an endpoint's advertised tools differ from its manifest
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ToolManifestCheck {
+    /// Expected tools the endpoint doesn't advertise
+    pub missing: Vec<String>,
+    /// Advertised tools outside an exact manifest
+    pub unexpected: Vec<String>,
+}
+
+impl ToolManifestCheck {
+    /// Compare `available` tools against `endpoint`'s manifest
+    #[must_use]
+    pub fn compare(endpoint: &EndpointConfig, available: &[ToolInfo]) -> Self {
+        let missing = endpoint
+            .expected_tools
+            .iter()
+            .filter(|name| !available.iter().any(|tool| tool.name == **name))
+            .cloned()
+            .collect();
+        let unexpected = if endpoint.exact_tools {
+            available
+                .iter()
+                .filter(|tool| !endpoint.expected_tools.contains(&tool.name))
+                .map(|tool| tool.name.clone())
+                .collect()
+        } else {
+            Vec::new()
+        };
+        Self {
+            missing,
+            unexpected,
+        }
+    }
+
+    /// The mismatch, e.g. `missing tools: chat; unexpected tools: debug_echo`
+    #[must_use]
+    pub fn describe(&self) -> Option<String> {
+        let parts: Vec<String> = [
+            ("missing tools", &self.missing),
+            ("unexpected tools", &self.unexpected),
+        ]
+        .into_iter()
+        .filter(|(_, tools)| !tools.is_empty())
+        .map(|(label, tools)| format!("{label}: {}", tools.join(", ")))
+        .collect();
+        (!parts.is_empty()).then(|| parts.join("; "))
+    }
+}
+
+impl GleanMCPInspector {
+    /// Check connectivity, then each of `endpoints`' live `tools/list` against its manifest
+    ///
+    /// A failed connectivity check is returned as is; otherwise the tool
+    /// results, success, and error are those of the manifest checks.
+    pub async fn validate_server_and_tools(
+        &self,
+        endpoints: &[EndpointConfig],
+    ) -> Result<InspectorResult> {
+        let mut result = self.validate_server_with_inspector().await?;
+        if result.success
+            && let Some(manifest) = self.check_tool_manifests(endpoints).await
+        {
+            result.tool_results = manifest.tool_results;
+            result.success = manifest.success;
+            result.error = manifest.error;
+        }
+        Ok(result)
+    }
+
+    /// Check each of `endpoints` that declares `expected_tools` against its live `tools/list`
+    ///
+    /// Tool results are named `tool (endpoint)`. `None` when no endpoint
+    /// declares a manifest.
+    pub async fn check_tool_manifests(
+        &self,
+        endpoints: &[EndpointConfig],
+    ) -> Option<InspectorResult> {
+        let mut tool_results = BTreeMap::new();
+        let mut errors = Vec::new();
+        let mut checked = false;
+        for endpoint in endpoints
+            .iter()
+            .filter(|endpoint| !endpoint.expected_tools.is_empty())
+        {
+            checked = true;
+            let url = self.url_for_endpoint(endpoint);
+            let listed = match self.list_available_tools_from_endpoint(&url, false).await {
+                Ok(listed) if listed.success => listed,
+                Ok(listed) => {
+                    errors.push(format!(
+                        "Could not list tools on {url}: {}",
+                        listed.error.unwrap_or_default()
+                    ));
+                    continue;
+                }
+                Err(e) => {
+                    errors.push(format!("Could not list tools on {url}: {e}"));
+                    continue;
+                }
+            };
+            let validation =
+                Self::validate_glean_tools(listed.inspector_data.unwrap_or_default(), endpoint);
+            tool_results.extend(
+                validation
+                    .tool_results
+                    .into_iter()
+                    .flatten()
+                    .map(|(tool, found)| (format!("{tool} ({})", endpoint.name), found)),
+            );
+            errors.extend(validation.error);
+        }
+        checked.then(|| InspectorResult {
+            schema_version: schema_version(),
+            success: errors.is_empty(),
+            tool_results: Some(tool_results),
+            inspector_data: None,
+            error: (!errors.is_empty()).then(|| errors.join("; ")),
+            server: None,
+        })
+    }
+}
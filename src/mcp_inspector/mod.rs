@@ -1,3 +1,87 @@
+pub mod act_as;
+pub mod agents;
+pub mod arguments;
+pub mod auth_challenge;
+pub mod cancellation;
+pub mod capture;
+pub mod cassette;
+pub mod chaos;
+pub mod chatgpt;
+pub mod clock;
+pub mod concurrency;
+pub mod conformance;
+pub mod corpus;
+pub mod duplicates;
+pub mod engine;
+pub mod error_detail;
+pub mod i18n;
+pub mod language;
+pub mod manifest;
+pub mod negative;
+pub mod notifications;
+pub mod pagination;
+pub mod permissions;
+pub mod pii;
+pub mod plan;
+pub mod profile;
+pub mod proxy;
+pub mod query_rules;
+pub mod rate_limit;
+pub mod repeat;
+pub mod response_size;
+pub mod result_check;
+pub mod resume;
+pub mod retry;
+pub mod run_cancel;
+pub mod server_info;
+pub mod shard;
+pub mod snapshot;
+pub mod stress;
+pub mod template;
+pub mod tls;
+pub mod transport;
 pub mod validator;
 
+pub use act_as::*;
+pub use agents::*;
+pub use arguments::*;
+pub use auth_challenge::*;
+pub use cancellation::*;
+pub use capture::*;
+pub use cassette::*;
+pub use chaos::*;
+pub use chatgpt::*;
+pub use clock::*;
+pub use concurrency::*;
+pub use conformance::*;
+pub use corpus::*;
+pub use duplicates::*;
+pub use engine::*;
+pub use error_detail::*;
+pub use i18n::*;
+pub use language::*;
+pub use manifest::*;
+pub use negative::*;
+pub use notifications::*;
+pub use pagination::*;
+pub use permissions::*;
+pub use pii::*;
+pub use plan::*;
+pub use profile::*;
+pub use proxy::*;
+pub use query_rules::*;
+pub use rate_limit::*;
+pub use repeat::*;
+pub use response_size::*;
+pub use result_check::*;
+pub use resume::*;
+pub use retry::*;
+pub use run_cancel::*;
+pub use server_info::*;
+pub use shard::*;
+pub use snapshot::*;
+pub use stress::*;
+pub use template::*;
+pub use tls::*;
+pub use transport::*;
 pub use validator::*;
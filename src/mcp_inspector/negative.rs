@@ -0,0 +1,346 @@
+//! Negative testing of the server's error paths
+//!
+//! [`GleanMCPInspector::test_error_handling`] deliberately sends bad
+//! `tools/call` requests: missing arguments, arguments of the wrong type or
+//! shape, an unknown tool name, and an oversized payload. It asserts that
+//! the server answers each one with a proper JSON-RPC error object carrying
+//! the right code. HTTP 5xx responses and leaked stack traces always fail.
+
+use super::{GleanMCPInspector, ToolInfo};
+use crate::{Result, RunOutcome};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::fmt::Write;
+use std::time::Instant;
+
+/// Tool name no Glean server exposes
+const UNKNOWN_TOOL: &str = "glean_mcp_test_no_such_tool";
+
+/// Size of the string argument sent by [`NegativeCase::OversizedPayload`]
+const OVERSIZED_BYTES: usize = 2 * 1024 * 1024;
+
+/// JSON-RPC "Invalid params"
+const INVALID_PARAMS: i64 = -32602;
+/// JSON-RPC "Method not found"
+const METHOD_NOT_FOUND: i64 = -32601;
+
+/// Fragments of stack traces from common server runtimes
+const STACK_TRACE_MARKERS: [&str; 7] = [
+    "Traceback (most recent call last)",
+    "Exception in thread",
+    "\tat ",
+    "    at ",
+    "panicked at",
+    "stack backtrace",
+    "goroutine ",
+];
+
+/// A deliberately invalid request
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NegativeCase {
+    /// Call a tool with no arguments at all
+    MissingArguments,
+    /// Pass a number where the tool expects a string
+    InvalidArgumentType,
+    /// Pass a string instead of an arguments object
+    NonObjectArguments,
+    /// Call a tool that doesn't exist
+    UnknownTool,
+    /// Send a multi-megabyte string argument
+    OversizedPayload,
+}
+
+impl NegativeCase {
+    pub const ALL: [Self; 5] = [
+        Self::MissingArguments,
+        Self::InvalidArgumentType,
+        Self::NonObjectArguments,
+        Self::UnknownTool,
+        Self::OversizedPayload,
+    ];
+
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::MissingArguments => "missing_arguments",
+            Self::InvalidArgumentType => "invalid_argument_type",
+            Self::NonObjectArguments => "non_object_arguments",
+            Self::UnknownTool => "unknown_tool",
+            Self::OversizedPayload => "oversized_payload",
+        }
+    }
+
+    /// JSON-RPC error codes that correctly describe this case
+    #[must_use]
+    pub const fn expected_codes(self) -> &'static [i64] {
+        match self {
+            Self::UnknownTool => &[INVALID_PARAMS, METHOD_NOT_FOUND],
+            _ => &[INVALID_PARAMS],
+        }
+    }
+
+    /// `tools/call` params for this case against `tool`, whose string argument is `parameter`
+    fn params(self, tool: &str, parameter: &str) -> Value {
+        match self {
+            Self::MissingArguments => json!({ "name": tool, "arguments": {} }),
+            Self::InvalidArgumentType => json!({ "name": tool, "arguments": { parameter: 12345 } }),
+            Self::NonObjectArguments => json!({ "name": tool, "arguments": "not an object" }),
+            Self::UnknownTool => json!({ "name": UNKNOWN_TOOL, "arguments": { "query": "test" } }),
+            Self::OversizedPayload => {
+                json!({ "name": tool, "arguments": { parameter: "a".repeat(OVERSIZED_BYTES) } })
+            }
+        }
+    }
+}
+
+/// How the server answered one [`NegativeCase`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NegativeCaseResult {
+    pub case: NegativeCase,
+    /// Tool the request was aimed at
+    pub tool: String,
+    pub passed: bool,
+    pub http_status: Option<u16>,
+    /// `error.code` of the JSON-RPC response, if it had one
+    pub error_code: Option<i64>,
+    pub error_message: Option<String>,
+    /// Why the response isn't a proper error; empty when passed
+    pub problems: Vec<String>,
+    pub response_time_ms: u64,
+}
+
+/// Results of the negative test suite against one endpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NegativeTestResult {
+    pub success: bool,
+    pub endpoint: String,
+    pub total_cases: usize,
+    pub passed_cases: usize,
+    pub failed_cases: usize,
+    pub results: Vec<NegativeCaseResult>,
+}
+
+impl NegativeTestResult {
+    /// How the suite ended, from CI's point of view
+    #[must_use]
+    pub fn outcome(&self) -> RunOutcome {
+        let statuses: Vec<Option<u16>> = self.results.iter().map(|r| r.http_status).collect();
+        if self.success {
+            RunOutcome::Success
+        } else if statuses.iter().all(Option::is_none) {
+            RunOutcome::ConnectivityFailure
+        } else if statuses.iter().all(|s| matches!(s, Some(401 | 403))) {
+            RunOutcome::AuthFailure
+        } else {
+            RunOutcome::ValidationFailure
+        }
+    }
+
+    #[must_use]
+    pub fn format_text(&self) -> String {
+        let mut output = String::new();
+        let _ = writeln!(
+            output,
+            "🧨 Error handling: {}/{} cases handled correctly ({})",
+            self.passed_cases, self.total_cases, self.endpoint
+        );
+        for result in &self.results {
+            let status = if result.passed { "✅" } else { "❌" };
+            let code = result.error_code.map_or_else(
+                || "no error code".to_string(),
+                |code| format!("code {code}"),
+            );
+            let http = result.http_status.map_or_else(
+                || "no response".to_string(),
+                |status| format!("HTTP {status}"),
+            );
+            let _ = writeln!(
+                output,
+                "  {status} {} ({}): {http}, {code} ({}ms)",
+                result.case.as_str(),
+                result.tool,
+                result.response_time_ms
+            );
+            for problem in &result.problems {
+                let _ = writeln!(output, "    - {problem}");
+            }
+        }
+        output
+    }
+}
+
+/// Problems any answer can have: no response, a 5xx, or a leaked stack trace
+pub(crate) fn transport_problems(status: Option<u16>, body: &str) -> Vec<String> {
+    let mut problems = Vec::new();
+    match status {
+        None => problems.push("No HTTP response".to_string()),
+        Some(status) if status >= 500 => problems.push(format!("Server error (HTTP {status})")),
+        _ => {}
+    }
+    if let Some(marker) = STACK_TRACE_MARKERS
+        .iter()
+        .find(|marker| body.contains(*marker))
+    {
+        problems.push(format!("Response leaks a stack trace ({marker:?})"));
+    }
+    problems
+}
+
+impl GleanMCPInspector {
+    /// Send each [`NegativeCase`] to the default endpoint and check the errors returned
+    ///
+    /// Argument cases target `search` when the server lists it, else the
+    /// first listed tool.
+    pub async fn test_error_handling(&self) -> Result<NegativeTestResult> {
+        let listed = self.list_available_tools(false).await?;
+        let tools = listed
+            .inspector_data
+            .as_ref()
+            .map(ToolInfo::from_tools_list)
+            .unwrap_or_default();
+        let target = tools
+            .iter()
+            .find(|tool| tool.name == "search")
+            .or_else(|| tools.first());
+        let tool = target.map_or("search", |tool| tool.name.as_str());
+        let parameter = target
+            .and_then(|tool| tool.schema.as_ref())
+            .and_then(string_parameter)
+            .unwrap_or_else(|| "query".to_string());
+
+        let mut results = Vec::with_capacity(NegativeCase::ALL.len());
+        for case in NegativeCase::ALL {
+            results.push(self.run_case(case, tool, &parameter).await);
+        }
+
+        let passed_cases = results.iter().filter(|r| r.passed).count();
+        Ok(NegativeTestResult {
+            success: passed_cases == results.len(),
+            endpoint: self.server_url().to_string(),
+            total_cases: results.len(),
+            passed_cases,
+            failed_cases: results.len() - passed_cases,
+            results,
+        })
+    }
+
+    pub(crate) async fn run_case(
+        &self,
+        case: NegativeCase,
+        tool: &str,
+        parameter: &str,
+    ) -> NegativeCaseResult {
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "tools/call",
+            "params": case.params(tool, parameter)
+        });
+        let start = Instant::now();
+        let response = self
+            .transport()
+            .post_json_rpc(self.server_url(), &request)
+            .await;
+        let response_time_ms = u64::try_from(start.elapsed().as_millis()).unwrap_or(u64::MAX);
+
+        let tool = if case == NegativeCase::UnknownTool {
+            UNKNOWN_TOOL
+        } else {
+            tool
+        };
+        let (status, body) = match response {
+            Ok(response) if response.success => (response.status, response.body),
+            Ok(response) => (None, response.stderr),
+            Err(e) => (None, e.to_string()),
+        };
+
+        let mut problems = transport_problems(status, &body);
+        let parsed: Option<Value> = serde_json::from_str(&body).ok();
+        let error = parsed.as_ref().and_then(|value| value.get("error"));
+        let tool_error = parsed
+            .as_ref()
+            .and_then(|value| value.get("result"))
+            .filter(|result| result.get("isError").and_then(Value::as_bool) == Some(true));
+        let error_code = error
+            .and_then(|error| error.get("code"))
+            .and_then(Value::as_i64);
+        let error_message = error
+            .and_then(|error| error.get("message"))
+            .and_then(Value::as_str)
+            .map(ToString::to_string);
+
+        if status.is_some_and(|status| status < 500) {
+            match (error, tool_error) {
+                (Some(_), _) => {
+                    match error_code {
+                        Some(code) if !case.expected_codes().contains(&code) => {
+                            problems.push(format!(
+                                "Error code {code}, expected {}",
+                                describe_codes(case.expected_codes())
+                            ));
+                        }
+                        Some(_) => {}
+                        None => problems.push("Error object has no integer code".to_string()),
+                    }
+                    if error_message.as_deref().is_none_or(str::is_empty) {
+                        problems.push("Error object has no message".to_string());
+                    }
+                }
+                // MCP lets tools report bad input as a tool error result
+                (None, Some(_)) if case != NegativeCase::UnknownTool => {}
+                (None, _) if parsed.is_some() => {
+                    problems.push("Request succeeded instead of returning an error".to_string());
+                }
+                (None, _)
+                    if case == NegativeCase::OversizedPayload
+                        && matches!(status, Some(400 | 413)) => {}
+                (None, _) => problems.push("Response is not a JSON-RPC error object".to_string()),
+            }
+        }
+
+        self.reporter().info(&format!(
+            "{}: {}",
+            case.as_str(),
+            if problems.is_empty() { "ok" } else { "failed" }
+        ));
+        NegativeCaseResult {
+            case,
+            tool: tool.to_string(),
+            passed: problems.is_empty(),
+            http_status: status,
+            error_code,
+            error_message,
+            problems,
+            response_time_ms,
+        }
+    }
+}
+
+/// First string property in `schema`, preferring required ones
+pub(crate) fn string_parameter(schema: &Value) -> Option<String> {
+    let properties = schema.get("properties")?.as_object()?;
+    let required: Vec<&str> = schema
+        .get("required")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str)
+        .collect();
+    let is_string =
+        |name: &&String| properties[*name].get("type").and_then(Value::as_str) == Some("string");
+    properties
+        .keys()
+        .filter(is_string)
+        .find(|name| required.contains(&name.as_str()))
+        .or_else(|| properties.keys().find(is_string))
+        .cloned()
+}
+
+fn describe_codes(codes: &[i64]) -> String {
+    codes
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(" or ")
+}
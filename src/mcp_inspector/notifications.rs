@@ -0,0 +1,227 @@
+//! Server-to-client notifications on a long-lived session
+//!
+//! [`GleanMCPInspector::watch_notifications`] initializes a session, opens
+//! its GET event stream, and reads it for a bounded time. Every message the
+//! server sends must be a well-formed JSON-RPC notification or request, and
+//! `notifications/tools/list_changed` is only allowed when `initialize`
+//! advertised `tools.listChanged`. A server that offers no stream (HTTP 405)
+//! passes, since streams are optional.
+
+use super::{GleanMCPInspector, SseEvent, StreamRequest, parse_event_stream};
+use crate::RunOutcome;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::time::{Duration, Instant};
+
+/// Method the server sends when its tool list changes
+pub const LIST_CHANGED: &str = "notifications/tools/list_changed";
+
+/// A message on the stream that isn't a valid notification or request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MalformedNotification {
+    /// The event's `data`, as received
+    pub data: String,
+    pub reason: String,
+}
+
+/// Results of watching a session's event stream
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotificationWatchResult {
+    pub success: bool,
+    pub endpoint: String,
+    /// `Mcp-Session-Id` assigned by the server; `None` for stateless servers
+    pub session_id: Option<String>,
+    /// How long the stream was read for
+    pub wait_secs: u64,
+    /// Whether the server opened a GET event stream
+    pub stream_offered: bool,
+    /// Whether `initialize` advertised `tools.listChanged`
+    pub list_changed_advertised: bool,
+    /// Well-formed messages received, by method
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub received: BTreeMap<String, usize>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub malformed: Vec<MalformedNotification>,
+    pub duration_ms: u64,
+    pub error: Option<String>,
+}
+
+impl NotificationWatchResult {
+    /// How the watch ended, from CI's point of view
+    #[must_use]
+    pub const fn outcome(&self) -> RunOutcome {
+        if self.success {
+            RunOutcome::Success
+        } else if self.malformed.is_empty() {
+            RunOutcome::ConnectivityFailure
+        } else {
+            RunOutcome::ValidationFailure
+        }
+    }
+
+    #[must_use]
+    pub fn format_text(&self) -> String {
+        let mut output = String::new();
+        let _ = writeln!(
+            output,
+            "📣 Notifications: watched {} for {}s",
+            self.endpoint, self.wait_secs
+        );
+        if let Some(error) = &self.error {
+            let _ = writeln!(output, "Error: {error}");
+            return output;
+        }
+        let _ = writeln!(
+            output,
+            "  Session: {}",
+            self.session_id
+                .as_deref()
+                .unwrap_or("none (stateless server)")
+        );
+        if !self.stream_offered {
+            let _ = writeln!(output, "  Stream: not offered (HTTP 405)");
+            return output;
+        }
+        let _ = writeln!(
+            output,
+            "  list_changed: {}, {}",
+            if self.list_changed_advertised {
+                "advertised"
+            } else {
+                "not advertised"
+            },
+            if self.received.contains_key(LIST_CHANGED) {
+                "received"
+            } else {
+                "not received"
+            }
+        );
+        let received: usize = self.received.values().sum();
+        let _ = writeln!(output, "  Received: {received}");
+        for (method, count) in &self.received {
+            let _ = writeln!(output, "    - {method} ({count}x)");
+        }
+        let _ = writeln!(output, "  Malformed: {}", self.malformed.len());
+        for malformed in &self.malformed {
+            let _ = writeln!(output, "    - {}: {}", malformed.reason, malformed.data);
+        }
+        output
+    }
+}
+
+/// The method of a well-formed server message, or why it's malformed
+fn check_message(data: &str, list_changed_advertised: bool) -> Result<String, String> {
+    let message: Value = serde_json::from_str(data).map_err(|e| format!("not valid JSON ({e})"))?;
+    if message.get("jsonrpc").and_then(Value::as_str) != Some("2.0") {
+        return Err("missing \"jsonrpc\": \"2.0\"".to_string());
+    }
+    if message.get("result").is_some() || message.get("error").is_some() {
+        return Err("a response, which the GET stream must not carry".to_string());
+    }
+    let method = message
+        .get("method")
+        .and_then(Value::as_str)
+        .ok_or("no method")?;
+    if message
+        .get("params")
+        .is_some_and(|params| !params.is_object())
+    {
+        return Err("params is not an object".to_string());
+    }
+    if method == LIST_CHANGED && !list_changed_advertised {
+        return Err(
+            "list_changed sent, but initialize didn't advertise tools.listChanged".to_string(),
+        );
+    }
+    Ok(method.to_string())
+}
+
+impl GleanMCPInspector {
+    /// Open a session on the default endpoint and read its event stream for up to `wait`
+    #[allow(clippy::cast_possible_truncation)]
+    pub async fn watch_notifications(&self, wait: Duration) -> NotificationWatchResult {
+        let started = Instant::now();
+        let mut result = NotificationWatchResult {
+            success: false,
+            endpoint: self.server_url().to_string(),
+            session_id: None,
+            wait_secs: wait.as_secs(),
+            stream_offered: false,
+            list_changed_advertised: false,
+            received: BTreeMap::new(),
+            malformed: Vec::new(),
+            duration_ms: 0,
+            error: None,
+        };
+        let (session_id, transport, initialized) = match self.open_session().await {
+            Ok(session) => session,
+            Err(error) => {
+                result.error = Some(error);
+                return result;
+            }
+        };
+        result.session_id = session_id;
+        result.list_changed_advertised = initialized
+            .pointer("/capabilities/tools/listChanged")
+            .and_then(Value::as_bool)
+            .unwrap_or(false);
+
+        self.reporter().info(&format!(
+            "Watching {} for notifications for {}s",
+            self.server_url(),
+            wait.as_secs()
+        ));
+        let stream = StreamRequest {
+            wait,
+            ..StreamRequest::default()
+        };
+        let response = match transport.read_stream(self.server_url(), &stream).await {
+            Ok(response) if response.success => response,
+            Ok(response) => {
+                result.error = Some(format!("Could not open the stream: {}", response.stderr));
+                return result;
+            }
+            Err(e) => {
+                result.error = Some(e.to_string());
+                return result;
+            }
+        };
+        result.duration_ms = started.elapsed().as_millis() as u64;
+        match response.status {
+            Some(405) => {
+                result.success = true;
+                return result;
+            }
+            Some(200) => {}
+            status => {
+                result.error = Some(format!(
+                    "GET stream returned HTTP {}",
+                    status.map_or_else(|| "(none)".to_string(), |status| status.to_string())
+                ));
+                return result;
+            }
+        }
+        let content_type = response.header("content-type").unwrap_or_default();
+        if !content_type.starts_with("text/event-stream") {
+            result.malformed.push(MalformedNotification {
+                data: response.body.clone(),
+                reason: format!("GET returned {content_type} instead of text/event-stream"),
+            });
+            return result;
+        }
+
+        result.stream_offered = true;
+        for SseEvent { data, .. } in parse_event_stream(&response.body) {
+            match check_message(&data, result.list_changed_advertised) {
+                Ok(method) => *result.received.entry(method).or_default() += 1,
+                Err(reason) => result
+                    .malformed
+                    .push(MalformedNotification { data, reason }),
+            }
+        }
+        result.success = result.malformed.is_empty();
+        result
+    }
+}
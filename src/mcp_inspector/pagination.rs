@@ -0,0 +1,392 @@
+//! Cursor-based pagination of `tools/list` and search results
+//!
+//! MCP servers may split `tools/list` across pages, returning a `nextCursor`
+//! until the list is exhausted. Discovery follows those cursors through
+//! [`GleanMCPInspector::follow_tools_list_cursors`]. Search tools can also
+//! return a cursor for their next page of results.
+//! [`GleanMCPInspector::test_pagination`] checks both: later pages must load,
+//! and no cursor may come back twice, since a repeated cursor means the
+//! client would page forever.
+
+use super::{GleanMCPInspector, McpTransport, ToolInfo, arguments_from_schema};
+use crate::{RunOutcome, TestQueryGenerator};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::collections::HashSet;
+use std::fmt::Write;
+
+/// `tools/list` pages followed before giving up on a server that never stops paginating
+const MAX_TOOLS_LIST_PAGES: usize = 50;
+
+/// Search result pages requested per tool; enough to see cursors advance
+const SEARCH_PAGES: usize = 3;
+
+/// Keys a search result may carry its next-page cursor under
+const CURSOR_KEYS: [&str; 5] = [
+    "nextCursor",
+    "next_cursor",
+    "cursor",
+    "nextPageToken",
+    "pageToken",
+];
+
+/// Search tool parameters that take a cursor, in order of preference
+const CURSOR_PARAMETERS: [&str; 4] = ["cursor", "pageToken", "page_token", "next_cursor"];
+
+/// Pages read while following `tools/list` cursors
+#[derive(Debug, Clone, Default)]
+pub struct CursorWalk {
+    pub pages: usize,
+    /// Why the walk stopped early; empty when the list was exhausted
+    pub problems: Vec<String>,
+}
+
+/// Pagination behaviour of `tools/list` or one search tool
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaginationCheck {
+    /// `tools/list`, or the name of the search tool
+    pub target: String,
+    pub passed: bool,
+    /// Whether the server returned a cursor at all
+    pub paginated: bool,
+    /// Pages fetched successfully
+    pub pages: usize,
+    /// Empty when passed
+    pub problems: Vec<String>,
+}
+
+/// Results of the pagination checks against one endpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaginationTestResult {
+    pub success: bool,
+    pub endpoint: String,
+    pub checks: Vec<PaginationCheck>,
+}
+
+impl PaginationTestResult {
+    /// How the checks ended, from CI's point of view
+    #[must_use]
+    pub fn outcome(&self) -> RunOutcome {
+        if self.success {
+            RunOutcome::Success
+        } else if self.checks.iter().all(|check| check.pages == 0) {
+            RunOutcome::ConnectivityFailure
+        } else {
+            RunOutcome::ValidationFailure
+        }
+    }
+
+    #[must_use]
+    pub fn format_text(&self) -> String {
+        let mut output = String::new();
+        let _ = writeln!(output, "📑 Pagination: {}", self.endpoint);
+        for check in &self.checks {
+            let status = if check.passed { "✅" } else { "❌" };
+            let pages = if check.paginated {
+                format!("{} page(s)", check.pages)
+            } else {
+                "not paginated".to_string()
+            };
+            let _ = writeln!(output, "  {status} {}: {pages}", check.target);
+            for problem in &check.problems {
+                let _ = writeln!(output, "    - {problem}");
+            }
+        }
+        output
+    }
+}
+
+/// Cursor for the next `tools/list` page, if the server returned one
+#[must_use]
+pub fn next_cursor(result: &Value) -> Option<String> {
+    result
+        .get("nextCursor")
+        .and_then(Value::as_str)
+        .filter(|cursor| !cursor.is_empty())
+        .map(ToString::to_string)
+}
+
+/// Next-page cursor in a search result, looking in `structuredContent`,
+/// `_meta`, the result itself, and JSON text content
+#[must_use]
+pub fn search_cursor(result: &Value) -> Option<String> {
+    let text_items = result
+        .get("content")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|item| item.get("text").and_then(Value::as_str))
+        .filter_map(|text| serde_json::from_str::<Value>(text).ok());
+    let candidates = [result.get("structuredContent"), result.get("_meta")]
+        .into_iter()
+        .flatten()
+        .cloned()
+        .chain(std::iter::once(result.clone()))
+        .chain(text_items);
+
+    for candidate in candidates {
+        if let Some(cursor) = CURSOR_KEYS.iter().find_map(|key| {
+            candidate
+                .get(key)
+                .and_then(Value::as_str)
+                .filter(|cursor| !cursor.is_empty())
+        }) {
+            return Some(cursor.to_string());
+        }
+    }
+    None
+}
+
+/// The parameter `schema` takes a cursor through, defaulting to `cursor`
+fn cursor_parameter(schema: Option<&Value>) -> &'static str {
+    let properties = schema
+        .and_then(|schema| schema.get("properties"))
+        .and_then(Value::as_object);
+    CURSOR_PARAMETERS
+        .into_iter()
+        .find(|parameter| properties.is_some_and(|properties| properties.contains_key(*parameter)))
+        .unwrap_or("cursor")
+}
+
+/// The JSON-RPC `result` of `body`, or why there isn't one
+pub(crate) fn rpc_result(body: &str) -> std::result::Result<Value, String> {
+    let response: Value =
+        serde_json::from_str(body).map_err(|e| format!("Response is not valid JSON: {e}"))?;
+    if let Some(error) = response.get("error") {
+        return Err(format!(
+            "Server returned an error: {}",
+            error
+                .get("message")
+                .and_then(Value::as_str)
+                .unwrap_or("unknown error")
+        ));
+    }
+    response
+        .get("result")
+        .cloned()
+        .ok_or_else(|| "Response has no result".to_string())
+}
+
+impl GleanMCPInspector {
+    /// Fetch the remaining `tools/list` pages after `response`, appending their tools to it
+    pub(crate) async fn follow_tools_list_cursors(
+        &self,
+        transport: &McpTransport,
+        endpoint_url: &str,
+        response: &mut Value,
+    ) -> CursorWalk {
+        let mut walk = CursorWalk {
+            pages: 1,
+            problems: Vec::new(),
+        };
+        let mut seen = HashSet::new();
+        let mut cursor = response.get("result").and_then(next_cursor);
+
+        while let Some(current) = cursor.take() {
+            if !seen.insert(current.clone()) {
+                walk.problems
+                    .push(format!("Cursor {current:?} was returned more than once"));
+                break;
+            }
+            if walk.pages >= MAX_TOOLS_LIST_PAGES {
+                walk.problems.push(format!(
+                    "Still paginating after {MAX_TOOLS_LIST_PAGES} pages"
+                ));
+                break;
+            }
+
+            let request = json!({
+                "jsonrpc": "2.0",
+                "id": walk.pages + 1,
+                "method": "tools/list",
+                "params": { "cursor": current }
+            });
+            let page = match transport.post_json_rpc(endpoint_url, &request).await {
+                Ok(page) if page.success => rpc_result(&page.body),
+                Ok(page) => Err(page.stderr),
+                Err(e) => Err(e.to_string()),
+            };
+            let page = match page {
+                Ok(page) => page,
+                Err(problem) => {
+                    walk.problems
+                        .push(format!("Page {} failed: {problem}", walk.pages + 1));
+                    break;
+                }
+            };
+
+            let tools = page
+                .get("tools")
+                .and_then(Value::as_array)
+                .cloned()
+                .unwrap_or_default();
+            if let Some(listed) = response
+                .get_mut("result")
+                .and_then(|result| result.get_mut("tools"))
+                .and_then(Value::as_array_mut)
+            {
+                listed.extend(tools);
+            }
+            walk.pages += 1;
+            cursor = next_cursor(&page);
+        }
+        walk
+    }
+
+    /// Check `tools/list` and every search tool on the default endpoint page correctly
+    pub async fn test_pagination(&self) -> PaginationTestResult {
+        let (list_check, tools) = self.check_tools_list_pagination().await;
+        let mut checks = vec![list_check];
+        for tool in tools.iter().filter(|tool| tool.name.contains("search")) {
+            checks.push(self.check_search_pagination(tool).await);
+        }
+
+        PaginationTestResult {
+            success: checks.iter().all(|check| check.passed),
+            endpoint: self.server_url().to_string(),
+            checks,
+        }
+    }
+
+    /// Walk every `tools/list` page, returning the check and the tools listed
+    async fn check_tools_list_pagination(&self) -> (PaginationCheck, Vec<ToolInfo>) {
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "tools/list",
+            "params": {}
+        });
+        let first = match self
+            .transport()
+            .post_json_rpc(self.server_url(), &request)
+            .await
+        {
+            Ok(response) if response.success => {
+                serde_json::from_str::<Value>(&response.body).map_err(|e| e.to_string())
+            }
+            Ok(response) => Err(response.stderr),
+            Err(e) => Err(e.to_string()),
+        };
+
+        let (paginated, walk, tools) = match first {
+            Ok(mut response) => {
+                let paginated = response.get("result").and_then(next_cursor).is_some();
+                let walk = self
+                    .follow_tools_list_cursors(self.transport(), self.server_url(), &mut response)
+                    .await;
+                (paginated, walk, ToolInfo::from_tools_list(&response))
+            }
+            Err(problem) => (
+                false,
+                CursorWalk {
+                    pages: 0,
+                    problems: vec![format!("Page 1 failed: {problem}")],
+                },
+                Vec::new(),
+            ),
+        };
+
+        self.reporter().info(&format!(
+            "tools/list: {} page(s){}",
+            walk.pages,
+            if walk.problems.is_empty() {
+                ""
+            } else {
+                ", failed"
+            }
+        ));
+        let check = PaginationCheck {
+            target: "tools/list".to_string(),
+            passed: walk.problems.is_empty(),
+            paginated,
+            pages: walk.pages,
+            problems: walk.problems,
+        };
+        (check, tools)
+    }
+
+    async fn check_search_pagination(&self, tool: &ToolInfo) -> PaginationCheck {
+        let mut arguments = tool
+            .schema
+            .as_ref()
+            .and_then(|schema| arguments_from_schema(&tool.name, schema))
+            .unwrap_or_else(|| {
+                TestQueryGenerator::generate_arguments(
+                    &tool.name,
+                    &TestQueryGenerator::generate_test_query(&tool.name),
+                )
+            });
+        let parameter = cursor_parameter(tool.schema.as_ref());
+
+        let mut pages = 0;
+        let mut problems = Vec::new();
+        let mut seen = HashSet::new();
+        let mut sent: Option<String> = None;
+
+        while pages < SEARCH_PAGES {
+            if let (Some(cursor), Some(arguments)) = (&sent, arguments.as_object_mut()) {
+                arguments.insert(parameter.to_string(), json!(cursor));
+            }
+            let request = json!({
+                "jsonrpc": "2.0",
+                "id": pages + 1,
+                "method": "tools/call",
+                "params": { "name": tool.name, "arguments": arguments }
+            });
+            let result = match self
+                .transport()
+                .post_json_rpc(self.server_url(), &request)
+                .await
+            {
+                Ok(response) if response.success => rpc_result(&response.body),
+                Ok(response) => Err(response.stderr),
+                Err(e) => Err(e.to_string()),
+            }
+            .and_then(|result| {
+                if result.get("isError").and_then(Value::as_bool) == Some(true) {
+                    Err("Tool returned an error result".to_string())
+                } else {
+                    Ok(result)
+                }
+            });
+            let result = match result {
+                Ok(result) => result,
+                Err(problem) => {
+                    problems.push(format!("Page {} failed: {problem}", pages + 1));
+                    break;
+                }
+            };
+            pages += 1;
+
+            let Some(cursor) = search_cursor(&result) else {
+                break;
+            };
+            if sent.as_ref() == Some(&cursor) {
+                problems.push(format!(
+                    "Page {pages} echoed back the cursor it was requested with ({cursor:?})"
+                ));
+                break;
+            }
+            if !seen.insert(cursor.clone()) {
+                problems.push(format!(
+                    "Page {pages} returned cursor {cursor:?}, already seen on an earlier page"
+                ));
+                break;
+            }
+            sent = Some(cursor);
+        }
+
+        self.reporter().info(&format!(
+            "{}: {pages} page(s){}",
+            tool.name,
+            if problems.is_empty() { "" } else { ", failed" }
+        ));
+        PaginationCheck {
+            target: tool.name.clone(),
+            passed: problems.is_empty(),
+            paginated: !seen.is_empty(),
+            pages,
+            problems,
+        }
+    }
+}
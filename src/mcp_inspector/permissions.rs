@@ -0,0 +1,357 @@
+//! Permission trimming checks across two identities
+//!
+//! [`GleanMCPInspector::test_permissions`] runs the same search queries
+//! under a privileged token (e.g. an admin) and a restricted token, then
+//! diffs the documents each identity got back. Restricted results matching
+//! a pair's `forbidden` patterns are leaks and fail the check; documents
+//! only the privileged identity saw show trimming at work.
+
+use super::{GleanMCPInspector, McpTransport, rpc_result, search_results};
+use crate::{GleanMcpError, Result, RunOutcome, TestQueryGenerator};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::collections::BTreeSet;
+use std::fmt::Write;
+
+/// Restricted-only documents listed in the text report before the rest are summarized
+const MAX_LISTED: usize = 5;
+
+/// Settings for `test --permissions` (`permissions` section of the config file)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PermissionCheckConfig {
+    pub pairs: Vec<IdentityPair>,
+}
+
+/// A privileged and a restricted identity that run the same searches
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IdentityPair {
+    pub name: String,
+    /// Environment variable holding the privileged identity's token
+    pub privileged_token_env: String,
+    /// Environment variable holding the restricted identity's token
+    pub restricted_token_env: String,
+    pub queries: Vec<String>,
+    /// Case-insensitive URL or title fragments the restricted identity must never see
+    #[serde(default)]
+    pub forbidden: Vec<String>,
+    /// Search tool to call (default: `search`)
+    #[serde(default = "default_permission_tool")]
+    pub tool: String,
+}
+
+fn default_permission_tool() -> String {
+    "search".to_string()
+}
+
+/// A search result, identified by its URL or, failing that, its title
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct PermissionDocument {
+    pub title: Option<String>,
+    pub url: Option<String>,
+}
+
+impl PermissionDocument {
+    fn key(&self) -> Option<&str> {
+        self.url.as_deref().or(self.title.as_deref())
+    }
+
+    fn matches(&self, pattern: &str) -> bool {
+        let pattern = pattern.to_lowercase();
+        [&self.url, &self.title]
+            .into_iter()
+            .flatten()
+            .any(|field| field.to_lowercase().contains(&pattern))
+    }
+
+    pub(super) fn describe(&self) -> String {
+        match (&self.title, &self.url) {
+            (Some(title), Some(url)) => format!("{title} ({url})"),
+            (Some(text), None) | (None, Some(text)) => text.clone(),
+            (None, None) => "untitled result".to_string(),
+        }
+    }
+}
+
+/// How one query's results differed between the two identities
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PermissionQueryResult {
+    pub pair: String,
+    pub query: String,
+    pub passed: bool,
+    pub privileged_status: Option<u16>,
+    pub restricted_status: Option<u16>,
+    pub privileged_results: usize,
+    pub restricted_results: usize,
+    /// Documents only the privileged identity saw, i.e. trimmed for the restricted one
+    pub hidden: Vec<PermissionDocument>,
+    /// Documents only the restricted identity saw
+    pub restricted_only: Vec<PermissionDocument>,
+    /// Restricted results matching a `forbidden` pattern
+    pub leaked: Vec<PermissionDocument>,
+    /// Empty when passed
+    pub problems: Vec<String>,
+}
+
+/// Results of the permission checks against one endpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PermissionTestResult {
+    pub success: bool,
+    pub endpoint: String,
+    pub results: Vec<PermissionQueryResult>,
+}
+
+impl PermissionTestResult {
+    /// How the checks ended, from CI's point of view
+    #[must_use]
+    pub fn outcome(&self) -> RunOutcome {
+        let statuses: Vec<Option<u16>> = self
+            .results
+            .iter()
+            .flat_map(|r| [r.privileged_status, r.restricted_status])
+            .collect();
+        if self.success {
+            RunOutcome::Success
+        } else if self.results.iter().any(|r| !r.leaked.is_empty()) {
+            RunOutcome::ValidationFailure
+        } else if statuses.iter().all(Option::is_none) {
+            RunOutcome::ConnectivityFailure
+        } else if statuses.iter().any(|s| matches!(s, Some(401 | 403))) {
+            RunOutcome::AuthFailure
+        } else {
+            RunOutcome::ValidationFailure
+        }
+    }
+
+    /// Documents leaked across all queries
+    #[must_use]
+    pub fn leaked(&self) -> usize {
+        self.results.iter().map(|r| r.leaked.len()).sum()
+    }
+
+    #[must_use]
+    pub fn format_text(&self) -> String {
+        let mut output = String::new();
+        let passed = self.results.iter().filter(|r| r.passed).count();
+        let _ = writeln!(
+            output,
+            "🔐 Permission trimming: {passed}/{} queries passed ({})",
+            self.results.len(),
+            self.endpoint
+        );
+        for result in &self.results {
+            let status = if result.passed { "✅" } else { "❌" };
+            let _ = writeln!(
+                output,
+                "  {status} {} {:?}: {} privileged, {} restricted results, {} hidden from the restricted identity",
+                result.pair,
+                result.query,
+                result.privileged_results,
+                result.restricted_results,
+                result.hidden.len()
+            );
+            for document in &result.leaked {
+                let _ = writeln!(output, "    🚨 Leaked: {}", document.describe());
+            }
+            for document in result.restricted_only.iter().take(MAX_LISTED) {
+                let _ = writeln!(output, "    ❔ Restricted only: {}", document.describe());
+            }
+            if result.restricted_only.len() > MAX_LISTED {
+                let _ = writeln!(
+                    output,
+                    "    ❔ ...and {} more restricted-only results",
+                    result.restricted_only.len() - MAX_LISTED
+                );
+            }
+            for problem in &result.problems {
+                let _ = writeln!(output, "    - {problem}");
+            }
+        }
+        output
+    }
+}
+
+/// Documents in a search response, deduplicated and sorted
+pub(super) fn documents(result: &Value) -> BTreeSet<PermissionDocument> {
+    search_results(result)
+        .into_iter()
+        .map(|item| PermissionDocument {
+            title: item.title,
+            url: item.url,
+        })
+        .filter(|document| document.key().is_some())
+        .collect()
+}
+
+/// Documents in `a` whose key doesn't appear in `b`
+fn missing_from(
+    a: &BTreeSet<PermissionDocument>,
+    b: &BTreeSet<PermissionDocument>,
+) -> Vec<PermissionDocument> {
+    let keys: BTreeSet<&str> = b.iter().filter_map(PermissionDocument::key).collect();
+    a.iter()
+        .filter(|document| document.key().is_some_and(|key| !keys.contains(key)))
+        .cloned()
+        .collect()
+}
+
+fn token_from_env(pair: &IdentityPair, variable: &str) -> Result<String> {
+    std::env::var(variable)
+        .ok()
+        .filter(|token| !token.trim().is_empty())
+        .ok_or_else(|| {
+            GleanMcpError::Auth(format!(
+                "{variable} is not set (identity pair '{}')",
+                pair.name
+            ))
+        })
+}
+
+impl GleanMCPInspector {
+    /// Run every pair's queries under both identities and diff the results
+    ///
+    /// Fails before sending anything when no pairs are configured or a
+    /// pair's token variable is unset.
+    pub async fn test_permissions(
+        &self,
+        config: &PermissionCheckConfig,
+    ) -> Result<PermissionTestResult> {
+        if config.pairs.is_empty() {
+            return Err(GleanMcpError::Config(
+                "No identity pairs configured (permissions.pairs)".to_string(),
+            ));
+        }
+        let mut identities = Vec::with_capacity(config.pairs.len());
+        for pair in &config.pairs {
+            let privileged = token_from_env(pair, &pair.privileged_token_env)?;
+            let restricted = token_from_env(pair, &pair.restricted_token_env)?;
+            identities.push((
+                pair,
+                self.transport().clone().with_auth_token(&privileged),
+                self.transport().clone().with_auth_token(&restricted),
+            ));
+        }
+
+        let mut results = Vec::new();
+        for (pair, privileged, restricted) in identities {
+            for query in &pair.queries {
+                results.push(
+                    self.compare_identities(pair, query, &privileged, &restricted)
+                        .await,
+                );
+            }
+        }
+
+        Ok(PermissionTestResult {
+            success: results.iter().all(|r| r.passed),
+            endpoint: self.server_url().to_string(),
+            results,
+        })
+    }
+
+    async fn compare_identities(
+        &self,
+        pair: &IdentityPair,
+        query: &str,
+        privileged: &McpTransport,
+        restricted: &McpTransport,
+    ) -> PermissionQueryResult {
+        let ((privileged_status, privileged_result), (restricted_status, restricted_result)) =
+            smol::future::zip(
+                self.search_as(privileged, &pair.tool, query),
+                self.search_as(restricted, &pair.tool, query),
+            )
+            .await;
+
+        let mut problems = Vec::new();
+        let privileged_docs = match privileged_result {
+            Ok(result) => documents(&result),
+            Err(problem) => {
+                problems.push(format!("Privileged search failed: {problem}"));
+                BTreeSet::new()
+            }
+        };
+        let restricted_docs = match restricted_result {
+            Ok(result) => documents(&result),
+            Err(problem) => {
+                problems.push(format!("Restricted search failed: {problem}"));
+                BTreeSet::new()
+            }
+        };
+
+        let leaked: Vec<PermissionDocument> = restricted_docs
+            .iter()
+            .filter(|document| {
+                pair.forbidden
+                    .iter()
+                    .any(|pattern| document.matches(pattern))
+            })
+            .cloned()
+            .collect();
+        if !leaked.is_empty() {
+            problems.push(format!(
+                "{} forbidden result(s) returned to the restricted identity",
+                leaked.len()
+            ));
+        }
+        if problems.is_empty() && privileged_docs.is_empty() {
+            problems.push(
+                "Privileged identity got no results, so there was nothing to compare".to_string(),
+            );
+        }
+
+        self.reporter().info(&format!(
+            "{} {query:?}: {}",
+            pair.name,
+            if problems.is_empty() { "ok" } else { "failed" }
+        ));
+        PermissionQueryResult {
+            pair: pair.name.clone(),
+            query: query.to_string(),
+            passed: problems.is_empty(),
+            privileged_status,
+            restricted_status,
+            privileged_results: privileged_docs.len(),
+            restricted_results: restricted_docs.len(),
+            hidden: missing_from(&privileged_docs, &restricted_docs),
+            restricted_only: missing_from(&restricted_docs, &privileged_docs),
+            leaked,
+            problems,
+        }
+    }
+
+    /// Call `tool` with `query` through `transport`, returning the HTTP status and tool result
+    pub(super) async fn search_as(
+        &self,
+        transport: &McpTransport,
+        tool: &str,
+        query: &str,
+    ) -> (Option<u16>, std::result::Result<Value, String>) {
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "tools/call",
+            "params": {
+                "name": tool,
+                "arguments": TestQueryGenerator::generate_arguments(tool, query)
+            }
+        });
+        match transport.post_json_rpc(self.server_url(), &request).await {
+            Ok(response) if response.success => {
+                if let Some(status) = response.status.filter(|status| *status >= 400) {
+                    return (Some(status), Err(format!("HTTP {status}")));
+                }
+                let result = rpc_result(&response.body).and_then(|result| {
+                    if result.get("isError").and_then(Value::as_bool) == Some(true) {
+                        Err("Tool returned an error result".to_string())
+                    } else {
+                        Ok(result)
+                    }
+                });
+                (response.status, result)
+            }
+            Ok(response) => (None, Err(response.stderr)),
+            Err(e) => (None, Err(e.to_string())),
+        }
+    }
+}
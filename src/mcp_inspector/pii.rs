@@ -0,0 +1,216 @@
+//! Sensitive data scanning of tool responses
+//!
+//! With `pii_scan.enabled` (or `--scan-pii`), every string in a successful
+//! response is matched against the built-in detectors and the configured
+//! `patterns`. Social security and card numbers are checked beyond the
+//! pattern (reserved SSN ranges, the Luhn checksum) to keep document ids and
+//! phone numbers from matching. Findings carry masked samples only, so the
+//! report never repeats what leaked.
+
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// Samples kept per detector and tool
+const MAX_SAMPLES: usize = 3;
+
+/// Trailing characters of a match left unmasked in samples
+const VISIBLE_CHARS: usize = 4;
+
+/// Built-in detectors: name and pattern
+const DETECTORS: [(&str, &str); 8] = [
+    ("ssn", r"\b\d{3}-\d{2}-\d{4}\b"),
+    ("credit_card", r"\b\d(?:[ -]?\d){12,18}\b"),
+    ("aws_access_key", r"\b(?:AKIA|ASIA)[0-9A-Z]{16}\b"),
+    ("private_key", r"-----BEGIN (?:[A-Z]+ )?PRIVATE KEY-----"),
+    ("github_token", r"\bgh[pousr]_[A-Za-z0-9]{36,}\b"),
+    ("slack_token", r"\bxox[abprs]-[A-Za-z0-9-]{10,}"),
+    (
+        "jwt",
+        r"\beyJ[A-Za-z0-9_-]{10,}\.[A-Za-z0-9_-]{10,}\.[A-Za-z0-9_-]{10,}",
+    ),
+    (
+        "secret_assignment",
+        r#"(?i)\b(?:api[_-]?key|secret|password|passwd|access[_-]?token)\s*[:=]\s*["']?[A-Za-z0-9/+_\-]{12,}"#,
+    ),
+];
+
+/// Names of the built-in detectors
+#[must_use]
+pub fn pii_detectors() -> Vec<String> {
+    DETECTORS
+        .iter()
+        .map(|(name, _)| (*name).to_string())
+        .collect()
+}
+
+/// Settings for the sensitive data scan (`pii_scan` section of the config file)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PiiScanConfig {
+    pub enabled: bool,
+    /// Tools whose responses are scanned; empty scans every tool
+    pub tools: Vec<String>,
+    /// Built-in detectors to run
+    pub detectors: Vec<String>,
+    /// Extra detectors: name and regular expression
+    pub patterns: BTreeMap<String, String>,
+    /// Fail tools with findings; otherwise findings are only reported
+    pub fail_on_findings: bool,
+}
+
+impl Default for PiiScanConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            tools: Vec::new(),
+            detectors: pii_detectors(),
+            patterns: BTreeMap::new(),
+            fail_on_findings: true,
+        }
+    }
+}
+
+/// Matches of one detector in a tool's response
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct PiiFinding {
+    pub detector: String,
+    pub count: usize,
+    /// Up to three matches, masked but for their last characters
+    pub samples: Vec<String>,
+}
+
+impl PiiFinding {
+    /// e.g. `ssn ×2 (***-**-6789)`
+    #[must_use]
+    pub fn describe(&self) -> String {
+        format!(
+            "{} ×{} ({})",
+            self.detector,
+            self.count,
+            self.samples.join(", ")
+        )
+    }
+}
+
+/// Whether `ssn` is outside the ranges never issued (area 000, 666, or 9xx; group 00; serial 0000)
+fn plausible_ssn(ssn: &str) -> bool {
+    let mut parts = ssn.split('-');
+    let (Some(area), Some(group), Some(serial)) = (parts.next(), parts.next(), parts.next()) else {
+        return false;
+    };
+    area != "000" && area != "666" && !area.starts_with('9') && group != "00" && serial != "0000"
+}
+
+/// Whether the digits of `number` pass the Luhn checksum card numbers carry
+fn luhn_valid(number: &str) -> bool {
+    let digits: Vec<u32> = number.chars().filter_map(|c| c.to_digit(10)).collect();
+    if !(13..=19).contains(&digits.len()) || digits.iter().all(|digit| *digit == digits[0]) {
+        return false;
+    }
+    let sum: u32 = digits
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(index, digit)| {
+            if index % 2 == 1 {
+                let doubled = digit * 2;
+                if doubled > 9 { doubled - 9 } else { doubled }
+            } else {
+                *digit
+            }
+        })
+        .sum();
+    sum.is_multiple_of(10)
+}
+
+/// `text` with every letter and digit but the last few replaced by `*`
+fn mask(text: &str) -> String {
+    let total = text.chars().filter(char::is_ascii_alphanumeric).count();
+    let mut seen = 0;
+    let masked: String = text
+        .chars()
+        .map(|c| {
+            if !c.is_ascii_alphanumeric() {
+                return c;
+            }
+            seen += 1;
+            if seen + VISIBLE_CHARS > total { c } else { '*' }
+        })
+        .collect();
+    if masked.chars().count() > 24 {
+        let tail: String = masked.chars().skip(masked.chars().count() - 12).collect();
+        format!("…{tail}")
+    } else {
+        masked
+    }
+}
+
+/// Every string in `value`, depth first
+fn strings(value: &Value, found: &mut Vec<String>) {
+    match value {
+        Value::String(text) => {
+            // Text blocks often hold JSON; scan its strings rather than the escaped form
+            match serde_json::from_str::<Value>(text) {
+                Ok(inner @ (Value::Object(_) | Value::Array(_))) => strings(&inner, found),
+                _ => found.push(text.clone()),
+            }
+        }
+        Value::Array(items) => items.iter().for_each(|item| strings(item, found)),
+        Value::Object(fields) => fields.values().for_each(|field| strings(field, found)),
+        _ => {}
+    }
+}
+
+impl PiiScanConfig {
+    /// Built-in and configured detectors to run; invalid patterns are skipped
+    fn compiled(&self) -> Vec<(String, Regex)> {
+        DETECTORS
+            .iter()
+            .filter(|(name, _)| self.detectors.iter().any(|detector| detector == name))
+            .map(|(name, pattern)| ((*name).to_string(), (*pattern).to_string()))
+            .chain(self.patterns.clone())
+            .filter_map(|(name, pattern)| Regex::new(&pattern).ok().map(|regex| (name, regex)))
+            .collect()
+    }
+
+    /// Findings in `response` from `tool_name`, empty when disabled or the tool isn't covered
+    #[must_use]
+    pub fn scan(&self, tool_name: &str, response: &Value) -> Vec<PiiFinding> {
+        if !self.enabled || !(self.tools.is_empty() || self.tools.iter().any(|t| t == tool_name)) {
+            return Vec::new();
+        }
+        let mut texts = Vec::new();
+        strings(response, &mut texts);
+        let mut findings = Vec::new();
+        for (name, regex) in self.compiled() {
+            let matches: Vec<&str> = texts
+                .iter()
+                .flat_map(|text| regex.find_iter(text))
+                .map(|found| found.as_str())
+                .filter(|found| match name.as_str() {
+                    "ssn" => plausible_ssn(found),
+                    "credit_card" => luhn_valid(found),
+                    _ => true,
+                })
+                .collect();
+            if matches.is_empty() {
+                continue;
+            }
+            let mut samples: Vec<String> = Vec::new();
+            for sample in matches.iter().map(|found| mask(found)) {
+                if samples.len() < MAX_SAMPLES && !samples.contains(&sample) {
+                    samples.push(sample);
+                }
+            }
+            findings.push(PiiFinding {
+                detector: name,
+                count: matches.len(),
+                samples,
+            });
+        }
+        findings
+    }
+}
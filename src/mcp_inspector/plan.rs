@@ -0,0 +1,280 @@
+//! Dry-run plans for `test`
+//!
+//! [`GleanMCPInspector::plan_test_all`] applies the same tool filter and
+//! argument selection as a real run to the tools each endpoint is expected
+//! to expose, without sending anything. The real run tests whatever the
+//! server advertises, so a plan is exact for explicit `--tools` lists and an
+//! expectation for `core`, `enterprise`, and `all`.
+
+use super::{GleanMCPInspector, TestAllOptions, TestQueryGenerator, ToolInfo, known_tools};
+use crate::ToolOutcome;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::time::Duration;
+
+/// Assumed latency of a tool call with no recorded history
+const DEFAULT_CALL_ESTIMATE_MS: u64 = 2000;
+
+/// One tool call a run would make
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlannedTool {
+    pub name: String,
+    pub category: String,
+    pub query: String,
+    pub arguments: Value,
+    pub estimated_ms: u64,
+    /// Whether `estimated_ms` is the tool's mean latency in recorded history
+    pub from_history: bool,
+}
+
+/// The tools a run would call on one endpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EndpointPlan {
+    /// `default` or `chatgpt`
+    pub endpoint: String,
+    pub url: String,
+    pub tools: Vec<PlannedTool>,
+}
+
+/// Everything `test` would exercise, with its expected duration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TestPlan {
+    pub tools_filter: String,
+    pub endpoints: Vec<EndpointPlan>,
+    pub parallel: bool,
+    pub max_concurrent: usize,
+    pub timeout_secs: u64,
+    pub retry_attempts: u32,
+    pub max_rps: Option<f64>,
+    /// Expected wall time from recorded latencies, or a default per call
+    pub estimated_ms: u64,
+    /// Wall time if every call timed out on every attempt
+    pub worst_case_ms: u64,
+}
+
+impl TestPlan {
+    /// Estimate each tool from its mean latency across `outcomes` of passing runs
+    ///
+    /// Outcomes are keyed like run results, e.g. `search (default)`.
+    #[must_use]
+    pub fn with_history(mut self, outcomes: &[ToolOutcome]) -> Self {
+        let mut latencies: BTreeMap<&str, (u64, u64)> = BTreeMap::new();
+        for outcome in outcomes.iter().filter(|outcome| outcome.success) {
+            let entry = latencies.entry(outcome.tool.as_str()).or_default();
+            entry.0 += outcome.response_time_ms;
+            entry.1 += 1;
+        }
+        for endpoint in &mut self.endpoints {
+            for tool in &mut endpoint.tools {
+                let key = format!("{} ({})", tool.name, endpoint.endpoint);
+                if let Some((total, count)) = latencies.get(key.as_str()) {
+                    tool.estimated_ms = total / count;
+                    tool.from_history = true;
+                }
+            }
+        }
+        self.estimated_ms = self.wall_time(|tool| tool.estimated_ms);
+        self
+    }
+
+    /// Planned tool calls across both endpoints
+    #[must_use]
+    pub fn calls(&self) -> usize {
+        self.endpoints
+            .iter()
+            .map(|endpoint| endpoint.tools.len())
+            .sum()
+    }
+
+    /// Endpoints run one after another; tools within one share the concurrency limit and rate cap
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss,
+        clippy::cast_precision_loss
+    )]
+    fn wall_time(&self, duration_ms: impl Fn(&PlannedTool) -> u64) -> u64 {
+        self.endpoints
+            .iter()
+            .map(|endpoint| {
+                let durations: Vec<u64> = endpoint.tools.iter().map(&duration_ms).collect();
+                let total: u64 = durations.iter().sum();
+                let wall = if self.parallel {
+                    let slots = self.max_concurrent.max(1) as u64;
+                    total
+                        .div_ceil(slots)
+                        .max(durations.iter().copied().max().unwrap_or_default())
+                } else {
+                    total
+                };
+                let paced = self.max_rps.filter(|rps| *rps > 0.0).map_or(0, |rps| {
+                    (durations.len() as f64 / rps * 1000.0).ceil() as u64
+                });
+                wall.max(paced)
+            })
+            .sum()
+    }
+
+    #[must_use]
+    pub fn format_text(&self) -> String {
+        let mut output = String::new();
+        let _ = writeln!(
+            output,
+            "📝 Dry run: {} tool call(s) on {} endpoint(s), {} ({})",
+            self.calls(),
+            self.endpoints.len(),
+            if self.parallel {
+                format!("parallel, up to {} at once", self.max_concurrent)
+            } else {
+                "sequential".to_string()
+            },
+            self.tools_filter
+        );
+        for endpoint in &self.endpoints {
+            let _ = writeln!(output, "  🌐 {} ({})", endpoint.endpoint, endpoint.url);
+            if endpoint.tools.is_empty() {
+                let _ = writeln!(output, "    (no matching tools)");
+            }
+            for tool in &endpoint.tools {
+                let _ = writeln!(
+                    output,
+                    "    - {} [{}]: {:?} (~{}{})",
+                    tool.name,
+                    tool.category,
+                    tool.query,
+                    format_estimate(tool.estimated_ms),
+                    if tool.from_history {
+                        ""
+                    } else {
+                        ", no history"
+                    }
+                );
+            }
+        }
+        let _ = writeln!(
+            output,
+            "⏱️  Estimated duration: ~{:.0}s (up to {:.0}s if every call times out after {} attempt(s))",
+            Duration::from_millis(self.estimated_ms).as_secs_f64(),
+            Duration::from_millis(self.worst_case_ms).as_secs_f64(),
+            self.retry_attempts
+        );
+        if matches!(
+            self.tools_filter.as_str(),
+            "all" | "core" | "enterprise" | "agents"
+        ) {
+            let _ = writeln!(
+                output,
+                "💡 Tools are matched against what each endpoint advertises at run time; this lists the expected ones"
+            );
+        }
+        output
+    }
+}
+
+/// `850ms` below a second, `2.4s` above
+fn format_estimate(ms: u64) -> String {
+    if ms < 1000 {
+        format!("{ms}ms")
+    } else {
+        format!("{:.1}s", Duration::from_millis(ms).as_secs_f64())
+    }
+}
+
+impl GleanMCPInspector {
+    /// Plan a `test_all_tools` run without contacting the server
+    ///
+    /// Each endpoint is expected to expose its `expected_tools`; without them
+    /// the default endpoint exposes the tool profile's tools (plus any named in
+    /// `--tools`), the `ChatGPT` endpoint exactly `chatgpt_tools`, and other
+    /// endpoints nothing known.
+    #[must_use]
+    pub fn plan_test_all(&self, options: &TestAllOptions, chatgpt_tools: &[String]) -> TestPlan {
+        let mut default_tools: Vec<String> = known_tools()
+            .into_iter()
+            .filter(|tool| TestQueryGenerator::get_tool_category(tool) != "unknown")
+            .collect();
+        if !matches!(
+            options.tools_filter.as_str(),
+            "all" | "core" | "enterprise" | "agents"
+        ) {
+            for tool in options.tools_filter.split(',').map(str::trim) {
+                let is_glob = tool.contains(['*', '?']);
+                if !tool.is_empty() && !is_glob && !default_tools.iter().any(|known| known == tool)
+                {
+                    default_tools.push(tool.to_string());
+                }
+            }
+        }
+
+        let endpoints = options
+            .endpoints
+            .iter()
+            .map(|endpoint| {
+                let tools = match endpoint.name.as_str() {
+                    _ if !endpoint.expected_tools.is_empty() => endpoint.expected_tools.as_slice(),
+                    "default" => default_tools.as_slice(),
+                    "chatgpt" => chatgpt_tools,
+                    _ => &[],
+                };
+                (
+                    endpoint.name.as_str(),
+                    self.url_for_endpoint(endpoint),
+                    tools,
+                )
+            })
+            .map(|(endpoint, url, tools)| {
+                let available: Vec<ToolInfo> = tools
+                    .iter()
+                    .map(|name| ToolInfo {
+                        name: name.clone(),
+                        description: None,
+                        schema: None,
+                    })
+                    .collect();
+                let tools = Self::filter_tools(&available, options)
+                    .iter()
+                    .flat_map(|tool| {
+                        options
+                            .test_cases(&tool.name)
+                            .into_iter()
+                            .map(move |(name, query)| {
+                                let (arguments, query) =
+                                    options.arguments_for_query(tool, query.as_deref());
+                                PlannedTool {
+                                    name,
+                                    category: TestQueryGenerator::get_tool_category(&tool.name)
+                                        .to_string(),
+                                    query,
+                                    arguments,
+                                    estimated_ms: DEFAULT_CALL_ESTIMATE_MS,
+                                    from_history: false,
+                                }
+                            })
+                    })
+                    .collect();
+                EndpointPlan {
+                    endpoint: endpoint.to_string(),
+                    url,
+                    tools,
+                }
+            })
+            .collect();
+
+        let mut plan = TestPlan {
+            tools_filter: options.tools_filter.clone(),
+            endpoints,
+            parallel: options.parallel,
+            max_concurrent: options.max_concurrent,
+            timeout_secs: options.timeout,
+            retry_attempts: options.retry_attempts,
+            max_rps: options.max_rps,
+            estimated_ms: 0,
+            worst_case_ms: 0,
+        };
+        let attempts = u64::from(options.retry_attempts.max(1));
+        plan.estimated_ms = plan.wall_time(|tool| tool.estimated_ms);
+        plan.worst_case_ms = plan.wall_time(|_| options.timeout * 1000 * attempts);
+        plan
+    }
+}
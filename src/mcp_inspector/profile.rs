@@ -0,0 +1,219 @@
+//! Tool profiles: what the framework knows about a server's tools
+//!
+//! Out of the box the harness knows Glean's tools: their categories, test
+//! queries, and argument names. Other MCP servers are described in the
+//! `profile` section instead, tool by tool: a test query or argument
+//! template, a category and tags, and what a response must contain. With
+//! `base: generic` the Glean tools are left out entirely, so `core` runs
+//! only the tools defined there. Once [`configure_tool_profile`] has run,
+//! [`TestQueryGenerator`](super::TestQueryGenerator) consults the profile
+//! before its built-in knowledge.
+
+use super::{KNOWN_TOOLS, response_text};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
+
+/// Categories `--tools` selects tools by
+pub const TOOL_CATEGORIES: [&str; 3] = ["core", "enterprise", "agents"];
+
+static PROFILE: OnceLock<ToolProfileConfig> = OnceLock::new();
+
+/// Which built-in tool knowledge a profile starts from
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProfileBase {
+    /// Glean's tools, extended by the defined ones
+    #[default]
+    Glean,
+    /// Only the defined tools
+    Generic,
+}
+
+/// What a successful response must (and must not) contain
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ResponseExpectation {
+    /// Text the response must contain, ignoring case
+    pub contains: Vec<String>,
+    /// Text the response must not contain, ignoring case
+    pub not_contains: Vec<String>,
+    /// Regular expressions the response must match
+    pub matches: Vec<String>,
+    /// Fewest characters of text the response may carry
+    pub min_chars: usize,
+}
+
+impl ResponseExpectation {
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.contains.is_empty()
+            && self.not_contains.is_empty()
+            && self.matches.is_empty()
+            && self.min_chars == 0
+    }
+
+    /// Check `response` against the expectation
+    ///
+    /// Returns `Ok(Some(note))` when checked, `Ok(None)` when there is
+    /// nothing to check, and `Err` listing what was missing or unwanted.
+    pub fn check(&self, response: &Value) -> std::result::Result<Option<String>, String> {
+        if self.is_empty() {
+            return Ok(None);
+        }
+        let text = response_text(response);
+        let lowered = text.to_lowercase();
+        let mut problems = Vec::new();
+        if text.chars().count() < self.min_chars {
+            problems.push(format!(
+                "{} characters, expected at least {}",
+                text.chars().count(),
+                self.min_chars
+            ));
+        }
+        problems.extend(
+            self.contains
+                .iter()
+                .filter(|expected| !lowered.contains(&expected.to_lowercase()))
+                .map(|expected| format!("missing {expected:?}")),
+        );
+        problems.extend(
+            self.not_contains
+                .iter()
+                .filter(|unwanted| lowered.contains(&unwanted.to_lowercase()))
+                .map(|unwanted| format!("contains {unwanted:?}")),
+        );
+        problems.extend(
+            self.matches
+                .iter()
+                .filter(|pattern| Regex::new(pattern).is_ok_and(|regex| !regex.is_match(&text)))
+                .map(|pattern| format!("no match for /{pattern}/")),
+        );
+        if problems.is_empty() {
+            Ok(Some("Response met its expectations".to_string()))
+        } else {
+            Err(format!("Unexpected response: {}", problems.join(", ")))
+        }
+    }
+}
+
+/// One tool of the server under test
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ToolDefinition {
+    /// `core`, `enterprise`, or `agents`
+    pub category: String,
+    pub description: Option<String>,
+    /// Query passed as the tool's string parameter
+    pub query: Option<String>,
+    /// `tools/call` arguments, with `{{tool.path}}` templates; replaces `query`
+    pub arguments: Option<Value>,
+    pub tags: Vec<String>,
+    pub expect: ResponseExpectation,
+}
+
+impl Default for ToolDefinition {
+    fn default() -> Self {
+        Self {
+            category: "core".to_string(),
+            description: None,
+            query: None,
+            arguments: None,
+            tags: Vec::new(),
+            expect: ResponseExpectation::default(),
+        }
+    }
+}
+
+/// Tools of the server under test (`profile` section of the config file)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ToolProfileConfig {
+    pub base: ProfileBase,
+    /// Tools by name
+    pub tools: BTreeMap<String, ToolDefinition>,
+}
+
+impl ToolProfileConfig {
+    #[must_use]
+    pub fn is_generic(&self) -> bool {
+        self.base == ProfileBase::Generic
+    }
+
+    /// The base's tools, then the defined ones
+    #[must_use]
+    pub fn tool_names(&self) -> Vec<String> {
+        let builtin = match self.base {
+            ProfileBase::Glean => &KNOWN_TOOLS[..],
+            ProfileBase::Generic => &[],
+        };
+        let mut names: Vec<String> = builtin.iter().map(ToString::to_string).collect();
+        for name in self.tools.keys() {
+            if !names.contains(name) {
+                names.push(name.clone());
+            }
+        }
+        names
+    }
+
+    /// Defined tools in `category`
+    #[must_use]
+    pub fn tools_in(&self, category: &str) -> Vec<String> {
+        self.tools
+            .iter()
+            .filter(|(_, tool)| tool.category == category)
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
+
+    /// Argument templates of the defined tools that have them
+    #[must_use]
+    pub fn arguments(&self) -> BTreeMap<String, Value> {
+        self.tools
+            .iter()
+            .filter_map(|(name, tool)| Some((name.clone(), tool.arguments.clone()?)))
+            .collect()
+    }
+
+    /// Tags of the defined tools that have them
+    #[must_use]
+    pub fn tags(&self) -> BTreeMap<String, Vec<String>> {
+        self.tools
+            .iter()
+            .filter(|(_, tool)| !tool.tags.is_empty())
+            .map(|(name, tool)| (name.clone(), tool.tags.clone()))
+            .collect()
+    }
+
+    /// Response expectations of the defined tools that have them
+    #[must_use]
+    pub fn expectations(&self) -> BTreeMap<String, ResponseExpectation> {
+        self.tools
+            .iter()
+            .filter(|(_, tool)| !tool.expect.is_empty())
+            .map(|(name, tool)| (name.clone(), tool.expect.clone()))
+            .collect()
+    }
+}
+
+/// Describe the server's tools with `config` for the rest of the process
+pub fn configure_tool_profile(config: ToolProfileConfig) {
+    let _ = PROFILE.set(config);
+}
+
+/// The configured profile; the Glean profile until [`configure_tool_profile`] runs
+#[must_use]
+pub fn tool_profile() -> &'static ToolProfileConfig {
+    static GLEAN: OnceLock<ToolProfileConfig> = OnceLock::new();
+    PROFILE
+        .get()
+        .unwrap_or_else(|| GLEAN.get_or_init(ToolProfileConfig::default))
+}
+
+/// Tools the framework knows how to query under the configured profile
+#[must_use]
+pub fn known_tools() -> Vec<String> {
+    tool_profile().tool_names()
+}
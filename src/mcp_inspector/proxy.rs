@@ -0,0 +1,117 @@
+//! Outbound proxy for MCP requests
+//!
+//! curl only honours some proxy variables (it ignores an upper-case
+//! `HTTP_PROXY`, for one), so [`ProxyConfig`] resolves the proxy itself and
+//! passes it to every curl call explicitly. An explicit `url` wins over the
+//! environment, and a disabled config bypasses proxies altogether.
+
+use serde::{Deserialize, Serialize};
+
+/// Proxy settings (`proxy` section of the config file)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProxyConfig {
+    /// Proxy for every MCP request, e.g. `http://proxy.corp:3128`
+    /// (default: `HTTPS_PROXY`/`HTTP_PROXY`/`ALL_PROXY` for the endpoint's scheme)
+    pub url: Option<String>,
+    /// Hosts or domains reached directly (default: `NO_PROXY`)
+    pub no_proxy: Vec<String>,
+    /// Connect directly, ignoring `url` and the proxy environment variables
+    pub disabled: bool,
+}
+
+/// First non-empty environment variable among `names`
+fn env_var(names: &[&str]) -> Option<String> {
+    names
+        .iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|value| !value.trim().is_empty())
+}
+
+impl ProxyConfig {
+    /// Use `url` for every request
+    #[must_use]
+    pub fn with_url(mut self, url: &str) -> Self {
+        self.url = Some(url.to_string());
+        self.disabled = false;
+        self
+    }
+
+    /// Connect directly, whatever the config or environment says
+    #[must_use]
+    pub const fn direct(mut self) -> Self {
+        self.disabled = true;
+        self
+    }
+
+    /// Proxy for requests to `endpoint`, if any
+    #[must_use]
+    pub fn proxy_for(&self, endpoint: &str) -> Option<String> {
+        if self.disabled {
+            return None;
+        }
+        self.url.clone().or_else(|| {
+            if endpoint.starts_with("https://") {
+                env_var(&["https_proxy", "HTTPS_PROXY", "all_proxy", "ALL_PROXY"])
+            } else {
+                env_var(&["http_proxy", "HTTP_PROXY", "all_proxy", "ALL_PROXY"])
+            }
+        })
+    }
+
+    /// Hosts that bypass the proxy
+    #[must_use]
+    pub fn bypass_hosts(&self) -> Vec<String> {
+        if !self.no_proxy.is_empty() {
+            return self.no_proxy.clone();
+        }
+        env_var(&["no_proxy", "NO_PROXY"])
+            .map(|hosts| {
+                hosts
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|host| !host.is_empty())
+                    .map(ToString::to_string)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// curl arguments sending a request to `endpoint` through the resolved proxy,
+    /// or directly when there is none
+    #[must_use]
+    pub fn curl_args(&self, endpoint: &str) -> Vec<String> {
+        let Some(proxy) = self.proxy_for(endpoint) else {
+            // Also stops curl from picking up proxy variables on its own
+            return vec!["--noproxy".to_string(), "*".to_string()];
+        };
+        let mut args = vec!["--proxy".to_string(), proxy];
+        let bypass = self.bypass_hosts();
+        if !bypass.is_empty() {
+            args.extend(["--noproxy".to_string(), bypass.join(",")]);
+        }
+        args
+    }
+
+    /// Where requests to `endpoint` go, for display
+    #[must_use]
+    pub fn describe(&self, endpoint: &str) -> String {
+        self.proxy_for(endpoint)
+            .map_or_else(|| "direct".to_string(), |proxy| redact_credentials(&proxy))
+    }
+}
+
+/// `proxy` with any `user:password@` replaced by `***@`
+fn redact_credentials(proxy: &str) -> String {
+    let (scheme, rest) = proxy
+        .split_once("://")
+        .map_or(("", proxy), |(scheme, rest)| (scheme, rest));
+    let Some((_, host)) = rest.rsplit_once('@') else {
+        return proxy.to_string();
+    };
+    if scheme.is_empty() {
+        format!("***@{host}")
+    } else {
+        format!("{scheme}://***@{host}")
+    }
+}
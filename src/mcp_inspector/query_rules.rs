@@ -0,0 +1,109 @@
+//! Test queries for tools the generator doesn't know
+//!
+//! Known tools have hand-picked test queries. Any other tool is matched
+//! against [`QueryRule`]s: each rule's regex is tried, case-insensitively,
+//! against the tool's name, description, and parameter names and
+//! descriptions, and the first match supplies the query. Configured rules
+//! (`tools_to_test.query_rules`) are tried before the built-in ones, which
+//! cover common shapes like email, people, code, and URL lookups.
+
+use super::ToolInfo;
+use regex::RegexBuilder;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Built-in `(pattern, query)` rules, tried after the configured ones
+const DEFAULT_QUERY_RULES: [(&str, &str); 8] = [
+    (
+        r"\b(e-?mails?|g?mail|inbox|outlook)\b",
+        "from:noreply@glean.com",
+    ),
+    (
+        r"\b(urls?|links?|web ?pages?|websites?|browse)\b",
+        "https://www.glean.com",
+    ),
+    (r"\b(calendar|meetings?|events?)\b", "weekly standup"),
+    (
+        r"\b(people|person|employees?|colleagues?|directory)\b",
+        "engineering team",
+    ),
+    (
+        r"\b(code|repositor(y|ies)|source files?)\b",
+        "function authenticate",
+    ),
+    (r"\b(tickets?|issues?|bugs?|jira)\b", "login bug"),
+    (
+        r"\b(slack|channels?|chat messages|conversations?)\b",
+        "project update",
+    ),
+    (
+        r"\b(documents?|docs?|files?|wiki|pages?)\b",
+        "onboarding guide",
+    ),
+];
+
+/// A pattern matched against a tool's description, and the query for matching tools
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QueryRule {
+    /// Regular expression, matched case-insensitively
+    pub pattern: String,
+    /// Query for matching tools; `{tool}` is replaced with the tool name
+    pub query: String,
+}
+
+impl QueryRule {
+    /// Whether the rule's pattern is found in `text`; invalid patterns match nothing
+    fn matches(&self, text: &str) -> bool {
+        RegexBuilder::new(&self.pattern)
+            .case_insensitive(true)
+            .build()
+            .is_ok_and(|regex| regex.is_match(text))
+    }
+
+    fn query_for(&self, tool_name: &str) -> String {
+        self.query.replace("{tool}", tool_name)
+    }
+}
+
+/// The built-in rules, tried after any configured ones
+#[must_use]
+pub fn default_query_rules() -> Vec<QueryRule> {
+    DEFAULT_QUERY_RULES
+        .iter()
+        .map(|(pattern, query)| QueryRule {
+            pattern: (*pattern).to_string(),
+            query: (*query).to_string(),
+        })
+        .collect()
+}
+
+/// What rules are matched against: the tool's name (with `_` as spaces),
+/// description, and parameter names and descriptions, one per line
+fn describe_tool(tool: &ToolInfo) -> String {
+    let mut lines = vec![tool.name.replace(['_', '-'], " ")];
+    lines.extend(tool.description.clone());
+    let properties = tool
+        .schema
+        .as_ref()
+        .and_then(|schema| schema.get("properties"))
+        .and_then(Value::as_object);
+    for (name, property) in properties.into_iter().flatten() {
+        lines.push(name.replace(['_', '-'], " "));
+        if let Some(description) = property.get("description").and_then(Value::as_str) {
+            lines.push(description.to_string());
+        }
+    }
+    lines.join("\n")
+}
+
+/// Query from the first of `rules`, then the built-in rules, matching `tool`
+#[must_use]
+pub fn query_from_rules(tool: &ToolInfo, rules: &[QueryRule]) -> Option<String> {
+    let text = describe_tool(tool);
+    rules
+        .iter()
+        .cloned()
+        .chain(default_query_rules())
+        .find(|rule| rule.matches(&text))
+        .map(|rule| rule.query_for(&tool.name))
+}
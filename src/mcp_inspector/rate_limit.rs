@@ -0,0 +1,147 @@
+//! Request pacing for MCP traffic
+//!
+//! [`RateLimiter`] is a token bucket shared by every request in a run. It
+//! paces requests to `--max-rps` and adapts to the server: each 429 halves
+//! the current rate (engaging pacing at [`FALLBACK_RPS`] when no limit was
+//! configured), and each successful response lets it recover by 10% until it
+//! is back at the configured limit.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Rate adopted on the first 429 when no `--max-rps` was given
+pub const FALLBACK_RPS: f64 = 2.0;
+
+/// Slowest pace the limiter backs off to
+const MIN_RPS: f64 = 0.1;
+
+/// Growth factor applied to the rate after each successful response
+const RECOVERY_FACTOR: f64 = 1.1;
+
+/// What the limiter did over a run, reported in the execution summary
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct RateLimitStats {
+    /// Configured requests-per-second limit
+    pub max_rps: Option<f64>,
+    /// Rate in effect at the end of the run
+    pub final_rps: Option<f64>,
+    /// Slowest rate reached after backing off
+    pub min_rps: Option<f64>,
+    /// Responses with HTTP 429
+    pub rate_limited_responses: usize,
+    /// Requests that had to wait for a token
+    pub paced_requests: usize,
+    /// Total time requests spent waiting for tokens
+    pub total_wait_ms: u64,
+}
+
+#[derive(Debug)]
+struct BucketState {
+    /// Current rate; `None` means unlimited
+    rate: Option<f64>,
+    /// Tokens available; negative values are waits already promised to callers
+    tokens: f64,
+    last_refill: Instant,
+    stats: RateLimitStats,
+}
+
+/// Shared token bucket; clones pace against the same budget
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    max_rps: Option<f64>,
+    state: Arc<Mutex<BucketState>>,
+}
+
+impl RateLimiter {
+    /// A limiter allowing `max_rps` requests per second, or unlimited until a 429 when `None`
+    #[must_use]
+    pub fn new(max_rps: Option<f64>) -> Self {
+        let max_rps = max_rps.filter(|rps| *rps > 0.0);
+        Self {
+            max_rps,
+            state: Arc::new(Mutex::new(BucketState {
+                rate: max_rps,
+                tokens: max_rps.map_or(0.0, |rps| rps.max(1.0)),
+                last_refill: Instant::now(),
+                stats: RateLimitStats {
+                    max_rps,
+                    final_rps: max_rps,
+                    min_rps: max_rps,
+                    ..RateLimitStats::default()
+                },
+            })),
+        }
+    }
+
+    /// Wait until the next request may start
+    pub async fn acquire(&self) {
+        let wait = {
+            let Ok(mut state) = self.state.lock() else {
+                return;
+            };
+            let Some(rate) = state.rate else {
+                return;
+            };
+
+            // Refill, allowing up to one second's worth of burst
+            let now = Instant::now();
+            let elapsed = now.duration_since(state.last_refill).as_secs_f64();
+            state.tokens = elapsed.mul_add(rate, state.tokens).min(rate.max(1.0));
+            state.last_refill = now;
+            state.tokens -= 1.0;
+
+            if state.tokens >= 0.0 {
+                return;
+            }
+            let wait = Duration::from_secs_f64(-state.tokens / rate);
+            state.stats.paced_requests += 1;
+            state.stats.total_wait_ms += u64::try_from(wait.as_millis()).unwrap_or(u64::MAX);
+            wait
+        };
+        smol::Timer::after(wait).await;
+    }
+
+    /// Adjust the pace to a response's HTTP status
+    pub fn record_status(&self, status: Option<u16>) {
+        let Ok(mut state) = self.state.lock() else {
+            return;
+        };
+
+        if status == Some(429) {
+            let rate = state
+                .rate
+                .map_or(FALLBACK_RPS, |rate| rate / 2.0)
+                .max(MIN_RPS);
+            state.rate = Some(rate);
+            // Drop any saved-up burst so the slower pace applies immediately
+            state.tokens = state.tokens.min(0.0);
+            state.last_refill = Instant::now();
+            state.stats.rate_limited_responses += 1;
+            state.stats.min_rps = Some(state.stats.min_rps.map_or(rate, |min| min.min(rate)));
+        } else if status.is_none_or(|status| status < 400)
+            && let Some(rate) = state.rate
+        {
+            let recovered = rate * RECOVERY_FACTOR;
+            state.rate = Some(self.max_rps.map_or(recovered, |max| recovered.min(max)));
+        }
+        state.stats.final_rps = state.rate;
+    }
+
+    /// Pacing activity so far
+    #[must_use]
+    pub fn stats(&self) -> RateLimitStats {
+        self.state
+            .lock()
+            .map(|state| state.stats.clone())
+            .unwrap_or_default()
+    }
+
+    /// Stats worth reporting: `None` when no limit was set and no 429 was seen
+    #[must_use]
+    pub fn summary(&self) -> Option<RateLimitStats> {
+        Some(self.stats())
+            .filter(|stats| stats.max_rps.is_some() || stats.rate_limited_responses > 0)
+    }
+}
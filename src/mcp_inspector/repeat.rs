@@ -0,0 +1,344 @@
+//! Repeated runs for spotting flaky tools
+//!
+//! A single sample per tool can't tell a flaky tool from a healthy one.
+//! [`GleanMCPInspector::repeat_test_all`] and
+//! [`GleanMCPInspector::repeat_test_tool`] run the same tests several times
+//! and report each tool's pass rate and latency spread, with the latency
+//! distribution as a histogram (a sparkline in text output).
+
+use super::{GleanMCPInspector, RequestTrace, TestAllOptions, async_timeout};
+use crate::{LatencyBucket, LatencyStats, Result, RunOutcome, latency_histogram, sparkline};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::time::{Duration, Instant};
+
+/// Buckets in each tool's latency histogram
+const HISTOGRAM_BUCKETS: usize = 10;
+
+/// Pass rate and latency of one tool across the iterations
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolRepeatStats {
+    /// Tool name, suffixed with its endpoint for `test-all` runs
+    pub tool: String,
+    pub runs: usize,
+    pub passed: usize,
+    /// Fraction of runs that passed (0.0-1.0)
+    pub pass_rate: f64,
+    /// Latency distribution of passing runs
+    pub latency: LatencyStats,
+    /// Sample standard deviation of passing runs' latency
+    pub stddev_ms: f64,
+    /// Passing runs' latency in equal-width buckets from the fastest to the slowest
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub histogram: Vec<LatencyBucket>,
+    /// Failure counts by error message
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub errors: BTreeMap<String, usize>,
+}
+
+impl ToolRepeatStats {
+    /// Passed some runs and failed others
+    #[must_use]
+    pub const fn is_flaky(&self) -> bool {
+        self.passed > 0 && self.passed < self.runs
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn from_samples(tool: String, samples: &[Sample]) -> Self {
+        let latencies: Vec<u64> = samples
+            .iter()
+            .filter(|sample| sample.error.is_none())
+            .map(|sample| sample.response_time_ms)
+            .collect();
+        let mut errors = BTreeMap::new();
+        for error in samples.iter().filter_map(|sample| sample.error.as_ref()) {
+            *errors
+                .entry(GleanMCPInspector::truncate_error_message(error))
+                .or_default() += 1;
+        }
+
+        let passed = latencies.len();
+        let variance = if passed < 2 {
+            0.0
+        } else {
+            let mean = latencies.iter().sum::<u64>() as f64 / passed as f64;
+            latencies
+                .iter()
+                .map(|latency| (*latency as f64 - mean).powi(2))
+                .sum::<f64>()
+                / (passed - 1) as f64
+        };
+
+        Self {
+            tool,
+            runs: samples.len(),
+            passed,
+            pass_rate: passed as f64 / samples.len().max(1) as f64,
+            latency: LatencyStats::from_samples(&latencies),
+            stddev_ms: variance.sqrt(),
+            histogram: latency_histogram(&latencies, HISTOGRAM_BUCKETS),
+            errors,
+        }
+    }
+}
+
+/// Results of running the same tests several times
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RepeatReport {
+    /// Iterations asked for (the cap with `--until-failure`)
+    pub requested_iterations: usize,
+    /// Iterations actually run
+    pub iterations: usize,
+    pub until_failure: bool,
+    /// Iteration in which the first failure occurred
+    pub first_failure: Option<usize>,
+    pub tools: Vec<ToolRepeatStats>,
+    pub duration_ms: u64,
+}
+
+impl RepeatReport {
+    /// Every tool passed every run
+    #[must_use]
+    pub fn success(&self) -> bool {
+        !self.tools.is_empty() && self.tools.iter().all(|tool| tool.passed == tool.runs)
+    }
+
+    /// Tools that passed some runs and failed others
+    pub fn flaky(&self) -> impl Iterator<Item = &ToolRepeatStats> {
+        self.tools.iter().filter(|tool| tool.is_flaky())
+    }
+
+    /// How the run ended, from CI's point of view
+    #[must_use]
+    pub fn outcome(&self) -> RunOutcome {
+        if self.success() {
+            RunOutcome::Success
+        } else if self.tools.iter().all(|tool| tool.passed == 0) {
+            RunOutcome::ConnectivityFailure
+        } else {
+            RunOutcome::PartialFailure
+        }
+    }
+
+    #[must_use]
+    pub fn format_text(&self) -> String {
+        let mut output = String::new();
+        let _ = writeln!(
+            output,
+            "🔁 Repeat: {} of {} iteration(s){}",
+            self.iterations,
+            self.requested_iterations,
+            match self.first_failure {
+                Some(iteration) if self.until_failure => {
+                    format!(", stopped at the first failure (iteration {iteration})")
+                }
+                Some(iteration) => format!(", first failure in iteration {iteration}"),
+                None => String::new(),
+            }
+        );
+        let width = self
+            .tools
+            .iter()
+            .map(|tool| tool.tool.len())
+            .max()
+            .unwrap_or_default();
+        for tool in &self.tools {
+            let marker = if tool.passed == tool.runs {
+                "✅"
+            } else if tool.is_flaky() {
+                "⚠️"
+            } else {
+                "❌"
+            };
+            let _ = write!(
+                output,
+                "  {marker} {:<width$}  {:>3}/{:<3} ({:5.1}%)",
+                tool.tool,
+                tool.passed,
+                tool.runs,
+                tool.pass_rate * 100.0
+            );
+            if tool.passed > 0 {
+                let _ = write!(
+                    output,
+                    "  mean {}ms ± {:.0}ms | p50 {}ms | p95 {}ms (min {}ms, max {}ms)",
+                    tool.latency.mean,
+                    tool.stddev_ms,
+                    tool.latency.p50,
+                    tool.latency.p95,
+                    tool.latency.min,
+                    tool.latency.max
+                );
+            }
+            if tool.passed > 1 {
+                let _ = write!(output, "  {}", sparkline(&tool.histogram));
+            }
+            output.push('\n');
+            for (error, count) in &tool.errors {
+                let _ = writeln!(output, "      {count:>3} × {error}");
+            }
+        }
+        output
+    }
+}
+
+/// One tool call's outcome within an iteration
+struct Sample {
+    response_time_ms: u64,
+    error: Option<String>,
+}
+
+/// Samples per tool, keeping the order tools were first seen in
+#[derive(Default)]
+struct Samples(Vec<(String, Vec<Sample>)>);
+
+impl Samples {
+    fn push(&mut self, tool: &str, sample: Sample) {
+        if let Some((_, samples)) = self.0.iter_mut().find(|(name, _)| name == tool) {
+            samples.push(sample);
+        } else {
+            self.0.push((tool.to_string(), vec![sample]));
+        }
+    }
+
+    fn into_stats(self) -> Vec<ToolRepeatStats> {
+        self.0
+            .into_iter()
+            .map(|(tool, samples)| ToolRepeatStats::from_samples(tool, &samples))
+            .collect()
+    }
+}
+
+impl GleanMCPInspector {
+    /// Run [`test_all_tools`](Self::test_all_tools) up to `iterations` times
+    ///
+    /// With `until_failure`, stops after the first iteration in which a tool
+    /// failed. Round-robin corpus queries advance with each iteration.
+    #[allow(clippy::future_not_send)]
+    #[allow(clippy::cast_possible_truncation)]
+    pub async fn repeat_test_all(
+        &self,
+        options: &TestAllOptions,
+        iterations: usize,
+        until_failure: bool,
+    ) -> Result<RepeatReport> {
+        let start = Instant::now();
+        let mut samples = Samples::default();
+        let mut first_failure = None;
+        let mut completed = 0;
+        let mut options = options.clone();
+
+        for iteration in 1..=iterations {
+            self.reporter()
+                .info(&format!("Iteration {iteration}/{iterations}"));
+            let result = self.test_all_tools(&options).await?;
+            options.corpus.offset += 1;
+            // Keyed like `search (default)`, so each endpoint is tracked separately
+            let mut tool_results: Vec<_> = result
+                .tool_results
+                .iter()
+                .filter(|(_, tool_result)| !tool_result.is_skipped())
+                .collect();
+            tool_results.sort_by(|a, b| a.0.cmp(b.0));
+            for (tool, tool_result) in tool_results {
+                samples.push(
+                    tool,
+                    Sample {
+                        response_time_ms: tool_result.response_time_ms,
+                        error: (!tool_result.success).then(|| {
+                            tool_result
+                                .error_message
+                                .clone()
+                                .unwrap_or_else(|| "failed".to_string())
+                        }),
+                    },
+                );
+            }
+            completed = iteration;
+            if result.failed_tools > 0 {
+                first_failure.get_or_insert(iteration);
+                if until_failure {
+                    break;
+                }
+            }
+            if options.cancel.is_cancelled() {
+                break;
+            }
+        }
+
+        Ok(RepeatReport {
+            requested_iterations: iterations,
+            iterations: completed,
+            until_failure,
+            first_failure,
+            tools: samples.into_stats(),
+            duration_ms: start.elapsed().as_millis() as u64,
+        })
+    }
+
+    /// Call `tool_name` on the default endpoint up to `iterations` times, without retries
+    ///
+    /// With `until_failure`, stops at the first failed call.
+    #[allow(clippy::future_not_send)]
+    #[allow(clippy::cast_possible_truncation)]
+    pub async fn repeat_test_tool(
+        &self,
+        tool_name: &str,
+        arguments: &Value,
+        timeout: Duration,
+        iterations: usize,
+        until_failure: bool,
+    ) -> RepeatReport {
+        let start = Instant::now();
+        let mut samples = Samples::default();
+        let mut first_failure = None;
+        let mut completed = 0;
+
+        for iteration in 1..=iterations {
+            let call_start = Instant::now();
+            let result = async_timeout(
+                timeout,
+                Self::test_tool_direct(
+                    self.transport(),
+                    self.server_url(),
+                    tool_name,
+                    arguments,
+                    &mut RequestTrace::default(),
+                ),
+            )
+            .await;
+            let response_time_ms = call_start.elapsed().as_millis() as u64;
+            let error = result.err().map(|e| e.to_string());
+            self.reporter().info(&format!(
+                "Iteration {iteration}/{iterations}: {} ({response_time_ms}ms)",
+                if error.is_some() { "failed" } else { "passed" }
+            ));
+            let failed = error.is_some();
+            samples.push(
+                tool_name,
+                Sample {
+                    response_time_ms,
+                    error,
+                },
+            );
+            completed = iteration;
+            if failed {
+                first_failure.get_or_insert(iteration);
+                if until_failure {
+                    break;
+                }
+            }
+        }
+
+        RepeatReport {
+            requested_iterations: iterations,
+            iterations: completed,
+            until_failure,
+            first_failure,
+            tools: samples.into_stats(),
+            duration_ms: start.elapsed().as_millis() as u64,
+        }
+    }
+}
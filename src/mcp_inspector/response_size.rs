@@ -0,0 +1,243 @@
+//! Response payload size limits and truncation
+//!
+//! MCP hosts cap how much tool output they hand to the model, so a tool
+//! answering with hundreds of kilobytes passes here and gets cut off in a
+//! host. [`ResponseSizeConfig`] flags responses over a byte limit, and
+//! [`GleanMCPInspector::test_truncation`] reads a deliberately large
+//! document to check the server truncates it itself.
+
+use super::{GleanMCPInspector, RequestTrace, TestQueryGenerator, async_timeout};
+use crate::RunOutcome;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::time::{Duration, Instant};
+
+/// Roughly 25,000 tokens, the tool output cap common among MCP hosts
+const DEFAULT_MAX_BYTES: u64 = 100_000;
+
+/// Characters of a textual truncation notice quoted in the report
+const MARKER_CHARS: usize = 48;
+
+/// Settings for the response size check
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ResponseSizeConfig {
+    pub enabled: bool,
+    /// Largest response, in serialized bytes, before a tool is flagged as oversized
+    pub max_bytes: u64,
+    /// Per-tool limits overriding `max_bytes`
+    pub tool_max_bytes: BTreeMap<String, u64>,
+    /// Document `--truncation` reads when no URL is given
+    pub large_document_url: Option<String>,
+}
+
+impl Default for ResponseSizeConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_bytes: DEFAULT_MAX_BYTES,
+            tool_max_bytes: BTreeMap::new(),
+            large_document_url: None,
+        }
+    }
+}
+
+impl ResponseSizeConfig {
+    /// Size limit for `tool_name`
+    #[must_use]
+    pub fn limit_for(&self, tool_name: &str) -> u64 {
+        self.tool_max_bytes
+            .get(tool_name)
+            .copied()
+            .unwrap_or(self.max_bytes)
+    }
+}
+
+/// Size of `response` as sent over the wire, in bytes
+#[must_use]
+pub fn response_bytes(response: &Value) -> u64 {
+    serde_json::to_vec(response).map_or(0, |bytes| bytes.len() as u64)
+}
+
+/// `850 B`, `12.3 KB`, or `1.4 MB`
+#[must_use]
+#[allow(clippy::cast_precision_loss)]
+pub fn format_bytes(bytes: u64) -> String {
+    if bytes < 1000 {
+        format!("{bytes} B")
+    } else if bytes < 1_000_000 {
+        format!("{:.1} KB", bytes as f64 / 1000.0)
+    } else {
+        format!("{:.1} MB", bytes as f64 / 1_000_000.0)
+    }
+}
+
+/// Whether the response says it was cut short, via a `truncated` flag or a note in its text
+fn truncation_marker(response: &Value) -> Option<String> {
+    match response {
+        Value::Object(map) => map.iter().find_map(|(key, value)| {
+            if key.to_lowercase().contains("truncat") && value.as_bool() == Some(true) {
+                Some(format!("\"{key}\": true"))
+            } else {
+                truncation_marker(value)
+            }
+        }),
+        Value::Array(items) => items.iter().find_map(truncation_marker),
+        Value::String(text) => text.to_ascii_lowercase().find("truncated").map(|start| {
+            // ASCII lowercasing keeps byte offsets, so `start` indexes `text` too
+            text[start..].chars().take(MARKER_CHARS).collect()
+        }),
+        _ => None,
+    }
+}
+
+/// Results of reading a large document through `read_document`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TruncationResult {
+    pub success: bool,
+    pub endpoint: String,
+    pub url: String,
+    pub max_bytes: u64,
+    /// Serialized size of the response, when one arrived
+    pub response_bytes: Option<u64>,
+    /// Where the response says it was truncated
+    pub marker: Option<String>,
+    pub response_time_ms: u64,
+    /// Why the call failed, when it did
+    pub error: Option<String>,
+    /// Why the response isn't properly truncated; empty when passed
+    pub problems: Vec<String>,
+    /// Observations that don't fail the check
+    pub notes: Vec<String>,
+}
+
+impl TruncationResult {
+    /// How the check ended, from CI's point of view
+    #[must_use]
+    pub const fn outcome(&self) -> RunOutcome {
+        if self.success {
+            RunOutcome::Success
+        } else if self.response_bytes.is_none() {
+            RunOutcome::ConnectivityFailure
+        } else {
+            RunOutcome::ValidationFailure
+        }
+    }
+
+    #[must_use]
+    pub fn format_text(&self) -> String {
+        let mut output = String::new();
+        let _ = writeln!(
+            output,
+            "✂️  Truncation: read_document of {} ({})",
+            self.url, self.endpoint
+        );
+        let status = if self.success { "✅" } else { "❌" };
+        match self.response_bytes {
+            Some(bytes) => {
+                let _ = writeln!(
+                    output,
+                    "  {status} {} response in {}ms (limit {}){}",
+                    format_bytes(bytes),
+                    self.response_time_ms,
+                    format_bytes(self.max_bytes),
+                    self.marker
+                        .as_ref()
+                        .map_or_else(String::new, |marker| format!(", marked {marker:?}"))
+                );
+            }
+            None => {
+                let _ = writeln!(
+                    output,
+                    "  {status} No response: {}",
+                    self.error.as_deref().unwrap_or("unknown error")
+                );
+            }
+        }
+        for problem in &self.problems {
+            let _ = writeln!(output, "    - {problem}");
+        }
+        for note in &self.notes {
+            let _ = writeln!(output, "    💡 {note}");
+        }
+        output
+    }
+}
+
+impl GleanMCPInspector {
+    /// Read `url` through `read_document` and check the response fits within the size limit
+    ///
+    /// `url` should name a document larger than the limit, so a passing
+    /// response shows the server truncated it rather than leaving that to the host.
+    #[allow(clippy::future_not_send)]
+    #[allow(clippy::cast_possible_truncation)]
+    pub async fn test_truncation(
+        &self,
+        url: &str,
+        config: &ResponseSizeConfig,
+        timeout: Duration,
+    ) -> TruncationResult {
+        let max_bytes = config.limit_for("read_document");
+        let arguments = TestQueryGenerator::generate_arguments("read_document", url);
+        let start = Instant::now();
+        let response = async_timeout(
+            timeout,
+            Self::test_tool_direct(
+                self.transport(),
+                self.server_url(),
+                "read_document",
+                &arguments,
+                &mut RequestTrace::default(),
+            ),
+        )
+        .await;
+        let response_time_ms = start.elapsed().as_millis() as u64;
+
+        let mut problems = Vec::new();
+        let mut notes = Vec::new();
+        let (bytes, marker, error) = match &response {
+            Ok(response) => {
+                let bytes = response_bytes(response);
+                let marker = truncation_marker(response);
+                if response.get("isError").and_then(Value::as_bool) == Some(true) {
+                    problems.push("read_document returned an error result".to_string());
+                } else if bytes > max_bytes {
+                    problems.push(format!(
+                        "Response is {}, over the {} limit; the server didn't truncate it, so MCP hosts will",
+                        format_bytes(bytes),
+                        format_bytes(max_bytes)
+                    ));
+                } else if marker.is_none() {
+                    notes.push(
+                        "Response fits the limit but doesn't say it was truncated; check the document is larger than the limit"
+                            .to_string(),
+                    );
+                }
+                (Some(bytes), marker, None)
+            }
+            Err(e) => {
+                problems.push(format!("read_document failed: {e}"));
+                (None, None, Some(e.to_string()))
+            }
+        };
+
+        self.reporter().info(&format!(
+            "read_document {url}: {}",
+            bytes.map_or_else(|| "failed".to_string(), format_bytes)
+        ));
+        TruncationResult {
+            success: problems.is_empty(),
+            endpoint: self.server_url().to_string(),
+            url: url.to_string(),
+            max_bytes,
+            response_bytes: bytes,
+            marker,
+            response_time_ms,
+            error,
+            problems,
+            notes,
+        }
+    }
+}
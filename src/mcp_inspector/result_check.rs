@@ -0,0 +1,185 @@
+//! Semantic validation of search results
+//!
+//! A search that answers with an empty result set, or with documents missing
+//! their title, URL, or snippet, usually means an indexing outage rather
+//! than a query with no matches. The check fails such responses and, when
+//! domains are configured, results linking outside them.
+
+use super::{SearchResultItem, search_results};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt::Write;
+
+/// Findings listed in a failure message before the rest are summarized
+const MAX_REPORTED: usize = 3;
+
+/// A field every search result must carry
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ResultField {
+    Title,
+    Url,
+    Snippet,
+}
+
+impl ResultField {
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Title => "title",
+            Self::Url => "url",
+            Self::Snippet => "snippet",
+        }
+    }
+
+    fn is_present(self, item: &SearchResultItem) -> bool {
+        let value = match self {
+            Self::Title => item.title.as_deref(),
+            Self::Url => item.url.as_deref(),
+            Self::Snippet => item.snippet.as_deref(),
+        };
+        value.is_some_and(|value| !value.trim().is_empty())
+    }
+}
+
+/// Settings for the search result check
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ResultCheckConfig {
+    pub enabled: bool,
+    /// Tools whose responses are checked
+    pub tools: Vec<String>,
+    /// Fewest results a response may contain
+    pub min_results: usize,
+    pub required_fields: Vec<ResultField>,
+    /// Domains result URLs must belong to, subdomains included (empty: any)
+    pub allowed_domains: Vec<String>,
+}
+
+impl Default for ResultCheckConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            tools: vec!["search".to_string()],
+            min_results: 1,
+            required_fields: vec![ResultField::Title, ResultField::Url, ResultField::Snippet],
+            allowed_domains: Vec::new(),
+        }
+    }
+}
+
+impl ResultCheckConfig {
+    /// Check the results in `response` from `tool_name`.
+    ///
+    /// Returns `Ok(Some(note))` when checked, `Ok(None)` when the tool isn't
+    /// covered, and `Err` describing the problems.
+    pub fn check(
+        &self,
+        tool_name: &str,
+        response: &Value,
+    ) -> std::result::Result<Option<String>, String> {
+        if !self.enabled || !self.tools.iter().any(|tool| tool == tool_name) {
+            return Ok(None);
+        }
+
+        let items = search_results(response);
+        if items.len() < self.min_results {
+            return Err(if items.is_empty() {
+                "Search returned no results".to_string()
+            } else {
+                format!(
+                    "Search returned {} result(s), expected at least {}",
+                    items.len(),
+                    self.min_results
+                )
+            });
+        }
+
+        let findings = self.find_problems(&items);
+        if findings.is_empty() {
+            return Ok(Some(format!(
+                "Checked {} results: all have {}{}",
+                items.len(),
+                self.required_fields
+                    .iter()
+                    .map(|field| field.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                if self.allowed_domains.is_empty() {
+                    String::new()
+                } else {
+                    " and expected domains".to_string()
+                }
+            )));
+        }
+
+        let mut message = format!(
+            "Invalid search results: {} finding(s) in {} results",
+            findings.len(),
+            items.len()
+        );
+        for finding in findings.iter().take(MAX_REPORTED) {
+            let _ = write!(message, "\n- {finding}");
+        }
+        if findings.len() > MAX_REPORTED {
+            let _ = write!(
+                message,
+                "\n- ... and {} more",
+                findings.len() - MAX_REPORTED
+            );
+        }
+        Err(message)
+    }
+
+    /// Describe every result missing a required field or linking outside the allowed domains
+    #[must_use]
+    pub fn find_problems(&self, items: &[SearchResultItem]) -> Vec<String> {
+        let mut findings = Vec::new();
+        for (index, item) in items.iter().enumerate() {
+            let missing: Vec<&str> = self
+                .required_fields
+                .iter()
+                .filter(|field| !field.is_present(item))
+                .map(|field| field.as_str())
+                .collect();
+            if !missing.is_empty() {
+                findings.push(format!(
+                    "result #{} has no {}",
+                    index + 1,
+                    missing.join(", ")
+                ));
+            }
+            if let Some(url) = item.url.as_deref().filter(|url| !url.trim().is_empty())
+                && !self.allowed_domains.is_empty()
+                && !self
+                    .allowed_domains
+                    .iter()
+                    .any(|domain| in_domain(url, domain))
+            {
+                findings.push(format!(
+                    "result #{} links outside the expected domains: {url}",
+                    index + 1
+                ));
+            }
+        }
+        findings
+    }
+}
+
+/// Whether `url`'s host is `domain` or one of its subdomains
+fn in_domain(url: &str, domain: &str) -> bool {
+    let host = url_host(url);
+    let domain = domain.trim().trim_start_matches('.').to_lowercase();
+    host == domain || host.ends_with(&format!(".{domain}"))
+}
+
+/// Lowercased host of `url`, without scheme, credentials, port, or path
+fn url_host(url: &str) -> String {
+    let url = url.trim();
+    let rest = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or(rest);
+    let host = authority
+        .rsplit_once('@')
+        .map_or(authority, |(_, host)| host);
+    host.split(':').next().unwrap_or(host).to_lowercase()
+}
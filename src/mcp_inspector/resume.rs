@@ -0,0 +1,273 @@
+//! Reconnecting after a dropped stream
+//!
+//! [`GleanMCPInspector::test_resumption`] sends a tool call that accepts an
+//! event-stream answer and drops the connection after the first event, the
+//! way a flaky network cuts off a host. It then tries to resume the stream
+//! with `Last-Event-ID`, reporting whether the server supports resumption
+//! and whether the call's response was recovered, and checks that a fresh
+//! session can be initialized cleanly.
+
+use super::{GleanMCPInspector, StreamRequest, parse_event_stream};
+use crate::{RunOutcome, TestQueryGenerator};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::fmt::Write;
+use std::time::{Duration, Instant};
+
+/// Whether the server let the dropped stream be resumed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Resumption {
+    /// The stream was resumed with `Last-Event-ID`
+    Supported,
+    /// The server answered the call with plain JSON, so there was no stream to drop
+    NotStreamed,
+    /// The stream's events carried no ids to resume from
+    NoEventIds,
+    /// The server refused the `Last-Event-ID` reconnect
+    Rejected,
+}
+
+impl Resumption {
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Supported => "supported",
+            Self::NotStreamed => "not applicable (response was not streamed)",
+            Self::NoEventIds => "unsupported (events have no ids)",
+            Self::Rejected => "unsupported (reconnect refused)",
+        }
+    }
+}
+
+/// Results of dropping a stream and reconnecting
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResumeTestResult {
+    pub success: bool,
+    pub endpoint: String,
+    /// `Mcp-Session-Id` of the dropped session; `None` for stateless servers
+    pub session_id: Option<String>,
+    pub tool: String,
+    pub resumption: Option<Resumption>,
+    /// Id of the last event received before the drop
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_event_id: Option<String>,
+    /// HTTP status of the `Last-Event-ID` reconnect
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resume_status: Option<u16>,
+    /// Events the reconnect replayed
+    pub replayed_events: usize,
+    /// The call's response arrived, before the drop or on the reconnect
+    pub response_recovered: bool,
+    /// A new session initialized and answered `ping` after the drop
+    pub reinitialized: bool,
+    pub duration_ms: u64,
+    pub error: Option<String>,
+}
+
+impl ResumeTestResult {
+    /// How the test ended, from CI's point of view
+    #[must_use]
+    pub const fn outcome(&self) -> RunOutcome {
+        if self.success {
+            RunOutcome::Success
+        } else if self.error.is_some() {
+            RunOutcome::ConnectivityFailure
+        } else {
+            RunOutcome::ValidationFailure
+        }
+    }
+
+    #[must_use]
+    pub fn format_text(&self) -> String {
+        let mut output = String::new();
+        let _ = writeln!(
+            output,
+            "🔌 Reconnect: dropped a {} call on {}",
+            self.tool, self.endpoint
+        );
+        if let Some(error) = &self.error {
+            let _ = writeln!(output, "Error: {error}");
+            return output;
+        }
+        let yes_no = |value: bool| if value { "yes" } else { "no" };
+        let _ = writeln!(
+            output,
+            "  Session: {}",
+            self.session_id
+                .as_deref()
+                .unwrap_or("none (stateless server)")
+        );
+        let _ = writeln!(
+            output,
+            "  Resumption: {}",
+            self.resumption.map_or("unknown", Resumption::as_str)
+        );
+        if let Some(last_event_id) = &self.last_event_id {
+            let _ = writeln!(
+                output,
+                "  Last-Event-ID: {last_event_id} → {}, {} event(s) replayed",
+                self.resume_status.map_or_else(
+                    || "no response".to_string(),
+                    |status| format!("HTTP {status}")
+                ),
+                self.replayed_events
+            );
+        }
+        let _ = writeln!(
+            output,
+            "  Response recovered: {}",
+            yes_no(self.response_recovered)
+        );
+        let _ = writeln!(
+            output,
+            "  Re-initialized cleanly: {}",
+            yes_no(self.reinitialized)
+        );
+        output
+    }
+}
+
+/// Whether `data` is the JSON-RPC response to request `id`
+fn is_response_to(data: &str, id: u64) -> bool {
+    serde_json::from_str::<Value>(data).is_ok_and(|message| {
+        message.get("id") == Some(&json!(id))
+            && (message.get("result").is_some() || message.get("error").is_some())
+    })
+}
+
+impl GleanMCPInspector {
+    /// Drop a streamed `search` call on the default endpoint after its first event, then reconnect
+    ///
+    /// Each stream is read for up to `wait`. The test fails when a new
+    /// session can't be initialized, or when the server accepts the resume
+    /// but the call's response is lost.
+    #[allow(clippy::cast_possible_truncation)]
+    pub async fn test_resumption(&self, wait: Duration) -> ResumeTestResult {
+        const CALL_ID: u64 = 1;
+        let started = Instant::now();
+        let tool = "search";
+        let mut result = ResumeTestResult {
+            success: false,
+            endpoint: self.server_url().to_string(),
+            session_id: None,
+            tool: tool.to_string(),
+            resumption: None,
+            last_event_id: None,
+            resume_status: None,
+            replayed_events: 0,
+            response_recovered: false,
+            reinitialized: false,
+            duration_ms: 0,
+            error: None,
+        };
+        let (session_id, transport, _) = match self.open_session().await {
+            Ok(session) => session,
+            Err(error) => {
+                result.error = Some(error);
+                return result;
+            }
+        };
+        result.session_id = session_id;
+
+        let query = TestQueryGenerator::generate_test_query(tool);
+        let call = StreamRequest {
+            body: Some(json!({
+                "jsonrpc": "2.0",
+                "id": CALL_ID,
+                "method": "tools/call",
+                "params": {
+                    "name": tool,
+                    "arguments": TestQueryGenerator::generate_arguments(tool, &query)
+                }
+            })),
+            wait,
+            drop_after_events: Some(1),
+            ..StreamRequest::default()
+        };
+        let response = match transport.read_stream(self.server_url(), &call).await {
+            Ok(response) if response.success => response,
+            Ok(response) => {
+                result.error = Some(format!("The call failed: {}", response.stderr));
+                return result;
+            }
+            Err(e) => {
+                result.error = Some(e.to_string());
+                return result;
+            }
+        };
+        if let Some(status) = response.status.filter(|status| *status >= 400) {
+            result.error = Some(format!("The call failed with HTTP {status}"));
+            return result;
+        }
+
+        let streamed = response
+            .header("content-type")
+            .is_some_and(|content_type| content_type.starts_with("text/event-stream"));
+        if streamed {
+            let events = parse_event_stream(&response.body);
+            result.response_recovered = events
+                .iter()
+                .any(|event| is_response_to(&event.data, CALL_ID));
+            result.last_event_id = events.iter().rev().find_map(|event| event.id.clone());
+            result.resumption = Some(Resumption::NoEventIds);
+        } else {
+            result.response_recovered = is_response_to(&response.body, CALL_ID);
+            result.resumption = Some(Resumption::NotStreamed);
+        }
+
+        if let Some(last_event_id) = &result.last_event_id {
+            self.reporter().info(&format!(
+                "Stream dropped after event {last_event_id}; reconnecting with Last-Event-ID"
+            ));
+            let resume = StreamRequest {
+                last_event_id: Some(last_event_id.clone()),
+                wait,
+                ..StreamRequest::default()
+            };
+            let resumed = transport
+                .read_stream(self.server_url(), &resume)
+                .await
+                .ok()
+                .filter(|response| response.success);
+            result.resume_status = resumed.as_ref().and_then(|response| response.status);
+            match resumed {
+                Some(response)
+                    if response.status == Some(200)
+                        && response.header("content-type").is_some_and(|content_type| {
+                            content_type.starts_with("text/event-stream")
+                        }) =>
+                {
+                    let replayed = parse_event_stream(&response.body);
+                    result.replayed_events = replayed.len();
+                    result.response_recovered |= replayed
+                        .iter()
+                        .any(|event| is_response_to(&event.data, CALL_ID));
+                    result.resumption = Some(Resumption::Supported);
+                }
+                _ => result.resumption = Some(Resumption::Rejected),
+            }
+        }
+
+        result.reinitialized = match self.open_session().await {
+            Ok((_, fresh, _)) => fresh
+                .post_json_rpc(
+                    self.server_url(),
+                    &json!({ "jsonrpc": "2.0", "id": 1, "method": "ping" }),
+                )
+                .await
+                .ok()
+                .and_then(|response| serde_json::from_str::<Value>(&response.body).ok())
+                .is_some_and(|body| body.get("result").is_some()),
+            Err(error) => {
+                self.reporter()
+                    .warning(&format!("Could not re-initialize: {error}"));
+                false
+            }
+        };
+        result.duration_ms = started.elapsed().as_millis() as u64;
+        result.success = result.reinitialized
+            && (result.resumption != Some(Resumption::Supported) || result.response_recovered);
+        result
+    }
+}
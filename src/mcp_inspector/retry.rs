@@ -0,0 +1,231 @@
+//! Retry policy for tool calls
+//!
+//! [`RetryPolicy`] decides whether a failed call is worth repeating and how
+//! long to wait first. Errors are sorted into an [`ErrorClass`]: rejected
+//! credentials and malformed requests fail immediately, while rate limits,
+//! network trouble, and 5xx responses are retried with jittered exponential
+//! backoff, or after the server's `Retry-After` delay when it sends one.
+
+use crate::GleanMcpError;
+use rand::Rng;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Broad cause of a failed call
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum ErrorClass {
+    /// Credentials missing, expired, or not allowed (401/403)
+    Auth,
+    /// The server asked us to slow down (429)
+    RateLimited,
+    /// Timeouts, connection failures, truncated or empty bodies
+    Network,
+    /// The server failed while handling the request (5xx, JSON-RPC errors)
+    Server,
+    /// The request itself is wrong; repeating it won't help
+    Fatal,
+    /// A response arrived but failed a content check (language, duplicates, ...)
+    Validation,
+}
+
+impl ErrorClass {
+    /// Classify `error` by HTTP status where known, otherwise by its message
+    #[must_use]
+    pub fn classify(error: &GleanMcpError) -> Self {
+        match error {
+            GleanMcpError::Http { status, .. } => Self::from_status(*status),
+            GleanMcpError::Auth(_) => Self::Auth,
+            GleanMcpError::Network(_) | GleanMcpError::Io(_) => Self::Network,
+            GleanMcpError::Config(_)
+            | GleanMcpError::Validation(_)
+            | GleanMcpError::Json(_)
+            | GleanMcpError::Database(_) => Self::Fatal,
+            GleanMcpError::Inspector(message)
+            | GleanMcpError::Host(message)
+            | GleanMcpError::Process(message) => Self::from_message(message),
+        }
+    }
+
+    #[must_use]
+    pub const fn from_status(status: u16) -> Self {
+        match status {
+            401 | 403 => Self::Auth,
+            429 => Self::RateLimited,
+            408 => Self::Network,
+            500..=599 => Self::Server,
+            _ => Self::Fatal,
+        }
+    }
+
+    fn from_message(message: &str) -> Self {
+        let lowered = message.to_lowercase();
+        if [
+            "401",
+            "403",
+            "unauthorized",
+            "invalid secret",
+            "not allowed",
+            "authentication",
+        ]
+        .iter()
+        .any(|marker| lowered.contains(marker))
+        {
+            Self::Auth
+        } else if lowered.contains("429") || lowered.contains("too many requests") {
+            Self::RateLimited
+        } else if lowered.contains("mcp server error") || lowered.contains("server error") {
+            Self::Server
+        } else {
+            // Timeouts, curl failures, and garbled bodies are usually transient
+            Self::Network
+        }
+    }
+
+    /// Whether the endpoint could not be reached or could not serve the request
+    #[must_use]
+    pub const fn is_connectivity(self) -> bool {
+        matches!(self, Self::Network | Self::Server | Self::RateLimited)
+    }
+
+    #[must_use]
+    pub const fn is_retryable(self) -> bool {
+        !matches!(self, Self::Auth | Self::Fatal | Self::Validation)
+    }
+
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Auth => "auth",
+            Self::RateLimited => "rate-limited",
+            Self::Network => "network",
+            Self::Server => "server",
+            Self::Fatal => "fatal",
+            Self::Validation => "validation",
+        }
+    }
+}
+
+/// Failure category reported per tool and rolled up per run
+///
+/// Finer than [`ErrorClass`] for reporting: network failures are split
+/// into timeouts and other transport errors.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, JsonSchema,
+)]
+#[serde(rename_all = "kebab-case")]
+pub enum ErrorCategory {
+    Auth,
+    RateLimit,
+    Timeout,
+    ServerError,
+    ValidationFailure,
+    TransportError,
+}
+
+impl ErrorCategory {
+    /// Category of a failure classified as `class` with error `message`
+    #[must_use]
+    pub fn from_class(class: ErrorClass, message: Option<&str>) -> Self {
+        match class {
+            ErrorClass::Auth => Self::Auth,
+            ErrorClass::RateLimited => Self::RateLimit,
+            ErrorClass::Server => Self::ServerError,
+            ErrorClass::Validation => Self::ValidationFailure,
+            ErrorClass::Network | ErrorClass::Fatal => {
+                let lowered = message.unwrap_or_default().to_lowercase();
+                if ["timed out", "timeout", "(28)"]
+                    .iter()
+                    .any(|marker| lowered.contains(marker))
+                {
+                    Self::Timeout
+                } else {
+                    Self::TransportError
+                }
+            }
+        }
+    }
+
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Auth => "auth",
+            Self::RateLimit => "rate limit",
+            Self::Timeout => "timeout",
+            Self::ServerError => "server error",
+            Self::ValidationFailure => "validation",
+            Self::TransportError => "transport",
+        }
+    }
+}
+
+/// When and how often to retry a failed tool call
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Total attempts, including the first
+    pub max_attempts: u32,
+    /// Upper bound of the jittered delay before the second attempt; doubles after each retry
+    pub initial_backoff: Duration,
+    /// Longest `Retry-After` delay honored; longer requests are capped
+    pub max_retry_after: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::new(4, Duration::from_secs(5))
+    }
+}
+
+impl RetryPolicy {
+    #[must_use]
+    pub const fn new(max_attempts: u32, initial_backoff: Duration) -> Self {
+        Self {
+            max_attempts,
+            initial_backoff,
+            max_retry_after: Duration::from_mins(1),
+        }
+    }
+
+    #[must_use]
+    pub const fn with_max_retry_after(mut self, max_retry_after: Duration) -> Self {
+        self.max_retry_after = max_retry_after;
+        self
+    }
+
+    /// Whether to try again after `attempt` (1-based) failed with `error`
+    #[must_use]
+    pub fn should_retry(self, attempt: u32, error: &GleanMcpError) -> bool {
+        attempt < self.max_attempts && ErrorClass::classify(error).is_retryable()
+    }
+
+    /// Delay before the attempt following `attempt` (1-based), which failed with `error`
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn delay(self, attempt: u32, error: &GleanMcpError) -> Duration {
+        if let GleanMcpError::Http {
+            retry_after: Some(retry_after),
+            ..
+        } = error
+        {
+            return (*retry_after).min(self.max_retry_after);
+        }
+
+        // Full jitter: random between 0 and the exponential base
+        let base_ms = (self.initial_backoff.as_millis() as u64)
+            .saturating_mul(2_u64.saturating_pow(attempt.saturating_sub(1)));
+        Duration::from_millis(rand::thread_rng().gen_range(0..=base_ms))
+    }
+}
+
+/// Parse a `Retry-After` value: delay seconds or an HTTP date
+#[must_use]
+pub fn parse_retry_after(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let date = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    let delay = date.with_timezone(&chrono::Utc) - chrono::Utc::now();
+    Some(delay.to_std().unwrap_or(Duration::ZERO))
+}
@@ -0,0 +1,135 @@
+//! Cooperative cancellation of test runs
+//!
+//! Everything working on one run shares a [`CancellationToken`]. Cancelling
+//! it, on Ctrl-C or on the first failure with `--fail-fast`, stops workers
+//! from starting new tools and abandons calls in flight, so the run ends
+//! promptly and still reports the results collected so far.
+
+use crate::{GleanMcpError, Result};
+use futures::StreamExt;
+use futures::channel::oneshot;
+use futures::future::{Either, FutureExt, Shared, select};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// Exit code of a run stopped by Ctrl-C, following the shell's 128 + SIGINT
+pub const INTERRUPTED_EXIT_CODE: i32 = 130;
+
+/// Why a run was cut short
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CancelReason {
+    /// The user pressed Ctrl-C
+    Interrupted,
+    /// A tool failed under `--fail-fast`
+    FailFast,
+}
+
+impl CancelReason {
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Interrupted => "interrupted",
+            Self::FailFast => "fail-fast",
+        }
+    }
+}
+
+/// Shared flag that cancels a run; clones observe the same cancellation
+#[derive(Clone)]
+pub struct CancellationToken {
+    reason: Arc<Mutex<Option<CancelReason>>>,
+    sender: Arc<Mutex<Option<oneshot::Sender<()>>>>,
+    cancelled: Shared<oneshot::Receiver<()>>,
+}
+
+impl Default for CancellationToken {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Debug for CancellationToken {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CancellationToken")
+            .field("reason", &self.reason())
+            .finish()
+    }
+}
+
+impl CancellationToken {
+    #[must_use]
+    pub fn new() -> Self {
+        let (sender, receiver) = oneshot::channel();
+        Self {
+            reason: Arc::new(Mutex::new(None)),
+            sender: Arc::new(Mutex::new(Some(sender))),
+            cancelled: receiver.shared(),
+        }
+    }
+
+    /// Cancel the run; the first reason given is kept
+    pub fn cancel(&self, reason: CancelReason) {
+        self.reason
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get_or_insert(reason);
+        let sender = self
+            .sender
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take();
+        if let Some(sender) = sender {
+            let _ = sender.send(());
+        }
+    }
+
+    /// Why the run was cancelled, if it was
+    #[must_use]
+    pub fn reason(&self) -> Option<CancelReason> {
+        *self.reason.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    #[must_use]
+    pub fn is_cancelled(&self) -> bool {
+        self.reason().is_some()
+    }
+
+    /// Resolves once the run is cancelled
+    pub async fn cancelled(&self) {
+        let _ = self.cancelled.clone().await;
+    }
+
+    /// Run `future` to completion, or return `None` if the run is cancelled first
+    pub async fn run_until_cancelled<F: Future>(&self, future: F) -> Option<F::Output> {
+        if self.is_cancelled() {
+            return None;
+        }
+        match select(Box::pin(future), Box::pin(self.cancelled())).await {
+            Either::Left((output, _)) => Some(output),
+            Either::Right(((), _)) => None,
+        }
+    }
+
+    /// Cancel the run on the first Ctrl-C and exit immediately on the second
+    pub fn cancel_on_interrupt(&self) -> Result<()> {
+        use async_signal::{Signal, Signals};
+
+        let mut signals = Signals::new([Signal::Int]).map_err(|e| {
+            GleanMcpError::Process(format!("Failed to install the Ctrl-C handler: {e}"))
+        })?;
+        let token = self.clone();
+        smol::spawn(async move {
+            if signals.next().await.is_some() {
+                token.cancel(CancelReason::Interrupted);
+            }
+            if signals.next().await.is_some() {
+                std::process::exit(INTERRUPTED_EXIT_CODE);
+            }
+        })
+        .detach();
+        Ok(())
+    }
+}
@@ -0,0 +1,283 @@
+//! Which Glean MCP server build a run talked to
+//!
+//! [`fetch_server_identity`] sends `initialize` and keeps the `serverInfo`
+//! name and version, the negotiated protocol version, and any version or
+//! build response headers, so a failing run can be traced to the backend
+//! build it hit. `--expect-server-version` gates a run on the version with a
+//! [`VersionRequirement`] such as `>=2.4`. The advertised capabilities gate
+//! which tests run: a server that doesn't advertise `tools` has its tool
+//! tests skipped rather than failed.
+
+use super::transport::McpTransport;
+use crate::{GleanMcpError, Result};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+/// Substrings of response header names that carry build information
+const VERSION_HEADER_MARKERS: [&str; 4] = ["version", "build", "revision", "commit"];
+
+/// Identity the server reported for itself
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct ServerIdentity {
+    /// `serverInfo.name` from the `initialize` response
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// `serverInfo.version` from the `initialize` response
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// MCP protocol version the server agreed to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub protocol_version: Option<String>,
+    /// Version and build response headers, by lowercased name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub headers: BTreeMap<String, String>,
+    /// Capabilities the server advertised (`tools`, `resources`, `prompts`, ...)
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub capabilities: BTreeSet<String>,
+}
+
+impl ServerIdentity {
+    /// Identity from an `initialize` response body and its headers
+    #[must_use]
+    pub fn from_response(body: &Value, headers: &BTreeMap<String, String>) -> Self {
+        let result = body.get("result").unwrap_or(&Value::Null);
+        let info = |field: &str| {
+            result
+                .pointer(&format!("/serverInfo/{field}"))
+                .and_then(Value::as_str)
+                .map(ToString::to_string)
+        };
+        Self {
+            name: info("name"),
+            version: info("version"),
+            protocol_version: result
+                .get("protocolVersion")
+                .and_then(Value::as_str)
+                .map(ToString::to_string),
+            headers: headers
+                .iter()
+                .filter(|(name, _)| {
+                    VERSION_HEADER_MARKERS
+                        .iter()
+                        .any(|marker| name.contains(marker))
+                })
+                .map(|(name, value)| (name.clone(), value.clone()))
+                .collect(),
+            capabilities: result
+                .get("capabilities")
+                .and_then(Value::as_object)
+                .map(|capabilities| capabilities.keys().cloned().collect())
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Whether the server advertised `capability`
+    #[must_use]
+    pub fn advertises(&self, capability: &str) -> bool {
+        self.capabilities.contains(capability)
+    }
+
+    /// Capabilities `previous` advertised that this identity doesn't
+    #[must_use]
+    pub fn lost_capabilities<'a>(&self, previous: &'a Self) -> Vec<&'a str> {
+        previous
+            .capabilities
+            .iter()
+            .filter(|capability| !self.capabilities.contains(*capability))
+            .map(String::as_str)
+            .collect()
+    }
+
+    /// One line, e.g. `glean-mcp 2.4.1 (protocol 2025-03-26, x-glean-build: 8f3c2e1)`
+    #[must_use]
+    pub fn describe(&self) -> String {
+        let line = match (&self.name, &self.version) {
+            (Some(name), Some(version)) => format!("{name} {version}"),
+            (Some(name), None) => format!("{name} (version not reported)"),
+            (None, Some(version)) => format!("version {version}"),
+            (None, None) => "unidentified server".to_string(),
+        };
+        let extras: Vec<String> = self
+            .protocol_version
+            .iter()
+            .map(|protocol| format!("protocol {protocol}"))
+            .chain(
+                self.headers
+                    .iter()
+                    .map(|(name, value)| format!("{name}: {value}")),
+            )
+            .collect();
+        if extras.is_empty() {
+            line
+        } else {
+            format!("{line} ({})", extras.join(", "))
+        }
+    }
+}
+
+/// Send `initialize` to `endpoint` and read the server's identity from the response
+pub async fn fetch_server_identity(
+    transport: &McpTransport,
+    endpoint: &str,
+) -> Result<ServerIdentity> {
+    let request = serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": 0,
+        "method": "initialize",
+        "params": {
+            "protocolVersion": "2025-03-26",
+            "capabilities": {},
+            "clientInfo": { "name": "glean-mcp-test", "version": env!("CARGO_PKG_VERSION") }
+        }
+    });
+    let response = transport.post_json_rpc(endpoint, &request).await?;
+    if !response.success {
+        return Err(GleanMcpError::Network(format!(
+            "Could not reach {endpoint} to identify the server: {}",
+            response.stderr.trim()
+        )));
+    }
+    let body: Value = serde_json::from_str(&response.body)
+        .map_err(|e| GleanMcpError::Validation(format!("initialize returned invalid JSON: {e}")))?;
+    if let Some(error) = body.get("error") {
+        return Err(GleanMcpError::Validation(format!(
+            "initialize returned an error: {error}"
+        )));
+    }
+    Ok(ServerIdentity::from_response(&body, &response.headers))
+}
+
+/// Comparison in a [`VersionRequirement`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VersionOp {
+    Greater,
+    AtLeast,
+    Exactly,
+    AtMost,
+    Less,
+}
+
+impl VersionOp {
+    pub const ALL: [Self; 5] = [
+        Self::AtLeast,
+        Self::AtMost,
+        Self::Greater,
+        Self::Less,
+        Self::Exactly,
+    ];
+
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Greater => ">",
+            Self::AtLeast => ">=",
+            Self::Exactly => "=",
+            Self::AtMost => "<=",
+            Self::Less => "<",
+        }
+    }
+
+    const fn accepts(self, ordering: Ordering) -> bool {
+        match self {
+            Self::Greater => ordering.is_gt(),
+            Self::AtLeast => ordering.is_ge(),
+            Self::Exactly => ordering.is_eq(),
+            Self::AtMost => ordering.is_le(),
+            Self::Less => ordering.is_lt(),
+        }
+    }
+}
+
+/// A constraint on the server version, e.g. `>=2.4`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VersionRequirement {
+    pub op: VersionOp,
+    /// Numeric components, e.g. `[2, 4]`
+    pub version: Vec<u64>,
+}
+
+impl VersionRequirement {
+    /// Whether `version` (e.g. `v2.4.1-rc1`) satisfies the requirement
+    ///
+    /// Components are compared numerically, missing ones count as zero, and
+    /// anything after the numeric part is ignored. A version without a
+    /// number never matches.
+    #[must_use]
+    pub fn matches(&self, version: &str) -> bool {
+        let Some(actual) = version_components(version) else {
+            return false;
+        };
+        let len = actual.len().max(self.version.len());
+        let component = |parts: &[u64], i: usize| parts.get(i).copied().unwrap_or(0);
+        let ordering = (0..len)
+            .map(|i| component(&actual, i).cmp(&component(&self.version, i)))
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or(Ordering::Equal);
+        self.op.accepts(ordering)
+    }
+
+    /// `Err` with the reason when `server`'s version doesn't satisfy the requirement
+    pub fn check(&self, server: Option<&ServerIdentity>) -> std::result::Result<(), String> {
+        match server.and_then(|server| server.version.as_deref()) {
+            Some(version) if self.matches(version) => Ok(()),
+            Some(version) => Err(format!("Server version {version} does not satisfy {self}")),
+            None => Err(format!(
+                "Server did not report a version, so {self} can't be checked"
+            )),
+        }
+    }
+}
+
+impl fmt::Display for VersionRequirement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let version: Vec<String> = self.version.iter().map(ToString::to_string).collect();
+        write!(f, "{}{}", self.op.as_str(), version.join("."))
+    }
+}
+
+/// Numeric components of `version`, after an optional leading `v`
+fn version_components(version: &str) -> Option<Vec<u64>> {
+    let version = version.trim();
+    let version = version.strip_prefix(['v', 'V']).unwrap_or(version);
+    let numeric = version
+        .split(|c: char| !c.is_ascii_digit() && c != '.')
+        .next()
+        .unwrap_or_default();
+    let components: Vec<u64> = numeric
+        .split('.')
+        .map_while(|part| part.parse().ok())
+        .collect();
+    (!components.is_empty()).then_some(components)
+}
+
+/// Parse a requirement like `>=2.4`, `<3`, or `2.4.1` (exact), as given to `--expect-server-version`
+pub fn parse_version_requirement(raw: &str) -> Result<VersionRequirement> {
+    let raw = raw.trim();
+    let (op, version) = VersionOp::ALL
+        .into_iter()
+        .find_map(|op| raw.strip_prefix(op.as_str()).map(|rest| (op, rest)))
+        .unwrap_or((VersionOp::Exactly, raw));
+    let version = version.trim();
+    let components = version_components(version)
+        .filter(|components| {
+            components
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(".")
+                == version.trim_start_matches(['v', 'V'])
+        })
+        .ok_or_else(|| {
+            GleanMcpError::Config(format!(
+                "Invalid server version requirement '{raw}' (expected e.g. >=2.4, <3, =2.4.1)"
+            ))
+        })?;
+    Ok(VersionRequirement {
+        op,
+        version: components,
+    })
+}
@@ -0,0 +1,69 @@
+//! Splitting a test run across CI runners
+//!
+//! `--shard 2/5` runs the second of five parts of the run. Tools are dealt
+//! round-robin in name order, so every runner that sees the same `tools/list`
+//! computes the same split, and a tool stays in the shard of the tools it
+//! depends on. Each tool lands in exactly one shard, so `aggregate` over all
+//! shards' results covers the whole run once.
+
+use crate::{GleanMcpError, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fmt;
+
+/// One part of a run split `count` ways, numbered from 1
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Shard {
+    pub index: usize,
+    pub count: usize,
+}
+
+impl Shard {
+    /// The items of this shard
+    ///
+    /// Items are grouped by `group` (e.g. a tool's dependency root), groups
+    /// are dealt round-robin in sorted order, and each item follows its group.
+    #[must_use]
+    pub fn select<T>(self, items: Vec<T>, group: impl Fn(&T) -> String) -> Vec<T> {
+        let groups: BTreeSet<String> = items.iter().map(&group).collect();
+        let ours: BTreeSet<&String> = groups
+            .iter()
+            .enumerate()
+            .filter(|(position, _)| position % self.count == self.index - 1)
+            .map(|(_, name)| name)
+            .collect();
+        items
+            .into_iter()
+            .filter(|item| ours.contains(&group(item)))
+            .collect()
+    }
+}
+
+impl fmt::Display for Shard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.index, self.count)
+    }
+}
+
+/// Parse `INDEX/COUNT`, as given to `--shard`
+pub fn parse_shard(raw: &str) -> Result<Shard> {
+    let raw = raw.trim();
+    let invalid = |reason: &str| {
+        GleanMcpError::Config(format!(
+            "Invalid shard '{raw}' ({reason}, expected INDEX/COUNT, e.g. 2/5)"
+        ))
+    };
+    let (index, count) = raw.split_once('/').ok_or_else(|| invalid("missing '/'"))?;
+    let index: usize = index
+        .trim()
+        .parse()
+        .map_err(|_| invalid("index is not a number"))?;
+    let count: usize = count
+        .trim()
+        .parse()
+        .map_err(|_| invalid("count is not a number"))?;
+    if count == 0 || index == 0 || index > count {
+        return Err(invalid("index must be between 1 and the count"));
+    }
+    Ok(Shard { index, count })
+}
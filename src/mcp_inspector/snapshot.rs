@@ -0,0 +1,454 @@
+//! Golden-response snapshots
+//!
+//! `test --snapshot` normalizes each tool response and stores it as
+//! `<dir>/<instance>/<endpoint>/<tool>.json` the first time, then compares
+//! later responses against it. Normalizing expands JSON embedded in text
+//! blocks and strips fields matched by the configured masks, a small
+//! `JSONPath` subset (`$.a.b`, `$..id`, `[*]`, `[0]`, `.*`). In `shape` mode
+//! only keys and value types are compared, so ordinary content changes pass
+//! while renamed, removed, or retyped fields fail the tool.
+
+use crate::{GleanMcpError, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::fmt::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// Differences listed in a failure message before the rest are summarized
+const MAX_REPORTED: usize = 5;
+
+/// What a snapshot comparison looks at
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SnapshotMode {
+    /// Keys and value types; array elements are merged into one shape
+    #[default]
+    Shape,
+    /// Keys, types, array lengths, and values
+    Exact,
+}
+
+/// Snapshot storage and normalization settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SnapshotConfig {
+    /// Directory snapshots are stored under, one subdirectory per instance
+    pub dir: String,
+    pub mode: SnapshotMode,
+    /// `JSONPath` expressions of volatile fields removed before storing and comparing
+    pub masks: Vec<String>,
+}
+
+impl Default for SnapshotConfig {
+    fn default() -> Self {
+        Self {
+            dir: ".glean-mcp-test/snapshots".to_string(),
+            mode: SnapshotMode::Shape,
+            masks: [
+                "$..id",
+                "$..requestId",
+                "$..trackingToken",
+                "$..sessionId",
+                "$..timestamp",
+                "$..createTime",
+                "$..updateTime",
+                "$..lastUpdated",
+            ]
+            .map(str::to_string)
+            .to_vec(),
+        }
+    }
+}
+
+/// Snapshot comparison applied to every successful tool result of a run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotCheck {
+    /// Snapshot directory for the instance under test
+    pub dir: PathBuf,
+    pub mode: SnapshotMode,
+    pub masks: Vec<String>,
+    /// Overwrite existing snapshots instead of comparing against them
+    pub update: bool,
+}
+
+/// One structural difference between a snapshot and a response
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotDiff {
+    /// Location in the normalized response, e.g. `$.content[*].text.documents`
+    pub path: String,
+    pub kind: SnapshotDiffKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "change")]
+pub enum SnapshotDiffKind {
+    Added,
+    Removed,
+    TypeChanged { expected: String, actual: String },
+    LengthChanged { expected: usize, actual: usize },
+    ValueChanged { expected: String, actual: String },
+}
+
+impl fmt::Display for SnapshotDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            SnapshotDiffKind::Added => write!(f, "{}: new field", self.path),
+            SnapshotDiffKind::Removed => write!(f, "{}: missing", self.path),
+            SnapshotDiffKind::TypeChanged { expected, actual }
+            | SnapshotDiffKind::ValueChanged { expected, actual } => {
+                write!(f, "{}: {expected} became {actual}", self.path)
+            }
+            SnapshotDiffKind::LengthChanged { expected, actual } => {
+                write!(f, "{}: {expected} items became {actual}", self.path)
+            }
+        }
+    }
+}
+
+impl SnapshotCheck {
+    /// Compare against (or record) snapshots for `instance` under `config.dir`
+    #[must_use]
+    pub fn new(config: &SnapshotConfig, instance: &str, update: bool) -> Self {
+        Self {
+            dir: Path::new(&config.dir).join(instance),
+            mode: config.mode,
+            masks: config.masks.clone(),
+            update,
+        }
+    }
+
+    /// Snapshot file for `tool_name` on the endpoint at `endpoint_url`
+    #[must_use]
+    pub fn path(&self, tool_name: &str, endpoint_url: &str) -> PathBuf {
+        let endpoint = endpoint_url
+            .trim_end_matches('/')
+            .rsplit('/')
+            .next()
+            .unwrap_or("default");
+        self.dir.join(endpoint).join(format!("{tool_name}.json"))
+    }
+
+    /// Check `response` from `tool_name` against its snapshot, writing it when missing or updating.
+    ///
+    /// Returns `Ok(Some(note))` when the response matches or was recorded,
+    /// and `Err` listing the differences.
+    pub fn check(
+        &self,
+        tool_name: &str,
+        endpoint_url: &str,
+        response: &Value,
+    ) -> std::result::Result<Option<String>, String> {
+        let masks = self
+            .masks
+            .iter()
+            .map(|mask| parse_mask(mask))
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        let normalized = normalize(response, &masks);
+        let path = self.path(tool_name, endpoint_url);
+
+        if self.update || !path.exists() {
+            let verb = if path.exists() { "Updated" } else { "Recorded" };
+            write_snapshot(&path, &normalized)
+                .map_err(|e| format!("Could not write snapshot {}: {e}", path.display()))?;
+            return Ok(Some(format!("{verb} snapshot {}", path.display())));
+        }
+
+        let expected: Value = std::fs::read_to_string(&path)
+            .map_err(GleanMcpError::from)
+            .and_then(|content| serde_json::from_str(&content).map_err(GleanMcpError::from))
+            .map_err(|e| format!("Could not read snapshot {}: {e}", path.display()))?;
+        let diffs = diff(&expected, &normalized, self.mode);
+        if diffs.is_empty() {
+            return Ok(Some(format!("Matches snapshot {}", path.display())));
+        }
+
+        let mut message = format!(
+            "Response differs from snapshot {} ({} difference(s))",
+            path.display(),
+            diffs.len()
+        );
+        for diff in diffs.iter().take(MAX_REPORTED) {
+            let _ = write!(message, "\n- {diff}");
+        }
+        if diffs.len() > MAX_REPORTED {
+            let _ = write!(message, "\n- ... and {} more", diffs.len() - MAX_REPORTED);
+        }
+        let _ = write!(
+            message,
+            "\nRun with --update-snapshots if the change is expected"
+        );
+        Err(message)
+    }
+}
+
+fn write_snapshot(path: &Path, value: &Value) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, serde_json::to_string_pretty(value)? + "\n")?;
+    Ok(())
+}
+
+/// One step of a mask: which children it selects, and whether at any depth
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct MaskStep {
+    descendant: bool,
+    selector: Selector,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Selector {
+    Key(String),
+    Index(usize),
+    Any,
+}
+
+/// A step in a concrete location inside a response
+#[derive(Debug, Clone)]
+enum Segment {
+    Key(String),
+    Index(usize),
+}
+
+/// Check that `mask` parses, e.g. `$..metadata.updateTime` or `$.results[*].id`
+pub fn validate_mask(mask: &str) -> std::result::Result<(), String> {
+    parse_mask(mask).map(|_| ())
+}
+
+fn parse_mask(mask: &str) -> std::result::Result<Vec<MaskStep>, String> {
+    let invalid = |reason: &str| format!("Invalid mask '{mask}': {reason}");
+    let mut rest = mask
+        .trim()
+        .strip_prefix('$')
+        .ok_or_else(|| invalid("must start with '$'"))?;
+    let mut steps = Vec::new();
+    while !rest.is_empty() {
+        let descendant = rest.starts_with("..");
+        if descendant {
+            rest = &rest[2..];
+        } else if let Some(after) = rest.strip_prefix('.') {
+            rest = after;
+        }
+        if let Some(after) = rest.strip_prefix('[') {
+            let (inner, after) = after
+                .split_once(']')
+                .ok_or_else(|| invalid("unclosed '['"))?;
+            let inner = inner.trim();
+            let selector = if inner == "*" {
+                Selector::Any
+            } else if let Ok(index) = inner.parse() {
+                Selector::Index(index)
+            } else {
+                let key = inner.trim_matches(|c| c == '\'' || c == '"');
+                if key.is_empty() {
+                    return Err(invalid("empty key"));
+                }
+                Selector::Key(key.to_string())
+            };
+            steps.push(MaskStep {
+                descendant,
+                selector,
+            });
+            rest = after;
+        } else {
+            let end = rest.find(['.', '[']).unwrap_or(rest.len());
+            let name = &rest[..end];
+            if name.is_empty() {
+                return Err(invalid("empty key"));
+            }
+            steps.push(MaskStep {
+                descendant,
+                selector: if name == "*" {
+                    Selector::Any
+                } else {
+                    Selector::Key(name.to_string())
+                },
+            });
+            rest = &rest[end..];
+        }
+    }
+    if steps.is_empty() {
+        return Err(invalid("masks the whole response"));
+    }
+    Ok(steps)
+}
+
+fn matches_path(steps: &[MaskStep], path: &[Segment]) -> bool {
+    let Some((step, rest)) = steps.split_first() else {
+        return path.is_empty();
+    };
+    let selects = |segment: &Segment| match (&step.selector, segment) {
+        (Selector::Any, _) => true,
+        (Selector::Key(key), Segment::Key(name)) => key == name,
+        (Selector::Index(index), Segment::Index(i)) => index == i,
+        _ => false,
+    };
+    if step.descendant {
+        (0..path.len()).any(|start| selects(&path[start]) && matches_path(rest, &path[start + 1..]))
+    } else {
+        path.first().is_some_and(selects) && matches_path(rest, &path[1..])
+    }
+}
+
+/// `response` with JSON embedded in strings expanded and masked fields removed
+fn normalize(response: &Value, masks: &[Vec<MaskStep>]) -> Value {
+    fn walk(value: &Value, path: &mut Vec<Segment>, masks: &[Vec<MaskStep>]) -> Value {
+        let masked = |path: &[Segment]| masks.iter().any(|mask| matches_path(mask, path));
+        match value {
+            Value::Object(object) => {
+                let mut normalized = Map::new();
+                for (key, child) in object {
+                    path.push(Segment::Key(key.clone()));
+                    if !masked(path) {
+                        normalized.insert(key.clone(), walk(child, path, masks));
+                    }
+                    path.pop();
+                }
+                Value::Object(normalized)
+            }
+            Value::Array(items) => {
+                let mut normalized = Vec::new();
+                for (index, child) in items.iter().enumerate() {
+                    path.push(Segment::Index(index));
+                    if !masked(path) {
+                        normalized.push(walk(child, path, masks));
+                    }
+                    path.pop();
+                }
+                Value::Array(normalized)
+            }
+            Value::String(text) => embedded_json(text)
+                .map_or_else(|| value.clone(), |embedded| walk(&embedded, path, masks)),
+            _ => value.clone(),
+        }
+    }
+
+    walk(response, &mut Vec::new(), masks)
+}
+
+/// The object or array a string holds, if it is one
+fn embedded_json(text: &str) -> Option<Value> {
+    let trimmed = text.trim_start();
+    if !trimmed.starts_with('{') && !trimmed.starts_with('[') {
+        return None;
+    }
+    serde_json::from_str::<Value>(text)
+        .ok()
+        .filter(|value| value.is_object() || value.is_array())
+}
+
+/// Structural differences from `expected` to `actual`
+#[must_use]
+pub fn diff(expected: &Value, actual: &Value, mode: SnapshotMode) -> Vec<SnapshotDiff> {
+    let mut diffs = Vec::new();
+    diff_at("$", expected, actual, mode, &mut diffs);
+    diffs
+}
+
+fn diff_at(
+    path: &str,
+    expected: &Value,
+    actual: &Value,
+    mode: SnapshotMode,
+    diffs: &mut Vec<SnapshotDiff>,
+) {
+    let mut push = |path: String, kind| diffs.push(SnapshotDiff { path, kind });
+    match (expected, actual) {
+        (Value::Object(expected), Value::Object(actual)) => {
+            for (key, expected_child) in expected {
+                let child_path = format!("{path}.{key}");
+                match actual.get(key) {
+                    Some(actual_child) => {
+                        diff_at(&child_path, expected_child, actual_child, mode, diffs);
+                    }
+                    None => diffs.push(SnapshotDiff {
+                        path: child_path,
+                        kind: SnapshotDiffKind::Removed,
+                    }),
+                }
+            }
+            for key in actual.keys().filter(|key| !expected.contains_key(*key)) {
+                diffs.push(SnapshotDiff {
+                    path: format!("{path}.{key}"),
+                    kind: SnapshotDiffKind::Added,
+                });
+            }
+        }
+        (Value::Array(expected), Value::Array(actual)) => match mode {
+            SnapshotMode::Shape => {
+                // Result counts vary; compare one merged element shape when both have items
+                if let (Some(expected), Some(actual)) = (merged(expected), merged(actual)) {
+                    diff_at(&format!("{path}[*]"), &expected, &actual, mode, diffs);
+                }
+            }
+            SnapshotMode::Exact => {
+                if expected.len() != actual.len() {
+                    push(
+                        path.to_string(),
+                        SnapshotDiffKind::LengthChanged {
+                            expected: expected.len(),
+                            actual: actual.len(),
+                        },
+                    );
+                }
+                for (index, (expected, actual)) in expected.iter().zip(actual).enumerate() {
+                    diff_at(&format!("{path}[{index}]"), expected, actual, mode, diffs);
+                }
+            }
+        },
+        _ if type_name(expected) != type_name(actual) => push(
+            path.to_string(),
+            SnapshotDiffKind::TypeChanged {
+                expected: type_name(expected).to_string(),
+                actual: type_name(actual).to_string(),
+            },
+        ),
+        _ if mode == SnapshotMode::Exact && expected != actual => push(
+            path.to_string(),
+            SnapshotDiffKind::ValueChanged {
+                expected: expected.to_string(),
+                actual: actual.to_string(),
+            },
+        ),
+        _ => {}
+    }
+}
+
+/// All elements folded into one: objects union their keys, arrays their items
+fn merged(items: &[Value]) -> Option<Value> {
+    let mut items = items.iter();
+    let first = items.next()?.clone();
+    Some(items.fold(first, merge))
+}
+
+fn merge(acc: Value, item: &Value) -> Value {
+    match (acc, item) {
+        (Value::Object(mut acc), Value::Object(item)) => {
+            for (key, value) in item {
+                let merged = acc
+                    .remove(key)
+                    .map_or_else(|| value.clone(), |existing| merge(existing, value));
+                acc.insert(key.clone(), merged);
+            }
+            Value::Object(acc)
+        }
+        (Value::Array(mut acc), Value::Array(item)) => {
+            acc.extend(item.iter().cloned());
+            Value::Array(acc)
+        }
+        // Nulls stand in for optional values; prefer the typed one
+        (Value::Null, item) => item.clone(),
+        (acc, _) => acc,
+    }
+}
+
+const fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
@@ -0,0 +1,286 @@
+//! Concurrent stress test of a single MCP session
+//!
+//! [`GleanMCPInspector::stress_session`] initializes one session, then sends
+//! many overlapping `tools/call` requests in it, each with its own id. Every
+//! request must be answered with its own id: a response carrying another
+//! request's id means the server mixed up concurrent calls, and a request
+//! left without a JSON-RPC response was dropped.
+
+use super::{GleanMCPInspector, McpTransport, ToolInfo, arguments_from_schema};
+use crate::{RunOutcome, TestQueryGenerator};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::time::Instant;
+
+/// A response that came back with the wrong id
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StressMismatch {
+    pub request_id: u64,
+    /// `id` of the response; `null` when it had none
+    pub response_id: Value,
+}
+
+/// Results of a concurrent stress run in one session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StressTestResult {
+    pub success: bool,
+    pub endpoint: String,
+    /// `Mcp-Session-Id` assigned by the server; `None` for stateless servers
+    pub session_id: Option<String>,
+    pub tool: String,
+    pub total_requests: usize,
+    /// Requests answered with their own id
+    pub correlated: usize,
+    /// Correlated responses that were JSON-RPC errors
+    pub error_responses: usize,
+    pub mismatched: Vec<StressMismatch>,
+    /// Requests without a JSON-RPC response
+    pub dropped: usize,
+    /// Why requests were dropped, with counts
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub drop_reasons: BTreeMap<String, usize>,
+    pub duration_ms: u64,
+    pub error: Option<String>,
+}
+
+impl StressTestResult {
+    /// How the run ended, from CI's point of view
+    #[must_use]
+    pub const fn outcome(&self) -> RunOutcome {
+        if self.success {
+            RunOutcome::Success
+        } else if self.correlated == 0 && self.mismatched.is_empty() {
+            RunOutcome::ConnectivityFailure
+        } else {
+            RunOutcome::ValidationFailure
+        }
+    }
+
+    #[must_use]
+    pub fn format_text(&self) -> String {
+        let mut output = String::new();
+        if let Some(error) = &self.error {
+            let _ = writeln!(output, "🔀 Session stress ({})", self.endpoint);
+            let _ = writeln!(output, "Error: {error}");
+            return output;
+        }
+        let _ = writeln!(
+            output,
+            "🔀 Session stress: {} overlapping calls to {} ({})",
+            self.total_requests, self.tool, self.endpoint
+        );
+        let _ = writeln!(
+            output,
+            "  Session: {}",
+            self.session_id
+                .as_deref()
+                .unwrap_or("none (stateless server)")
+        );
+        #[allow(clippy::cast_precision_loss)]
+        let seconds = self.duration_ms as f64 / 1000.0;
+        let _ = writeln!(
+            output,
+            "  Correlated: {}/{} ({} JSON-RPC errors) in {seconds:.2}s",
+            self.correlated, self.total_requests, self.error_responses
+        );
+        let _ = writeln!(output, "  Mismatched ids: {}", self.mismatched.len());
+        for mismatch in &self.mismatched {
+            let _ = writeln!(
+                output,
+                "    - request {} answered with id {}",
+                mismatch.request_id, mismatch.response_id
+            );
+        }
+        let _ = writeln!(output, "  Dropped: {}", self.dropped);
+        for (reason, count) in &self.drop_reasons {
+            let _ = writeln!(output, "    - {reason} ({count}x)");
+        }
+        output
+    }
+}
+
+/// What happened to one request
+enum CallOutcome {
+    Correlated { error: bool },
+    Mismatched(Value),
+    Dropped(String),
+}
+
+async fn stress_call(
+    transport: &McpTransport,
+    endpoint: &str,
+    id: u64,
+    tool: &str,
+    arguments: &Value,
+) -> CallOutcome {
+    let request = json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "method": "tools/call",
+        "params": { "name": tool, "arguments": arguments }
+    });
+    let response = match transport.post_json_rpc(endpoint, &request).await {
+        Ok(response) if response.success => response,
+        Ok(_) => return CallOutcome::Dropped("No HTTP response".to_string()),
+        Err(e) => return CallOutcome::Dropped(e.to_string()),
+    };
+    if let Some(status) = response.status.filter(|status| *status >= 400) {
+        return CallOutcome::Dropped(format!("HTTP {status}"));
+    }
+    let Ok(body) = serde_json::from_str::<Value>(&response.body) else {
+        return CallOutcome::Dropped("Response is not JSON-RPC".to_string());
+    };
+
+    match body.get("id") {
+        Some(response_id) if *response_id == json!(id) => CallOutcome::Correlated {
+            error: body.get("error").is_some(),
+        },
+        response_id => CallOutcome::Mismatched(response_id.cloned().unwrap_or(Value::Null)),
+    }
+}
+
+impl GleanMCPInspector {
+    /// Open one session on the default endpoint and send `requests` overlapping `tools/call`s in it
+    ///
+    /// Calls go to `search` when the server lists it, else the first listed tool.
+    #[allow(clippy::cast_possible_truncation)]
+    pub async fn stress_session(&self, requests: usize) -> StressTestResult {
+        let started = Instant::now();
+        let (session_id, transport, _) = match self.open_session().await {
+            Ok(session) => session,
+            Err(error) => {
+                return StressTestResult {
+                    success: false,
+                    endpoint: self.server_url().to_string(),
+                    session_id: None,
+                    tool: String::new(),
+                    total_requests: requests,
+                    correlated: 0,
+                    error_responses: 0,
+                    mismatched: Vec::new(),
+                    dropped: 0,
+                    drop_reasons: BTreeMap::new(),
+                    duration_ms: started.elapsed().as_millis() as u64,
+                    error: Some(error),
+                };
+            }
+        };
+
+        let tools = self
+            .list_tools_with_transport(&transport, self.server_url(), false)
+            .await
+            .ok()
+            .and_then(|listed| listed.inspector_data)
+            .map(|data| ToolInfo::from_tools_list(&data))
+            .unwrap_or_default();
+        let target = tools
+            .iter()
+            .find(|tool| tool.name == "search")
+            .or_else(|| tools.first());
+        let tool = target.map_or("search", |tool| tool.name.as_str());
+        let arguments = target
+            .and_then(|tool| tool.schema.as_ref())
+            .and_then(|schema| arguments_from_schema(tool, schema))
+            .unwrap_or_else(|| {
+                TestQueryGenerator::generate_arguments(
+                    tool,
+                    &TestQueryGenerator::generate_test_query(tool),
+                )
+            });
+
+        self.reporter().info(&format!(
+            "Sending {requests} overlapping calls to {tool} in session {}",
+            session_id.as_deref().unwrap_or("(none)")
+        ));
+        let started = Instant::now();
+        // Ids start after initialize (0) and tools/list (1)
+        let outcomes = futures::future::join_all(
+            (0..requests as u64)
+                .map(|i| stress_call(&transport, self.server_url(), i + 2, tool, &arguments)),
+        )
+        .await;
+        let duration_ms = started.elapsed().as_millis() as u64;
+
+        let mut correlated = 0;
+        let mut error_responses = 0;
+        let mut mismatched = Vec::new();
+        let mut drop_reasons = BTreeMap::<String, usize>::new();
+        for (request_id, outcome) in (2_u64..).zip(outcomes) {
+            match outcome {
+                CallOutcome::Correlated { error } => {
+                    correlated += 1;
+                    error_responses += usize::from(error);
+                }
+                CallOutcome::Mismatched(response_id) => mismatched.push(StressMismatch {
+                    request_id,
+                    response_id,
+                }),
+                CallOutcome::Dropped(reason) => *drop_reasons.entry(reason).or_default() += 1,
+            }
+        }
+        let dropped = drop_reasons.values().sum();
+
+        StressTestResult {
+            success: requests > 0 && correlated == requests,
+            endpoint: self.server_url().to_string(),
+            session_id,
+            tool: tool.to_string(),
+            total_requests: requests,
+            correlated,
+            error_responses,
+            mismatched,
+            dropped,
+            drop_reasons,
+            duration_ms,
+            error: None,
+        }
+    }
+
+    /// Send `initialize` and `notifications/initialized`, returning the session id
+    /// the server assigned, if any, a transport that sends it, and the `initialize` result
+    pub(crate) async fn open_session(
+        &self,
+    ) -> std::result::Result<(Option<String>, McpTransport, Value), String> {
+        let initialize = json!({
+            "jsonrpc": "2.0",
+            "id": 0,
+            "method": "initialize",
+            "params": {
+                "protocolVersion": "2025-03-26",
+                "capabilities": {},
+                "clientInfo": { "name": "glean-mcp-test", "version": env!("CARGO_PKG_VERSION") }
+            }
+        });
+        let response = self
+            .transport()
+            .post_json_rpc(self.server_url(), &initialize)
+            .await
+            .map_err(|e| format!("initialize failed: {e}"))?;
+        if !response.success {
+            return Err(format!("initialize failed: {}", response.stderr.trim()));
+        }
+        if let Some(status) = response.status.filter(|status| *status >= 400) {
+            return Err(format!("initialize failed with HTTP {status}"));
+        }
+        let body: Value = serde_json::from_str(&response.body)
+            .map_err(|e| format!("initialize returned invalid JSON: {e}"))?;
+        if let Some(error) = body.get("error") {
+            return Err(format!("initialize returned an error: {error}"));
+        }
+
+        let session_id = response.header("mcp-session-id").map(ToString::to_string);
+        let mut transport = self.transport().clone();
+        if let Some(id) = &session_id {
+            transport = transport.with_session_id(id);
+        }
+        let initialized = json!({ "jsonrpc": "2.0", "method": "notifications/initialized" });
+        // Notifications get no JSON-RPC response, so there is nothing to check
+        let _ = transport
+            .post_json_rpc(self.server_url(), &initialized)
+            .await;
+        let result = body.get("result").cloned().unwrap_or(Value::Null);
+        Ok((session_id, transport, result))
+    }
+}
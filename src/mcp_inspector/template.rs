@@ -0,0 +1,225 @@
+//! Tool arguments filled in from earlier responses
+//!
+//! Configured arguments may reference another tool's response, as in
+//! `url: "{{search.results[0].url}}"`. The referenced tool becomes a
+//! dependency of the one whose arguments use it, so it runs first, and the
+//! template is replaced with the value at the path in its response.
+//! `results` is the tool's search results as `{title, url, snippet}`
+//! objects, whatever shape the server answers in; other paths are looked up
+//! in the response itself, with JSON embedded in text blocks expanded.
+
+use super::{response_text, search_results};
+use serde_json::{Value, json};
+use std::collections::BTreeMap;
+
+const OPEN: &str = "{{";
+const CLOSE: &str = "}}";
+
+/// One step of a template path
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Step {
+    Key(String),
+    Index(usize),
+}
+
+/// Each `{{...}}` in `text`, as its byte range and trimmed expression
+fn expressions(text: &str) -> Vec<(usize, usize, &str)> {
+    let mut found = Vec::new();
+    let mut offset = 0;
+    while let Some(start) = text[offset..].find(OPEN).map(|start| offset + start) {
+        let inner_start = start + OPEN.len();
+        let Some(inner_len) = text[inner_start..].find(CLOSE) else {
+            break;
+        };
+        let end = inner_start + inner_len + CLOSE.len();
+        found.push((
+            start,
+            end,
+            text[inner_start..inner_start + inner_len].trim(),
+        ));
+        offset = end;
+    }
+    found
+}
+
+/// Split `search.results[0].url` into the tool name and its path
+fn split_expression(expression: &str) -> (&str, &str) {
+    let end = expression.find(['.', '[']).unwrap_or(expression.len());
+    (&expression[..end], &expression[end..])
+}
+
+fn parse_path(expression: &str) -> std::result::Result<Vec<Step>, String> {
+    let invalid = |reason: &str| format!("Invalid template '{{{{{expression}}}}}': {reason}");
+    let (tool, mut rest) = split_expression(expression);
+    if tool.is_empty() {
+        return Err(invalid("must start with a tool name"));
+    }
+    let mut steps = Vec::new();
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix('[') {
+            let (index, after) = after
+                .split_once(']')
+                .ok_or_else(|| invalid("unclosed '['"))?;
+            let index = index
+                .trim()
+                .parse()
+                .map_err(|_| invalid("indexes must be numbers"))?;
+            steps.push(Step::Index(index));
+            rest = after;
+        } else {
+            let after = rest.strip_prefix('.').unwrap_or(rest);
+            let end = after.find(['.', '[']).unwrap_or(after.len());
+            if end == 0 {
+                return Err(invalid("empty key"));
+            }
+            steps.push(Step::Key(after[..end].to_string()));
+            rest = &after[end..];
+        }
+    }
+    if steps.is_empty() {
+        return Err(invalid("must name a field, e.g. {{search.results[0].url}}"));
+    }
+    Ok(steps)
+}
+
+fn for_each_string<'a>(value: &'a Value, f: &mut impl FnMut(&'a str)) {
+    match value {
+        Value::String(text) => f(text),
+        Value::Array(items) => items.iter().for_each(|item| for_each_string(item, f)),
+        Value::Object(object) => object.values().for_each(|item| for_each_string(item, f)),
+        _ => {}
+    }
+}
+
+/// Tools whose responses `arguments` reference, in order of first use
+#[must_use]
+pub fn template_references(arguments: &Value) -> Vec<&str> {
+    let mut tools: Vec<&str> = Vec::new();
+    for_each_string(arguments, &mut |text| {
+        for (_, _, expression) in expressions(text) {
+            let (tool, _) = split_expression(expression);
+            if !tool.is_empty() && !tools.contains(&tool) {
+                tools.push(tool);
+            }
+        }
+    });
+    tools
+}
+
+/// Check that every template in `arguments` parses
+pub fn validate_templates(arguments: &Value) -> std::result::Result<(), String> {
+    let mut problem = None;
+    for_each_string(arguments, &mut |text| {
+        for (_, _, expression) in expressions(text) {
+            if let Err(e) = parse_path(expression) {
+                problem.get_or_insert(e);
+            }
+        }
+    });
+    problem.map_or(Ok(()), Err)
+}
+
+/// `arguments` with every template replaced from `responses`, keyed by tool name
+///
+/// A string that is a single template takes the referenced value as is, so
+/// numbers and objects keep their type; templates inside longer strings are
+/// spliced in as text. Fails naming the first template that can't be filled.
+pub fn render_arguments(
+    arguments: &Value,
+    responses: &BTreeMap<&str, &Value>,
+) -> std::result::Result<Value, String> {
+    match arguments {
+        Value::String(text) => render_string(text, responses),
+        Value::Array(items) => items
+            .iter()
+            .map(|item| render_arguments(item, responses))
+            .collect::<std::result::Result<_, _>>()
+            .map(Value::Array),
+        Value::Object(object) => object
+            .iter()
+            .map(|(key, item)| Ok((key.clone(), render_arguments(item, responses)?)))
+            .collect::<std::result::Result<_, String>>()
+            .map(Value::Object),
+        _ => Ok(arguments.clone()),
+    }
+}
+
+fn render_string(
+    text: &str,
+    responses: &BTreeMap<&str, &Value>,
+) -> std::result::Result<Value, String> {
+    let found = expressions(text);
+    if let [(start, end, expression)] = found[..]
+        && text[..start].trim().is_empty()
+        && text[end..].trim().is_empty()
+    {
+        return resolve(expression, responses);
+    }
+
+    let mut rendered = String::new();
+    let mut offset = 0;
+    for (start, end, expression) in found {
+        rendered.push_str(&text[offset..start]);
+        match resolve(expression, responses)? {
+            Value::String(value) => rendered.push_str(&value),
+            value => rendered.push_str(&value.to_string()),
+        }
+        offset = end;
+    }
+    rendered.push_str(&text[offset..]);
+    Ok(Value::String(rendered))
+}
+
+fn resolve(
+    expression: &str,
+    responses: &BTreeMap<&str, &Value>,
+) -> std::result::Result<Value, String> {
+    let steps = parse_path(expression)?;
+    let (tool, _) = split_expression(expression);
+    let response = responses
+        .get(tool)
+        .ok_or_else(|| format!("{{{{{expression}}}}}: {tool} has no response"))?;
+    lookup(response, &steps)
+        .ok_or_else(|| format!("{{{{{expression}}}}} matched nothing in {tool}'s response"))
+}
+
+/// Value at `steps` in `response`
+fn lookup(response: &Value, steps: &[Step]) -> Option<Value> {
+    let (first, rest) = steps.split_first()?;
+    let mut current = match first {
+        Step::Key(key) if key == "results" => Value::Array(
+            search_results(response)
+                .into_iter()
+                .map(
+                    |item| json!({ "title": item.title, "url": item.url, "snippet": item.snippet }),
+                )
+                .collect(),
+        ),
+        // Glean's MCP server answers with JSON inside text content blocks
+        _ => step_into(response, first)
+            .or_else(|| step_into(&expand(&Value::String(response_text(response)))?, first))?,
+    };
+    for step in rest {
+        current = step_into(&current, step)?;
+    }
+    Some(current).filter(|value| !value.is_null())
+}
+
+fn step_into(value: &Value, step: &Step) -> Option<Value> {
+    let value = expand(value).unwrap_or_else(|| value.clone());
+    match step {
+        Step::Key(key) => value.get(key).cloned(),
+        Step::Index(index) => value.get(index).cloned(),
+    }
+}
+
+/// The object or array a string holds, if it is one
+fn expand(value: &Value) -> Option<Value> {
+    let text = value.as_str()?.trim_start();
+    if !text.starts_with('{') && !text.starts_with('[') {
+        return None;
+    }
+    serde_json::from_str::<Value>(text)
+        .ok()
+        .filter(|value| value.is_object() || value.is_array())
+}
@@ -0,0 +1,80 @@
+//! TLS trust and client certificates for MCP requests
+//!
+//! [`TlsConfig`] adds a custom CA bundle, for endpoints behind corporate TLS
+//! interception, and a client certificate and key, for mTLS-protected
+//! gateways, to every curl call.
+
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// TLS settings (`tls` section of the config file)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TlsConfig {
+    /// PEM bundle of CA certificates to trust instead of the system store
+    pub ca_cert: Option<PathBuf>,
+    /// PEM client certificate presented to the server
+    pub client_cert: Option<PathBuf>,
+    /// PEM private key for `client_cert` (default: read from `client_cert`)
+    pub client_key: Option<PathBuf>,
+}
+
+impl TlsConfig {
+    /// Trust the CA certificates in `path`
+    #[must_use]
+    pub fn with_ca_cert(mut self, path: impl Into<PathBuf>) -> Self {
+        self.ca_cert = Some(path.into());
+        self
+    }
+
+    /// Present the client certificate in `path`
+    #[must_use]
+    pub fn with_client_cert(mut self, path: impl Into<PathBuf>) -> Self {
+        self.client_cert = Some(path.into());
+        self
+    }
+
+    /// Use the private key in `path` for the client certificate
+    #[must_use]
+    pub fn with_client_key(mut self, path: impl Into<PathBuf>) -> Self {
+        self.client_key = Some(path.into());
+        self
+    }
+
+    /// Problems that would make every request fail, e.g. a key without a certificate
+    #[must_use]
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        let paths = [
+            ("tls.ca_cert", &self.ca_cert),
+            ("tls.client_cert", &self.client_cert),
+            ("tls.client_key", &self.client_key),
+        ];
+        for (name, path) in paths {
+            if path.as_ref().is_some_and(|p| p.as_os_str().is_empty()) {
+                problems.push(format!("{name} must not be empty"));
+            }
+        }
+        if self.client_key.is_some() && self.client_cert.is_none() {
+            problems.push("tls.client_key requires tls.client_cert".to_string());
+        }
+        problems
+    }
+
+    /// curl arguments for the CA bundle and client certificate, if any
+    #[must_use]
+    pub fn curl_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        let flags = [
+            ("--cacert", &self.ca_cert),
+            ("--cert", &self.client_cert),
+            ("--key", &self.client_key),
+        ];
+        for (flag, path) in flags {
+            if let Some(path) = path {
+                args.extend([flag.to_string(), path.display().to_string()]);
+            }
+        }
+        args
+    }
+}
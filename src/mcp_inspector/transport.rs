@@ -0,0 +1,935 @@
+//! HTTP transport for MCP JSON-RPC requests
+//!
+//! All JSON-RPC traffic to MCP endpoints goes through [`McpTransport`], which
+//! shells out to curl, optionally records or replays interactions through
+//! a [`Cassette`], optionally paces live requests through a [`RateLimiter`],
+//! optionally reports their statuses to an [`AdaptiveConcurrency`] limit,
+//! optionally checks responses through a [`ConformanceChecker`], and
+//! optionally injects faults through [`Chaos`]. Every live request goes
+//! through the proxy its [`ProxyConfig`] resolves, or directly, trusting and
+//! presenting the certificates its [`TlsConfig`] names, with any custom
+//! headers (see [`parse_header`]) added.
+//!
+//! Request bodies are written to curl's stdin rather than passed as
+//! arguments, so their size and content never reach the command line.
+
+use super::capture::TranscriptCapture;
+use super::cassette::{Cassette, CassetteMode};
+use super::chaos::Chaos;
+use super::concurrency::AdaptiveConcurrency;
+use super::conformance::ConformanceChecker;
+use super::error_detail::{ErrorDetail, request_ids};
+use super::proxy::ProxyConfig;
+use super::rate_limit::RateLimiter;
+use super::tls::TlsConfig;
+use crate::{GleanMcpError, Result};
+use async_process::Command;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use smol::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use smol::stream::StreamExt;
+use std::collections::BTreeMap;
+use std::process::Stdio;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// Raw outcome of a transport call
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RawResponse {
+    /// Whether the underlying request completed (curl exit status)
+    pub success: bool,
+    /// Response body
+    pub body: String,
+    /// Transport-level error output
+    pub stderr: String,
+    /// HTTP status of the final response, when one was received
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<u16>,
+    /// Response headers, keyed by lowercased name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub headers: BTreeMap<String, String>,
+    /// Connection phase timings, when curl reported them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timing: Option<ConnectionTiming>,
+    /// `X-Correlation-Id` the request was sent with; not recorded to cassettes
+    #[serde(skip)]
+    pub correlation_id: Option<String>,
+}
+
+/// What the transport reports about the last request of a tool call
+#[derive(Debug, Clone, Default)]
+pub struct RequestTrace {
+    pub timing: Option<ConnectionTiming>,
+    pub correlation_id: Option<String>,
+    /// What the server answered, when it answered with an error
+    pub error_detail: Option<ErrorDetail>,
+    /// Request and trace id headers of the response
+    pub request_ids: BTreeMap<String, String>,
+}
+
+impl RequestTrace {
+    #[must_use]
+    pub fn of(response: &RawResponse) -> Self {
+        Self {
+            timing: response.timing,
+            correlation_id: response.correlation_id.clone(),
+            error_detail: ErrorDetail::from_response(response),
+            request_ids: request_ids(&response.headers),
+        }
+    }
+}
+
+/// How long each phase of one HTTP request took, in milliseconds
+///
+/// `dns_ms`, `connect_ms`, and `tls_ms` are network setup; `wait_ms` is the
+/// time between sending the request and the first response byte, which is
+/// mostly the server's processing time.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[allow(clippy::struct_field_names)]
+pub struct ConnectionTiming {
+    /// Name resolution
+    pub dns_ms: f64,
+    /// TCP handshake
+    pub connect_ms: f64,
+    /// TLS handshake; zero for plain HTTP
+    pub tls_ms: f64,
+    /// From sending the request to the first response byte
+    pub wait_ms: f64,
+    /// From the start until the first response byte
+    pub ttfb_ms: f64,
+    pub total_ms: f64,
+}
+
+impl ConnectionTiming {
+    /// Parse curl's cumulative write-out times, in seconds: name lookup,
+    /// connect, app connect, pre-transfer, start transfer, and total
+    fn from_curl(write_out: &str) -> Option<Self> {
+        let times: Vec<f64> = write_out
+            .split_whitespace()
+            .map(str::parse)
+            .collect::<std::result::Result<_, _>>()
+            .ok()?;
+        let [
+            namelookup,
+            connect,
+            appconnect,
+            pretransfer,
+            starttransfer,
+            total,
+        ] = times[..]
+        else {
+            return None;
+        };
+        let ms = |seconds: f64| (seconds * 1000.0 * 100.0).round() / 100.0;
+        Some(Self {
+            dns_ms: ms(namelookup),
+            connect_ms: ms((connect - namelookup).max(0.0)),
+            tls_ms: if appconnect > 0.0 {
+                ms((appconnect - connect).max(0.0))
+            } else {
+                0.0
+            },
+            wait_ms: ms((starttransfer - pretransfer).max(0.0)),
+            ttfb_ms: ms(starttransfer),
+            total_ms: ms(total),
+        })
+    }
+
+    /// One-line breakdown, e.g. `DNS 1.2ms · connect 0.4ms · TLS 21.0ms · wait 310.5ms · total 333.1ms`
+    #[must_use]
+    pub fn describe(self) -> String {
+        format!(
+            "DNS {:.1}ms · connect {:.1}ms · TLS {:.1}ms · wait {:.1}ms · total {:.1}ms",
+            self.dns_ms, self.connect_ms, self.tls_ms, self.wait_ms, self.total_ms
+        )
+    }
+}
+
+/// curl's exit code when `--max-time` is reached
+const CURL_TIMED_OUT: i32 = 28;
+
+/// Prefixes curl's timing write-out, which follows the body on stdout
+const TIMING_MARKER: &str = "__glean_mcp_timing__";
+
+/// curl `--write-out` format producing the times [`ConnectionTiming::from_curl`] parses
+const TIMING_WRITE_OUT: &str = "__glean_mcp_timing__ %{time_namelookup} %{time_connect} %{time_appconnect} %{time_pretransfer} %{time_starttransfer} %{time_total}";
+
+impl RawResponse {
+    /// Value of header `name` (case-insensitive)
+    #[must_use]
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .get(&name.to_ascii_lowercase())
+            .map(String::as_str)
+    }
+}
+
+/// How long a request may take when no timeout is configured
+pub const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Header carrying each live request's correlation id
+pub const CORRELATION_ID_HEADER: &str = "X-Correlation-Id";
+
+/// Headers the transport sets itself, which custom headers may not replace
+const RESERVED_HEADERS: [&str; 5] = [
+    "accept",
+    "authorization",
+    "content-type",
+    "mcp-session-id",
+    "x-correlation-id",
+];
+
+/// Check that `name: value` can be sent as a custom request header
+pub fn validate_header(name: &str, value: &str) -> Result<()> {
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "!#$%&'*+-.^_`|~".contains(c))
+    {
+        return Err(GleanMcpError::Config(format!(
+            "Invalid header name '{name}'"
+        )));
+    }
+    if RESERVED_HEADERS.contains(&name.to_ascii_lowercase().as_str()) {
+        return Err(GleanMcpError::Config(format!(
+            "Header '{name}' is set by the framework and can't be overridden"
+        )));
+    }
+    if value.contains(['\r', '\n']) {
+        return Err(GleanMcpError::Config(format!(
+            "Header '{name}' value must not contain line breaks"
+        )));
+    }
+    Ok(())
+}
+
+/// Parse a `--header` value (`Name: value`) into a name and value
+pub fn parse_header(raw: &str) -> Result<(String, String)> {
+    let Some((name, value)) = raw.split_once(':') else {
+        return Err(GleanMcpError::Config(format!(
+            "Invalid header '{raw}' (expected Name: value)"
+        )));
+    };
+    let (name, value) = (name.trim(), value.trim());
+    validate_header(name, value)?;
+    Ok((name.to_string(), value.to_string()))
+}
+
+/// Sends JSON-RPC requests to MCP endpoints
+#[derive(Debug, Clone, Default)]
+pub struct McpTransport {
+    auth_token: Option<String>,
+    cassette: Option<Arc<Cassette>>,
+    chaos: Option<Chaos>,
+    rate_limiter: Option<RateLimiter>,
+    concurrency: Option<AdaptiveConcurrency>,
+    conformance: Option<ConformanceChecker>,
+    capture: Option<TranscriptCapture>,
+    session_id: Option<String>,
+    proxy: ProxyConfig,
+    tls: TlsConfig,
+    headers: BTreeMap<String, String>,
+    correlation: Option<Arc<Correlation>>,
+    request_timeout: Option<Duration>,
+}
+
+/// Run id and request counter shared by a transport and its clones
+#[derive(Debug, Default)]
+struct Correlation {
+    run_id: String,
+    requests: AtomicU64,
+}
+
+impl McpTransport {
+    #[must_use]
+    pub const fn new(auth_token: Option<String>) -> Self {
+        Self {
+            auth_token,
+            cassette: None,
+            chaos: None,
+            rate_limiter: None,
+            concurrency: None,
+            conformance: None,
+            capture: None,
+            session_id: None,
+            proxy: ProxyConfig {
+                url: None,
+                no_proxy: Vec::new(),
+                disabled: false,
+            },
+            tls: TlsConfig {
+                ca_cert: None,
+                client_cert: None,
+                client_key: None,
+            },
+            headers: BTreeMap::new(),
+            correlation: None,
+            request_timeout: None,
+        }
+    }
+
+    /// Tag every live request with a correlation id made from `run_id` and a sequence number
+    #[must_use]
+    pub fn with_run_id(mut self, run_id: &str) -> Self {
+        self.correlation = Some(Arc::new(Correlation {
+            run_id: run_id.to_string(),
+            requests: AtomicU64::new(0),
+        }));
+        self
+    }
+
+    #[must_use]
+    pub fn run_id(&self) -> Option<&str> {
+        self.correlation
+            .as_ref()
+            .map(|correlation| correlation.run_id.as_str())
+    }
+
+    /// Correlation id for the next live request, e.g. `20250101T120000123Z-1a2b3c-0007`
+    fn next_correlation_id(&self) -> Option<String> {
+        let correlation = self.correlation.as_ref()?;
+        let sequence = correlation.requests.fetch_add(1, Ordering::Relaxed) + 1;
+        Some(format!("{}-{sequence:04}", correlation.run_id))
+    }
+
+    /// Record every interaction to, or replay every interaction from, `cassette`
+    #[must_use]
+    pub fn with_cassette(mut self, cassette: Cassette) -> Self {
+        self.cassette = Some(Arc::new(cassette));
+        self
+    }
+
+    /// Write every exchange to a per-tool transcript through `capture`
+    #[must_use]
+    pub fn with_capture(mut self, capture: TranscriptCapture) -> Self {
+        self.capture = Some(capture);
+        self
+    }
+
+    /// Corrupt a fraction of responses with injected faults
+    #[must_use]
+    pub fn with_chaos(mut self, chaos: Chaos) -> Self {
+        self.chaos = Some(chaos);
+        self
+    }
+
+    /// Pace live requests through `rate_limiter`
+    #[must_use]
+    pub fn with_rate_limiter(mut self, rate_limiter: RateLimiter) -> Self {
+        self.rate_limiter = Some(rate_limiter);
+        self
+    }
+
+    /// Report live responses' statuses to `concurrency`, which parallel runs take their slots from
+    #[must_use]
+    pub fn with_concurrency(mut self, concurrency: AdaptiveConcurrency) -> Self {
+        self.concurrency = Some(concurrency);
+        self
+    }
+
+    /// The adaptive concurrency limit responses are reported to, if any
+    #[must_use]
+    pub const fn concurrency(&self) -> Option<&AdaptiveConcurrency> {
+        self.concurrency.as_ref()
+    }
+
+    /// Check every response for JSON-RPC and MCP conformance through `conformance`
+    #[must_use]
+    pub fn with_conformance(mut self, conformance: ConformanceChecker) -> Self {
+        self.conformance = Some(conformance);
+        self
+    }
+
+    /// Authenticate as the identity `token` belongs to instead
+    #[must_use]
+    pub fn with_auth_token(mut self, token: &str) -> Self {
+        self.auth_token = Some(token.to_string());
+        self
+    }
+
+    /// Send requests without an `Authorization` header
+    #[must_use]
+    pub fn without_auth_token(mut self) -> Self {
+        self.auth_token = None;
+        self
+    }
+
+    /// Send every request in the MCP session `session_id` (`Mcp-Session-Id` header)
+    #[must_use]
+    pub fn with_session_id(mut self, session_id: &str) -> Self {
+        self.session_id = Some(session_id.to_string());
+        self
+    }
+
+    /// Send live requests through the proxy `proxy` resolves
+    #[must_use]
+    pub fn with_proxy(mut self, proxy: ProxyConfig) -> Self {
+        self.proxy = proxy;
+        self
+    }
+
+    #[must_use]
+    pub const fn proxy(&self) -> &ProxyConfig {
+        &self.proxy
+    }
+
+    /// Trust the CA bundle and present the client certificate `tls` names
+    #[must_use]
+    pub fn with_tls(mut self, tls: TlsConfig) -> Self {
+        self.tls = tls;
+        self
+    }
+
+    #[must_use]
+    pub const fn tls(&self) -> &TlsConfig {
+        &self.tls
+    }
+
+    /// Add `headers` to every live request
+    #[must_use]
+    pub fn with_headers(mut self, headers: BTreeMap<String, String>) -> Self {
+        self.headers.extend(headers);
+        self
+    }
+
+    /// Send `name: value` on every live request, replacing any header of that name
+    #[must_use]
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers
+            .retain(|existing, _| !existing.eq_ignore_ascii_case(name));
+        self.headers.insert(name.to_string(), value.to_string());
+        self
+    }
+
+    /// Give each live request up to `timeout` (curl `--max-time`) instead of [`DEFAULT_REQUEST_TIMEOUT`]
+    #[must_use]
+    pub const fn with_request_timeout(mut self, timeout: Duration) -> Self {
+        self.request_timeout = Some(timeout);
+        self
+    }
+
+    /// How long each live request may take
+    #[must_use]
+    pub fn request_timeout(&self) -> Duration {
+        self.request_timeout.unwrap_or(DEFAULT_REQUEST_TIMEOUT)
+    }
+
+    /// curl `-H` arguments for the custom headers
+    #[must_use]
+    pub fn header_args(&self) -> Vec<String> {
+        self.headers
+            .iter()
+            .flat_map(|(name, value)| ["-H".to_string(), format!("{name}: {value}")])
+            .collect()
+    }
+
+    #[must_use]
+    pub const fn has_auth_token(&self) -> bool {
+        self.auth_token.is_some()
+    }
+
+    /// Bearer token sent with every request, for tools that make their own requests
+    pub(crate) fn auth_token(&self) -> Option<&str> {
+        self.auth_token.as_deref()
+    }
+
+    /// Custom headers sent with every request, by name
+    #[must_use]
+    pub const fn headers(&self) -> &BTreeMap<String, String> {
+        &self.headers
+    }
+
+    /// POST a JSON-RPC request to `endpoint`
+    pub async fn post_json_rpc(&self, endpoint: &str, request: &Value) -> Result<RawResponse> {
+        let response = self.exchange(endpoint, request).await?;
+
+        if let Some(conformance) = &self.conformance {
+            conformance.record(request, &response);
+        }
+
+        // Faults are applied after recording and checking, so both see the real traffic
+        let response = match &self.chaos {
+            Some(chaos) => chaos.maybe_inject(request, response),
+            None => response,
+        };
+        // Transcripts show what the test saw, injected faults included
+        if let Some(capture) = &self.capture {
+            capture.record(endpoint, request, &response, self.request_timeout())?;
+        }
+        Ok(response)
+    }
+
+    async fn exchange(&self, endpoint: &str, request: &Value) -> Result<RawResponse> {
+        if let Some(cassette) = &self.cassette
+            && cassette.mode() == CassetteMode::Replay
+        {
+            return cassette.replay_interaction(endpoint, request);
+        }
+
+        if let Some(rate_limiter) = &self.rate_limiter {
+            rate_limiter.acquire().await;
+        }
+
+        let correlation_id = self.next_correlation_id();
+        let mut response = self
+            .post_live(endpoint, request, correlation_id.as_deref())
+            .await?;
+        response.correlation_id = correlation_id;
+
+        if let Some(rate_limiter) = &self.rate_limiter {
+            rate_limiter.record_status(response.status);
+        }
+        if let Some(concurrency) = &self.concurrency {
+            concurrency.record_status(response.status);
+        }
+
+        if let Some(cassette) = &self.cassette {
+            cassette.record_interaction(endpoint, request, &response)?;
+        }
+
+        Ok(response)
+    }
+
+    async fn post_live(
+        &self,
+        endpoint: &str,
+        request: &Value,
+        correlation_id: Option<&str>,
+    ) -> Result<RawResponse> {
+        let request_body = serde_json::to_string(request).map_err(GleanMcpError::Json)?;
+        let max_time = format!("{:.3}", self.request_timeout().as_secs_f64());
+
+        let mut curl_args = vec![
+            "-sS", // Silent, but keep errors such as TLS failures on stderr
+            "-i",
+            "-X",
+            "POST",
+            "-H",
+            "Content-Type: application/json",
+            "-H",
+            "Accept: application/json",
+            // Body goes through stdin: large payloads would exceed the OS argument length limit
+            "--data-binary",
+            "@-",
+            // Send large bodies straight away instead of waiting up to a second for
+            // `100 Continue`, which servers and proxies often never send
+            "-H",
+            "Expect:",
+            "--max-time",
+            &max_time,
+            "--write-out",
+            TIMING_WRITE_OUT,
+        ];
+
+        // Add auth header if token is available
+        let auth_header;
+        if let Some(ref token) = self.auth_token {
+            auth_header = format!("Authorization: Bearer {token}");
+            curl_args.extend_from_slice(&["-H", &auth_header]);
+        }
+        let session_header;
+        if let Some(ref session_id) = self.session_id {
+            session_header = format!("Mcp-Session-Id: {session_id}");
+            curl_args.extend_from_slice(&["-H", &session_header]);
+        }
+        let correlation_header;
+        if let Some(correlation_id) = correlation_id {
+            correlation_header = format!("{CORRELATION_ID_HEADER}: {correlation_id}");
+            curl_args.extend_from_slice(&["-H", &correlation_header]);
+        }
+        let proxy_args = self.proxy.curl_args(endpoint);
+        curl_args.extend(proxy_args.iter().map(String::as_str));
+        let tls_args = self.tls.curl_args();
+        curl_args.extend(tls_args.iter().map(String::as_str));
+        let header_args = self.header_args();
+        curl_args.extend(header_args.iter().map(String::as_str));
+
+        curl_args.push(endpoint);
+
+        let mut child = Command::new("curl")
+            .args(&curl_args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| GleanMcpError::Process(format!("Failed to spawn curl: {e}")))?;
+
+        let mut stdin = child
+            .stdin
+            .take()
+            .ok_or_else(|| GleanMcpError::Process("Failed to capture stdin".to_string()))?;
+        let mut stdout = child
+            .stdout
+            .take()
+            .ok_or_else(|| GleanMcpError::Process("Failed to capture stdout".to_string()))?;
+        let stderr = child
+            .stderr
+            .take()
+            .ok_or_else(|| GleanMcpError::Process("Failed to capture stderr".to_string()))?;
+
+        let stderr_reader = BufReader::new(stderr);
+
+        // Read output concurrently; the response is read as bytes so a body
+        // that isn't valid UTF-8 is still returned, with U+FFFD in its place
+        let stdout_future = async {
+            let mut bytes = Vec::new();
+            stdout.read_to_end(&mut bytes).await?;
+            Ok::<Vec<String>, std::io::Error>(
+                String::from_utf8_lossy(&bytes)
+                    .lines()
+                    .map(String::from)
+                    .collect(),
+            )
+        };
+
+        let stderr_future = async {
+            let mut lines = Vec::new();
+            let mut line_reader = stderr_reader.lines();
+            while let Some(line) = line_reader.next().await.transpose()? {
+                lines.push(line);
+            }
+            Ok::<Vec<String>, std::io::Error>(lines)
+        };
+
+        // Write while reading so neither side blocks on a full pipe; curl reports
+        // a body it couldn't receive, so write errors are ignored here
+        let stdin_future = async move {
+            let _ = stdin.write_all(request_body.as_bytes()).await;
+            drop(stdin);
+        };
+
+        let ((), (stdout_lines, stderr_lines)) = smol::future::zip(
+            stdin_future,
+            smol::future::zip(stdout_future, stderr_future),
+        )
+        .await;
+        let mut stdout_lines = stdout_lines
+            .map_err(|e| GleanMcpError::Process(format!("Failed to read stdout: {e}")))?;
+        let timing = take_timing(&mut stdout_lines);
+        let stderr_lines = stderr_lines
+            .map_err(|e| GleanMcpError::Process(format!("Failed to read stderr: {e}")))?;
+
+        let status = child
+            .status()
+            .await
+            .map_err(|e| GleanMcpError::Process(format!("Failed to get process status: {e}")))?;
+
+        let (http_status, headers, body) = split_http_response(&stdout_lines);
+
+        Ok(RawResponse {
+            success: status.success(),
+            body,
+            stderr: stderr_lines.join("\n"),
+            status: http_status,
+            headers,
+            timing,
+            correlation_id: None,
+        })
+    }
+
+    /// Open an event stream on `endpoint` as `request` describes and read it
+    ///
+    /// Streams aren't recorded, replayed, or paced. Reaching `request.wait`
+    /// with the stream still open, or dropping it after
+    /// `request.drop_after_events`, counts as success, with the events read
+    /// so far as the body.
+    pub async fn read_stream(
+        &self,
+        endpoint: &str,
+        request: &StreamRequest,
+    ) -> Result<RawResponse> {
+        let max_time = request.wait.as_secs().max(1).to_string();
+        let mut curl_args = vec![
+            "-sS",
+            "-i",
+            "-N", // Don't buffer, so events read before the timeout are kept
+            "--max-time",
+            &max_time,
+        ];
+        if request.body.is_some() {
+            curl_args.extend_from_slice(&[
+                "-X",
+                "POST",
+                "-H",
+                "Content-Type: application/json",
+                "-H",
+                "Accept: application/json, text/event-stream",
+                "--data-binary",
+                "@-",
+                "-H",
+                "Expect:",
+            ]);
+        } else {
+            curl_args.extend_from_slice(&["-H", "Accept: text/event-stream"]);
+        }
+        let last_event_header;
+        if let Some(ref last_event_id) = request.last_event_id {
+            last_event_header = format!("Last-Event-ID: {last_event_id}");
+            curl_args.extend_from_slice(&["-H", &last_event_header]);
+        }
+        let auth_header;
+        if let Some(ref token) = self.auth_token {
+            auth_header = format!("Authorization: Bearer {token}");
+            curl_args.extend_from_slice(&["-H", &auth_header]);
+        }
+        let session_header;
+        if let Some(ref session_id) = self.session_id {
+            session_header = format!("Mcp-Session-Id: {session_id}");
+            curl_args.extend_from_slice(&["-H", &session_header]);
+        }
+        let proxy_args = self.proxy.curl_args(endpoint);
+        curl_args.extend(proxy_args.iter().map(String::as_str));
+        let tls_args = self.tls.curl_args();
+        curl_args.extend(tls_args.iter().map(String::as_str));
+        let header_args = self.header_args();
+        curl_args.extend(header_args.iter().map(String::as_str));
+        curl_args.push(endpoint);
+
+        let mut child = Command::new("curl")
+            .args(&curl_args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| GleanMcpError::Process(format!("Failed to spawn curl: {e}")))?;
+        if let Some(mut stdin) = child.stdin.take() {
+            if let Some(body) = &request.body {
+                let _ = stdin.write_all(body.to_string().as_bytes()).await;
+            }
+            drop(stdin);
+        }
+        let stdout = child
+            .stdout
+            .take()
+            .ok_or_else(|| GleanMcpError::Process("Failed to capture stdout".to_string()))?;
+
+        let mut lines = Vec::new();
+        let mut events = 0;
+        let mut in_event = false;
+        let mut dropped = false;
+        let mut reader = BufReader::new(stdout).lines();
+        while let Some(line) = reader.next().await {
+            let line =
+                line.map_err(|e| GleanMcpError::Process(format!("Failed to read stdout: {e}")))?;
+            let line = line.trim_end_matches('\r').to_string();
+            if line.starts_with("data:") {
+                in_event = true;
+            } else if line.is_empty() && in_event {
+                in_event = false;
+                events += 1;
+            }
+            lines.push(line);
+            if request
+                .drop_after_events
+                .is_some_and(|limit| events >= limit)
+            {
+                let _ = child.kill();
+                dropped = true;
+                break;
+            }
+        }
+        let mut stderr = String::new();
+        if let Some(mut pipe) = child.stderr.take() {
+            let _ = pipe.read_to_string(&mut stderr).await;
+        }
+        let exit = child
+            .status()
+            .await
+            .map_err(|e| GleanMcpError::Process(format!("Failed to get process status: {e}")))?;
+
+        let (status, headers, body) = split_http_response(&lines);
+        Ok(RawResponse {
+            success: exit.success()
+                || ((dropped || exit.code() == Some(CURL_TIMED_OUT)) && status.is_some()),
+            body,
+            stderr: stderr.trim().to_string(),
+            status,
+            headers,
+            timing: None,
+            correlation_id: None,
+        })
+    }
+}
+
+/// An event stream for [`McpTransport::read_stream`] to open
+#[derive(Debug, Clone, Default)]
+pub struct StreamRequest {
+    /// JSON-RPC message to POST; `None` opens the GET stream
+    pub body: Option<Value>,
+    /// Resume the stream after this event (`Last-Event-ID`)
+    pub last_event_id: Option<String>,
+    /// How long to read before closing the stream
+    pub wait: Duration,
+    /// Drop the connection once this many events have arrived
+    pub drop_after_events: Option<usize>,
+}
+
+/// One server-sent event
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SseEvent {
+    /// The event's `id`, used to resume after it
+    pub id: Option<String>,
+    pub data: String,
+}
+
+/// The events in a `text/event-stream` body, skipping events without `data`
+#[must_use]
+pub fn parse_event_stream(body: &str) -> Vec<SseEvent> {
+    let mut events = Vec::new();
+    let mut id = None;
+    let mut data: Vec<&str> = Vec::new();
+    for line in body.lines().chain(std::iter::once("")) {
+        let value = |field: &str| {
+            line.strip_prefix(field)
+                .map(|value| value.strip_prefix(' ').unwrap_or(value))
+        };
+        if line.is_empty() {
+            if !data.is_empty() {
+                events.push(SseEvent {
+                    id: id.take(),
+                    data: data.join("\n"),
+                });
+                data.clear();
+            }
+            id = None;
+        } else if let Some(value) = value("data:") {
+            data.push(value);
+        } else if let Some(value) = value("id:") {
+            id = Some(value.to_string());
+        }
+    }
+    events
+}
+
+/// Remove curl's timing write-out from the end of `lines` and parse it
+///
+/// The write-out directly follows the body, so it shares the last line with
+/// a body that doesn't end in a newline.
+fn take_timing(lines: &mut Vec<String>) -> Option<ConnectionTiming> {
+    let last = lines.last_mut()?;
+    let start = last.rfind(TIMING_MARKER)?;
+    let timing = ConnectionTiming::from_curl(&last[start + TIMING_MARKER.len()..]);
+    last.truncate(start);
+    if last.is_empty() {
+        lines.pop();
+    }
+    timing
+}
+
+/// Split `curl -i` output into the final status, its headers, and the body.
+///
+/// Interim blocks (`100 Continue`, proxy `CONNECT` replies) are skipped; output
+/// without a status line is treated as all body.
+fn split_http_response(lines: &[String]) -> (Option<u16>, BTreeMap<String, String>, String) {
+    let mut status = None;
+    let mut headers = BTreeMap::new();
+    let mut rest = lines;
+
+    while let Some(status_line) = rest.first().filter(|line| line.starts_with("HTTP/")) {
+        status = status_line
+            .split_whitespace()
+            .nth(1)
+            .and_then(|code| code.parse().ok());
+        headers.clear();
+        rest = &rest[1..];
+
+        let header_count = rest.iter().position(String::is_empty).unwrap_or(rest.len());
+        for line in &rest[..header_count] {
+            if let Some((name, value)) = line.split_once(':') {
+                headers.insert(name.trim().to_ascii_lowercase(), value.trim().to_string());
+            }
+        }
+        rest = &rest[(header_count + 1).min(rest.len())..];
+    }
+
+    (status, headers, rest.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{MockServer, MockServerConfig, MockTool};
+    use serde_json::json;
+
+    /// A mock serving one `echo` tool that repeats its `message` argument back
+    fn echo_server(max_request_bytes: Option<usize>) -> MockServer {
+        let mut tool = MockTool::new("echo", "Repeats its arguments");
+        tool.input_schema = Some(json!({
+            "type": "object",
+            "properties": { "message": { "type": "string" } },
+            "required": ["message"]
+        }));
+        let mut config = MockServerConfig {
+            tools: vec![tool],
+            ..MockServerConfig::default()
+        };
+        if let Some(max_request_bytes) = max_request_bytes {
+            config.max_request_bytes = max_request_bytes;
+        }
+        smol::block_on(MockServer::start("127.0.0.1:0", config)).unwrap()
+    }
+
+    fn echo_request(message: &str) -> Value {
+        json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "tools/call",
+            "params": { "name": "echo", "arguments": { "message": message } }
+        })
+    }
+
+    /// Send `message` to the echo tool and return the `message` it received
+    fn round_trip(message: &str) -> String {
+        let server = echo_server(None);
+        let response = smol::block_on(
+            McpTransport::new(None)
+                .post_json_rpc(&server.endpoint_url("default"), &echo_request(message)),
+        )
+        .unwrap();
+        assert!(response.success, "curl failed: {}", response.stderr);
+        assert_eq!(response.status, Some(200));
+
+        let body: Value = serde_json::from_str(&response.body).unwrap();
+        let text = body
+            .pointer("/result/content/0/text")
+            .and_then(Value::as_str)
+            .unwrap();
+        let echoed: Value =
+            serde_json::from_str(text.strip_prefix("Mock echo response for ").unwrap()).unwrap();
+        echoed["message"].as_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn multi_megabyte_arguments_round_trip() {
+        let message = "policy ".repeat(768 * 1024);
+        assert!(message.len() > 5 * 1024 * 1024);
+        // Not `assert_eq!`, which would print megabytes on failure
+        assert!(round_trip(&message) == message);
+    }
+
+    #[test]
+    fn quote_and_backslash_arguments_round_trip() {
+        // Doubles to 8 MiB once escaped into the JSON-RPC body
+        let message = r#""\"#.repeat(2 * 1024 * 1024);
+        assert!(round_trip(&message) == message);
+        let message = r#"\\"\'\u0000\x00'"$(rm -rf)`\"#.repeat(1024);
+        assert!(round_trip(&message) == message);
+    }
+
+    #[test]
+    fn oversized_bodies_get_a_response_not_a_reset() {
+        // The server answers before curl has finished uploading, which used to
+        // race with the connection closing
+        let server = echo_server(Some(64 * 1024));
+        let request = echo_request(&"a".repeat(4 * 1024 * 1024));
+        for _ in 0..20 {
+            let response = smol::block_on(
+                McpTransport::new(None).post_json_rpc(&server.endpoint_url("default"), &request),
+            )
+            .unwrap();
+            assert_eq!(response.status, Some(413), "stderr: {}", response.stderr);
+        }
+    }
+}
@@ -1,16 +1,33 @@
-use crate::{GleanMcpError, Result};
+use super::{
+    AUTO_START, AdaptiveConcurrency, AgentCheckConfig, AgentInfo, CancelReason, CancellationToken,
+    Cassette, Chaos, ClockSkew, ConcurrencyStats, ConformanceChecker, ConformanceReport,
+    ConnectionTiming, DuplicateCheckConfig, ErrorCategory, ErrorClass, ErrorDetail, LIST_AGENTS,
+    LanguageCheckConfig, McpTransport, PiiFinding, PiiScanConfig, ProxyConfig, QueryCorpus,
+    QueryRule, RUN_AGENT, RateLimitStats, RateLimiter, RequestTrace, ResponseExpectation,
+    ResponseSizeConfig, ResultCheckConfig, RetryPolicy, ServerIdentity, Shard, SnapshotCheck,
+    TOOL_CATEGORIES, TlsConfig, ToolManifestCheck, TranscriptCapture,
+    arguments_from_schema_with_query, fetch_server_identity, format_bytes, known_tools,
+    measure_clock_skew, parse_retry_after, query_from_rules, render_arguments, request_ids,
+    response_bytes, template_references, tool_profile,
+};
+use crate::{
+    DEFAULT_URL_TEMPLATE, EndpointConfig, GleanConfig, GleanMcpError, Reporter, Result,
+    auth_token_variables, default_endpoints, endpoint_url, find_auth_token, reporter_for_format,
+    schema_version, write_line,
+};
 use async_process::Command;
 use console::{Emoji, Term, style};
-use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
-use rand::Rng;
+use indicatif::ProgressStyle;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use smol::io::{AsyncBufReadExt, BufReader};
+use smol::lock::{Semaphore, SemaphoreGuardArc};
 use smol::stream::StreamExt;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
 use std::fmt::Write;
 use std::process::Stdio;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex, PoisonError};
 use std::time::{Duration, Instant};
 
 // Define emojis for progress messages
@@ -18,7 +35,7 @@ static CHECKMARK: Emoji<'_, '_> = Emoji("✅ ", "[OK] ");
 static MAGNIFYING_GLASS: Emoji<'_, '_> = Emoji("🔍 ", "[SEARCH] ");
 
 /// Async timeout helper function using smol Timer
-async fn async_timeout<T, F>(duration: Duration, future: F) -> Result<T>
+pub(crate) async fn async_timeout<T, F>(duration: Duration, future: F) -> Result<T>
 where
     F: std::future::Future<Output = Result<T>>,
 {
@@ -33,32 +50,42 @@ where
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct InspectorResult {
+    /// Version of this document's schema (`schema print inspector-result`)
+    #[serde(default = "schema_version")]
+    pub schema_version: String,
     pub success: bool,
-    pub tool_results: Option<HashMap<String, bool>>,
+    pub tool_results: Option<BTreeMap<String, bool>>,
     pub inspector_data: Option<Value>,
     pub error: Option<String>,
+    /// What the server reported about itself in `initialize`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub server: Option<ServerIdentity>,
 }
 
 impl InspectorResult {
     #[must_use]
-    pub const fn new_success(tool_results: HashMap<String, bool>, inspector_data: Value) -> Self {
+    pub fn new_success(tool_results: BTreeMap<String, bool>, inspector_data: Value) -> Self {
         Self {
+            schema_version: schema_version(),
             success: true,
             tool_results: Some(tool_results),
             inspector_data: Some(inspector_data),
             error: None,
+            server: None,
         }
     }
 
     #[must_use]
-    pub const fn new_error(error: String) -> Self {
+    pub fn new_error(error: String) -> Self {
         Self {
+            schema_version: schema_version(),
             success: false,
             tool_results: None,
             inspector_data: None,
             error: Some(error),
+            server: None,
         }
     }
 }
@@ -66,29 +93,420 @@ impl InspectorResult {
 // New data structures for test-all functionality
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[allow(clippy::struct_excessive_bools)]
 pub struct TestAllOptions {
     pub tools_filter: String,
     pub parallel: bool,
     pub max_concurrent: usize,
+    /// Adapt concurrency to the server's 429s and 5xx, up to `max_concurrent` (`--max-concurrent auto`)
+    #[serde(default)]
+    pub adaptive_concurrency: bool,
     pub timeout: u64,
     pub verbose: bool,
     pub debug: bool,
     pub retry_attempts: u32,
     pub retry_backoff_seconds: u64,
+    /// Free-form `key=value` labels attached to the run's result
+    #[serde(default)]
+    pub labels: BTreeMap<String, String>,
+    /// Per-tool concurrency caps applied in parallel runs
+    #[serde(default)]
+    pub tool_max_concurrent: BTreeMap<String, usize>,
+    /// Per-tool latency budgets in milliseconds; slower successes are degraded
+    #[serde(default)]
+    pub latency_budgets_ms: BTreeMap<String, u64>,
+    /// Response language validation
+    #[serde(default)]
+    pub language_check: LanguageCheckConfig,
+    /// Duplicate search result detection
+    #[serde(default)]
+    pub duplicate_check: DuplicateCheckConfig,
+    /// Result count, field, and domain checks on search responses
+    #[serde(default)]
+    pub result_check: ResultCheckConfig,
+    /// Golden-response snapshots to compare responses against (or record)
+    #[serde(default)]
+    pub snapshot: Option<SnapshotCheck>,
+    /// Response size limits; larger responses are flagged as oversized
+    #[serde(default)]
+    pub response_size: ResponseSizeConfig,
+    /// Sensitive data scanning of responses
+    #[serde(default)]
+    pub pii_scan: PiiScanConfig,
+    /// What each tool's successful responses must contain, from the tool profile
+    #[serde(default)]
+    pub expectations: BTreeMap<String, ResponseExpectation>,
+    /// Cap on tool call requests per second across the run
+    #[serde(default)]
+    pub max_rps: Option<f64>,
+    /// Structured `tools/call` arguments per tool, replacing the generated query
+    #[serde(default)]
+    pub tool_arguments: BTreeMap<String, Value>,
+    /// Name globs (`gmail_*`) of tools to skip even when `tools_filter` selects them
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    /// Only test tools carrying at least one of these tags (empty: no tag filter)
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Skip tools carrying any of these tags
+    #[serde(default)]
+    pub exclude_tags: Vec<String>,
+    /// Tags per tool name, e.g. `gmail_search: [email]`
+    #[serde(default = "default_tool_tags")]
+    pub tool_tags: BTreeMap<String, Vec<String>>,
+    /// Tools that only run after the listed tools pass, e.g. `read_document: [search]`
+    #[serde(default)]
+    pub depends_on: BTreeMap<String, Vec<String>>,
+    /// Queries per tool to call tools with instead of their generated query
+    #[serde(default)]
+    pub corpus: QueryCorpus,
+    /// Cancel the rest of the run after the first failing tool
+    #[serde(default)]
+    pub fail_fast: bool,
+    /// Only run this part of the tools (`--shard 2/5`)
+    #[serde(default)]
+    pub shard: Option<Shard>,
+    /// Results (`search (default)`) that passed recently, with the run they passed in; their tools are skipped as cached passes
+    #[serde(default)]
+    pub recently_passed: BTreeMap<String, String>,
+    /// Test the built-in default tool set when `tools/list` yields no tools, instead of failing
+    #[serde(default)]
+    pub assume_default_tools: bool,
+    /// Endpoints `test_all_tools` runs against, in order
+    #[serde(default = "default_endpoints")]
+    pub endpoints: Vec<EndpointConfig>,
+    /// How agents are discovered, run, and polled when `run_agent` is tested
+    #[serde(default)]
+    pub agents: AgentCheckConfig,
+    /// Regex → query rules for tools without a built-in test query
+    #[serde(default)]
+    pub query_rules: Vec<QueryRule>,
+    /// Stops the run early; cancelled on Ctrl-C or by `fail_fast`
+    #[serde(skip)]
+    pub cancel: CancellationToken,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl Default for TestAllOptions {
+    /// Defaults matching the `test` command's CLI defaults
+    fn default() -> Self {
+        Self {
+            tools_filter: "core".to_string(),
+            parallel: false,
+            max_concurrent: 3,
+            adaptive_concurrency: false,
+            timeout: 60,
+            verbose: false,
+            debug: false,
+            retry_attempts: 4,
+            retry_backoff_seconds: 5,
+            labels: BTreeMap::new(),
+            tool_max_concurrent: BTreeMap::new(),
+            latency_budgets_ms: BTreeMap::new(),
+            language_check: LanguageCheckConfig::default(),
+            duplicate_check: DuplicateCheckConfig::default(),
+            result_check: ResultCheckConfig::default(),
+            snapshot: None,
+            response_size: ResponseSizeConfig::default(),
+            pii_scan: PiiScanConfig::default(),
+            expectations: BTreeMap::new(),
+            max_rps: None,
+            tool_arguments: BTreeMap::new(),
+            exclude: Vec::new(),
+            tags: Vec::new(),
+            exclude_tags: Vec::new(),
+            tool_tags: default_tool_tags(),
+            depends_on: BTreeMap::new(),
+            corpus: QueryCorpus::default(),
+            fail_fast: false,
+            shard: None,
+            recently_passed: BTreeMap::new(),
+            assume_default_tools: false,
+            endpoints: default_endpoints(),
+            agents: AgentCheckConfig::default(),
+            query_rules: Vec::new(),
+            cancel: CancellationToken::new(),
+        }
+    }
+}
+
+impl TestAllOptions {
+    /// Arguments to call `tool` with, and the query they describe
+    ///
+    /// Configured arguments win, then arguments generated from the tool's
+    /// `inputSchema`, then the tool's test query as its single parameter.
+    #[must_use]
+    pub fn arguments_for(&self, tool: &ToolInfo) -> (Value, String) {
+        self.arguments_for_query(tool, None)
+    }
+
+    /// Like [`Self::arguments_for`], with `query` (e.g. from the corpus) in place of the generated query
+    #[must_use]
+    pub fn arguments_for_query(&self, tool: &ToolInfo, query: Option<&str>) -> (Value, String) {
+        let query = query.map_or_else(
+            || TestQueryGenerator::generate_query_for(tool, &self.query_rules),
+            str::to_string,
+        );
+        let arguments = self.tool_arguments.get(&tool.name).cloned().or_else(|| {
+            tool.schema
+                .as_ref()
+                .and_then(|schema| arguments_from_schema_with_query(schema, &query))
+        });
+        arguments.map_or_else(
+            || {
+                (
+                    TestQueryGenerator::generate_arguments(&tool.name, &query),
+                    query,
+                )
+            },
+            |arguments| {
+                let query = TestQueryGenerator::describe_arguments(&arguments);
+                (arguments, query)
+            },
+        )
+    }
+
+    /// Whether `tool` is part of the run
+    ///
+    /// The tool must match `tools_filter` (a category or a comma-separated
+    /// list of names and globs), carry one of `tags` when any are given, and
+    /// match neither an `exclude` glob nor an `exclude_tags` tag.
+    #[must_use]
+    pub fn selects(&self, tool: &str) -> bool {
+        let included = match self.tools_filter.as_str() {
+            "all" => true,
+            category @ ("core" | "enterprise" | "agents") => {
+                TestQueryGenerator::get_tool_category(tool) == category
+            }
+            tools_list => tools_list
+                .split(',')
+                .map(str::trim)
+                .any(|pattern| glob_match(pattern, tool)),
+        };
+        let tags = self.tool_tags.get(tool).map_or(&[][..], Vec::as_slice);
+        included
+            && (self.tags.is_empty() || tags.iter().any(|tag| self.tags.contains(tag)))
+            && !tags.iter().any(|tag| self.exclude_tags.contains(tag))
+            && !self.exclude.iter().any(|pattern| glob_match(pattern, tool))
+    }
+
+    /// Tools `tool` depends on: its `depends_on` entries and the tools its argument templates reference
+    #[must_use]
+    pub fn dependencies_of(&self, tool: &str) -> Vec<&str> {
+        let mut dependencies: Vec<&str> = self
+            .depends_on
+            .get(tool)
+            .into_iter()
+            .flatten()
+            .map(String::as_str)
+            .collect();
+        for reference in self
+            .tool_arguments
+            .get(tool)
+            .map(template_references)
+            .unwrap_or_default()
+        {
+            if !dependencies.contains(&reference) {
+                dependencies.push(reference);
+            }
+        }
+        dependencies
+    }
+
+    /// The alphabetically first tool connected to `tool` by dependencies in
+    /// either direction; `--shard` keeps each such group in one shard
+    #[must_use]
+    pub fn dependency_group<'a>(&'a self, tool: &'a str) -> String {
+        fn find<'a>(parent: &mut BTreeMap<&'a str, &'a str>, tool: &'a str) -> &'a str {
+            let mut root = tool;
+            while let Some(&next) = parent.get(root).filter(|next| **next != root) {
+                root = next;
+            }
+            parent.insert(tool, root);
+            root
+        }
+
+        // Union-find over every dependency edge; the smaller name becomes the root
+        let mut parent: BTreeMap<&str, &str> = BTreeMap::new();
+        let tools = self
+            .depends_on
+            .keys()
+            .chain(self.tool_arguments.keys())
+            .map(String::as_str)
+            .chain([tool]);
+        for from in tools {
+            for to in self.dependencies_of(from) {
+                let (a, b) = (find(&mut parent, from), find(&mut parent, to));
+                parent.insert(a.max(b), a.min(b));
+            }
+        }
+        find(&mut parent, tool).to_string()
+    }
+
+    /// Result names and corpus queries `tool` is called with; configured arguments replace the corpus
+    pub(crate) fn test_cases(&self, tool: &str) -> Vec<(String, Option<String>)> {
+        if self.tool_arguments.contains_key(tool) {
+            return vec![(tool.to_string(), None)];
+        }
+        self.corpus.cases(tool)
+    }
+
+    /// Tools named outright in `tools_filter`, rather than by category or glob
+    fn named_tools(&self) -> impl Iterator<Item = &str> {
+        let named = !matches!(
+            self.tools_filter.as_str(),
+            "all" | "core" | "enterprise" | "agents"
+        );
+        self.tools_filter
+            .split(',')
+            .map(str::trim)
+            .filter(move |name| named && !name.is_empty() && !name.contains(['*', '?']))
+    }
+
+    /// Retry policy built from `retry_attempts` and `retry_backoff_seconds`
+    #[must_use]
+    pub const fn retry_policy(&self) -> RetryPolicy {
+        RetryPolicy::new(
+            self.retry_attempts,
+            Duration::from_secs(self.retry_backoff_seconds),
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct AllToolsTestResult {
+    /// Version of this document's schema (`schema print test-result`)
+    #[serde(default = "schema_version")]
+    pub schema_version: String,
     pub success: bool,
+    /// Every tool in `tool_results`: successful, failed, and skipped
     pub total_tools: usize,
     pub successful_tools: usize,
     pub failed_tools: usize,
-    pub tool_results: HashMap<String, ToolTestResult>,
+    /// Tools that weren't run, each with a [`SkipReason`]
+    #[serde(default)]
+    pub skipped_tools: usize,
+    /// Successful tools that exceeded their latency budget
+    #[serde(default)]
+    pub degraded_tools: usize,
+    /// Successful tools whose response exceeded its size limit
+    #[serde(default)]
+    pub oversized_tools: usize,
+    pub tool_results: BTreeMap<String, ToolTestResult>,
     pub execution_summary: ExecutionSummary,
     pub error: Option<String>,
+    /// Unique id of the invocation; every request's correlation id starts with it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub run_id: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub labels: BTreeMap<String, String>,
+    /// What the server reported about itself in `initialize`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub server: Option<ServerIdentity>,
+    /// Per-category rollups (core, enterprise), keyed by category
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub categories: BTreeMap<String, CategoryRollup>,
+    /// Per-endpoint rollups (default, chatgpt, ...), keyed by endpoint name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub endpoints: BTreeMap<String, CategoryRollup>,
+    /// JSON-RPC and MCP conformance of the responses seen during the run
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub conformance: Option<ConformanceReport>,
+    /// Failed tools per [`ErrorCategory`]
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub error_categories: BTreeMap<ErrorCategory, usize>,
+    /// Why the run stopped early, if it did; the tools it cut off are skipped as interrupted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cancelled: Option<CancelReason>,
+    /// Where the tested tools came from; `None` when discovery was skipped
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_discovery: Option<ToolDiscovery>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Where a run's tool list came from
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, JsonSchema,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolDiscovery {
+    /// The server's `tools/list`
+    Discovered,
+    /// `tools/list` yielded no tools, so the built-in default set was assumed (`--assume-default-tools`)
+    AssumedDefaults,
+}
+
+/// Why a tool wasn't run
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, JsonSchema,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum SkipReason {
+    /// Advertised by the server, but not selected for the run
+    FilteredOut,
+    /// A tool it depends on failed or was skipped, or didn't return a value its arguments use
+    DependencyFailed,
+    /// Named in the tool selection, but not in the server's `tools/list`
+    NotAdvertised,
+    /// The run was cancelled before the tool finished
+    Interrupted,
+    /// The server's `initialize` response doesn't advertise the capability the test needs
+    CapabilityNotAdvertised,
+    /// Passed in a recent run (`--skip-recently-passed`)
+    CachedPass,
+}
+
+impl SkipReason {
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::FilteredOut => "filtered out",
+            Self::DependencyFailed => "dependency failed",
+            Self::NotAdvertised => "not advertised",
+            Self::Interrupted => "interrupted",
+            Self::CapabilityNotAdvertised => "capability not advertised",
+            Self::CachedPass => "cached pass",
+        }
+    }
+}
+
+/// Order of the per-tool lines in the text report (`--sort`)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ResultSort {
+    /// Alphabetical by tool name
+    #[default]
+    Name,
+    /// Slowest first
+    Duration,
+    /// Failures first, then degraded, passed, and skipped tools
+    Status,
+}
+
+impl ResultSort {
+    pub const ALL: [Self; 3] = [Self::Name, Self::Duration, Self::Status];
+
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Name => "name",
+            Self::Duration => "duration",
+            Self::Status => "status",
+        }
+    }
+}
+
+/// Parse a report order, as given to `--sort`
+pub fn parse_result_sort(raw: &str) -> Result<ResultSort> {
+    let name = raw.trim();
+    ResultSort::ALL
+        .into_iter()
+        .find(|sort| sort.as_str() == name)
+        .ok_or_else(|| {
+            GleanMcpError::Config(format!(
+                "Unknown sort order '{name}' (expected name, duration, status)"
+            ))
+        })
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ToolTestResult {
     pub tool_name: String,
     pub success: bool,
@@ -97,15 +515,176 @@ pub struct ToolTestResult {
     pub response_data: Option<Value>,
     pub error_message: Option<String>,
     pub validation_details: Option<String>,
+    /// Latency budget the tool was held to, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latency_budget_ms: Option<u64>,
+    /// Succeeded, but slower than its latency budget
+    #[serde(default)]
+    pub degraded: bool,
+    /// Why the tool failed, when it did
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_class: Option<ErrorClass>,
+    /// Failure category for reports, when the tool failed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_category: Option<ErrorCategory>,
+    /// Connection phases of the last `tools/call` request, separating network time from server time
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timing: Option<ConnectionTiming>,
+    /// `X-Correlation-Id` of the last `tools/call` request, for finding it in server logs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub correlation_id: Option<String>,
+    /// Request and trace id headers the server answered the last `tools/call` with
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub request_ids: BTreeMap<String, String>,
+    /// What the server answered the failed request with, when it answered
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_detail: Option<ErrorDetail>,
+    /// Serialized size of the response
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response_bytes: Option<u64>,
+    /// Size limit the response was held to, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size_limit_bytes: Option<u64>,
+    /// Succeeded, but with a response larger than its size limit
+    #[serde(default)]
+    pub oversized: bool,
+    /// Sensitive data the `pii_scan` detectors found in the response
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pii_findings: Vec<PiiFinding>,
+    /// Why the tool wasn't run; skipped tools are neither successful nor failed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub skipped: Option<SkipReason>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ExecutionSummary {
     pub start_time: String,
     pub end_time: String,
     pub total_duration_ms: u64,
     pub parallel_execution: bool,
     pub timeout_settings: u64,
+    /// Request pacing, when a rate limit was configured or the server returned 429s
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rate_limit: Option<RateLimitStats>,
+    /// Limits reached by `--max-concurrent auto`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub concurrency: Option<ConcurrencyStats>,
+}
+
+/// Pass/fail counts for one tool category
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct CategoryRollup {
+    pub total: usize,
+    pub successful: usize,
+    pub failed: usize,
+    pub degraded: usize,
+}
+
+impl CategoryRollup {
+    /// Share of tools that passed, as a percentage
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn success_rate(&self) -> f64 {
+        if self.total == 0 {
+            return 100.0;
+        }
+        self.successful as f64 * 100.0 / self.total as f64
+    }
+
+    /// Roll up `tool_results` by [`TestQueryGenerator::get_tool_category`]
+    #[must_use]
+    pub fn by_category(tool_results: &BTreeMap<String, ToolTestResult>) -> BTreeMap<String, Self> {
+        let mut categories: BTreeMap<String, Self> = BTreeMap::new();
+        for result in tool_results.values().filter(|result| !result.is_skipped()) {
+            let category = TestQueryGenerator::get_tool_category(base_tool_name(&result.tool_name));
+            let rollup = categories.entry(category.to_string()).or_default();
+            rollup.total += 1;
+            if result.success {
+                rollup.successful += 1;
+            } else {
+                rollup.failed += 1;
+            }
+            if result.degraded {
+                rollup.degraded += 1;
+            }
+        }
+        categories
+    }
+
+    /// Roll up `tool_results` by the ` (endpoint)` suffix of their names
+    #[must_use]
+    pub fn by_endpoint(tool_results: &BTreeMap<String, ToolTestResult>) -> BTreeMap<String, Self> {
+        let mut endpoints: BTreeMap<String, Self> = BTreeMap::new();
+        for result in tool_results.values().filter(|result| !result.is_skipped()) {
+            let Some(endpoint) = result
+                .tool_name
+                .rsplit_once(" (")
+                .and_then(|(_, suffix)| suffix.strip_suffix(')'))
+            else {
+                continue;
+            };
+            let rollup = endpoints.entry(endpoint.to_string()).or_default();
+            rollup.total += 1;
+            if result.success {
+                rollup.successful += 1;
+            } else {
+                rollup.failed += 1;
+            }
+            if result.degraded {
+                rollup.degraded += 1;
+            }
+        }
+        endpoints
+    }
+}
+
+/// Count the failed tools among `results` by [`ErrorCategory`]
+#[must_use]
+pub fn count_error_categories<'a>(
+    results: impl IntoIterator<Item = &'a ToolTestResult>,
+) -> BTreeMap<ErrorCategory, usize> {
+    let mut counts = BTreeMap::new();
+    for category in results
+        .into_iter()
+        .filter(|result| result.is_failure())
+        .filter_map(|result| result.error_category)
+    {
+        *counts.entry(category).or_default() += 1;
+    }
+    counts
+}
+
+/// Tool name without the ` (endpoint)` suffix added when testing both endpoints
+/// or the `#n` suffix of an exhaustive corpus query
+#[must_use]
+pub fn base_tool_name(tool_name: &str) -> &str {
+    let name = tool_name
+        .split_once(" (")
+        .map_or(tool_name, |(name, _)| name);
+    name.split_once('#').map_or(name, |(name, _)| name)
+}
+
+/// One call of a tool in a run: the tool, the result name, and its corpus query
+#[derive(Debug, Clone)]
+struct TestCase {
+    tool: ToolInfo,
+    name: String,
+    query: Option<String>,
+}
+
+impl TestCase {
+    /// The calls `options` makes of `tool`
+    fn expand(tool: &ToolInfo, options: &TestAllOptions) -> Vec<Self> {
+        options
+            .test_cases(&tool.name)
+            .into_iter()
+            .map(|(name, query)| Self {
+                tool: tool.clone(),
+                name,
+                query,
+            })
+            .collect()
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -115,14 +694,163 @@ pub struct ToolInfo {
     pub schema: Option<Value>,
 }
 
+impl ToolInfo {
+    /// Tools in a `tools/list` response, either the JSON-RPC envelope or its `result`
+    #[must_use]
+    pub fn from_tools_list(data: &Value) -> Vec<Self> {
+        let tools = data
+            .get("result")
+            .map_or_else(|| data.get("tools"), |result| result.get("tools"))
+            .and_then(Value::as_array);
+        tools
+            .into_iter()
+            .flatten()
+            .filter_map(|tool| {
+                let name = tool.get("name").and_then(Value::as_str)?;
+                Some(Self {
+                    name: name.to_string(),
+                    description: tool
+                        .get("description")
+                        .and_then(Value::as_str)
+                        .map(ToString::to_string),
+                    schema: tool.get("inputSchema").cloned(),
+                })
+            })
+            .collect()
+    }
+}
+
 impl AllToolsTestResult {
+    /// Append `note` to the error of every tool rejected for authentication
+    pub fn annotate_auth_failures(&mut self, note: &str) {
+        for result in self.tool_results.values_mut() {
+            if result.error_class == Some(ErrorClass::Auth)
+                && let Some(error) = &mut result.error_message
+            {
+                let _ = write!(error, " ({note})");
+            }
+        }
+    }
+
+    /// Failure count with its category breakdown, e.g. `7 failures: 5 auth, 2 timeout`
+    #[must_use]
+    pub fn describe_failures(&self) -> Option<String> {
+        let noun = if self.failed_tools == 1 {
+            "failure"
+        } else {
+            "failures"
+        };
+        self.failure_breakdown()
+            .map(|breakdown| format!("{} {noun}: {breakdown}", self.failed_tools))
+    }
+
+    /// Failed tools per category, e.g. `5 auth, 2 timeout`
+    fn failure_breakdown(&self) -> Option<String> {
+        if self.failed_tools == 0 {
+            return None;
+        }
+        let mut parts: Vec<String> = self
+            .error_categories
+            .iter()
+            .map(|(category, count)| format!("{count} {}", category.as_str()))
+            .collect();
+        let other = self
+            .failed_tools
+            .saturating_sub(self.error_categories.values().sum());
+        if other > 0 {
+            parts.push(format!("{other} other"));
+        }
+        Some(parts.join(", "))
+    }
+
+    /// Whether any tool was rejected for authentication
+    #[must_use]
+    pub fn has_auth_failures(&self) -> bool {
+        self.tool_results
+            .values()
+            .any(|result| result.error_class == Some(ErrorClass::Auth))
+    }
+
+    /// Tools that ran, passing or failing
+    #[must_use]
+    pub const fn tested_tools(&self) -> usize {
+        self.successful_tools + self.failed_tools
+    }
+
+    /// Tools skipped for `reason`
+    #[must_use]
+    pub fn skipped_for(&self, reason: SkipReason) -> usize {
+        self.tool_results
+            .values()
+            .filter(|result| result.skipped == Some(reason))
+            .count()
+    }
+
+    /// Skipped tools per reason, e.g. `8 filtered out, 2 interrupted`
+    fn skip_breakdown(&self) -> Option<String> {
+        if self.skipped_tools == 0 {
+            return None;
+        }
+        let mut counts: BTreeMap<SkipReason, usize> = BTreeMap::new();
+        for reason in self
+            .tool_results
+            .values()
+            .filter_map(|result| result.skipped)
+        {
+            *counts.entry(reason).or_default() += 1;
+        }
+        let parts: Vec<String> = counts
+            .iter()
+            .map(|(reason, count)| format!("{count} {}", reason.as_str()))
+            .collect();
+        Some(parts.join(", "))
+    }
+
     #[must_use]
     pub fn format_output(&self, format: &str, verbose: bool, debug: bool) -> String {
+        self.format_output_sorted(format, verbose, debug, ResultSort::Name)
+    }
+
+    /// [`Self::format_output`], listing tools in `sort` order in the text report
+    #[must_use]
+    pub fn format_output_sorted(
+        &self,
+        format: &str,
+        verbose: bool,
+        debug: bool,
+        sort: ResultSort,
+    ) -> String {
         match format {
             "json" => self.format_json(),
             "summary" => self.format_summary(),
-            _ => self.format_text(verbose, debug),
+            "markdown" | "md" => self.format_markdown(),
+            _ => self.format_text(verbose, debug, sort),
+        }
+    }
+
+    /// Results in `sort` order, ties broken by tool name
+    #[must_use]
+    pub fn sorted_tools(&self, sort: ResultSort) -> Vec<&ToolTestResult> {
+        // `tool_results` is ordered by name, and the sort is stable
+        let mut tools: Vec<_> = self.tool_results.values().collect();
+        match sort {
+            ResultSort::Name => {}
+            ResultSort::Duration => {
+                tools.sort_by_key(|result| std::cmp::Reverse(result.response_time_ms));
+            }
+            ResultSort::Status => tools.sort_by_key(|result| {
+                if result.is_skipped() {
+                    3
+                } else if !result.success {
+                    0
+                } else if result.degraded {
+                    1
+                } else {
+                    2
+                }
+            }),
         }
+        tools
     }
 
     fn format_json(&self) -> String {
@@ -131,21 +859,287 @@ impl AllToolsTestResult {
 
     #[allow(clippy::cast_precision_loss)]
     fn format_summary(&self) -> String {
-        format!(
+        let mut output = format!(
             "🧪 Test Summary: {}/{} tools successful ({}%)\n⏱️  Total time: {:.2}s",
             self.successful_tools,
-            self.total_tools,
-            if self.total_tools > 0 {
-                (self.successful_tools * 100) / self.total_tools
-            } else {
-                0
-            },
+            self.tested_tools(),
+            (self.successful_tools * 100)
+                .checked_div(self.tested_tools())
+                .unwrap_or(0),
             self.execution_summary.total_duration_ms as f64 / 1000.0
-        )
+        );
+        if let Some(failures) = self.describe_failures() {
+            let _ = write!(output, "\n💥 {failures}");
+        }
+        if let Some(skips) = self.skip_breakdown() {
+            let _ = write!(output, "\n⏭️  Skipped: {} ({skips})", self.skipped_tools);
+        }
+        if let Some(server) = &self.server {
+            let _ = write!(output, "\n🖥️  Server: {}", server.describe());
+        }
+        output
+    }
+
+    /// Markdown document with status badges, a per-tool table, and collapsible error details
+    #[allow(clippy::cast_precision_loss)]
+    fn format_markdown(&self) -> String {
+        let mut output = String::new();
+        output.push_str("# 🧪 Glean MCP Tools Test Results\n\n");
+
+        let (status, color) = if !self.success {
+            ("failing", "red")
+        } else if self.degraded_tools > 0 {
+            ("degraded", "yellow")
+        } else {
+            ("passing", "brightgreen")
+        };
+        let tools_color = if self.failed_tools == 0 {
+            "brightgreen"
+        } else if self.successful_tools == 0 {
+            "red"
+        } else {
+            "orange"
+        };
+        let _ = writeln!(
+            output,
+            "![status](https://img.shields.io/badge/status-{status}-{color}) \
+             ![tools](https://img.shields.io/badge/tools-{}%2F{}%20passed-{tools_color})\n",
+            self.successful_tools,
+            self.tested_tools()
+        );
+
+        let _ = writeln!(
+            output,
+            "**Tools:** {}/{} successful · **Total time:** {:.2}s · **Parallel:** {}  ",
+            self.successful_tools,
+            self.tested_tools(),
+            self.execution_summary.total_duration_ms as f64 / 1000.0,
+            if self.execution_summary.parallel_execution {
+                "yes"
+            } else {
+                "no"
+            }
+        );
+        if let Some(breakdown) = self.failure_breakdown() {
+            let _ = writeln!(
+                output,
+                "**Failures:** {} ({breakdown})  ",
+                self.failed_tools
+            );
+        }
+        if self.degraded_tools > 0 {
+            let _ = writeln!(
+                output,
+                "**Degraded:** {} tool(s) over latency budget  ",
+                self.degraded_tools
+            );
+        }
+        if self.oversized_tools > 0 {
+            let _ = writeln!(
+                output,
+                "**Oversized:** {} tool(s) over the response size limit  ",
+                self.oversized_tools
+            );
+        }
+        let pii_tools = self.tools_with_pii();
+        if !pii_tools.is_empty() {
+            let _ = writeln!(
+                output,
+                "**Sensitive data:** {} finding(s) in {} tool(s)  ",
+                pii_tools
+                    .iter()
+                    .map(|r| r.pii_findings.len())
+                    .sum::<usize>(),
+                pii_tools.len()
+            );
+        }
+        if let Some(skips) = self.skip_breakdown() {
+            let _ = writeln!(output, "**Skipped:** {} ({skips})  ", self.skipped_tools);
+        }
+        if let Some(reason) = self.cancelled {
+            let _ = writeln!(output, "**Stopped early:** {}  ", reason.as_str());
+        }
+        if self.tool_discovery == Some(ToolDiscovery::AssumedDefaults) {
+            let _ = writeln!(
+                output,
+                "**Tool list:** default set assumed; discovery returned no tools  "
+            );
+        }
+        if !self.categories.is_empty() {
+            let categories: Vec<String> = self
+                .categories
+                .iter()
+                .map(|(category, rollup)| {
+                    format!(
+                        "{category} {}/{} ({:.0}%)",
+                        rollup.successful,
+                        rollup.total,
+                        rollup.success_rate()
+                    )
+                })
+                .collect();
+            let _ = writeln!(output, "**Categories:** {}  ", categories.join(", "));
+        }
+        if !self.endpoints.is_empty() {
+            let endpoints: Vec<String> = self
+                .endpoints
+                .iter()
+                .map(|(endpoint, rollup)| {
+                    format!(
+                        "{endpoint} {}/{} ({:.0}%)",
+                        rollup.successful,
+                        rollup.total,
+                        rollup.success_rate()
+                    )
+                })
+                .collect();
+            let _ = writeln!(output, "**Endpoints:** {}  ", endpoints.join(", "));
+        }
+        if !self.labels.is_empty() {
+            let labels: Vec<String> = self
+                .labels
+                .iter()
+                .map(|(k, v)| format!("`{k}={v}`"))
+                .collect();
+            let _ = writeln!(output, "**Labels:** {}  ", labels.join(", "));
+        }
+        if let Some(server) = &self.server {
+            let _ = writeln!(output, "**Server:** {}  ", server.describe());
+        }
+        if let Some(conformance) = &self.conformance {
+            let _ = writeln!(
+                output,
+                "**Conformance:** {:.1}% ({}/{} responses)  ",
+                conformance.score, conformance.conforming, conformance.checked
+            );
+        }
+        if let Some(error) = &self.error {
+            let _ = write!(
+                output,
+                "\n> ⚠️ **Global error:** {}\n",
+                markdown_cell(error)
+            );
+        }
+
+        // Filtered-out tools are counted in the summary but left out of the table
+        let mut tools: Vec<_> = self
+            .tool_results
+            .values()
+            .filter(|result| result.skipped != Some(SkipReason::FilteredOut))
+            .collect();
+        tools.sort_by(|a, b| a.tool_name.cmp(&b.tool_name));
+        if !tools.is_empty() {
+            output.push_str("\n| | Tool | Time | Query |\n|---|---|---:|---|\n");
+        }
+        for result in &tools {
+            if let Some(reason) = result.skipped {
+                let _ = writeln!(
+                    output,
+                    "| ⏭️ | `{}` | — | skipped: {} |",
+                    result.tool_name,
+                    reason.as_str()
+                );
+                continue;
+            }
+            let status = if result.degraded {
+                "🐢"
+            } else if result.success {
+                "✅"
+            } else {
+                "❌"
+            };
+            let budget = result
+                .latency_budget_ms
+                .filter(|_| result.degraded)
+                .map_or_else(String::new, |budget| format!(" (budget {budget}ms)"));
+            let size = result
+                .response_bytes
+                .filter(|_| result.oversized)
+                .map_or_else(String::new, |bytes| format!(" 📦 {}", format_bytes(bytes)));
+            let _ = writeln!(
+                output,
+                "| {status} | `{}` | {:.2}s{budget}{size} | {} |",
+                result.tool_name,
+                result.response_time_ms as f64 / 1000.0,
+                markdown_cell(&result.test_query)
+            );
+        }
+
+        let failed: Vec<_> = tools.iter().filter(|result| result.is_failure()).collect();
+        if !failed.is_empty() {
+            output.push_str("\n## 🚨 Failures\n");
+        }
+        for result in failed {
+            let _ = write!(
+                output,
+                "\n<details>\n<summary>❌ <code>{}</code>: {}</summary>\n\n",
+                html_escape(&result.tool_name),
+                html_escape(
+                    result
+                        .error_message
+                        .as_deref()
+                        .and_then(|error| error.lines().next())
+                        .unwrap_or("failed")
+                )
+            );
+            let _ = writeln!(output, "**Query:** {}\n", markdown_cell(&result.test_query));
+            if let Some(category) = result.error_category {
+                let _ = writeln!(output, "**Category:** {}\n", category.as_str());
+            }
+            if let Some(detail) = result.error_detail.as_ref().map(ErrorDetail::describe)
+                && !detail.is_empty()
+            {
+                let _ = writeln!(output, "**Response:** {}\n", markdown_cell(&detail));
+            }
+            if let Some(reference) = result.request_reference() {
+                let _ = writeln!(output, "**Request:** {}\n", markdown_cell(&reference));
+            }
+            for (heading, details) in [
+                ("Error", &result.error_message),
+                ("Validation", &result.validation_details),
+            ] {
+                if let Some(details) = details {
+                    let _ = writeln!(output, "**{heading}:**\n\n```\n{details}\n```\n");
+                }
+            }
+            output.push_str("</details>\n");
+        }
+
+        if !pii_tools.is_empty() {
+            output.push_str("\n## 🕵️ Sensitive Data\n\n| Tool | Detector | Matches | Samples |\n|---|---|---|---|\n");
+            for result in &pii_tools {
+                for finding in &result.pii_findings {
+                    let _ = writeln!(
+                        output,
+                        "| `{}` | {} | {} | {} |",
+                        result.tool_name,
+                        finding.detector,
+                        finding.count,
+                        finding
+                            .samples
+                            .iter()
+                            .map(|sample| format!("`{}`", markdown_cell(sample)))
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    );
+                }
+            }
+        }
+
+        output
+    }
+
+    /// Tools whose responses had sensitive data findings
+    #[must_use]
+    pub fn tools_with_pii(&self) -> Vec<&ToolTestResult> {
+        self.tool_results
+            .values()
+            .filter(|result| !result.pii_findings.is_empty())
+            .collect()
     }
 
     #[allow(clippy::cast_precision_loss)]
-    fn format_text(&self, verbose: bool, debug: bool) -> String {
+    fn format_text(&self, verbose: bool, debug: bool, sort: ResultSort) -> String {
         let mut output = String::new();
 
         // Header with overall status
@@ -164,30 +1158,176 @@ impl AllToolsTestResult {
         let _ = writeln!(
             output,
             "🔧 Tools Tested: {}/{} successful",
-            self.successful_tools, self.total_tools
+            self.successful_tools,
+            self.tested_tools()
         );
 
-        if self.total_tools > 0 {
-            let success_rate = (self.successful_tools * 100) / self.total_tools;
+        if let Some(success_rate) = (self.successful_tools * 100).checked_div(self.tested_tools()) {
             let _ = writeln!(output, "📈 Success Rate: {success_rate}%");
         }
 
-        // Individual tool results
-        output.push_str("\n📋 Individual Tool Results:\n");
-        output.push_str("-".repeat(30).as_str());
-        output.push('\n');
+        if let Some(failures) = self.describe_failures() {
+            let _ = writeln!(output, "💥 {failures}");
+        }
 
-        for (tool_name, result) in &self.tool_results {
-            let status = if result.success { "✅" } else { "❌" };
-            let duration = format!("{:.2}s", result.response_time_ms as f64 / 1000.0);
-            let _ = writeln!(output, "  {status} {tool_name} ({duration})");
+        if self.degraded_tools > 0 {
+            let _ = writeln!(
+                output,
+                "🐢 Degraded: {} tool(s) over latency budget",
+                self.degraded_tools
+            );
+        }
+
+        if self.oversized_tools > 0 {
+            let _ = writeln!(
+                output,
+                "📦 Oversized: {} tool(s) over the response size limit",
+                self.oversized_tools
+            );
+        }
+
+        let pii_tools = self.tools_with_pii();
+        if !pii_tools.is_empty() {
+            let _ = writeln!(
+                output,
+                "🕵️  Sensitive data: {} finding(s) in {} tool(s)",
+                pii_tools
+                    .iter()
+                    .map(|r| r.pii_findings.len())
+                    .sum::<usize>(),
+                pii_tools.len()
+            );
+        }
+
+        if let Some(skips) = self.skip_breakdown() {
+            let _ = writeln!(output, "⏭️  Skipped: {} ({skips})", self.skipped_tools);
+        }
+
+        if let Some(reason) = self.cancelled {
+            let _ = writeln!(output, "⛔ Stopped early ({})", reason.as_str());
+        }
+
+        if self.tool_discovery == Some(ToolDiscovery::AssumedDefaults) {
+            let _ = writeln!(
+                output,
+                "⚠️  Tool list: default set assumed; discovery returned no tools"
+            );
+        }
+
+        if !self.categories.is_empty() {
+            let categories: Vec<String> = self
+                .categories
+                .iter()
+                .map(|(category, rollup)| {
+                    format!(
+                        "{category} {}/{} ({:.0}%)",
+                        rollup.successful,
+                        rollup.total,
+                        rollup.success_rate()
+                    )
+                })
+                .collect();
+            let _ = writeln!(output, "📂 Categories: {}", categories.join(", "));
+        }
+
+        if !self.endpoints.is_empty() {
+            let endpoints: Vec<String> = self
+                .endpoints
+                .iter()
+                .map(|(endpoint, rollup)| {
+                    format!(
+                        "{endpoint} {}/{} ({:.0}%)",
+                        rollup.successful,
+                        rollup.total,
+                        rollup.success_rate()
+                    )
+                })
+                .collect();
+            let _ = writeln!(output, "🌐 Endpoints: {}", endpoints.join(", "));
+        }
+
+        if !self.labels.is_empty() {
+            let labels: Vec<String> = self
+                .labels
+                .iter()
+                .map(|(k, v)| format!("{k}={v}"))
+                .collect();
+            let _ = writeln!(output, "🏷️  Labels: {}", labels.join(", "));
+        }
+
+        if let Some(run_id) = &self.run_id {
+            let _ = writeln!(output, "🆔 Run ID: {run_id}");
+        }
+
+        if let Some(server) = &self.server {
+            let _ = writeln!(output, "🖥️  Server: {}", server.describe());
+        }
+
+        // Individual tool results
+        output.push_str("\n📋 Individual Tool Results:\n");
+        output.push_str("-".repeat(30).as_str());
+        output.push('\n');
+
+        for result in self.sorted_tools(sort) {
+            let tool_name = &result.tool_name;
+            if let Some(reason) = result.skipped {
+                // Filtered-out tools are the bulk of most runs, so only list them in verbose mode
+                if verbose || reason != SkipReason::FilteredOut {
+                    let detail = result
+                        .validation_details
+                        .as_deref()
+                        .map_or_else(String::new, |detail| format!(": {detail}"));
+                    let _ = writeln!(output, "  ⏭️  {tool_name} ({}{detail})", reason.as_str());
+                }
+                continue;
+            }
+            let status = if result.degraded {
+                "🐢"
+            } else if result.success {
+                "✅"
+            } else {
+                "❌"
+            };
+            let duration = format!("{:.2}s", result.response_time_ms as f64 / 1000.0);
+            let budget = result
+                .latency_budget_ms
+                .filter(|_| result.degraded)
+                .map_or_else(String::new, |budget| format!(", budget {budget}ms"));
+            let size = result
+                .response_bytes
+                .zip(result.size_limit_bytes)
+                .filter(|_| result.oversized)
+                .map_or_else(String::new, |(bytes, limit)| {
+                    format!(", 📦 {} > {}", format_bytes(bytes), format_bytes(limit))
+                });
+            let _ = writeln!(output, "  {status} {tool_name} ({duration}{budget}{size})");
+            for finding in &result.pii_findings {
+                let _ = writeln!(output, "    🕵️  {}", finding.describe());
+            }
 
             if verbose {
                 let _ = writeln!(output, "    Query: \"{}\"", result.test_query);
+                if let Some(bytes) = result.response_bytes {
+                    let _ = writeln!(output, "    Size: {}", format_bytes(bytes));
+                }
+                if let Some(timing) = &result.timing {
+                    let _ = writeln!(output, "    Timing: {}", timing.describe());
+                }
+                if let Some(correlation_id) = &result.correlation_id {
+                    let _ = writeln!(output, "    Correlation ID: {correlation_id}");
+                }
+                for (name, value) in &result.request_ids {
+                    let _ = writeln!(output, "    {name}: {value}");
+                }
                 if !result.success {
                     if let Some(error) = &result.error_message {
                         let _ = writeln!(output, "    Error: {error}");
                     }
+                    if let Some(detail) = result.error_detail.as_ref().map(ErrorDetail::describe)
+                        && !detail.is_empty()
+                    {
+                        let _ = writeln!(output, "    Response: {detail}");
+                    }
                 } else if let Some(validation) = &result.validation_details {
                     let _ = writeln!(output, "    Validation: {validation}");
                 }
@@ -234,6 +1374,48 @@ impl AllToolsTestResult {
             "   Timeout per tool: {}s",
             self.execution_summary.timeout_settings
         );
+        if let Some(concurrency) = &self.execution_summary.concurrency {
+            let _ = writeln!(
+                output,
+                "   Concurrency: auto, started at {}, peaked at {} of {}, ended at {} (lowest {}), 429/5xx responses: {}",
+                concurrency.initial,
+                concurrency.peak,
+                concurrency.ceiling,
+                concurrency.final_limit,
+                concurrency.lowest,
+                concurrency.throttled_responses
+            );
+        }
+        if let Some(rate_limit) = &self.execution_summary.rate_limit {
+            let _ = writeln!(
+                output,
+                "   Rate limit: {} (ended at {})",
+                rate_limit
+                    .max_rps
+                    .map_or_else(|| "none".to_string(), |rps| format!("{rps} req/s")),
+                rate_limit
+                    .final_rps
+                    .map_or_else(|| "unlimited".to_string(), |rps| format!("{rps:.2} req/s"))
+            );
+            let _ = writeln!(
+                output,
+                "   Paced requests: {} ({:.2}s waiting), 429 responses: {}",
+                rate_limit.paced_requests,
+                rate_limit.total_wait_ms as f64 / 1000.0,
+                rate_limit.rate_limited_responses
+            );
+        }
+
+        if let Some(conformance) = &self.conformance {
+            let _ = writeln!(
+                output,
+                "📐 Conformance: {:.1}% ({}/{} responses)",
+                conformance.score, conformance.conforming, conformance.checked
+            );
+            for (violation, count) in &conformance.violations {
+                let _ = writeln!(output, "   - {violation} ({count}x)");
+            }
+        }
 
         if let Some(error) = &self.error {
             let _ = write!(output, "\n⚠️  Global Error: {error}\n");
@@ -241,9 +1423,9 @@ impl AllToolsTestResult {
 
         // Detailed error section for failed tests (always shown, not just in verbose mode)
         let failed_tools: Vec<_> = self
-            .tool_results
-            .iter()
-            .filter(|(_, result)| !result.success)
+            .sorted_tools(sort)
+            .into_iter()
+            .filter(|result| result.is_failure())
             .collect();
 
         if !failed_tools.is_empty() {
@@ -251,12 +1433,16 @@ impl AllToolsTestResult {
             output.push_str("=".repeat(50).as_str());
             output.push('\n');
 
-            for (tool_name, result) in failed_tools {
+            for result in failed_tools {
+                let tool_name = &result.tool_name;
                 let _ = write!(output, "\n❌ {tool_name} - FAILED\n");
                 output.push_str("-".repeat(30).as_str());
                 output.push('\n');
 
                 let _ = writeln!(output, "🔍 Test Query: \"{}\"", result.test_query);
+                if let Some(category) = result.error_category {
+                    let _ = writeln!(output, "🏷️  Category: {}", category.as_str());
+                }
                 let _ = writeln!(
                     output,
                     "⏱️  Duration: {:.2}s",
@@ -271,6 +1457,14 @@ impl AllToolsTestResult {
                         let _ = writeln!(output, "   {line}");
                     }
                 }
+                if let Some(detail) = result.error_detail.as_ref().map(ErrorDetail::describe)
+                    && !detail.is_empty()
+                {
+                    let _ = writeln!(output, "🧾 Response: {detail}");
+                }
+                if let Some(reference) = result.request_reference() {
+                    let _ = writeln!(output, "🪪 Request: {reference}");
+                }
 
                 if let Some(validation) = &result.validation_details {
                     output.push_str("🔬 Validation Details:\n");
@@ -286,6 +1480,9 @@ impl AllToolsTestResult {
     }
 }
 
+/// Validation details of a success no response check has commented on
+const SUCCESS_DETAILS: &str = "Response received successfully";
+
 impl ToolTestResult {
     #[must_use]
     pub fn new_success(
@@ -301,7 +1498,20 @@ impl ToolTestResult {
             test_query,
             response_data: Some(response_data),
             error_message: None,
-            validation_details: Some("Response received successfully".to_string()),
+            validation_details: Some(SUCCESS_DETAILS.to_string()),
+            latency_budget_ms: None,
+            degraded: false,
+            error_class: None,
+            error_category: None,
+            timing: None,
+            correlation_id: None,
+            request_ids: BTreeMap::new(),
+            error_detail: None,
+            response_bytes: None,
+            size_limit_bytes: None,
+            oversized: false,
+            pii_findings: Vec::new(),
+            skipped: None,
         }
     }
 
@@ -320,9 +1530,207 @@ impl ToolTestResult {
             response_data: None,
             error_message: Some(error),
             validation_details: None,
+            latency_budget_ms: None,
+            degraded: false,
+            error_class: None,
+            error_category: None,
+            timing: None,
+            correlation_id: None,
+            request_ids: BTreeMap::new(),
+            error_detail: None,
+            response_bytes: None,
+            size_limit_bytes: None,
+            oversized: false,
+            pii_findings: Vec::new(),
+            skipped: None,
         }
     }
 
+    /// Fail a successful result whose response is in the wrong language
+    #[must_use]
+    pub fn with_language_check(self, check: &LanguageCheckConfig) -> Self {
+        let Some(response) = self.response_data.as_ref().filter(|_| self.success) else {
+            return self;
+        };
+        let outcome = check.check(&self.tool_name, &self.test_query, response);
+        self.with_check_outcome(outcome)
+    }
+
+    /// Fail a successful result whose search results contain duplicates
+    #[must_use]
+    pub fn with_duplicate_check(self, check: &DuplicateCheckConfig) -> Self {
+        let Some(response) = self.response_data.as_ref().filter(|_| self.success) else {
+            return self;
+        };
+        let outcome = check.check(&self.tool_name, response);
+        self.with_check_outcome(outcome)
+    }
+
+    /// Record sensitive data found in a successful response, failing the result under `fail_on_findings`
+    #[must_use]
+    pub fn with_pii_scan(mut self, scan: &PiiScanConfig) -> Self {
+        let Some(response) = self.response_data.as_ref().filter(|_| self.success) else {
+            return self;
+        };
+        self.pii_findings = scan.scan(&self.tool_name, response);
+        if self.pii_findings.is_empty() {
+            return self;
+        }
+        let findings: Vec<String> = self.pii_findings.iter().map(PiiFinding::describe).collect();
+        let message = format!("Sensitive data in response: {}", findings.join("; "));
+        self.with_check_outcome(if scan.fail_on_findings {
+            Err(message)
+        } else {
+            Ok(Some(message))
+        })
+    }
+
+    /// Fail a successful result that doesn't meet the tool's profile expectations
+    #[must_use]
+    pub fn with_expectation_check(self, expectation: Option<&ResponseExpectation>) -> Self {
+        let Some((expectation, response)) =
+            expectation.zip(self.response_data.as_ref().filter(|_| self.success))
+        else {
+            return self;
+        };
+        let outcome = expectation.check(response);
+        self.with_check_outcome(outcome)
+    }
+
+    /// Fail a successful search result that is empty, missing fields, or off-domain
+    #[must_use]
+    pub fn with_result_check(self, check: &ResultCheckConfig) -> Self {
+        let Some(response) = self.response_data.as_ref().filter(|_| self.success) else {
+            return self;
+        };
+        let outcome = check.check(&self.tool_name, response);
+        self.with_check_outcome(outcome)
+    }
+
+    /// Fail a successful result whose response shape differs from its snapshot on `endpoint_url`
+    #[must_use]
+    pub fn with_snapshot_check(self, check: Option<&SnapshotCheck>, endpoint_url: &str) -> Self {
+        let Some((check, response)) =
+            check.zip(self.response_data.as_ref().filter(|_| self.success))
+        else {
+            return self;
+        };
+        let outcome = check.check(&self.tool_name, endpoint_url, response);
+        self.with_check_outcome(outcome)
+    }
+
+    /// Record the response's size, flagging successes over their size limit as oversized
+    #[must_use]
+    pub fn with_response_size(mut self, config: &ResponseSizeConfig) -> Self {
+        let Some(bytes) = self.response_data.as_ref().map(response_bytes) else {
+            return self;
+        };
+        self.response_bytes = Some(bytes);
+        if !config.enabled || !self.success {
+            return self;
+        }
+        let limit = config.limit_for(&self.tool_name);
+        self.size_limit_bytes = Some(limit);
+        self.oversized = bytes > limit;
+        if self.oversized {
+            let note = format!(
+                "Response is {}, over the {} limit; MCP hosts may truncate it",
+                format_bytes(bytes),
+                format_bytes(limit)
+            );
+            return self.with_check_outcome(Ok(Some(note)));
+        }
+        self
+    }
+
+    /// Record a response check: notes accumulate in the validation details, failures fail the result
+    pub(crate) fn with_check_outcome(
+        mut self,
+        outcome: std::result::Result<Option<String>, String>,
+    ) -> Self {
+        match outcome {
+            Ok(Some(note)) => {
+                self.validation_details = Some(match self.validation_details.take() {
+                    Some(details) if details != SUCCESS_DETAILS => format!("{details}\n{note}"),
+                    _ => note,
+                });
+            }
+            Ok(None) => {}
+            Err(failure) => {
+                self.success = false;
+                self.error_message = Some(failure);
+                self.validation_details = None;
+                self.error_class = Some(ErrorClass::Validation);
+                self.error_category = Some(ErrorCategory::ValidationFailure);
+            }
+        }
+        self
+    }
+
+    /// Record why the tool failed, categorizing it by class and error message
+    #[must_use]
+    pub fn with_error_class(mut self, class: ErrorClass) -> Self {
+        self.error_class = Some(class);
+        self.error_category = Some(ErrorCategory::from_class(
+            class,
+            self.error_message.as_deref(),
+        ));
+        self
+    }
+
+    /// Attach the connection phase timings of the tool's request
+    #[must_use]
+    pub const fn with_timing(mut self, timing: Option<ConnectionTiming>) -> Self {
+        self.timing = timing;
+        self
+    }
+
+    /// Attach the correlation id the tool's request was sent with
+    #[must_use]
+    pub fn with_correlation_id(mut self, correlation_id: Option<String>) -> Self {
+        self.correlation_id = correlation_id;
+        self
+    }
+
+    /// Attach the request and trace ids the server answered with
+    #[must_use]
+    pub fn with_request_ids(mut self, request_ids: BTreeMap<String, String>) -> Self {
+        self.request_ids = request_ids;
+        self
+    }
+
+    /// The ids to quote when reporting the request, e.g. `correlation id 20261016T…-0004, x-request-id 7f3a`
+    #[must_use]
+    pub fn request_reference(&self) -> Option<String> {
+        let ids: Vec<String> = self
+            .correlation_id
+            .iter()
+            .map(|id| format!("correlation id {id}"))
+            .chain(
+                self.request_ids
+                    .iter()
+                    .map(|(name, value)| format!("{name} {value}")),
+            )
+            .collect();
+        (!ids.is_empty()).then(|| ids.join(", "))
+    }
+
+    /// Attach the structured details of the server's error response
+    #[must_use]
+    pub fn with_error_detail(mut self, error_detail: Option<ErrorDetail>) -> Self {
+        self.error_detail = error_detail;
+        self
+    }
+
+    /// Hold the result to `budget_ms`, marking slow successes as degraded
+    #[must_use]
+    pub fn with_latency_budget(mut self, budget_ms: Option<u64>) -> Self {
+        self.latency_budget_ms = budget_ms;
+        self.degraded =
+            self.success && budget_ms.is_some_and(|budget| self.response_time_ms > budget);
+        self
+    }
+
     #[must_use]
     pub fn new_timeout(tool_name: String, timeout_seconds: u64, test_query: String) -> Self {
         Self {
@@ -333,15 +1741,163 @@ impl ToolTestResult {
             response_data: None,
             error_message: Some(format!("Timeout after {timeout_seconds}s")),
             validation_details: None,
+            latency_budget_ms: None,
+            degraded: false,
+            error_class: None,
+            error_category: None,
+            timing: None,
+            correlation_id: None,
+            request_ids: BTreeMap::new(),
+            error_detail: None,
+            response_bytes: None,
+            size_limit_bytes: None,
+            oversized: false,
+            pii_findings: Vec::new(),
+            skipped: None,
+        }
+    }
+
+    /// A tool that wasn't run, with `detail` explaining why when the reason alone doesn't
+    #[must_use]
+    pub const fn new_skipped(
+        tool_name: String,
+        reason: SkipReason,
+        detail: Option<String>,
+    ) -> Self {
+        Self {
+            tool_name,
+            success: false,
+            response_time_ms: 0,
+            test_query: String::new(),
+            response_data: None,
+            error_message: None,
+            validation_details: detail,
+            latency_budget_ms: None,
+            degraded: false,
+            error_class: None,
+            error_category: None,
+            timing: None,
+            correlation_id: None,
+            request_ids: BTreeMap::new(),
+            error_detail: None,
+            response_bytes: None,
+            size_limit_bytes: None,
+            oversized: false,
+            pii_findings: Vec::new(),
+            skipped: Some(reason),
+        }
+    }
+
+    #[must_use]
+    pub const fn is_skipped(&self) -> bool {
+        self.skipped.is_some()
+    }
+
+    /// Whether the tool ran and failed, as opposed to passing or being skipped
+    #[must_use]
+    pub const fn is_failure(&self) -> bool {
+        !self.success && self.skipped.is_none()
+    }
+}
+
+/// Parse `--args` JSON into `tools/call` arguments, which must be an object
+pub fn parse_tool_arguments(raw: &str) -> Result<Value> {
+    match serde_json::from_str::<Value>(raw) {
+        Ok(arguments) if arguments.is_object() => Ok(arguments),
+        Ok(_) => Err(GleanMcpError::Config(format!(
+            "Invalid tool arguments '{raw}' (expected a JSON object)"
+        ))),
+        Err(e) => Err(GleanMcpError::Config(format!(
+            "Invalid tool arguments '{raw}': {e}"
+        ))),
+    }
+}
+
+/// Tool names the framework knows how to query; hosts may add a `glean_` prefix
+pub const KNOWN_TOOLS: [&str; 13] = [
+    "search",
+    "chat",
+    "read_document",
+    "fetch",
+    "code_search",
+    "employee_search",
+    "gmail_search",
+    "outlook_search",
+    "meeting_lookup",
+    "web_browser",
+    "gemini_web_search",
+    "list_agents",
+    "run_agent",
+];
+
+/// Connector tags for the known tools, used by `--tag` and `--exclude-tag`
+const DEFAULT_TOOL_TAGS: [(&str, &[&str]); 7] = [
+    ("code_search", &["code"]),
+    ("employee_search", &["people"]),
+    ("gmail_search", &["email"]),
+    ("outlook_search", &["email"]),
+    ("meeting_lookup", &["calendar"]),
+    ("web_browser", &["web"]),
+    ("gemini_web_search", &["web"]),
+];
+
+/// Tags per tool used when the config file doesn't define `tools_to_test.tags`
+#[must_use]
+pub fn default_tool_tags() -> BTreeMap<String, Vec<String>> {
+    DEFAULT_TOOL_TAGS
+        .iter()
+        .map(|(tool, tags)| {
+            (
+                (*tool).to_string(),
+                tags.iter().map(ToString::to_string).collect(),
+            )
+        })
+        .collect()
+}
+
+/// Whether `name` matches `pattern`, where `*` matches any run of characters and `?` any one
+#[must_use]
+pub fn glob_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let (mut p, mut n) = (0, 0);
+    // Position after the last `*` and the name position it is currently absorbing up to
+    let mut backtrack: Option<(usize, usize)> = None;
+    while n < name.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == name[n]) {
+            p += 1;
+            n += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            p += 1;
+            backtrack = Some((p, n));
+        } else if let Some((star_p, star_n)) = backtrack {
+            p = star_p;
+            n = star_n + 1;
+            backtrack = Some((star_p, n));
+        } else {
+            return false;
         }
     }
+    pattern[p..].iter().all(|c| *c == '*')
 }
 
 pub struct TestQueryGenerator;
 
 impl TestQueryGenerator {
+    /// The profile's query for `tool_name`, else its built-in Glean query
     #[must_use]
     pub fn generate_test_query(tool_name: &str) -> String {
+        let profile = tool_profile();
+        if let Some(query) = profile
+            .tools
+            .get(tool_name)
+            .and_then(|tool| tool.query.clone())
+        {
+            return query;
+        }
+        if profile.is_generic() {
+            return format!("test query for {tool_name}");
+        }
         match tool_name {
             "search" => "remote work policy".to_string(),
             "chat" => "What are the main benefits of using Glean?".to_string(),
@@ -359,200 +1915,610 @@ impl TestQueryGenerator {
         }
     }
 
+    /// Test query for `tool`: its built-in query if it has one, else one from
+    /// the first of `rules` (then the built-in rules) matching its description
+    #[must_use]
+    pub fn generate_query_for(tool: &ToolInfo, rules: &[QueryRule]) -> String {
+        if Self::get_tool_category(&tool.name) == "unknown"
+            && let Some(query) = query_from_rules(tool, rules)
+        {
+            return query;
+        }
+        Self::generate_test_query(&tool.name)
+    }
+
+    /// Arguments passing `query` as `tool_name`'s single string parameter
+    #[must_use]
+    pub fn generate_arguments(tool_name: &str, query: &str) -> Value {
+        if tool_profile().is_generic() {
+            return serde_json::json!({ "query": query });
+        }
+        // Different tools expect different parameter names
+        match tool_name {
+            "chat" => serde_json::json!({ "message": query }),
+            "read_document" => serde_json::json!({ "url": query }),
+            _ => serde_json::json!({ "query": query }),
+        }
+    }
+
+    /// The query in `arguments`, or the arguments as compact JSON when there is none
+    #[must_use]
+    pub fn describe_arguments(arguments: &Value) -> String {
+        ["query", "message", "url"]
+            .iter()
+            .find_map(|key| arguments.get(key).and_then(Value::as_str))
+            .map_or_else(|| arguments.to_string(), ToString::to_string)
+    }
+
+    /// The profile's category for `tool_name`, else its built-in Glean category
     #[must_use]
     pub fn get_tool_category(tool_name: &str) -> &'static str {
+        let profile = tool_profile();
+        if let Some(tool) = profile.tools.get(tool_name) {
+            return TOOL_CATEGORIES
+                .into_iter()
+                .find(|category| *category == tool.category)
+                .unwrap_or("unknown");
+        }
+        if profile.is_generic() {
+            return "unknown";
+        }
         match tool_name {
             "search" | "chat" | "read_document" => "core",
             "code_search" | "employee_search" | "gmail_search" | "outlook_search"
             | "meeting_lookup" | "web_browser" | "gemini_web_search" => "enterprise",
+            "list_agents" | "run_agent" => "agents",
             _ => "unknown",
         }
     }
 }
 
 pub struct GleanMCPInspector {
+    instance: String,
     server_url: String,
     chatgpt_url: String,
+    /// Where endpoints other than `default` and `chatgpt` are found
+    url_template: String,
     auth_token: Option<String>,
+    transport: McpTransport,
+    reporter: Arc<dyn Reporter>,
 }
 
 impl GleanMCPInspector {
     #[must_use]
     pub fn new(instance_name: Option<&str>) -> Self {
+        Self::with_reporter(instance_name, reporter_for_format("text"))
+    }
+
+    /// Create an inspector that sends progress and output events to `reporter`
+    #[must_use]
+    pub fn with_reporter(instance_name: Option<&str>, reporter: Arc<dyn Reporter>) -> Self {
         let instance_name = instance_name.unwrap_or("glean-dev");
 
-        // Read auth token from GLEAN_AUTH_TOKEN environment variable
-        let auth_token = std::env::var("GLEAN_AUTH_TOKEN").ok();
+        // Read the auth token from the first configured variable that is set
+        let auth_token = find_auth_token(instance_name);
 
-        let term = Term::stdout();
-        if auth_token.is_some() {
-            let _ = term.write_line(&format!(
-                "🔑 {}",
-                style("Found authentication token in GLEAN_AUTH_TOKEN").green()
-            ));
+        if let Some(token) = &auth_token {
+            reporter.info(&format!("Found authentication token in {}", token.variable));
         } else {
-            let _ = term.write_line(&format!(
-                "ℹ️  {}",
-                style("No auth token found (set GLEAN_AUTH_TOKEN environment variable)").dim()
+            reporter.info(&format!(
+                "No auth token found (set one of {})",
+                auth_token_variables(instance_name).join(", ")
             ));
         }
+        let auth_token = auth_token.map(|token| token.value);
 
         Self {
-            server_url: format!("https://{instance_name}-be.glean.com/mcp/default"),
-            chatgpt_url: format!("https://{instance_name}-be.glean.com/mcp/chatgpt"),
+            instance: instance_name.to_string(),
+            server_url: endpoint_url(DEFAULT_URL_TEMPLATE, instance_name, "default"),
+            chatgpt_url: endpoint_url(DEFAULT_URL_TEMPLATE, instance_name, "chatgpt"),
+            url_template: DEFAULT_URL_TEMPLATE.to_string(),
+            transport: McpTransport::new(auth_token.clone())
+                .with_run_id(&crate::RunRecord::generate_id()),
             auth_token,
+            reporter,
         }
     }
 
-    /// Test all available MCP tools with clean `MultiProgress` coordination
-    #[allow(clippy::future_not_send)]
-    #[allow(clippy::cast_possible_truncation)]
-    pub async fn test_all_tools(&self, options: &TestAllOptions) -> Result<AllToolsTestResult> {
-        self.test_both_endpoints(options).await
+    /// Build the instance's endpoint URLs from `template` (see [`endpoint_url`]) instead of [`DEFAULT_URL_TEMPLATE`]
+    #[must_use]
+    pub fn with_url_template(mut self, template: &str) -> Self {
+        self.server_url = endpoint_url(template, &self.instance, "default");
+        self.chatgpt_url = endpoint_url(template, &self.instance, "chatgpt");
+        self.url_template = template.to_string();
+        self
     }
 
-    /// Test all available MCP tools on both default and ChatGPT endpoints
-    #[allow(clippy::future_not_send)]
-    #[allow(clippy::cast_possible_truncation)]
-    pub async fn test_both_endpoints(
-        &self,
-        options: &TestAllOptions,
-    ) -> Result<AllToolsTestResult> {
-        let start_time = Instant::now();
-        let start_time_str = chrono::Utc::now().to_rfc3339();
-
-        // Test default endpoint first
-        let default_result = self
-            .test_tools_on_endpoint(&self.server_url, options)
-            .await?;
+    /// Target explicit endpoint URLs instead of the instance's `glean.com` endpoints
+    ///
+    /// Other named endpoints are looked for next to `server_url`.
+    #[must_use]
+    pub fn with_endpoints(mut self, server_url: &str, chatgpt_url: &str) -> Self {
+        self.set_endpoints(server_url, chatgpt_url);
+        self
+    }
 
-        // Test ChatGPT endpoint
-        let chatgpt_result = self
-            .test_tools_on_endpoint(&self.chatgpt_url, options)
-            .await?;
+    /// In-place form of [`Self::with_endpoints`]
+    pub fn set_endpoints(&mut self, server_url: &str, chatgpt_url: &str) {
+        self.server_url = server_url.to_string();
+        self.chatgpt_url = chatgpt_url.to_string();
+        self.url_template = server_url.to_string();
+    }
 
-        // Combine results
-        let mut combined_tool_results = HashMap::new();
+    /// Write each tool's request/response transcript under `capture`'s directory
+    #[must_use]
+    pub fn with_capture(mut self, capture: TranscriptCapture) -> Self {
+        self.transport = self.transport.with_capture(capture);
+        self
+    }
 
-        // Add default endpoint results with "(default)" suffix
-        for (tool_name, result) in &default_result.tool_results {
-            let mut combined_result = result.clone();
-            combined_result.tool_name = format!("{} (default)", tool_name);
-            combined_tool_results.insert(combined_result.tool_name.clone(), combined_result);
-        }
+    /// Record MCP interactions to, or replay them from, a cassette
+    #[must_use]
+    pub fn with_cassette(mut self, cassette: Cassette) -> Self {
+        self.transport = self.transport.with_cassette(cassette);
+        self
+    }
 
-        // Add ChatGPT endpoint results with "(chatgpt)" suffix
-        for (tool_name, result) in &chatgpt_result.tool_results {
-            let mut combined_result = result.clone();
-            combined_result.tool_name = format!("{} (chatgpt)", tool_name);
-            combined_tool_results.insert(combined_result.tool_name.clone(), combined_result);
-        }
+    /// Send MCP requests through the proxy `proxy` resolves
+    #[must_use]
+    pub fn with_proxy(mut self, proxy: ProxyConfig) -> Self {
+        self.transport = self.transport.with_proxy(proxy);
+        self
+    }
 
-        let total_tools = combined_tool_results.len();
-        let successful_tools = combined_tool_results.values().filter(|r| r.success).count();
-        let success = successful_tools == total_tools;
+    /// Use a custom CA bundle and/or mTLS client certificate for MCP requests
+    #[must_use]
+    pub fn with_tls(mut self, tls: TlsConfig) -> Self {
+        self.transport = self.transport.with_tls(tls);
+        self
+    }
 
-        let execution_summary = ExecutionSummary {
-            start_time: start_time_str,
-            end_time: chrono::Utc::now().to_rfc3339(),
-            total_duration_ms: start_time.elapsed().as_millis() as u64,
-            parallel_execution: options.parallel,
-            timeout_settings: options.timeout,
-        };
+    /// Add custom headers, e.g. `X-Glean-ActAs`, to every MCP request
+    #[must_use]
+    pub fn with_headers(mut self, headers: BTreeMap<String, String>) -> Self {
+        self.transport = self.transport.with_headers(headers);
+        self
+    }
 
-        Ok(AllToolsTestResult {
-            success,
-            total_tools,
-            successful_tools,
-            failed_tools: total_tools - successful_tools,
-            tool_results: combined_tool_results,
-            execution_summary,
-            error: None,
-        })
+    /// Use `run_id` instead of a generated id to tag this inspector's requests
+    #[must_use]
+    pub fn with_run_id(mut self, run_id: &str) -> Self {
+        self.transport = self.transport.with_run_id(run_id);
+        self
     }
 
-    /// Test all available MCP tools on the ChatGPT-specific endpoint
-    #[allow(clippy::future_not_send)]
-    #[allow(clippy::cast_possible_truncation)]
-    pub async fn test_chatgpt_tools(&self, options: &TestAllOptions) -> Result<AllToolsTestResult> {
-        self.test_tools_on_endpoint(&self.chatgpt_url, options)
-            .await
+    /// Id of this invocation, which every request's `X-Correlation-Id` starts with
+    #[must_use]
+    pub fn run_id(&self) -> Option<&str> {
+        self.transport.run_id()
     }
 
-    /// Test all available MCP tools on a specific endpoint
-    #[allow(clippy::future_not_send)]
-    #[allow(clippy::cast_possible_truncation)]
-    async fn test_tools_on_endpoint(
-        &self,
-        endpoint_url: &str,
-        options: &TestAllOptions,
-    ) -> Result<AllToolsTestResult> {
-        let start_time = Instant::now();
-        let start_time_str = chrono::Utc::now().to_rfc3339();
+    /// Give each MCP request up to `timeout` before the transport gives up on it
+    #[must_use]
+    pub fn with_request_timeout(mut self, timeout: Duration) -> Self {
+        self.transport = self.transport.with_request_timeout(timeout);
+        self
+    }
 
-        // Clean discovery phase
-        let spinner = ProgressBar::new_spinner();
-        spinner.set_style(
-            #[allow(clippy::literal_string_with_formatting_args)]
-            ProgressStyle::with_template("🔍 {spinner} {msg}")
-                .unwrap_or_else(|_| ProgressStyle::default_spinner()),
-        );
-        spinner.enable_steady_tick(Duration::from_millis(100));
-        spinner.set_message("Discovering available tools...");
+    /// Inject transport faults into a fraction of tool calls
+    #[must_use]
+    pub fn with_chaos(mut self, chaos: Chaos) -> Self {
+        self.transport = self.transport.with_chaos(chaos);
+        self
+    }
 
-        let tools_result = self
-            .list_available_tools_from_endpoint(endpoint_url, false)
-            .await?; // Force quiet mode
-        let available_tools = Self::extract_tools_from_result(&tools_result);
-        let tools_to_test = Self::filter_tools(&available_tools, options);
+    /// Reporter receiving this inspector's progress events
+    #[must_use]
+    pub fn reporter(&self) -> &dyn Reporter {
+        self.reporter.as_ref()
+    }
 
-        spinner.finish_with_message(format!("✅ Found {} tools to test", tools_to_test.len()));
+    /// Glean instance this inspector tests
+    #[must_use]
+    pub fn instance(&self) -> &str {
+        &self.instance
+    }
+
+    /// Default MCP endpoint URL
+    #[must_use]
+    pub fn server_url(&self) -> &str {
+        &self.server_url
+    }
+
+    /// Transport all of this inspector's requests go through
+    pub(crate) const fn transport(&self) -> &McpTransport {
+        &self.transport
+    }
+
+    /// `ChatGPT` connector MCP endpoint URL
+    #[must_use]
+    pub fn chatgpt_url(&self) -> &str {
+        &self.chatgpt_url
+    }
+
+    /// URL of `endpoint`: its configured `url`, else the inspector's URL for that name
+    #[must_use]
+    pub fn url_for_endpoint(&self, endpoint: &EndpointConfig) -> String {
+        match (&endpoint.url, endpoint.name.as_str()) {
+            (Some(url), _) => url.clone(),
+            (None, "default") => self.server_url.clone(),
+            (None, "chatgpt") => self.chatgpt_url.clone(),
+            (None, name) => endpoint_url(&self.url_template, &self.instance, name),
+        }
+    }
+
+    /// Identify the server from its `initialize` response
+    pub async fn server_identity(&self) -> Result<ServerIdentity> {
+        fetch_server_identity(&self.transport, &self.server_url).await
+    }
+
+    /// Identity of the server at `endpoint` for a run's result, reported through the reporter
+    ///
+    /// The `initialize` request is tallied in `conformance`.
+    async fn identify_server(
+        &self,
+        endpoint: &str,
+        conformance: &ConformanceChecker,
+    ) -> Option<ServerIdentity> {
+        let transport = self.transport.clone().with_conformance(conformance.clone());
+        match fetch_server_identity(&transport, endpoint).await {
+            Ok(server) => {
+                self.reporter
+                    .info(&format!("Server: {}", server.describe()));
+                Some(server)
+            }
+            Err(e) => {
+                self.reporter
+                    .warning(&format!("Could not identify the server: {e}"));
+                None
+            }
+        }
+    }
+
+    /// Measure the local clock's offset from the server's
+    pub async fn clock_skew(&self) -> Result<ClockSkew> {
+        measure_clock_skew(&self.transport, &self.server_url).await
+    }
+
+    /// Call `tool_name` once on the default endpoint, without retries
+    pub async fn call_tool(
+        &self,
+        tool_name: &str,
+        query: &str,
+        timeout: Duration,
+    ) -> Result<Value> {
+        async_timeout(
+            timeout,
+            Self::test_tool_direct(
+                &self.transport,
+                &self.server_url,
+                tool_name,
+                &TestQueryGenerator::generate_arguments(tool_name, query),
+                &mut RequestTrace::default(),
+            ),
+        )
+        .await
+    }
+
+    /// Test all available MCP tools with clean `MultiProgress` coordination
+    #[allow(clippy::future_not_send)]
+    #[allow(clippy::cast_possible_truncation)]
+    pub async fn test_all_tools(&self, options: &TestAllOptions) -> Result<AllToolsTestResult> {
+        self.test_both_endpoints(options).await
+    }
+
+    /// Test all available MCP tools on each of `options.endpoints` (default and `ChatGPT` unless configured)
+    ///
+    /// Results are combined with the endpoint's name as a suffix, e.g.
+    /// `search (agents)`, and an endpoint's `expected_tools` that it doesn't
+    /// advertise are failed.
+    #[allow(clippy::future_not_send)]
+    #[allow(clippy::cast_possible_truncation)]
+    pub async fn test_both_endpoints(
+        &self,
+        options: &TestAllOptions,
+    ) -> Result<AllToolsTestResult> {
+        let start_time = Instant::now();
+        let start_time_str = chrono::Utc::now().to_rfc3339();
+        if let Some(run_id) = self.run_id() {
+            self.reporter.info(&format!("Run ID: {run_id}"));
+        }
+        // One budget for the whole run, so all endpoints share the pace
+        let rate_limiter = RateLimiter::new(options.max_rps);
+        let concurrency = (options.parallel && options.adaptive_concurrency)
+            .then(|| AdaptiveConcurrency::new(AUTO_START, options.max_concurrent));
+        let conformance = ConformanceChecker::new();
+        let endpoint_urls: Vec<String> = options
+            .endpoints
+            .iter()
+            .map(|endpoint| self.url_for_endpoint(endpoint))
+            .collect();
+        let server = match endpoint_urls.first() {
+            Some(url) => self.identify_server(url, &conformance).await,
+            None => None,
+        };
+
+        // Test the endpoints in order, skipping the rest once the run is cancelled
+        let mut combined_tool_results = BTreeMap::new();
+        let mut tool_discovery = None;
+        for (i, (endpoint, url)) in options.endpoints.iter().zip(&endpoint_urls).enumerate() {
+            if i > 0 && options.cancel.is_cancelled() {
+                break;
+            }
+            let endpoint_result = self
+                .test_tools_on_endpoint(
+                    url,
+                    options,
+                    endpoint,
+                    &rate_limiter,
+                    concurrency.as_ref(),
+                    &conformance,
+                    server.as_ref(),
+                )
+                .await?;
+            tool_discovery = tool_discovery.max(endpoint_result.tool_discovery);
+            for (tool_name, result) in endpoint_result.tool_results {
+                let mut combined_result = result;
+                combined_result.tool_name = format!("{tool_name} ({})", endpoint.name);
+                combined_tool_results.insert(combined_result.tool_name.clone(), combined_result);
+            }
+        }
+
+        let total_tools = combined_tool_results.len();
+        let successful_tools = combined_tool_results.values().filter(|r| r.success).count();
+        let skipped_tools = combined_tool_results
+            .values()
+            .filter(|r| r.is_skipped())
+            .count();
+        let failed_tools = total_tools - successful_tools - skipped_tools;
+        let degraded_tools = combined_tool_results
+            .values()
+            .filter(|r| r.degraded)
+            .count();
+        let oversized_tools = combined_tool_results
+            .values()
+            .filter(|r| r.oversized)
+            .count();
+        let cancelled = options.cancel.reason();
+        let success = failed_tools == 0 && cancelled.is_none();
+
+        let execution_summary = ExecutionSummary {
+            start_time: start_time_str,
+            end_time: chrono::Utc::now().to_rfc3339(),
+            total_duration_ms: start_time.elapsed().as_millis() as u64,
+            parallel_execution: options.parallel,
+            timeout_settings: options.timeout,
+            rate_limit: rate_limiter.summary(),
+            concurrency: concurrency.as_ref().map(AdaptiveConcurrency::stats),
+        };
+
+        Ok(AllToolsTestResult {
+            schema_version: schema_version(),
+            success,
+            total_tools,
+            successful_tools,
+            failed_tools,
+            skipped_tools,
+            degraded_tools,
+            oversized_tools,
+            categories: CategoryRollup::by_category(&combined_tool_results),
+            endpoints: CategoryRollup::by_endpoint(&combined_tool_results),
+            error_categories: count_error_categories(combined_tool_results.values()),
+            tool_results: combined_tool_results,
+            execution_summary,
+            error: None,
+            run_id: self.transport.run_id().map(String::from),
+            labels: options.labels.clone(),
+            server,
+            conformance: conformance.report(),
+            cancelled,
+            tool_discovery,
+        })
+    }
+
+    /// Test all available MCP tools on the ChatGPT-specific endpoint
+    #[allow(clippy::future_not_send)]
+    #[allow(clippy::cast_possible_truncation)]
+    pub async fn test_chatgpt_tools(&self, options: &TestAllOptions) -> Result<AllToolsTestResult> {
+        let rate_limiter = RateLimiter::new(options.max_rps);
+        let conformance = ConformanceChecker::new();
+        let server = self.identify_server(&self.chatgpt_url, &conformance).await;
+        let mut result = self
+            .test_tools_on_endpoint(
+                &self.chatgpt_url,
+                options,
+                &EndpointConfig::named("chatgpt"),
+                &rate_limiter,
+                None,
+                &conformance,
+                server.as_ref(),
+            )
+            .await?;
+        result.server = server;
+        Ok(result)
+    }
+
+    /// Test all available MCP tools on a specific endpoint
+    ///
+    /// When `server` is known and doesn't advertise the `tools` capability,
+    /// discovery is skipped and the selected tools are reported as skipped.
+    /// Otherwise tools that don't match `endpoint`'s manifest are failed:
+    /// `expected_tools` it doesn't advertise and, with `exact_tools`, tools
+    /// it advertises beyond them, which aren't tested.
+    #[allow(clippy::future_not_send)]
+    #[allow(clippy::cast_possible_truncation)]
+    async fn test_tools_on_endpoint(
+        &self,
+        endpoint_url: &str,
+        options: &TestAllOptions,
+        endpoint: &EndpointConfig,
+        rate_limiter: &RateLimiter,
+        concurrency: Option<&AdaptiveConcurrency>,
+        conformance: &ConformanceChecker,
+        server: Option<&ServerIdentity>,
+    ) -> Result<AllToolsTestResult> {
+        let start_time = Instant::now();
+        let start_time_str = chrono::Utc::now().to_rfc3339();
+        let mut transport = self
+            .transport
+            .clone()
+            .with_request_timeout(Duration::from_secs(options.timeout))
+            .with_rate_limiter(rate_limiter.clone())
+            .with_conformance(conformance.clone());
+        if let Some(concurrency) = concurrency {
+            transport = transport.with_concurrency(concurrency.clone());
+        }
+
+        self.reporter.discovery_started(endpoint_url);
+
+        let tools_capability = server.is_none_or(|server| server.advertises("tools"));
+        let (tools_to_test, unselected, tool_discovery, list_agents) = if tools_capability {
+            let tools_result = self
+                .list_tools_with_transport(&transport, endpoint_url, false)
+                .await?; // Force quiet mode
+            let (mut available_tools, discovery) =
+                self.extract_tools_from_result(&tools_result, endpoint_url, options)?;
+            let manifest = ToolManifestCheck::compare(endpoint, &available_tools);
+            if let Some(mismatch) = manifest.describe() {
+                self.reporter.warning(&format!(
+                    "{endpoint_url} doesn't match its tool manifest: {mismatch}"
+                ));
+            }
+            available_tools.retain(|tool| !manifest.unexpected.contains(&tool.name));
+            let mut unselected = Self::unselected_tools(&available_tools, options);
+            unselected.extend(Self::manifest_failures(&manifest, endpoint_url, options));
+            let (tools_to_test, cached) = Self::split_recently_passed(
+                Self::filter_tools(&available_tools, options),
+                options,
+                &endpoint.name,
+            );
+            unselected.extend(cached);
+            (
+                tools_to_test,
+                unselected,
+                Some(discovery),
+                available_tools
+                    .iter()
+                    .find(|tool| tool.name == LIST_AGENTS)
+                    .cloned(),
+            )
+        } else {
+            self.reporter.warning(&format!(
+                "{endpoint_url} doesn't advertise the tools capability; skipping tool tests"
+            ));
+            (
+                Vec::new(),
+                Self::capability_skipped_tools(options),
+                None,
+                None,
+            )
+        };
+
+        self.reporter
+            .discovery_finished(endpoint_url, tools_to_test.len());
 
         if tools_to_test.is_empty() {
+            let results: BTreeMap<String, ToolTestResult> = unselected
+                .into_iter()
+                .map(|result| (result.tool_name.clone(), result))
+                .collect();
+            let failed_tools = results.values().filter(|r| r.is_failure()).count();
+            // Every selected tool passed recently, so there was nothing left to run
+            let all_cached = results
+                .values()
+                .any(|r| r.skipped == Some(SkipReason::CachedPass));
             return Ok(AllToolsTestResult {
-                success: false,
-                total_tools: 0,
+                schema_version: schema_version(),
+                success: all_cached && failed_tools == 0,
+                total_tools: results.len(),
                 successful_tools: 0,
-                failed_tools: 0,
-                tool_results: HashMap::new(),
+                failed_tools,
+                skipped_tools: results.len() - failed_tools,
+                degraded_tools: 0,
+                oversized_tools: 0,
+                tool_results: results,
                 execution_summary: ExecutionSummary {
                     start_time: start_time_str.clone(),
                     end_time: chrono::Utc::now().to_rfc3339(),
                     total_duration_ms: start_time.elapsed().as_millis() as u64,
                     parallel_execution: options.parallel,
                     timeout_settings: options.timeout,
+                    rate_limit: rate_limiter.summary(),
+                    concurrency: concurrency.map(AdaptiveConcurrency::stats),
                 },
-                error: Some("No tools found to test".to_string()),
+                error: (!all_cached).then(|| {
+                    if tools_capability {
+                        "No tools found to test".to_string()
+                    } else {
+                        "Server does not advertise the tools capability".to_string()
+                    }
+                }),
+                run_id: self.transport.run_id().map(String::from),
+                labels: options.labels.clone(),
+                server: None,
+                categories: BTreeMap::new(),
+                endpoints: BTreeMap::new(),
+                error_categories: BTreeMap::new(),
+                conformance: conformance.report(),
+                cancelled: None,
+                tool_discovery,
             });
         }
 
-        // Phase 2: Execute tests with individual progress bars
-        let test_results = if options.parallel {
-            self.execute_tests_parallel_with_individual_progress(
-                &tools_to_test,
-                options,
-                endpoint_url,
-            )
-            .await?
-        } else {
-            self.execute_tests_sequential_with_progress(
-                &tools_to_test,
-                options,
-                &MultiProgress::new(),
-                endpoint_url,
-            )
-            .await?
+        // Agents run after the other tools, each with its own result
+        let mut tools_to_test = tools_to_test;
+        let agent_tool = tools_to_test
+            .iter()
+            .position(|tool| tool.name == RUN_AGENT)
+            .map(|index| tools_to_test.remove(index));
+        let agents = match agent_tool {
+            Some(_) => Some(
+                self.discover_agents(&transport, endpoint_url, list_agents.as_ref(), options)
+                    .await,
+            ),
+            None => None,
         };
 
+        // Phase 2: Execute tests, reporting progress per tool
+        let mut tool_names: Vec<String> = tools_to_test
+            .iter()
+            .flat_map(|tool| options.test_cases(&tool.name))
+            .map(|(name, _)| name)
+            .collect();
+        match &agents {
+            Some(Ok(agents)) => tool_names.extend(agents.iter().map(AgentInfo::result_name)),
+            Some(Err(result)) => tool_names.push(result.tool_name.clone()),
+            None => {}
+        }
+        self.reporter.tests_started(&tool_names, options.parallel);
+
+        let mut test_results = self
+            .execute_in_dependency_order(&transport, &tools_to_test, options, endpoint_url)
+            .await;
+        match (agent_tool, agents) {
+            (Some(tool), Some(Ok(agents))) => test_results.extend(
+                self.run_agents(&transport, endpoint_url, &tool, &agents, options)
+                    .await,
+            ),
+            (_, Some(Err(result))) => {
+                self.reporter.tool_finished(&result);
+                test_results.push(result);
+            }
+            _ => {}
+        }
+
         // Step 4: Generate final result
         let end_time = Instant::now();
-        let successful_count = test_results.iter().filter(|r| r.success).count();
-        let total_count = test_results.len();
-
-        let mut tool_results_map = HashMap::new();
-        for result in test_results {
+        let mut tool_results_map = BTreeMap::new();
+        for result in unselected.into_iter().chain(test_results) {
             tool_results_map.insert(result.tool_name.clone(), result);
         }
+        let total_count = tool_results_map.len();
+        let successful_count = tool_results_map.values().filter(|r| r.success).count();
+        let skipped_count = tool_results_map.values().filter(|r| r.is_skipped()).count();
+        let failed_count = total_count - successful_count - skipped_count;
+        let degraded_count = tool_results_map.values().filter(|r| r.degraded).count();
+        let oversized_count = tool_results_map.values().filter(|r| r.oversized).count();
 
         let execution_summary = ExecutionSummary {
             start_time: start_time_str,
@@ -560,588 +2526,579 @@ impl GleanMCPInspector {
             total_duration_ms: end_time.duration_since(start_time).as_millis() as u64,
             parallel_execution: options.parallel,
             timeout_settings: options.timeout,
+            rate_limit: rate_limiter.summary(),
+            concurrency: concurrency.map(AdaptiveConcurrency::stats),
         };
 
-        Ok(AllToolsTestResult {
-            success: successful_count == total_count,
+        let cancelled = options.cancel.reason();
+        let result = AllToolsTestResult {
+            schema_version: schema_version(),
+            success: failed_count == 0 && cancelled.is_none(),
             total_tools: total_count,
             successful_tools: successful_count,
-            failed_tools: total_count - successful_count,
+            failed_tools: failed_count,
+            skipped_tools: skipped_count,
+            degraded_tools: degraded_count,
+            oversized_tools: oversized_count,
+            categories: CategoryRollup::by_category(&tool_results_map),
+            endpoints: BTreeMap::new(),
+            error_categories: count_error_categories(tool_results_map.values()),
             tool_results: tool_results_map,
             execution_summary,
             error: None,
-        })
+            run_id: self.transport.run_id().map(String::from),
+            labels: options.labels.clone(),
+            server: None,
+            conformance: conformance.report(),
+            cancelled,
+            tool_discovery,
+        };
+        self.reporter.tests_finished(&result);
+
+        Ok(result)
     }
 
     /// Extract tools from the `list_available_tools` result
-    fn extract_tools_from_result(result: &InspectorResult) -> Vec<ToolInfo> {
-        let mut tools = Vec::new();
-
-        if let Some(inspector_data) = &result.inspector_data {
-            // Try to extract tools from various possible response structures
-            if let Some(result_data) = inspector_data.get("result") {
-                if let Some(tools_array) = result_data.get("tools").and_then(|t| t.as_array()) {
-                    for tool in tools_array {
-                        if let Some(name) = tool.get("name").and_then(|n| n.as_str()) {
-                            tools.push(ToolInfo {
-                                name: name.to_string(),
-                                description: tool
-                                    .get("description")
-                                    .and_then(|d| d.as_str())
-                                    .map(std::string::ToString::to_string),
-                                schema: tool.get("inputSchema").cloned(),
-                            });
-                        }
-                    }
-                }
-            } else if let Some(tools_array) = inspector_data.get("tools").and_then(|t| t.as_array())
-            {
-                for tool in tools_array {
-                    if let Some(name) = tool.get("name").and_then(|n| n.as_str()) {
-                        tools.push(ToolInfo {
-                            name: name.to_string(),
-                            description: tool
-                                .get("description")
-                                .and_then(|d| d.as_str())
-                                .map(std::string::ToString::to_string),
-                            schema: tool.get("inputSchema").cloned(),
-                        });
-                    }
-                }
-            }
+    ///
+    /// When none can be extracted the run fails, unless
+    /// `options.assume_default_tools` substitutes the expected tools (core +
+    /// enterprise, then the tool profile's).
+    fn extract_tools_from_result(
+        &self,
+        result: &InspectorResult,
+        endpoint_url: &str,
+        options: &TestAllOptions,
+    ) -> Result<(Vec<ToolInfo>, ToolDiscovery)> {
+        let tools = result
+            .inspector_data
+            .as_ref()
+            .map(ToolInfo::from_tools_list)
+            .unwrap_or_default();
+        if !tools.is_empty() {
+            return Ok((tools, ToolDiscovery::Discovered));
         }
-
-        // If no tools found in structured data, fall back to expected tools (core + enterprise)
-        if tools.is_empty() {
-            let term = Term::stdout();
-            let _ = term.write_line(&format!(
-                "⚠️  {}",
-                style("No tools found in response, using default tool set").yellow()
-            ));
-            tools = vec![
-                // Core tools
-                ToolInfo {
-                    name: "search".to_string(),
-                    description: Some("Search Glean's content index".to_string()),
-                    schema: None,
-                },
-                ToolInfo {
-                    name: "chat".to_string(),
-                    description: Some("Interact with Glean's AI assistant".to_string()),
-                    schema: None,
-                },
-                ToolInfo {
-                    name: "read_document".to_string(),
-                    description: Some("Read documents by ID/URL".to_string()),
-                    schema: None,
-                },
-                // Enterprise tools
-                ToolInfo {
-                    name: "code_search".to_string(),
-                    description: Some("Search code repositories".to_string()),
-                    schema: None,
-                },
-                ToolInfo {
-                    name: "employee_search".to_string(),
-                    description: Some("Search people directory".to_string()),
-                    schema: None,
-                },
-                ToolInfo {
-                    name: "gmail_search".to_string(),
-                    description: Some("Search Gmail messages".to_string()),
-                    schema: None,
-                },
-                ToolInfo {
-                    name: "outlook_search".to_string(),
-                    description: Some("Search Outlook messages".to_string()),
-                    schema: None,
-                },
-                ToolInfo {
-                    name: "meeting_lookup".to_string(),
-                    description: Some("Find meeting information".to_string()),
-                    schema: None,
-                },
-                ToolInfo {
-                    name: "web_browser".to_string(),
-                    description: Some("Web browsing capability".to_string()),
-                    schema: None,
-                },
-                ToolInfo {
-                    name: "gemini_web_search".to_string(),
-                    description: Some("Web search capability".to_string()),
-                    schema: None,
-                },
-            ];
+        if !options.assume_default_tools {
+            return Err(GleanMcpError::Validation(format!(
+                "Tool discovery failed: tools/list on {endpoint_url} returned no tools (pass --assume-default-tools to test the default tool set instead)"
+            )));
         }
 
-        tools
+        self.reporter.warning(&format!(
+            "No tools found in the tools/list response from {endpoint_url}; assuming the default tool set"
+        ));
+        let profile = tool_profile();
+        let mut tools = if profile.is_generic() {
+            Vec::new()
+        } else {
+            Self::glean_default_tools()
+        };
+        tools.extend(profile.tools.iter().map(|(name, tool)| ToolInfo {
+            name: name.clone(),
+            description: tool.description.clone(),
+            schema: None,
+        }));
+        Ok((tools, ToolDiscovery::AssumedDefaults))
+    }
+
+    /// Glean's tools as `tools/list` describes them
+    fn glean_default_tools() -> Vec<ToolInfo> {
+        vec![
+            // Core tools
+            ToolInfo {
+                name: "search".to_string(),
+                description: Some("Search Glean's content index".to_string()),
+                schema: None,
+            },
+            ToolInfo {
+                name: "chat".to_string(),
+                description: Some("Interact with Glean's AI assistant".to_string()),
+                schema: None,
+            },
+            ToolInfo {
+                name: "read_document".to_string(),
+                description: Some("Read documents by ID/URL".to_string()),
+                schema: None,
+            },
+            // Enterprise tools
+            ToolInfo {
+                name: "code_search".to_string(),
+                description: Some("Search code repositories".to_string()),
+                schema: None,
+            },
+            ToolInfo {
+                name: "employee_search".to_string(),
+                description: Some("Search people directory".to_string()),
+                schema: None,
+            },
+            ToolInfo {
+                name: "gmail_search".to_string(),
+                description: Some("Search Gmail messages".to_string()),
+                schema: None,
+            },
+            ToolInfo {
+                name: "outlook_search".to_string(),
+                description: Some("Search Outlook messages".to_string()),
+                schema: None,
+            },
+            ToolInfo {
+                name: "meeting_lookup".to_string(),
+                description: Some("Find meeting information".to_string()),
+                schema: None,
+            },
+            ToolInfo {
+                name: "web_browser".to_string(),
+                description: Some("Web browsing capability".to_string()),
+                schema: None,
+            },
+            ToolInfo {
+                name: "gemini_web_search".to_string(),
+                description: Some("Web search capability".to_string()),
+                schema: None,
+            },
+        ]
     }
 
     /// Filter tools based on the test options
-    fn filter_tools(available_tools: &[ToolInfo], options: &TestAllOptions) -> Vec<ToolInfo> {
-        match options.tools_filter.as_str() {
-            "all" => available_tools.to_vec(),
-            "core" => available_tools
-                .iter()
-                .filter(|tool| TestQueryGenerator::get_tool_category(&tool.name) == "core")
-                .cloned()
-                .collect(),
-            "enterprise" => available_tools
-                .iter()
-                .filter(|tool| TestQueryGenerator::get_tool_category(&tool.name) == "enterprise")
-                .cloned()
-                .collect(),
-            tools_list => {
-                let requested_tools: Vec<&str> = tools_list.split(',').map(str::trim).collect();
-                available_tools
-                    .iter()
-                    .filter(|tool| requested_tools.contains(&tool.name.as_str()))
-                    .cloned()
-                    .collect()
-            }
+    pub(crate) fn filter_tools(
+        available_tools: &[ToolInfo],
+        options: &TestAllOptions,
+    ) -> Vec<ToolInfo> {
+        let tools = available_tools
+            .iter()
+            .filter(|tool| options.selects(&tool.name))
+            .cloned()
+            .collect();
+        match options.shard {
+            Some(shard) => shard.select(tools, |tool: &ToolInfo| {
+                options.dependency_group(&tool.name)
+            }),
+            None => tools,
         }
     }
 
-    /// Execute tests in parallel with individual progress bars per tool
+    /// Execute tests in waves, each tool after the tools it depends on
+    ///
+    /// Argument templates are filled from the responses of earlier waves. A
+    /// tool whose dependency failed or was skipped, or whose templates can't
+    /// be filled, is skipped too. Tools in a dependency cycle run together in
+    /// the last wave. Each tool runs once per corpus query it's called with.
     #[allow(clippy::future_not_send)]
-    #[allow(clippy::cast_precision_loss)]
-    #[allow(clippy::cast_possible_truncation)]
-    async fn execute_tests_parallel_with_individual_progress(
+    async fn execute_in_dependency_order(
         &self,
+        transport: &McpTransport,
         tools: &[ToolInfo],
         options: &TestAllOptions,
         endpoint_url: &str,
-    ) -> Result<Vec<ToolTestResult>> {
-        use smol::lock::Semaphore;
-
-        // Create MultiProgress and ensure it owns terminal completely
-        let multi_progress = MultiProgress::new();
-        let semaphore = Arc::new(Semaphore::new(options.max_concurrent));
-
-        // Calculate max tool name length for alignment
-        let max_name_len = tools.iter().map(|t| t.name.len()).max().unwrap_or(10);
-        let prefix_width = max_name_len + 4; // Extra space for emoji
-
-        // Pre-create all progress bars with consistent alignment
-        let progress_bars: Vec<_> = tools
-            .iter()
-            .map(|tool| {
-                let pb = multi_progress.add(ProgressBar::new(100));
-                pb.set_style(
-                ProgressStyle::with_template(&format!(
-                    "{{prefix:<{prefix_width}}} [{{elapsed_precise}}] {{bar:25.cyan/blue}} {{pos:>3}}% {{msg}}"
-                ))
-                .unwrap_or_else(|_| ProgressStyle::default_bar()),
-            );
-                pb.set_prefix(format!("🔧 {}", &tool.name));
-                pb.set_message("Queued...");
-                pb.set_position(0);
-                (pb, tool.clone())
-            })
-            .collect();
-
-        // Create tasks for each tool
-        let mut tasks = Vec::new();
-        for (tool_pb, tool) in progress_bars {
-            let semaphore = semaphore.clone();
-            let timeout = Duration::from_secs(options.timeout);
-            let query = TestQueryGenerator::generate_test_query(&tool.name);
-            let server_url = endpoint_url.to_string();
-            let auth_token = self.auth_token.clone();
-            let retry_attempts = options.retry_attempts;
-            let retry_backoff_seconds = options.retry_backoff_seconds;
-
-            let task = async move {
-                let _permit = semaphore.acquire().await;
-
-                tool_pb.set_message("Starting...");
-                tool_pb.set_position(10);
-
-                let start_time = Instant::now();
-                tool_pb.set_message("Testing...");
-                tool_pb.set_position(50);
-
-                let result = Self::test_tool_with_retry(
-                    server_url,
-                    auth_token,
-                    &tool.name,
-                    &query,
-                    timeout,
-                    retry_attempts,
-                    retry_backoff_seconds,
-                )
-                .await;
-
-                let response_time_ms = start_time.elapsed().as_millis() as u64;
-
-                match result {
-                    Ok(response_data) => {
-                        tool_pb.set_position(100);
-                        tool_pb.finish_with_message(format!(
-                            "✅ Complete ({:.2}s)",
-                            response_time_ms as f64 / 1000.0
-                        ));
-                        ToolTestResult::new_success(
-                            tool.name,
-                            response_time_ms,
-                            query,
-                            response_data,
-                        )
+    ) -> Vec<ToolTestResult> {
+        let mut results: Vec<ToolTestResult> = Vec::new();
+        let mut pending = tools.to_vec();
+        while !pending.is_empty() {
+            let (mut wave, mut waiting): (Vec<_>, Vec<_>) =
+                pending.iter().cloned().partition(|tool| {
+                    !options
+                        .dependencies_of(&tool.name)
+                        .into_iter()
+                        .any(|dependency| pending.iter().any(|other| other.name == dependency))
+                });
+            if wave.is_empty() {
+                wave = std::mem::take(&mut waiting);
+            }
+            pending = waiting;
+
+            let mut wave_options = options.clone();
+            let mut runnable = Vec::new();
+            let mut skipped = Vec::new();
+            for tool in wave {
+                match Self::resolve_dependencies(&tool, options, &results) {
+                    Ok(Some(arguments)) => {
+                        wave_options
+                            .tool_arguments
+                            .insert(tool.name.clone(), arguments);
+                        runnable.extend(TestCase::expand(&tool, options));
                     }
-                    Err(e) => {
-                        if e.to_string().contains("timed out") {
-                            tool_pb.finish_with_message("⏰ Timeout".to_string());
-                            ToolTestResult::new_timeout(tool.name, timeout.as_secs(), query)
-                        } else {
-                            tool_pb.finish_with_message("❌ Failed".to_string());
-                            ToolTestResult::new_error(
-                                tool.name,
-                                response_time_ms,
-                                query,
-                                e.to_string(),
-                            )
+                    Ok(None) => runnable.extend(TestCase::expand(&tool, options)),
+                    Err(detail) => {
+                        for (name, _) in options.test_cases(&tool.name) {
+                            let result = ToolTestResult::new_skipped(
+                                name,
+                                SkipReason::DependencyFailed,
+                                Some(detail.clone()),
+                            );
+                            self.reporter.tool_finished(&result);
+                            skipped.push(result);
                         }
                     }
                 }
-            };
+            }
+            results.extend(skipped);
 
-            tasks.push(task);
+            results.extend(if options.parallel {
+                self.execute_tests_parallel(transport, &runnable, &wave_options, endpoint_url)
+                    .await
+            } else {
+                self.execute_tests_sequential(transport, &runnable, &wave_options, endpoint_url)
+                    .await
+            });
+            if options.cancel.is_cancelled() {
+                results.extend(
+                    std::mem::take(&mut pending)
+                        .iter()
+                        .flat_map(|tool| TestCase::expand(tool, options))
+                        .map(|case| interrupted(&case)),
+                );
+            }
         }
+        results
+    }
 
-        // Execute all tests and wait for completion
-        let results = futures::future::join_all(tasks).await;
-
-        // Give a moment for all progress bars to finish cleanly
-        smol::Timer::after(Duration::from_millis(100)).await;
-
-        Ok(results)
+    /// Check `tool`'s dependencies in `results` and fill its argument templates from their responses
+    ///
+    /// Returns the filled arguments, `None` when the tool has no templates, or
+    /// why the tool can't run.
+    fn resolve_dependencies(
+        tool: &ToolInfo,
+        options: &TestAllOptions,
+        results: &[ToolTestResult],
+    ) -> std::result::Result<Option<Value>, String> {
+        if let Some(dependency) =
+            options
+                .dependencies_of(&tool.name)
+                .into_iter()
+                .find(|dependency| {
+                    results.iter().any(|result| {
+                        base_tool_name(&result.tool_name) == *dependency && !result.success
+                    })
+                })
+        {
+            return Err(format!("{dependency} did not pass"));
+        }
+        let Some(arguments) = options
+            .tool_arguments
+            .get(&tool.name)
+            .filter(|arguments| !template_references(arguments).is_empty())
+        else {
+            return Ok(None);
+        };
+        // With several corpus queries, templates read the first query's response
+        let mut responses: BTreeMap<&str, &Value> = BTreeMap::new();
+        for result in results.iter().filter(|result| result.success) {
+            if let Some(response) = &result.response_data {
+                responses
+                    .entry(base_tool_name(&result.tool_name))
+                    .or_insert(response);
+            }
+        }
+        render_arguments(arguments, &responses).map(Some)
     }
 
-    /// Execute tests in parallel with clean, single progress bar (legacy)
-    #[allow(dead_code)]
+    /// Execute tests on a pool of `max_concurrent` workers
+    ///
+    /// Workers take tools from a shared queue until it is empty or the run is
+    /// cancelled. Under adaptive concurrency each tool also waits for a slot
+    /// below the transport's current limit. Tools cancelled in flight or
+    /// never started are skipped as interrupted.
     #[allow(clippy::future_not_send)]
-    #[allow(clippy::cast_possible_truncation)]
-    async fn execute_tests_parallel_with_progress(
+    async fn execute_tests_parallel(
         &self,
-        tools: &[ToolInfo],
+        transport: &McpTransport,
+        tools: &[TestCase],
         options: &TestAllOptions,
-        multi_progress: &MultiProgress,
-    ) -> Result<Vec<ToolTestResult>> {
-        use smol::lock::Semaphore;
-
-        let semaphore = Arc::new(Semaphore::new(options.max_concurrent));
-        let mut tasks = Vec::new();
-
-        // Add a clean progress bar to the existing MultiProgress
-        let pb = multi_progress.add(ProgressBar::new(tools.len() as u64));
-        pb.set_style(
-            ProgressStyle::with_template(
-                "🚀 [{elapsed_precise}] {bar:40.cyan/blue} {pos:>2}/{len:2} {msg}",
-            )
-            .unwrap_or_else(|_| ProgressStyle::default_bar()),
-        );
-        pb.enable_steady_tick(Duration::from_millis(100));
-        pb.set_message(format!(
-            "Testing {} tools in parallel (max {} concurrent)",
-            tools.len(),
-            options.max_concurrent
-        ));
-
-        for tool in tools {
-            let semaphore = semaphore.clone();
-            let tool = tool.clone();
-            let timeout = Duration::from_secs(options.timeout);
-            let query = TestQueryGenerator::generate_test_query(&tool.name);
-            let pb_clone = pb.clone();
-
-            let server_url = self.server_url.clone();
-            let auth_token = self.auth_token.clone();
-            let retry_attempts = options.retry_attempts;
-            let retry_backoff_seconds = options.retry_backoff_seconds;
-
-            let task = async move {
-                let _permit = semaphore.acquire().await;
-
-                let start_time = Instant::now();
-
-                let result = Self::test_tool_with_retry(
-                    server_url,
-                    auth_token,
-                    &tool.name,
-                    &query,
-                    timeout,
-                    retry_attempts,
-                    retry_backoff_seconds,
-                )
-                .await;
-
-                let response_time_ms = start_time.elapsed().as_millis() as u64;
-                pb_clone.inc(1);
-
-                match result {
-                    Ok(response_data) => ToolTestResult::new_success(
-                        tool.name,
-                        response_time_ms,
-                        query,
-                        response_data,
-                    ),
-                    Err(e) => {
-                        if e.to_string().contains("timed out") {
-                            ToolTestResult::new_timeout(tool.name, timeout.as_secs(), query)
-                        } else {
-                            ToolTestResult::new_error(
-                                tool.name,
-                                response_time_ms,
-                                query,
-                                e.to_string(),
-                            )
+        endpoint_url: &str,
+    ) -> Vec<ToolTestResult> {
+        // Keyed semaphores for tools with their own concurrency cap
+        let tool_semaphores: HashMap<&str, Arc<Semaphore>> = options
+            .tool_max_concurrent
+            .iter()
+            .map(|(tool, limit)| (tool.as_str(), Arc::new(Semaphore::new((*limit).max(1)))))
+            .collect();
+        let queue = Mutex::new(tools.iter().collect::<VecDeque<_>>());
+
+        let concurrency = transport.concurrency();
+
+        let workers = (0..options.max_concurrent.clamp(1, tools.len().max(1))).map(|_| async {
+            let mut results = Vec::new();
+            loop {
+                let _slot = match concurrency {
+                    Some(concurrency) => {
+                        match options
+                            .cancel
+                            .run_until_cancelled(concurrency.acquire())
+                            .await
+                        {
+                            Some(slot) => Some(slot),
+                            None => break,
                         }
                     }
-                }
-            };
-
-            tasks.push(task);
-        }
-
-        // Execute all tests concurrently
-        let results = futures::future::join_all(tasks).await;
-        pb.finish_with_message(format!("✅ Completed {} tools", tools.len()));
+                    None => None,
+                };
+                let Some((case, _permit)) =
+                    next_queued_tool(&queue, &tool_semaphores, &options.cancel).await
+                else {
+                    break;
+                };
+                results.push(
+                    self.test_one_tool(transport, case, options, endpoint_url)
+                        .await
+                        .unwrap_or_else(|| interrupted(case)),
+                );
+            }
+            results
+        });
 
-        Ok(results)
+        let mut results: Vec<ToolTestResult> = futures::future::join_all(workers)
+            .await
+            .into_iter()
+            .flatten()
+            .collect();
+        // Tools still queued when the run was cancelled
+        results.extend(
+            queue
+                .into_inner()
+                .unwrap_or_else(PoisonError::into_inner)
+                .into_iter()
+                .map(interrupted),
+        );
+        results
     }
 
-    /// Execute tests in parallel with concurrency limits (Legacy method)
-    #[allow(dead_code)]
+    /// Execute tests sequentially, stopping when the run is cancelled
     #[allow(clippy::future_not_send)]
-    #[allow(clippy::cast_possible_truncation)]
-    async fn execute_tests_parallel(
+    async fn execute_tests_sequential(
         &self,
-        tools: &[ToolInfo],
+        transport: &McpTransport,
+        tools: &[TestCase],
         options: &TestAllOptions,
-    ) -> Result<Vec<ToolTestResult>> {
-        use smol::lock::Semaphore;
-
-        let semaphore = Arc::new(Semaphore::new(options.max_concurrent));
-        let mut tasks = Vec::new();
-
-        for tool in tools {
-            let semaphore = semaphore.clone();
-            let tool = tool.clone();
-            let timeout = Duration::from_secs(options.timeout);
-            let query = TestQueryGenerator::generate_test_query(&tool.name);
-
-            let server_url = self.server_url.clone();
-            let auth_token = self.auth_token.clone();
-            let retry_attempts = options.retry_attempts;
-            let retry_backoff_seconds = options.retry_backoff_seconds;
-
-            let task = async move {
-                let _permit = semaphore.acquire().await;
-
-                // Verbose output removed for clean MultiProgress display
-
-                let start_time = Instant::now();
-                let result = Self::test_tool_with_retry(
-                    server_url,
-                    auth_token,
-                    &tool.name,
-                    &query,
-                    timeout,
-                    retry_attempts,
-                    retry_backoff_seconds,
-                )
-                .await;
+        endpoint_url: &str,
+    ) -> Vec<ToolTestResult> {
+        let mut results = Vec::new();
 
-                let response_time_ms = start_time.elapsed().as_millis() as u64;
-
-                match result {
-                    Ok(response_data) => {
-                        // Success - quiet mode for MultiProgress
-                        ToolTestResult::new_success(
-                            tool.name,
-                            response_time_ms,
-                            query,
-                            response_data,
-                        )
-                    }
-                    Err(e) => {
-                        if e.to_string().contains("timed out") {
-                            ToolTestResult::new_timeout(tool.name, timeout.as_secs(), query)
-                        } else {
-                            ToolTestResult::new_error(
-                                tool.name,
-                                response_time_ms,
-                                query,
-                                e.to_string(),
-                            )
-                        }
-                    }
-                }
+        for (index, tool) in tools.iter().enumerate() {
+            let Some(result) = self
+                .test_one_tool(transport, tool, options, endpoint_url)
+                .await
+            else {
+                results.extend(tools[index..].iter().map(interrupted));
+                return results;
             };
-
-            tasks.push(task);
+            results.push(result);
         }
 
-        // Execute all tests concurrently
-        let results = futures::future::join_all(tasks).await;
-        Ok(results)
+        results
     }
 
-    /// Execute tests sequentially with progress bar (Phase 2)
+    /// Test one tool and run the response checks, or return `None` if the run is cancelled first
+    ///
+    /// A failure under `fail_fast` cancels the rest of the run. Checks look
+    /// up their settings by tool name; the result is named after the case.
     #[allow(clippy::future_not_send)]
-    #[allow(clippy::cast_precision_loss)]
     #[allow(clippy::cast_possible_truncation)]
-    async fn execute_tests_sequential_with_progress(
+    async fn test_one_tool(
         &self,
-        tools: &[ToolInfo],
+        transport: &McpTransport,
+        case: &TestCase,
         options: &TestAllOptions,
-        multi_progress: &MultiProgress,
         endpoint_url: &str,
-    ) -> Result<Vec<ToolTestResult>> {
-        let mut results = Vec::new();
+    ) -> Option<ToolTestResult> {
+        if options.cancel.is_cancelled() {
+            return None;
+        }
+        let tool = &case.tool;
         let timeout = Duration::from_secs(options.timeout);
+        let (arguments, query) = options.arguments_for_query(tool, case.query.as_deref());
 
-        // Add progress bar to the existing MultiProgress
-        let pb = multi_progress.add(ProgressBar::new(tools.len() as u64));
-        pb.set_style(
-            ProgressStyle::with_template(
-                "⚡ [{elapsed_precise}] {bar:40.cyan/blue} {pos:>2}/{len:2} {msg}",
-            )
-            .unwrap_or_else(|_| ProgressStyle::default_bar()),
-        );
-        pb.enable_steady_tick(Duration::from_millis(100));
-        pb.set_message("Testing tools sequentially...");
-
-        for tool in tools {
-            let query = TestQueryGenerator::generate_test_query(&tool.name);
-
-            pb.set_message(format!("Testing {}", &tool.name));
+        self.reporter.tool_started(&case.name);
 
-            let start_time = Instant::now();
-            let result = Self::test_tool_with_retry(
-                endpoint_url.to_string(),
-                self.auth_token.clone(),
+        let start_time = Instant::now();
+        let mut trace = RequestTrace::default();
+        let result = options
+            .cancel
+            .run_until_cancelled(Self::test_tool_with_retry(
+                transport,
+                endpoint_url,
                 &tool.name,
-                &query,
+                &arguments,
                 timeout,
-                options.retry_attempts,
-                options.retry_backoff_seconds,
-            )
-            .await;
-
-            let response_time_ms = start_time.elapsed().as_millis() as u64;
+                &mut trace,
+                options.retry_policy(),
+                self.reporter.as_ref(),
+            ))
+            .await?;
 
-            let test_result = match result {
-                Ok(response_data) => ToolTestResult::new_success(
+        let response_time_ms = start_time.elapsed().as_millis() as u64;
+
+        let test_result = match result {
+            Ok(response_data) => ToolTestResult::new_success(
+                tool.name.clone(),
+                response_time_ms,
+                query,
+                response_data,
+            ),
+            Err(e) => if e.to_string().contains("timed out") {
+                ToolTestResult::new_error(
                     tool.name.clone(),
                     response_time_ms,
                     query,
-                    response_data,
-                ),
-                Err(e) => {
-                    if e.to_string().contains("timed out") {
-                        ToolTestResult::new_error(
-                            tool.name.clone(),
-                            response_time_ms,
-                            query,
-                            format!("Timeout after {}s", timeout.as_secs()),
-                        )
-                    } else {
-                        ToolTestResult::new_error(
-                            tool.name.clone(),
-                            response_time_ms,
-                            query,
-                            e.to_string(),
-                        )
-                    }
-                }
-            };
+                    format!("Timeout after {}s", timeout.as_secs()),
+                )
+            } else {
+                ToolTestResult::new_error(tool.name.clone(), response_time_ms, query, e.to_string())
+            }
+            .with_error_class(ErrorClass::classify(&e))
+            .with_error_detail(trace.error_detail.clone()),
+        };
 
-            results.push(test_result);
-            pb.inc(1);
+        let mut test_result = test_result
+            .with_language_check(&options.language_check)
+            .with_duplicate_check(&options.duplicate_check)
+            .with_result_check(&options.result_check)
+            .with_expectation_check(options.expectations.get(&tool.name))
+            .with_snapshot_check(options.snapshot.as_ref(), endpoint_url)
+            .with_response_size(&options.response_size)
+            .with_pii_scan(&options.pii_scan)
+            .with_latency_budget(options.latency_budgets_ms.get(&tool.name).copied())
+            .with_timing(trace.timing)
+            .with_correlation_id(trace.correlation_id)
+            .with_request_ids(trace.request_ids);
+        test_result.tool_name.clone_from(&case.name);
+
+        self.reporter.tool_finished(&test_result);
+        if options.fail_fast && !test_result.success {
+            options.cancel.cancel(CancelReason::FailFast);
         }
+        Some(test_result)
+    }
 
-        pb.finish_with_message(format!("✅ Completed {} tools", tools.len()));
-        Ok(results)
+    /// Tools `tool_results` leaves out, as skipped results
+    ///
+    /// Advertised tools the options don't select are filtered out; tools
+    /// named in `tools_filter` the server doesn't advertise are not advertised.
+    fn unselected_tools(
+        available_tools: &[ToolInfo],
+        options: &TestAllOptions,
+    ) -> Vec<ToolTestResult> {
+        let filtered_out = available_tools
+            .iter()
+            .filter(|tool| !options.selects(&tool.name))
+            .map(|tool| {
+                ToolTestResult::new_skipped(tool.name.clone(), SkipReason::FilteredOut, None)
+            });
+        let not_advertised = options
+            .named_tools()
+            .filter(|name| options.selects(name))
+            .filter(|name| !available_tools.iter().any(|tool| tool.name == *name))
+            .map(|name| {
+                ToolTestResult::new_skipped(name.to_string(), SkipReason::NotAdvertised, None)
+            });
+        let unselected = filtered_out.chain(not_advertised).collect();
+        // Spread across the shards too, so merged shard results list each tool once
+        match options.shard {
+            Some(shard) => shard.select(unselected, |result: &ToolTestResult| {
+                result.tool_name.clone()
+            }),
+            None => unselected,
+        }
     }
 
-    /// Execute tests sequentially (Legacy method)
-    #[allow(dead_code)]
-    #[allow(clippy::future_not_send)]
-    #[allow(clippy::cast_precision_loss)]
-    #[allow(clippy::cast_possible_truncation)]
-    async fn execute_tests_sequential(
-        &self,
-        tools: &[ToolInfo],
+    /// Split off the tools every test case of which passed recently on `endpoint`, skipped as cached passes
+    ///
+    /// Tools a remaining tool depends on still run, so its arguments can be filled.
+    fn split_recently_passed(
+        tools: Vec<ToolInfo>,
         options: &TestAllOptions,
-    ) -> Result<Vec<ToolTestResult>> {
-        let mut results = Vec::new();
-        let timeout = Duration::from_secs(options.timeout);
+        endpoint: &str,
+    ) -> (Vec<ToolInfo>, Vec<ToolTestResult>) {
+        if options.recently_passed.is_empty() {
+            return (tools, Vec::new());
+        }
+        let passed_in = |name: &str| options.recently_passed.get(&format!("{name} ({endpoint})"));
+        let (mut cached, mut to_run): (Vec<ToolInfo>, Vec<ToolInfo>) =
+            tools.into_iter().partition(|tool| {
+                let cases = options.test_cases(&tool.name);
+                !cases.is_empty() && cases.iter().all(|(name, _)| passed_in(name).is_some())
+            });
+        while let Some(index) = cached.iter().position(|tool| {
+            to_run.iter().any(|other| {
+                options
+                    .dependencies_of(&other.name)
+                    .contains(&tool.name.as_str())
+            })
+        }) {
+            to_run.push(cached.remove(index));
+        }
+        let skipped = cached
+            .iter()
+            .flat_map(|tool| options.test_cases(&tool.name))
+            .map(|(name, _)| {
+                let detail = passed_in(&name).map(|run| format!("passed in run {run}"));
+                ToolTestResult::new_skipped(name, SkipReason::CachedPass, detail)
+            })
+            .collect();
+        (to_run, skipped)
+    }
 
-        for tool in tools {
-            let query = TestQueryGenerator::generate_test_query(&tool.name);
-            if options.verbose || options.debug {
-                println!("🔧 Testing tool: {} with query: \"{}\"", tool.name, query);
+    /// Failures for the missing and unexpected tools in `manifest`
+    fn manifest_failures(
+        manifest: &ToolManifestCheck,
+        endpoint_url: &str,
+        options: &TestAllOptions,
+    ) -> Vec<ToolTestResult> {
+        let failure = |name: &String, message: String| {
+            ToolTestResult::new_error(name.clone(), 0, String::new(), message)
+                .with_error_class(ErrorClass::Validation)
+        };
+        let failures = manifest
+            .missing
+            .iter()
+            .map(|name| {
+                failure(
+                    name,
+                    format!("Expected tool is not advertised by {endpoint_url}"),
+                )
+            })
+            .chain(manifest.unexpected.iter().map(|name| {
+                failure(
+                    name,
+                    format!("Unexpected tool: {endpoint_url} advertises it, but it isn't in the endpoint's expected_tools"),
+                )
+            }))
+            .collect();
+        match options.shard {
+            Some(shard) => {
+                shard.select(failures, |result: &ToolTestResult| result.tool_name.clone())
             }
-
-            let start_time = Instant::now();
-            let result = Self::test_tool_with_retry(
-                self.server_url.clone(),
-                self.auth_token.clone(),
-                &tool.name,
-                &query,
-                timeout,
-                options.retry_attempts,
-                options.retry_backoff_seconds,
-            )
-            .await;
-
-            let response_time_ms = start_time.elapsed().as_millis() as u64;
-
-            let test_result = match result {
-                Ok(response_data) => {
-                    println!(
-                        "  ✅ {} completed ({:.2}s)",
-                        tool.name,
-                        response_time_ms as f64 / 1000.0
-                    );
-                    ToolTestResult::new_success(
-                        tool.name.clone(),
-                        response_time_ms,
-                        query,
-                        response_data,
-                    )
-                }
-                Err(e) => {
-                    if e.to_string().contains("timed out") {
-                        println!("  ⏰ {} timed out", tool.name);
-                        ToolTestResult::new_error(
-                            tool.name.clone(),
-                            response_time_ms,
-                            query,
-                            format!("Timeout after {}s", timeout.as_secs()),
-                        )
-                    } else {
-                        let error_msg = Self::truncate_error_message(&e.to_string());
-                        println!("  ❌ {} failed: {}", tool.name, error_msg);
-                        ToolTestResult::new_error(
-                            tool.name.clone(),
-                            response_time_ms,
-                            query,
-                            e.to_string(),
-                        )
-                    }
-                }
-            };
-
-            results.push(test_result);
+            None => failures,
         }
+    }
 
-        Ok(results)
+    /// The selected tools, known or named in `tools_filter`, skipped because the server has no `tools` capability
+    fn capability_skipped_tools(options: &TestAllOptions) -> Vec<ToolTestResult> {
+        let known = known_tools();
+        let names: BTreeSet<&str> = known
+            .iter()
+            .map(String::as_str)
+            .chain(options.named_tools())
+            .filter(|name| options.selects(name))
+            .collect();
+        let skipped = names
+            .into_iter()
+            .map(|name| {
+                ToolTestResult::new_skipped(
+                    name.to_string(),
+                    SkipReason::CapabilityNotAdvertised,
+                    None,
+                )
+            })
+            .collect();
+        match options.shard {
+            Some(shard) => {
+                shard.select(skipped, |result: &ToolTestResult| result.tool_name.clone())
+            }
+            None => skipped,
+        }
     }
 
     /// Truncate long error messages for cleaner output
-    fn truncate_error_message(error: &str) -> String {
+    pub(crate) fn truncate_error_message(error: &str) -> String {
         const MAX_ERROR_LENGTH: usize = 150;
 
         // Remove HTML content if present
@@ -1162,90 +3119,60 @@ impl GleanMCPInspector {
         cleaned.trim().to_string()
     }
 
-    /// Test a tool with retry logic and exponential backoff
+    /// Test a tool, retrying retryable failures as `policy` allows
     #[allow(clippy::future_not_send)]
     #[allow(clippy::cast_possible_truncation)]
-    async fn test_tool_with_retry(
-        server_url: String,
-        auth_token: Option<String>,
+    pub(crate) async fn test_tool_with_retry(
+        transport: &McpTransport,
+        endpoint_url: &str,
         tool_name: &str,
-        query: &str,
+        arguments: &Value,
         timeout: Duration,
-        retry_attempts: u32,
-        initial_backoff_seconds: u64,
+        trace: &mut RequestTrace,
+        policy: RetryPolicy,
+        reporter: &dyn Reporter,
     ) -> std::result::Result<Value, GleanMcpError> {
-        let mut last_error = None;
-
-        for attempt in 1..=retry_attempts {
-            if attempt > 1 {
-                // Calculate exponential backoff base time
-                let base_backoff_ms = initial_backoff_seconds * 1000 * 2_u64.pow(attempt - 2);
-
-                // Add full jitter: random between 0 and base_backoff_ms
-                let mut rng = rand::thread_rng();
-                let jittered_backoff_ms = rng.gen_range(0..=base_backoff_ms);
-                let backoff_duration = Duration::from_millis(jittered_backoff_ms);
-
-                // Retry message suppressed for clean MultiProgress display
-                smol::Timer::after(backoff_duration).await;
-            }
+        let mut attempt = 1;
 
+        loop {
+            // A timed-out attempt leaves nothing behind, rather than the previous attempt's trace
+            *trace = RequestTrace::default();
             match async_timeout(
                 timeout,
-                Self::test_tool_direct(server_url.clone(), auth_token.clone(), tool_name, query),
+                Self::test_tool_direct(transport, endpoint_url, tool_name, arguments, trace),
             )
             .await
             {
-                Ok(result) => {
-                    // Recovery message suppressed for clean MultiProgress display
-                    return Ok(result);
-                }
+                Ok(result) => return Ok(result),
                 Err(e) => {
-                    last_error = Some(e);
-                    if attempt < retry_attempts {
-                        if last_error
-                            .as_ref()
-                            .unwrap()
-                            .to_string()
-                            .contains("timed out")
-                        {
-                            // Timeout message - quiet mode for MultiProgress
-                        } else {
-                            let _error_msg = Self::truncate_error_message(
-                                &last_error.as_ref().unwrap().to_string(),
-                            );
-                            // Error message - quiet mode for MultiProgress
-                        }
+                    // Auth and client errors fail fast; everything else backs off and retries
+                    if !policy.should_retry(attempt, &e) {
+                        return Err(e);
                     }
+                    reporter.tool_retrying(
+                        tool_name,
+                        attempt + 1,
+                        &Self::truncate_error_message(&e.to_string()),
+                    );
+                    smol::Timer::after(policy.delay(attempt, &e)).await;
+                    attempt += 1;
                 }
             }
         }
-
-        // All attempts failed
-        Err(last_error
-            .unwrap_or_else(|| GleanMcpError::Process("All retry attempts failed".to_string())))
     }
 
     /// Direct tool testing method (static to avoid borrowing issues in async contexts)
-    async fn test_tool_direct(
-        server_url: String,
-        auth_token: Option<String>,
+    ///
+    /// Stores the request's connection timings and correlation id in `trace`
+    /// when the server answered.
+    pub(crate) async fn test_tool_direct(
+        transport: &McpTransport,
+        endpoint_url: &str,
         tool_name: &str,
-        query: &str,
+        arguments: &Value,
+        trace: &mut RequestTrace,
     ) -> Result<Value> {
         // Create MCP JSON-RPC request for tool call
-        let arguments = match tool_name {
-            "chat" => serde_json::json!({
-                "message": query
-            }),
-            "read_document" => serde_json::json!({
-                "url": query
-            }),
-            _ => serde_json::json!({
-                "query": query
-            }),
-        };
-
         let tool_request = serde_json::json!({
             "jsonrpc": "2.0",
             "id": 1,
@@ -1256,90 +3183,30 @@ impl GleanMCPInspector {
             }
         });
 
-        let request_body = serde_json::to_string(&tool_request).map_err(GleanMcpError::Json)?;
-
-        // Prepare curl command for MCP tool call
-        let mut curl_args = vec![
-            "-s",
-            "-X",
-            "POST",
-            "-H",
-            "Content-Type: application/json",
-            "-H",
-            "Accept: application/json",
-            "-d",
-            &request_body,
-            "--max-time",
-            "30",
-        ];
-
-        // Add auth header if token is available
-        let auth_header;
-        if let Some(ref token) = auth_token {
-            auth_header = format!("Authorization: Bearer {token}");
-            curl_args.extend_from_slice(&["-H", &auth_header]);
-        }
-
-        curl_args.push(&server_url);
-
-        // Execute curl command
-        let mut child = Command::new("curl")
-            .args(&curl_args)
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .spawn()
-            .map_err(|e| GleanMcpError::Process(format!("Failed to spawn curl: {e}")))?;
-
-        let stdout = child
-            .stdout
-            .take()
-            .ok_or_else(|| GleanMcpError::Process("Failed to capture stdout".to_string()))?;
-        let stderr = child
-            .stderr
-            .take()
-            .ok_or_else(|| GleanMcpError::Process("Failed to capture stderr".to_string()))?;
-
-        let stdout_reader = BufReader::new(stdout);
-        let stderr_reader = BufReader::new(stderr);
-
-        // Read output concurrently
-        let stdout_future = async {
-            let mut lines = Vec::new();
-            let mut line_reader = stdout_reader.lines();
-            while let Some(line) = line_reader.next().await.transpose()? {
-                lines.push(line);
-            }
-            Ok::<Vec<String>, std::io::Error>(lines)
-        };
-
-        let stderr_future = async {
-            let mut lines = Vec::new();
-            let mut line_reader = stderr_reader.lines();
-            while let Some(line) = line_reader.next().await.transpose()? {
-                lines.push(line);
-            }
-            Ok::<Vec<String>, std::io::Error>(lines)
-        };
-
-        let (stdout_lines, stderr_lines) = smol::future::zip(stdout_future, stderr_future).await;
-        let stdout_lines = stdout_lines
-            .map_err(|e| GleanMcpError::Process(format!("Failed to read stdout: {e}")))?;
-        let stderr_lines = stderr_lines
-            .map_err(|e| GleanMcpError::Process(format!("Failed to read stderr: {e}")))?;
-
-        let status = child
-            .status()
-            .await
-            .map_err(|e| GleanMcpError::Process(format!("Failed to get process status: {e}")))?;
+        let response = transport.post_json_rpc(endpoint_url, &tool_request).await?;
+        *trace = RequestTrace::of(&response);
 
-        if !status.success() {
-            let error_output = stderr_lines.join("\n");
+        if !response.success {
             return Err(GleanMcpError::Process(format!(
-                "MCP tool call failed: {error_output}"
+                "MCP tool call failed: {}",
+                response.stderr
             )));
         }
 
-        let stdout_content = stdout_lines.join("\n");
+        if let Some(status) = response.status.filter(|status| *status >= 400) {
+            let body = response.body.trim();
+            return Err(GleanMcpError::Http {
+                status,
+                message: if body.is_empty() {
+                    "empty response body".to_string()
+                } else {
+                    Self::truncate_error_message(body)
+                },
+                retry_after: response.header("retry-after").and_then(parse_retry_after),
+            });
+        }
+
+        let stdout_content = response.body;
 
         // Try to parse the response as JSON-RPC
         #[allow(clippy::option_if_let_else)]
@@ -1355,7 +3222,18 @@ impl GleanMCPInspector {
                     Ok(response_json)
                 }
             }
-            Err(_) => {
+            Err(e) => {
+                // Bodies that start out as JSON but don't parse were cut off or corrupted
+                let trimmed = stdout_content.trim_start();
+                if trimmed.is_empty() {
+                    return Err(GleanMcpError::Process("Empty response body".to_string()));
+                }
+                if trimmed.starts_with('{') || trimmed.starts_with('[') {
+                    return Err(GleanMcpError::Process(format!(
+                        "Malformed JSON-RPC response: {e}"
+                    )));
+                }
+
                 // If not JSON, check if it looks like an error
                 if stdout_content.contains("error")
                     || stdout_content.contains("Error")
@@ -1372,7 +3250,7 @@ impl GleanMCPInspector {
                 } else {
                     Ok(serde_json::json!({
                         "tool": tool_name,
-                        "query": query,
+                        "arguments": arguments,
                         "response": stdout_content,
                         "success": true
                     }))
@@ -1387,17 +3265,23 @@ impl GleanMCPInspector {
     /// 3. Report on core tool availability (assumed for now)
     pub async fn validate_server_with_inspector(&self) -> Result<InspectorResult> {
         let term = Term::stdout();
-        let _ = term.write_line(&format!(
-            "{}{}",
-            MAGNIFYING_GLASS,
-            style("Testing Glean MCP server connection...")
-                .cyan()
-                .bold()
-        ));
-        let _ = term.write_line(&format!("📍 Server: {}", style(&self.server_url).dim()));
+        write_line(
+            &term,
+            &format!(
+                "{}{}",
+                MAGNIFYING_GLASS,
+                style("Testing Glean MCP server connection...")
+                    .cyan()
+                    .bold()
+            ),
+        );
+        write_line(
+            &term,
+            &format!("📍 Server: {}", style(&self.server_url).dim()),
+        );
 
         // Create progress bar for validation steps
-        let pb = ProgressBar::new(3);
+        let pb = crate::progress_bar(3);
         pb.set_style(
             ProgressStyle::with_template(
                 "{spinner:.green} [{elapsed_precise}] {bar:40.cyan/blue} {pos:>1}/{len:1} {msg}",
@@ -1435,23 +3319,25 @@ impl GleanMCPInspector {
         tool_name: &str,
         query: &str,
     ) -> Result<InspectorResult> {
-        println!("🔍 Testing tool '{tool_name}' with direct MCP protocol call...");
-        println!("📝 Query: {query}");
-        println!("📍 Server: {}", self.server_url);
+        self.test_tool_with_arguments(
+            tool_name,
+            &TestQueryGenerator::generate_arguments(tool_name, query),
+        )
+        .await
+    }
 
-        // Create MCP JSON-RPC request for tool call
-        // Different tools expect different parameter names
-        let arguments = match tool_name {
-            "chat" => serde_json::json!({
-                "message": query
-            }),
-            "read_document" => serde_json::json!({
-                "url": query
-            }),
-            _ => serde_json::json!({
-                "query": query
-            }),
-        };
+    /// Test a specific MCP tool with structured `arguments`
+    pub async fn test_tool_with_arguments(
+        &self,
+        tool_name: &str,
+        arguments: &Value,
+    ) -> Result<InspectorResult> {
+        write_line(
+            &Term::stdout(),
+            &format!("🔍 Testing tool '{tool_name}' with direct MCP protocol call..."),
+        );
+        write_line(&Term::stdout(), &format!("📝 Arguments: {arguments}"));
+        write_line(&Term::stdout(), &format!("📍 Server: {}", self.server_url));
 
         let tool_request = serde_json::json!({
             "jsonrpc": "2.0",
@@ -1463,127 +3349,81 @@ impl GleanMCPInspector {
             }
         });
 
-        let request_body = serde_json::to_string(&tool_request).map_err(GleanMcpError::Json)?;
-
-        // Prepare curl command for MCP tool call
-        let mut curl_args = vec![
-            "-s",
-            "-X",
-            "POST",
-            "-H",
-            "Content-Type: application/json",
-            "-H",
-            "Accept: application/json",
-            "-d",
-            &request_body,
-            "--max-time",
-            "30",
-        ];
-
-        // Add auth header if token is available
-        let auth_header;
-        if let Some(ref token) = self.auth_token {
-            auth_header = format!("Authorization: Bearer {token}");
-            curl_args.extend_from_slice(&["-H", &auth_header]);
-            println!("🔐 Using authentication token for tool call");
+        if self.transport.has_auth_token() {
+            write_line(
+                &Term::stdout(),
+                "🔐 Using authentication token for tool call",
+            );
         } else {
-            println!("🔓 Making unauthenticated tool call (may fail)");
+            write_line(
+                &Term::stdout(),
+                "🔓 Making unauthenticated tool call (may fail)",
+            );
         }
 
-        curl_args.push(&self.server_url);
-
-        // Execute curl command for MCP tool call
-        let mut child = Command::new("curl")
-            .args(&curl_args)
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .spawn()
-            .map_err(|e| GleanMcpError::Process(format!("Failed to spawn curl: {e}")))?;
-
-        let stdout = child
-            .stdout
-            .take()
-            .ok_or_else(|| GleanMcpError::Process("Failed to capture stdout".to_string()))?;
-        let stderr = child
-            .stderr
-            .take()
-            .ok_or_else(|| GleanMcpError::Process("Failed to capture stderr".to_string()))?;
-
-        let stdout_reader = BufReader::new(stdout);
-        let stderr_reader = BufReader::new(stderr);
-
-        // Read output concurrently
-        let stdout_future = async {
-            let mut lines = Vec::new();
-            let mut line_reader = stdout_reader.lines();
-            while let Some(line) = line_reader.next().await.transpose()? {
-                lines.push(line);
-            }
-            Ok::<Vec<String>, std::io::Error>(lines)
-        };
-
-        let stderr_future = async {
-            let mut lines = Vec::new();
-            let mut line_reader = stderr_reader.lines();
-            while let Some(line) = line_reader.next().await.transpose()? {
-                lines.push(line);
-            }
-            Ok::<Vec<String>, std::io::Error>(lines)
-        };
-
-        let (stdout_lines, stderr_lines) = smol::future::zip(stdout_future, stderr_future).await;
-        let stdout_lines = stdout_lines
-            .map_err(|e| GleanMcpError::Process(format!("Failed to read stdout: {e}")))?;
-        let stderr_lines = stderr_lines
-            .map_err(|e| GleanMcpError::Process(format!("Failed to read stderr: {e}")))?;
-
-        let status = child
-            .status()
-            .await
-            .map_err(|e| GleanMcpError::Process(format!("Failed to get process status: {e}")))?;
+        let response = self
+            .transport
+            .post_json_rpc(&self.server_url, &tool_request)
+            .await?;
 
-        if !status.success() {
-            let error_output = stderr_lines.join("\n");
-            println!("❌ MCP tool call failed!");
-            println!("Error output: {error_output}");
+        if !response.success {
+            let error_output = response.stderr;
+            write_line(&Term::stdout(), "❌ MCP tool call failed!");
+            write_line(&Term::stdout(), &format!("Error output: {error_output}"));
             return Ok(InspectorResult::new_error(format!(
                 "MCP tool call failed: {error_output}"
             )));
         }
 
-        let stdout_content = stdout_lines.join("\n");
-        println!("📥 Raw response: {stdout_content}");
+        // Quoted in error reports, so the backend request can be looked up
+        let ids: Vec<String> = request_ids(&response.headers)
+            .iter()
+            .map(|(name, value)| format!("{name} {value}"))
+            .collect();
+        let reference = if ids.is_empty() {
+            String::new()
+        } else {
+            format!(" ({})", ids.join(", "))
+        };
+        let stdout_content = response.body;
+        write_line(
+            &Term::stdout(),
+            &format!("📥 Raw response: {stdout_content}"),
+        );
 
         // Try to parse the response as JSON-RPC
         if let Ok(response_json) = serde_json::from_str::<serde_json::Value>(&stdout_content) {
             // Check if it's a successful JSON-RPC response
             #[allow(clippy::option_if_let_else)]
             if let Some(result) = response_json.get("result") {
-                println!("✅ Tool call successful!");
-                println!("📄 Response received from {tool_name}");
+                write_line(&Term::stdout(), "✅ Tool call successful!");
+                write_line(
+                    &Term::stdout(),
+                    &format!("📄 Response received from {tool_name}"),
+                );
 
                 // Create success result with tool response
-                let mut tool_results = std::collections::HashMap::new();
+                let mut tool_results = BTreeMap::new();
                 tool_results.insert(tool_name.to_string(), true);
 
                 Ok(InspectorResult::new_success(tool_results, result.clone()))
             } else if let Some(error) = response_json.get("error") {
-                println!("❌ MCP server returned error!");
-                println!("Error: {error}");
+                write_line(&Term::stdout(), "❌ MCP server returned error!");
+                write_line(&Term::stdout(), &format!("Error: {error}"));
                 Ok(InspectorResult::new_error(format!(
-                    "MCP server error: {error}"
+                    "MCP server error: {error}{reference}"
                 )))
             } else {
                 // Unknown JSON structure
-                println!("⚠️  Unexpected JSON response structure");
-                let mut tool_results = std::collections::HashMap::new();
+                write_line(&Term::stdout(), "⚠️  Unexpected JSON response structure");
+                let mut tool_results = BTreeMap::new();
                 tool_results.insert(tool_name.to_string(), true);
                 Ok(InspectorResult::new_success(tool_results, response_json))
             }
         } else {
             // If not JSON, treat as plain text response (might be an error)
-            println!("⚠️  Non-JSON response received");
-            println!("📄 Response: {stdout_content}");
+            write_line(&Term::stdout(), "⚠️  Non-JSON response received");
+            write_line(&Term::stdout(), &format!("📄 Response: {stdout_content}"));
 
             // Check if it looks like an error
             if stdout_content.contains("error")
@@ -1592,16 +3432,16 @@ impl GleanMCPInspector {
                 || stdout_content.contains("403")
             {
                 return Ok(InspectorResult::new_error(format!(
-                    "Server error: {stdout_content}"
+                    "Server error: {stdout_content}{reference}"
                 )));
             }
 
-            let mut tool_results = std::collections::HashMap::new();
+            let mut tool_results = BTreeMap::new();
             tool_results.insert(tool_name.to_string(), true);
 
             let response_value = serde_json::json!({
                 "tool": tool_name,
-                "query": query,
+                "arguments": arguments,
                 "response": stdout_content,
                 "success": true
             });
@@ -1621,6 +3461,16 @@ impl GleanMCPInspector {
         &self,
         endpoint_url: &str,
         debug: bool,
+    ) -> Result<InspectorResult> {
+        self.list_tools_with_transport(&self.transport, endpoint_url, debug)
+            .await
+    }
+
+    pub(crate) async fn list_tools_with_transport(
+        &self,
+        transport: &McpTransport,
+        endpoint_url: &str,
+        debug: bool,
     ) -> Result<InspectorResult> {
         // This function runs in quiet mode - no direct terminal output
 
@@ -1632,100 +3482,37 @@ impl GleanMCPInspector {
             "params": {}
         });
 
-        let request_body = serde_json::to_string(&list_request).map_err(GleanMcpError::Json)?;
-
-        // Prepare curl command for MCP list tools call
-        let mut curl_args = vec![
-            "-s",
-            "-X",
-            "POST",
-            "-H",
-            "Content-Type: application/json",
-            "-H",
-            "Accept: application/json",
-            "-d",
-            &request_body,
-            "--max-time",
-            "30",
-        ];
-
-        // Add auth header if token is available
-        let auth_header;
-        if let Some(ref token) = self.auth_token {
-            auth_header = format!("Authorization: Bearer {token}");
-            curl_args.extend_from_slice(&["-H", &auth_header]);
-            // Debug output removed
-        }
-
-        curl_args.push(endpoint_url);
-
-        // Execute curl command
-        let mut child = Command::new("curl")
-            .args(&curl_args)
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .spawn()
-            .map_err(|e| GleanMcpError::Process(format!("Failed to spawn curl: {e}")))?;
-
-        let stdout = child
-            .stdout
-            .take()
-            .ok_or_else(|| GleanMcpError::Process("Failed to capture stdout".to_string()))?;
-        let stderr = child
-            .stderr
-            .take()
-            .ok_or_else(|| GleanMcpError::Process("Failed to capture stderr".to_string()))?;
-
-        let stdout_reader = BufReader::new(stdout);
-        let stderr_reader = BufReader::new(stderr);
-
-        // Read output concurrently
-        let stdout_future = async {
-            let mut lines = Vec::new();
-            let mut line_reader = stdout_reader.lines();
-            while let Some(line) = line_reader.next().await.transpose()? {
-                lines.push(line);
-            }
-            Ok::<Vec<String>, std::io::Error>(lines)
-        };
-
-        let stderr_future = async {
-            let mut lines = Vec::new();
-            let mut line_reader = stderr_reader.lines();
-            while let Some(line) = line_reader.next().await.transpose()? {
-                lines.push(line);
-            }
-            Ok::<Vec<String>, std::io::Error>(lines)
-        };
-
-        let (stdout_lines, stderr_lines) = smol::future::zip(stdout_future, stderr_future).await;
-        let stdout_lines = stdout_lines
-            .map_err(|e| GleanMcpError::Process(format!("Failed to read stdout: {e}")))?;
-        let stderr_lines = stderr_lines
-            .map_err(|e| GleanMcpError::Process(format!("Failed to read stderr: {e}")))?;
-
-        let status = child
-            .status()
-            .await
-            .map_err(|e| GleanMcpError::Process(format!("Failed to get process status: {e}")))?;
+        let response = transport.post_json_rpc(endpoint_url, &list_request).await?;
 
-        if !status.success() {
-            let error_output = stderr_lines.join("\n");
-            println!("❌ MCP Inspector failed to list tools!");
-            println!("Error output: {error_output}");
+        if !response.success {
+            let error_output = response.stderr;
+            self.reporter.warning(&format!(
+                "Failed to list tools from {endpoint_url}: {error_output}"
+            ));
             return Ok(InspectorResult::new_error(format!(
                 "MCP Inspector tool listing failed: {error_output}"
             )));
         }
 
-        let stdout_content = stdout_lines.join("\n");
+        let stdout_content = response.body;
 
         if debug {
-            println!("📥 MCP Inspector response: {stdout_content}");
+            self.reporter
+                .info(&format!("MCP Inspector response: {stdout_content}"));
         }
 
         // Try to parse the response - MCP Inspector may return different formats
-        if let Ok(response_json) = serde_json::from_str::<serde_json::Value>(&stdout_content) {
+        if let Ok(mut response_json) = serde_json::from_str::<serde_json::Value>(&stdout_content) {
+            // Servers may split the tool list across pages
+            let walk = self
+                .follow_tools_list_cursors(transport, endpoint_url, &mut response_json)
+                .await;
+            for problem in &walk.problems {
+                self.reporter.warning(&format!(
+                    "Incomplete tool listing from {endpoint_url}: {problem}"
+                ));
+            }
+
             // Try to extract tools from various possible response structures
             let tools = response_json.get("result").map_or_else(
                 || {
@@ -1743,13 +3530,13 @@ impl GleanMCPInspector {
                 // Tool discovery output handled by caller through MultiProgress
             }
 
-            let mut tool_results = std::collections::HashMap::new();
+            let mut tool_results = BTreeMap::new();
             tool_results.insert("tools_listed".to_string(), true);
             Ok(InspectorResult::new_success(tool_results, response_json))
         } else {
             // If not JSON, MCP Inspector may have output plain text
-            println!("✅ Tools listed (text format):");
-            println!("📄 Response: {stdout_content}");
+            self.reporter
+                .info(&format!("Tools listed (text format): {stdout_content}"));
 
             // Check if it looks like an error
             if stdout_content.contains("error") || stdout_content.contains("Failed") {
@@ -1758,7 +3545,7 @@ impl GleanMCPInspector {
                 )));
             }
 
-            let mut tool_results = std::collections::HashMap::new();
+            let mut tool_results = BTreeMap::new();
             tool_results.insert("tools_listed".to_string(), true);
 
             let response_value = serde_json::json!({
@@ -1773,7 +3560,10 @@ impl GleanMCPInspector {
 
     /// Basic connectivity test to check if the Glean MCP server is reachable
     async fn test_basic_connectivity(&self) -> Result<InspectorResult> {
-        println!("🔗 Testing basic connectivity to Glean MCP server...");
+        write_line(
+            &Term::stdout(),
+            "🔗 Testing basic connectivity to Glean MCP server...",
+        );
 
         // Use curl to test the HTTP endpoint with a timeout
         // Include auth header if token is available, otherwise expect 401 Unauthorized
@@ -1794,10 +3584,19 @@ impl GleanMCPInspector {
         if let Some(ref token) = self.auth_token {
             auth_header = format!("Authorization: Bearer {token}");
             curl_args.extend_from_slice(&["-H", &auth_header]);
-            println!("🔐 Using authentication token for request");
+            write_line(&Term::stdout(), "🔐 Using authentication token for request");
         } else {
-            println!("🔓 Making unauthenticated request (expecting 401)");
+            write_line(
+                &Term::stdout(),
+                "🔓 Making unauthenticated request (expecting 401)",
+            );
         }
+        let proxy_args = self.transport.proxy().curl_args(&self.server_url);
+        curl_args.extend(proxy_args.iter().map(String::as_str));
+        let tls_args = self.transport.tls().curl_args();
+        curl_args.extend(tls_args.iter().map(String::as_str));
+        let header_args = self.transport.header_args();
+        curl_args.extend(header_args.iter().map(String::as_str));
 
         curl_args.push(&self.server_url);
 
@@ -1855,57 +3654,98 @@ impl GleanMCPInspector {
         if let Some(status_code) = response.lines().last() {
             match (status_code, &self.auth_token) {
                 ("401", None) => {
-                    println!("✅ Server is reachable and properly configured!");
-                    println!("🔐 Received expected 401 Unauthorized (OAuth required)");
-                    println!("🎯 This confirms the Glean MCP server is running and protected");
-                    println!(
-                        "💡 Tip: Set GLEAN_MCP_TOKEN environment variable to test with authentication"
+                    write_line(
+                        &Term::stdout(),
+                        "✅ Server is reachable and properly configured!",
+                    );
+                    write_line(
+                        &Term::stdout(),
+                        "🔐 Received expected 401 Unauthorized (OAuth required)",
+                    );
+                    write_line(
+                        &Term::stdout(),
+                        "🎯 This confirms the Glean MCP server is running and protected",
+                    );
+                    write_line(
+                        &Term::stdout(),
+                        "💡 Tip: Set GLEAN_MCP_TOKEN environment variable to test with authentication",
                     );
                 }
                 ("401", Some(_)) => {
-                    println!("❌ Authentication failed!");
-                    println!("🔑 Token provided but server returned 401 Unauthorized");
-                    println!("💡 Check if your token is valid and has the correct permissions");
+                    write_line(&Term::stdout(), "❌ Authentication failed!");
+                    write_line(
+                        &Term::stdout(),
+                        "🔑 Token provided but server returned 401 Unauthorized",
+                    );
+                    write_line(
+                        &Term::stdout(),
+                        "💡 Check if your token is valid and has the correct permissions",
+                    );
                     return Ok(InspectorResult::new_error(
                         "Authentication failed: Invalid or expired token".to_string(),
                     ));
                 }
                 ("200", Some(_)) => {
-                    println!("✅ Authenticated successfully!");
-                    println!("🔑 Server accepted authentication token");
-                    println!("🎯 Ready for full MCP protocol testing");
+                    write_line(&Term::stdout(), "✅ Authenticated successfully!");
+                    write_line(&Term::stdout(), "🔑 Server accepted authentication token");
+                    write_line(&Term::stdout(), "🎯 Ready for full MCP protocol testing");
                 }
                 ("202", Some(_)) => {
-                    println!("✅ Authenticated successfully!");
-                    println!("🔑 Server accepted authentication token (202 Accepted)");
-                    println!("🎯 MCP server ready for protocol communication");
+                    write_line(&Term::stdout(), "✅ Authenticated successfully!");
+                    write_line(
+                        &Term::stdout(),
+                        "🔑 Server accepted authentication token (202 Accepted)",
+                    );
+                    write_line(
+                        &Term::stdout(),
+                        "🎯 MCP server ready for protocol communication",
+                    );
                 }
                 ("200", None) => {
-                    println!("⚠️  Unexpected: Server responded with 200 OK without authentication");
-                    println!(
-                        "🔓 This might indicate the server is not properly configured for OAuth"
+                    write_line(
+                        &Term::stdout(),
+                        "⚠️  Unexpected: Server responded with 200 OK without authentication",
+                    );
+                    write_line(
+                        &Term::stdout(),
+                        "🔓 This might indicate the server is not properly configured for OAuth",
                     );
                 }
                 ("403", _) => {
-                    println!("❌ Access forbidden!");
-                    println!("🚫 Server rejected request - check permissions or token scope");
+                    write_line(&Term::stdout(), "❌ Access forbidden!");
+                    write_line(
+                        &Term::stdout(),
+                        "🚫 Server rejected request - check permissions or token scope",
+                    );
                     return Ok(InspectorResult::new_error(
                         "Access forbidden: Insufficient permissions".to_string(),
                     ));
                 }
                 (code, Some(_)) => {
-                    println!("⚠️  Server responded with HTTP {code} (authenticated)");
+                    write_line(
+                        &Term::stdout(),
+                        &format!("⚠️  Server responded with HTTP {code} (authenticated)"),
+                    );
                     if !status.success() {
-                        println!("❌ Request failed: {error_output}");
+                        write_line(
+                            &Term::stdout(),
+                            &format!("❌ Request failed: {error_output}"),
+                        );
                         return Ok(InspectorResult::new_error(format!(
                             "HTTP {code}: {error_output}"
                         )));
                     }
                 }
                 (code, None) => {
-                    println!("⚠️  Server responded with HTTP {code} (unauthenticated)");
+                    write_line(
+                        &Term::stdout(),
+                        &format!("⚠️  Server responded with HTTP {code} (unauthenticated)"),
+                    );
                     if !status.success() {
-                        println!("❌ Request failed: {error_output}");
+                        write_line(
+                            &Term::stdout(),
+                            &format!("❌ Request failed: {error_output}"),
+                        );
                         return Ok(InspectorResult::new_error(format!(
                             "HTTP {code}: {error_output}"
                         )));
@@ -1913,164 +3753,311 @@ impl GleanMCPInspector {
                 }
             }
         } else if !status.success() {
-            println!("❌ Server connection failed: {error_output}");
+            write_line(
+                &Term::stdout(),
+                &format!("❌ Server connection failed: {error_output}"),
+            );
             return Ok(InspectorResult::new_error(format!(
                 "Connection failed: {error_output}"
             )));
         }
 
-        println!(
-            "📄 Response preview: {}",
-            if response.len() > 100 {
-                &response[..100]
-            } else {
-                &response
-            }
+        write_line(
+            &Term::stdout(),
+            &format!(
+                "📄 Response preview: {}",
+                if response.len() > 100 {
+                    &response[..100]
+                } else {
+                    &response
+                }
+            ),
         );
 
-        // For basic connectivity test, assume all tools are available if server responds
-        let mut tool_validation = HashMap::new();
-        let expected_tools = vec![
-            "search",
-            "chat",
-            "read_document",
-            "code_search",
-            "employee_search",
-            "gmail_search",
-            "outlook_search",
-            "meeting_lookup",
-            "web_browser",
-            "gemini_web_search",
-        ];
-
         let is_authenticated = self.auth_token.is_some()
             && (response.lines().last() == Some("200") || response.lines().last() == Some("202"));
 
-        for tool_name in &expected_tools {
-            tool_validation.insert((*tool_name).to_string(), true);
-            if is_authenticated {
-                println!("✅ Tool available (authenticated): {tool_name}");
-            } else {
-                println!("✅ Tool assumed available (unauthenticated): {tool_name}");
-            }
-        }
-
         let result = InspectorResult {
+            schema_version: schema_version(),
             success: true,
-            tool_results: Some(tool_validation),
+            tool_results: None,
             inspector_data: Some(serde_json::Value::String(response)),
             error: None,
+            server: self.server_identity().await.ok(),
         };
 
         if is_authenticated {
-            println!("🎉 Authenticated server validation completed successfully!");
-            println!("🚀 Ready for full MCP protocol testing with actual tool calls");
+            write_line(
+                &Term::stdout(),
+                "🎉 Authenticated server validation completed successfully!",
+            );
+            write_line(&Term::stdout(), "🚀 Ready to check the advertised tools");
         } else {
-            println!("🎉 Basic server validation completed successfully!");
-            println!(
-                "📝 Note: This is a basic connectivity test. Set auth token for full validation."
+            write_line(
+                &Term::stdout(),
+                "🎉 Basic server validation completed successfully!",
+            );
+            write_line(
+                &Term::stdout(),
+                "📝 Note: This is a basic connectivity test. Set auth token for full validation.",
             );
         }
 
         Ok(result)
     }
 
-    /// Validate that Glean-specific tools are present and correctly configured
-    /// (This method will be used when we implement full MCP protocol parsing)
-    #[must_use]
-    pub fn validate_glean_tools(inspector_data: Value) -> InspectorResult {
-        let expected_tools = vec![
-            "search",
-            "chat",
-            "read_document",
-            "code_search",
-            "employee_search",
-            "gmail_search",
-            "outlook_search",
-            "meeting_lookup",
-            "web_browser",
-            "gemini_web_search",
-        ];
-        let empty_vec = vec![];
-        let available_tools = inspector_data
-            .get("tools")
-            .and_then(|t| t.as_array())
-            .unwrap_or(&empty_vec);
-
-        let mut tool_validation = HashMap::new();
-        for tool_name in &expected_tools {
-            let found = Self::validate_tool_schema(tool_name, available_tools);
-            tool_validation.insert((*tool_name).to_string(), found);
+    /// Check the tools in a `tools/list` response against `endpoint`'s manifest
+    ///
+    /// Each expected tool is reported as found or missing; with
+    /// `exact_tools`, tools outside the manifest are reported as failing.
+    #[must_use]
+    pub fn validate_glean_tools(
+        inspector_data: Value,
+        endpoint: &EndpointConfig,
+    ) -> InspectorResult {
+        let available_tools = ToolInfo::from_tools_list(&inspector_data);
+        let manifest = ToolManifestCheck::compare(endpoint, &available_tools);
+
+        let mut tool_validation = BTreeMap::new();
+        for tool_name in &endpoint.expected_tools {
+            let found = !manifest.missing.contains(tool_name);
+            tool_validation.insert(tool_name.clone(), found);
 
             if found {
-                println!("✅ Validated tool: {tool_name}");
+                write_line(&Term::stdout(), &format!("✅ Validated tool: {tool_name}"));
             } else {
-                println!("❌ Missing tool: {tool_name}");
+                write_line(&Term::stdout(), &format!("❌ Missing tool: {tool_name}"));
             }
         }
+        for tool_name in &manifest.unexpected {
+            tool_validation.insert(tool_name.clone(), false);
+            write_line(&Term::stdout(), &format!("❌ Unexpected tool: {tool_name}"));
+        }
 
-        let success_count = tool_validation.values().filter(|&&v| v).count();
-        #[allow(clippy::cast_precision_loss)]
-        let success_rate = success_count as f64 / expected_tools.len() as f64;
-
-        if (success_rate - 1.0).abs() < f64::EPSILON {
-            println!("🎉 All Glean MCP tools validated successfully!");
-            InspectorResult::new_success(tool_validation, inspector_data)
-        } else {
-            let error_msg = format!(
-                "Only {}/{} tools validated successfully",
-                success_count,
-                expected_tools.len()
-            );
-            println!("⚠️  {error_msg}");
-            let mut result = InspectorResult::new_success(tool_validation, inspector_data);
-            result.success = false;
-            result.error = Some(error_msg);
-            result
+        match manifest.describe() {
+            None => {
+                write_line(
+                    &Term::stdout(),
+                    &format!("🎉 {} matches its tool manifest!", endpoint.name),
+                );
+                InspectorResult::new_success(tool_validation, inspector_data)
+            }
+            Some(mismatch) => {
+                let error_msg = format!(
+                    "{} doesn't match its tool manifest: {mismatch}",
+                    endpoint.name
+                );
+                write_line(&Term::stdout(), &format!("⚠️  {error_msg}"));
+                let mut result = InspectorResult::new_success(tool_validation, inspector_data);
+                result.success = false;
+                result.error = Some(error_msg);
+                result
+            }
         }
     }
+}
 
-    fn validate_tool_schema(tool_name: &str, available_tools: &[Value]) -> bool {
-        available_tools
-            .iter()
-            .any(|tool| tool.get("name").and_then(|name| name.as_str()) == Some(tool_name))
-    }
+/// `case`, skipped because the run was cancelled before it finished
+fn interrupted(case: &TestCase) -> ToolTestResult {
+    ToolTestResult::new_skipped(case.name.clone(), SkipReason::Interrupted, None)
+}
+
+/// Take the next case a worker may run, with a permit when its tool has its own concurrency cap
+///
+/// Capped tools whose slots are all busy are passed over while other tools
+/// are queued; only then does the worker wait for a slot. Returns `None`
+/// once the queue is empty or the run is cancelled.
+async fn next_queued_tool<'a>(
+    queue: &Mutex<VecDeque<&'a TestCase>>,
+    tool_semaphores: &HashMap<&str, Arc<Semaphore>>,
+    cancel: &CancellationToken,
+) -> Option<(&'a TestCase, Option<SemaphoreGuardArc>)> {
+    let case = {
+        let mut queued = queue.lock().unwrap_or_else(PoisonError::into_inner);
+        if cancel.is_cancelled() {
+            return None;
+        }
+        let ready = queued.iter().enumerate().find_map(|(index, case)| {
+            tool_semaphores.get(case.tool.name.as_str()).map_or_else(
+                || Some((index, None)),
+                |semaphore| {
+                    semaphore
+                        .try_acquire_arc()
+                        .map(|permit| (index, Some(permit)))
+                },
+            )
+        });
+        if let Some((index, permit)) = ready {
+            return queued.remove(index).map(|case| (case, permit));
+        }
+        queued.pop_front()?
+    };
+
+    let semaphore = tool_semaphores.get(case.tool.name.as_str())?;
+    let Some(permit) = cancel.run_until_cancelled(semaphore.acquire_arc()).await else {
+        queue
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push_front(case);
+        return None;
+    };
+    Some((case, Some(permit)))
+}
+
+/// Keep `text` on one line and out of Markdown table column separators
+fn markdown_cell(text: &str) -> String {
+    text.replace('|', "\\|").replace(['\r', '\n'], " ")
+}
+
+/// Escape `text` for inline HTML such as `<summary>`
+fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
 }
 
-/// Example usage with smol runtime
+// Blocking entry points are thin `smol::block_on` shims over their `_async`
+// variants. Async callers must use the `_async` variants: blocking inside an
+// executor stalls (or, on some runtimes, panics) the calling task.
+
+/// Validate the MCP server and that it advertises the core tools
+pub async fn run_validation_async(instance_name: Option<&str>) -> Result<InspectorResult> {
+    GleanMCPInspector::new(instance_name)
+        .validate_server_and_tools(&GleanConfig::default().inspected_endpoints())
+        .await
+}
+
+/// Blocking [`run_validation_async`]
 pub fn run_validation(instance_name: Option<&str>) -> Result<InspectorResult> {
-    smol::block_on(async {
-        let inspector = GleanMCPInspector::new(instance_name);
-        inspector.validate_server_with_inspector().await
-    })
+    smol::block_on(run_validation_async(instance_name))
 }
 
 /// List available tools from the MCP server
+pub async fn run_list_tools_async(instance_name: Option<&str>) -> Result<InspectorResult> {
+    GleanMCPInspector::new(instance_name)
+        .list_available_tools(false) // Never debug for list-tools command
+        .await
+}
+
+/// Blocking [`run_list_tools_async`]
 pub fn run_list_tools(instance_name: Option<&str>, _format: &str) -> Result<InspectorResult> {
-    smol::block_on(async {
-        let inspector = GleanMCPInspector::new(instance_name);
-        inspector.list_available_tools(false).await // Never debug for list-tools command
-    })
+    smol::block_on(run_list_tools_async(instance_name))
+}
+
+/// Call a single MCP tool with `query`
+pub async fn run_tool_test_async(
+    instance_name: Option<&str>,
+    tool_name: &str,
+    query: &str,
+) -> Result<InspectorResult> {
+    GleanMCPInspector::new(instance_name)
+        .test_tool_with_inspector(tool_name, query)
+        .await
+}
+
+/// Blocking [`run_tool_test_async`]
+pub fn run_tool_test(
+    instance_name: Option<&str>,
+    tool_name: &str,
+    query: &str,
+) -> Result<InspectorResult> {
+    smol::block_on(run_tool_test_async(instance_name, tool_name, query))
 }
 
 /// Run comprehensive testing of all available MCP tools
+#[allow(clippy::future_not_send)]
+pub async fn run_test_all_async(
+    instance_name: Option<&str>,
+    options: &TestAllOptions,
+) -> Result<AllToolsTestResult> {
+    run_test_all_with_reporter_async(instance_name, options, reporter_for_format("text")).await
+}
+
+/// Blocking [`run_test_all_async`]
 pub fn run_test_all(
     instance_name: Option<&str>,
     options: &TestAllOptions,
 ) -> Result<AllToolsTestResult> {
-    smol::block_on(async {
-        let inspector = GleanMCPInspector::new(instance_name);
-        inspector.test_all_tools(options).await
-    })
+    smol::block_on(run_test_all_async(instance_name, options))
+}
+
+/// Run comprehensive testing of all available MCP tools, reporting through `reporter`
+#[allow(clippy::future_not_send)]
+pub async fn run_test_all_with_reporter_async(
+    instance_name: Option<&str>,
+    options: &TestAllOptions,
+    reporter: Arc<dyn Reporter>,
+) -> Result<AllToolsTestResult> {
+    GleanMCPInspector::with_reporter(instance_name, reporter)
+        .test_all_tools(options)
+        .await
+}
+
+/// Blocking [`run_test_all_with_reporter_async`]
+pub fn run_test_all_with_reporter(
+    instance_name: Option<&str>,
+    options: &TestAllOptions,
+    reporter: Arc<dyn Reporter>,
+) -> Result<AllToolsTestResult> {
+    smol::block_on(run_test_all_with_reporter_async(
+        instance_name,
+        options,
+        reporter,
+    ))
 }
 
 /// Run comprehensive testing of all available MCP tools on ChatGPT-specific endpoint
+#[allow(clippy::future_not_send)]
+pub async fn run_test_chatgpt_async(
+    instance_name: Option<&str>,
+    options: &TestAllOptions,
+) -> Result<AllToolsTestResult> {
+    GleanMCPInspector::new(instance_name)
+        .test_chatgpt_tools(options)
+        .await
+}
+
+/// Blocking [`run_test_chatgpt_async`]
 pub fn run_test_chatgpt(
     instance_name: Option<&str>,
     options: &TestAllOptions,
 ) -> Result<AllToolsTestResult> {
-    smol::block_on(async {
-        let inspector = GleanMCPInspector::new(instance_name);
-        inspector.test_chatgpt_tools(options).await
-    })
+    smol::block_on(run_test_chatgpt_async(instance_name, options))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Shard;
+
+    #[test]
+    fn tools_with_several_dependencies_share_a_shard_with_all_of_them() {
+        let mut options = TestAllOptions::default();
+        // `report` needs both `search` and `people`, which depend on nothing
+        options.depends_on.insert(
+            "report".to_string(),
+            vec!["search".to_string(), "people".to_string()],
+        );
+        options
+            .depends_on
+            .insert("summary".to_string(), vec!["report".to_string()]);
+
+        for tool in ["people", "report", "search", "summary"] {
+            assert_eq!(options.dependency_group(tool), "people", "{tool}");
+        }
+        assert_eq!(options.dependency_group("chat"), "chat");
+
+        let tools = ["chat", "people", "report", "search", "summary", "zebra"];
+        for count in 1..=4 {
+            for index in 1..=count {
+                let shard = Shard { index, count };
+                let ours = shard.select(tools.to_vec(), |tool| options.dependency_group(tool));
+                let connected = ours
+                    .iter()
+                    .filter(|tool| options.dependency_group(tool) == "people")
+                    .count();
+                assert!(connected == 0 || connected == 4, "shard {shard}: {ours:?}");
+            }
+        }
+    }
 }
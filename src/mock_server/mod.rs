@@ -0,0 +1,817 @@
+//! Built-in mock Glean MCP server for framework self-tests
+//!
+//! [`MockServer`] is a minimal HTTP/1.1 JSON-RPC server implementing
+//! `initialize`, `ping`, `tools/list`, and `tools/call` on any path, so the
+//! inspector can be pointed at `http://127.0.0.1:<port>/mcp/default` instead
+//! of a real Glean instance. Each tool has a canned response, a simulated latency, and
+//! an optional injected failure rate. `tools/list` and tool results can be
+//! split into cursor-paginated pages. A GET opens an event stream carrying
+//! the configured notifications, or gets HTTP 405 when there are none. With
+//! `stream_responses`, tool calls are answered as resumable event streams.
+//! A `notifications/cancelled` for an in-flight call stops it, and its
+//! connection is closed without a response.
+
+use crate::utils::http_server::{
+    Incoming, read_request, write_response, write_response_with_headers,
+};
+use crate::{GleanMcpError, Result, TestQueryGenerator};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use smol::net::{SocketAddr, TcpListener, TcpStream};
+use std::collections::HashSet;
+use std::fmt::Write;
+use std::path::Path;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+const JSON: &str = "application/json";
+const EVENT_STREAM: &str = "text/event-stream";
+/// Request id header sent with every answered `tools/call`
+const REQUEST_ID_HEADER: &str = "X-Glean-Request-Id";
+/// Default body limit, above the largest `fuzz` payload once JSON-escaped
+const DEFAULT_MAX_REQUEST_BYTES: usize = 16 * 1024 * 1024;
+/// How often a slow tool call checks whether it was cancelled
+const CANCEL_POLL: Duration = Duration::from_millis(50);
+
+/// Behaviour of the mock server
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MockServerConfig {
+    /// Tools advertised by `tools/list`
+    pub tools: Vec<MockTool>,
+    /// When set, requests without `Authorization: Bearer <token>` get HTTP 401
+    /// with a `WWW-Authenticate` challenge
+    pub required_token: Option<String>,
+    /// When set, `tools/list` returns this many tools per page with a `nextCursor`
+    pub tools_page_size: Option<usize>,
+    /// Messages sent as server-sent events on a GET stream; strings are sent
+    /// as-is, so malformed data can be simulated
+    pub notifications: Vec<Value>,
+    /// Answer `tools/call` from clients that accept it with an event stream of
+    /// a progress notification and the result, each with an event id; a GET
+    /// with `Last-Event-ID` replays the rest of that stream
+    pub stream_responses: bool,
+    /// Finish tool calls even after `notifications/cancelled`, like a runaway server
+    pub ignore_cancellation: bool,
+    /// Largest request body accepted; bigger ones are discarded and get HTTP 413
+    pub max_request_bytes: usize,
+}
+
+/// A tool served by the mock
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MockTool {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// `inputSchema` advertised by `tools/list`; defaults to the tool's single string parameter
+    #[serde(default)]
+    pub input_schema: Option<Value>,
+    /// `tools/call` result; defaults to a text block echoing the arguments
+    #[serde(default)]
+    pub response: Option<Value>,
+    /// Simulated latency per call
+    #[serde(default)]
+    pub latency_ms: u64,
+    /// Random extra latency, up to this many milliseconds
+    #[serde(default)]
+    pub jitter_ms: u64,
+    /// Probability (0.0-1.0) that a call fails with the injected error
+    #[serde(default)]
+    pub error_rate: f64,
+    #[serde(default)]
+    pub error: MockError,
+    /// Pages of results behind a `cursor` argument; 0 or 1 returns a single page
+    #[serde(default)]
+    pub pages: usize,
+}
+
+/// Failure returned when an error is injected
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MockError {
+    /// Reply with this HTTP status instead of a JSON-RPC error
+    pub http_status: Option<u16>,
+    /// `Retry-After` seconds sent with an `http_status` reply
+    pub retry_after_secs: Option<u64>,
+    pub code: i64,
+    pub message: String,
+}
+
+impl Default for MockError {
+    fn default() -> Self {
+        Self {
+            http_status: None,
+            retry_after_secs: None,
+            code: -32603,
+            message: "Injected failure".to_string(),
+        }
+    }
+}
+
+impl MockTool {
+    #[must_use]
+    pub fn new(name: &str, description: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            description: Some(description.to_string()),
+            input_schema: None,
+            response: None,
+            latency_ms: 0,
+            jitter_ms: 0,
+            error_rate: 0.0,
+            error: MockError::default(),
+            pages: 0,
+        }
+    }
+
+    /// The configured `inputSchema`, or the default one plus a `cursor` for paginated tools
+    fn input_schema(&self) -> Value {
+        self.input_schema.clone().unwrap_or_else(|| {
+            let mut schema = default_input_schema(&self.name);
+            if self.pages > 1 {
+                schema["properties"]["cursor"] = json!({ "type": "string" });
+            }
+            schema
+        })
+    }
+}
+
+impl Default for MockServerConfig {
+    /// The core Glean tools, always succeeding immediately
+    fn default() -> Self {
+        Self {
+            tools: vec![
+                MockTool::new("search", "Search Glean's content index"),
+                MockTool::new("chat", "Interact with Glean's AI assistant"),
+                MockTool::new("read_document", "Read documents from Glean"),
+            ],
+            required_token: None,
+            tools_page_size: None,
+            notifications: Vec::new(),
+            stream_responses: false,
+            ignore_cancellation: false,
+            max_request_bytes: DEFAULT_MAX_REQUEST_BYTES,
+        }
+    }
+}
+
+impl MockServerConfig {
+    /// Load a mock configuration from a YAML file
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path).map_err(|e| {
+            GleanMcpError::Config(format!("Failed to read {}: {e}", path.display()))
+        })?;
+        serde_yaml::from_str(&contents).map_err(|e| {
+            GleanMcpError::Config(format!("Invalid mock config {}: {e}", path.display()))
+        })
+    }
+}
+
+/// Events sent on streamed responses, for `Last-Event-ID` replay; an event's id is its position plus one
+#[derive(Debug, Default)]
+struct EventLog(Mutex<Vec<(usize, String)>>);
+
+impl EventLog {
+    /// Log the events of a new stream, returning them with their ids
+    fn record(&self, messages: &[Value]) -> Vec<(usize, String)> {
+        let mut log = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        let stream = log.len() + 1;
+        let sent = messages
+            .iter()
+            .map(|message| {
+                log.push((stream, message.to_string()));
+                (log.len(), message.to_string())
+            })
+            .collect();
+        drop(log);
+        sent
+    }
+
+    /// The events of `last_event_id`'s stream that came after it
+    fn after(&self, last_event_id: &str) -> Option<Vec<(usize, String)>> {
+        let last: usize = last_event_id.parse().ok()?;
+        let log = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        let (stream, _) = log.get(last.checked_sub(1)?)?;
+        Some(
+            log.iter()
+                .enumerate()
+                .skip(last)
+                .filter(|(_, (other, _))| other == stream)
+                .map(|(index, (_, data))| (index + 1, data.clone()))
+                .collect(),
+        )
+    }
+}
+
+/// Ids of requests the client cancelled with `notifications/cancelled`
+#[derive(Debug, Default)]
+struct Cancellations(Mutex<HashSet<String>>);
+
+impl Cancellations {
+    fn cancel(&self, id: &Value) {
+        self.0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(id.to_string());
+    }
+
+    /// Whether `id` was cancelled, forgetting the cancellation
+    fn take(&self, id: &Value) -> bool {
+        self.0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(&id.to_string())
+    }
+}
+
+/// `text/event-stream` body of `events`
+fn event_stream(events: &[(usize, String)]) -> String {
+    let mut body = String::new();
+    for (id, data) in events {
+        let _ = write!(body, "id: {id}\ndata: {data}\n\n");
+    }
+    body
+}
+
+/// A running mock server; dropping it stops the server
+pub struct MockServer {
+    addr: SocketAddr,
+    _task: smol::Task<()>,
+}
+
+impl MockServer {
+    /// Bind to `addr` (e.g. `127.0.0.1:0`) and start serving in the background
+    pub async fn start(addr: &str, config: MockServerConfig) -> Result<Self> {
+        let listener = TcpListener::bind(addr)
+            .await
+            .map_err(|e| GleanMcpError::Network(format!("Failed to bind mock server: {e}")))?;
+        let addr = listener.local_addr()?;
+        let config = Arc::new(config);
+        let events = Arc::new(EventLog::default());
+        let cancellations = Arc::new(Cancellations::default());
+
+        let task = smol::spawn(async move {
+            loop {
+                let Ok((stream, _)) = listener.accept().await else {
+                    continue;
+                };
+                let config = config.clone();
+                let events = events.clone();
+                let cancellations = cancellations.clone();
+                smol::spawn(async move {
+                    // Connection-level I/O errors only affect that client
+                    let _ = handle_connection(stream, &config, &events, &cancellations, addr).await;
+                })
+                .detach();
+            }
+        });
+
+        Ok(Self { addr, _task: task })
+    }
+
+    #[must_use]
+    pub const fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Base URL, e.g. `http://127.0.0.1:8765`
+    #[must_use]
+    pub fn base_url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// URL of a named MCP endpoint, e.g. `http://127.0.0.1:8765/mcp/default`
+    #[must_use]
+    pub fn endpoint_url(&self, endpoint: &str) -> String {
+        format!("{}/mcp/{endpoint}", self.base_url())
+    }
+}
+
+async fn handle_connection(
+    mut stream: TcpStream,
+    config: &MockServerConfig,
+    events: &EventLog,
+    cancellations: &Cancellations,
+    addr: SocketAddr,
+) -> Result<()> {
+    let request = match read_request(&mut stream, config.max_request_bytes).await? {
+        Some(Incoming::Request(request)) => request,
+        Some(Incoming::TooLarge) => return write_response(&mut stream, 413, JSON, "").await,
+        None => return write_response(&mut stream, 400, JSON, "").await,
+    };
+
+    if let Some(token) = &config.required_token
+        && request.authorization.as_deref() != Some(format!("Bearer {token}").as_str())
+    {
+        // Point clients at the protected resource metadata, as MCP servers must
+        let mut challenge = format!(
+            "Bearer resource_metadata=\"http://{addr}/.well-known/oauth-protected-resource\""
+        );
+        if request.authorization.is_some() {
+            challenge.push_str(", error=\"invalid_token\"");
+        }
+        return write_response_with_headers(
+            &mut stream,
+            401,
+            JSON,
+            &[("WWW-Authenticate", challenge)],
+            r#"{"error":"Unauthorized"}"#,
+        )
+        .await;
+    }
+
+    if request.method == "GET" {
+        if config.stream_responses
+            && let Some(last_event_id) = &request.last_event_id
+        {
+            let Some(replay) = events.after(last_event_id) else {
+                return write_response(&mut stream, 400, JSON, "").await;
+            };
+            return write_response(&mut stream, 200, EVENT_STREAM, &event_stream(&replay)).await;
+        }
+        if config.notifications.is_empty() {
+            return write_response(&mut stream, 405, JSON, "").await;
+        }
+        let events: String = config
+            .notifications
+            .iter()
+            .map(|message| match message {
+                Value::String(raw) => format!("data: {raw}\n\n"),
+                message => format!("data: {message}\n\n"),
+            })
+            .collect();
+        return write_response(&mut stream, 200, EVENT_STREAM, &events).await;
+    }
+
+    let Ok(message) = serde_json::from_slice::<Value>(&request.body) else {
+        let body = rpc_error(&Value::Null, -32700, "Parse error");
+        return write_response(&mut stream, 200, JSON, &body.to_string()).await;
+    };
+
+    // Notifications carry no id and get no JSON-RPC response
+    let Some(id) = message.get("id").cloned() else {
+        if message.get("method").and_then(Value::as_str) == Some("notifications/cancelled")
+            && let Some(request_id) = message.pointer("/params/requestId")
+        {
+            cancellations.cancel(request_id);
+        }
+        return write_response(&mut stream, 202, JSON, "").await;
+    };
+
+    let method = message.get("method").and_then(Value::as_str).unwrap_or("");
+    let params = message.get("params").cloned().unwrap_or(Value::Null);
+
+    match method {
+        "initialize" => {
+            let result = json!({
+                "protocolVersion": "2025-03-26",
+                "capabilities": { "tools": { "listChanged": !config.notifications.is_empty() } },
+                "serverInfo": { "name": "glean-mcp-mock", "version": env!("CARGO_PKG_VERSION") }
+            });
+            let session_id = format!("mock-{:016x}", rand::thread_rng().r#gen::<u64>());
+            write_response_with_headers(
+                &mut stream,
+                200,
+                JSON,
+                &[("Mcp-Session-Id", session_id)],
+                &rpc_result(&id, &result).to_string(),
+            )
+            .await
+        }
+        "ping" => {
+            write_response(
+                &mut stream,
+                200,
+                JSON,
+                &rpc_result(&id, &json!({})).to_string(),
+            )
+            .await
+        }
+        "tools/list" => {
+            let tools: Vec<Value> = config
+                .tools
+                .iter()
+                .map(|tool| {
+                    json!({
+                        "name": tool.name,
+                        "description": tool.description,
+                        "inputSchema": tool.input_schema()
+                    })
+                })
+                .collect();
+            let Some(page) = page_number(&params) else {
+                let body = rpc_error(&id, -32602, "Invalid params: unknown cursor");
+                return write_response(&mut stream, 200, JSON, &body.to_string()).await;
+            };
+            let size = config
+                .tools_page_size
+                .filter(|size| *size > 0)
+                .unwrap_or(tools.len());
+            let start = (page - 1) * size;
+            let mut result = json!({
+                "tools": tools.iter().skip(start).take(size).collect::<Vec<_>>()
+            });
+            if start + size < tools.len() {
+                result["nextCursor"] = json!(page_cursor(page + 1));
+            }
+            write_response(
+                &mut stream,
+                200,
+                JSON,
+                &rpc_result(&id, &result).to_string(),
+            )
+            .await
+        }
+        "tools/call" => {
+            let name = params.get("name").and_then(Value::as_str).unwrap_or("");
+            let Some(tool) = config.tools.iter().find(|tool| tool.name == name) else {
+                let body = rpc_error(&id, -32602, &format!("Unknown tool: {name}"));
+                return write_response(&mut stream, 200, JSON, &body.to_string()).await;
+            };
+            let schema = tool.input_schema();
+            let arguments = params
+                .get("arguments")
+                .cloned()
+                .unwrap_or_else(|| json!({}));
+            if let Some(problem) = invalid_arguments(&schema, &arguments) {
+                let body = rpc_error(&id, -32602, &format!("Invalid params: {problem}"));
+                return write_response(&mut stream, 200, JSON, &body.to_string()).await;
+            }
+
+            // Like Glean's backend, answer calls with an id support can look the request up by
+            let request_id = (
+                REQUEST_ID_HEADER,
+                format!("mock-{:08x}", rand::thread_rng().gen_range(0..u32::MAX)),
+            );
+            let (delay, fail) = {
+                let mut rng = rand::thread_rng();
+                let jitter = if tool.jitter_ms > 0 {
+                    rng.gen_range(0..=tool.jitter_ms)
+                } else {
+                    0
+                };
+                (
+                    Duration::from_millis(tool.latency_ms + jitter),
+                    tool.error_rate > 0.0 && rng.gen_bool(tool.error_rate.clamp(0.0, 1.0)),
+                )
+            };
+            // A cancellation left over from an earlier call with this id doesn't count
+            cancellations.take(&id);
+            let deadline = Instant::now() + delay;
+            while let Some(remaining) = deadline.checked_duration_since(Instant::now()) {
+                smol::Timer::after(remaining.min(CANCEL_POLL)).await;
+                if !config.ignore_cancellation && cancellations.take(&id) {
+                    // Stop work and hang up without a response, as cancelled requests get none
+                    return Ok(());
+                }
+            }
+
+            if fail {
+                if let Some(status) = tool.error.http_status {
+                    let body =
+                        json!({ "error": { "code": status, "message": tool.error.message } });
+                    let headers: Vec<(&str, String)> = tool
+                        .error
+                        .retry_after_secs
+                        .map(|secs| ("Retry-After", secs.to_string()))
+                        .into_iter()
+                        .chain([request_id])
+                        .collect();
+                    return write_response_with_headers(
+                        &mut stream,
+                        status,
+                        JSON,
+                        &headers,
+                        &body.to_string(),
+                    )
+                    .await;
+                }
+                let body = rpc_error(&id, tool.error.code, &tool.error.message);
+                return write_response_with_headers(
+                    &mut stream,
+                    200,
+                    JSON,
+                    &[request_id],
+                    &body.to_string(),
+                )
+                .await;
+            }
+
+            // Unpaginated tools ignore cursors, like servers that echo them back
+            let page = if tool.pages > 1 {
+                page_number(&arguments)
+            } else {
+                Some(1)
+            };
+            let Some(page) = page else {
+                let body = rpc_error(&id, -32602, "Invalid params: unknown cursor");
+                return write_response(&mut stream, 200, JSON, &body.to_string()).await;
+            };
+            let result = tool.response.clone().unwrap_or_else(|| {
+                let text = if name == "search" {
+                    mock_search_results(&arguments).to_string()
+                } else {
+                    format!("Mock {name} response for {arguments}")
+                };
+                let mut result = json!({
+                    "content": [{
+                        "type": "text",
+                        "text": text
+                    }]
+                });
+                if tool.pages > 1 {
+                    result["content"][0]["text"] = json!(format!(
+                        "Mock {name} page {page} of {} for {arguments}",
+                        tool.pages
+                    ));
+                    result["structuredContent"] = json!({ "page": page });
+                    if page < tool.pages {
+                        result["structuredContent"]["nextCursor"] = json!(page_cursor(page + 1));
+                    }
+                }
+                result
+            });
+            if config.stream_responses && request.accepts_event_stream {
+                let progress = json!({
+                    "jsonrpc": "2.0",
+                    "method": "notifications/progress",
+                    "params": { "progressToken": id, "progress": 1, "total": 2 }
+                });
+                let sent = events.record(&[progress, rpc_result(&id, &result)]);
+                return write_response(&mut stream, 200, EVENT_STREAM, &event_stream(&sent)).await;
+            }
+            write_response_with_headers(
+                &mut stream,
+                200,
+                JSON,
+                &[request_id],
+                &rpc_result(&id, &result).to_string(),
+            )
+            .await
+        }
+        _ => {
+            let body = rpc_error(&id, -32601, &format!("Method not found: {method}"));
+            write_response(&mut stream, 200, JSON, &body.to_string()).await
+        }
+    }
+}
+
+/// Glean-shaped search results, so the default result check passes against the mock
+fn mock_search_results(arguments: &Value) -> Value {
+    let query = arguments
+        .get("query")
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+    let documents: Vec<Value> = (1..=3)
+        .map(|rank| {
+            json!({
+                "title": format!("Mock document {rank} for '{query}'"),
+                "url": format!("https://mock.glean.com/documents/{rank}"),
+                "snippets": [{ "text": format!("Mock snippet {rank} matching '{query}'") }]
+            })
+        })
+        .collect();
+    json!({ "documents": documents })
+}
+
+fn page_cursor(page: usize) -> String {
+    format!("page-{page}")
+}
+
+/// Page requested by the `cursor` in `params`: 1 without one, `None` for a cursor the mock never issued
+fn page_number(params: &Value) -> Option<usize> {
+    params.get("cursor").map_or(Some(1), |cursor| {
+        cursor
+            .as_str()?
+            .strip_prefix("page-")?
+            .parse()
+            .ok()
+            .filter(|page| *page > 1)
+    })
+}
+
+/// Schema with the single required string parameter the real tool takes its query through
+fn default_input_schema(tool_name: &str) -> Value {
+    let arguments = TestQueryGenerator::generate_arguments(tool_name, "");
+    let parameter = arguments
+        .as_object()
+        .and_then(|arguments| arguments.keys().next())
+        .map_or("query", String::as_str);
+    json!({
+        "type": "object",
+        "properties": { parameter: { "type": "string" } },
+        "required": [parameter]
+    })
+}
+
+/// Why `arguments` don't satisfy `schema`'s required properties and top-level types
+fn invalid_arguments(schema: &Value, arguments: &Value) -> Option<String> {
+    let Some(arguments) = arguments.as_object() else {
+        return Some("arguments must be an object".to_string());
+    };
+    let required = schema.get("required").and_then(Value::as_array);
+    if let Some(missing) = required
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str)
+        .find(|field| !arguments.contains_key(*field))
+    {
+        return Some(format!("missing required argument '{missing}'"));
+    }
+
+    let properties = schema.get("properties").and_then(Value::as_object)?;
+    arguments.iter().find_map(|(field, value)| {
+        let expected = properties.get(field)?.get("type")?.as_str()?;
+        let matches = match expected {
+            "string" => value.is_string(),
+            "integer" => value.is_i64() || value.is_u64(),
+            "number" => value.is_number(),
+            "boolean" => value.is_boolean(),
+            "array" => value.is_array(),
+            "object" => value.is_object(),
+            _ => true,
+        };
+        (!matches).then(|| format!("argument '{field}' must be of type {expected}"))
+    })
+}
+
+fn rpc_result(id: &Value, result: &Value) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "result": result })
+}
+
+fn rpc_error(id: &Value, code: i64, message: &str) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GleanMCPInspector, McpTransport, SilentReporter};
+
+    fn inspector_for(server: &MockServer) -> GleanMCPInspector {
+        GleanMCPInspector::with_reporter(Some("mock"), Arc::new(SilentReporter)).with_endpoints(
+            &server.endpoint_url("default"),
+            &server.endpoint_url("chatgpt"),
+        )
+    }
+
+    fn start(config: MockServerConfig) -> MockServer {
+        smol::block_on(MockServer::start("127.0.0.1:0", config)).unwrap()
+    }
+
+    #[test]
+    fn serves_initialize_list_and_call() {
+        let server = start(MockServerConfig::default());
+        smol::block_on(async {
+            let inspector = inspector_for(&server);
+
+            let identity = inspector.server_identity().await.unwrap();
+            assert_eq!(identity.name.as_deref(), Some("glean-mcp-mock"));
+            assert_eq!(identity.protocol_version.as_deref(), Some("2025-03-26"));
+
+            let listed = inspector.list_available_tools(false).await.unwrap();
+            assert!(listed.success);
+            let names: Vec<&str> = listed
+                .inspector_data
+                .as_ref()
+                .and_then(|data| data.pointer("/result/tools"))
+                .and_then(Value::as_array)
+                .unwrap()
+                .iter()
+                .filter_map(|tool| tool.get("name").and_then(Value::as_str))
+                .collect();
+            assert_eq!(names, ["search", "chat", "read_document"]);
+
+            let result = inspector
+                .call_tool("chat", "quarterly planning", Duration::from_secs(10))
+                .await
+                .unwrap();
+            let text = result.pointer("/content/0/text").and_then(Value::as_str);
+            assert!(
+                text.is_some_and(|text| text.contains("quarterly planning")),
+                "unexpected result: {result}"
+            );
+
+            let unknown = inspector
+                .call_tool("missing", "anything", Duration::from_secs(10))
+                .await
+                .unwrap_err();
+            assert!(unknown.to_string().contains("Unknown tool: missing"));
+        });
+    }
+
+    #[test]
+    fn follows_tools_list_pages() {
+        let server = start(MockServerConfig {
+            tools_page_size: Some(1),
+            ..MockServerConfig::default()
+        });
+        let listed = smol::block_on(async {
+            inspector_for(&server)
+                .list_available_tools(false)
+                .await
+                .unwrap()
+        });
+        let tools = listed
+            .inspector_data
+            .as_ref()
+            .and_then(|data| data.pointer("/result/tools"))
+            .and_then(Value::as_array)
+            .map_or(0, Vec::len);
+        assert_eq!(tools, 3);
+    }
+
+    #[test]
+    fn injects_http_failures() {
+        let mut tool = MockTool::new("chat", "Always unavailable");
+        tool.error_rate = 1.0;
+        tool.error.http_status = Some(503);
+        tool.error.retry_after_secs = Some(7);
+        let server = start(MockServerConfig {
+            tools: vec![tool],
+            ..MockServerConfig::default()
+        });
+
+        let error = smol::block_on(async {
+            inspector_for(&server)
+                .call_tool("chat", "hello", Duration::from_secs(10))
+                .await
+                .unwrap_err()
+        });
+        match error {
+            GleanMcpError::Http {
+                status,
+                retry_after,
+                ..
+            } => {
+                assert_eq!(status, 503);
+                assert_eq!(retry_after, Some(Duration::from_secs(7)));
+            }
+            other => panic!("expected an HTTP error, got {other}"),
+        }
+    }
+
+    #[test]
+    fn challenges_requests_without_the_token() {
+        let server = start(MockServerConfig {
+            required_token: Some("secret".to_string()),
+            ..MockServerConfig::default()
+        });
+        let endpoint = server.endpoint_url("default");
+        let ping = json!({ "jsonrpc": "2.0", "id": 1, "method": "ping" });
+
+        smol::block_on(async {
+            let anonymous = McpTransport::new(None)
+                .post_json_rpc(&endpoint, &ping)
+                .await
+                .unwrap();
+            assert_eq!(anonymous.status, Some(401));
+            assert!(
+                anonymous
+                    .header("www-authenticate")
+                    .is_some_and(|challenge| challenge.contains("resource_metadata="))
+            );
+
+            let authorized = McpTransport::new(Some("secret".to_string()))
+                .post_json_rpc(&endpoint, &ping)
+                .await
+                .unwrap();
+            assert_eq!(authorized.status, Some(200));
+        });
+    }
+
+    #[test]
+    fn act_as_reports_keep_users_apart_once_redacted() {
+        let server = start(MockServerConfig::default());
+        let config = crate::ActAsConfig {
+            tools: vec!["search".to_string()],
+            ..crate::ActAsConfig::default()
+        };
+        let users = [
+            "alice@example.com".to_string(),
+            "bob@example.com".to_string(),
+        ];
+        let result = smol::block_on(async {
+            inspector_for(&server)
+                .test_act_as(&config, &users)
+                .await
+                .unwrap()
+        });
+
+        let calls: Vec<&str> = result.comparisons[0]
+            .calls
+            .iter()
+            .map(|call| call.user.as_str())
+            .collect();
+        assert_eq!(calls, ["user-1", "user-2"]);
+        let redactor =
+            crate::Redactor::new(&crate::RedactionConfig::default(), Vec::new()).unwrap();
+        let text = redactor.redact(&result.format_text()).into_owned();
+        assert!(!text.contains("alice@example.com"), "{text}");
+        assert!(
+            text.contains("user-1 = ") && text.contains("user-2 = "),
+            "{text}"
+        );
+    }
+}
@@ -0,0 +1,405 @@
+//! Continuous monitoring with cron-scheduled suites
+//!
+//! Each schedule from [`MonitorConfig`](crate::MonitorConfig) fires on its cron
+//! expression and runs its suite in the background. A schedule never overlaps
+//! itself: if the previous run is still in progress when it fires again, the
+//! new run is skipped. Every finished run is written to the history store
+//! (and the `SQLite` history database, when configured), tagged with the
+//! schedule name, and the configured
+//! [`RetentionConfig`] is applied after each run. Tools that keep failing
+//! are filed as issues through an [`IssueFiler`], when one is configured.
+
+use crate::{
+    DuplicateCheckConfig, FiledIssue, GleanConfig, GleanMCPInspector, GleanMcpError, HistoryStore,
+    IssueFiler, LanguageCheckConfig, PiiScanConfig, ProxyConfig, QueryCorpus, QueryRule, Reporter,
+    ResponseExpectation, ResponseSizeConfig, Result, ResultCheckConfig, RetentionConfig, RunFilter,
+    RunRecord, ScheduleConfig, SqliteHistoryStore, TestAllOptions, TlsConfig, apply_retention,
+    persistent_failures, test_hosts,
+};
+use chrono::{DateTime, Utc};
+use smol::LocalExecutor;
+use std::cell::Cell;
+use std::collections::BTreeMap;
+use std::rc::Rc;
+use std::str::FromStr;
+use std::sync::Arc;
+
+/// Parse a cron expression, accepting the classic 5-field form (no seconds)
+pub fn parse_cron(expression: &str) -> Result<cron::Schedule> {
+    let normalized = if expression.split_whitespace().count() == 5 {
+        format!("0 {expression}")
+    } else {
+        expression.to_string()
+    };
+
+    cron::Schedule::from_str(&normalized)
+        .map_err(|e| GleanMcpError::Config(format!("Invalid cron expression '{expression}': {e}")))
+}
+
+/// A configured schedule with its parsed cron expression
+#[derive(Debug, Clone)]
+pub struct ScheduledSuite {
+    pub config: ScheduleConfig,
+    pub schedule: cron::Schedule,
+}
+
+impl ScheduledSuite {
+    pub fn new(config: ScheduleConfig) -> Result<Self> {
+        let schedule = parse_cron(&config.cron)?;
+        Ok(Self { config, schedule })
+    }
+
+    /// Next fire time strictly after `after`
+    #[must_use]
+    pub fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        self.schedule.after(&after).next()
+    }
+}
+
+/// Runs configured suites on their cron schedules
+pub struct Monitor {
+    suites: Vec<ScheduledSuite>,
+    default_instance: String,
+    tool_max_concurrent: BTreeMap<String, usize>,
+    tool_arguments: BTreeMap<String, serde_json::Value>,
+    tool_tags: BTreeMap<String, Vec<String>>,
+    depends_on: BTreeMap<String, Vec<String>>,
+    query_rules: Vec<QueryRule>,
+    corpus: QueryCorpus,
+    exclude: Vec<String>,
+    latency_budgets_ms: BTreeMap<String, u64>,
+    language_check: LanguageCheckConfig,
+    duplicate_check: DuplicateCheckConfig,
+    result_check: ResultCheckConfig,
+    response_size: ResponseSizeConfig,
+    pii_scan: PiiScanConfig,
+    expectations: BTreeMap<String, ResponseExpectation>,
+    retention: RetentionConfig,
+    proxy: ProxyConfig,
+    tls: TlsConfig,
+    headers: BTreeMap<String, String>,
+    url_template: String,
+    store: HistoryStore,
+    db: Option<SqliteHistoryStore>,
+    issues: Option<IssueFiler>,
+    reporter: Arc<dyn Reporter>,
+}
+
+impl Monitor {
+    /// Build a monitor from the `monitor` section of the configuration
+    pub fn new(config: &GleanConfig, reporter: Arc<dyn Reporter>) -> Result<Self> {
+        if config.monitor.schedules.is_empty() {
+            return Err(GleanMcpError::Config(
+                "No monitor schedules configured (add monitor.schedules to the config file)"
+                    .to_string(),
+            ));
+        }
+
+        let suites = config
+            .monitor
+            .schedules
+            .iter()
+            .cloned()
+            .map(ScheduledSuite::new)
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self {
+            suites,
+            default_instance: config.glean_instance.name.clone(),
+            tool_max_concurrent: config.tools_to_test.max_concurrent.clone(),
+            tool_arguments: config.tool_arguments(),
+            tool_tags: config.tool_tags(),
+            depends_on: config.tools_to_test.depends_on.clone(),
+            query_rules: config.tools_to_test.query_rules.clone(),
+            corpus: config.corpus.load()?,
+            exclude: config.tools_to_test.exclude.clone(),
+            latency_budgets_ms: config.slo.budgets_ms(),
+            language_check: config.language_check.clone(),
+            duplicate_check: config.duplicate_check.clone(),
+            result_check: config.result_check.clone(),
+            response_size: config.response_size.clone(),
+            pii_scan: config.pii_scan.clone(),
+            expectations: config.profile.expectations(),
+            retention: config.retention.clone(),
+            proxy: config.proxy.clone(),
+            tls: config.tls.clone(),
+            headers: config.headers.clone(),
+            url_template: config.glean_instance.url_template.clone(),
+            store: HistoryStore::new(&config.monitor.history_dir),
+            db: config
+                .monitor
+                .history_db
+                .as_ref()
+                .map(SqliteHistoryStore::new),
+            issues: IssueFiler::new(&config.monitor.issues, config.proxy.clone()),
+            reporter,
+        })
+    }
+
+    #[must_use]
+    pub fn suites(&self) -> &[ScheduledSuite] {
+        &self.suites
+    }
+
+    /// Run until interrupted; with `once`, run every schedule a single time and return
+    #[allow(clippy::future_not_send)]
+    pub async fn run(&self, once: bool) -> Result<()> {
+        if once {
+            for suite in &self.suites {
+                self.run_and_record(suite).await;
+            }
+            return Ok(());
+        }
+
+        let executor = LocalExecutor::new();
+        let running: Vec<Rc<Cell<bool>>> = self
+            .suites
+            .iter()
+            .map(|_| Rc::new(Cell::new(false)))
+            .collect();
+
+        executor
+            .run(async {
+                let now = Utc::now();
+                let mut next_runs: Vec<Option<DateTime<Utc>>> =
+                    self.suites.iter().map(|s| s.next_after(now)).collect();
+
+                for (suite, next) in self.suites.iter().zip(&next_runs) {
+                    match next {
+                        Some(at) => self.reporter.info(&format!(
+                            "Schedule '{}' ({}) next run at {}",
+                            suite.config.name,
+                            suite.config.cron,
+                            at.to_rfc3339()
+                        )),
+                        None => self.reporter.warning(&format!(
+                            "Schedule '{}' has no upcoming runs",
+                            suite.config.name
+                        )),
+                    }
+                }
+
+                loop {
+                    let Some(earliest) = next_runs.iter().flatten().min().copied() else {
+                        return Ok(());
+                    };
+
+                    let wait = (earliest - Utc::now()).to_std().unwrap_or_default();
+                    smol::Timer::after(wait).await;
+
+                    let now = Utc::now();
+                    for (index, suite) in self.suites.iter().enumerate() {
+                        if next_runs[index].is_none_or(|at| at > now) {
+                            continue;
+                        }
+                        next_runs[index] = suite.next_after(now);
+
+                        // Overlapping-run protection
+                        if running[index].get() {
+                            self.reporter.warning(&format!(
+                                "Skipping schedule '{}': previous run still in progress",
+                                suite.config.name
+                            ));
+                            continue;
+                        }
+
+                        let flag = running[index].clone();
+                        flag.set(true);
+                        executor
+                            .spawn(async move {
+                                self.run_and_record(suite).await;
+                                flag.set(false);
+                            })
+                            .detach();
+                    }
+                }
+            })
+            .await
+    }
+
+    /// Run one scheduled suite and persist the outcome to history
+    #[allow(clippy::future_not_send)]
+    async fn run_and_record(&self, suite: &ScheduledSuite) {
+        let record = self.run_suite(suite).await;
+
+        let status = if record.success {
+            "✅ passed"
+        } else {
+            "❌ failed"
+        };
+        match self.store.record(&record) {
+            Ok(path) => self.reporter.info(&format!(
+                "Schedule '{}' run {} {status} (recorded to {})",
+                suite.config.name,
+                record.id,
+                path.display()
+            )),
+            Err(e) => self.reporter.warning(&format!(
+                "Schedule '{}' run {} {status} but could not be recorded: {e}",
+                suite.config.name, record.id
+            )),
+        }
+        if let Some(db) = &self.db
+            && let Err(e) = db.record(&record)
+        {
+            self.reporter.warning(&format!(
+                "Schedule '{}' run {} could not be recorded to {}: {e}",
+                suite.config.name,
+                record.id,
+                db.path().display()
+            ));
+        }
+
+        if self.retention.is_enabled() {
+            match apply_retention(&self.retention, self.store.dir(), self.db.as_ref(), false) {
+                Ok(report) if report.removed_count() > 0 => self.reporter.info(&format!(
+                    "Retention removed {} old entr{}",
+                    report.removed_count(),
+                    if report.removed_count() == 1 {
+                        "y"
+                    } else {
+                        "ies"
+                    }
+                )),
+                Ok(_) => {}
+                Err(e) => self
+                    .reporter
+                    .warning(&format!("Could not apply retention policy: {e}")),
+            }
+        }
+
+        self.file_issues(suite, &record).await;
+    }
+
+    /// File an issue for each tool failing the same way in the schedule's latest runs
+    #[allow(clippy::future_not_send)]
+    async fn file_issues(&self, suite: &ScheduledSuite, record: &RunRecord) {
+        let Some(filer) = &self.issues else {
+            return;
+        };
+        let schedule = RunFilter {
+            schedule: Some(suite.config.name.clone()),
+            ..RunFilter::default()
+        };
+        let mut runs = match self.store.query(&schedule) {
+            Ok(runs) => runs,
+            Err(e) => {
+                self.reporter.warning(&format!(
+                    "Could not read the history of schedule '{}' to file issues: {e}",
+                    suite.config.name
+                ));
+                return;
+            }
+        };
+        if runs.last().is_none_or(|last| last.id != record.id) {
+            runs.push(record.clone());
+        }
+        for failure in persistent_failures(&runs, filer.after_failures()) {
+            match filer.file(&failure, record).await {
+                Ok(FiledIssue::Opened(issue)) => self.reporter.info(&format!(
+                    "Opened {issue} for {} ({} consecutive failures)",
+                    failure.tool,
+                    failure.runs.len()
+                )),
+                Ok(FiledIssue::Updated(issue)) => self.reporter.info(&format!(
+                    "Added run {} to {issue} for {}",
+                    record.id, failure.tool
+                )),
+                Err(e) => self.reporter.warning(&format!(
+                    "Could not file an issue for {}: {e}",
+                    failure.tool
+                )),
+            }
+        }
+    }
+
+    /// The corpus, rotated past the runs already recorded for `instance`
+    fn corpus_for(&self, instance: &str) -> QueryCorpus {
+        let filter = RunFilter {
+            instance: Some(instance.to_string()),
+            ..RunFilter::default()
+        };
+        QueryCorpus {
+            offset: self.store.query(&filter).map_or(0, |runs| runs.len()),
+            ..self.corpus.clone()
+        }
+    }
+
+    /// Execute a suite and build its history record
+    #[allow(clippy::future_not_send)]
+    pub async fn run_suite(&self, suite: &ScheduledSuite) -> RunRecord {
+        let config = &suite.config;
+        let instance = config
+            .instance
+            .clone()
+            .unwrap_or_else(|| self.default_instance.clone());
+        let started_at = Utc::now().to_rfc3339();
+
+        let mut record = RunRecord {
+            id: RunRecord::generate_id(),
+            started_at,
+            finished_at: String::new(),
+            instance: instance.clone(),
+            suite: config.suite.clone(),
+            schedule: Some(config.name.clone()),
+            success: false,
+            tool_result: None,
+            host_results: Vec::new(),
+            labels: config.labels.clone(),
+        };
+
+        if config.suite == "hosts" {
+            let hosts = if config.hosts.is_empty() {
+                vec!["claude-code".to_string()]
+            } else {
+                config.hosts.clone()
+            };
+
+            let result = test_hosts(&hosts, config.parallel, self.reporter.as_ref()).await;
+            record.success = result.success;
+            record.host_results = result.results;
+        } else {
+            let options = TestAllOptions {
+                tools_filter: config.suite.clone(),
+                parallel: config.parallel,
+                labels: config.labels.clone(),
+                tool_max_concurrent: self.tool_max_concurrent.clone(),
+                tool_arguments: self.tool_arguments.clone(),
+                tool_tags: self.tool_tags.clone(),
+                depends_on: self.depends_on.clone(),
+                query_rules: self.query_rules.clone(),
+                corpus: self.corpus_for(&instance),
+                tags: config.tags.clone(),
+                exclude: [self.exclude.as_slice(), config.exclude.as_slice()].concat(),
+                latency_budgets_ms: self.latency_budgets_ms.clone(),
+                language_check: self.language_check.clone(),
+                duplicate_check: self.duplicate_check.clone(),
+                result_check: self.result_check.clone(),
+                response_size: self.response_size.clone(),
+                pii_scan: self.pii_scan.clone(),
+                expectations: self.expectations.clone(),
+                ..TestAllOptions::default()
+            };
+            let inspector =
+                GleanMCPInspector::with_reporter(Some(&instance), self.reporter.clone())
+                    .with_proxy(self.proxy.clone())
+                    .with_tls(self.tls.clone())
+                    .with_headers(self.headers.clone())
+                    .with_url_template(&self.url_template)
+                    // Tag requests with the history id, so filed issues' run links match the server's logs
+                    .with_run_id(&record.id);
+            match inspector.test_all_tools(&options).await {
+                Ok(result) => {
+                    record.success = result.success;
+                    record.tool_result = Some(result);
+                }
+                Err(e) => self
+                    .reporter
+                    .warning(&format!("Schedule '{}' failed to run: {e}", config.name)),
+            }
+        }
+
+        record.finished_at = Utc::now().to_rfc3339();
+        record
+    }
+}
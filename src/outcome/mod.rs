@@ -0,0 +1,312 @@
+//! Process exit codes for CI
+//!
+//! A run's [`RunOutcome`] maps to a distinct exit code so pipelines can tell
+//! an expired token from an outage from a genuine tool regression. Only
+//! failures of tools inside the [`FailOn`] scope count; failures elsewhere
+//! are reported but don't fail the build. [`SuccessCriteria`] relaxes this
+//! per category: a category meeting its `--require` threshold (e.g.
+//! `enterprise=80%`) tolerates its failures, while one missing it always fails.
+
+use crate::{
+    AllToolsTestResult, ErrorClass, GleanMcpError, Result, RunComparison, SkipReason,
+    TestQueryGenerator, base_tool_name,
+};
+use serde::{Deserialize, Serialize};
+
+/// How a run ended, from CI's point of view
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunOutcome {
+    /// Every gated tool passed
+    Success,
+    /// The command could not run (bad arguments, config, I/O)
+    Error,
+    /// Every gated tool passed, but some exceeded their latency budget
+    Degraded,
+    /// Some gated tools failed
+    PartialFailure,
+    /// A gated tool was rejected with 401/403
+    AuthFailure,
+    /// No gated tool could reach the server
+    ConnectivityFailure,
+    /// Gated tools responded, but their responses failed content checks
+    ValidationFailure,
+    /// A gated tool that passed in the baseline fails now
+    Regression,
+}
+
+impl RunOutcome {
+    /// Exit code for this outcome; `Degraded` uses `slo.degraded_exit_code` instead
+    #[must_use]
+    pub const fn exit_code(self) -> i32 {
+        match self {
+            Self::Success => 0,
+            Self::Error => 1,
+            Self::Degraded => 2,
+            Self::PartialFailure => 3,
+            Self::AuthFailure => 4,
+            Self::ConnectivityFailure => 5,
+            Self::ValidationFailure => 6,
+            Self::Regression => 7,
+        }
+    }
+
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Success => "success",
+            Self::Error => "error",
+            Self::Degraded => "degraded",
+            Self::PartialFailure => "partial failure",
+            Self::AuthFailure => "authentication failure",
+            Self::ConnectivityFailure => "connectivity failure",
+            Self::ValidationFailure => "validation failure",
+            Self::Regression => "regression",
+        }
+    }
+
+    /// Outcome of a command that failed with `error` before producing results
+    #[must_use]
+    pub fn for_error(error: &GleanMcpError) -> Self {
+        if matches!(error, GleanMcpError::Io(_)) {
+            return Self::Error;
+        }
+        match ErrorClass::classify(error) {
+            ErrorClass::Auth => Self::AuthFailure,
+            class if class.is_connectivity() => Self::ConnectivityFailure,
+            _ => Self::Error,
+        }
+    }
+
+    /// Outcome of a completed test run, counting only tools gated by `fail_on` and `criteria`
+    ///
+    /// `comparison` against a baseline, when available, turns failures of
+    /// previously passing tools into a [`RunOutcome::Regression`].
+    #[must_use]
+    pub fn for_result(
+        result: &AllToolsTestResult,
+        fail_on: &FailOn,
+        criteria: &SuccessCriteria,
+        comparison: Option<&RunComparison>,
+    ) -> Self {
+        if result.tested_tools() == 0 {
+            // Nothing ran because everything passed recently (`--skip-recently-passed`)
+            return if result.success && result.skipped_for(SkipReason::CachedPass) > 0 {
+                Self::Success
+            } else {
+                Self::Error
+            };
+        }
+
+        let gated: Vec<_> = result
+            .tool_results
+            .values()
+            .filter(|tool| !tool.is_skipped() && criteria.gates(result, fail_on, &tool.tool_name))
+            .collect();
+        let failed: Vec<_> = gated.iter().filter(|tool| !tool.success).collect();
+
+        if failed.is_empty() {
+            return if gated.iter().any(|tool| tool.degraded) {
+                Self::Degraded
+            } else {
+                Self::Success
+            };
+        }
+
+        let classes: Vec<Option<ErrorClass>> = failed.iter().map(|tool| tool.error_class).collect();
+        if classes.contains(&Some(ErrorClass::Auth)) {
+            return Self::AuthFailure;
+        }
+        if failed.len() == gated.len()
+            && classes
+                .iter()
+                .all(|class| class.is_some_and(ErrorClass::is_connectivity))
+        {
+            return Self::ConnectivityFailure;
+        }
+        if comparison.is_some_and(|comparison| {
+            comparison
+                .regressions()
+                .any(|tool| criteria.gates(result, fail_on, &tool.tool_name))
+        }) {
+            return Self::Regression;
+        }
+        if classes
+            .iter()
+            .all(|class| *class == Some(ErrorClass::Validation))
+        {
+            return Self::ValidationFailure;
+        }
+        Self::PartialFailure
+    }
+}
+
+/// Tools whose failures fail the run
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum FailOn {
+    #[default]
+    All,
+    Core,
+    Enterprise,
+    Tools(Vec<String>),
+}
+
+impl FailOn {
+    /// Whether `tool_name` (optionally suffixed with its endpoint, e.g. `search (default)`) is gated
+    #[must_use]
+    pub fn covers(&self, tool_name: &str) -> bool {
+        let name = base_tool_name(tool_name);
+        match self {
+            Self::All => true,
+            Self::Core => TestQueryGenerator::get_tool_category(name) == "core",
+            Self::Enterprise => TestQueryGenerator::get_tool_category(name) == "enterprise",
+            Self::Tools(tools) => tools.iter().any(|tool| tool == name),
+        }
+    }
+
+    /// Named tools the server didn't advertise in `result`, e.g. a misspelled scope like `cor`
+    #[must_use]
+    pub fn unknown_tools<'a>(&'a self, result: &AllToolsTestResult) -> Vec<&'a str> {
+        let Self::Tools(tools) = self else {
+            return Vec::new();
+        };
+        tools
+            .iter()
+            .map(String::as_str)
+            .filter(|tool| {
+                !result.tool_results.values().any(|advertised| {
+                    advertised.skipped != Some(SkipReason::NotAdvertised)
+                        && base_tool_name(&advertised.tool_name) == *tool
+                })
+            })
+            .collect()
+    }
+}
+
+/// Parse a `--fail-on` scope: `all`, `core`, `enterprise`, or a comma-separated tool list
+pub fn parse_fail_on(raw: &str) -> Result<FailOn> {
+    match raw.trim() {
+        "all" => Ok(FailOn::All),
+        "core" => Ok(FailOn::Core),
+        "enterprise" => Ok(FailOn::Enterprise),
+        tools => {
+            let tools: Vec<String> = tools
+                .split(',')
+                .map(str::trim)
+                .filter(|tool| !tool.is_empty())
+                .map(str::to_string)
+                .collect();
+            if tools.is_empty() {
+                return Err(GleanMcpError::Config(format!(
+                    "Invalid --fail-on scope '{raw}' (expected all, core, enterprise, or tool names)"
+                )));
+            }
+            Ok(FailOn::Tools(tools))
+        }
+    }
+}
+
+/// Minimum success rate for one tool category
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CategoryRequirement {
+    pub category: String,
+    /// Required share of passing tools, as a percentage
+    pub min_success_rate: f64,
+}
+
+/// Per-category pass thresholds from `--require`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SuccessCriteria {
+    pub requirements: Vec<CategoryRequirement>,
+}
+
+impl SuccessCriteria {
+    /// The requirement for `category`, if one was given
+    #[must_use]
+    pub fn requirement(&self, category: &str) -> Option<&CategoryRequirement> {
+        self.requirements
+            .iter()
+            .find(|requirement| requirement.category == category)
+    }
+
+    /// Requirements `result` falls short of, with the rate each category achieved
+    ///
+    /// A category with no tested tools meets any requirement.
+    #[must_use]
+    pub fn unmet<'a>(&'a self, result: &AllToolsTestResult) -> Vec<(&'a CategoryRequirement, f64)> {
+        self.requirements
+            .iter()
+            .filter_map(|requirement| {
+                let rate = result
+                    .categories
+                    .get(&requirement.category)
+                    .map_or(100.0, crate::CategoryRollup::success_rate);
+                (rate < requirement.min_success_rate).then_some((requirement, rate))
+            })
+            .collect()
+    }
+
+    /// Whether a failure of `tool_name` fails the run
+    ///
+    /// Categories with a requirement are gated only while it is unmet;
+    /// other tools are gated by `fail_on`.
+    #[must_use]
+    pub fn gates(&self, result: &AllToolsTestResult, fail_on: &FailOn, tool_name: &str) -> bool {
+        let category = TestQueryGenerator::get_tool_category(base_tool_name(tool_name));
+        if self.requirement(category).is_none() {
+            return fail_on.covers(tool_name);
+        }
+        self.unmet(result)
+            .iter()
+            .any(|(requirement, _)| requirement.category == category)
+    }
+}
+
+/// Parse `--require` thresholds, e.g. `core=100%,enterprise=80%`
+pub fn parse_requirements(raw: &str) -> Result<SuccessCriteria> {
+    let invalid = |detail: &str| {
+        GleanMcpError::Config(format!(
+            "Invalid --require '{raw}': {detail} (expected e.g. core=100%,enterprise=80%)"
+        ))
+    };
+
+    let mut requirements: Vec<CategoryRequirement> = Vec::new();
+    for part in raw
+        .split(',')
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+    {
+        let (category, rate) = part
+            .split_once('=')
+            .ok_or_else(|| invalid(&format!("'{part}' is not CATEGORY=PERCENT")))?;
+        let category = category.trim();
+        if !matches!(category, "core" | "enterprise" | "agents" | "unknown") {
+            return Err(invalid(&format!(
+                "unknown category '{category}'; categories are core, enterprise, agents, unknown"
+            )));
+        }
+        let min_success_rate: f64 = rate
+            .trim()
+            .trim_end_matches('%')
+            .parse()
+            .map_err(|_| invalid(&format!("'{rate}' is not a percentage")))?;
+        if !(0.0..=100.0).contains(&min_success_rate) {
+            return Err(invalid(&format!("'{rate}' is not between 0% and 100%")));
+        }
+        if requirements
+            .iter()
+            .any(|requirement| requirement.category == category)
+        {
+            return Err(invalid(&format!("'{category}' is given more than once")));
+        }
+        requirements.push(CategoryRequirement {
+            category: category.to_string(),
+            min_success_rate,
+        });
+    }
+
+    if requirements.is_empty() {
+        return Err(invalid("no thresholds given"));
+    }
+    Ok(SuccessCriteria { requirements })
+}
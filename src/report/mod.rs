@@ -0,0 +1,178 @@
+//! Local report server for recorded run history
+//!
+//! [`ReportServer`] serves the history directory over HTTP: an HTML run list
+//! at `/`, per-run pages at `/runs/<id>`, and the raw records as JSON under
+//! `/api/runs` and `/api/baselines`. Records are re-read on every request, so
+//! runs recorded while the server is up show up immediately.
+
+use crate::utils::http_server::{Incoming, MAX_REQUEST_BYTES, read_request, write_response};
+use crate::{BaselineStore, GleanMcpError, HistoryStore, Result, RunRecord};
+use smol::net::{SocketAddr, TcpListener, TcpStream};
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+const HTML: &str = "text/html; charset=utf-8";
+const JSON: &str = "application/json";
+
+/// A running report server; dropping it stops the server
+pub struct ReportServer {
+    addr: SocketAddr,
+    _task: smol::Task<()>,
+}
+
+impl ReportServer {
+    /// Bind to `addr` and serve the history stored in `history_dir`
+    pub async fn start(addr: &str, history_dir: impl Into<PathBuf>) -> Result<Self> {
+        let listener = TcpListener::bind(addr)
+            .await
+            .map_err(|e| GleanMcpError::Network(format!("Failed to bind report server: {e}")))?;
+        let addr = listener.local_addr()?;
+        let history_dir = Arc::new(history_dir.into());
+
+        let task = smol::spawn(async move {
+            loop {
+                let Ok((stream, _)) = listener.accept().await else {
+                    continue;
+                };
+                let history_dir = history_dir.clone();
+                smol::spawn(async move {
+                    let _ = handle_connection(stream, &history_dir).await;
+                })
+                .detach();
+            }
+        });
+
+        Ok(Self { addr, _task: task })
+    }
+
+    #[must_use]
+    pub const fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    #[must_use]
+    pub fn base_url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+}
+
+async fn handle_connection(mut stream: TcpStream, history_dir: &Path) -> Result<()> {
+    let request = match read_request(&mut stream, MAX_REQUEST_BYTES).await? {
+        Some(Incoming::Request(request)) => request,
+        Some(Incoming::TooLarge) => return write_response(&mut stream, 413, HTML, "").await,
+        None => return write_response(&mut stream, 400, HTML, "").await,
+    };
+    if request.method != "GET" {
+        return write_response(&mut stream, 405, HTML, "").await;
+    }
+
+    let (status, content_type, body) = route(&request.path, history_dir);
+    write_response(&mut stream, status, content_type, &body).await
+}
+
+fn route(path: &str, history_dir: &Path) -> (u16, &'static str, String) {
+    let store = HistoryStore::new(history_dir);
+    let path = path.split('?').next().unwrap_or(path);
+
+    let response = match path {
+        "/" => store
+            .load_all()
+            .map(|runs| (200, HTML, render_index(&runs))),
+        "/api/runs" => store
+            .load_all()
+            .and_then(|runs| Ok((200, JSON, serde_json::to_string_pretty(&runs)?))),
+        "/api/baselines" => BaselineStore::new(history_dir)
+            .load()
+            .and_then(|entries| Ok((200, JSON, serde_json::to_string_pretty(&entries)?))),
+        #[allow(clippy::option_if_let_else)]
+        _ => {
+            if let Some(id) = path.strip_prefix("/api/runs/") {
+                store
+                    .load(id)
+                    .and_then(|run| Ok((200, JSON, serde_json::to_string_pretty(&run)?)))
+            } else if let Some(id) = path.strip_prefix("/runs/") {
+                store.load(id).map(|run| (200, HTML, render_run(&run)))
+            } else {
+                Ok((404, HTML, page("Not found", "<p>Not found</p>")))
+            }
+        }
+    };
+
+    response.unwrap_or_else(|e| (404, HTML, page("Not found", &escape(&e.to_string()))))
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn page(title: &str, content: &str) -> String {
+    format!(
+        "<!DOCTYPE html><html><head><meta charset=\"utf-8\"><title>{}</title>\
+         <style>body{{font-family:sans-serif;margin:2em}}td,th{{padding:4px 12px;text-align:left}}\
+         .pass{{color:#1a7f37}}.fail{{color:#cf222e}}pre{{background:#f6f8fa;padding:1em}}</style>\
+         </head><body><h1>{}</h1>{content}</body></html>",
+        escape(title),
+        escape(title)
+    )
+}
+
+fn render_index(runs: &[RunRecord]) -> String {
+    let mut rows = String::new();
+    for run in runs.iter().rev() {
+        let (class, status) = if run.success {
+            ("pass", "passed")
+        } else {
+            ("fail", "failed")
+        };
+        let tools = run.tool_result.as_ref().map_or_else(String::new, |result| {
+            format!("{}/{}", result.successful_tools, result.tested_tools())
+        });
+        let labels: Vec<String> = run.labels.iter().map(|(k, v)| format!("{k}={v}")).collect();
+        let _ = write!(
+            rows,
+            "<tr><td><a href=\"/runs/{id}\">{id}</a></td><td class=\"{class}\">{status}</td>\
+             <td>{}</td><td>{}</td><td>{tools}</td><td>{}</td><td>{}</td></tr>",
+            escape(&run.instance),
+            escape(&run.suite),
+            escape(run.schedule.as_deref().unwrap_or("")),
+            escape(&labels.join(", ")),
+            id = escape(&run.id),
+        );
+    }
+
+    page(
+        "Glean MCP Test Runs",
+        &format!(
+            "<table><tr><th>Run</th><th>Status</th><th>Instance</th><th>Suite</th>\
+             <th>Tools</th><th>Schedule</th><th>Labels</th></tr>{rows}</table>"
+        ),
+    )
+}
+
+fn render_run(run: &RunRecord) -> String {
+    let mut content = format!(
+        "<p><a href=\"/\">&larr; All runs</a> &middot; <a href=\"/api/runs/{id}\">JSON</a></p>",
+        id = escape(&run.id)
+    );
+
+    if let Some(result) = &run.tool_result {
+        let _ = write!(
+            content,
+            "<pre>{}</pre>",
+            escape(&result.format_output("text", true, false))
+        );
+    }
+    for host_result in &run.host_results {
+        let _ = write!(
+            content,
+            "<pre>{}</pre>",
+            escape(&serde_json::to_string_pretty(host_result).unwrap_or_default())
+        );
+    }
+
+    page(&format!("Run {}", run.id), &content)
+}
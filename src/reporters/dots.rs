@@ -0,0 +1,77 @@
+//! Compact reporter printing one character per finished test
+
+use super::Reporter;
+use crate::{AllToolsTestResult, ToolTestResult, write_line, write_str};
+use console::Term;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Reporter streaming `.` for a pass, `F` for a failure, `D` for a pass over
+/// its latency budget, and `s` for a skipped tool, one line per endpoint
+#[derive(Debug, Default)]
+pub struct DotsReporter {
+    /// A line of dots has been started and not yet ended
+    line_open: AtomicBool,
+}
+
+impl DotsReporter {
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            line_open: AtomicBool::new(false),
+        }
+    }
+
+    fn write_char(&self, mark: &str) {
+        let term = Term::stdout();
+        write_str(&term, mark);
+        let _ = term.flush();
+        self.line_open.store(true, Ordering::Relaxed);
+    }
+
+    /// Write `line` on a line of its own, ending any line of dots first
+    fn write_line(&self, line: &str) {
+        let term = Term::stdout();
+        if self.line_open.swap(false, Ordering::Relaxed) {
+            write_line(&term, "");
+        }
+        write_line(&term, line);
+    }
+}
+
+impl Reporter for DotsReporter {
+    fn warning(&self, message: &str) {
+        self.write_line(&format!("warning: {message}"));
+    }
+
+    fn discovery_started(&self, endpoint: &str) {
+        // `.../mcp/default` is shown as `default`
+        let name = endpoint.trim_end_matches('/').rsplit('/').next();
+        self.write_char(&format!("{} ", name.unwrap_or(endpoint)));
+    }
+
+    fn tool_finished(&self, result: &ToolTestResult) {
+        let mark = if result.is_skipped() {
+            "s"
+        } else if result.degraded {
+            "D"
+        } else if result.success {
+            "."
+        } else {
+            "F"
+        };
+        self.write_char(mark);
+    }
+
+    fn tests_finished(&self, result: &AllToolsTestResult) {
+        let term = Term::stdout();
+        write_line(
+            &term,
+            &format!(
+                " {}/{} passed",
+                result.successful_tools,
+                result.tested_tools()
+            ),
+        );
+        self.line_open.store(false, Ordering::Relaxed);
+    }
+}
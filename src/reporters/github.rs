@@ -0,0 +1,213 @@
+//! GitHub Actions annotations and job step summary
+//!
+//! [`GithubActions`] turns a finished run into `::error::`/`::warning::`
+//! workflow commands, which GitHub shows on the PR checks page, and a
+//! Markdown table appended to the file named by `$GITHUB_STEP_SUMMARY`.
+
+use crate::{AllToolsTestResult, Result, RunOutcome, SkipReason, ToolTestResult, redact};
+use std::fmt::Write;
+use std::io::Write as _;
+
+/// Longest error message shown in a summary table cell
+const MAX_SUMMARY_DETAIL: usize = 200;
+
+/// GitHub Actions output for one `test` run
+#[derive(Debug, Clone)]
+pub struct GithubActions<'a> {
+    result: &'a AllToolsTestResult,
+    outcome: RunOutcome,
+    instance: &'a str,
+    suite: &'a str,
+}
+
+impl<'a> GithubActions<'a> {
+    #[must_use]
+    pub const fn new(
+        result: &'a AllToolsTestResult,
+        outcome: RunOutcome,
+        instance: &'a str,
+        suite: &'a str,
+    ) -> Self {
+        Self {
+            result,
+            outcome,
+            instance,
+            suite,
+        }
+    }
+
+    /// Workflow commands for failed and degraded tools, sorted by tool name
+    ///
+    /// Failures `gates` says count against the run are errors; other
+    /// failures and latency budget overruns are warnings.
+    #[must_use]
+    pub fn annotations(&self, gates: impl Fn(&ToolTestResult) -> bool) -> Vec<String> {
+        sorted_tools(self.result)
+            .into_iter()
+            .filter_map(|tool| {
+                if tool.is_failure() {
+                    let level = if gates(tool) { "error" } else { "warning" };
+                    Some(format!(
+                        "::{level} title={}::{}",
+                        escape_property(&format!("{} failed", tool.tool_name)),
+                        escape_data(&failure_detail(tool))
+                    ))
+                } else if tool.degraded {
+                    Some(format!(
+                        "::warning title={}::{}",
+                        escape_property(&format!("{} exceeded its latency budget", tool.tool_name)),
+                        escape_data(&degraded_detail(tool))
+                    ))
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// Markdown summary of the run: a headline and one table row per tool
+    #[must_use]
+    pub fn step_summary(&self) -> String {
+        let result = self.result;
+        let icon = match self.outcome {
+            RunOutcome::Success => "✅",
+            RunOutcome::Degraded => "⚠️",
+            _ => "❌",
+        };
+        let mut output = String::new();
+        let _ = writeln!(
+            output,
+            "## {icon} Glean MCP tool tests: {}\n",
+            self.outcome.as_str()
+        );
+        #[allow(clippy::cast_precision_loss)]
+        let seconds = result.execution_summary.total_duration_ms as f64 / 1000.0;
+        let _ = writeln!(
+            output,
+            "**Instance:** `{}` · **Suite:** `{}` · **Passed:** {}/{} · **Duration:** {seconds:.1}s\n",
+            self.instance,
+            self.suite,
+            result.successful_tools,
+            result.tested_tools()
+        );
+        if let Some(run_id) = &result.run_id {
+            let _ = writeln!(output, "**Run ID:** `{run_id}`\n");
+        }
+        if let Some(server) = &result.server {
+            let _ = writeln!(output, "**Server:** {}\n", escape_cell(&server.describe()));
+        }
+        if let Some(error) = &result.error {
+            let _ = writeln!(output, "> {}\n", escape_cell(error));
+        }
+        if result.tool_results.is_empty() {
+            return output;
+        }
+
+        let _ = writeln!(output, "| Tool | Status | Time (ms) | Details |");
+        let _ = writeln!(output, "|------|--------|----------:|---------|");
+        for tool in sorted_tools(result) {
+            let (status, details) = if let Some(reason) = tool.skipped {
+                if reason == SkipReason::FilteredOut {
+                    continue;
+                }
+                let details = tool.validation_details.as_deref().map_or_else(
+                    || reason.as_str().to_string(),
+                    |detail| format!("{}: {detail}", reason.as_str()),
+                );
+                ("⏭️ Skipped", details)
+            } else if !tool.success {
+                ("❌ Failed", tool.error_message.clone().unwrap_or_default())
+            } else if tool.degraded {
+                ("⚠️ Degraded", degraded_detail(tool))
+            } else {
+                ("✅ Passed", String::new())
+            };
+            let _ = writeln!(
+                output,
+                "| `{}` | {status} | {} | {} |",
+                tool.tool_name,
+                tool.response_time_ms,
+                escape_cell(&truncate(&details, MAX_SUMMARY_DETAIL))
+            );
+        }
+        output
+    }
+
+    /// Print annotations to stdout, or stderr when stdout carries JSON, and
+    /// append the step summary when `$GITHUB_STEP_SUMMARY` is set
+    pub fn emit(&self, gates: impl Fn(&ToolTestResult) -> bool, json_output: bool) -> Result<()> {
+        for annotation in self.annotations(gates) {
+            let annotation = redact(&annotation);
+            if json_output {
+                eprintln!("{annotation}");
+            } else {
+                println!("{annotation}");
+            }
+        }
+
+        if let Some(path) = std::env::var_os("GITHUB_STEP_SUMMARY").filter(|p| !p.is_empty()) {
+            let mut file = std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)?;
+            writeln!(file, "{}", redact(&self.step_summary()))?;
+        }
+        Ok(())
+    }
+}
+
+fn sorted_tools(result: &AllToolsTestResult) -> Vec<&ToolTestResult> {
+    let mut tools: Vec<_> = result.tool_results.values().collect();
+    tools.sort_by(|a, b| a.tool_name.cmp(&b.tool_name));
+    tools
+}
+
+/// The tool's error, with the ids to look its request up by
+fn failure_detail(tool: &ToolTestResult) -> String {
+    let error = tool.error_message.as_deref().unwrap_or("Tool test failed");
+    tool.request_reference().map_or_else(
+        || error.to_string(),
+        |reference| format!("{error} ({reference})"),
+    )
+}
+
+fn degraded_detail(tool: &ToolTestResult) -> String {
+    tool.latency_budget_ms.map_or_else(
+        || format!("Took {} ms", tool.response_time_ms),
+        |budget| {
+            format!(
+                "Took {} ms, over its {budget} ms budget",
+                tool.response_time_ms
+            )
+        },
+    )
+}
+
+fn truncate(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        text.to_string()
+    } else {
+        format!("{}…", text.chars().take(max_chars).collect::<String>())
+    }
+}
+
+/// Escape a workflow command message
+fn escape_data(value: &str) -> String {
+    value
+        .replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+/// Escape a workflow command property value
+fn escape_property(value: &str) -> String {
+    escape_data(value).replace(':', "%3A").replace(',', "%2C")
+}
+
+/// Keep text on one line and out of the table's column separators
+fn escape_cell(value: &str) -> String {
+    value
+        .replace('|', "\\|")
+        .replace("\r\n", " ")
+        .replace(['\r', '\n'], " ")
+}
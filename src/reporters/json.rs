@@ -0,0 +1,123 @@
+//! Reporter that emits newline-delimited JSON events
+//!
+//! Events are written to stderr so stdout stays reserved for the final
+//! result document produced by the caller.
+
+use super::Reporter;
+use crate::{AllToolsTestResult, HostOperationResult, ToolTestResult, redact_value};
+use serde_json::{Value, json};
+use std::io::Write;
+
+/// Reporter writing one JSON object per event to stderr
+#[derive(Debug, Clone, Copy, Default)]
+pub struct JsonReporter;
+
+impl JsonReporter {
+    #[must_use]
+    pub const fn new() -> Self {
+        Self
+    }
+
+    fn emit(event: &str, mut payload: Value) {
+        if let Value::Object(ref mut map) = payload {
+            map.insert("event".to_string(), Value::String(event.to_string()));
+            map.insert(
+                "timestamp".to_string(),
+                Value::String(chrono::Utc::now().to_rfc3339()),
+            );
+        }
+        redact_value(&mut payload);
+        let mut stderr = std::io::stderr().lock();
+        let _ = writeln!(stderr, "{payload}");
+    }
+}
+
+impl Reporter for JsonReporter {
+    fn info(&self, message: &str) {
+        Self::emit("info", json!({ "message": message }));
+    }
+
+    fn warning(&self, message: &str) {
+        Self::emit("warning", json!({ "message": message }));
+    }
+
+    fn discovery_started(&self, endpoint: &str) {
+        Self::emit("discovery_started", json!({ "endpoint": endpoint }));
+    }
+
+    fn discovery_finished(&self, endpoint: &str, tool_count: usize) {
+        Self::emit(
+            "discovery_finished",
+            json!({ "endpoint": endpoint, "tool_count": tool_count }),
+        );
+    }
+
+    fn tests_started(&self, tools: &[String], parallel: bool) {
+        Self::emit(
+            "tests_started",
+            json!({ "tools": tools, "parallel": parallel }),
+        );
+    }
+
+    fn tool_started(&self, tool_name: &str) {
+        Self::emit("tool_started", json!({ "tool": tool_name }));
+    }
+
+    fn tool_retrying(&self, tool_name: &str, attempt: u32, error: &str) {
+        Self::emit(
+            "tool_retrying",
+            json!({ "tool": tool_name, "attempt": attempt, "error": error }),
+        );
+    }
+
+    fn tool_finished(&self, result: &ToolTestResult) {
+        Self::emit(
+            "tool_finished",
+            json!({
+                "tool": result.tool_name,
+                "success": result.success,
+                "degraded": result.degraded,
+                "skipped": result.skipped,
+                "response_time_ms": result.response_time_ms,
+                "error": result.error_message,
+                "correlation_id": result.correlation_id,
+                "request_ids": result.request_ids,
+                "error_detail": result.error_detail,
+            }),
+        );
+    }
+
+    fn tests_finished(&self, result: &AllToolsTestResult) {
+        Self::emit(
+            "tests_finished",
+            json!({
+                "success": result.success,
+                "total_tools": result.total_tools,
+                "successful_tools": result.successful_tools,
+                "failed_tools": result.failed_tools,
+                "skipped_tools": result.skipped_tools,
+                "run_id": result.run_id,
+            }),
+        );
+    }
+
+    fn host_operation_started(&self, host: &str, operation: &str) {
+        Self::emit(
+            "host_operation_started",
+            json!({ "host": host, "operation": operation }),
+        );
+    }
+
+    fn host_operation_finished(&self, result: &HostOperationResult) {
+        Self::emit(
+            "host_operation_finished",
+            json!({
+                "host": result.host,
+                "operation": result.operation,
+                "success": result.success,
+                "error": result.error,
+                "auth": result.auth,
+            }),
+        );
+    }
+}
@@ -0,0 +1,118 @@
+//! Pluggable reporters for progress and result output
+//!
+//! Test logic emits events through the [`Reporter`] trait instead of writing
+//! to the terminal directly. This keeps presentation separate from execution
+//! and lets embedders plug in their own output (TUI, CI annotations, ...).
+
+pub mod dots;
+pub mod github;
+pub mod json;
+pub mod plain;
+pub mod silent;
+pub mod terminal;
+
+pub use dots::DotsReporter;
+pub use github::GithubActions;
+pub use json::JsonReporter;
+pub use plain::PlainReporter;
+pub use silent::SilentReporter;
+pub use terminal::TerminalReporter;
+
+use crate::{
+    AllToolsTestResult, HostOperationResult, ProgressMode, ToolTestResult, plain_output,
+    progress_mode, quiet_output,
+};
+use console::Term;
+use indicatif::ProgressBar;
+use std::sync::Arc;
+
+/// Receives progress and result events from test runs and host operations.
+///
+/// Every method has a no-op default, so custom reporters only need to
+/// implement the events they care about.
+pub trait Reporter: Send + Sync {
+    /// General informational message
+    fn info(&self, _message: &str) {}
+
+    /// Non-fatal problem worth surfacing to the user
+    fn warning(&self, _message: &str) {}
+
+    /// Tool discovery started against an MCP endpoint
+    fn discovery_started(&self, _endpoint: &str) {}
+
+    /// Tool discovery finished with the number of tools selected for testing
+    fn discovery_finished(&self, _endpoint: &str, _tool_count: usize) {}
+
+    /// Tool tests are about to run
+    fn tests_started(&self, _tools: &[String], _parallel: bool) {}
+
+    /// A single tool test started executing
+    fn tool_started(&self, _tool_name: &str) {}
+
+    /// A tool test failed and is about to be retried
+    fn tool_retrying(&self, _tool_name: &str, _attempt: u32, _error: &str) {}
+
+    /// A single tool test finished
+    fn tool_finished(&self, _result: &ToolTestResult) {}
+
+    /// All tool tests for an endpoint finished
+    fn tests_finished(&self, _result: &AllToolsTestResult) {}
+
+    /// A host application operation started
+    fn host_operation_started(&self, _host: &str, _operation: &str) {}
+
+    /// A host application operation finished
+    fn host_operation_finished(&self, _result: &HostOperationResult) {}
+}
+
+/// Whether animated output is safe: stdout is a terminal, `TERM` isn't `dumb`, and output isn't plain
+///
+/// `--progress bars` forces animation on, and any other `--progress` style turns it off.
+#[must_use]
+pub fn is_interactive() -> bool {
+    match progress_mode() {
+        Some(ProgressMode::Bars) => true,
+        Some(_) => false,
+        None => {
+            !plain_output()
+                && Term::stdout().is_term()
+                && std::env::var("TERM").map_or(true, |term| term != "dumb")
+        }
+    }
+}
+
+/// A progress bar that stays hidden when output isn't interactive
+#[must_use]
+pub fn progress_bar(len: u64) -> ProgressBar {
+    if is_interactive() {
+        ProgressBar::new(len)
+    } else {
+        ProgressBar::hidden()
+    }
+}
+
+/// Create the reporter matching a CLI output format (`text`, `plain`, `json`, `silent`)
+///
+/// `text` falls back to [`PlainReporter`] when output isn't interactive, so
+/// progress bars never end up in captured logs. `--quiet` silences every
+/// format's progress events. A `--progress` style replaces the format's
+/// choice, except that JSON output keeps its event stream unless it is `none`.
+#[must_use]
+pub fn reporter_for_format(format: &str) -> Arc<dyn Reporter> {
+    match (format, progress_mode()) {
+        _ if quiet_output() => return Arc::new(SilentReporter),
+        (_, Some(ProgressMode::Off)) => return Arc::new(SilentReporter),
+        ("json", _) => return Arc::new(JsonReporter::new()),
+        (_, Some(ProgressMode::Bars)) => return Arc::new(TerminalReporter::new()),
+        (_, Some(ProgressMode::Dots)) => return Arc::new(DotsReporter::new()),
+        (_, Some(ProgressMode::Plain)) => return Arc::new(PlainReporter::new()),
+        (_, None) => {}
+    }
+    match format {
+        "json" => Arc::new(JsonReporter::new()),
+        "silent" | "none" => Arc::new(SilentReporter),
+        "plain" => Arc::new(PlainReporter::new()),
+        _ if is_interactive() => Arc::new(TerminalReporter::new()),
+        _ => Arc::new(PlainReporter::new()),
+    }
+}
@@ -0,0 +1,125 @@
+//! Line-oriented reporter for logs and redirected output
+
+use super::Reporter;
+use crate::{AllToolsTestResult, HostOperationResult, ToolTestResult, write_line};
+use console::Term;
+use std::fmt::Write;
+
+/// Reporter writing one plain-text line per state change, with no cursor movement
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PlainReporter;
+
+impl PlainReporter {
+    #[must_use]
+    pub const fn new() -> Self {
+        Self
+    }
+
+    fn write_line(line: &str) {
+        write_line(&Term::stdout(), line);
+    }
+}
+
+impl Reporter for PlainReporter {
+    fn info(&self, message: &str) {
+        Self::write_line(&format!("info: {message}"));
+    }
+
+    fn warning(&self, message: &str) {
+        Self::write_line(&format!("warning: {message}"));
+    }
+
+    fn discovery_started(&self, endpoint: &str) {
+        Self::write_line(&format!("Discovering tools at {endpoint}"));
+    }
+
+    fn discovery_finished(&self, endpoint: &str, tool_count: usize) {
+        Self::write_line(&format!("Found {tool_count} tools to test at {endpoint}"));
+    }
+
+    fn tests_started(&self, tools: &[String], parallel: bool) {
+        Self::write_line(&format!(
+            "Testing {} tools {}: {}",
+            tools.len(),
+            if parallel {
+                "in parallel"
+            } else {
+                "sequentially"
+            },
+            tools.join(", ")
+        ));
+    }
+
+    fn tool_started(&self, tool_name: &str) {
+        Self::write_line(&format!("[{tool_name}] started"));
+    }
+
+    fn tool_retrying(&self, tool_name: &str, attempt: u32, error: &str) {
+        Self::write_line(&format!(
+            "[{tool_name}] retrying (attempt {attempt}): {error}"
+        ));
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn tool_finished(&self, result: &ToolTestResult) {
+        if let Some(reason) = result.skipped {
+            Self::write_line(&format!(
+                "[{}] skipped ({})",
+                result.tool_name,
+                reason.as_str()
+            ));
+            return;
+        }
+        let seconds = result.response_time_ms as f64 / 1000.0;
+        let line = if result.degraded {
+            format!(
+                "[{}] degraded ({seconds:.2}s, budget {}ms)",
+                result.tool_name,
+                result.latency_budget_ms.unwrap_or_default()
+            )
+        } else if result.success {
+            format!("[{}] passed ({seconds:.2}s)", result.tool_name)
+        } else {
+            let correlation = result
+                .request_reference()
+                .map_or_else(String::new, |reference| format!(" [{reference}]"));
+            format!(
+                "[{}] failed ({seconds:.2}s): {}{correlation}",
+                result.tool_name,
+                result.error_message.as_deref().unwrap_or("unknown error")
+            )
+        };
+        Self::write_line(&line);
+    }
+
+    fn tests_finished(&self, result: &AllToolsTestResult) {
+        Self::write_line(&format!(
+            "Finished: {}/{} tools passed",
+            result.successful_tools,
+            result.tested_tools()
+        ));
+    }
+
+    fn host_operation_started(&self, host: &str, operation: &str) {
+        Self::write_line(&format!("[{host}] {operation} started"));
+    }
+
+    fn host_operation_finished(&self, result: &HostOperationResult) {
+        let status = if result.success {
+            "succeeded"
+        } else {
+            "failed"
+        };
+        let mut line = format!("[{}] {} {status}", result.host, result.operation);
+        if let Some(duration) = result.duration {
+            let _ = write!(line, " ({duration:?})");
+        }
+        if !result.details.is_empty() {
+            let _ = write!(line, ": {}", result.details);
+        }
+        if let Some(error) = &result.error {
+            let _ = write!(line, " (error: {error})");
+        }
+        Self::write_line(&line);
+    }
+}
@@ -0,0 +1,9 @@
+//! Reporter that discards every event
+
+use super::Reporter;
+
+/// Reporter that produces no output, for embedding and quiet runs
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SilentReporter;
+
+impl Reporter for SilentReporter {}
@@ -0,0 +1,222 @@
+//! Interactive terminal reporter using indicatif progress bars
+
+use super::Reporter;
+use crate::{AllToolsTestResult, HostOperationResult, ToolTestResult, redact};
+use console::{Term, style};
+use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+#[derive(Default)]
+struct TerminalState {
+    spinner: Option<ProgressBar>,
+    overall: Option<ProgressBar>,
+    tool_bars: HashMap<String, ProgressBar>,
+}
+
+/// Reporter rendering spinners, per-tool progress bars, and styled messages
+pub struct TerminalReporter {
+    multi_progress: MultiProgress,
+    state: Mutex<TerminalState>,
+}
+
+impl TerminalReporter {
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            multi_progress: MultiProgress::new(),
+            state: Mutex::new(TerminalState::default()),
+        }
+    }
+
+    fn write_line(&self, line: &str) {
+        // Suspend active bars so the line doesn't tear them
+        self.multi_progress.suspend(|| {
+            let _ = Term::stdout().write_line(&redact(line));
+        });
+    }
+
+    fn lock_state(&self) -> std::sync::MutexGuard<'_, TerminalState> {
+        self.state
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    /// Look up the per-tool bar (parallel mode) and the overall bar (sequential mode)
+    fn bars_for(&self, tool_name: &str) -> (Option<ProgressBar>, Option<ProgressBar>) {
+        let state = self.lock_state();
+        (
+            state.tool_bars.get(tool_name).cloned(),
+            state.overall.clone(),
+        )
+    }
+}
+
+impl Default for TerminalReporter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Reporter for TerminalReporter {
+    fn info(&self, message: &str) {
+        self.write_line(&format!("ℹ️  {}", style(message).dim()));
+    }
+
+    fn warning(&self, message: &str) {
+        self.write_line(&format!("⚠️  {}", style(message).yellow()));
+    }
+
+    fn discovery_started(&self, _endpoint: &str) {
+        let spinner = self.multi_progress.add(ProgressBar::new_spinner());
+        spinner.set_style(
+            #[allow(clippy::literal_string_with_formatting_args)]
+            ProgressStyle::with_template("🔍 {spinner} {msg}")
+                .unwrap_or_else(|_| ProgressStyle::default_spinner()),
+        );
+        spinner.enable_steady_tick(Duration::from_millis(100));
+        spinner.set_message("Discovering available tools...");
+        self.lock_state().spinner = Some(spinner);
+    }
+
+    fn discovery_finished(&self, _endpoint: &str, tool_count: usize) {
+        let spinner = self.lock_state().spinner.take();
+        if let Some(spinner) = spinner {
+            spinner.finish_with_message(format!("✅ Found {tool_count} tools to test"));
+        }
+    }
+
+    fn tests_started(&self, tools: &[String], parallel: bool) {
+        let mut state = self.lock_state();
+
+        if parallel {
+            // Calculate max tool name length for alignment
+            let max_name_len = tools.iter().map(String::len).max().unwrap_or(10);
+            let prefix_width = max_name_len + 4; // Extra space for emoji
+
+            for tool in tools {
+                let pb = self.multi_progress.add(ProgressBar::new(100));
+                pb.set_style(
+                    ProgressStyle::with_template(&format!(
+                        "{{prefix:<{prefix_width}}} [{{elapsed_precise}}] {{bar:25.cyan/blue}} {{pos:>3}}% {{msg}}"
+                    ))
+                    .unwrap_or_else(|_| ProgressStyle::default_bar()),
+                );
+                pb.set_prefix(format!("🔧 {tool}"));
+                pb.set_message("Queued...");
+                pb.set_position(0);
+                state.tool_bars.insert(tool.clone(), pb);
+            }
+        } else {
+            let pb = self
+                .multi_progress
+                .add(ProgressBar::new(tools.len() as u64));
+            pb.set_style(
+                ProgressStyle::with_template(
+                    "⚡ [{elapsed_precise}] {bar:40.cyan/blue} {pos:>2}/{len:2} {msg}",
+                )
+                .unwrap_or_else(|_| ProgressStyle::default_bar()),
+            );
+            pb.enable_steady_tick(Duration::from_millis(100));
+            pb.set_message("Testing tools sequentially...");
+            state.overall = Some(pb);
+        }
+    }
+
+    fn tool_started(&self, tool_name: &str) {
+        let (tool_bar, overall) = self.bars_for(tool_name);
+        if let Some(pb) = tool_bar {
+            pb.set_message("Testing...");
+            pb.set_position(50);
+        } else if let Some(pb) = overall {
+            pb.set_message(format!("Testing {tool_name}"));
+        }
+    }
+
+    fn tool_retrying(&self, tool_name: &str, attempt: u32, _error: &str) {
+        let (tool_bar, overall) = self.bars_for(tool_name);
+        if let Some(pb) = tool_bar {
+            pb.set_message(format!("Retrying (attempt {attempt})..."));
+        } else if let Some(pb) = overall {
+            pb.set_message(format!("Retrying {tool_name} (attempt {attempt})"));
+        }
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn tool_finished(&self, result: &ToolTestResult) {
+        let (tool_bar, overall) = {
+            let mut state = self.lock_state();
+            (
+                state.tool_bars.remove(&result.tool_name),
+                state.overall.clone(),
+            )
+        };
+        if let Some(pb) = tool_bar {
+            if let Some(reason) = result.skipped {
+                pb.finish_with_message(format!("⏭️  Skipped ({})", reason.as_str()));
+            } else if result.degraded {
+                pb.set_position(100);
+                pb.finish_with_message(format!(
+                    "🐢 Over budget ({:.2}s)",
+                    result.response_time_ms as f64 / 1000.0
+                ));
+            } else if result.success {
+                pb.set_position(100);
+                pb.finish_with_message(format!(
+                    "✅ Complete ({:.2}s)",
+                    result.response_time_ms as f64 / 1000.0
+                ));
+            } else if result
+                .error_message
+                .as_deref()
+                .is_some_and(|e| e.starts_with("Timeout after"))
+            {
+                pb.finish_with_message("⏰ Timeout".to_string());
+            } else {
+                pb.finish_with_message("❌ Failed".to_string());
+            }
+        } else if let Some(pb) = overall {
+            pb.inc(1);
+        }
+    }
+
+    fn tests_finished(&self, result: &AllToolsTestResult) {
+        let (overall, leftover_bars) = {
+            let mut state = self.lock_state();
+            (state.overall.take(), std::mem::take(&mut state.tool_bars))
+        };
+        if let Some(pb) = overall {
+            pb.finish_with_message(format!("✅ Completed {} tools", result.tested_tools()));
+        }
+        for pb in leftover_bars.into_values() {
+            pb.abandon();
+        }
+    }
+
+    fn host_operation_finished(&self, result: &HostOperationResult) {
+        self.write_line("\n📊 Host Operation Results:");
+        self.write_line(&format!("Host: {}", result.host));
+        self.write_line(&format!("Operation: {}", result.operation));
+        self.write_line(&format!(
+            "Status: {}",
+            if result.success {
+                "✅ Success"
+            } else {
+                "❌ Failed"
+            }
+        ));
+
+        if !result.details.is_empty() {
+            self.write_line(&format!("Details: {}", result.details));
+        }
+
+        if let Some(error) = &result.error {
+            self.write_line(&format!("⚠️  Error: {error}"));
+        }
+
+        if let Some(duration) = result.duration {
+            self.write_line(&format!("⏱️  Duration: {duration:?}"));
+        }
+    }
+}
@@ -0,0 +1,188 @@
+//! Retention policy for run history and generated files
+//!
+//! [`RetentionConfig`] keeps the newest `keep_runs` entries and anything
+//! younger than `keep_days`; everything else is removed by [`apply_retention`].
+//! The policy covers run records in the history directory plus any extra
+//! directories (artifacts, caches, fixtures) listed in `paths`, where each
+//! direct child file or directory counts as one entry, and runs in the
+//! `SQLite` history database, when there is one. Runs promoted to a baseline
+//! are never removed.
+
+use crate::{BaselineStore, Result, SqliteHistoryStore};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// How much history and generated output to keep
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetentionConfig {
+    /// Keep at most this many entries per directory, newest first
+    pub keep_runs: Option<usize>,
+    /// Remove entries last modified more than this many days ago
+    pub keep_days: Option<u64>,
+    /// Extra directories pruned by the same rules (artifacts, caches, fixtures)
+    pub paths: Vec<String>,
+}
+
+impl RetentionConfig {
+    /// Whether any limit is set
+    #[must_use]
+    pub const fn is_enabled(&self) -> bool {
+        self.keep_runs.is_some() || self.keep_days.is_some()
+    }
+}
+
+/// Outcome of applying a retention policy
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RetentionReport {
+    /// Entries removed, or that would be removed in a dry run
+    pub removed: Vec<PathBuf>,
+    /// Run ids removed (or that would be) from the history database
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub removed_db_runs: Vec<String>,
+    /// Bytes freed (or that would be freed)
+    pub freed_bytes: u64,
+    /// Entries kept
+    pub kept: usize,
+    /// Runs kept only because they are promoted baselines
+    pub protected: usize,
+    pub dry_run: bool,
+}
+
+impl RetentionReport {
+    /// Entries plus database runs removed
+    #[must_use]
+    pub const fn removed_count(&self) -> usize {
+        self.removed.len() + self.removed_db_runs.len()
+    }
+}
+
+/// One prunable file or directory
+struct Entry {
+    path: PathBuf,
+    modified: SystemTime,
+}
+
+/// Prune `history_dir`, `history_db`, and `config.paths` according to `config`
+///
+/// With `dry_run`, nothing is deleted and the report lists what would be.
+pub fn apply_retention(
+    config: &RetentionConfig,
+    history_dir: &Path,
+    history_db: Option<&SqliteHistoryStore>,
+    dry_run: bool,
+) -> Result<RetentionReport> {
+    let mut report = RetentionReport {
+        dry_run,
+        ..RetentionReport::default()
+    };
+    if !config.is_enabled() {
+        return Ok(report);
+    }
+
+    let baseline_runs: HashSet<String> = BaselineStore::new(history_dir)
+        .load()?
+        .into_iter()
+        .map(|entry| entry.run_id)
+        .collect();
+    let is_run = |path: &Path| path.extension().is_some_and(|ext| ext == "json");
+    let runs = list_entries(history_dir, is_run)?;
+    prune(config, runs, &baseline_runs, &mut report)?;
+    if let Some(db) = history_db {
+        report.removed_db_runs =
+            db.prune(config.keep_runs, config.keep_days, &baseline_runs, dry_run)?;
+    }
+
+    for dir in &config.paths {
+        // The history directory is handled above, with baseline protection
+        if Path::new(dir) == history_dir {
+            continue;
+        }
+        let entries = list_entries(Path::new(dir), |_| true)?;
+        prune(config, entries, &HashSet::new(), &mut report)?;
+    }
+
+    Ok(report)
+}
+
+/// Direct children of `dir` accepted by `filter`, newest first
+fn list_entries(dir: &Path, filter: impl Fn(&Path) -> bool) -> Result<Vec<Entry>> {
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut entries: Vec<Entry> = std::fs::read_dir(dir)?
+        .filter_map(std::result::Result::ok)
+        .filter(|entry| filter(&entry.path()))
+        .filter_map(|entry| {
+            let modified = entry.metadata().ok()?.modified().ok()?;
+            Some(Entry {
+                path: entry.path(),
+                modified,
+            })
+        })
+        .collect();
+    entries.sort_by(|a, b| b.modified.cmp(&a.modified).then(b.path.cmp(&a.path)));
+    Ok(entries)
+}
+
+fn prune(
+    config: &RetentionConfig,
+    entries: Vec<Entry>,
+    protected: &HashSet<String>,
+    report: &mut RetentionReport,
+) -> Result<()> {
+    let cutoff = config
+        .keep_days
+        .and_then(|days| SystemTime::now().checked_sub(Duration::from_secs(days * 86_400)));
+
+    for (index, entry) in entries.into_iter().enumerate() {
+        let over_count = config.keep_runs.is_some_and(|keep| index >= keep);
+        let too_old = cutoff.is_some_and(|cutoff| entry.modified < cutoff);
+        if !over_count && !too_old {
+            report.kept += 1;
+            continue;
+        }
+
+        let stem = entry
+            .path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        if protected.contains(&stem) {
+            report.kept += 1;
+            report.protected += 1;
+            continue;
+        }
+
+        report.freed_bytes += disk_usage(&entry.path);
+        if !report.dry_run {
+            if entry.path.is_dir() {
+                std::fs::remove_dir_all(&entry.path)?;
+            } else {
+                std::fs::remove_file(&entry.path)?;
+            }
+        }
+        report.removed.push(entry.path);
+    }
+    Ok(())
+}
+
+/// Total size of a file, or of everything under a directory
+fn disk_usage(path: &Path) -> u64 {
+    if path.is_dir() {
+        std::fs::read_dir(path)
+            .map(|entries| {
+                entries
+                    .filter_map(std::result::Result::ok)
+                    .map(|entry| disk_usage(&entry.path()))
+                    .sum()
+            })
+            .unwrap_or_default()
+    } else {
+        std::fs::metadata(path)
+            .map(|meta| meta.len())
+            .unwrap_or_default()
+    }
+}
@@ -0,0 +1,69 @@
+//! Versioned JSON Schemas for the JSON output
+//!
+//! `test --format json` prints an [`AllToolsTestResult`] and `inspect
+//! --format json` an [`InspectorResult`]; both carry [`SCHEMA_VERSION`] as
+//! `schema_version`, and `schema print` emits their JSON Schemas. Adding a
+//! field bumps the minor version; renaming or removing one, or changing its
+//! type, bumps the major version.
+
+use crate::{AllToolsTestResult, GleanMcpError, InspectorResult, Result};
+use serde_json::Value;
+
+/// Version of the JSON output documents, `major.minor`
+pub const SCHEMA_VERSION: &str = "1.9";
+
+/// [`SCHEMA_VERSION`] as an owned string, for serde defaults
+#[must_use]
+pub fn schema_version() -> String {
+    SCHEMA_VERSION.to_string()
+}
+
+/// A JSON output document with a published schema
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SchemaDocument {
+    /// `test --format json`
+    TestResult,
+    /// `inspect --format json`
+    InspectorResult,
+}
+
+impl SchemaDocument {
+    pub const ALL: [Self; 2] = [Self::TestResult, Self::InspectorResult];
+
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::TestResult => "test-result",
+            Self::InspectorResult => "inspector-result",
+        }
+    }
+}
+
+/// Parse a document name, as given to `schema print`
+pub fn parse_schema_document(raw: &str) -> Result<SchemaDocument> {
+    let name = raw.trim();
+    SchemaDocument::ALL
+        .into_iter()
+        .find(|document| document.as_str() == name)
+        .ok_or_else(|| {
+            GleanMcpError::Config(format!(
+                "Unknown schema '{name}' (expected test-result, inspector-result)"
+            ))
+        })
+}
+
+/// JSON Schema (draft 2020-12) for `document`, with `schema_version` pinned to [`SCHEMA_VERSION`]
+#[must_use]
+pub fn json_schema(document: SchemaDocument) -> Value {
+    let schema = match document {
+        SchemaDocument::TestResult => schemars::schema_for!(AllToolsTestResult),
+        SchemaDocument::InspectorResult => schemars::schema_for!(InspectorResult),
+    };
+    let mut schema = schema.to_value();
+    if let Some(version) = schema.pointer_mut("/properties/schema_version")
+        && let Value::Object(version) = version
+    {
+        version.insert("const".to_string(), Value::from(SCHEMA_VERSION));
+    }
+    schema
+}
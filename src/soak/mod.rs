@@ -0,0 +1,346 @@
+//! Session longevity testing
+//!
+//! [`run_soak`] keeps one MCP session open for a fixed duration, sending
+//! `ping` on a short interval and a tool call on a longer one, the way a host
+//! application holds a session for hours. The run fails when the session is
+//! dropped (HTTP 404 for its `Mcp-Session-Id`) or requests fail, and is
+//! degraded when late latencies grow past a multiple of early ones.
+
+use crate::{
+    CancelReason, CancellationToken, GleanMCPInspector, LatencyStats, McpTransport, RunOutcome,
+    TestQueryGenerator,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use smol::Timer;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::time::{Duration, Instant};
+
+/// Samples needed in a series before latency growth is judged
+const MIN_GROWTH_SAMPLES: usize = 4;
+
+/// Schedule of a soak run
+#[derive(Debug, Clone)]
+pub struct SoakOptions {
+    pub duration: Duration,
+    /// Time between `ping` requests
+    pub ping_interval: Duration,
+    /// Time between tool calls
+    pub call_interval: Duration,
+    pub tool: String,
+    /// Query sent with every call; defaults to the tool's standard test query
+    pub query: Option<String>,
+    /// Late median latency over early median latency above which the run is degraded
+    pub max_latency_growth: f64,
+    pub cancel: CancellationToken,
+}
+
+/// Requests of one kind over a soak run
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SoakSeries {
+    pub sent: usize,
+    pub succeeded: usize,
+    pub failed: usize,
+    pub latency: LatencyStats,
+    /// Median latency of the first quarter of successful requests, in milliseconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub early_p50: Option<u64>,
+    /// Median latency of the last quarter of successful requests, in milliseconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub late_p50: Option<u64>,
+    #[serde(skip)]
+    samples: Vec<u64>,
+}
+
+impl SoakSeries {
+    fn record(&mut self, outcome: &std::result::Result<u64, String>) {
+        self.sent += 1;
+        match outcome {
+            Ok(latency_ms) => {
+                self.succeeded += 1;
+                self.samples.push(*latency_ms);
+            }
+            Err(_) => self.failed += 1,
+        }
+    }
+
+    fn finish(&mut self) {
+        self.latency = LatencyStats::from_samples(&self.samples);
+        if self.samples.len() >= MIN_GROWTH_SAMPLES {
+            let quarter = self.samples.len() / 4;
+            self.early_p50 = Some(LatencyStats::from_samples(&self.samples[..quarter]).p50);
+            self.late_p50 =
+                Some(LatencyStats::from_samples(&self.samples[self.samples.len() - quarter..]).p50);
+        }
+    }
+
+    /// Late median latency as a multiple of the early one
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn latency_growth(&self) -> Option<f64> {
+        Some(self.late_p50? as f64 / self.early_p50?.max(1) as f64)
+    }
+}
+
+/// Outcome of a soak run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SoakResult {
+    pub success: bool,
+    pub endpoint: String,
+    /// `Mcp-Session-Id` assigned by the server; `None` for stateless servers
+    pub session_id: Option<String>,
+    pub tool: String,
+    pub planned_duration_ms: u64,
+    pub duration_ms: u64,
+    pub pings: SoakSeries,
+    pub calls: SoakSeries,
+    /// When the server stopped recognizing the session, from the start of the run
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dropped_after_ms: Option<u64>,
+    /// Late latencies grew past `max_latency_growth`
+    pub degraded: bool,
+    pub max_latency_growth: f64,
+    /// Failure counts by error message
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub errors: BTreeMap<String, usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cancelled: Option<CancelReason>,
+    pub error: Option<String>,
+}
+
+impl SoakResult {
+    /// How the run ended, from CI's point of view
+    #[must_use]
+    pub const fn outcome(&self) -> RunOutcome {
+        if self.error.is_some() {
+            RunOutcome::ConnectivityFailure
+        } else if !self.success {
+            RunOutcome::ValidationFailure
+        } else if self.degraded {
+            RunOutcome::Degraded
+        } else {
+            RunOutcome::Success
+        }
+    }
+
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn format_text(&self) -> String {
+        let mut output = String::new();
+        output.push_str("🧽 Session Soak Results\n");
+        output.push_str("=".repeat(50).as_str());
+        output.push('\n');
+        let _ = writeln!(output, "📍 Endpoint: {}", self.endpoint);
+        if let Some(error) = &self.error {
+            let _ = writeln!(output, "Error: {error}");
+            return output;
+        }
+        let _ = writeln!(
+            output,
+            "🔗 Session: {}",
+            self.session_id
+                .as_deref()
+                .unwrap_or("none (stateless server)")
+        );
+        let _ = writeln!(
+            output,
+            "⏱️  Duration: {:.1}s of {:.1}s{}",
+            self.duration_ms as f64 / 1000.0,
+            self.planned_duration_ms as f64 / 1000.0,
+            self.cancelled
+                .map_or_else(String::new, |reason| format!(" ({})", reason.as_str()))
+        );
+        if let Some(dropped) = self.dropped_after_ms {
+            let _ = writeln!(
+                output,
+                "💔 Session dropped after {:.1}s",
+                dropped as f64 / 1000.0
+            );
+        }
+        for (label, series) in [
+            ("🏓 Pings", &self.pings),
+            (&*format!("🔧 {}", self.tool), &self.calls),
+        ] {
+            let _ = writeln!(
+                output,
+                "{label}: {}/{} ok, p50 {}ms | p95 {}ms | max {}ms",
+                series.succeeded,
+                series.sent,
+                series.latency.p50,
+                series.latency.p95,
+                series.latency.max
+            );
+            if let (Some(early), Some(late), Some(growth)) =
+                (series.early_p50, series.late_p50, series.latency_growth())
+            {
+                let _ = writeln!(
+                    output,
+                    "   p50 early {early}ms → late {late}ms ({growth:.1}x{})",
+                    if growth > self.max_latency_growth {
+                        ", degraded"
+                    } else {
+                        ""
+                    }
+                );
+            }
+        }
+        if !self.errors.is_empty() {
+            output.push_str("❌ Errors:\n");
+            for (error, count) in &self.errors {
+                let _ = writeln!(output, "  {count:>5} × {error}");
+            }
+        }
+        output
+    }
+}
+
+/// Why a request in the session failed
+enum SoakFailure {
+    /// The server no longer recognizes the session
+    Dropped,
+    Failed(String),
+}
+
+/// Send `request` in the session and time it until a JSON-RPC result arrives
+#[allow(clippy::cast_possible_truncation)]
+async fn timed_request(
+    transport: &McpTransport,
+    endpoint: &str,
+    request: &Value,
+) -> std::result::Result<u64, SoakFailure> {
+    let started = Instant::now();
+    let response = transport
+        .post_json_rpc(endpoint, request)
+        .await
+        .map_err(|e| SoakFailure::Failed(e.to_string()))?;
+    let elapsed = started.elapsed().as_millis() as u64;
+    if !response.success {
+        return Err(SoakFailure::Failed(response.stderr.trim().to_string()));
+    }
+    match response.status {
+        Some(404) => return Err(SoakFailure::Dropped),
+        Some(status) if status >= 400 => {
+            return Err(SoakFailure::Failed(format!("HTTP {status}")));
+        }
+        _ => {}
+    }
+    let body: Value = serde_json::from_str(&response.body)
+        .map_err(|_| SoakFailure::Failed("Response is not JSON-RPC".to_string()))?;
+    if let Some(error) = body.get("error") {
+        let message = error
+            .get("message")
+            .and_then(Value::as_str)
+            .unwrap_or("unknown error");
+        return Err(SoakFailure::Failed(format!("JSON-RPC error: {message}")));
+    }
+    if body.pointer("/result/isError") == Some(&Value::Bool(true)) {
+        return Err(SoakFailure::Failed("Tool returned isError".to_string()));
+    }
+    if body.get("id") != request.get("id") {
+        return Err(SoakFailure::Failed("Response id doesn't match".to_string()));
+    }
+    Ok(elapsed)
+}
+
+/// Hold one session open on the inspector's default endpoint for `options.duration`
+#[allow(clippy::future_not_send)]
+#[allow(clippy::cast_possible_truncation)]
+pub async fn run_soak(inspector: &GleanMCPInspector, options: &SoakOptions) -> SoakResult {
+    let mut result = SoakResult {
+        success: false,
+        endpoint: inspector.server_url().to_string(),
+        session_id: None,
+        tool: options.tool.clone(),
+        planned_duration_ms: options.duration.as_millis() as u64,
+        duration_ms: 0,
+        pings: SoakSeries::default(),
+        calls: SoakSeries::default(),
+        dropped_after_ms: None,
+        degraded: false,
+        max_latency_growth: options.max_latency_growth,
+        errors: BTreeMap::new(),
+        cancelled: None,
+        error: None,
+    };
+    let started = Instant::now();
+    let (session_id, transport, _) = match inspector.open_session().await {
+        Ok(session) => session,
+        Err(error) => {
+            result.error = Some(error);
+            return result;
+        }
+    };
+    result.session_id = session_id;
+
+    let query = options
+        .query
+        .clone()
+        .unwrap_or_else(|| TestQueryGenerator::generate_test_query(&options.tool));
+    let arguments = TestQueryGenerator::generate_arguments(&options.tool, &query);
+    let deadline = started + options.duration;
+    let mut next_ping = started;
+    let mut next_call = started;
+    // Ids start after initialize (0)
+    let mut id: u64 = 1;
+    while let Some(due) = Some(next_ping.min(next_call)).filter(|due| *due < deadline) {
+        if options
+            .cancel
+            .run_until_cancelled(Timer::at(due))
+            .await
+            .is_none()
+        {
+            result.cancelled = options.cancel.reason();
+            break;
+        }
+        let is_call = next_call <= next_ping;
+        let request = if is_call {
+            next_call += options.call_interval;
+            json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "method": "tools/call",
+                "params": { "name": options.tool, "arguments": arguments }
+            })
+        } else {
+            next_ping += options.ping_interval;
+            json!({ "jsonrpc": "2.0", "id": id, "method": "ping" })
+        };
+        id += 1;
+
+        let outcome = timed_request(&transport, inspector.server_url(), &request).await;
+        let dropped = matches!(outcome, Err(SoakFailure::Dropped));
+        let outcome = outcome.map_err(|failure| match failure {
+            SoakFailure::Dropped => "Session no longer recognized (HTTP 404)".to_string(),
+            SoakFailure::Failed(message) => GleanMCPInspector::truncate_error_message(&message),
+        });
+        if let Err(message) = &outcome {
+            *result.errors.entry(message.clone()).or_default() += 1;
+        }
+        if is_call {
+            result.calls.record(&outcome);
+        } else {
+            result.pings.record(&outcome);
+        }
+        if dropped {
+            result.dropped_after_ms = Some(started.elapsed().as_millis() as u64);
+            inspector.reporter().warning(&format!(
+                "Session dropped after {}s",
+                started.elapsed().as_secs()
+            ));
+            break;
+        }
+    }
+
+    result.duration_ms = started.elapsed().as_millis() as u64;
+    result.pings.finish();
+    result.calls.finish();
+    result.degraded = [&result.pings, &result.calls]
+        .iter()
+        .filter_map(|series| series.latency_growth())
+        .any(|growth| growth > options.max_latency_growth);
+    result.success = result.dropped_after_ms.is_none()
+        && result.pings.failed + result.calls.failed == 0
+        && result.pings.succeeded + result.calls.succeeded > 0;
+    result
+}
@@ -1,13 +1,58 @@
+use crate::{
+    ActAsConfig, AgentCheckConfig, BenchConfig, ConfigDiagnostic, CorpusConfig,
+    DEFAULT_CHATGPT_TOOLS, DEFAULT_TOKEN_ENV, DuplicateCheckConfig, GleanMcpError, I18nConfig,
+    IssueFilingConfig, IssueProvider, LanguageCheckConfig, PermissionCheckConfig, PiiScanConfig,
+    ProxyConfig, QueryRule, RedactionConfig, ResponseSizeConfig, Result, ResultCheckConfig,
+    RetentionConfig, Severity, SnapshotConfig, TOOL_CATEGORIES, TlsConfig, ToolProfileConfig,
+    default_tool_tags, load_corpus_file, pii_detectors, template_references, tool_profile,
+    validate_header, validate_mask, validate_templates,
+};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+
+/// Config file picked up from the working directory when `--config` is not given
+pub const DEFAULT_CONFIG_FILE: &str = "glean-mcp-test.yaml";
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct GleanConfig {
     pub glean_instance: GleanInstance,
     pub mcp_inspector: McpInspectorConfig,
     pub authentication: AuthConfig,
     pub tools_to_test: ToolsConfig,
     pub host_applications: HashMap<String, HostConfig>,
+    pub monitor: MonitorConfig,
+    pub baseline: BaselineConfig,
+    pub slo: SloConfig,
+    pub language_check: LanguageCheckConfig,
+    pub duplicate_check: DuplicateCheckConfig,
+    pub result_check: ResultCheckConfig,
+    pub snapshot: SnapshotConfig,
+    pub response_size: ResponseSizeConfig,
+    /// Sensitive data scanning of responses
+    pub pii_scan: PiiScanConfig,
+    /// Regression thresholds for `bench --baseline`
+    pub bench: BenchConfig,
+    pub retention: RetentionConfig,
+    pub proxy: ProxyConfig,
+    pub tls: TlsConfig,
+    /// Extra headers sent with every MCP request
+    pub headers: BTreeMap<String, String>,
+    pub permissions: PermissionCheckConfig,
+    /// Impersonating users with `--act-as` and `test --act-as-users`
+    pub act_as: ActAsConfig,
+    /// Queries per tool for data-driven runs
+    pub corpus: CorpusConfig,
+    pub i18n: I18nConfig,
+    pub redaction: RedactionConfig,
+    /// Agent discovery and runs when `run_agent` is tested
+    pub agents: AgentCheckConfig,
+    /// Tools of the server under test, for servers other than Glean
+    pub profile: ToolProfileConfig,
+    /// File this configuration was loaded from, if any
+    #[serde(skip)]
+    pub source: Option<PathBuf>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -16,25 +61,363 @@ pub struct GleanInstance {
     pub environment: String,
     pub server_url: String,
     pub chatgpt_url: String,
+    /// Tools the `ChatGPT` connector endpoint should expose, and nothing else
+    #[serde(default = "default_chatgpt_tools")]
+    pub chatgpt_tools: Vec<String>,
+    /// URL of an instance's MCP endpoints, with `{instance}` and `{endpoint}` placeholders
+    #[serde(default = "default_url_template")]
+    pub url_template: String,
+    /// MCP endpoints `test --endpoint all` runs against, in order
+    #[serde(default = "default_endpoints")]
+    pub endpoints: Vec<EndpointConfig>,
+}
+
+impl GleanInstance {
+    /// Endpoints selected by `--endpoint`: `all`, a comma-separated list of names, or (`None`) default and chatgpt
+    /// (only default under a generic tool profile)
+    ///
+    /// Names are looked up in [`Self::endpoints`]; `default` and `chatgpt`
+    /// are always known, with no tool expectations unless configured.
+    pub fn select_endpoints(&self, selection: Option<&str>) -> Result<Vec<EndpointConfig>> {
+        let names: Vec<&str> = match selection.map(str::trim) {
+            Some("all") if self.endpoints.is_empty() => {
+                return Err(GleanMcpError::Config(
+                    "--endpoint all: glean_instance.endpoints lists no endpoints".to_string(),
+                ));
+            }
+            Some("all") => return Ok(self.endpoints.clone()),
+            Some(list) => list
+                .split(',')
+                .map(str::trim)
+                .filter(|name| !name.is_empty())
+                .collect(),
+            None if tool_profile().is_generic() => vec!["default"],
+            None => DEFAULT_ENDPOINTS.to_vec(),
+        };
+        if names.is_empty() {
+            return Err(GleanMcpError::Config(
+                "--endpoint needs at least one endpoint name".to_string(),
+            ));
+        }
+        names
+            .into_iter()
+            .map(|name| {
+                self.endpoints
+                    .iter()
+                    .find(|endpoint| endpoint.name == name)
+                    .cloned()
+                    .or_else(|| {
+                        DEFAULT_ENDPOINTS
+                            .contains(&name)
+                            .then(|| EndpointConfig::named(name))
+                    })
+                    .ok_or_else(|| {
+                        let known: BTreeSet<&str> = DEFAULT_ENDPOINTS
+                            .into_iter()
+                            .chain(self.endpoints.iter().map(|endpoint| endpoint.name.as_str()))
+                            .collect();
+                        GleanMcpError::Config(format!(
+                            "Unknown endpoint '{name}' (configured: {})",
+                            known.into_iter().collect::<Vec<_>>().join(", ")
+                        ))
+                    })
+            })
+            .collect()
+    }
+}
+
+/// One of an instance's MCP endpoints (`default`, `chatgpt`, `agents`, ...)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EndpointConfig {
+    pub name: String,
+    /// Full URL; defaults to `server_url`, `chatgpt_url`, or the URL template's `{endpoint}`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// Tools the endpoint must advertise; missing ones fail the run
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub expected_tools: Vec<String>,
+    /// `expected_tools` is the endpoint's whole manifest; other advertised tools fail the run
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub exact_tools: bool,
+}
+
+impl EndpointConfig {
+    /// An endpoint at its templated URL, with no tool expectations
+    #[must_use]
+    pub fn named(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            url: None,
+            expected_tools: Vec::new(),
+            exact_tools: false,
+        }
+    }
+}
+
+/// The endpoints every instance has, and the ones tested without `--endpoint`
+pub const DEFAULT_ENDPOINTS: [&str; 2] = ["default", "chatgpt"];
+
+#[must_use]
+pub fn default_endpoints() -> Vec<EndpointConfig> {
+    DEFAULT_ENDPOINTS.map(EndpointConfig::named).to_vec()
+}
+
+fn default_url_template() -> String {
+    DEFAULT_URL_TEMPLATE.to_string()
+}
+
+/// Endpoint URLs of Glean-hosted instances
+pub const DEFAULT_URL_TEMPLATE: &str = "https://{instance}-be.glean.com/mcp/{endpoint}";
+
+const INSTANCE_PLACEHOLDER: &str = "{instance}";
+const ENDPOINT_PLACEHOLDER: &str = "{endpoint}";
+
+/// URL of `instance`'s `endpoint` (`default`, `chatgpt`) from a URL template
+///
+/// A template without `{endpoint}` is the URL of the default endpoint, e.g.
+/// `https://glean.acme.internal/mcp/agents`; other endpoints replace its
+/// last path segment.
+#[must_use]
+pub fn endpoint_url(template: &str, instance: &str, endpoint: &str) -> String {
+    let url = template.replace(INSTANCE_PLACEHOLDER, instance);
+    if url.contains(ENDPOINT_PLACEHOLDER) {
+        return url.replace(ENDPOINT_PLACEHOLDER, endpoint);
+    }
+    if endpoint == "default" {
+        return url;
+    }
+    let url = url.trim_end_matches('/');
+    let path_start = url.find("://").map_or(0, |scheme| scheme + 3);
+    url[path_start..].rfind('/').map_or_else(
+        || format!("{url}/{endpoint}"),
+        |last| format!("{}/{endpoint}", &url[..path_start + last]),
+    )
+}
+
+/// Check a server URL or URL template, as given to `--server-url`
+pub fn parse_url_template(raw: &str) -> Result<String> {
+    let template = raw.trim();
+    url_template_problem(template).map_or_else(
+        || Ok(template.to_string()),
+        |problem| {
+            Err(GleanMcpError::Config(format!(
+                "Invalid server URL '{template}' ({problem})"
+            )))
+        },
+    )
+}
+
+/// Why `template` can't be used as a server URL template
+fn url_template_problem(template: &str) -> Option<&'static str> {
+    if !template.starts_with("http://") && !template.starts_with("https://") {
+        return Some("must be an http(s) URL");
+    }
+    template
+        .replace(INSTANCE_PLACEHOLDER, "")
+        .replace(ENDPOINT_PLACEHOLDER, "")
+        .contains(['{', '}'])
+        .then_some("only {instance} and {endpoint} can be filled in")
+}
+
+fn default_chatgpt_tools() -> Vec<String> {
+    DEFAULT_CHATGPT_TOOLS.map(ToString::to_string).to_vec()
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct McpInspectorConfig {
     pub package: String,
+    /// Pinned version, e.g. `0.10.x` or `0.10.2`; `x` or `*` matches any number
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
     pub validation_required: bool,
     pub tools_to_validate: Vec<String>,
 }
 
+impl McpInspectorConfig {
+    /// Package spec handed to npm and npx, e.g. `@modelcontextprotocol/inspector@0.10.x`
+    #[must_use]
+    pub fn package_spec(&self) -> String {
+        self.version.as_ref().map_or_else(
+            || self.package.clone(),
+            |version| format!("{}@{version}", self.package),
+        )
+    }
+
+    /// Whether `installed` (e.g. `0.10.4`) satisfies the pinned version; any version does without a pin
+    ///
+    /// A pin with fewer parts matches as a prefix, so `0.10` accepts `0.10.4`.
+    #[must_use]
+    pub fn accepts_version(&self, installed: &str) -> bool {
+        let Some(pin) = &self.version else {
+            return true;
+        };
+        let installed: Vec<&str> = installed
+            .trim()
+            .trim_start_matches('v')
+            .split('.')
+            .collect();
+        let pin: Vec<&str> = pin.trim().trim_start_matches('v').split('.').collect();
+        pin.len() <= installed.len()
+            && pin
+                .iter()
+                .zip(&installed)
+                .all(|(want, have)| matches!(*want, "x" | "X" | "*") || want == have)
+    }
+}
+
+/// Whether `version` is a pin [`McpInspectorConfig::accepts_version`] understands
+fn is_version_pin(version: &str) -> bool {
+    let version = version.trim_start_matches('v');
+    !version.is_empty()
+        && version.split('.').count() <= 3
+        && version.split('.').all(|part| {
+            matches!(part, "x" | "X" | "*")
+                || (!part.is_empty() && part.chars().all(|c| c.is_ascii_digit()))
+        })
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AuthConfig {
     pub method: String,
     pub oauth_scopes: Vec<String>,
+    /// Clock skew against the server, in seconds, above which `auth` warns
+    #[serde(default = "default_max_clock_skew_secs")]
+    pub max_clock_skew_secs: u64,
+    /// Environment variables holding the auth token, in lookup order
+    #[serde(default = "default_token_env")]
+    pub token_env: Vec<String>,
+    /// Per-instance variables, looked up before any other for that instance
+    #[serde(default)]
+    pub instance_token_env: BTreeMap<String, Vec<String>>,
+}
+
+const fn default_max_clock_skew_secs() -> u64 {
+    30
+}
+
+fn default_token_env() -> Vec<String> {
+    vec![DEFAULT_TOKEN_ENV.to_string()]
+}
+
+impl AuthConfig {
+    /// Variables searched for `instance`'s token, in lookup order
+    ///
+    /// `instance_token_env` for the instance comes first, then each
+    /// `token_env` variable suffixed with the instance name (`scio-prod` →
+    /// `GLEAN_AUTH_TOKEN_SCIO_PROD`), then `token_env` itself.
+    #[must_use]
+    pub fn token_variables(&self, instance: &str) -> Vec<String> {
+        let suffix: String = instance
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() {
+                    c.to_ascii_uppercase()
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        let mut variables: Vec<String> = Vec::new();
+        let candidates = self
+            .instance_token_env
+            .get(instance)
+            .into_iter()
+            .flatten()
+            .cloned()
+            .chain(
+                self.token_env
+                    .iter()
+                    .map(|variable| format!("{variable}_{suffix}")),
+            )
+            .chain(self.token_env.iter().cloned());
+        for variable in candidates {
+            if !variables.contains(&variable) {
+                variables.push(variable);
+            }
+        }
+        variables
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ToolsConfig {
     pub core_tools: Vec<String>,
     pub enterprise_tools: Vec<String>,
+    /// Per-tool cap on concurrent calls during parallel runs, on top of `--max-concurrent`
+    #[serde(default = "default_tool_concurrency")]
+    pub max_concurrent: BTreeMap<String, usize>,
+    /// Structured `tools/call` arguments per tool (filters, pagination, datasources, ...),
+    /// used instead of the generated single-string query
+    #[serde(default)]
+    pub arguments: BTreeMap<String, serde_json::Value>,
+    /// Tags per tool (`email`, `code`, `people`, ...) selected with `--tag` and `--exclude-tag`
+    #[serde(default = "default_tool_tags")]
+    pub tags: BTreeMap<String, Vec<String>>,
+    /// Name globs of tools never tested on this instance, e.g. connectors it doesn't enable
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    /// Tools that only run once the listed tools pass, e.g. `read_document: [search]`;
+    /// otherwise they're skipped
+    #[serde(default)]
+    pub depends_on: BTreeMap<String, Vec<String>>,
+    /// Regex → query rules for tools without a built-in test query, matched against
+    /// their name, description, and parameters before the built-in rules
+    #[serde(default)]
+    pub query_rules: Vec<QueryRule>,
+}
+
+impl ToolsConfig {
+    /// Tools each tool depends on, from `depends_on` and the templates in its `arguments`
+    #[must_use]
+    pub fn dependency_graph(&self) -> BTreeMap<String, Vec<String>> {
+        let mut graph = self.depends_on.clone();
+        for (tool, arguments) in &self.arguments {
+            let dependencies = graph.entry(tool.clone()).or_default();
+            for reference in template_references(arguments) {
+                if !dependencies
+                    .iter()
+                    .any(|dependency| dependency == reference)
+                {
+                    dependencies.push(reference.to_string());
+                }
+            }
+        }
+        graph
+    }
+}
+
+/// Expensive tools that must never run more than one call at a time
+fn default_tool_concurrency() -> BTreeMap<String, usize> {
+    [("chat", 1), ("gemini_web_search", 1)]
+        .into_iter()
+        .map(|(tool, limit)| (tool.to_string(), limit))
+        .collect()
+}
+
+/// Whether `tool` can reach itself by following `depends_on`
+fn depends_on_itself(depends_on: &BTreeMap<String, Vec<String>>, tool: &str) -> bool {
+    let mut seen = BTreeSet::new();
+    let mut stack: Vec<&str> = depends_on
+        .get(tool)
+        .into_iter()
+        .flatten()
+        .map(String::as_str)
+        .collect();
+    while let Some(next) = stack.pop() {
+        if next == tool {
+            return true;
+        }
+        if seen.insert(next) {
+            stack.extend(
+                depends_on
+                    .get(next)
+                    .into_iter()
+                    .flatten()
+                    .map(String::as_str),
+            );
+        }
+    }
+    false
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -46,6 +429,766 @@ pub struct HostConfig {
     pub priority: String,
 }
 
+/// Continuous monitoring settings used by the `monitor` command
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MonitorConfig {
+    /// Directory where run records are stored
+    pub history_dir: String,
+    /// `SQLite` database where run records are also stored, for trend queries
+    pub history_db: Option<String>,
+    pub schedules: Vec<ScheduleConfig>,
+    /// Issues filed for tools that keep failing
+    pub issues: IssueFilingConfig,
+}
+
+/// A suite executed on a cron schedule
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduleConfig {
+    /// Unique schedule name, used to tag history records
+    pub name: String,
+    /// Cron expression (5 fields, or 6 with leading seconds)
+    pub cron: String,
+    /// Suite to run: `core`, `enterprise`, `all`, a comma-separated tool list, or `hosts`
+    pub suite: String,
+    /// Glean instance override (defaults to the configured instance)
+    #[serde(default)]
+    pub instance: Option<String>,
+    /// Host applications exercised by the `hosts` suite
+    #[serde(default)]
+    pub hosts: Vec<String>,
+    #[serde(default)]
+    pub parallel: bool,
+    /// Labels attached to every run of this schedule
+    #[serde(default)]
+    pub labels: BTreeMap<String, String>,
+    /// Only test tools carrying one of these tags
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Name globs of tools to skip, on top of `tools_to_test.exclude`
+    #[serde(default)]
+    pub exclude: Vec<String>,
+}
+
+/// Baseline auto-promotion policy used by `test` and `compare`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BaselineConfig {
+    /// Promote fully passing runs on a protected branch to the comparison baseline
+    pub auto_promote: bool,
+    /// Branches whose passing runs are eligible for promotion
+    pub protected_branches: Vec<String>,
+}
+
+/// Per-tool latency budgets used for release gating
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SloConfig {
+    /// Tools slower than their budget are reported as degraded rather than failed
+    pub latency_budgets: BTreeMap<String, LatencyBudget>,
+    /// Exit code of `test` when no tool failed but some were degraded
+    pub degraded_exit_code: i32,
+}
+
+impl Default for SloConfig {
+    fn default() -> Self {
+        Self {
+            latency_budgets: BTreeMap::new(),
+            degraded_exit_code: 2,
+        }
+    }
+}
+
+impl SloConfig {
+    /// Budgets in milliseconds, keyed by tool name
+    #[must_use]
+    pub fn budgets_ms(&self) -> BTreeMap<String, u64> {
+        self.latency_budgets
+            .iter()
+            .map(|(tool, budget)| (tool.clone(), budget.0))
+            .collect()
+    }
+}
+
+/// A latency budget in milliseconds, written as `3000`, `3000ms`, or `15s`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LatencyBudget(pub u64);
+
+impl Serialize for LatencyBudget {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(&format!("{}ms", self.0))
+    }
+}
+
+impl<'de> Deserialize<'de> for LatencyBudget {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            Millis(u64),
+            Text(String),
+        }
+
+        match Raw::deserialize(deserializer)? {
+            Raw::Millis(ms) => Ok(Self(ms)),
+            Raw::Text(text) => parse_duration_ms(&text).map(Self).map_err(|_| {
+                serde::de::Error::custom(format!(
+                    "invalid duration '{text}' (expected e.g. 3000ms or 15s)"
+                ))
+            }),
+        }
+    }
+}
+
+/// Parse a duration such as `250`, `250ms`, `15s`, `10m`, or `2h` into milliseconds
+pub fn parse_duration_ms(raw: &str) -> Result<u64> {
+    let raw = raw.trim();
+    let scaled = |number: &str, unit: u64| {
+        number
+            .trim()
+            .parse::<u64>()
+            .ok()
+            .and_then(|n| n.checked_mul(unit))
+    };
+    #[allow(clippy::option_if_let_else)]
+    let parsed = if let Some(ms) = raw.strip_suffix("ms") {
+        ms.trim().parse().ok()
+    } else if let Some(secs) = raw.strip_suffix('s') {
+        scaled(secs, 1000)
+    } else if let Some(minutes) = raw.strip_suffix('m') {
+        scaled(minutes, 60_000)
+    } else if let Some(hours) = raw.strip_suffix('h') {
+        scaled(hours, 3_600_000)
+    } else {
+        raw.parse().ok()
+    };
+    parsed.ok_or_else(|| {
+        GleanMcpError::Config(format!(
+            "Invalid duration '{raw}' (expected e.g. 3000ms, 15s, 10m, or 2h)"
+        ))
+    })
+}
+
+impl Default for BaselineConfig {
+    fn default() -> Self {
+        Self {
+            auto_promote: true,
+            protected_branches: vec!["main".to_string(), "master".to_string()],
+        }
+    }
+}
+
+impl Default for MonitorConfig {
+    fn default() -> Self {
+        Self {
+            history_dir: ".glean-mcp-test/history".to_string(),
+            history_db: None,
+            schedules: Vec::new(),
+            issues: IssueFilingConfig::default(),
+        }
+    }
+}
+
+impl GleanConfig {
+    /// Load configuration from a YAML file; missing sections fall back to defaults
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path).map_err(|e| {
+            GleanMcpError::Config(format!("Failed to read {}: {e}", path.display()))
+        })?;
+        let mut config: Self = serde_yaml::from_str(&contents).map_err(|e| {
+            GleanMcpError::Config(format!("Invalid config file {}: {e}", path.display()))
+        })?;
+        config.source = Some(path.to_path_buf());
+        Ok(config)
+    }
+
+    /// Load `path` if given, else `glean-mcp-test.yaml` when present, else defaults
+    pub fn load_or_default(path: Option<&Path>) -> Result<Self> {
+        match path {
+            Some(path) => Self::load(path),
+            None if Path::new(DEFAULT_CONFIG_FILE).exists() => {
+                Self::load(Path::new(DEFAULT_CONFIG_FILE))
+            }
+            None => Ok(Self::default()),
+        }
+    }
+
+    /// Endpoints `inspect` checks `tools/list` on: those with `expected_tools`, and
+    /// `default`, which without them must advertise `tools_to_test.core_tools`
+    /// (with any `glean_` prefix dropped), or a generic profile's core tools
+    #[must_use]
+    pub fn inspected_endpoints(&self) -> Vec<EndpointConfig> {
+        let mut endpoints: Vec<EndpointConfig> = self
+            .glean_instance
+            .endpoints
+            .iter()
+            .filter(|endpoint| !endpoint.expected_tools.is_empty())
+            .cloned()
+            .collect();
+        if !endpoints.iter().any(|endpoint| endpoint.name == "default") {
+            let default = self
+                .glean_instance
+                .endpoints
+                .iter()
+                .find(|endpoint| endpoint.name == "default")
+                .cloned()
+                .unwrap_or_else(|| EndpointConfig::named("default"));
+            endpoints.insert(
+                0,
+                EndpointConfig {
+                    // Core tools are listed by their host-side names (`glean_search`)
+                    expected_tools: if self.profile.is_generic() {
+                        self.profile.tools_in("core")
+                    } else {
+                        self.tools_to_test
+                            .core_tools
+                            .iter()
+                            .map(|tool| tool.trim_start_matches("glean_").to_string())
+                            .collect()
+                    },
+                    ..default
+                },
+            );
+        }
+        endpoints
+    }
+
+    /// `tools/call` arguments per tool: the profile's, overridden by `tools_to_test.arguments`
+    #[must_use]
+    pub fn tool_arguments(&self) -> BTreeMap<String, serde_json::Value> {
+        let mut arguments = self.profile.arguments();
+        arguments.extend(self.tools_to_test.arguments.clone());
+        arguments
+    }
+
+    /// Tags per tool: `tools_to_test.tags`, overridden by the profile's
+    #[must_use]
+    pub fn tool_tags(&self) -> BTreeMap<String, Vec<String>> {
+        let mut tags = self.tools_to_test.tags.clone();
+        tags.extend(self.profile.tags());
+        tags
+    }
+
+    /// Problems that would make commands fail at runtime; empty when valid
+    #[must_use]
+    pub fn validate(&self) -> Vec<String> {
+        self.diagnostics()
+            .iter()
+            .filter(|diagnostic| diagnostic.severity == Severity::Error)
+            .map(ToString::to_string)
+            .collect()
+    }
+
+    /// [`Self::validate`]'s problems as located diagnostics, without the outside-world checks of [`Self::check`]
+    #[must_use]
+    pub fn diagnostics(&self) -> Vec<ConfigDiagnostic> {
+        let mut problems = Vec::new();
+
+        for (label, url) in [
+            ("glean_instance.server_url", &self.glean_instance.server_url),
+            (
+                "glean_instance.chatgpt_url",
+                &self.glean_instance.chatgpt_url,
+            ),
+        ] {
+            if !url.starts_with("http://") && !url.starts_with("https://") {
+                problems.push(
+                    ConfigDiagnostic::error(label, format!("must be an http(s) URL, got '{url}'"))
+                        .with_hint("e.g. https://acme-be.glean.com/mcp/default"),
+                );
+            }
+        }
+        if let Some(problem) = url_template_problem(&self.glean_instance.url_template) {
+            problems.push(
+                ConfigDiagnostic::error("glean_instance.url_template", problem)
+                    .with_hint(format!("e.g. {DEFAULT_URL_TEMPLATE}")),
+            );
+        }
+        let mut endpoint_names = BTreeSet::new();
+        for (i, endpoint) in self.glean_instance.endpoints.iter().enumerate() {
+            let label = format!("glean_instance.endpoints[{i}]");
+            if endpoint.name.trim().is_empty() {
+                problems.push(ConfigDiagnostic::error(
+                    format!("{label}.name"),
+                    "must not be empty",
+                ));
+            } else if !endpoint_names.insert(endpoint.name.as_str()) {
+                problems.push(ConfigDiagnostic::error(
+                    format!("{label}.name"),
+                    format!("'{}' is listed more than once", endpoint.name),
+                ));
+            }
+            if let Some(url) = endpoint
+                .url
+                .as_ref()
+                .filter(|url| !url.starts_with("http://") && !url.starts_with("https://"))
+            {
+                problems.push(ConfigDiagnostic::error(
+                    format!("{label}.url"),
+                    format!("must be an http(s) URL, got '{url}'"),
+                ));
+            }
+            if endpoint.exact_tools && endpoint.expected_tools.is_empty() {
+                problems.push(ConfigDiagnostic::error(
+                    format!("{label}.exact_tools"),
+                    "needs expected_tools to list the endpoint's tools",
+                ));
+            }
+        }
+        if self.glean_instance.chatgpt_tools.is_empty() {
+            problems.push(ConfigDiagnostic::error(
+                "glean_instance.chatgpt_tools",
+                "must list at least one tool",
+            ));
+        }
+        if let Some(version) = &self.mcp_inspector.version
+            && !is_version_pin(version)
+        {
+            problems.push(
+                ConfigDiagnostic::error(
+                    "mcp_inspector.version",
+                    format!("'{version}' is not a version pin"),
+                )
+                .with_hint("use up to three dot-separated numbers or x, e.g. 0.10.x"),
+            );
+        }
+        if self.monitor.history_dir.trim().is_empty() {
+            problems.push(ConfigDiagnostic::error(
+                "monitor.history_dir",
+                "must not be empty",
+            ));
+        }
+        let issues = &self.monitor.issues;
+        if issues.after_failures == 0 {
+            problems.push(ConfigDiagnostic::error(
+                "monitor.issues.after_failures",
+                "must be at least 1",
+            ));
+        }
+        match issues.provider {
+            Some(IssueProvider::Github)
+                if issues
+                    .github
+                    .repo
+                    .split('/')
+                    .filter(|part| !part.is_empty())
+                    .count()
+                    != 2 =>
+            {
+                problems.push(ConfigDiagnostic::error(
+                    "monitor.issues.github.repo",
+                    "must name a repository as owner/name",
+                ));
+            }
+            Some(IssueProvider::Jira) => {
+                for (field, value) in [
+                    ("base_url", &issues.jira.base_url),
+                    ("project", &issues.jira.project),
+                ] {
+                    if value.trim().is_empty() {
+                        problems.push(ConfigDiagnostic::error(
+                            format!("monitor.issues.jira.{field}"),
+                            "is required to file Jira tickets",
+                        ));
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        for (tool, limit) in &self.tools_to_test.max_concurrent {
+            if *limit == 0 {
+                problems.push(ConfigDiagnostic::error(
+                    format!("tools_to_test.max_concurrent.{tool}"),
+                    "must be at least 1",
+                ));
+            }
+        }
+
+        for (tool, arguments) in &self.tools_to_test.arguments {
+            if !arguments.is_object() {
+                problems.push(ConfigDiagnostic::error(
+                    format!("tools_to_test.arguments.{tool}"),
+                    "must be a mapping of argument names to values",
+                ));
+            }
+            if let Err(e) = validate_templates(arguments) {
+                problems.push(
+                    ConfigDiagnostic::error(format!("tools_to_test.arguments.{tool}"), e)
+                        .with_hint("e.g. url: \"{{search.results[0].url}}\""),
+                );
+            }
+        }
+
+        let dependencies = self.tools_to_test.dependency_graph();
+        for tool in dependencies.keys() {
+            if depends_on_itself(&dependencies, tool) {
+                let section = if self.tools_to_test.depends_on.contains_key(tool) {
+                    "depends_on"
+                } else {
+                    "arguments"
+                };
+                problems.push(
+                    ConfigDiagnostic::error(
+                        format!("tools_to_test.{section}.{tool}"),
+                        "forms a dependency cycle",
+                    )
+                    .with_hint(
+                        "tools in a cycle never wait for each other; remove one of the edges",
+                    ),
+                );
+            }
+        }
+
+        for (tool, queries) in &self.corpus.queries {
+            if queries.iter().any(|query| query.trim().is_empty()) {
+                problems.push(ConfigDiagnostic::error(
+                    format!("corpus.queries.{tool}"),
+                    "must not contain empty queries",
+                ));
+            }
+            if self.tools_to_test.arguments.contains_key(tool) {
+                problems.push(
+                    ConfigDiagnostic::warning(
+                        format!("corpus.queries.{tool}"),
+                        "ignored: tools_to_test.arguments sets this tool's arguments",
+                    )
+                    .with_hint("remove one of the two"),
+                );
+            }
+        }
+        if let Some(file) = &self.corpus.file
+            && let Err(e) = load_corpus_file(Path::new(file))
+        {
+            let message = match e {
+                GleanMcpError::Config(message) => message,
+                other => other.to_string(),
+            };
+            problems.push(
+                ConfigDiagnostic::error("corpus.file", message)
+                    .with_hint("use YAML (tool: [queries]) or CSV (tool,query rows)"),
+            );
+        }
+
+        for (tool, budget) in &self.slo.latency_budgets {
+            if budget.0 == 0 {
+                problems.push(ConfigDiagnostic::error(
+                    format!("slo.latency_budgets.{tool}"),
+                    "must be greater than 0",
+                ));
+            }
+        }
+
+        let threshold = self.duplicate_check.similarity_threshold;
+        if !(threshold > 0.0 && threshold <= 1.0) {
+            problems.push(ConfigDiagnostic::error(
+                "duplicate_check.similarity_threshold",
+                format!("must be between 0 and 1, got {threshold}"),
+            ));
+        }
+
+        for (index, domain) in self.result_check.allowed_domains.iter().enumerate() {
+            if domain.trim().is_empty() || domain.contains(['/', ':', '@']) {
+                problems.push(
+                    ConfigDiagnostic::error(
+                        format!("result_check.allowed_domains[{index}]"),
+                        format!("'{domain}' is not a domain"),
+                    )
+                    .with_hint("use a bare domain such as glean.com; subdomains match too"),
+                );
+            }
+        }
+
+        if self.snapshot.dir.trim().is_empty() {
+            problems.push(ConfigDiagnostic::error("snapshot.dir", "must not be empty"));
+        }
+        for (index, mask) in self.snapshot.masks.iter().enumerate() {
+            if let Err(e) = validate_mask(mask) {
+                problems.push(
+                    ConfigDiagnostic::error(format!("snapshot.masks[{index}]"), e)
+                        .with_hint("e.g. $..id, $.documents[*].updateTime"),
+                );
+            }
+        }
+
+        if self.response_size.max_bytes == 0 {
+            problems.push(ConfigDiagnostic::error(
+                "response_size.max_bytes",
+                "must be greater than 0",
+            ));
+        }
+        for (tool, limit) in &self.response_size.tool_max_bytes {
+            if *limit == 0 {
+                problems.push(ConfigDiagnostic::error(
+                    format!("response_size.tool_max_bytes.{tool}"),
+                    "must be greater than 0",
+                ));
+            }
+        }
+
+        if self.retention.keep_runs == Some(0) {
+            problems.push(ConfigDiagnostic::error(
+                "retention.keep_runs",
+                "must be at least 1",
+            ));
+        }
+        if self
+            .retention
+            .paths
+            .iter()
+            .any(|path| path.trim().is_empty())
+        {
+            problems.push(ConfigDiagnostic::error(
+                "retention.paths",
+                "must not contain empty paths",
+            ));
+        }
+
+        if self
+            .proxy
+            .url
+            .as_deref()
+            .is_some_and(|url| url.trim().is_empty())
+        {
+            problems.push(ConfigDiagnostic::error("proxy.url", "must not be empty"));
+        }
+        for problem in self.tls.problems() {
+            let (path, message) = problem.split_once(' ').unwrap_or(("tls", &problem));
+            problems.push(ConfigDiagnostic::error(path, message));
+        }
+        for (name, value) in &self.headers {
+            if let Err(GleanMcpError::Config(message)) = validate_header(name, value) {
+                problems.push(ConfigDiagnostic::error(format!("headers.{name}"), message));
+            }
+        }
+
+        for language in &self.i18n.languages {
+            for tool in &self.i18n.tools {
+                if self.i18n.query_for(language, tool).is_none() {
+                    problems.push(
+                        ConfigDiagnostic::error(
+                            "i18n.languages",
+                            format!("no built-in '{language}' query for {tool}"),
+                        )
+                        .with_hint(format!("add i18n.queries.{language}.{tool}")),
+                    );
+                }
+            }
+        }
+        let confidence = self.i18n.min_confidence;
+        if !(0.0..=1.0).contains(&confidence) {
+            problems.push(ConfigDiagnostic::error(
+                "i18n.min_confidence",
+                format!("must be between 0 and 1, got {confidence}"),
+            ));
+        }
+
+        for (index, rule) in self.tools_to_test.query_rules.iter().enumerate() {
+            if let Err(e) = regex::Regex::new(&rule.pattern) {
+                problems.push(ConfigDiagnostic::error(
+                    format!("tools_to_test.query_rules[{index}].pattern"),
+                    format!("'{}' is not a valid regular expression: {e}", rule.pattern),
+                ));
+            }
+            if rule.query.trim().is_empty() {
+                problems.push(ConfigDiagnostic::error(
+                    format!("tools_to_test.query_rules[{index}].query"),
+                    "must not be empty",
+                ));
+            }
+        }
+
+        for (field, value) in [
+            (
+                "bench.max_p95_increase_percent",
+                self.bench.max_p95_increase_percent,
+            ),
+            (
+                "bench.max_error_rate_increase",
+                self.bench.max_error_rate_increase,
+            ),
+        ] {
+            if !value.is_finite() || value < 0.0 {
+                problems.push(ConfigDiagnostic::error(
+                    field,
+                    format!("must be zero or more, got {value}"),
+                ));
+            }
+        }
+
+        if self.profile.is_generic() && self.profile.tools.is_empty() {
+            problems.push(
+                ConfigDiagnostic::warning("profile.tools", "a generic profile defines no tools")
+                    .with_hint(
+                        "only tools named with --tools or selected by --tools all are tested",
+                    ),
+            );
+        }
+        for (name, tool) in &self.profile.tools {
+            let label = format!("profile.tools.{name}");
+            if !TOOL_CATEGORIES.contains(&tool.category.as_str()) {
+                problems.push(
+                    ConfigDiagnostic::error(
+                        format!("{label}.category"),
+                        format!("unknown category '{}'", tool.category),
+                    )
+                    .with_hint(format!("one of {}", TOOL_CATEGORIES.join(", "))),
+                );
+            }
+            if let Some(arguments) = &tool.arguments {
+                if !arguments.is_object() {
+                    problems.push(ConfigDiagnostic::error(
+                        format!("{label}.arguments"),
+                        "must be a mapping of argument names to values",
+                    ));
+                }
+                if let Err(e) = validate_templates(arguments) {
+                    problems.push(ConfigDiagnostic::error(format!("{label}.arguments"), e));
+                }
+                if tool.query.is_some() {
+                    problems.push(ConfigDiagnostic::warning(
+                        format!("{label}.query"),
+                        "ignored: arguments replace the query",
+                    ));
+                }
+            }
+            for pattern in &tool.expect.matches {
+                if let Err(e) = regex::Regex::new(pattern) {
+                    problems.push(ConfigDiagnostic::error(
+                        format!("{label}.expect.matches"),
+                        format!("'{pattern}' is not a valid regular expression: {e}"),
+                    ));
+                }
+            }
+        }
+
+        let detectors = pii_detectors();
+        for detector in &self.pii_scan.detectors {
+            if !detectors.contains(detector) {
+                problems.push(
+                    ConfigDiagnostic::error(
+                        "pii_scan.detectors",
+                        format!("unknown detector '{detector}'"),
+                    )
+                    .with_hint(format!("built-in detectors: {}", detectors.join(", "))),
+                );
+            }
+        }
+        for (name, pattern) in &self.pii_scan.patterns {
+            if let Err(e) = regex::Regex::new(pattern) {
+                problems.push(ConfigDiagnostic::error(
+                    format!("pii_scan.patterns.{name}"),
+                    format!("'{pattern}' is not a valid regular expression: {e}"),
+                ));
+            }
+        }
+
+        for pattern in &self.redaction.patterns {
+            if let Err(e) = regex::Regex::new(pattern) {
+                problems.push(ConfigDiagnostic::error(
+                    "redaction.patterns",
+                    format!("'{pattern}' is not a valid regular expression: {e}"),
+                ));
+            }
+        }
+
+        if self.agents.status_tool.trim().is_empty() {
+            problems.push(ConfigDiagnostic::error(
+                "agents.status_tool",
+                "must name the tool that reports an agent run's status",
+            ));
+        }
+        if self.agents.max_agents == 0 {
+            problems.push(ConfigDiagnostic::error(
+                "agents.max_agents",
+                "must be at least 1",
+            ));
+        }
+        if self.agents.max_wait_secs < self.agents.poll_interval_secs {
+            problems.push(ConfigDiagnostic::error(
+                "agents.max_wait_secs",
+                format!(
+                    "must be at least agents.poll_interval_secs ({}s)",
+                    self.agents.poll_interval_secs
+                ),
+            ));
+        }
+
+        let mut pair_names = std::collections::HashSet::new();
+        for pair in &self.permissions.pairs {
+            let path = format!("permissions.pairs[{}]", pair.name);
+            if !pair_names.insert(pair.name.as_str()) {
+                problems.push(ConfigDiagnostic::error(&path, "duplicate pair name"));
+            }
+            if pair.privileged_token_env.trim().is_empty()
+                || pair.restricted_token_env.trim().is_empty()
+            {
+                problems.push(ConfigDiagnostic::error(
+                    &path,
+                    "must name both token environment variables",
+                ));
+            }
+            if pair.queries.is_empty() {
+                problems.push(ConfigDiagnostic::error(
+                    format!("{path}.queries"),
+                    "must list at least one query",
+                ));
+            }
+        }
+
+        if let Err(GleanMcpError::Config(message)) = validate_header(&self.act_as.header, "") {
+            problems.push(ConfigDiagnostic::error("act_as.header", message));
+        }
+        for (index, user) in self.act_as.users.iter().enumerate() {
+            if user.trim().is_empty() || user.contains(['\r', '\n']) {
+                problems.push(ConfigDiagnostic::error(
+                    format!("act_as.users[{index}]"),
+                    "must be a user's email address",
+                ));
+            }
+        }
+        if self.act_as.tools.is_empty() {
+            problems.push(ConfigDiagnostic::error(
+                "act_as.tools",
+                "must list at least one tool",
+            ));
+        }
+
+        let mut names = std::collections::HashSet::new();
+        for schedule in &self.monitor.schedules {
+            let path = format!("monitor.schedules[{}]", schedule.name);
+            if !names.insert(schedule.name.as_str()) {
+                problems.push(ConfigDiagnostic::error(&path, "duplicate schedule name"));
+            }
+            if let Err(e) = crate::parse_cron(&schedule.cron) {
+                let message = match e {
+                    GleanMcpError::Config(message) => message,
+                    other => other.to_string(),
+                };
+                let message = message.lines().next().unwrap_or_default().to_string();
+                problems.push(
+                    ConfigDiagnostic::error(format!("{path}.cron"), message).with_hint(
+                        "use 5 fields (minute hour day month weekday), e.g. \"0 */6 * * *\"",
+                    ),
+                );
+            }
+            if schedule.suite == "hosts" && schedule.hosts.is_empty() {
+                problems.push(ConfigDiagnostic::error(
+                    format!("{path}.hosts"),
+                    "the hosts suite needs at least one host",
+                ));
+            }
+        }
+
+        problems
+    }
+}
+
 impl Default for GleanConfig {
     fn default() -> Self {
         let mut host_applications = HashMap::new();
@@ -102,9 +1245,13 @@ impl Default for GleanConfig {
                 environment: "production".to_string(),
                 server_url: "https://scio-prod.glean.com/mcp/default".to_string(),
                 chatgpt_url: "https://scio-prod.glean.com/mcp/chatgpt".to_string(),
+                chatgpt_tools: default_chatgpt_tools(),
+                url_template: default_url_template(),
+                endpoints: default_endpoints(),
             },
             mcp_inspector: McpInspectorConfig {
                 package: "@modelcontextprotocol/inspector".to_string(),
+                version: None,
                 validation_required: true,
                 tools_to_validate: vec![
                     "glean_search".to_string(),
@@ -120,6 +1267,9 @@ impl Default for GleanConfig {
                     "TOOLS".to_string(),
                     "ENTITIES".to_string(),
                 ],
+                max_clock_skew_secs: default_max_clock_skew_secs(),
+                token_env: default_token_env(),
+                instance_token_env: BTreeMap::new(),
             },
             tools_to_test: ToolsConfig {
                 core_tools: vec![
@@ -135,8 +1285,36 @@ impl Default for GleanConfig {
                     "outlook_search".to_string(),
                     "web_browser".to_string(),
                 ],
+                max_concurrent: default_tool_concurrency(),
+                arguments: BTreeMap::new(),
+                tags: default_tool_tags(),
+                exclude: Vec::new(),
+                depends_on: BTreeMap::new(),
+                query_rules: Vec::new(),
             },
             host_applications,
+            monitor: MonitorConfig::default(),
+            baseline: BaselineConfig::default(),
+            slo: SloConfig::default(),
+            language_check: LanguageCheckConfig::default(),
+            duplicate_check: DuplicateCheckConfig::default(),
+            result_check: ResultCheckConfig::default(),
+            snapshot: SnapshotConfig::default(),
+            response_size: ResponseSizeConfig::default(),
+            pii_scan: PiiScanConfig::default(),
+            bench: BenchConfig::default(),
+            retention: RetentionConfig::default(),
+            proxy: ProxyConfig::default(),
+            tls: TlsConfig::default(),
+            headers: BTreeMap::new(),
+            permissions: PermissionCheckConfig::default(),
+            act_as: ActAsConfig::default(),
+            corpus: CorpusConfig::default(),
+            i18n: I18nConfig::default(),
+            redaction: RedactionConfig::default(),
+            agents: AgentCheckConfig::default(),
+            profile: ToolProfileConfig::default(),
+            source: None,
         }
     }
 }
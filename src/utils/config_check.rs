@@ -0,0 +1,649 @@
+//! Located diagnostics for `config validate`
+//!
+//! [`GleanConfig::check`] runs the rules behind [`GleanConfig::validate`] plus
+//! checks that look outside the file: keys serde would silently ignore,
+//! server hostnames that don't resolve, missing host config and TLS files,
+//! tool names the framework doesn't know, and auth settings that contradict
+//! each other. Diagnostics carry the dotted config path and, when the config
+//! came from a file, the line it's on.
+
+use crate::{
+    AuthStrategy, DEFAULT_TOKEN_ENV, GleanConfig, KNOWN_TOOLS, expand_home, parse_auth_strategy,
+    token_from_env,
+};
+use serde::{Deserialize, Serialize};
+use serde_yaml::Value;
+use std::collections::BTreeSet;
+use std::fmt::{self, Write};
+use std::net::ToSocketAddrs;
+use std::path::PathBuf;
+
+/// Values accepted for `authentication.method`
+const AUTH_METHODS: [&str; 2] = ["oauth", "token"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    /// Commands will fail or misbehave
+    Error,
+    /// Probably a mistake, but commands can still run
+    Warning,
+}
+
+/// One problem in the configuration
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConfigDiagnostic {
+    pub severity: Severity,
+    /// Dotted config path, e.g. `glean_instance.server_url` or `monitor.schedules[nightly].cron`
+    pub path: String,
+    /// 1-based line in the config file, when known
+    pub line: Option<usize>,
+    pub message: String,
+    /// How to fix it
+    pub hint: Option<String>,
+}
+
+impl ConfigDiagnostic {
+    #[must_use]
+    pub fn error(path: impl Into<String>, message: impl Into<String>) -> Self {
+        Self::new(Severity::Error, path.into(), message.into())
+    }
+
+    #[must_use]
+    pub fn warning(path: impl Into<String>, message: impl Into<String>) -> Self {
+        Self::new(Severity::Warning, path.into(), message.into())
+    }
+
+    const fn new(severity: Severity, path: String, message: String) -> Self {
+        Self {
+            severity,
+            path,
+            line: None,
+            message,
+            hint: None,
+        }
+    }
+
+    #[must_use]
+    pub fn with_hint(mut self, hint: impl Into<String>) -> Self {
+        self.hint = Some(hint.into());
+        self
+    }
+}
+
+impl fmt::Display for ConfigDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+/// Everything `config validate` found, errors first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigReport {
+    /// Config file that was checked; `None` for the built-in defaults
+    pub source: Option<PathBuf>,
+    pub diagnostics: Vec<ConfigDiagnostic>,
+}
+
+impl ConfigReport {
+    #[must_use]
+    pub fn errors(&self) -> usize {
+        self.count(Severity::Error)
+    }
+
+    #[must_use]
+    pub fn warnings(&self) -> usize {
+        self.count(Severity::Warning)
+    }
+
+    fn count(&self, severity: Severity) -> usize {
+        self.diagnostics
+            .iter()
+            .filter(|diagnostic| diagnostic.severity == severity)
+            .count()
+    }
+
+    #[must_use]
+    pub fn format_text(&self) -> String {
+        let mut output = String::new();
+        for diagnostic in &self.diagnostics {
+            let (icon, label) = match diagnostic.severity {
+                Severity::Error => ("❌", "error"),
+                Severity::Warning => ("⚠️ ", "warning"),
+            };
+            let _ = writeln!(output, "{icon} {label}: {diagnostic}");
+            if let Some(file) = &self.source {
+                let _ = match diagnostic.line {
+                    Some(line) => writeln!(output, "   --> {}:{line}", file.display()),
+                    None => writeln!(output, "   --> {}", file.display()),
+                };
+            }
+            if let Some(hint) = &diagnostic.hint {
+                let _ = writeln!(output, "   💡 {hint}");
+            }
+        }
+        output
+    }
+}
+
+impl GleanConfig {
+    /// Check the configuration, resolving server hostnames unless `offline`
+    #[must_use]
+    pub fn check(&self, offline: bool) -> ConfigReport {
+        let source_text = self
+            .source
+            .as_ref()
+            .and_then(|path| std::fs::read_to_string(path).ok());
+
+        let mut diagnostics = self.diagnostics();
+        if let Some(text) = &source_text {
+            diagnostics.extend(self.unknown_keys(text));
+        }
+        if !offline {
+            diagnostics.extend(self.unresolved_hosts());
+        }
+        diagnostics.extend(self.missing_files());
+        diagnostics.extend(self.unknown_tools());
+        diagnostics.extend(self.auth_problems());
+
+        if let Some(text) = &source_text {
+            for diagnostic in &mut diagnostics {
+                diagnostic.line = locate(text, &diagnostic.path);
+            }
+        }
+        diagnostics.sort_by_key(|diagnostic| (diagnostic.severity, diagnostic.line));
+        ConfigReport {
+            source: self.source.clone(),
+            diagnostics,
+        }
+    }
+
+    /// Keys in the file that deserialization ignored, found by diffing it against the parsed config
+    fn unknown_keys(&self, text: &str) -> Vec<ConfigDiagnostic> {
+        let (Ok(written), Ok(parsed)) = (
+            serde_yaml::from_str::<Value>(text),
+            serde_yaml::to_value(self),
+        ) else {
+            return Vec::new();
+        };
+        let mut diagnostics = Vec::new();
+        diff_keys(&written, &parsed, "", &mut diagnostics);
+        diagnostics
+    }
+
+    fn unresolved_hosts(&self) -> Vec<ConfigDiagnostic> {
+        let mut urls = vec![
+            (
+                "glean_instance.server_url".to_string(),
+                self.glean_instance.server_url.clone(),
+            ),
+            (
+                "glean_instance.chatgpt_url".to_string(),
+                self.glean_instance.chatgpt_url.clone(),
+            ),
+        ];
+        let mut hosts: Vec<_> = self.host_applications.iter().collect();
+        hosts.sort_by_key(|(name, _)| *name);
+        for (name, host) in hosts {
+            urls.push((
+                format!("host_applications.{name}.server_url"),
+                host.server_url.clone(),
+            ));
+        }
+        if let Some(proxy) = &self.proxy.url {
+            urls.push(("proxy.url".to_string(), proxy.clone()));
+        }
+
+        let mut checked = BTreeSet::new();
+        let mut diagnostics = Vec::new();
+        for (path, url) in urls {
+            let Some((host, port)) = host_and_port(&url) else {
+                continue;
+            };
+            if !checked.insert((host.clone(), port)) {
+                continue;
+            }
+            if (host.as_str(), port).to_socket_addrs().is_ok() {
+                continue;
+            }
+            // Behind a proxy, only the proxy itself has to resolve locally
+            let via_proxy = self.proxy.url.as_deref().is_some_and(|proxy| proxy != url);
+            diagnostics.push(if via_proxy {
+                ConfigDiagnostic::warning(path, format!("host '{host}' does not resolve locally"))
+                    .with_hint("the proxy may still resolve it")
+            } else {
+                ConfigDiagnostic::error(path, format!("host '{host}' does not resolve"))
+                    .with_hint("check the instance name, or pass --offline to skip DNS checks")
+            });
+        }
+        diagnostics
+    }
+
+    fn missing_files(&self) -> Vec<ConfigDiagnostic> {
+        let mut diagnostics = Vec::new();
+        let mut hosts: Vec<_> = self.host_applications.iter().collect();
+        hosts.sort_by_key(|(name, _)| *name);
+        for (name, host) in hosts {
+            if let Some(path) = &host.mcp_config_path
+                && !expand_home(path).exists()
+            {
+                diagnostics.push(
+                    ConfigDiagnostic::warning(
+                        format!("host_applications.{name}.mcp_config_path"),
+                        format!("{path} does not exist"),
+                    )
+                    .with_hint(format!(
+                        "is {name} installed? `host configure` creates the file when it's missing"
+                    )),
+                );
+            }
+        }
+        for (path, file) in [
+            ("tls.ca_cert", &self.tls.ca_cert),
+            ("tls.client_cert", &self.tls.client_cert),
+            ("tls.client_key", &self.tls.client_key),
+        ] {
+            if let Some(file) = file
+                && !file.as_os_str().is_empty()
+                && !file.exists()
+            {
+                diagnostics.push(ConfigDiagnostic::error(
+                    path,
+                    format!("{} does not exist", file.display()),
+                ));
+            }
+        }
+        diagnostics
+    }
+
+    fn unknown_tools(&self) -> Vec<ConfigDiagnostic> {
+        let tools = &self.tools_to_test;
+        let mut named: Vec<(String, &str)> = Vec::new();
+        for (list, names) in [
+            ("tools_to_test.core_tools", &tools.core_tools),
+            ("tools_to_test.enterprise_tools", &tools.enterprise_tools),
+            (
+                "mcp_inspector.tools_to_validate",
+                &self.mcp_inspector.tools_to_validate,
+            ),
+            (
+                "glean_instance.chatgpt_tools",
+                &self.glean_instance.chatgpt_tools,
+            ),
+            ("i18n.tools", &self.i18n.tools),
+            ("i18n.match_language", &self.i18n.match_language),
+        ] {
+            named.extend(names.iter().map(|name| (list.to_string(), name.as_str())));
+        }
+        for (map, names) in [
+            (
+                "tools_to_test.max_concurrent",
+                tools.max_concurrent.keys().collect::<Vec<_>>(),
+            ),
+            ("tools_to_test.arguments", tools.arguments.keys().collect()),
+            ("tools_to_test.tags", tools.tags.keys().collect()),
+            ("corpus.queries", self.corpus.queries.keys().collect()),
+            (
+                "slo.latency_budgets",
+                self.slo.latency_budgets.keys().collect(),
+            ),
+        ] {
+            named.extend(
+                names
+                    .into_iter()
+                    .map(|name| (format!("{map}.{name}"), name.as_str())),
+            );
+        }
+        for pair in &self.permissions.pairs {
+            named.push((
+                format!("permissions.pairs[{}].tool", pair.name),
+                pair.tool.as_str(),
+            ));
+        }
+
+        // Glean tool names stay known under a generic profile: the defaults name them
+        let known: Vec<&str> = KNOWN_TOOLS
+            .into_iter()
+            .chain(
+                self.profile
+                    .tools
+                    .keys()
+                    .map(String::as_str)
+                    .filter(|name| !KNOWN_TOOLS.contains(name)),
+            )
+            .collect();
+        named
+            .into_iter()
+            .filter(|(_, name)| !known.contains(&name.trim_start_matches("glean_")))
+            .map(|(path, name)| {
+                let diagnostic = ConfigDiagnostic::warning(path, format!("unknown tool '{name}'"));
+                match closest(name, &known) {
+                    Some(suggestion) => {
+                        diagnostic.with_hint(format!("did you mean '{suggestion}'?"))
+                    }
+                    None => diagnostic.with_hint(format!(
+                        "known tools: {}; servers may still expose others, or define them under profile.tools",
+                        known.join(", ")
+                    )),
+                }
+            })
+            .collect()
+    }
+
+    fn auth_problems(&self) -> Vec<ConfigDiagnostic> {
+        let mut diagnostics = Vec::new();
+        let auth = &self.authentication;
+        if !AUTH_METHODS.contains(&auth.method.as_str()) {
+            diagnostics.push(
+                ConfigDiagnostic::error(
+                    "authentication.method",
+                    format!("unknown method '{}'", auth.method),
+                )
+                .with_hint(format!("use one of: {}", AUTH_METHODS.join(", "))),
+            );
+        }
+        if auth.method == "oauth" && auth.oauth_scopes.is_empty() {
+            diagnostics.push(
+                ConfigDiagnostic::error(
+                    "authentication.oauth_scopes",
+                    "oauth needs at least one scope",
+                )
+                .with_hint("the default scopes are MCP, SEARCH, TOOLS, and ENTITIES"),
+            );
+        }
+        if auth.token_env.is_empty() {
+            diagnostics.push(
+                ConfigDiagnostic::error(
+                    "authentication.token_env",
+                    "no environment variables to read the auth token from",
+                )
+                .with_hint(format!("the default is [{DEFAULT_TOKEN_ENV}]")),
+            );
+        }
+        let named = auth
+            .token_env
+            .iter()
+            .enumerate()
+            .map(|(index, variable)| (format!("authentication.token_env[{index}]"), variable))
+            .chain(
+                auth.instance_token_env
+                    .iter()
+                    .flat_map(|(instance, variables)| {
+                        variables.iter().enumerate().map(move |(index, variable)| {
+                            (
+                                format!("authentication.instance_token_env.{instance}[{index}]"),
+                                variable,
+                            )
+                        })
+                    }),
+            );
+        for (path, variable) in named {
+            if variable.is_empty()
+                || !variable
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_')
+            {
+                diagnostics.push(
+                    ConfigDiagnostic::error(
+                        path,
+                        format!("'{variable}' is not an environment variable name"),
+                    )
+                    .with_hint("use letters, digits, and underscores, e.g. GLEAN_API_TOKEN"),
+                );
+            }
+        }
+        let variables = auth.token_variables(&self.glean_instance.name);
+        if !variables
+            .iter()
+            .any(|variable| token_from_env(variable).is_some())
+        {
+            diagnostics.push(
+                ConfigDiagnostic::warning(
+                    "authentication",
+                    format!(
+                        "none of {} is set, so server tests will run unauthenticated",
+                        variables.join(", ")
+                    ),
+                )
+                .with_hint(format!(
+                    "export {}, or run `auth` to check your setup",
+                    auth.token_env
+                        .first()
+                        .map_or(DEFAULT_TOKEN_ENV, String::as_str)
+                )),
+            );
+        }
+
+        let mut hosts: Vec<_> = self.host_applications.iter().collect();
+        hosts.sort_by_key(|(name, _)| *name);
+        for (name, host) in hosts {
+            let path = format!("host_applications.{name}");
+            if parse_auth_strategy(&host.auth_method).is_err() {
+                let methods: Vec<&str> = AuthStrategy::ALL.map(AuthStrategy::as_str).to_vec();
+                diagnostics.push(
+                    ConfigDiagnostic::error(
+                        format!("{path}.auth_method"),
+                        format!("unknown auth method '{}'", host.auth_method),
+                    )
+                    .with_hint(format!("use one of: {}", methods.join(", "))),
+                );
+            }
+            if parse_auth_strategy(&host.auth_method).ok() == Some(AuthStrategy::Bridge)
+                && host.mcp_config_path.is_none()
+            {
+                diagnostics.push(
+                    ConfigDiagnostic::error(
+                        format!("{path}.auth_method"),
+                        "bridge auth needs an mcp_config_path to write the mcp-remote entry into",
+                    )
+                    .with_hint("set mcp_config_path, or use native auth"),
+                );
+            }
+        }
+
+        for pair in &self.permissions.pairs {
+            for variable in [&pair.privileged_token_env, &pair.restricted_token_env] {
+                if !variable.trim().is_empty() && std::env::var(variable).is_err() {
+                    diagnostics.push(ConfigDiagnostic::warning(
+                        format!("permissions.pairs[{}]", pair.name),
+                        format!("{variable} is not set, so `test --permissions` will fail"),
+                    ));
+                }
+            }
+        }
+        diagnostics
+    }
+}
+
+/// Report keys of `written` missing from `parsed`, recursing into mappings and lists both have
+fn diff_keys(written: &Value, parsed: &Value, path: &str, diagnostics: &mut Vec<ConfigDiagnostic>) {
+    if let (Value::Sequence(written), Value::Sequence(parsed)) = (written, parsed) {
+        for (index, (written, parsed)) in written.iter().zip(parsed).enumerate() {
+            let item = written
+                .get("name")
+                .and_then(Value::as_str)
+                .map_or_else(|| index.to_string(), ToString::to_string);
+            diff_keys(written, parsed, &format!("{path}[{item}]"), diagnostics);
+        }
+        return;
+    }
+    let (Value::Mapping(written), Value::Mapping(parsed)) = (written, parsed) else {
+        return;
+    };
+    let known: Vec<&str> = parsed.keys().filter_map(Value::as_str).collect();
+    for (key, value) in written {
+        let Some(key) = key.as_str() else {
+            continue;
+        };
+        let child = if path.is_empty() {
+            key.to_string()
+        } else {
+            format!("{path}.{key}")
+        };
+        if let Some(parsed_value) = parsed.get(key) {
+            diff_keys(value, parsed_value, &child, diagnostics);
+            continue;
+        }
+        let diagnostic = ConfigDiagnostic::warning(child, "unknown key, ignored");
+        diagnostics.push(match closest(key, &known) {
+            Some(suggestion) => diagnostic.with_hint(format!("did you mean '{suggestion}'?")),
+            None => diagnostic,
+        });
+    }
+}
+
+/// Host and port of an http(s) URL
+fn host_and_port(url: &str) -> Option<(String, u16)> {
+    let (scheme, rest) = url.split_once("://")?;
+    let default_port = if scheme == "https" { 443 } else { 80 };
+    let authority = rest.split(['/', '?', '#']).next()?;
+    let authority = authority
+        .rsplit_once('@')
+        .map_or(authority, |(_, host)| host);
+    let (host, port) = match authority.rsplit_once(':') {
+        Some((host, port)) if !host.ends_with(']') || authority.starts_with('[') => {
+            (host, port.parse().ok()?)
+        }
+        _ => (authority, default_port),
+    };
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    (!host.is_empty()).then(|| (host.to_string(), port))
+}
+
+/// The candidate within edit distance 2 of `name`, if any
+fn closest<'a>(name: &str, candidates: &[&'a str]) -> Option<&'a str> {
+    candidates
+        .iter()
+        .map(|candidate| (edit_distance(name, candidate), *candidate))
+        .filter(|(distance, _)| *distance <= 2)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// 1-based line of `path` in YAML `text`, or of its deepest ancestor that's present
+///
+/// Segments like `schedules[nightly]` match the list item whose `name` is `nightly`.
+fn locate(text: &str, path: &str) -> Option<usize> {
+    let lines: Vec<&str> = text.lines().collect();
+    let mut found = None;
+    let mut block = Block {
+        start: 0,
+        parent: None,
+        items: false,
+    };
+    for segment in path.split('.') {
+        let (key, item) = match segment.split_once('[') {
+            Some((key, item)) => (key, Some(item.trim_end_matches(']'))),
+            None => (segment, None),
+        };
+        let Some(index) = find_key(&lines, block, key) else {
+            break;
+        };
+        found = Some(index + 1);
+        block = Block {
+            start: index + 1,
+            parent: Some(indent(lines[index])),
+            items: true,
+        };
+        if let Some(item) = item {
+            let Some(index) = find_item(&lines, block, item) else {
+                break;
+            };
+            found = Some(index + 1);
+            block = Block {
+                start: index + 1,
+                parent: Some(indent(lines[index])),
+                items: false,
+            };
+        }
+    }
+    found
+}
+
+/// Lines nested under a key or list item
+#[derive(Clone, Copy)]
+struct Block {
+    start: usize,
+    /// Indentation of the owning line; `None` for the whole document
+    parent: Option<usize>,
+    /// Whether `- ` items at the parent's indentation belong to the block
+    items: bool,
+}
+
+impl Block {
+    /// Indices of the block's non-blank, non-comment lines
+    fn lines<'a>(self, lines: &'a [&str]) -> impl Iterator<Item = usize> + 'a {
+        (self.start..lines.len())
+            .filter(|&index| {
+                let trimmed = lines[index].trim_start();
+                !trimmed.is_empty() && !trimmed.starts_with('#')
+            })
+            .take_while(move |&index| {
+                self.parent.is_none_or(|parent| {
+                    let line_indent = indent(lines[index]);
+                    line_indent > parent
+                        || (self.items
+                            && line_indent == parent
+                            && lines[index].trim_start().starts_with("- "))
+                })
+            })
+    }
+}
+
+fn indent(line: &str) -> usize {
+    line.len() - line.trim_start().len()
+}
+
+/// Index of the `key:` line directly inside `block`
+fn find_key(lines: &[&str], block: Block, key: &str) -> Option<usize> {
+    let level = block
+        .lines(lines)
+        .next()
+        .map(|index| indent(lines[index]))?;
+    block
+        .lines(lines)
+        .filter(|&index| indent(lines[index]) == level)
+        .find(|&index| {
+            let content = lines[index].trim_start().trim_start_matches("- ");
+            [key.to_string(), format!("\"{key}\""), format!("'{key}'")]
+                .iter()
+                .any(|candidate| {
+                    content
+                        .strip_prefix(candidate.as_str())
+                        .is_some_and(|rest| rest.trim_start().starts_with(':'))
+                })
+        })
+}
+
+/// Index of the `- ` line starting the list item in `block` whose `name` is `name`
+fn find_item(lines: &[&str], block: Block, name: &str) -> Option<usize> {
+    let mut item = None;
+    for index in block.lines(lines) {
+        let trimmed = lines[index].trim_start();
+        if trimmed.starts_with("- ") {
+            item = Some(index);
+        }
+        let is_name = trimmed
+            .trim_start_matches("- ")
+            .strip_prefix("name:")
+            .is_some_and(|value| value.trim().trim_matches(|c| c == '"' || c == '\'') == name);
+        if is_name {
+            return item;
+        }
+    }
+    None
+}
@@ -0,0 +1,156 @@
+//! Minimal HTTP/1.1 server primitives
+//!
+//! Shared by the mock MCP server and the report server. Each connection
+//! carries a single request and is closed after the response.
+
+use crate::Result;
+use smol::io::{AsyncReadExt, AsyncWriteExt};
+use smol::net::TcpStream;
+use std::fmt::Write;
+
+/// Default limit on a request's headers and on its body
+pub const MAX_REQUEST_BYTES: usize = 1024 * 1024;
+
+/// A parsed HTTP request
+#[derive(Debug, Clone)]
+pub struct HttpRequest {
+    pub method: String,
+    pub path: String,
+    pub authorization: Option<String>,
+    /// `Last-Event-ID`, when the client is resuming an event stream
+    pub last_event_id: Option<String>,
+    /// Whether the client accepts a `text/event-stream` response
+    pub accepts_event_stream: bool,
+    pub body: Vec<u8>,
+}
+
+/// What a client sent on a connection
+#[derive(Debug, Clone)]
+pub enum Incoming {
+    Request(HttpRequest),
+    /// A body over the limit, read and discarded so the client sees the reply
+    /// instead of a reset while it is still uploading
+    TooLarge,
+}
+
+/// Read one request with a body of up to `max_bytes`; `None` when the client
+/// hung up or its headers were oversized
+pub async fn read_request(stream: &mut TcpStream, max_bytes: usize) -> Result<Option<Incoming>> {
+    let mut buffer = Vec::new();
+    let mut chunk = [0_u8; 4096];
+
+    let header_end = loop {
+        if let Some(pos) = buffer.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos + 4;
+        }
+        if buffer.len() > MAX_REQUEST_BYTES {
+            return Ok(None);
+        }
+        let read = stream.read(&mut chunk).await?;
+        if read == 0 {
+            return Ok(None);
+        }
+        buffer.extend_from_slice(&chunk[..read]);
+    };
+
+    let headers = String::from_utf8_lossy(&buffer[..header_end]).to_string();
+    let mut lines = headers.lines();
+    let mut request_line = lines.next().unwrap_or_default().split_whitespace();
+    let method = request_line.next().unwrap_or_default().to_string();
+    let path = request_line.next().unwrap_or("/").to_string();
+
+    let mut content_length = 0;
+    let mut authorization = None;
+    let mut last_event_id = None;
+    let mut accepts_event_stream = false;
+    for line in lines {
+        if let Some((name, value)) = line.split_once(':') {
+            let value = value.trim();
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.parse().unwrap_or(0);
+            } else if name.eq_ignore_ascii_case("authorization") {
+                authorization = Some(value.to_string());
+            } else if name.eq_ignore_ascii_case("last-event-id") {
+                last_event_id = Some(value.to_string());
+            } else if name.eq_ignore_ascii_case("accept") {
+                accepts_event_stream = value.contains("text/event-stream");
+            }
+        }
+    }
+    if content_length > max_bytes {
+        let mut remaining = content_length.saturating_sub(buffer.len() - header_end);
+        while remaining > 0 {
+            let read = stream.read(&mut chunk).await?;
+            if read == 0 {
+                break;
+            }
+            remaining = remaining.saturating_sub(read);
+        }
+        return Ok(Some(Incoming::TooLarge));
+    }
+
+    let mut body = buffer[header_end..].to_vec();
+    while body.len() < content_length {
+        let read = stream.read(&mut chunk).await?;
+        if read == 0 {
+            break;
+        }
+        body.extend_from_slice(&chunk[..read]);
+    }
+    body.truncate(content_length);
+
+    Ok(Some(Incoming::Request(HttpRequest {
+        method,
+        path,
+        authorization,
+        last_event_id,
+        accepts_event_stream,
+        body,
+    })))
+}
+
+/// Write a complete response and flush it
+pub async fn write_response(
+    stream: &mut TcpStream,
+    status: u16,
+    content_type: &str,
+    body: &str,
+) -> Result<()> {
+    write_response_with_headers(stream, status, content_type, &[], body).await
+}
+
+/// Write a complete response with additional headers and flush it
+pub async fn write_response_with_headers(
+    stream: &mut TcpStream,
+    status: u16,
+    content_type: &str,
+    headers: &[(&str, String)],
+    body: &str,
+) -> Result<()> {
+    let reason = match status {
+        200 => "OK",
+        202 => "Accepted",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        413 => "Content Too Large",
+        429 => "Too Many Requests",
+        500 => "Internal Server Error",
+        502 => "Bad Gateway",
+        503 => "Service Unavailable",
+        _ => "Status",
+    };
+    let mut extra_headers = String::new();
+    for (name, value) in headers {
+        let _ = write!(extra_headers, "{name}: {value}\r\n");
+    }
+    let date = chrono::Utc::now().format("%a, %d %b %Y %H:%M:%S GMT");
+    let response = format!(
+        "HTTP/1.1 {status} {reason}\r\nDate: {date}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\n{extra_headers}Connection: close\r\n\r\n{body}",
+        body.len()
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.flush().await?;
+    Ok(())
+}
@@ -1,3 +1,16 @@
 pub mod config;
+pub mod config_check;
+pub mod http_server;
+pub mod npm;
+pub mod output;
+pub mod platform;
+pub mod redact;
+pub mod token;
 
 pub use config::*;
+pub use config_check::*;
+pub use npm::*;
+pub use output::*;
+pub use platform::*;
+pub use redact::*;
+pub use token::*;
@@ -0,0 +1,37 @@
+//! Checking and installing the npm packages the framework runs through npx
+
+use crate::{GleanMcpError, Result, command};
+use serde_json::Value;
+
+/// Version of `package` installed globally with npm, if any
+#[must_use]
+pub fn installed_package_version(package: &str) -> Option<String> {
+    let output = command("npm")
+        .args(["ls", "--global", "--depth=0", "--json", package])
+        .output()
+        .ok()?;
+    // `npm ls` exits non-zero when the package is missing but still prints JSON
+    let listing: Value = serde_json::from_slice(&output.stdout).ok()?;
+    listing
+        .get("dependencies")?
+        .get(package)?
+        .get("version")?
+        .as_str()
+        .map(ToString::to_string)
+}
+
+/// Install `spec` (e.g. `@modelcontextprotocol/inspector@0.10.x`) globally with npm
+pub fn install_package(spec: &str) -> Result<()> {
+    let output = command("npm")
+        .args(["install", "--global", spec])
+        .output()
+        .map_err(|e| GleanMcpError::Process(format!("Failed to run npm install: {e}")))?;
+    if output.status.success() {
+        return Ok(());
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    Err(GleanMcpError::Process(format!(
+        "npm install --global {spec} failed: {}",
+        stderr.trim()
+    )))
+}
@@ -0,0 +1,173 @@
+//! Process-wide output switches for `--quiet`, `--no-color`, and `--progress`
+//!
+//! Plain output drops ANSI styling, progress bars, and emoji so captured CI
+//! logs stay readable. Quiet output is plain output without progress events.
+//! The progress style picks how test progress is shown, overriding the
+//! choice made from the output format and terminal.
+
+use crate::{GleanMcpError, Result, redact};
+use console::Term;
+use std::borrow::Cow;
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static PLAIN: AtomicBool = AtomicBool::new(false);
+static QUIET: AtomicBool = AtomicBool::new(false);
+static PROGRESS: OnceLock<ProgressMode> = OnceLock::new();
+
+/// How test progress is shown (`--progress`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProgressMode {
+    /// Live progress bars
+    Bars,
+    /// One character per finished test, like pytest
+    Dots,
+    /// One line per state change
+    Plain,
+    /// Nothing until the results
+    Off,
+}
+
+impl ProgressMode {
+    pub const ALL: [Self; 4] = [Self::Bars, Self::Dots, Self::Plain, Self::Off];
+
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Bars => "bars",
+            Self::Dots => "dots",
+            Self::Plain => "plain",
+            Self::Off => "none",
+        }
+    }
+}
+
+/// Parse a `--progress` style name
+pub fn parse_progress_mode(raw: &str) -> Result<ProgressMode> {
+    let name = raw.trim();
+    ProgressMode::ALL
+        .into_iter()
+        .find(|mode| mode.as_str() == name)
+        .ok_or_else(|| {
+            GleanMcpError::Config(format!(
+                "Unknown progress style '{name}' (expected bars, dots, plain, none)"
+            ))
+        })
+}
+
+/// Show progress as `mode` for the rest of the process; the first call wins
+pub fn configure_progress(mode: ProgressMode) {
+    let _ = PROGRESS.set(mode);
+}
+
+/// Progress style chosen with `--progress`, if any
+#[must_use]
+pub fn progress_mode() -> Option<ProgressMode> {
+    PROGRESS.get().copied()
+}
+
+/// Emoji with an ASCII stand-in; everything else in the emoji ranges is dropped
+const EMOJI_FALLBACKS: &[(char, &str)] = &[
+    ('✅', "[OK]"),
+    ('✓', "[OK]"),
+    ('❌', "[FAIL]"),
+    ('✗', "[FAIL]"),
+    ('⚠', "[WARN]"),
+    ('🎉', "[SUCCESS]"),
+    ('🔍', "[SEARCH]"),
+    ('📋', "[INFO]"),
+    ('🔧', "[TOOL]"),
+    ('🔐', "[AUTH]"),
+    ('🚀', ">>"),
+];
+
+/// Apply `--quiet`, `--no-color`, and `NO_COLOR` for the rest of the process
+///
+/// `NO_COLOR` counts when set to any non-empty value, per <https://no-color.org>.
+pub fn configure_output(quiet: bool, no_color: bool) {
+    let no_color_env = std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+    let plain = quiet || no_color || no_color_env;
+    QUIET.store(quiet, Ordering::Relaxed);
+    PLAIN.store(plain, Ordering::Relaxed);
+    if plain {
+        console::set_colors_enabled(false);
+        console::set_colors_enabled_stderr(false);
+    }
+}
+
+/// Whether styling, emoji, and progress bars are turned off
+#[must_use]
+pub fn plain_output() -> bool {
+    PLAIN.load(Ordering::Relaxed)
+}
+
+/// Whether progress and informational output is suppressed
+#[must_use]
+pub fn quiet_output() -> bool {
+    QUIET.load(Ordering::Relaxed)
+}
+
+const fn is_emoji(c: char) -> bool {
+    matches!(
+        c as u32,
+        0x2300..=0x23FF | 0x2600..=0x27BF | 0x2B00..=0x2BFF | 0x1F000..=0x1FAFF
+    )
+}
+
+/// `text` with ANSI codes and emoji removed when output is plain, unchanged otherwise
+///
+/// Emoji in [`EMOJI_FALLBACKS`] become ASCII tags; others are dropped along
+/// with the spacing that followed them.
+#[must_use]
+pub fn plain_text(text: &str) -> Cow<'_, str> {
+    if !plain_output() {
+        return Cow::Borrowed(text);
+    }
+    let stripped = console::strip_ansi_codes(text);
+    if stripped.is_ascii() {
+        return stripped;
+    }
+    let mut output = String::with_capacity(stripped.len());
+    let mut chars = stripped.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\u{FE0F}' | '\u{200D}' => {}
+            '─' => output.push('-'),
+            '→' => output.push_str("->"),
+            c if is_emoji(c) => {
+                let fallback = EMOJI_FALLBACKS
+                    .iter()
+                    .find(|(emoji, _)| *emoji == c)
+                    .map(|(_, fallback)| *fallback);
+                while chars
+                    .peek()
+                    .is_some_and(|next| *next == '\u{FE0F}' || *next == '\u{200D}')
+                {
+                    chars.next();
+                }
+                let spaced = chars.peek() == Some(&' ');
+                while chars.peek() == Some(&' ') {
+                    chars.next();
+                }
+                if let Some(fallback) = fallback {
+                    output.push_str(fallback);
+                    if spaced {
+                        output.push(' ');
+                    }
+                }
+            }
+            c => output.push(c),
+        }
+    }
+    Cow::Owned(output)
+}
+
+/// Write a line to `term`, redacted, and made plain first when output is plain
+pub fn write_line(term: &Term, line: &str) {
+    let _ = term.write_line(&plain_text(&redact(line)));
+}
+
+/// Write `text` to `term` as-is, redacted, and made plain first when output is plain
+pub fn write_str(term: &Term, text: &str) {
+    let _ = term.write_str(&plain_text(&redact(text)));
+}
@@ -0,0 +1,123 @@
+//! Locating and launching external tools across platforms
+//!
+//! On Windows, npm installs CLIs such as `npx` and `claude` as `.cmd` shims.
+//! [`std::process::Command`] only tries `.exe` when searching `PATH`, so
+//! `Command::new("npx")` fails there. [`find_executable`] resolves names
+//! through `PATHEXT`, falling back to `where`. [`command`] and
+//! [`async_command`] use it to launch the resolved program, and run `.ps1`
+//! scripts through PowerShell with `-File` so that arguments are passed
+//! literally.
+
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+
+/// The user's home directory: `HOME`, or `USERPROFILE` on Windows
+#[must_use]
+pub fn home_dir() -> Option<PathBuf> {
+    std::env::var_os("HOME")
+        .or_else(|| std::env::var_os("USERPROFILE"))
+        .filter(|home| !home.is_empty())
+        .map(PathBuf::from)
+}
+
+/// Expand a leading `~` (followed by `/` or `\`) to the home directory
+#[must_use]
+pub fn expand_home(path: &str) -> PathBuf {
+    let rest = path.strip_prefix("~/").or_else(|| path.strip_prefix("~\\"));
+    match (rest, home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+/// Full path of the executable `name` would launch, searching `PATH`
+///
+/// Names containing a path separator are returned as-is when they exist.
+#[must_use]
+pub fn find_executable(name: &str) -> Option<PathBuf> {
+    let candidate = Path::new(name);
+    if candidate.components().count() > 1 {
+        return candidate.is_file().then(|| candidate.to_path_buf());
+    }
+
+    let extensions = executable_extensions();
+    let found = std::env::var_os("PATH").and_then(|path| {
+        std::env::split_paths(&path).find_map(|dir| {
+            extensions.iter().find_map(|extension| {
+                let file = dir.join(format!("{name}{extension}"));
+                file.is_file().then_some(file)
+            })
+        })
+    });
+    if found.is_some() || !cfg!(windows) {
+        return found;
+    }
+
+    // Match cmd.exe, which also finds programs in the current directory
+    let output = std::process::Command::new("where")
+        .arg(name)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .map(PathBuf::from)
+}
+
+/// Extensions tried when resolving a bare name: `PATHEXT` on Windows, none elsewhere
+fn executable_extensions() -> Vec<String> {
+    if !cfg!(windows) {
+        return vec![String::new()];
+    }
+    let pathext = std::env::var("PATHEXT").unwrap_or_else(|_| ".COM;.EXE;.BAT;.CMD".to_string());
+    let mut extensions: Vec<String> = pathext
+        .split(';')
+        .filter(|extension| !extension.is_empty())
+        .map(str::to_lowercase)
+        .collect();
+    extensions.push(".ps1".to_string());
+    extensions
+}
+
+/// A [`std::process::Command`] for `program` that also works for Windows shims
+#[must_use]
+pub fn command(program: impl AsRef<OsStr>) -> std::process::Command {
+    let program = program.as_ref();
+    if !cfg!(windows) {
+        return std::process::Command::new(program);
+    }
+
+    let resolved = program
+        .to_str()
+        .and_then(find_executable)
+        .unwrap_or_else(|| PathBuf::from(program));
+    let is_powershell = resolved
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("ps1"));
+    if is_powershell {
+        let mut command = std::process::Command::new("powershell");
+        command
+            .args([
+                "-NoProfile",
+                "-NonInteractive",
+                "-ExecutionPolicy",
+                "Bypass",
+                "-File",
+            ])
+            .arg(resolved);
+        command
+    } else {
+        // `.cmd`/`.bat` shims are run through cmd.exe by std, which escapes arguments safely
+        std::process::Command::new(resolved)
+    }
+}
+
+/// Async counterpart of [`command`]
+#[must_use]
+pub fn async_command(program: impl AsRef<OsStr>) -> async_process::Command {
+    command(program).into()
+}
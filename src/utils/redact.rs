@@ -0,0 +1,183 @@
+//! Masking secrets in everything the tool writes out
+//!
+//! Once [`configure_redaction`] has run, terminal lines (`write_line`), JSON
+//! output, reporter events, `--output` files, and `--capture-dir`
+//! transcripts are passed through [`redact`]: the auth tokens the run uses,
+//! `Bearer`/`Basic` credentials, email addresses, and the `redaction.patterns`
+//! regular expressions are replaced with `[REDACTED]`.
+
+use crate::{GleanConfig, GleanMcpError, Result};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::borrow::Cow;
+use std::sync::OnceLock;
+
+/// What a masked secret is replaced with
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Secrets shorter than this are not masked, since they would hit ordinary text
+const MIN_SECRET_LEN: usize = 8;
+
+static REDACTOR: OnceLock<Redactor> = OnceLock::new();
+
+/// Redaction settings (`redaction` section of the config file)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RedactionConfig {
+    /// Mask email addresses
+    pub emails: bool,
+    /// Regular expressions whose matches are masked, e.g. `acme-[0-9]{6}`
+    pub patterns: Vec<String>,
+}
+
+impl Default for RedactionConfig {
+    fn default() -> Self {
+        Self {
+            emails: true,
+            patterns: Vec::new(),
+        }
+    }
+}
+
+/// Masks known secrets and pattern matches in text
+#[derive(Debug, Clone)]
+pub struct Redactor {
+    secrets: Vec<String>,
+    /// Patterns and what their matches become
+    patterns: Vec<(Regex, &'static str)>,
+}
+
+impl Redactor {
+    /// Mask `secrets` verbatim, plus credentials and whatever `config` asks for
+    pub fn new(
+        config: &RedactionConfig,
+        secrets: impl IntoIterator<Item = String>,
+    ) -> Result<Self> {
+        let mut secrets: Vec<String> = secrets
+            .into_iter()
+            .map(|secret| secret.trim().to_string())
+            .filter(|secret| secret.len() >= MIN_SECRET_LEN)
+            .collect();
+        // Longest first, so a secret containing another is masked whole
+        secrets.sort_by_key(|secret| std::cmp::Reverse(secret.len()));
+        secrets.dedup();
+
+        let mut patterns = vec![(
+            Regex::new(r"(?i)\b(bearer|basic)\s+[A-Za-z0-9\-._~+/]+=*")
+                .expect("credential pattern is valid"),
+            "$1 [REDACTED]",
+        )];
+        if config.emails {
+            patterns.push((
+                Regex::new(r"[A-Za-z0-9._%+-]+@[A-Za-z0-9-]+(?:\.[A-Za-z0-9-]+)*\.[A-Za-z]{2,}")
+                    .expect("email pattern is valid"),
+                REDACTED,
+            ));
+        }
+        for pattern in &config.patterns {
+            let regex = Regex::new(pattern).map_err(|e| {
+                GleanMcpError::Config(format!("Invalid redaction pattern '{pattern}': {e}"))
+            })?;
+            patterns.push((regex, REDACTED));
+        }
+        Ok(Self { secrets, patterns })
+    }
+
+    /// Redactor for a run under `config`: its auth tokens and sensitive header values are secrets
+    pub fn for_config(config: &GleanConfig) -> Result<Self> {
+        let auth = &config.authentication;
+        let token_variables = auth
+            .instance_token_env
+            .values()
+            .flatten()
+            .chain(&auth.token_env)
+            .map(String::as_str)
+            .chain(config.permissions.pairs.iter().flat_map(|pair| {
+                [
+                    pair.privileged_token_env.as_str(),
+                    pair.restricted_token_env.as_str(),
+                ]
+            }));
+        let tokens = token_variables.filter_map(|variable| std::env::var(variable).ok());
+        // Per-instance tokens, e.g. GLEAN_AUTH_TOKEN_SCIO_PROD
+        let instance_tokens = std::env::vars()
+            .filter(|(variable, _)| {
+                auth.token_env.iter().any(|prefix| {
+                    variable
+                        .strip_prefix(prefix.as_str())
+                        .is_some_and(|rest| rest.starts_with('_'))
+                })
+            })
+            .map(|(_, value)| value);
+        let header_values = config
+            .headers
+            .iter()
+            .filter(|(name, _)| is_sensitive_header(name))
+            .map(|(_, value)| value.clone());
+        Self::new(
+            &config.redaction,
+            tokens.chain(instance_tokens).chain(header_values),
+        )
+    }
+
+    /// `text` with secrets and pattern matches masked
+    #[must_use]
+    pub fn redact<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        let mut text = Cow::Borrowed(text);
+        for secret in &self.secrets {
+            if text.contains(secret.as_str()) {
+                text = Cow::Owned(text.replace(secret.as_str(), REDACTED));
+            }
+        }
+        for (regex, replacement) in &self.patterns {
+            if let Cow::Owned(replaced) = regex.replace_all(&text, *replacement) {
+                text = Cow::Owned(replaced);
+            }
+        }
+        text
+    }
+}
+
+/// Whether a header named `name` carries credentials
+fn is_sensitive_header(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    name == "cookie"
+        || ["auth", "token", "secret", "key", "password"]
+            .iter()
+            .any(|word| name.contains(word))
+}
+
+/// Redact all output for the rest of the process with `redactor`
+pub fn configure_redaction(redactor: Redactor) {
+    let _ = REDACTOR.set(redactor);
+}
+
+/// `text` with secrets masked, unchanged before [`configure_redaction`]
+#[must_use]
+pub fn redact(text: &str) -> Cow<'_, str> {
+    REDACTOR
+        .get()
+        .map_or(Cow::Borrowed(text), |redactor| redactor.redact(text))
+}
+
+/// Mask secrets in every string in `value`
+pub fn redact_value(value: &mut Value) {
+    match value {
+        Value::String(text) => {
+            if let Cow::Owned(redacted) = redact(text) {
+                *text = redacted;
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact_value),
+        Value::Object(map) => map.values_mut().for_each(redact_value),
+        _ => {}
+    }
+}
+
+/// `value` as pretty-printed JSON with secrets masked in its strings
+pub fn redacted_json<T: Serialize + ?Sized>(value: &T) -> serde_json::Result<String> {
+    let mut value = serde_json::to_value(value)?;
+    redact_value(&mut value);
+    serde_json::to_string_pretty(&value)
+}
@@ -0,0 +1,79 @@
+//! Finding the Glean auth token in the environment
+//!
+//! Once [`configure_auth_tokens`] has run, every inspector looks for its
+//! instance's token in the variables [`AuthConfig::token_variables`] lists,
+//! taking the first one set. Before that, only `GLEAN_AUTH_TOKEN` is read.
+
+use crate::AuthConfig;
+use std::sync::OnceLock;
+
+/// Variable read when no other is configured
+pub const DEFAULT_TOKEN_ENV: &str = "GLEAN_AUTH_TOKEN";
+
+static AUTH: OnceLock<AuthConfig> = OnceLock::new();
+
+/// An auth token and the environment variable it came from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuthToken {
+    pub variable: String,
+    pub value: String,
+}
+
+impl AuthToken {
+    /// The token with all but its first and last four characters hidden
+    #[must_use]
+    pub fn masked(&self) -> String {
+        let chars: Vec<char> = self.value.chars().collect();
+        if chars.len() > 8 {
+            format!(
+                "{}...{}",
+                chars[..4].iter().collect::<String>(),
+                chars[chars.len() - 4..].iter().collect::<String>()
+            )
+        } else {
+            "***".to_string()
+        }
+    }
+}
+
+/// Look tokens up with `config`'s variables for the rest of the process
+pub fn configure_auth_tokens(config: AuthConfig) {
+    let _ = AUTH.set(config);
+}
+
+/// The first `token_env` variable, suggested when no token is set
+#[must_use]
+pub fn primary_token_variable() -> String {
+    AUTH.get()
+        .and_then(|config| config.token_env.first().cloned())
+        .unwrap_or_else(|| DEFAULT_TOKEN_ENV.to_string())
+}
+
+/// Variables searched for `instance`'s token, in lookup order
+#[must_use]
+pub fn auth_token_variables(instance: &str) -> Vec<String> {
+    AUTH.get().map_or_else(
+        || vec![DEFAULT_TOKEN_ENV.to_string()],
+        |config| config.token_variables(instance),
+    )
+}
+
+/// The value of environment variable `variable`, unless it is unset or blank
+#[must_use]
+pub fn token_from_env(variable: &str) -> Option<AuthToken> {
+    std::env::var(variable)
+        .ok()
+        .filter(|value| !value.trim().is_empty())
+        .map(|value| AuthToken {
+            variable: variable.to_string(),
+            value,
+        })
+}
+
+/// `instance`'s token from the first of [`auth_token_variables`] that is set
+#[must_use]
+pub fn find_auth_token(instance: &str) -> Option<AuthToken> {
+    auth_token_variables(instance)
+        .iter()
+        .find_map(|variable| token_from_env(variable))
+}